@@ -1,16 +1,20 @@
 use super::global_config_ext::GlobalConfigExt;
 use anyhow::{anyhow, bail, Result};
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf};
 use zapatos::{
     common::{
         types::{
             CliConfig, CliError, CliTypedResult, ConfigSearchMode, ProfileConfig, DEFAULT_PROFILE,
         },
-        utils::{create_dir_if_not_exist, read_from_file, write_to_user_only_file},
+        utils::{read_from_file, write_to_user_only_file},
     },
     config::GlobalConfig,
     genesis::git::from_yaml,
 };
+use zapatos_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    PrivateKey,
+};
 
 const CONFIG_FILE: &str = "config.yaml";
 const LEGACY_CONFIG_FILE: &str = "config.yml";
@@ -86,8 +90,16 @@ impl CliConfigExt for CliConfig {
     fn save_ext(&self) -> CliTypedResult<()> {
         let _0l_folder = _0l_folder(ConfigSearchMode::CurrentDir)?;
 
-        // Create if it doesn't exist
-        create_dir_if_not_exist(_0l_folder.as_path())?;
+        // Create if it doesn't exist, only accessible by the user as the config holds keys
+        if !_0l_folder.exists() {
+            let mut builder = std::fs::DirBuilder::new();
+            builder.recursive(true);
+            #[cfg(unix)]
+            std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+            builder
+                .create(&_0l_folder)
+                .map_err(|err| CliError::IO(format!("{}", _0l_folder.display()), err))?;
+        }
 
         // Save over previous config file
         let config_file = _0l_folder.join(CONFIG_FILE);
@@ -106,6 +118,68 @@ impl CliConfigExt for CliConfig {
     }
 }
 
+/// Replace the keys of the profiles holding `old_public_key` with the new key, e.g. after
+/// rotating the authentication key of their account. Returns the names of the updated profiles
+pub fn update_profile_keys(
+    old_public_key: &Ed25519PublicKey,
+    new_private_key: &Ed25519PrivateKey,
+) -> Result<Vec<String>> {
+    let mut config = CliConfig::load_ext(ConfigSearchMode::CurrentDir)?;
+    let mut updated = vec![];
+    for (name, profile) in config.profiles.iter_mut().flatten() {
+        let public_key = profile
+            .public_key
+            .clone()
+            .or_else(|| profile.private_key.as_ref().map(PrivateKey::public_key));
+        if public_key.as_ref() == Some(old_public_key) {
+            profile.private_key = Some(new_private_key.clone());
+            profile.public_key = Some(new_private_key.public_key());
+            updated.push(name.to_owned());
+        }
+    }
+    if !updated.is_empty() {
+        config.save_ext()?;
+    }
+    Ok(updated)
+}
+
+/// The private key of the named profile, else of the default profile. None without a key in
+/// the profile, or without a config when no profile is named
+pub fn profile_private_key(profile: Option<&str>) -> Result<Option<Ed25519PrivateKey>> {
+    if profile.is_none() && !CliConfig::config_exists_ext(ConfigSearchMode::CurrentDir) {
+        return Ok(None);
+    }
+    Ok(CliConfig::load_profile_ext(
+        Some(profile.unwrap_or(DEFAULT_PROFILE)),
+        ConfigSearchMode::CurrentDir,
+    )?
+    .and_then(|profile| profile.private_key))
+}
+
+/// Path of the config file in use, none when there is no config
+pub fn config_file_path() -> Result<Option<PathBuf>> {
+    if !CliConfig::config_exists_ext(ConfigSearchMode::CurrentDir) {
+        return Ok(None);
+    }
+    let folder = _0l_folder(ConfigSearchMode::CurrentDir)?;
+    let config_file = folder.join(CONFIG_FILE);
+    if config_file.exists() {
+        Ok(Some(config_file))
+    } else {
+        Ok(Some(folder.join(LEGACY_CONFIG_FILE)))
+    }
+}
+
+/// The profiles of the config by name, none when there is no config
+pub fn load_profiles() -> Result<BTreeMap<String, ProfileConfig>> {
+    if !CliConfig::config_exists_ext(ConfigSearchMode::CurrentDir) {
+        return Ok(BTreeMap::new());
+    }
+    Ok(CliConfig::load_ext(ConfigSearchMode::CurrentDir)?
+        .profiles
+        .unwrap_or_default())
+}
+
 fn _0l_folder(mode: ConfigSearchMode) -> CliTypedResult<PathBuf> {
     let global_config = GlobalConfig::load_ext()?;
     global_config.get_config_location_ext(mode)
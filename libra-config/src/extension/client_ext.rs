@@ -1,27 +1,185 @@
 use super::cli_config_ext::CliConfigExt;
-use anyhow::{Context, Result};
-use std::{str::FromStr, time::Duration};
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
 use url::Url;
 use zapatos::common::types::{CliConfig, ConfigSearchMode, DEFAULT_PROFILE};
-use zapatos_rest_client::Client;
+use zapatos_rest_client::{error::RestError, Client};
 
 pub const DEFAULT_TIMEOUT_SECS: u64 = 10;
+pub const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 5;
 pub const USER_AGENT: &str = concat!("libra-config/", env!("CARGO_PKG_VERSION"));
+/// How long an unused connection stays in the pool
+const POOL_IDLE_TIMEOUT_SECS: u64 = 90;
+/// Interval of the TCP keep-alive probes, so that idle connections are not dropped on the way
+const TCP_KEEPALIVE_SECS: u64 = 60;
 
+static CONNECT_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(DEFAULT_CONNECT_TIMEOUT_SECS);
+static REQUEST_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(DEFAULT_TIMEOUT_SECS);
+static HTTP_CLIENT: Mutex<Option<((u64, u64), reqwest::Client)>> = Mutex::new(None);
+
+/// Set the timeouts of the clients built afterwards, e.g. from command line flags
+pub fn set_timeouts(connect_timeout_secs: u64, request_timeout_secs: u64) {
+    CONNECT_TIMEOUT_SECS.store(connect_timeout_secs, Ordering::Relaxed);
+    REQUEST_TIMEOUT_SECS.store(request_timeout_secs, Ordering::Relaxed);
+}
+
+#[async_trait]
 pub trait ClientExt {
     fn default() -> Result<Client>;
+
+    /// Connect to the REST endpoint of the named profile of the config
+    fn from_profile(profile: &str) -> Result<Client>;
+
+    /// Connect to the given REST endpoint instead of the one stored in the config.
+    /// Nothing is persisted, and the node is pinged so a bad url fails early.
+    async fn from_url(url: &Url) -> Result<Client>;
 }
 
+#[async_trait]
 impl ClientExt for Client {
     fn default() -> Result<Client> {
-        let profile =
-            CliConfig::load_profile_ext(Some(DEFAULT_PROFILE), ConfigSearchMode::CurrentDir)?
-                .unwrap_or_default();
-        let rest_url = profile.rest_url.context("Rest url is not set")?;
-        Ok(Client::new_with_timeout_and_user_agent(
-            Url::from_str(&rest_url).unwrap(),
-            Duration::from_secs(DEFAULT_TIMEOUT_SECS),
-            USER_AGENT,
-        ))
+        Client::from_profile(DEFAULT_PROFILE)
+    }
+
+    fn from_profile(profile: &str) -> Result<Client> {
+        let rest_url = profile_rest_urls(Some(profile))?
+            .into_iter()
+            .next()
+            .context("Rest url is not set")?;
+        new_client(rest_url)
+    }
+
+    async fn from_url(url: &Url) -> Result<Client> {
+        let client = new_client(url.to_owned())?;
+        if let Err(err) = client.get_index().await {
+            let err = anyhow!(err);
+            return Err(match timeout_error(&err) {
+                Some(timeout) => timeout,
+                None => err.context(format!("Unable to reach the node at {url}")),
+            });
+        }
+        Ok(client)
+    }
+}
+
+/// The REST endpoints of the named profile of the config, else of the default profile, in the
+/// order to try them. The rest_url of a profile may list several endpoints separated by commas
+pub fn profile_rest_urls(profile: Option<&str>) -> Result<Vec<Url>> {
+    let profile = CliConfig::load_profile_ext(
+        Some(profile.unwrap_or(DEFAULT_PROFILE)),
+        ConfigSearchMode::CurrentDir,
+    )?
+    .unwrap_or_default();
+    profile
+        .rest_url
+        .as_deref()
+        .map_or(Ok(vec![]), parse_rest_urls)
+}
+
+/// Parse a comma separated list of REST endpoints
+pub fn parse_rest_urls(rest_urls: &str) -> Result<Vec<Url>> {
+    rest_urls
+        .split(',')
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(|url| Url::from_str(url).context(format!("Invalid rest url {url}")))
+        .collect()
+}
+
+/// A plain "timed out" error naming the node when the error comes from a request which timed
+/// out, as the error chain of the HTTP client says little about it
+pub fn timeout_error(err: &anyhow::Error) -> Option<anyhow::Error> {
+    err.chain().find_map(|cause| {
+        let reqwest_err = match cause.downcast_ref::<RestError>() {
+            Some(RestError::Unknown(err)) => err.downcast_ref::<reqwest::Error>(),
+            _ => cause.downcast_ref::<reqwest::Error>(),
+        }
+        .filter(|err| err.is_timeout())?;
+        let secs = if reqwest_err.is_connect() {
+            CONNECT_TIMEOUT_SECS.load(Ordering::Relaxed)
+        } else {
+            REQUEST_TIMEOUT_SECS.load(Ordering::Relaxed)
+        };
+        let url = reqwest_err
+            .url()
+            .map(|url| url.origin().ascii_serialization())
+            .unwrap_or_else(|| "the node".to_string());
+        Some(anyhow!("Request timed out after {secs}s talking to {url}"))
+    })
+}
+
+/// The HTTP client under every REST client of the process. Its connections are kept alive and
+/// pooled, so the requests to a node after the first one skip the TCP and TLS handshakes. Built
+/// again when the timeouts change. reqwest reads the HTTPS_PROXY, HTTP_PROXY and NO_PROXY
+/// environment variables itself, so requests go through the proxy they set
+pub fn http_client() -> Result<reqwest::Client> {
+    let timeouts = (
+        CONNECT_TIMEOUT_SECS.load(Ordering::Relaxed),
+        REQUEST_TIMEOUT_SECS.load(Ordering::Relaxed),
+    );
+    let mut shared = HTTP_CLIENT
+        .lock()
+        .expect("the HTTP client lock is never poisoned");
+    if let Some((built_with, client)) = shared.as_ref() {
+        if *built_with == timeouts {
+            // A clone shares the connection pool
+            return Ok(client.clone());
+        }
+    }
+    let client = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(timeouts.0))
+        .timeout(Duration::from_secs(timeouts.1))
+        .pool_idle_timeout(Duration::from_secs(POOL_IDLE_TIMEOUT_SECS))
+        .tcp_keepalive(Duration::from_secs(TCP_KEEPALIVE_SECS))
+        .user_agent(USER_AGENT)
+        .build()
+        .context("Failed to build the HTTP client")?;
+    *shared = Some((timeouts, client.clone()));
+    Ok(client)
+}
+
+/// A REST client of the node at `url` on the shared HTTP client. Nothing is sent yet
+pub fn new_client(url: Url) -> Result<Client> {
+    Ok(Client::from((http_client()?, url)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{net::TcpListener, thread};
+
+    #[test]
+    fn parse_a_list_of_rest_urls() {
+        let urls =
+            parse_rest_urls("http://localhost:8080, https://fullnode.example.com/v1,").unwrap();
+        assert_eq!(urls.len(), 2);
+        assert_eq!(urls[1].as_str(), "https://fullnode.example.com/v1");
+        assert!(parse_rest_urls("localhost:8080,not a url").is_err());
+    }
+
+    #[tokio::test]
+    async fn time_out_on_a_slow_node() {
+        // Accepts connections and never answers
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let _connections = listener.incoming().collect::<Vec<_>>();
+        });
+
+        set_timeouts(DEFAULT_CONNECT_TIMEOUT_SECS, 1);
+        let url = Url::from_str(&format!("http://{address}")).unwrap();
+        let err = Client::from_url(&url).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Request timed out after 1s talking to http://{address}")
+        );
     }
 }
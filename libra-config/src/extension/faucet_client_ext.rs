@@ -1,4 +1,4 @@
-use super::cli_config_ext::CliConfigExt;
+use super::{cli_config_ext::CliConfigExt, client_ext::parse_rest_urls};
 use anyhow::{Context, Result};
 use std::str::FromStr;
 use url::Url;
@@ -8,6 +8,12 @@ use zapatos_rest_client::FaucetClient;
 
 pub trait FaucetClientExt {
     fn default() -> Result<FaucetClient>;
+
+    /// Use the faucet against the REST endpoint of the named profile of the config
+    fn from_profile(profile: &str) -> Result<FaucetClient>;
+
+    /// Use the faucet against the given REST endpoint instead of the configured one
+    fn from_rest_url(rest_url: &Url) -> FaucetClient;
 }
 
 static FAUCET_URL: Lazy<Url> = Lazy::new(|| {
@@ -22,16 +28,25 @@ static FAUCET_URL: Lazy<Url> = Lazy::new(|| {
 
 impl FaucetClientExt for FaucetClient {
     fn default() -> Result<FaucetClient> {
-        let profile = CliConfig::load_profile_ext(
-            Some(DEFAULT_PROFILE),
-            ConfigSearchMode::CurrentDirAndParents,
-        )
-        .context("Unable to locate 0l config file!")?
-        .unwrap_or_default();
-        let rest_url = profile.rest_url.context("Rest url is not set")?;
-        Ok(FaucetClient::new(
-            FAUCET_URL.clone(),
-            Url::from_str(&rest_url).unwrap(),
-        ))
+        FaucetClient::from_profile(DEFAULT_PROFILE)
+    }
+
+    fn from_profile(profile: &str) -> Result<FaucetClient> {
+        let profile =
+            CliConfig::load_profile_ext(Some(profile), ConfigSearchMode::CurrentDirAndParents)
+                .context("Unable to locate 0l config file!")?
+                .unwrap_or_default();
+        let rest_url = profile
+            .rest_url
+            .as_deref()
+            .map(parse_rest_urls)
+            .transpose()?
+            .and_then(|urls| urls.into_iter().next())
+            .context("Rest url is not set")?;
+        Ok(FaucetClient::new(FAUCET_URL.clone(), rest_url))
+    }
+
+    fn from_rest_url(rest_url: &Url) -> FaucetClient {
+        FaucetClient::new(FAUCET_URL.clone(), rest_url.to_owned())
     }
 }
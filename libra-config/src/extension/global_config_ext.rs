@@ -35,7 +35,8 @@ impl GlobalConfigExt for GlobalConfig {
     }
 }
 
-fn global_folder() -> CliTypedResult<PathBuf> {
+/// The 0L config folder in the home directory
+pub fn global_folder() -> CliTypedResult<PathBuf> {
     if let Some(dir) = dirs::home_dir() {
         Ok(dir.join(CONFIG_FOLDER))
     } else {
@@ -1,5 +1,5 @@
 use anyhow::{bail, Result};
-use libra_config::extension::cli_config_ext::CliConfigExt;
+use libra_config::extension::{cli_config_ext::CliConfigExt, client_ext::parse_rest_urls};
 use std::{collections::BTreeMap, str::FromStr};
 use url::Url;
 use zapatos::{
@@ -61,15 +61,17 @@ pub async fn run(public_key: &str, profile: Option<&str>) -> Result<()> {
         _ => bail!("0L only supports Local and Custom networks (for now)"),
     }
 
-    let client = Client::new(
-        Url::parse(
-            profile_config
-                .rest_url
-                .as_ref()
-                .expect("Must have rest client as created above"),
-        )
-        .map_err(|err| CliError::UnableToParse("rest_url", err.to_string()))?,
-    );
+    let rest_url = parse_rest_urls(
+        profile_config
+            .rest_url
+            .as_ref()
+            .expect("Must have rest client as created above"),
+    )
+    .map_err(|err| CliError::UnableToParse("rest_url", format!("{err:#}")))?
+    .into_iter()
+    .next()
+    .ok_or_else(|| CliError::UnableToParse("rest_url", "no url given".to_string()))?;
+    let client = Client::new(rest_url);
 
     // lookup the address from onchain instead of deriving it
     // if this is the rotated key, deriving it will outputs an incorrect address
@@ -140,7 +142,7 @@ fn custom_network(profile_config: &mut ProfileConfig) -> CliTypedResult<()> {
     let rest_url = {
         let current = profile_config.rest_url.as_deref();
         eprintln!(
-            "Enter your rest endpoint [Current: {} | No input: Exit (or keep the existing if present)]",
+            "Enter your rest endpoints, separated by commas to fail over to the next ones [Current: {} | No input: Exit (or keep the existing if present)]",
             current.unwrap_or("None"),
         );
         let input = read_line("Rest endpoint")?;
@@ -154,11 +156,9 @@ fn custom_network(profile_config: &mut ProfileConfig) -> CliTypedResult<()> {
                 return Err(CliError::AbortedError);
             }
         } else {
-            Some(
-                Url::parse(input)
-                    .map_err(|err| CliError::UnableToParse("Rest Endpoint", err.to_string()))?
-                    .to_string(),
-            )
+            let urls = parse_rest_urls(input)
+                .map_err(|err| CliError::UnableToParse("Rest Endpoint", format!("{err:#}")))?;
+            Some(urls.iter().map(Url::as_str).collect::<Vec<_>>().join(","))
         }
     };
     profile_config.rest_url = rest_url;
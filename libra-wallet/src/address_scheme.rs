@@ -0,0 +1,202 @@
+//! The addresses a key can sign for. Accounts created before V7 have the legacy 0L address,
+//! the last 16 bytes of the authentication key, and accounts created since have the whole key.
+//! An account which rotated its key keeps its address, recorded by the originating address table
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::fmt;
+use zapatos_sdk::rest_client::{error::RestError, Client};
+use zapatos_types::{
+    account_address::AccountAddress, transaction::authenticator::AuthenticationKey,
+};
+
+/// How the address of an account relates to its key
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressScheme {
+    /// The authentication key as a whole
+    Current,
+    /// The last 16 bytes of the authentication key, padded with zeros
+    Legacy,
+    /// Another address, which rotated its key to this one
+    Rotated,
+}
+
+impl fmt::Display for AddressScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Current => "current (the authentication key)",
+            Self::Legacy => "legacy 0L v5/v6 (the last 16 bytes of the authentication key)",
+            Self::Rotated => "rotated (the account rotated its key to this one)",
+        })
+    }
+}
+
+/// The reads `find_account` makes, so that it can look the account up on another node than a
+/// `Client`
+#[async_trait]
+pub trait AccountLookup: Sync {
+    async fn account_resource(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+    ) -> Result<Option<Value>, RestError>;
+
+    async fn table_item(
+        &self,
+        handle: AccountAddress,
+        key_type: &str,
+        value_type: &str,
+        key: Value,
+    ) -> Result<Value, RestError>;
+}
+
+#[async_trait]
+impl AccountLookup for Client {
+    async fn account_resource(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+    ) -> Result<Option<Value>, RestError> {
+        Ok(self
+            .get_account_resource(address, resource_type)
+            .await?
+            .into_inner()
+            .map(|resource| resource.data))
+    }
+
+    async fn table_item(
+        &self,
+        handle: AccountAddress,
+        key_type: &str,
+        value_type: &str,
+        key: Value,
+    ) -> Result<Value, RestError> {
+        Ok(self
+            .get_table_item(handle, key_type, value_type, key)
+            .await?
+            .into_inner())
+    }
+}
+
+pub fn current_address(auth_key: &AuthenticationKey) -> AccountAddress {
+    auth_key.derived_address()
+}
+
+pub fn legacy_address(auth_key: &AuthenticationKey) -> AccountAddress {
+    let mut bytes = [0u8; AccountAddress::LENGTH];
+    bytes[16..].copy_from_slice(&auth_key.to_vec()[16..]);
+    AccountAddress::new(bytes)
+}
+
+/// The account on-chain which the key signs for: the address which rotated its key to it, else
+/// the address of the current scheme, else the legacy address. None when no account exists
+pub async fn find_account(
+    client: &impl AccountLookup,
+    auth_key: &AuthenticationKey,
+) -> Result<Option<(AccountAddress, AddressScheme)>> {
+    if let Some(address) = originating_address(client, auth_key).await? {
+        return Ok(Some((address, scheme_of(address, auth_key))));
+    }
+    for (address, scheme) in [
+        (current_address(auth_key), AddressScheme::Current),
+        (legacy_address(auth_key), AddressScheme::Legacy),
+    ] {
+        if account_exists(client, address).await? {
+            return Ok(Some((address, scheme)));
+        }
+    }
+    Ok(None)
+}
+
+fn scheme_of(address: AccountAddress, auth_key: &AuthenticationKey) -> AddressScheme {
+    if address == current_address(auth_key) {
+        AddressScheme::Current
+    } else if address == legacy_address(auth_key) {
+        AddressScheme::Legacy
+    } else {
+        AddressScheme::Rotated
+    }
+}
+
+async fn account_exists(client: &impl AccountLookup, address: AccountAddress) -> Result<bool> {
+    Ok(client
+        .account_resource(address, "0x1::account::Account")
+        .await
+        .context(format!(
+            "Failed to get the account {}",
+            address.to_hex_literal()
+        ))?
+        .is_some())
+}
+
+/// The address recorded for the key by `0x1::account::OriginatingAddress` on key rotation
+async fn originating_address(
+    client: &impl AccountLookup,
+    auth_key: &AuthenticationKey,
+) -> Result<Option<AccountAddress>> {
+    let table = client
+        .account_resource(AccountAddress::ONE, "0x1::account::OriginatingAddress")
+        .await
+        .context("Failed to get the originating address table")?;
+    let handle = match table
+        .as_ref()
+        .and_then(|table| table["address_map"]["handle"].as_str())
+    {
+        Some(handle) => AccountAddress::from_hex_literal(handle).context(format!(
+            "Invalid handle of the originating address table: {handle}"
+        ))?,
+        None => return Ok(None),
+    };
+    let key = Value::String(current_address(auth_key).to_hex_literal());
+    match client.table_item(handle, "address", "address", key).await {
+        Ok(item) => match item {
+            Value::String(address) => Ok(Some(
+                AccountAddress::from_hex_literal(&address)
+                    .context(format!("Invalid originating address {address}"))?,
+            )),
+            other => bail!("Invalid originating address {other}"),
+        },
+        Err(RestError::Api(err)) if err.status_code.as_u16() == 404 => Ok(None),
+        Err(err) => Err(err).context("Failed to get the originating address of the key"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_address_is_the_end_of_the_key() {
+        let auth_key: AuthenticationKey =
+            "fda03992f666875ddf854193fccd3e62ea111d066029490dd37c891ed9c3f880"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            "00000000000000000000000000000000ea111d066029490dd37c891ed9c3f880",
+            legacy_address(&auth_key).to_hex()
+        );
+        assert_eq!(
+            AddressScheme::Legacy,
+            scheme_of(legacy_address(&auth_key), &auth_key)
+        );
+        assert_eq!(
+            AddressScheme::Rotated,
+            scheme_of(AccountAddress::ONE, &auth_key)
+        );
+    }
+
+    #[test]
+    fn legacy_address_matches_the_legacy_derivation() {
+        let alice_mnem = "talent sunset lizard pill fame nuclear spy noodle basket okay critic grow sleep legend hurry pitch blanket clerk impose rough degree sock insane purse";
+        let keys = crate::legacy::get_keys_from_mnem(alice_mnem.to_string()).unwrap();
+        assert_eq!(
+            keys.child_0_owner.account,
+            legacy_address(&keys.child_0_owner.auth_key)
+        );
+        assert_ne!(
+            keys.child_0_owner.account,
+            current_address(&keys.child_0_owner.auth_key)
+        );
+    }
+}
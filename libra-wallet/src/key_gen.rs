@@ -1,31 +1,137 @@
 use anyhow::Result;
 use indoc::formatdoc;
-use libra_wallet::keys::validator_keygen;
+use libra_wallet::{
+    address_scheme::{current_address, find_account, legacy_address},
+    keys::{
+        generate_key_objects_from_legacy, mnemonic_keygen, validator_keygen,
+        validator_keygen_from_legacy, KeyFileOptions,
+    },
+    keystore::{read_password, Keystore},
+    legacy::legacy_keygen,
+    secret::Secret,
+    vanity::{grind, VanityPattern},
+};
 use ol_keys::wallet::get_account_from_mnem;
-use std::path::PathBuf;
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use url::Url;
 use zapatos_crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey};
+use zapatos_sdk::rest_client::Client;
 use zapatos_types::transaction::authenticator::AuthenticationKey;
 
-pub async fn run(mnemonic: Option<String>, output_dir: Option<PathBuf>) -> Result<String> {
+/// Where the keys go besides the output
+pub enum KeyOutput {
+    /// Key files in the directory, the current one by default. Files are not written for a
+    /// legacy mnemonic
+    Files {
+        output_dir: Option<PathBuf>,
+        options: KeyFileOptions,
+    },
+    /// Nothing is written, the private key is only printed when asked for
+    PrintOnly { show_private_key: bool },
+}
+
+/// A legacy mnemonic is given with the node to look its account up on, if any
+pub async fn run(
+    mnemonic: Option<Secret>,
+    legacy_mnemonic: Option<(Secret, Option<Url>)>,
+    derivation_index: u32,
+    key_output: KeyOutput,
+    keystore_file: Option<&Path>,
+    password_file: Option<&Path>,
+    vanity: Option<(VanityPattern, Option<Duration>)>,
+) -> Result<String> {
+    let (output_dir, options, show_private_key) = match key_output {
+        KeyOutput::Files {
+            output_dir,
+            options,
+        } => (Some(output_dir), options, true),
+        KeyOutput::PrintOnly { show_private_key } => {
+            (None, KeyFileOptions::default(), show_private_key)
+        }
+    };
+
+    let mut derivation_path = None;
+    let mut legacy_node = None;
     let private_key = if let Some(mnemonic) = mnemonic {
-        let (_, account_address, wallet_lib) = get_account_from_mnem(mnemonic)?;
+        let account = mnemonic_keygen(
+            mnemonic.expose(),
+            derivation_index,
+            output_dir.flatten(),
+            options,
+        )?;
+        derivation_path = Some(account.derivation_path);
+        account.account_private_key
+    } else if let Some((mnemonic, url)) = legacy_mnemonic {
+        legacy_node = Some(url);
+        let (_, account_address, wallet_lib) = get_account_from_mnem(mnemonic.expose().to_owned())?;
         Ed25519PrivateKey::try_from(
             wallet_lib
                 .get_private_key(&account_address)?
                 .to_bytes()
                 .as_ref(),
         )?
+    } else if let Some((pattern, timeout)) = vanity {
+        let legacy_keys = grind(&pattern, timeout)?;
+        let (_, _, private_identity, _) = match output_dir {
+            Some(output_dir) => validator_keygen_from_legacy(legacy_keys, output_dir, options)?,
+            None => generate_key_objects_from_legacy(legacy_keys)?,
+        };
+        private_identity.account_private_key
     } else {
-        let (_, _, private_identity, _) = validator_keygen(output_dir)?;
+        let (_, _, private_identity, _) = match output_dir {
+            Some(output_dir) => validator_keygen(output_dir, options)?,
+            None => generate_key_objects_from_legacy(legacy_keygen()?)?,
+        };
         private_identity.account_private_key
     };
 
     let public_key = Ed25519PublicKey::from(&private_key);
     let authentication_key = AuthenticationKey::ed25519(&public_key);
-    let private_key = hex::encode(private_key.to_bytes());
-    let account_address = authentication_key.derived_address().to_hex_literal();
+    // An encrypted key is not printed either
+    let private_key = match keystore_file {
+        Some(keystore_file) => {
+            let password = read_password(password_file, true)?;
+            Keystore::encrypt(&private_key, &password)?.save(keystore_file, false)?;
+            format!("encrypted in {}", keystore_file.display())
+        }
+        None if show_private_key => hex::encode(private_key.to_bytes()),
+        None => "not shown, pass --show-private-key".to_string(),
+    };
+    // A legacy key signs for its legacy address, unless the account on-chain says otherwise
+    let mut address_lines = String::new();
+    let account_address = match legacy_node {
+        Some(url) => {
+            let (address, scheme) = match url {
+                Some(url) => match find_account(&Client::new(url), &authentication_key).await? {
+                    Some((address, scheme)) => (address, format!("{scheme}, found on-chain")),
+                    None => (
+                        legacy_address(&authentication_key),
+                        "legacy 0L v5/v6, no account on-chain has either address".to_string(),
+                    ),
+                },
+                None => (
+                    legacy_address(&authentication_key),
+                    "legacy 0L v5/v6, not checked on-chain, pass --url".to_string(),
+                ),
+            };
+            address_lines = formatdoc!(
+                r#"
+                    Address scheme: {scheme}
+                    Legacy address: {}
+                    Current scheme address: {}
+                "#,
+                legacy_address(&authentication_key).to_hex_literal(),
+                current_address(&authentication_key).to_hex_literal()
+            );
+            address.to_hex_literal()
+        }
+        None => authentication_key.derived_address().to_hex_literal(),
+    };
 
-    Ok(formatdoc!(
+    let mut output = formatdoc!(
         r#"
             ====================================
             Private key: {private_key}
@@ -33,19 +139,69 @@ pub async fn run(mnemonic: Option<String>, output_dir: Option<PathBuf>) -> Resul
             Authentication key: {authentication_key}
             Account address: {account_address}
         "#
+    );
+    output.push_str(&address_lines);
+    if let Some(derivation_path) = derivation_path {
+        output.push_str(&format!("Derivation path: {derivation_path}\n"));
+    }
+    Ok(output)
+}
+
+/// The keys of the account of a Ledger device at the index, read from the Aptos app on it
+#[cfg(feature = "ledger")]
+pub fn ledger(derivation_index: u32) -> Result<String> {
+    let public_key = libra_wallet::ledger::Ledger::open()?.public_key(derivation_index)?;
+    let authentication_key = AuthenticationKey::ed25519(&public_key);
+    let account_address = authentication_key.derived_address().to_hex_literal();
+    Ok(formatdoc!(
+        r#"
+            ====================================
+            Public key: {public_key}
+            Authentication key: {authentication_key}
+            Account address: {account_address}
+            Derivation path: m/44'/637'/{derivation_index}'/0'/0'
+        "#
     ))
 }
 
+#[cfg(not(feature = "ledger"))]
+pub fn ledger(_derivation_index: u32) -> Result<String> {
+    anyhow::bail!(
+        "This build of libra-wallet has no Ledger support, build it with `cargo build --features ledger`"
+    )
+}
+
 #[cfg(test)]
 mod tests {
-    use super::run;
+    use super::{run, KeyOutput};
     use anyhow::{bail, Result};
+    use libra_wallet::{
+        keys::{KeyFileFormat, KeyFileOptions},
+        secret::Secret,
+    };
     use std::{fs, path::PathBuf};
 
+    fn print_only(show_private_key: bool) -> KeyOutput {
+        KeyOutput::PrintOnly { show_private_key }
+    }
+
     #[tokio::test]
     async fn generate_keys_properly() -> Result<()> {
         let output_dir = "temp";
-        let result = run("", Some(PathBuf::from(output_dir))).await.unwrap();
+        let result = run(
+            None,
+            None,
+            0,
+            KeyOutput::Files {
+                output_dir: Some(PathBuf::from(output_dir)),
+                options: KeyFileOptions::default(),
+            },
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
         let result = result.split("\n").collect::<Vec<_>>();
 
         let private_key = hex::decode(result[1].replace("Private key: ", "")).unwrap();
@@ -79,21 +235,129 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn generate_keys_from_private_key_properly() {
-        let private_key = "c43f57994644ebda1eabfebf84def73fbd1d3ce442a9d2b2f4cb9f4da7b9908c";
-        let result = run(private_key, None).await.unwrap();
-        let result = result.split("\n").collect::<Vec<_>>();
-        let expected_private_key = format!("Private key: {private_key}");
-        let expected_public_key =
-            "Public key: ef00c7b6f6246543445a847a6d136d293c107b05044f7fc105a063c93c50d7a0";
-        let expected_auth_key =
-            "Authentication key: fda03992f666875ddf854193fccd3e62ea111d066029490dd37c891ed9c3f880";
+    async fn generate_keys_from_mnemonic_properly() {
+        let mnemonic = "circle ship inner pact earn inflict valve retire mechanic talk mouse outer display snack dose ahead orient tooth shrimp achieve pink slam kingdom rifle";
+        let result = run(
+            Some(Secret::new(mnemonic.to_owned())),
+            None,
+            0,
+            print_only(true),
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let result = result.split('\n').collect::<Vec<_>>();
         let expected_account_address =
-            "Account address: 0xfda03992f666875ddf854193fccd3e62ea111d066029490dd37c891ed9c3f880";
+            "Account address: 0x2c929da2b537c51c5db4b5b71826757e7db21780413d362d350026f75f6f47ed";
 
-        assert_eq!(expected_private_key, result[1]);
-        assert_eq!(expected_public_key, result[2]);
-        assert_eq!(expected_auth_key, result[3]);
         assert_eq!(expected_account_address, result[4]);
+        assert_eq!("Derivation path: m/44'/637'/0'/0'/0'", result[5]);
+
+        // The same mnemonic always derives the same account
+        let again = run(
+            Some(Secret::new(mnemonic.to_owned())),
+            None,
+            0,
+            print_only(true),
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.join("\n"), again);
+
+        // Another index derives another account
+        let other = run(
+            Some(Secret::new(mnemonic.to_owned())),
+            None,
+            1,
+            print_only(true),
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_ne!(
+            expected_account_address,
+            other.split('\n').collect::<Vec<_>>()[4]
+        );
+    }
+
+    #[tokio::test]
+    async fn print_only_hides_the_private_key() {
+        let mnemonic = "circle ship inner pact earn inflict valve retire mechanic talk mouse outer display snack dose ahead orient tooth shrimp achieve pink slam kingdom rifle";
+        let result = run(
+            Some(Secret::new(mnemonic.to_owned())),
+            None,
+            0,
+            print_only(false),
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let result = result.split('\n').collect::<Vec<_>>();
+        assert_eq!("Private key: not shown, pass --show-private-key", result[1]);
+        assert_eq!(
+            "Account address: 0x2c929da2b537c51c5db4b5b71826757e7db21780413d362d350026f75f6f47ed",
+            result[4]
+        );
+    }
+
+    #[tokio::test]
+    async fn json_files_are_not_overwritten_without_force() {
+        let output_dir = PathBuf::from("temp-json/nested");
+        let files = |force| KeyOutput::Files {
+            output_dir: Some(output_dir.clone()),
+            options: KeyFileOptions {
+                format: KeyFileFormat::Json,
+                force,
+            },
+        };
+        run(None, None, 0, files(false), None, None, None)
+            .await
+            .unwrap();
+        let json = fs::read_to_string(output_dir.join("public-keys.json")).unwrap();
+        let refused = run(None, None, 0, files(false), None, None, None).await;
+        let forced = run(None, None, 0, files(true), None, None, None).await;
+        let overwritten = fs::read_to_string(output_dir.join("public-keys.json")).unwrap();
+        fs::remove_dir_all("temp-json").ok();
+
+        assert!(serde_json::from_str::<serde_json::Value>(&json).is_ok());
+        assert!(refused.unwrap_err().to_string().contains("--force"));
+        forced.unwrap();
+        assert_ne!(json, overwritten);
+    }
+
+    #[tokio::test]
+    async fn label_the_legacy_address() {
+        let alice_mnem = "talent sunset lizard pill fame nuclear spy noodle basket okay critic grow sleep legend hurry pitch blanket clerk impose rough degree sock insane purse";
+        let result = run(
+            None,
+            Some((alice_mnem.to_owned(), None)),
+            0,
+            print_only(false),
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let result = result.split('\n').collect::<Vec<_>>();
+        assert_eq!(
+            "Account address: 0x4c613c2f4b1e67ca8d98a542ee3f59f5",
+            result[4]
+        );
+        assert!(result[5].starts_with("Address scheme: legacy 0L"));
+        assert_eq!(
+            "Legacy address: 0x4c613c2f4b1e67ca8d98a542ee3f59f5",
+            result[6]
+        );
+        assert!(result[7].starts_with("Current scheme address: 0x"));
     }
 }
@@ -12,12 +12,20 @@ use crate::utils::{
 
 use serde::Serialize;
 
-use anyhow::anyhow;
+use anyhow::{anyhow, bail};
 use std::path::{Path, PathBuf};
 use zapatos_config::{config::IdentityBlob, keys::ConfigKey};
-use zapatos_crypto::{bls12381, ed25519::Ed25519PrivateKey, traits::PrivateKey, x25519};
+use zapatos_crypto::{
+    bls12381,
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    traits::PrivateKey,
+    x25519,
+};
 use zapatos_genesis::keys::{PrivateIdentity, PublicIdentity};
-use zapatos_types::transaction::authenticator::AuthenticationKey;
+use zapatos_sdk::types::LocalAccount;
+use zapatos_types::{
+    account_address::AccountAddress, transaction::authenticator::AuthenticationKey,
+};
 
 // These are consistent with Vendor
 const PRIVATE_KEYS_FILE: &str = "private-keys.yaml";
@@ -26,6 +34,65 @@ pub const VALIDATOR_FILE: &str = "validator-identity.yaml";
 const VFN_FILE: &str = "validator-full-node-identity.yaml";
 // This is Libra specific
 const USER_FILE: &str = "danger-user-private-keys.yaml";
+pub const ACCOUNT_KEYS_FILE: &str = "account-keys.yaml";
+
+/// Format of the key files, which also gives their extension
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum KeyFileFormat {
+    #[default]
+    Yaml,
+    Json,
+}
+
+/// How the key files are written
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KeyFileOptions {
+    pub format: KeyFileFormat,
+    /// Overwrite the key files which already exist, instead of refusing to
+    pub force: bool,
+}
+
+/// An end-user account derived from a BIP-39 mnemonic, the same way Aptos wallets do it.
+#[derive(Serialize)]
+pub struct DerivedAccount {
+    pub account_address: AccountAddress,
+    pub account_private_key: Ed25519PrivateKey,
+    pub account_public_key: Ed25519PublicKey,
+    pub authentication_key: AuthenticationKey,
+    /// Recorded so the account can be derived again from the same mnemonic
+    pub derivation_path: String,
+}
+
+/// BIP-44 path of the nth account, 637 being the Aptos coin type
+pub fn aptos_derivation_path(index: u32) -> String {
+    format!("m/44'/637'/{index}'/0'/0'")
+}
+
+/// Derive an account from a BIP-39 mnemonic, and save it if an output directory is given.
+pub fn mnemonic_keygen(
+    mnemonic: &str,
+    derivation_index: u32,
+    output_opt: Option<PathBuf>,
+    options: KeyFileOptions,
+) -> anyhow::Result<DerivedAccount> {
+    let derivation_path = aptos_derivation_path(derivation_index);
+    let account = LocalAccount::from_derive_path(&derivation_path, mnemonic.trim(), 0)?;
+    let derived_account = DerivedAccount {
+        account_address: account.address(),
+        account_private_key: Ed25519PrivateKey::try_from(
+            account.private_key().to_bytes().as_ref(),
+        )?,
+        account_public_key: account.public_key().to_owned(),
+        authentication_key: account.authentication_key(),
+        derivation_path,
+    };
+
+    if let Some(dir) = output_opt {
+        create_dir_if_not_exist(dir.as_path())?;
+        write_key_file(&dir, ACCOUNT_KEYS_FILE, &derived_account, options)?;
+    }
+    Ok(derived_account)
+}
 
 // new keys for user
 pub fn user_keygen(output_opt: Option<PathBuf>) -> anyhow::Result<()> {
@@ -33,7 +100,7 @@ pub fn user_keygen(output_opt: Option<PathBuf>) -> anyhow::Result<()> {
 
     if let Some(dir) = output_opt {
         if prompt_yes("Saving keys locally is VERY DANGEROUS, do you know what you are doing?") {
-            write_key_file(&dir, USER_FILE, user_keys)?;
+            write_key_file(&dir, USER_FILE, user_keys, KeyFileOptions::default())?;
         }
     }
     Ok(())
@@ -42,9 +109,17 @@ pub fn user_keygen(output_opt: Option<PathBuf>) -> anyhow::Result<()> {
 // NOTE: Devs: this is copied from zapatos_genesis::keys::generate_key_objects()  and modified to use our legacy keygen process.
 pub fn validator_keygen(
     output_opt: Option<PathBuf>,
+    options: KeyFileOptions,
 ) -> anyhow::Result<(IdentityBlob, IdentityBlob, PrivateIdentity, PublicIdentity)> {
-    let legacy_keys = legacy_keygen()?;
+    validator_keygen_from_legacy(legacy_keygen()?, output_opt, options)
+}
 
+/// Same as `validator_keygen` with keys generated beforehand, e.g. for a vanity address
+pub fn validator_keygen_from_legacy(
+    legacy_keys: LegacyKeys,
+    output_opt: Option<PathBuf>,
+    options: KeyFileOptions,
+) -> anyhow::Result<(IdentityBlob, IdentityBlob, PrivateIdentity, PublicIdentity)> {
     let (validator_blob, vfn_blob, private_identity, public_identity) =
         generate_key_objects_from_legacy(legacy_keys)?;
 
@@ -54,6 +129,7 @@ pub fn validator_keygen(
         &vfn_blob,
         &private_identity,
         &public_identity,
+        options,
     )?;
 
     Ok((validator_blob, vfn_blob, private_identity, public_identity))
@@ -68,25 +144,58 @@ pub fn refresh_validator_files(
     let (validator_blob, vfn_blob, private_identity, public_identity) =
         generate_key_objects_from_legacy(legacy_keys)?;
 
+    // Asked once for all the files, which are then overwritten
+    let output_dir = dir_default_to_current(&output_opt)?;
+    for filename in [PRIVATE_KEYS_FILE, PUBLIC_KEYS_FILE, VALIDATOR_FILE, VFN_FILE] {
+        check_if_file_exists(&output_dir.join(filename))?;
+    }
     save_val_files(
-        output_opt,
+        Some(output_dir),
         &validator_blob,
         &vfn_blob,
         &private_identity,
         &public_identity,
+        KeyFileOptions {
+            force: true,
+            ..Default::default()
+        },
     )?;
 
     Ok((validator_blob, vfn_blob, private_identity, public_identity))
 }
 
-fn write_key_file<T: Serialize>(output_dir: &Path, filename: &str, data: T) -> anyhow::Result<()> {
-    let file = output_dir.join(filename);
-    check_if_file_exists(file.as_path())?;
-    write_to_user_only_file(
-        file.as_path(),
-        PRIVATE_KEYS_FILE,
-        to_yaml(&data)?.as_bytes(),
-    )?;
+/// Path of the key file, with the extension of the format
+fn key_file_path(output_dir: &Path, filename: &str, format: KeyFileFormat) -> PathBuf {
+    match format {
+        KeyFileFormat::Yaml => output_dir.join(filename),
+        KeyFileFormat::Json => output_dir.join(filename).with_extension("json"),
+    }
+}
+
+fn refuse_existing_key_file(file: &Path, options: KeyFileOptions) -> anyhow::Result<()> {
+    if file.exists() && !options.force {
+        bail!(
+            "{} already exists, pass --force to overwrite it",
+            file.display()
+        );
+    }
+    Ok(())
+}
+
+fn write_key_file<T: Serialize>(
+    output_dir: &Path,
+    filename: &str,
+    data: T,
+    options: KeyFileOptions,
+) -> anyhow::Result<()> {
+    let file = key_file_path(output_dir, filename, options.format);
+    refuse_existing_key_file(&file, options)?;
+    let contents = match options.format {
+        KeyFileFormat::Yaml => to_yaml(&data)?,
+        KeyFileFormat::Json => serde_json::to_string_pretty(&data)?,
+    };
+    let name = file.display().to_string();
+    write_to_user_only_file(file.as_path(), &name, contents.as_bytes())?;
     Ok(())
 }
 
@@ -96,14 +205,19 @@ fn save_val_files(
     vfn_blob: &IdentityBlob,
     private_identity: &PrivateIdentity,
     public_identity: &PublicIdentity,
+    options: KeyFileOptions,
 ) -> anyhow::Result<()> {
     let output_dir = dir_default_to_current(&output_opt)?;
     create_dir_if_not_exist(output_dir.as_path())?;
+    // None of the files is written when one of them is refused
+    for filename in [PRIVATE_KEYS_FILE, PUBLIC_KEYS_FILE, VALIDATOR_FILE, VFN_FILE] {
+        refuse_existing_key_file(&key_file_path(&output_dir, filename, options.format), options)?;
+    }
 
-    write_key_file(&output_dir, PRIVATE_KEYS_FILE, private_identity)?;
-    write_key_file(&output_dir, PUBLIC_KEYS_FILE, public_identity)?;
-    write_key_file(&output_dir, VALIDATOR_FILE, validator_blob)?;
-    write_key_file(&output_dir, VFN_FILE, vfn_blob)?;
+    write_key_file(&output_dir, PRIVATE_KEYS_FILE, private_identity, options)?;
+    write_key_file(&output_dir, PUBLIC_KEYS_FILE, public_identity, options)?;
+    write_key_file(&output_dir, VALIDATOR_FILE, validator_blob, options)?;
+    write_key_file(&output_dir, VFN_FILE, vfn_blob, options)?;
 
     Ok(())
 }
@@ -0,0 +1,233 @@
+//! Private keys encrypted with a password, so they are never stored in plaintext.
+//! The password is stretched with scrypt into an AES-256-GCM key
+use crate::utils::write_to_user_only_file;
+use aes_gcm::{
+    aead::{rand_core::RngCore, Aead, KeyInit, OsRng},
+    Aes256Gcm, Nonce,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use dialoguer::Password;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+use zapatos_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    ValidCryptoMaterialStringExt,
+};
+use zapatos_types::transaction::authenticator::AuthenticationKey;
+use zeroize::Zeroizing;
+
+/// Version of the keystore file written by this version
+pub const KEYSTORE_VERSION: u32 = 1;
+
+/// Environment variable with the password of a keystore, for automation
+pub const KEYSTORE_PASSWORD_ENV: &str = "LIBRA_KEYSTORE_PASSWORD";
+
+// About 32 MiB of memory to derive the key, as recommended for interactive logins
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+// Bounds of the parameters read from a keystore, so that an edited file cannot make the key
+// derivation take more than 2 GiB of memory
+const MAX_SCRYPT_LOG_N: u8 = 20;
+const MAX_SCRYPT_R: u32 = 16;
+const MAX_SCRYPT_P: u32 = 16;
+const SALT_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u32,
+    /// Kept in the clear so the keystore can be told apart without the password
+    pub account_address: String,
+    pub public_key: String,
+    pub kdf: ScryptParams,
+    pub cipher: CipherParams,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScryptParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+    pub salt: String,
+}
+
+/// AES-256-GCM, the ciphertext ends with the authentication tag
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CipherParams {
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+impl Keystore {
+    pub fn encrypt(private_key: &Ed25519PrivateKey, password: &str) -> Result<Self> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let kdf = ScryptParams {
+            log_n: SCRYPT_LOG_N,
+            r: SCRYPT_R,
+            p: SCRYPT_P,
+            salt: hex::encode(salt),
+        };
+        let plaintext = Zeroizing::new(private_key.to_bytes());
+        let ciphertext = cipher(password, &kdf)?
+            .encrypt(Nonce::from_slice(&nonce), &plaintext[..])
+            .map_err(|_| anyhow!("Failed to encrypt the private key"))?;
+
+        let public_key = Ed25519PublicKey::from(private_key);
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            account_address: AuthenticationKey::ed25519(&public_key)
+                .derived_address()
+                .to_hex_literal(),
+            public_key: public_key.to_encoded_string()?,
+            kdf,
+            cipher: CipherParams {
+                nonce: hex::encode(nonce),
+                ciphertext: hex::encode(ciphertext),
+            },
+        })
+    }
+
+    pub fn decrypt(&self, password: &str) -> Result<Ed25519PrivateKey> {
+        let nonce = hex::decode(&self.cipher.nonce).context("Invalid nonce in the keystore")?;
+        if nonce.len() != NONCE_LEN {
+            bail!("Invalid nonce in the keystore");
+        }
+        let ciphertext =
+            hex::decode(&self.cipher.ciphertext).context("Invalid ciphertext in the keystore")?;
+        // Decryption only fails on a wrong password or an edited file, the tag does not match
+        let bytes = Zeroizing::new(
+            cipher(password, &self.kdf)?
+                .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+                .map_err(|_| anyhow!("Wrong password for the keystore"))?,
+        );
+        let private_key = Ed25519PrivateKey::try_from(bytes.as_slice())
+            .context("The keystore holds an invalid private key")?;
+        if Ed25519PublicKey::from(&private_key).to_encoded_string()? != self.public_key {
+            bail!("The private key of the keystore does not match its public key");
+        }
+        Ok(private_key)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .context(format!("Failed to read the keystore {}", path.display()))?;
+        let keystore: Self = serde_json::from_str(&content)
+            .context(format!("{} is not a keystore file", path.display()))?;
+        if keystore.version > KEYSTORE_VERSION {
+            bail!(
+                "{} is a keystore of version {}, this version reads up to version {KEYSTORE_VERSION}",
+                path.display(),
+                keystore.version
+            );
+        }
+        Ok(keystore)
+    }
+
+    pub fn save(&self, path: &Path, force: bool) -> Result<()> {
+        if path.exists() && !force {
+            bail!(
+                "{} already exists, use --force to overwrite it",
+                path.display()
+            );
+        }
+        write_to_user_only_file(
+            path,
+            &path.display().to_string(),
+            serde_json::to_string_pretty(self)?.as_bytes(),
+        )
+        .context(format!("Failed to write the keystore {}", path.display()))
+    }
+}
+
+/// Decrypt the keystore at the path, with the password from `read_password`
+pub fn load_private_key(path: &Path, password_file: Option<&Path>) -> Result<Ed25519PrivateKey> {
+    let keystore = Keystore::load(path)?;
+    let password = read_password(password_file, false)?;
+    keystore
+        .decrypt(&password)
+        .context(format!("Failed to open the keystore {}", path.display()))
+}
+
+/// The password from the file, else from the environment variable, else asked for on the
+/// terminal. A new password is asked for twice
+pub fn read_password(password_file: Option<&Path>, new: bool) -> Result<Zeroizing<String>> {
+    if let Some(path) = password_file {
+        let password = Zeroizing::new(fs::read_to_string(path).context(format!(
+            "Failed to read the password file {}",
+            path.display()
+        ))?);
+        return Ok(Zeroizing::new(
+            password.trim_end_matches(['\r', '\n']).to_string(),
+        ));
+    }
+    if let Ok(password) = std::env::var(KEYSTORE_PASSWORD_ENV) {
+        return Ok(Zeroizing::new(password));
+    }
+    let mut prompt = Password::new();
+    prompt.with_prompt("Keystore password");
+    if new {
+        prompt.with_confirmation("Repeat the password", "The passwords do not match");
+    }
+    prompt.interact().map(Zeroizing::new).context(format!(
+        "Failed to read the password, use --password-file or {KEYSTORE_PASSWORD_ENV} when there is no terminal"
+    ))
+}
+
+fn cipher(password: &str, kdf: &ScryptParams) -> Result<Aes256Gcm> {
+    if !(1..=MAX_SCRYPT_LOG_N).contains(&kdf.log_n)
+        || !(1..=MAX_SCRYPT_R).contains(&kdf.r)
+        || !(1..=MAX_SCRYPT_P).contains(&kdf.p)
+    {
+        bail!(
+            "The scrypt parameters of the keystore are out of bounds: log_n {}, r {} and p {}, this version reads up to {MAX_SCRYPT_LOG_N}, {MAX_SCRYPT_R} and {MAX_SCRYPT_P}",
+            kdf.log_n,
+            kdf.r,
+            kdf.p
+        );
+    }
+    let salt = hex::decode(&kdf.salt).context("Invalid salt in the keystore")?;
+    let params = scrypt::Params::new(kdf.log_n, kdf.r, kdf.p, 32)
+        .map_err(|_| anyhow!("Invalid scrypt parameters in the keystore"))?;
+    let mut key = Zeroizing::new(vec![0u8; 32]);
+    scrypt::scrypt(password.as_bytes(), &salt, &params, &mut key)
+        .map_err(|_| anyhow!("Failed to derive the keystore key"))?;
+    Aes256Gcm::new_from_slice(&key).map_err(|_| anyhow!("Invalid keystore key"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_and_decrypt_a_key() {
+        let private_key = Ed25519PrivateKey::try_from(&[3u8; 32][..]).unwrap();
+        let keystore = Keystore::encrypt(&private_key, "correct horse").unwrap();
+        let keystore: Keystore =
+            serde_json::from_str(&serde_json::to_string(&keystore).unwrap()).unwrap();
+        assert_eq!(keystore.decrypt("correct horse").unwrap(), private_key);
+
+        let err = keystore.decrypt("wrong horse").unwrap_err();
+        assert_eq!(err.to_string(), "Wrong password for the keystore");
+        // Neither the key nor the password show in the error chain
+        let rendered = format!("{err:?}");
+        assert!(!rendered.contains(&hex::encode([3u8; 32])[..8]));
+        assert!(!rendered.contains("horse"));
+    }
+
+    #[test]
+    fn refuse_costly_scrypt_parameters() {
+        let private_key = Ed25519PrivateKey::try_from(&[3u8; 32][..]).unwrap();
+        let mut keystore = Keystore::encrypt(&private_key, "correct horse").unwrap();
+        keystore.kdf.log_n = MAX_SCRYPT_LOG_N + 1;
+        let err = keystore.decrypt("correct horse").unwrap_err();
+        assert!(err.to_string().contains("out of bounds"), "{err}");
+
+        keystore.kdf.log_n = SCRYPT_LOG_N;
+        keystore.kdf.p = 0;
+        assert!(keystore.decrypt("correct horse").is_err());
+    }
+}
@@ -0,0 +1,206 @@
+use anyhow::{bail, Context, Result};
+use hidapi::{HidApi, HidDevice};
+use zapatos_crypto::ed25519::{Ed25519PublicKey, Ed25519Signature};
+use zapatos_types::{
+    account_address::AccountAddress, transaction::authenticator::AuthenticationKey,
+};
+
+const LEDGER_VENDOR_ID: u16 = 0x2c97;
+const CHANNEL: u16 = 0x0101;
+const TAG_APDU: u8 = 0x05;
+const REPORT_SIZE: usize = 64;
+const HARDENED: u32 = 0x8000_0000;
+const READ_TIMEOUT_MS: i32 = 60_000;
+
+const CLA_APTOS: u8 = 0x5b;
+const INS_GET_PUBLIC_KEY: u8 = 0x05;
+const INS_SIGN_TRANSACTION: u8 = 0x06;
+const P1_START: u8 = 0x00;
+const P2_MORE: u8 = 0x80;
+const P2_LAST: u8 = 0x00;
+const MAX_APDU_DATA: usize = 255;
+const STATUS_OK: u16 = 0x9000;
+const STATUS_REJECTED: u16 = 0x6985;
+
+/// The Aptos app of a Ledger device, talked to over USB HID. The keys never leave the device
+pub struct Ledger {
+    device: HidDevice,
+}
+
+impl Ledger {
+    /// Connect to the first Ledger device plugged in. The Aptos app must be open on it
+    pub fn open() -> Result<Self> {
+        let api = HidApi::new().context("Failed to access the USB devices")?;
+        let info = api
+            .device_list()
+            .find(|info| info.vendor_id() == LEDGER_VENDOR_ID)
+            .context("No Ledger device found, plug it in and unlock it")?;
+        let device = info
+            .open_device(&api)
+            .context("Failed to connect to the Ledger device")?;
+        Ok(Self { device })
+    }
+
+    pub fn public_key(&self, derivation_index: u32) -> Result<Ed25519PublicKey> {
+        let response = self.exchange(
+            INS_GET_PUBLIC_KEY,
+            P1_START,
+            P2_LAST,
+            &derivation_path(derivation_index),
+        )?;
+        // The length of the key with its prefix byte, the prefix, then the key
+        let length = usize::from(*response.first().context("Empty public key response")?);
+        let public_key = response
+            .get(2..1 + length)
+            .context("Truncated public key response")?;
+        Ok(Ed25519PublicKey::try_from(public_key)?)
+    }
+
+    /// The account address of the original key at the index, as it is before any rotation
+    pub fn address(&self, derivation_index: u32) -> Result<AccountAddress> {
+        Ok(AuthenticationKey::ed25519(&self.public_key(derivation_index)?).derived_address())
+    }
+
+    /// Sign the signing message of a transaction, the device shows the transaction and signs it
+    /// once the user approves it
+    pub fn sign(&self, derivation_index: u32, message: &[u8]) -> Result<Ed25519Signature> {
+        let mut response = self.exchange(
+            INS_SIGN_TRANSACTION,
+            P1_START,
+            P2_MORE,
+            &derivation_path(derivation_index),
+        )?;
+        let chunks = message.chunks(MAX_APDU_DATA).collect::<Vec<_>>();
+        for (index, chunk) in chunks.iter().enumerate() {
+            let p2 = if index + 1 == chunks.len() {
+                P2_LAST
+            } else {
+                P2_MORE
+            };
+            response = self.exchange(INS_SIGN_TRANSACTION, u8::try_from(index + 1)?, p2, chunk)?;
+        }
+        let length = usize::from(*response.first().context("Empty signature response")?);
+        let signature = response
+            .get(1..1 + length)
+            .context("Truncated signature response")?;
+        Ok(Ed25519Signature::try_from(signature)?)
+    }
+
+    /// Send an APDU and return the data of the response, without its status word
+    fn exchange(&self, ins: u8, p1: u8, p2: u8, data: &[u8]) -> Result<Vec<u8>> {
+        let mut apdu = vec![CLA_APTOS, ins, p1, p2, u8::try_from(data.len())?];
+        apdu.extend_from_slice(data);
+        for report in frame_apdu(&apdu) {
+            // hidapi expects the report id first, Ledger devices use none
+            let mut buffer = vec![0];
+            buffer.extend_from_slice(&report);
+            self.device
+                .write(&buffer)
+                .context("Failed to write to the Ledger device")?;
+        }
+
+        let mut response = self.read_response()?;
+        if response.len() < 2 {
+            bail!("Invalid response from the Ledger device");
+        }
+        let status_bytes = response.split_off(response.len() - 2);
+        match u16::from_be_bytes([status_bytes[0], status_bytes[1]]) {
+            STATUS_OK => Ok(response),
+            STATUS_REJECTED => bail!("The transaction was rejected on the Ledger device"),
+            status => bail!(
+                "The Ledger device returned status {status:#06x}, is the Aptos app open on it?"
+            ),
+        }
+    }
+
+    fn read_response(&self) -> Result<Vec<u8>> {
+        let mut response = vec![];
+        let mut length = None;
+        let mut sequence = 0u16;
+        while length.map_or(true, |length| response.len() < length) {
+            let mut report = [0u8; REPORT_SIZE];
+            let read = self
+                .device
+                .read_timeout(&mut report, READ_TIMEOUT_MS)
+                .context("Failed to read from the Ledger device")?;
+            if read == 0 {
+                bail!("Timed out waiting for the Ledger device");
+            }
+            if report[..5] != header(sequence)[..] {
+                bail!("Unexpected report from the Ledger device");
+            }
+            let data = if sequence == 0 {
+                length = Some(usize::from(u16::from_be_bytes([report[5], report[6]])));
+                &report[7..]
+            } else {
+                &report[5..]
+            };
+            response.extend_from_slice(data);
+            sequence += 1;
+        }
+        response.truncate(length.unwrap_or_default());
+        Ok(response)
+    }
+}
+
+/// The Aptos path m/44'/637'/<INDEX>'/0'/0', as the app takes it: the number of levels,
+/// then each level as a big endian u32
+fn derivation_path(derivation_index: u32) -> Vec<u8> {
+    let levels = [44, 637, derivation_index, 0, 0];
+    let mut path = vec![levels.len() as u8];
+    for level in levels {
+        path.extend_from_slice(&(level | HARDENED).to_be_bytes());
+    }
+    path
+}
+
+/// Split an APDU in HID reports. The first report holds the length of the APDU
+fn frame_apdu(apdu: &[u8]) -> Vec<[u8; REPORT_SIZE]> {
+    let mut data = (apdu.len() as u16).to_be_bytes().to_vec();
+    data.extend_from_slice(apdu);
+    data.chunks(REPORT_SIZE - 5)
+        .enumerate()
+        .map(|(sequence, chunk)| {
+            let mut report = [0u8; REPORT_SIZE];
+            report[..5].copy_from_slice(&header(sequence as u16));
+            report[5..5 + chunk.len()].copy_from_slice(chunk);
+            report
+        })
+        .collect()
+}
+
+fn header(sequence: u16) -> [u8; 5] {
+    let [channel_high, channel_low] = CHANNEL.to_be_bytes();
+    let [sequence_high, sequence_low] = sequence.to_be_bytes();
+    [
+        channel_high,
+        channel_low,
+        TAG_APDU,
+        sequence_high,
+        sequence_low,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_apdus_in_reports() {
+        assert_eq!(
+            derivation_path(2),
+            vec![
+                5, 0x80, 0, 0, 44, 0x80, 0, 0x02, 0x7d, 0x80, 0, 0, 2, 0x80, 0, 0, 0, 0x80, 0, 0, 0
+            ]
+        );
+
+        let apdu = vec![0xab; 100];
+        let reports = frame_apdu(&apdu);
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0][..7], [0x01, 0x01, 0x05, 0, 0, 0, 100]);
+        assert_eq!(reports[1][..5], [0x01, 0x01, 0x05, 0, 1]);
+        // 57 bytes of the APDU fit in the first report, the other 43 in the second
+        assert_eq!(reports[1][5 + 42], 0xab);
+        assert_eq!(reports[1][5 + 43], 0);
+    }
+}
@@ -1,7 +1,13 @@
 // #![warn(unused_crate_dependencies)]
 // Unused crate dependencies is not reliable. Uncomment to run check or use `cargo machete`
 
+pub mod address_scheme;
 pub mod keys;
+pub mod keystore;
+#[cfg(feature = "ledger")]
+pub mod ledger;
 pub mod legacy;
+pub mod secret;
 pub mod utils;
 pub mod validator_files;
+pub mod vanity;
@@ -1,8 +1,15 @@
 #![allow(dead_code)]
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Args, Parser, Subcommand};
+use key_gen::KeyOutput;
 use legacy::get_keys_from_prompt;
-use std::path::PathBuf;
+use libra_wallet::{
+    keys::{KeyFileFormat, KeyFileOptions},
+    secret::{install_panic_hook, Secret},
+    vanity::VanityPattern,
+};
+use std::{fs, path::PathBuf, time::Duration};
+use url::Url;
 
 mod key_gen;
 mod legacy;
@@ -19,13 +26,73 @@ struct Entry {
 enum Commands {
     /// Generate keys and account address locally
     Keygen {
-        /// Recover account from the given mnemonic
-        #[clap(short, long)]
-        mnemonic: Option<String>,
+        /// Recover account from the given BIP-39 mnemonic, derived with the Aptos path m/44'/637'/<DERIVATION_INDEX>'/0'/0'
+        #[clap(short, long, group = "mnemonic_source")]
+        mnemonic: Option<Secret>,
+
+        /// Read the BIP-39 mnemonic from a file instead
+        #[clap(long, group = "mnemonic_source")]
+        mnemonic_file: Option<PathBuf>,
+
+        /// Recover account from a mnemonic with the legacy 0L key derivation. Its address is the legacy 0L address unless --url finds the account at another one
+        #[clap(long, group = "mnemonic_source")]
+        legacy_mnemonic: Option<Secret>,
+
+        /// REST endpoint of a fullnode to look the account of --legacy-mnemonic up on: the address it rotated from, else the current scheme address, else the legacy one
+        #[clap(long, requires = "legacy_mnemonic")]
+        url: Option<Url>,
+
+        /// Index of the account to derive from the mnemonic, or of the key of the Ledger device
+        #[clap(long, default_value_t = 0)]
+        derivation_index: u32,
+
+        /// Print the account of the key of a Ledger device instead, the private key never leaves it
+        #[clap(long, conflicts_with_all = ["mnemonic_source", "output_dir", "encrypt", "vanity_prefix", "vanity_suffix"])]
+        ledger: bool,
 
-        /// Path of the directory to store yaml files
+        /// Path of the directory to store the key files, created when missing
         #[clap(short, long)]
         output_dir: Option<String>,
+
+        /// Format of the key files
+        #[clap(long, value_enum, default_value_t = KeyFileFormat::Yaml)]
+        format: KeyFileFormat,
+
+        /// Overwrite the key files which already exist
+        #[clap(long)]
+        force: bool,
+
+        /// Print the address and the keys without writing any file
+        #[clap(long, conflicts_with_all = ["output_dir", "encrypt", "force"])]
+        print_only: bool,
+
+        /// Also print the private key with --print-only
+        #[clap(long, requires = "print_only")]
+        show_private_key: bool,
+
+        /// Save the private key encrypted with a password to --keystore-file, instead of printing it
+        #[clap(long, conflicts_with = "output_dir")]
+        encrypt: bool,
+
+        /// Where --encrypt saves the keystore
+        #[clap(long, default_value = "keystore.json")]
+        keystore_file: PathBuf,
+
+        /// File holding the password of the keystore. Without it the password is read from the LIBRA_KEYSTORE_PASSWORD environment variable or asked for
+        #[clap(long, requires = "encrypt")]
+        password_file: Option<PathBuf>,
+
+        /// Generate keys until the account address starts with these hex digits, case insensitive. Each digit makes it 16 times longer
+        #[clap(long, conflicts_with = "mnemonic_source")]
+        vanity_prefix: Option<String>,
+
+        /// Generate keys until the account address ends with these hex digits, case insensitive
+        #[clap(long, conflicts_with = "mnemonic_source")]
+        vanity_suffix: Option<String>,
+
+        /// Give up the vanity search after this long
+        #[clap(long)]
+        timeout_secs: Option<u64>,
     },
     /// Use the legacy key derivation scheme
     Legacy(LegArgs),
@@ -46,6 +113,7 @@ struct LegArgs {
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    install_panic_hook();
     let cli = Entry::parse();
 
     // You can check for the existence of subcommands, and if found use their
@@ -73,11 +141,68 @@ async fn main() -> Result<()> {
         }
         Commands::Keygen {
             mnemonic,
+            mnemonic_file,
+            legacy_mnemonic,
+            url,
+            derivation_index,
+            ledger,
             output_dir,
+            format,
+            force,
+            print_only,
+            show_private_key,
+            encrypt,
+            keystore_file,
+            password_file,
+            vanity_prefix,
+            vanity_suffix,
+            timeout_secs,
         } => {
+            if *ledger {
+                println!("{}", key_gen::ledger(*derivation_index)?);
+                return Ok(());
+            }
+            let mnemonic = match mnemonic_file {
+                Some(path) => Some(Secret::new(fs::read_to_string(path).context(format!(
+                    "Failed to read the mnemonic file {}",
+                    path.display()
+                ))?)),
+                None => mnemonic.to_owned(),
+            };
+            let vanity = if vanity_prefix.is_some() || vanity_suffix.is_some() {
+                let pattern =
+                    VanityPattern::new(vanity_prefix.as_deref(), vanity_suffix.as_deref())?;
+                Some((pattern, timeout_secs.map(Duration::from_secs)))
+            } else {
+                None
+            };
+            let key_output = if *print_only {
+                KeyOutput::PrintOnly {
+                    show_private_key: *show_private_key,
+                }
+            } else {
+                KeyOutput::Files {
+                    output_dir: output_dir.as_ref().map(PathBuf::from),
+                    options: KeyFileOptions {
+                        format: *format,
+                        force: *force,
+                    },
+                }
+            };
             println!(
                 "{}",
-                key_gen::run(mnemonic.to_owned(), output_dir.as_ref().map(PathBuf::from)).await?
+                key_gen::run(
+                    mnemonic,
+                    legacy_mnemonic
+                        .to_owned()
+                        .map(|mnemonic| (mnemonic, url.to_owned())),
+                    *derivation_index,
+                    key_output,
+                    encrypt.then_some(keystore_file.as_path()),
+                    password_file.as_deref(),
+                    vanity
+                )
+                .await?
             );
         }
     }
@@ -0,0 +1,101 @@
+use std::{
+    convert::Infallible,
+    fmt,
+    io::Write,
+    panic::{self, PanicInfo},
+    str::FromStr,
+};
+use zeroize::Zeroize;
+
+/// Key material such as a private key or a mnemonic. It renders as `<redacted>` so it never
+/// ends up in an error or a log, and its buffer is wiped on drop
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(secret: String) -> Self {
+        Self(secret)
+    }
+
+    /// The secret itself, only to be handed to a parser or a signer
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Secret {
+    type Err = Infallible;
+
+    fn from_str(secret: &str) -> Result<Self, Self::Err> {
+        Ok(Self(secret.to_owned()))
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Replace the default panic hook, which prints the panic message, with one which only prints
+/// where the panic happened. The message of an unwrap quotes the error, which can hold a key
+pub fn install_panic_hook() {
+    panic::set_hook(Box::new(|info| report_panic(info, &mut std::io::stderr())));
+}
+
+fn report_panic(info: &PanicInfo<'_>, out: &mut impl Write) {
+    let location = info
+        .location()
+        .map_or("an unknown location".to_string(), |location| {
+            location.to_string()
+        });
+    // Nothing is left to do when stderr is gone
+    writeln!(
+        out,
+        "The program panicked at {location}. The panic message is not shown as it may hold key material"
+    )
+    .ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    const MNEMONIC: &str = "circle ship inner pact earn inflict valve retire mechanic talk mouse outer display snack dose ahead orient tooth shrimp achieve pink slam kingdom rifle";
+
+    #[test]
+    fn panics_do_not_echo_secrets() {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let hook_output = output.clone();
+        panic::set_hook(Box::new(move |info| {
+            report_panic(info, &mut *hook_output.lock().unwrap())
+        }));
+        let result = panic::catch_unwind(|| {
+            let parsed: Result<(), String> = Err(format!("invalid mnemonic {MNEMONIC}"));
+            parsed.unwrap();
+        });
+        drop(panic::take_hook());
+
+        assert!(result.is_err());
+        let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("secret.rs"), "{output}");
+        assert!(!output.contains("circle ship"), "{output}");
+        assert_eq!(
+            format!("{0} {0:?}", Secret::new(MNEMONIC.to_string())),
+            "<redacted> <redacted>"
+        );
+    }
+}
@@ -5,11 +5,12 @@
 use anyhow::{anyhow, bail};
 use dialoguer::Confirm;
 use serde::{de::DeserializeOwned, Serialize};
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt, PermissionsExt};
 use std::{
     env::current_dir,
-    fs::OpenOptions,
+    fs::{DirBuilder, OpenOptions},
     io::Write,
-    os::unix::fs::OpenOptionsExt,
     path::{Path, PathBuf},
 };
 use zapatos_genesis::keys::PublicIdentity;
@@ -37,7 +38,43 @@ pub fn write_to_user_only_file(path: &Path, name: &str, bytes: &[u8]) -> anyhow:
     let mut opts = OpenOptions::new();
     #[cfg(unix)]
     opts.mode(0o600);
-    write_to_file_with_opts(path, name, bytes, &mut opts)
+    write_to_file_with_opts(path, name, bytes, &mut opts)?;
+    // The mode only applies to a new file, an existing one keeps its permissions otherwise
+    #[cfg(unix)]
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600)).map_err(|e| {
+        anyhow!(
+            "cannot set the permissions of file: {}, message: {}",
+            name,
+            e
+        )
+    })?;
+    Ok(())
+}
+
+/// Check that a file holding a private key is only readable by its owner, as ssh does. A
+/// warning is printed otherwise, or it fails when `strict`. Not checked on Windows
+pub fn check_key_file_permissions(path: &Path, strict: bool) -> anyhow::Result<()> {
+    #[cfg(unix)]
+    {
+        let mode = std::fs::metadata(path)
+            .map_err(|e| anyhow!("cannot read file: {}, message: {}", path.display(), e))?
+            .permissions()
+            .mode()
+            & 0o777;
+        if mode & 0o077 != 0 {
+            let message = format!(
+                "{} holds a private key and is readable by other users (mode {mode:o}), run chmod 600 on it",
+                path.display()
+            );
+            if strict {
+                bail!(message);
+            }
+            eprintln!("WARNING: {message}");
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (path, strict);
+    Ok(())
 }
 
 /// Write a `&[u8]` to a file with the given options
@@ -69,7 +106,7 @@ pub fn dir_default_to_current(maybe_dir: &Option<PathBuf>) -> CliTypedResult<Pat
 pub fn create_dir_if_not_exist(dir: &Path) -> CliTypedResult<()> {
     // Check if the directory exists, if it's not a dir, it will also fail here
     if !dir.exists() || !dir.is_dir() {
-        std::fs::create_dir_all(dir).map_err(|e| anyhow!(e))?;
+        create_user_only_dir(dir)?;
         println!("Created {} folder", dir.display());
     } else {
         println!("{} folder already exists", dir.display());
@@ -77,6 +114,15 @@ pub fn create_dir_if_not_exist(dir: &Path) -> CliTypedResult<()> {
     Ok(())
 }
 
+/// Create the directory and its missing parents, only accessible by the user on Unix
+pub fn create_user_only_dir(dir: &Path) -> CliTypedResult<()> {
+    let mut builder = DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    builder.mode(0o700);
+    builder.create(dir).map_err(|e| anyhow!(e))
+}
+
 /// Note: We removed PromptOptions because it was not used
 pub fn prompt_yes_with_override(prompt: &str) -> CliTypedResult<()> {
     if prompt_yes(prompt) {
@@ -108,3 +154,39 @@ pub fn read_public_identity_file(public_identity_file: &Path) -> CliTypedResult<
     let bytes = read_from_file(public_identity_file)?;
     from_yaml(&String::from_utf8(bytes).map_err(|e| anyhow!(e))?)
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn mode(path: &Path) -> u32 {
+        std::fs::metadata(path).unwrap().permissions().mode() & 0o777
+    }
+
+    #[test]
+    fn key_files_are_user_only() {
+        let dir = std::env::temp_dir().join("libra-wallet-permissions-test");
+        std::fs::remove_dir_all(&dir).ok();
+        let keys_dir = dir.join("keys");
+        create_dir_if_not_exist(&keys_dir).unwrap();
+        let file = keys_dir.join("private-keys.yaml");
+        write_to_user_only_file(&file, "private-keys.yaml", b"key").unwrap();
+        let (dir_mode, file_mode) = (mode(&keys_dir), mode(&file));
+        check_key_file_permissions(&file, true).unwrap();
+
+        // A file readable by the group is only refused when strict
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o640)).unwrap();
+        let lenient = check_key_file_permissions(&file, false);
+        let strict = check_key_file_permissions(&file, true);
+        // Writing again restores the mode
+        write_to_user_only_file(&file, "private-keys.yaml", b"key").unwrap();
+        let rewritten_mode = mode(&file);
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(0o700, dir_mode);
+        assert_eq!(0o600, file_mode);
+        assert!(lenient.is_ok());
+        assert!(strict.unwrap_err().to_string().contains("chmod 600"));
+        assert_eq!(0o600, rewritten_mode);
+    }
+}
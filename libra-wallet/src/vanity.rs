@@ -0,0 +1,165 @@
+//! Grind new keys until the account address matches a pattern
+
+use crate::legacy::LegacyKeys;
+use anyhow::{bail, Result};
+use diem_wallet::WalletLibrary;
+use std::{
+    io::Write,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use zapatos_crypto::ed25519::Ed25519PublicKey;
+use zapatos_types::transaction::authenticator::AuthenticationKey;
+
+/// Longest pattern accepted, every hex digit multiplies the expected attempts by 16
+pub const MAX_VANITY_LENGTH: usize = 10;
+/// Patterns from this length on get a warning about how long they take
+const SLOW_VANITY_LENGTH: usize = 6;
+
+/// Hex digits the address must start and end with, case insensitive
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VanityPattern {
+    prefix: String,
+    suffix: String,
+}
+
+impl VanityPattern {
+    pub fn new(prefix: Option<&str>, suffix: Option<&str>) -> Result<Self> {
+        let prefix = normalize(prefix.unwrap_or_default(), "--vanity-prefix")?;
+        let suffix = normalize(suffix.unwrap_or_default(), "--vanity-suffix")?;
+        let length = prefix.len() + suffix.len();
+        if length == 0 {
+            bail!("Pass --vanity-prefix or --vanity-suffix");
+        }
+        if length > MAX_VANITY_LENGTH {
+            bail!(
+                "The vanity pattern has {length} hex digits, at most {MAX_VANITY_LENGTH} are supported"
+            );
+        }
+        Ok(Self { prefix, suffix })
+    }
+
+    /// Whether the address, as its 64 hex digits, matches the pattern
+    pub fn matches(&self, address_hex: &str) -> bool {
+        address_hex.starts_with(&self.prefix) && address_hex.ends_with(&self.suffix)
+    }
+
+    /// Attempts needed on average to find a match
+    pub fn expected_attempts(&self) -> f64 {
+        16_f64.powi((self.prefix.len() + self.suffix.len()) as i32)
+    }
+}
+
+fn normalize(pattern: &str, flag: &str) -> Result<String> {
+    let pattern = pattern.trim();
+    let pattern = pattern.strip_prefix("0x").unwrap_or(pattern);
+    if !pattern.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!("Invalid {flag}: {pattern}, only hex digits can be in an address");
+    }
+    Ok(pattern.to_ascii_lowercase())
+}
+
+/// Generate keys on every core until the address of the owner key matches the pattern, printing
+/// the progress to stderr. The keys are generated the same way as without a pattern
+pub fn grind(pattern: &VanityPattern, timeout: Option<Duration>) -> Result<LegacyKeys> {
+    let expected_attempts = pattern.expected_attempts();
+    if pattern.prefix.len() + pattern.suffix.len() >= SLOW_VANITY_LENGTH {
+        eprintln!(
+            "Warning: about {expected_attempts:.0} attempts are expected for this pattern, it may take hours"
+        );
+    }
+
+    let workers = thread::available_parallelism().map_or(1, |workers| workers.get());
+    let attempts = AtomicU64::new(0);
+    let done = AtomicBool::new(false);
+    let started = Instant::now();
+    let (sender, receiver) = mpsc::channel();
+
+    let found = thread::scope(|scope| {
+        for _ in 0..workers {
+            let sender = sender.clone();
+            let (attempts, done) = (&attempts, &done);
+            scope.spawn(move || {
+                while !done.load(Ordering::Relaxed) {
+                    let keys = LegacyKeys::new(&WalletLibrary::new());
+                    attempts.fetch_add(1, Ordering::Relaxed);
+                    let keys = match keys {
+                        Ok(keys) => keys,
+                        Err(err) => {
+                            let _ = sender.send(Err(err));
+                            return;
+                        }
+                    };
+                    let public_key = Ed25519PublicKey::from(&keys.child_0_owner.pri_key);
+                    let address = AuthenticationKey::ed25519(&public_key).derived_address();
+                    if pattern.matches(&address.to_hex()) {
+                        let _ = sender.send(Ok(keys));
+                        return;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        let result = loop {
+            match receiver.recv_timeout(Duration::from_secs(1)) {
+                Ok(result) => break Some(result),
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break None,
+            }
+            let elapsed = started.elapsed();
+            let tried = attempts.load(Ordering::Relaxed);
+            let rate = tried as f64 / elapsed.as_secs_f64();
+            eprint!(
+                "\r{tried} attempts, {rate:.0}/s on {workers} cores, expected {:.0}s in total",
+                expected_attempts / rate.max(1.0)
+            );
+            let _ = std::io::stderr().flush();
+            if timeout.map_or(false, |timeout| elapsed >= timeout) {
+                break None;
+            }
+        };
+        done.store(true, Ordering::Relaxed);
+        result
+    });
+    eprintln!();
+
+    match found {
+        Some(keys) => keys,
+        None => bail!(
+            "No address matched after {}s and {} attempts",
+            started.elapsed().as_secs(),
+            attempts.load(Ordering::Relaxed)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_vanity_patterns() {
+        let pattern = VanityPattern::new(Some("0xCAFE"), Some("01")).unwrap();
+        assert!(pattern.matches(&format!("cafe{}01", "0".repeat(58))));
+        assert!(!pattern.matches(&format!("cafe{}10", "0".repeat(58))));
+        assert_eq!(pattern.expected_attempts(), 16_f64.powi(6));
+
+        assert!(VanityPattern::new(None, None).is_err());
+        assert!(VanityPattern::new(Some("xyz"), None).is_err());
+        assert!(VanityPattern::new(Some("0123456789a"), None).is_err());
+    }
+
+    #[test]
+    fn grind_a_short_prefix() {
+        let pattern = VanityPattern::new(Some("a"), None).unwrap();
+        let keys = grind(&pattern, Some(Duration::from_secs(60))).unwrap();
+        let public_key = Ed25519PublicKey::from(&keys.child_0_owner.pri_key);
+        let address = AuthenticationKey::ed25519(&public_key).derived_address();
+        assert!(address.to_hex().starts_with('a'));
+    }
+}
@@ -0,0 +1,88 @@
+//! Latency of a balance query of many accounts: with a new HTTP client per lookup, as before
+//! the clients shared their connections, with the shared client one lookup at a time, and with
+//! the shared client and the lookups fanned out.
+//!
+//! cargo run --release -p query --example balance_latency -- <FULLNODE_URL> [ADDRESSES_FILE]
+//!
+//! The addresses file has one address per line, 50 lookups of 0x1 are made without it
+
+use anyhow::{Context, Result};
+use libra_config::extension::client_ext::new_client;
+use query::querier::Querier;
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
+use txs::{rest_client::Client, types::account_address::AccountAddress, util::parse_address};
+use url::Url;
+
+const ACCOUNTS: usize = 50;
+const ROUNDS: usize = 3;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let url = Url::from_str(&args.next().context("Pass the url of a fullnode")?)?;
+    let accounts = match args.next() {
+        Some(path) => std::fs::read_to_string(&path)
+            .context(format!("Failed to read {path}"))?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| parse_address(line, "the addresses file"))
+            .collect::<Result<Vec<_>>>()?,
+        None => vec![AccountAddress::ONE; ACCOUNTS],
+    };
+    println!("{} accounts, best of {ROUNDS} rounds", accounts.len());
+
+    let fresh = best_of(|| async {
+        for account in &accounts {
+            let inner = reqwest::Client::builder().build()?;
+            let querier = Querier::new(Client::from((inner, url.clone())));
+            querier.get_balances(&[*account], None, false).await?;
+        }
+        Ok(())
+    })
+    .await?;
+    report("New client per lookup", fresh, fresh);
+
+    let querier = Querier::new(new_client(url.clone())?);
+    let shared = best_of(|| async {
+        for account in &accounts {
+            querier.get_balances(&[*account], None, false).await?;
+        }
+        Ok(())
+    })
+    .await?;
+    report("Shared client, one at a time", shared, fresh);
+
+    let fanned_out = best_of(|| async {
+        querier.get_balances(&accounts, None, false).await?;
+        Ok(())
+    })
+    .await?;
+    report("Shared client, fanned out", fanned_out, fresh);
+    Ok(())
+}
+
+async fn best_of<F, Fut>(run: F) -> Result<Duration>
+where
+    F: Fn() -> Fut,
+    Fut: std::future::Future<Output = Result<()>>,
+{
+    let mut best = Duration::MAX;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        run().await?;
+        best = best.min(start.elapsed());
+    }
+    Ok(best)
+}
+
+fn report(mode: &str, elapsed: Duration, baseline: Duration) {
+    println!(
+        "{mode:<32} {:>8} ms  {:>5.1}x",
+        elapsed.as_millis(),
+        baseline.as_secs_f64() / elapsed.as_secs_f64()
+    );
+}
@@ -0,0 +1,188 @@
+//! CSV files of query results, e.g. for spreadsheets. Each layout has a header row and stable
+//! columns. Amounts are integers in base units, followed by the same amount in coins
+
+use crate::querier::AccountBalance;
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::{io, path::Path};
+use txs::util::{format_address, format_decimal_amount};
+use zapatos_sdk::{
+    rest_client::aptos_api_types::VersionedEvent, types::account_address::AccountAddress,
+};
+
+/// One row per account. The unlocked amount is the whole balance of the accounts which are not
+/// slow wallets. The amounts are empty and the error is set when the balance cannot be read
+pub const BALANCE_COLUMNS: [&str; 6] = [
+    "address",
+    "balance",
+    "balance_coins",
+    "unlocked",
+    "unlocked_coins",
+    "error",
+];
+
+/// One row per transaction. The fee is the gas used at the gas unit price
+pub const TRANSACTION_COLUMNS: [&str; 10] = [
+    "version",
+    "hash",
+    "sender",
+    "function",
+    "success",
+    "vm_status",
+    "gas_used",
+    "gas_unit_price",
+    "fee",
+    "fee_coins",
+];
+
+/// One row per event. The amount is empty for the events without one, the data is their JSON
+pub const EVENT_COLUMNS: [&str; 6] = [
+    "version",
+    "sequence_number",
+    "type",
+    "amount",
+    "amount_coins",
+    "data",
+];
+
+/// Where a CSV document is written
+pub enum Destination<'a> {
+    Stdout,
+    /// An existing file is only overwritten with `force`
+    File {
+        path: &'a Path,
+        force: bool,
+    },
+}
+
+/// Write the header and the rows, quoting the fields which hold commas, quotes or line breaks
+pub fn write_csv(destination: Destination, columns: &[&str], rows: &[Vec<String>]) -> Result<()> {
+    match destination {
+        Destination::Stdout => write_rows(csv::Writer::from_writer(io::stdout()), columns, rows),
+        Destination::File { path, force } => {
+            if path.exists() && !force {
+                bail!(
+                    "{} already exists, pass --force to overwrite it",
+                    path.display()
+                );
+            }
+            let writer = csv::Writer::from_path(path)
+                .context(format!("Failed to create {}", path.display()))?;
+            write_rows(writer, columns, rows).context(format!("Failed to write {}", path.display()))
+        }
+    }
+}
+
+fn write_rows<W: io::Write>(
+    mut writer: csv::Writer<W>,
+    columns: &[&str],
+    rows: &[Vec<String>],
+) -> Result<()> {
+    writer.write_record(columns)?;
+    for row in rows {
+        writer.write_record(row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn balance_row(
+    account: AccountAddress,
+    balance: &Result<AccountBalance>,
+    decimals: u8,
+) -> Vec<String> {
+    let mut row = vec![format_address(&account)];
+    match balance {
+        Ok(balance) => {
+            row.extend(amount_cells(Some(balance.balance), decimals));
+            row.extend(amount_cells(Some(balance.unlocked), decimals));
+            row.push(String::new());
+        }
+        Err(err) => {
+            row.extend(amount_cells(None, decimals));
+            row.extend(amount_cells(None, decimals));
+            row.push(format!("{err:#}"));
+        }
+    }
+    row
+}
+
+/// A row of the transactions listed by the Txs query
+pub fn transaction_row(transaction: &Value, decimals: u8) -> Vec<String> {
+    let mut row = TRANSACTION_COLUMNS[..8]
+        .iter()
+        .map(|column| cell(&transaction[column]))
+        .collect::<Vec<_>>();
+    let fee = transaction["gas_used"]
+        .as_u64()
+        .zip(transaction["gas_unit_price"].as_u64())
+        .map(|(gas_used, gas_unit_price)| gas_used * gas_unit_price);
+    row.extend(amount_cells(fee, decimals));
+    row
+}
+
+pub fn event_row(event: &VersionedEvent, decimals: u8) -> Vec<String> {
+    // Deposits and withdrawals hold their amount as a string
+    let amount = event.data["amount"]
+        .as_str()
+        .and_then(|amount| amount.parse::<u64>().ok());
+    let mut row = vec![
+        u64::from(event.version).to_string(),
+        u64::from(event.sequence_number).to_string(),
+        event.typ.to_string(),
+    ];
+    row.extend(amount_cells(amount, decimals));
+    row.push(event.data.to_string());
+    row
+}
+
+/// The amount in base units and in coins, both empty when there is none
+fn amount_cells(amount: Option<u64>, decimals: u8) -> [String; 2] {
+    match amount {
+        Some(amount) => [amount.to_string(), format_decimal_amount(amount, decimals)],
+        None => [String::new(), String::new()],
+    }
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.to_owned(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn quote_fields_and_keep_existing_files() {
+        let path = std::env::temp_dir().join("query-export-test.csv");
+        let _ = fs::remove_file(&path);
+        let transaction = json!({
+            "version": 7,
+            "hash": "0xab",
+            "function": "0x1::coin::transfer",
+            "success": false,
+            "vm_status": "Move abort in 0x1::coin: EINSUFFICIENT_BALANCE(0x10006), not enough",
+            "gas_used": 12,
+            "gas_unit_price": 100,
+        });
+        let rows = [transaction_row(&transaction, 6)];
+        let file = |force| Destination::File { path: &path, force };
+        write_csv(file(false), &TRANSACTION_COLUMNS, &rows).unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            written.lines().nth(1).unwrap(),
+            r#"7,0xab,,0x1::coin::transfer,false,"Move abort in 0x1::coin: EINSUFFICIENT_BALANCE(0x10006), not enough",12,100,1200,0.0012"#
+        );
+
+        assert!(write_csv(file(false), &TRANSACTION_COLUMNS, &rows).is_err());
+        write_csv(file(true), &BALANCE_COLUMNS, &[]).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 1);
+        fs::remove_file(&path).unwrap();
+    }
+}
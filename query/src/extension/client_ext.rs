@@ -1,19 +1,285 @@
-use anyhow::{Context, Result};
+use crate::querier::{EventSource, COIN_STORE_TYPE};
+use anyhow::{bail, Context, Result};
 use async_trait::async_trait;
-use zapatos_sdk::{rest_client::Client, types::account_address::AccountAddress};
+use libra_config::extension::client_ext::http_client;
+use serde_json::Value;
+use txs::{error::TxsError, retry::retry_read};
+use zapatos_sdk::{
+    rest_client::{
+        aptos_api_types::{MoveModule, Transaction, VersionedEvent},
+        error::RestError,
+        Client,
+    },
+    types::account_address::AccountAddress,
+};
+
+/// Most events the node returns in a single page
+pub(crate) const EVENTS_PAGE_SIZE: u64 = 100;
+/// Most transactions the node returns in a single page
+const TRANSACTIONS_PAGE_SIZE: u64 = 100;
 
 #[async_trait]
 pub trait ClientExt {
-    async fn get_account_resources_ext(&self, account: AccountAddress) -> Result<String>;
+    /// The ABI of every module published by the account, ordered by name
+    async fn get_account_modules_ext(&self, account: AccountAddress) -> Result<Vec<MoveModule>>;
+
+    /// Up to `limit` events of the stream starting at sequence number `start`,
+    /// or the most recent ones when `start` is not set. Pages are fetched as needed
+    async fn get_events_ext(
+        &self,
+        account: AccountAddress,
+        source: &EventSource,
+        start: Option<u64>,
+        limit: u64,
+    ) -> Result<Vec<VersionedEvent>>;
+
+    async fn get_events_page(
+        &self,
+        account: AccountAddress,
+        source: &EventSource,
+        start: Option<u64>,
+        limit: u64,
+    ) -> Result<Vec<VersionedEvent>>;
+
+    /// Up to `limit` committed transactions sent by the account, from `start_version` on
+    async fn get_sent_transactions(
+        &self,
+        account: AccountAddress,
+        start_version: u64,
+        limit: u64,
+    ) -> Result<Vec<Transaction>>;
+
+    /// Up to `limit` committed transactions which deposited coins into the account, from `start_version` on
+    async fn get_received_transactions(
+        &self,
+        account: AccountAddress,
+        start_version: u64,
+        limit: u64,
+    ) -> Result<Vec<Transaction>>;
+
+    /// The item of a table under `key`, which is encoded as the node expects a `key_type`. The
+    /// node decodes the item as `value_type`. Fails with `TxsError::NotFound` when the table has
+    /// no item under the key
+    async fn get_table_item_ext(
+        &self,
+        handle: AccountAddress,
+        key_type: &str,
+        value_type: &str,
+        key: &Value,
+    ) -> Result<Value>;
 }
 
 #[async_trait]
 impl ClientExt for Client {
-    async fn get_account_resources_ext(&self, account: AccountAddress) -> Result<String> {
-        let response = self
-            .get_account_resources(account)
+    async fn get_account_modules_ext(&self, account: AccountAddress) -> Result<Vec<MoveModule>> {
+        let modules = retry_read(|| self.get_account_modules(account))
             .await
-            .context("Failed to get account resources")?;
-        Ok(format!("{:#?}", response.inner()))
+            .context(format!(
+                "Failed to get the modules of {}",
+                account.to_hex_literal()
+            ))?
+            .into_inner();
+        let mut abis = vec![];
+        for module in modules {
+            if let Some(abi) = module
+                .try_parse_abi()
+                .context("Failed to parse the ABI of a module")?
+                .abi
+            {
+                abis.push(abi);
+            }
+        }
+        abis.sort_by(|a, b| a.name.as_str().cmp(b.name.as_str()));
+        Ok(abis)
+    }
+
+    async fn get_events_ext(
+        &self,
+        account: AccountAddress,
+        source: &EventSource,
+        start: Option<u64>,
+        limit: u64,
+    ) -> Result<Vec<VersionedEvent>> {
+        let mut start = match start {
+            Some(start) => start,
+            None => {
+                let latest = self.get_events_page(account, source, None, 1).await?;
+                match latest.first() {
+                    Some(event) => (u64::from(event.sequence_number) + 1).saturating_sub(limit),
+                    None => return Ok(vec![]),
+                }
+            }
+        };
+
+        let mut events = vec![];
+        while (events.len() as u64) < limit {
+            let page_size = (limit - events.len() as u64).min(EVENTS_PAGE_SIZE);
+            let page = self
+                .get_events_page(account, source, Some(start), page_size)
+                .await?;
+            let is_last_page = (page.len() as u64) < page_size;
+            if let Some(event) = page.last() {
+                start = u64::from(event.sequence_number) + 1;
+            }
+            events.extend(page);
+            if is_last_page {
+                break;
+            }
+        }
+        Ok(events)
+    }
+
+    async fn get_sent_transactions(
+        &self,
+        account: AccountAddress,
+        start_version: u64,
+        limit: u64,
+    ) -> Result<Vec<Transaction>> {
+        // The node pages the transactions of an account by sequence number, not by version
+        let mut start = 0;
+        let mut transactions = vec![];
+        loop {
+            let page = self
+                .get_account_transactions(account, Some(start), Some(TRANSACTIONS_PAGE_SIZE))
+                .await
+                .context(format!("Failed to get the transactions of {account}"))?
+                .into_inner();
+            let is_last_page = (page.len() as u64) < TRANSACTIONS_PAGE_SIZE;
+            start += page.len() as u64;
+
+            for transaction in page {
+                if transaction.version().unwrap_or_default() >= start_version {
+                    transactions.push(transaction);
+                }
+                if transactions.len() as u64 >= limit {
+                    return Ok(transactions);
+                }
+            }
+            if is_last_page {
+                return Ok(transactions);
+            }
+        }
+    }
+
+    async fn get_received_transactions(
+        &self,
+        account: AccountAddress,
+        start_version: u64,
+        limit: u64,
+    ) -> Result<Vec<Transaction>> {
+        let deposits = EventSource::Handle {
+            struct_tag: COIN_STORE_TYPE.to_string(),
+            field_name: "deposit_events".to_string(),
+        };
+        let mut start = 0;
+        let mut versions = vec![];
+        'pages: loop {
+            let page = self
+                .get_events_page(account, &deposits, Some(start), EVENTS_PAGE_SIZE)
+                .await?;
+            let is_last_page = (page.len() as u64) < EVENTS_PAGE_SIZE;
+            start += page.len() as u64;
+
+            for event in page {
+                let version = u64::from(event.version);
+                if version >= start_version && versions.last() != Some(&version) {
+                    versions.push(version);
+                }
+                if versions.len() as u64 >= limit {
+                    break 'pages;
+                }
+            }
+            if is_last_page {
+                break;
+            }
+        }
+
+        let mut transactions = vec![];
+        for version in versions {
+            transactions.push(
+                self.get_transaction_by_version(version)
+                    .await
+                    .context(format!(
+                        "Failed to get the transaction at version {version}"
+                    ))?
+                    .into_inner(),
+            );
+        }
+        Ok(transactions)
+    }
+
+    async fn get_table_item_ext(
+        &self,
+        handle: AccountAddress,
+        key_type: &str,
+        value_type: &str,
+        key: &Value,
+    ) -> Result<Value> {
+        let response = retry_read(|| self.get_table_item(handle, key_type, value_type, key)).await;
+        let handle = handle.to_hex_literal();
+        match response {
+            Ok(response) => Ok(response.into_inner()),
+            Err(RestError::Api(err)) if err.status_code.as_u16() == 404 => {
+                Err(TxsError::NotFound(format!(
+                    "Table {handle} has no item with the key {key}, or the table does not exist"
+                ))
+                .into())
+            }
+            // The node could not parse the types, or encode the key or decode the item with them
+            Err(RestError::Api(err)) if err.status_code.is_client_error() => bail!(
+                "The node could not read an item of table {handle} with a {key_type} key and a {value_type} value: {}",
+                err.error.message
+            ),
+            Err(err) => {
+                Err(err).context(format!("Failed to get the item of table {handle} with the key {key}"))
+            }
+        }
+    }
+
+    async fn get_events_page(
+        &self,
+        account: AccountAddress,
+        source: &EventSource,
+        start: Option<u64>,
+        limit: u64,
+    ) -> Result<Vec<VersionedEvent>> {
+        match source {
+            EventSource::Handle {
+                struct_tag,
+                field_name,
+            } => Ok(self
+                .get_account_events(account, struct_tag, field_name, start, Some(limit as u16))
+                .await
+                .context(format!(
+                    "Failed to get the {field_name} events of {struct_tag}"
+                ))?
+                .into_inner()),
+            EventSource::CreationNumber(creation_number) => {
+                // The rest client only looks events up by event handle
+                let mut url = reqwest::Url::parse(&self.path_prefix_string())?.join(&format!(
+                    "accounts/{}/events/{creation_number}",
+                    account.to_hex_literal()
+                ))?;
+                url.query_pairs_mut()
+                    .append_pair("limit", &limit.to_string());
+                if let Some(start) = start {
+                    url.query_pairs_mut()
+                        .append_pair("start", &start.to_string());
+                }
+
+                let context =
+                    format!("Failed to get the events with creation number {creation_number}");
+                http_client()?
+                    .get(url)
+                    .send()
+                    .await
+                    .context(context.clone())?
+                    .error_for_status()
+                    .context(context.clone())?
+                    .json::<Vec<VersionedEvent>>()
+                    .await
+                    .context(context)
+            }
+        }
     }
 }
@@ -1 +1,2 @@
 pub mod client_ext;
+pub mod node_api_ext;
@@ -0,0 +1,324 @@
+use crate::querier::COIN_STORE_TYPE;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::Value;
+use txs::{
+    extension::client_ext::{ledger_version_error, parse_view_request},
+    node_api::NodeApi,
+    retry::retry_read,
+};
+use zapatos_sdk::{rest_client::Resource, types::account_address::AccountAddress};
+
+/// The resource which makes an account a slow wallet
+const SLOW_WALLET_TYPE: &str = "0x1::slow_wallet::SlowWallet";
+
+/// Reads of the account state which only need a `NodeApi`, so that they run against a `MockNode`
+/// in tests
+#[async_trait]
+pub trait NodeApiExt {
+    /// Every resource of the account. The client follows the node's cursor until the last page,
+    /// so accounts with many resources come back complete
+    async fn get_account_resources_ext(
+        &self,
+        account: AccountAddress,
+        version: Option<u64>,
+    ) -> Result<Vec<Resource>>;
+
+    /// Data of a resource of the account, None when the account does not have it
+    async fn get_resource_ext(
+        &self,
+        account: AccountAddress,
+        resource_type: &str,
+    ) -> Result<Option<Value>>;
+
+    /// Balance of the account's native coin store, at a ledger version or the latest one
+    async fn get_account_balance_ext(
+        &self,
+        account: AccountAddress,
+        version: Option<u64>,
+    ) -> Result<u64>;
+
+    /// The unlocked and transferred amounts of a slow wallet, None when the account is not one
+    async fn get_slow_wallet_ext(
+        &self,
+        account: AccountAddress,
+        version: Option<u64>,
+    ) -> Result<Option<SlowWalletBalance>>;
+}
+
+#[async_trait]
+impl<N: NodeApi> NodeApiExt for N {
+    async fn get_account_resources_ext(
+        &self,
+        account: AccountAddress,
+        version: Option<u64>,
+    ) -> Result<Vec<Resource>> {
+        let response = match version {
+            Some(version) => retry_read(|| self.get_account_resources_at_version(account, version))
+                .await
+                .map_err(|err| {
+                    ledger_version_error(
+                        err,
+                        &format!("Failed to get account resources at ledger version {version}"),
+                    )
+                })?,
+            None => retry_read(|| self.get_account_resources(account))
+                .await
+                .context("Failed to get account resources")?,
+        };
+        Ok(response.into_inner())
+    }
+
+    async fn get_resource_ext(
+        &self,
+        account: AccountAddress,
+        resource_type: &str,
+    ) -> Result<Option<Value>> {
+        let resource = retry_read(|| self.get_account_resource(account, resource_type))
+            .await
+            .context(format!(
+                "Failed to get resource {resource_type} of {}",
+                account.to_hex_literal()
+            ))?
+            .into_inner();
+        Ok(resource.map(|r| r.data))
+    }
+
+    async fn get_account_balance_ext(
+        &self,
+        account: AccountAddress,
+        version: Option<u64>,
+    ) -> Result<u64> {
+        let version = match version {
+            Some(version) => version,
+            None => {
+                return Ok(retry_read(|| self.get_account_balance(account))
+                    .await
+                    .context(format!("Failed to get the balance of {account}"))?
+                    .into_inner())
+            }
+        };
+        let coin_store =
+            retry_read(|| self.get_account_resource_at_version(account, COIN_STORE_TYPE, version))
+                .await
+                .map_err(|err| {
+                    ledger_version_error(
+                        err,
+                        &format!(
+                            "Failed to get the balance of {account} at ledger version {version}"
+                        ),
+                    )
+                })?
+                .into_inner()
+                .context(format!(
+                    "{account} has no coin store at ledger version {version}"
+                ))?;
+        coin_store.data["coin"]["value"]
+            .as_str()
+            .and_then(|value| value.parse().ok())
+            .context("Invalid coin store value")
+    }
+
+    async fn get_slow_wallet_ext(
+        &self,
+        account: AccountAddress,
+        version: Option<u64>,
+    ) -> Result<Option<SlowWalletBalance>> {
+        let context = format!("Failed to get the slow wallet of {account}");
+        let slow_wallet = match version {
+            Some(version) => self
+                .get_account_resource_at_version(account, SLOW_WALLET_TYPE, version)
+                .await
+                .map_err(|err| ledger_version_error(err, &context))?,
+            None => self
+                .get_account_resource(account, SLOW_WALLET_TYPE)
+                .await
+                .context(context.to_owned())?,
+        }
+        .into_inner();
+        let slow_wallet = match slow_wallet {
+            Some(slow_wallet) => slow_wallet,
+            None => return Ok(None),
+        };
+
+        // The view function accounts for coins unlocked since the resource was last written
+        let request = parse_view_request(
+            "0x1::slow_wallet::unlocked_amount",
+            None,
+            Some(account.to_hex_literal()),
+        )?;
+        let unlocked = self
+            .view(&request, version)
+            .await
+            .map_err(|err| ledger_version_error(err, &context))?
+            .into_inner();
+        let unlocked = unlocked
+            .first()
+            .and_then(|v| v.as_str())
+            .and_then(|v| v.parse().ok())
+            .context("Invalid unlocked amount returned by the node")?;
+        let transferred = slow_wallet.data["transferred"]
+            .as_str()
+            .and_then(|v| v.parse().ok())
+            .context("Invalid transferred amount in the slow wallet")?;
+
+        Ok(Some(SlowWalletBalance {
+            unlocked,
+            transferred,
+        }))
+    }
+}
+
+/// The part of a slow wallet's balance which can be transferred, and what already was
+#[derive(Debug, Serialize)]
+pub struct SlowWalletBalance {
+    pub unlocked: u64,
+    pub transferred: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use txs::node_api::MockNode;
+
+    const ACCOUNT: &str = "0x2";
+    const SLOW_WALLET: &str = "0x1::slow_wallet::SlowWallet";
+
+    fn node() -> MockNode {
+        MockNode::from_json(r#"{ "chain_id": 4, "ledger_version": 100, "routes": {} }"#).unwrap()
+    }
+
+    fn account() -> AccountAddress {
+        AccountAddress::from_hex_literal(ACCOUNT).unwrap()
+    }
+
+    fn coin_store(value: u64) -> Value {
+        json!({ "type": COIN_STORE_TYPE, "data": { "coin": { "value": value.to_string() } } })
+    }
+
+    #[tokio::test]
+    async fn read_the_balance_at_a_version() {
+        let node = node()
+            .with_route(
+                &format!("GET /accounts/{ACCOUNT}/resource/{COIN_STORE_TYPE}"),
+                200,
+                coin_store(1000),
+            )
+            .with_route(
+                &format!("GET /accounts/{ACCOUNT}/resource/{COIN_STORE_TYPE}?ledger_version=7"),
+                200,
+                coin_store(400),
+            );
+        assert_eq!(
+            node.get_account_balance_ext(account(), None).await.unwrap(),
+            1000
+        );
+        assert_eq!(
+            node.get_account_balance_ext(account(), Some(7))
+                .await
+                .unwrap(),
+            400
+        );
+
+        let err = node
+            .get_account_balance_ext(account(), Some(6))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("at ledger version 6"));
+
+        let node = node.with_route(
+            &format!("GET /accounts/{ACCOUNT}/resource/{COIN_STORE_TYPE}?ledger_version=5"),
+            200,
+            Value::Null,
+        );
+        let err = node
+            .get_account_balance_ext(account(), Some(5))
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("has no coin store at ledger version 5"));
+    }
+
+    #[tokio::test]
+    async fn read_the_unlocked_amount_of_a_slow_wallet() {
+        let node = node().with_route(
+            &format!("GET /accounts/{ACCOUNT}/resource/{SLOW_WALLET}"),
+            200,
+            Value::Null,
+        );
+        assert!(node
+            .get_slow_wallet_ext(account(), None)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(node.view_requests().is_empty());
+
+        let node = node
+            .with_route(
+                &format!("GET /accounts/{ACCOUNT}/resource/{SLOW_WALLET}"),
+                200,
+                json!({ "type": SLOW_WALLET, "data": { "unlocked": "10", "transferred": "30" } }),
+            )
+            .with_route("POST /view", 200, json!(["25"]));
+        let slow_wallet = node
+            .get_slow_wallet_ext(account(), None)
+            .await
+            .unwrap()
+            .unwrap();
+        // The unlocked amount comes from the view function, not from the stale resource
+        assert_eq!(slow_wallet.unlocked, 25);
+        assert_eq!(slow_wallet.transferred, 30);
+        let request = &node.view_requests()[0];
+        assert_eq!(
+            request.function.to_string(),
+            "0x1::slow_wallet::unlocked_amount"
+        );
+        assert_eq!(request.arguments, vec![json!(ACCOUNT)]);
+
+        let node = node.with_route("POST /view", 200, json!([]));
+        let err = node.get_slow_wallet_ext(account(), None).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid unlocked amount returned by the node"
+        );
+    }
+
+    #[tokio::test]
+    async fn list_the_resources_at_a_version() {
+        let node = node()
+            .with_route(
+                &format!("GET /accounts/{ACCOUNT}/resources"),
+                200,
+                json!([coin_store(1000), { "type": SLOW_WALLET, "data": {} }]),
+            )
+            .with_route(
+                &format!("GET /accounts/{ACCOUNT}/resources?ledger_version=7"),
+                200,
+                json!([coin_store(400)]),
+            );
+        let resources = node
+            .get_account_resources_ext(account(), None)
+            .await
+            .unwrap();
+        assert_eq!(resources.len(), 2);
+        assert_eq!(resources[1].resource_type.to_string(), SLOW_WALLET);
+
+        let resources = node
+            .get_account_resources_ext(account(), Some(7))
+            .await
+            .unwrap();
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources[0].data["coin"]["value"], "400");
+
+        let err = node
+            .get_account_resources_ext(account(), Some(6))
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Failed to get account resources at ledger version 6"));
+    }
+}
@@ -0,0 +1,170 @@
+//! Tail an event stream, printing every new event as a JSON line
+
+use crate::{
+    extension::client_ext::{ClientExt, EVENTS_PAGE_SIZE},
+    querier::EventSource,
+};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path, time::Duration};
+use zapatos_sdk::{rest_client::Client, types::account_address::AccountAddress};
+
+/// Where following starts when there is no cursor file to resume from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FollowStart {
+    /// From this sequence number, 0 for the whole history
+    SequenceNumber(u64),
+    /// Only the events emitted from now on
+    Now,
+}
+
+/// Saved after every event, so that a restarted process resumes with the next one
+#[derive(Serialize, Deserialize)]
+struct Cursor {
+    account: String,
+    stream: String,
+    next_sequence_number: u64,
+}
+
+/// Print the events of the stream as they are emitted, until interrupted. A failed request is
+/// reported and retried from the same sequence number, so no event is skipped
+pub async fn follow(
+    client: &Client,
+    account: AccountAddress,
+    source: &EventSource,
+    start: FollowStart,
+    cursor_file: Option<&Path>,
+    interval: Duration,
+) -> Result<()> {
+    let mut next = match read_cursor(cursor_file, account, source)? {
+        Some(next) => next,
+        None => match start {
+            FollowStart::SequenceNumber(start) => start,
+            FollowStart::Now => next_sequence_number(client, account, source, interval).await,
+        },
+    };
+
+    loop {
+        let page = match client
+            .get_events_page(account, source, Some(next), EVENTS_PAGE_SIZE)
+            .await
+        {
+            Ok(page) => page,
+            Err(err) => {
+                eprintln!(
+                    "Warning: {err:#}, retrying from sequence number {next} in {}s",
+                    interval.as_secs()
+                );
+                tokio::time::sleep(interval).await;
+                continue;
+            }
+        };
+        let is_last_page = (page.len() as u64) < EVENTS_PAGE_SIZE;
+
+        for event in page {
+            println!("{}", serde_json::to_string(&event)?);
+            next = u64::from(event.sequence_number) + 1;
+            if let Some(path) = cursor_file {
+                write_cursor(path, account, source, next)?;
+            }
+        }
+        if is_last_page {
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
+
+/// The sequence number the next event will have, retried until the node answers
+async fn next_sequence_number(
+    client: &Client,
+    account: AccountAddress,
+    source: &EventSource,
+    interval: Duration,
+) -> u64 {
+    loop {
+        match client.get_events_page(account, source, None, 1).await {
+            Ok(latest) => {
+                return latest
+                    .first()
+                    .map_or(0, |event| u64::from(event.sequence_number) + 1)
+            }
+            Err(err) => {
+                eprintln!("Warning: {err:#}, retrying in {}s", interval.as_secs());
+                tokio::time::sleep(interval).await;
+            }
+        }
+    }
+}
+
+fn read_cursor(
+    cursor_file: Option<&Path>,
+    account: AccountAddress,
+    source: &EventSource,
+) -> Result<Option<u64>> {
+    let path = match cursor_file {
+        Some(path) if path.exists() => path,
+        _ => return Ok(None),
+    };
+    let cursor: Cursor = serde_json::from_str(
+        &fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?,
+    )
+    .context(format!("{} is not a cursor file", path.display()))?;
+    if cursor.account != account.to_hex_literal() || cursor.stream != source.to_string() {
+        bail!(
+            "{} is the cursor of the {} events of {}, not of this stream",
+            path.display(),
+            cursor.stream,
+            cursor.account
+        );
+    }
+    Ok(Some(cursor.next_sequence_number))
+}
+
+/// Written to a temporary file first, so that an interrupted write leaves the previous cursor
+fn write_cursor(
+    path: &Path,
+    account: AccountAddress,
+    source: &EventSource,
+    next_sequence_number: u64,
+) -> Result<()> {
+    let cursor = Cursor {
+        account: account.to_hex_literal(),
+        stream: source.to_string(),
+        next_sequence_number,
+    };
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, serde_json::to_string(&cursor)?)
+        .context(format!("Failed to write {}", temporary.display()))?;
+    fs::rename(&temporary, path).context(format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resume_from_the_cursor_file() {
+        let path = std::env::temp_dir().join("query-follow-cursor.json");
+        let _ = fs::remove_file(&path);
+        let source = EventSource::CreationNumber(2);
+        assert_eq!(
+            None,
+            read_cursor(Some(&path), AccountAddress::ONE, &source).unwrap()
+        );
+
+        write_cursor(&path, AccountAddress::ONE, &source, 42).unwrap();
+        assert_eq!(
+            Some(42),
+            read_cursor(Some(&path), AccountAddress::ONE, &source).unwrap()
+        );
+        // The cursor of another stream is refused rather than silently skipping events
+        assert!(read_cursor(
+            Some(&path),
+            AccountAddress::ONE,
+            &EventSource::CreationNumber(3)
+        )
+        .is_err());
+        assert!(read_cursor(Some(&path), AccountAddress::ZERO, &source).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,2 +1,5 @@
+pub mod export;
 pub mod extension;
+pub mod follow;
 pub mod querier;
+pub mod watch;
@@ -1,30 +1,82 @@
-use crate::extension::client_ext::ClientExt;
-use anyhow::Result;
+use crate::extension::{client_ext::ClientExt, node_api_ext::NodeApiExt};
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use txs::{
+    abi::format_signature,
+    args::{parse_move_type, parse_typed_arg},
+    client::native_or,
+    error::TxsError,
+    extension::client_ext::ClientExt as _,
+    fan_out::join_all_requests,
+    fungible_asset,
+    move_value::StructLayouts,
+    util::{format_address, format_decimal_amount, parse_function_id},
+};
 use zapatos_sdk::{
-    coin_client::CoinClient, rest_client::Client, types::account_address::AccountAddress,
+    move_types::language_storage::TypeTag,
+    rest_client::{aptos_api_types::MoveFunction, Client},
+    types::account_address::AccountAddress,
 };
 use QueryType::*;
 
+/// The resource read by the Balance query
+pub const COIN_STORE_TYPE: &str = "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>";
+
+/// The 0L supply figures of the native coin, by their key in the CoinInfo document: the view
+/// function and the position of the figure in what it returns. Those the framework does not
+/// have are left out
+const OL_SUPPLY_VIEWS: [(&str, &str, usize); 3] = [
+    ("final_supply", "0x1::libra_coin::get_final_supply", 0),
+    ("lifetime_burned", "0x1::burn::get_lifetime_tracker", 0),
+    ("lifetime_recycled", "0x1::burn::get_lifetime_tracker", 1),
+];
+
 #[derive(Debug)]
 pub enum QueryType {
     /// Account balance
     Balance {
         /// account to query txs of
         account: AccountAddress,
+        /// ledger version to read the balance at, the latest if not set
+        version: Option<u64>,
+        /// also the balance of the primary store of the fungible asset of this metadata
+        fa_metadata: Option<AccountAddress>,
+    },
+    /// Balances of several accounts, with their sum
+    Balances {
+        /// accounts to query the balance of
+        accounts: Vec<AccountAddress>,
+        /// ledger version to read the balances at, the latest if not set
+        version: Option<u64>,
+        /// fail when the balance of an account cannot be read, instead of reporting it in its row
+        strict: bool,
     },
     /// Unlocked Account balance
     UnlockedBalance {
         /// account to query txs of
         account: AccountAddress,
     },
-    /// Epoch and waypoint
-    Epoch,
+    /// Epoch timing and the validator set
+    Epoch {
+        /// only list the validators
+        validators_only: bool,
+    },
     /// Network block height
     BlockHeight,
     /// All account resources
     Resources {
         /// account to query txs of
         account: AccountAddress,
+        /// only list the struct tags of the resources
+        types_only: bool,
+        /// ledger version to read the resources at, the latest if not set
+        version: Option<u64>,
     },
     /// get a move value from account blob
     MoveValue {
@@ -43,27 +95,96 @@ pub enum QueryType {
     Txs {
         /// account to query txs of
         account: AccountAddress,
-        /// get transactions after this height
-        txs_height: Option<u64>,
+        /// get transactions from this version on
+        start_version: u64,
         /// limit how many txs
-        txs_count: Option<u64>,
-        /// filter by type
-        txs_type: Option<String>,
+        limit: u64,
+        /// also get the transactions which deposited coins into the account
+        received: bool,
     },
     /// Get events
     Events {
         /// account to query events
         account: AccountAddress,
-        /// switch for sent or received events.
-        sent_or_received: bool,
-        /// what event sequence number to start querying from, if DB does not have all.
+        /// which event stream of the account
+        source: EventSource,
+        /// what event sequence number to start querying from, the most recent events if not set.
         seq_start: Option<u64>,
+        /// limit how many events
+        limit: u64,
+    },
+    /// Summary of the 0L state of an account
+    AccountInfo {
+        /// account to summarize
+        account: AccountAddress,
     },
     /// get the validator's on-chain configuration, including network discovery addresses
     ValConfig {
         /// the account of the validator
         account: AccountAddress,
     },
+    /// Get an item of a table
+    TableItem {
+        /// handle of the table
+        handle: AccountAddress,
+        /// move type of the keys
+        key_type: String,
+        /// move type of the values
+        value_type: String,
+        /// key of the item, as JSON or bare
+        key: String,
+    },
+    /// Modules published by an account
+    Modules {
+        /// account which published the modules
+        account: AccountAddress,
+        /// only list the names of the modules
+        names_only: bool,
+    },
+    /// Name, symbol, decimals and total supply of a coin
+    CoinInfo {
+        /// struct tag of the coin
+        coin_type: TypeTag,
+    },
+    /// The proof-of-fee auction: the bids of the eligible validators, the seats and the projected
+    /// clearing bid
+    PofBids {
+        /// also whether this account would be seated at its current bid
+        account: Option<AccountAddress>,
+    },
+    /// Signature of a function from the on-chain ABI
+    Function {
+        /// function id as in 0x1::coin::transfer
+        function_id: String,
+    },
+}
+
+/// How an event stream of an account is identified
+#[derive(Debug)]
+pub enum EventSource {
+    /// An event handle field of a resource, e.g. `deposit_events` of `0x1::coin::CoinStore<...>`
+    Handle {
+        /// struct tag of the resource holding the handle
+        struct_tag: String,
+        /// name of the handle field
+        field_name: String,
+    },
+    /// The creation number of the event stream
+    CreationNumber(u64),
+}
+
+impl fmt::Display for EventSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Handle {
+                struct_tag,
+                field_name,
+            } => write!(f, "{struct_tag}/{field_name}"),
+            Self::CreationNumber(creation_number) => {
+                write!(f, "creation number {creation_number}")
+            }
+        }
+    }
 }
 
 pub struct Querier {
@@ -77,11 +198,324 @@ impl Querier {
 
     pub async fn query(&self, query_type: QueryType) -> Result<String> {
         let print = match query_type {
-            Balance { account } => {
-                let coin_client = CoinClient::new(&self.client);
-                coin_client.get_account_balance(&account).await?.to_string()
+            Balance {
+                account,
+                version,
+                fa_metadata,
+            } => {
+                let balance = self
+                    .client
+                    .get_account_balance_ext(account, version)
+                    .await?;
+                let mut lines = vec![match version {
+                    Some(version) => {
+                        format!("Account balance: {balance} coins at ledger version {version}")
+                    }
+                    None => format!("Account balance: {balance} coins"),
+                }];
+                if let Some(slow_wallet) = self.client.get_slow_wallet_ext(account, version).await?
+                {
+                    lines.push(format!(
+                        "Slow wallet, unlocked: {} coins",
+                        slow_wallet.unlocked
+                    ));
+                    lines.push(format!(
+                        "Slow wallet, transferred: {} coins",
+                        slow_wallet.transferred
+                    ));
+                }
+                if let Some(metadata) = fa_metadata {
+                    let asset = self.get_fungible_asset(account, metadata, version).await?;
+                    lines.push(format!(
+                        "Fungible asset {} ({}): {} ({} base units)",
+                        asset.symbol,
+                        format_address(&metadata),
+                        format_decimal_amount(asset.balance, asset.decimals),
+                        asset.balance
+                    ));
+                }
+                lines.join("\n")
+            }
+            Balances {
+                accounts,
+                version,
+                strict,
+            } => {
+                let balances = self.get_balances(&accounts, version, strict).await?;
+                let mut lines = vec![format!(
+                    "{:<66} {:>20} {:>20}",
+                    "ADDRESS", "BALANCE", "UNLOCKED"
+                )];
+                for (account, balance) in accounts.iter().zip(&balances) {
+                    lines.push(match balance {
+                        Ok(balance) => format!(
+                            "{:<66} {:>20} {:>20}",
+                            format_address(account),
+                            balance.balance,
+                            balance.unlocked
+                        ),
+                        Err(err) => format!("{:<66} error: {err:#}", format_address(account)),
+                    });
+                }
+                let (balance, unlocked) = total(&balances);
+                let found = balances.iter().filter(|b| b.is_ok()).count();
+                lines.push(format!(
+                    "{:<66} {balance:>20} {unlocked:>20}",
+                    format!("TOTAL ({found} of {} accounts)", accounts.len())
+                ));
+                lines.join("\n")
+            }
+            Resources {
+                account,
+                types_only,
+                version,
+            } => {
+                let resources = self
+                    .client
+                    .get_account_resources_ext(account, version)
+                    .await?;
+                let mut lines = vec![];
+                for resource in resources {
+                    lines.push(resource.resource_type.to_string());
+                    if !types_only {
+                        lines.push(serde_json::to_string_pretty(&resource.data)?);
+                    }
+                }
+                lines.join("\n")
+            }
+            Txs {
+                account,
+                start_version,
+                limit,
+                received,
+            } => {
+                let transactions = self
+                    .get_transactions(account, start_version, limit, received)
+                    .await?;
+                let mut lines = vec![format!(
+                    "{:<12} {:<66} {:<40} {:<10} {}",
+                    "VERSION", "HASH", "FUNCTION", "GAS USED", "VM STATUS"
+                )];
+                for t in &transactions {
+                    lines.push(format!(
+                        "{:<12} {:<66} {:<40} {:<10} {}",
+                        t["version"],
+                        t["hash"].as_str().unwrap_or_default(),
+                        t["function"].as_str().unwrap_or_default(),
+                        t["gas_used"],
+                        t["vm_status"].as_str().unwrap_or_default()
+                    ));
+                }
+                if let Some(next) = next_start_version(&transactions, limit) {
+                    lines.push(format!("More transactions from --start-version {next}"));
+                }
+                lines.join("\n")
+            }
+            AccountInfo { account } => {
+                let info = self.get_account_info(account).await?;
+                let unknown = |value: &Value| match value {
+                    Value::Null => "unknown".to_string(),
+                    Value::String(s) => s.to_owned(),
+                    value => value.to_string(),
+                };
+                let mut lines = vec![
+                    format!("Account: {}", format_address(&account)),
+                    format!("Balance: {} coins", unknown(&info["balance"])),
+                ];
+                if let Some(slow_wallet) = info["slow_wallet"].as_object() {
+                    lines.push(format!(
+                        "Slow wallet, unlocked: {} coins",
+                        slow_wallet["unlocked"]
+                    ));
+                    lines.push(format!(
+                        "Slow wallet, transferred: {} coins",
+                        slow_wallet["transferred"]
+                    ));
+                }
+                for (name, key) in [
+                    ("Sequence number", "sequence_number"),
+                    ("Validator in the active set", "is_validator"),
+                    ("Validator in the universe", "in_validator_universe"),
+                    ("Jailed", "is_jailed"),
+                    ("Community wallet", "is_community_wallet"),
+                ] {
+                    lines.push(format!("{name}: {}", unknown(&info[key])));
+                }
+                for (name, key) in [
+                    ("Vouches received", "vouches_received"),
+                    ("Vouches given", "vouches_given"),
+                ] {
+                    match info[key].as_array() {
+                        Some(vouches) => lines.push(format!(
+                            "{name}: {}",
+                            vouches.iter().map(unknown).collect::<Vec<_>>().join(", ")
+                        )),
+                        None => lines.push(format!("{name}: unknown")),
+                    }
+                }
+                lines.join("\n")
+            }
+            Epoch { validators_only } => {
+                let info = self.get_epoch_info().await?;
+                let mut lines = vec![];
+                if !validators_only {
+                    lines.push(format!("Epoch: {}", info["epoch"]));
+                    lines.push(format!(
+                        "Epoch started: {}",
+                        info["epoch_start"].as_str().unwrap_or_default()
+                    ));
+                    lines.push(format!("Epoch interval: {}s", info["epoch_interval_secs"]));
+                    lines.push(format!(
+                        "Next epoch boundary (estimated): {} ({}s from now)",
+                        info["estimated_boundary"].as_str().unwrap_or_default(),
+                        info["secs_to_boundary"]
+                    ));
+                    if !info["seats_offered"].is_null() {
+                        lines.push(format!("Seats offered: {}", info["seats_offered"]));
+                    }
+                    lines.push(String::new());
+                }
+                lines.push(format!("{:<66} {}", "VALIDATOR", "VOTING POWER"));
+                for validator in info["validators"].as_array().into_iter().flatten() {
+                    lines.push(format!(
+                        "{:<66} {}",
+                        validator["address"].as_str().unwrap_or_default(),
+                        validator["voting_power"]
+                    ));
+                }
+                lines.join("\n")
+            }
+            Events {
+                account,
+                source,
+                seq_start,
+                limit,
+            } => {
+                let events = self
+                    .client
+                    .get_events_ext(account, &source, seq_start, limit)
+                    .await?;
+                events
+                    .iter()
+                    .map(|e| format!("{} {} {}", u64::from(e.sequence_number), e.typ, e.data))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+            TableItem {
+                handle,
+                key_type,
+                value_type,
+                key,
+            } => {
+                match self
+                    .get_table_item(handle, &key_type, &value_type, &key)
+                    .await?
+                {
+                    Value::String(s) => s,
+                    value => serde_json::to_string_pretty(&value)?,
+                }
+            }
+            Modules {
+                account,
+                names_only,
+            } => {
+                let modules = self.client.get_account_modules_ext(account).await?;
+                let mut lines = vec![];
+                for module in modules {
+                    let module_id = format!("{}::{}", format_address(&account), module.name);
+                    lines.push(module_id);
+                    if names_only {
+                        continue;
+                    }
+                    for function in &module.exposed_functions {
+                        lines.push(format!(
+                            "  {:<8}{}",
+                            function_kind(function),
+                            format_function(&function.name.to_string(), function)
+                        ));
+                    }
+                }
+                lines.join("\n")
+            }
+            CoinInfo { coin_type } => {
+                let info = self.get_coin_info(&coin_type).await?;
+                let text = |value: &Value| value.as_str().unwrap_or("unknown").to_string();
+                let amount = |key: &str| match info[key].as_str() {
+                    Some(base_units) => format!(
+                        "{} coins ({base_units} base units)",
+                        text(&info[format!("{key}_coins").as_str()])
+                    ),
+                    None => "not tracked".to_string(),
+                };
+                let mut lines = vec![
+                    format!("Coin type: {coin_type}"),
+                    format!("Name: {}", text(&info["name"])),
+                    format!("Symbol: {}", text(&info["symbol"])),
+                    format!("Decimals: {}", info["decimals"]),
+                    format!("Total supply: {}", amount("supply")),
+                ];
+                for (key, _, _) in OL_SUPPLY_VIEWS {
+                    if info[key].is_string() {
+                        let name = key.replace('_', " ");
+                        let name = name[..1].to_uppercase() + &name[1..];
+                        lines.push(format!("{name}: {}", amount(key)));
+                    }
+                }
+                lines.join("\n")
+            }
+            PofBids { account } => format_pof_bids(&self.get_pof_bids(account).await?),
+            Function { function_id } => {
+                let function = self.get_function(&function_id).await?;
+                let mut lines = vec![
+                    format_function(&function_id, &function),
+                    match function_kind(&function) {
+                        "entry" => {
+                            "Entry function, call it with txs generate-transaction".to_string()
+                        }
+                        "view" => "View function, call it with txs view".to_string(),
+                        _ => "Neither an entry nor a view function, only Move code can call it"
+                            .to_string(),
+                    },
+                ];
+                let info = describe_function(&function_id, &function);
+                lines.push(format!(
+                    "Visibility: {}",
+                    info["visibility"].as_str().unwrap_or_default()
+                ));
+                for param in info["generic_type_params"].as_array().into_iter().flatten() {
+                    let constraints = param["constraints"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(Value::as_str)
+                        .collect::<Vec<_>>();
+                    let name = param["name"].as_str().unwrap_or_default();
+                    if constraints.is_empty() {
+                        lines.push(format!("Type parameter {name}"));
+                    } else {
+                        lines.push(format!(
+                            "Type parameter {name}: {}",
+                            constraints.join(" + ")
+                        ));
+                    }
+                }
+                for param in info["params"].as_array().into_iter().flatten() {
+                    let signer = if param["signer"] == json!(true) {
+                        ", a signer of the transaction"
+                    } else {
+                        ""
+                    };
+                    lines.push(format!(
+                        "Argument {}: {}{signer}",
+                        param["position"],
+                        param["type"].as_str().unwrap_or_default()
+                    ));
+                }
+                for ty in info["return"].as_array().into_iter().flatten() {
+                    lines.push(format!("Returns: {}", ty.as_str().unwrap_or_default()));
+                }
+                lines.join("\n")
             }
-            Resources { account } => self.client.get_account_resources_ext(account).await?,
             _ => {
                 //TODO: Implement other types of Query
                 String::new()
@@ -89,4 +523,783 @@ impl Querier {
         };
         Ok(print)
     }
+
+    /// Same as `query`, but returns a machine readable document for the json and yaml output formats
+    pub async fn query_json(&self, query_type: QueryType) -> Result<Value> {
+        let value = match query_type {
+            Balance {
+                account,
+                version,
+                fa_metadata,
+            } => {
+                let mut balance = json!({
+                    "account": format_address(&account),
+                    "resource_type": COIN_STORE_TYPE,
+                    "ledger_version": version,
+                    "balance": self.client.get_account_balance_ext(account, version).await?,
+                    "slow_wallet": self.client.get_slow_wallet_ext(account, version).await?,
+                });
+                if let Some(metadata) = fa_metadata {
+                    balance["fungible_asset"] = serde_json::to_value(
+                        self.get_fungible_asset(account, metadata, version).await?,
+                    )?;
+                }
+                balance
+            }
+            Balances {
+                accounts,
+                version,
+                strict,
+            } => {
+                let balances = self.get_balances(&accounts, version, strict).await?;
+                balances_json(&accounts, &balances, version)
+            }
+            Resources {
+                account,
+                types_only,
+                version,
+            } => {
+                let resources = self
+                    .client
+                    .get_account_resources_ext(account, version)
+                    .await?;
+                let resources = resources.into_iter().map(|r| {
+                    if types_only {
+                        json!(r.resource_type.to_string())
+                    } else {
+                        json!({ "type": r.resource_type.to_string(), "data": r.data })
+                    }
+                });
+                Value::Array(resources.collect())
+            }
+            Events {
+                account,
+                source,
+                seq_start,
+                limit,
+            } => {
+                let events = self
+                    .client
+                    .get_events_ext(account, &source, seq_start, limit)
+                    .await?;
+                serde_json::to_value(events)?
+            }
+            AccountInfo { account } => self.get_account_info(account).await?,
+            Epoch { validators_only } => {
+                let info = self.get_epoch_info().await?;
+                if validators_only {
+                    info["validators"].to_owned()
+                } else {
+                    info
+                }
+            }
+            Txs {
+                account,
+                start_version,
+                limit,
+                received,
+            } => {
+                let transactions = self
+                    .get_transactions(account, start_version, limit, received)
+                    .await?;
+                json!({
+                    "next_start_version": next_start_version(&transactions, limit),
+                    "transactions": transactions,
+                })
+            }
+            TableItem {
+                handle,
+                key_type,
+                value_type,
+                key,
+            } => {
+                self.get_table_item(handle, &key_type, &value_type, &key)
+                    .await?
+            }
+            Modules {
+                account,
+                names_only,
+            } => {
+                let modules = self.client.get_account_modules_ext(account).await?;
+                let modules = modules.iter().map(|module| {
+                    let module_id = format!("{}::{}", format_address(&account), module.name);
+                    if names_only {
+                        return json!(module_id);
+                    }
+                    let functions = module
+                        .exposed_functions
+                        .iter()
+                        .map(|function| {
+                            describe_function(&format!("{module_id}::{}", function.name), function)
+                        })
+                        .collect::<Vec<_>>();
+                    json!({ "module": module_id, "functions": functions })
+                });
+                Value::Array(modules.collect())
+            }
+            Function { function_id } => {
+                let function = self.get_function(&function_id).await?;
+                describe_function(&function_id, &function)
+            }
+            CoinInfo { coin_type } => self.get_coin_info(&coin_type).await?,
+            PofBids { account } => self.get_pof_bids(account).await?,
+            _ => {
+                //TODO: Implement other types of Query
+                Value::Null
+            }
+        };
+        Ok(value)
+    }
+
+    /// The balance of every account, looked up concurrently. A failed lookup only fails the row
+    /// of its account, unless `strict` is set
+    pub async fn get_balances(
+        &self,
+        accounts: &[AccountAddress],
+        version: Option<u64>,
+        strict: bool,
+    ) -> Result<Vec<Result<AccountBalance>>> {
+        let balances = join_all_requests(
+            accounts
+                .iter()
+                .map(|account| self.get_balance(*account, version)),
+        )
+        .await;
+        if !strict {
+            return Ok(balances);
+        }
+        let balances = accounts
+            .iter()
+            .zip(balances)
+            .map(|(account, balance)| {
+                balance.context(format!(
+                    "Failed to get the balance of {}",
+                    format_address(account)
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(balances.into_iter().map(Ok).collect())
+    }
+
+    async fn get_balance(
+        &self,
+        account: AccountAddress,
+        version: Option<u64>,
+    ) -> Result<AccountBalance> {
+        let balance = self
+            .client
+            .get_account_balance_ext(account, version)
+            .await?;
+        let slow_wallet = self.client.get_slow_wallet_ext(account, version).await?;
+        Ok(AccountBalance {
+            balance,
+            unlocked: slow_wallet.as_ref().map_or(balance, |s| s.unlocked),
+            slow_wallet: slow_wallet.is_some(),
+        })
+    }
+
+    async fn get_fungible_asset(
+        &self,
+        account: AccountAddress,
+        metadata: AccountAddress,
+        version: Option<u64>,
+    ) -> Result<FungibleAssetBalance> {
+        let balance = fungible_asset::balance(&self.client, account, metadata, version).await?;
+        let decimals = fungible_asset::decimals(&self.client, metadata).await?;
+        Ok(FungibleAssetBalance {
+            metadata: metadata.to_hex_literal(),
+            symbol: fungible_asset::symbol(&self.client, metadata).await?,
+            decimals,
+            balance,
+            balance_coins: format_decimal_amount(balance, decimals),
+        })
+    }
+
+    /// A function of the ABI of its module, listing the others when it has no such function
+    async fn get_function(&self, function_id: &str) -> Result<MoveFunction> {
+        let (address, module_name, function_name) = parse_function_id(function_id)?;
+        let module = self
+            .client
+            .get_module_abi(address, module_name.as_str())
+            .await?;
+        let names = module
+            .exposed_functions
+            .iter()
+            .map(|function| function.name.to_string())
+            .collect::<Vec<_>>();
+        match module
+            .exposed_functions
+            .into_iter()
+            .find(|function| function.name.as_str() == function_name.as_str())
+        {
+            Some(function) => Ok(function),
+            None => Err(TxsError::NotFound(format!(
+                "Module {}::{module_name} has no function {function_name}, its functions are: {}",
+                format_address(&address),
+                names.join(", ")
+            ))
+            .into()),
+        }
+    }
+
+    /// The item under the key, which is checked against the key type before asking the node.
+    /// The item is decoded by the value type as view function results are
+    async fn get_table_item(
+        &self,
+        handle: AccountAddress,
+        key_type: &str,
+        value_type: &str,
+        key: &str,
+    ) -> Result<Value> {
+        let key = parse_typed_arg(key_type, key).context("Invalid --key")?;
+        let item_type = parse_move_type(value_type).context("Invalid --value-type")?;
+        let item = self
+            .client
+            .get_table_item_ext(handle, key_type, value_type, &key.to_view_json()?)
+            .await?;
+        let layouts = StructLayouts::load(&self.client, &[item_type.clone()]).await?;
+        Ok(layouts.decode(&item_type, &item))
+    }
+
+    /// Balance, sequence number and 0L state of the account. A section the node cannot tell,
+    /// e.g. because the resource does not exist, is null instead of failing the query. The
+    /// sections are independent, so they are all requested at once
+    async fn get_account_info(&self, account: AccountAddress) -> Result<Value> {
+        let address = format_address(&account);
+        let (
+            account_resource,
+            balance,
+            slow_wallet,
+            validator_set,
+            eligible_validators,
+            vouches_received,
+            vouches_given,
+            is_jailed,
+            is_community_wallet,
+        ) = tokio::join!(
+            self.client
+                .get_resource_ext(account, "0x1::account::Account"),
+            self.client.get_account_balance_ext(account, None),
+            self.client.get_slow_wallet_ext(account, None),
+            self.client
+                .get_resource_ext(AccountAddress::ONE, "0x1::stake::ValidatorSet"),
+            self.view_first("0x1::validator_universe::get_eligible_validators", None),
+            self.view_first("0x1::vouch::get_received_vouches", Some(&address)),
+            self.view_first("0x1::vouch::get_given_vouches", Some(&address)),
+            self.view_first("0x1::jail::is_jailed", Some(&address)),
+            self.view_first("0x1::donor_voice::is_donor_voice", Some(&address)),
+        );
+
+        let sequence_number = account_resource?
+            .context(format!("Account {address} does not exist"))?["sequence_number"]
+            .as_str()
+            .and_then(|v| v.parse::<u64>().ok());
+        let balance = balance.ok();
+        let slow_wallet = slow_wallet.ok().flatten();
+        let is_validator = validator_set.ok().flatten().map(|set| {
+            set["active_validators"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .any(|validator| is_same_address(&validator["addr"], account))
+        });
+        let in_validator_universe = eligible_validators.map(|validators| {
+            validators
+                .as_array()
+                .into_iter()
+                .flatten()
+                .any(|validator| is_same_address(validator, account))
+        });
+
+        Ok(json!({
+            "account": address,
+            "sequence_number": sequence_number,
+            "balance": balance,
+            "slow_wallet": slow_wallet,
+            "is_validator": is_validator,
+            "in_validator_universe": in_validator_universe,
+            "vouches_received": vouches_received,
+            "vouches_given": vouches_given,
+            "is_jailed": is_jailed,
+            "is_community_wallet": is_community_wallet,
+        }))
+    }
+
+    /// Name, symbol and decimals of a coin from its CoinInfo, with its total supply in base units
+    /// and in coins, None when the supply is not tracked. The native coin also has the 0L supply
+    /// figures of `OL_SUPPLY_VIEWS` the framework has. Amounts are strings, as the node gives them
+    async fn get_coin_info(&self, coin_type: &TypeTag) -> Result<Value> {
+        let coin_address = match coin_type {
+            TypeTag::Struct(struct_tag) => struct_tag.address,
+            _ => bail!("Invalid coin type: {coin_type}"),
+        };
+        let coin_info = self
+            .client
+            .get_resource_ext(coin_address, &format!("0x1::coin::CoinInfo<{coin_type}>"))
+            .await?
+            .context(format!("{coin_type} is not a coin, it has no CoinInfo"))?;
+        let decimals = coin_info["decimals"]
+            .as_u64()
+            .and_then(|decimals| u8::try_from(decimals).ok())
+            .context(format!("No decimals in the coin info of {coin_type}"))?;
+        let coins = |base_units: &Value| {
+            base_units
+                .as_str()
+                .and_then(|base_units| base_units.parse().ok())
+                .map(|base_units| format_decimal_amount(base_units, decimals))
+        };
+
+        // The supply is an aggregator on chains which track it in parallel, only the view
+        // function reads it
+        let supply = self
+            .client
+            .view_ext("0x1::coin::supply", Some(coin_type.to_string()), None)
+            .await
+            .ok()
+            .and_then(|values| values.into_iter().next())
+            .unwrap_or_else(|| coin_info["supply"].clone());
+        let supply = match &supply["vec"][0] {
+            Value::String(supply) => json!(supply),
+            // The integer kept in the resource when the view function fails
+            optional_aggregator => optional_aggregator["integer"]["vec"][0]["value"].clone(),
+        };
+        let mut info = json!({
+            "coin_type": coin_type.to_string(),
+            "name": coin_info["name"],
+            "symbol": coin_info["symbol"],
+            "decimals": decimals,
+            "supply": supply,
+            "supply_coins": coins(&supply),
+        });
+
+        if *coin_type == native_or(None)? {
+            for (key, function_id, position) in OL_SUPPLY_VIEWS {
+                let value = self
+                    .client
+                    .view_ext(function_id, None, None)
+                    .await
+                    .ok()
+                    .and_then(|values| values.into_iter().nth(position));
+                if let Some(value) = value {
+                    info[format!("{key}_coins")] = json!(coins(&value));
+                    info[key] = value;
+                }
+            }
+        }
+        Ok(info)
+    }
+
+    /// The first value returned by a view function, None when the call fails
+    async fn view_first(&self, function_id: &str, args: Option<&str>) -> Option<Value> {
+        self.client
+            .view_ext(function_id, None, args.map(str::to_string))
+            .await
+            .ok()?
+            .into_iter()
+            .next()
+    }
+
+    /// Epoch number and timing, from the reconfiguration and block resources of the framework,
+    /// with the active validators and on 0L the number of seats offered by musical chairs
+    async fn get_epoch_info(&self) -> Result<Value> {
+        let configuration = self
+            .client
+            .get_resource_ext(AccountAddress::ONE, "0x1::reconfiguration::Configuration")
+            .await?
+            .context("The reconfiguration state is missing")?;
+        let block = self
+            .client
+            .get_resource_ext(AccountAddress::ONE, "0x1::block::BlockResource")
+            .await?
+            .context("The block state is missing")?;
+        let validator_set = self
+            .client
+            .get_resource_ext(AccountAddress::ONE, "0x1::stake::ValidatorSet")
+            .await?
+            .context("The validator set is missing")?;
+        // Only 0L has musical chairs
+        let chairs = self
+            .client
+            .get_resource_ext(AccountAddress::ONE, "0x1::musical_chairs::Chairs")
+            .await
+            .ok()
+            .flatten();
+
+        let as_u64 = |value: &Value| value.as_str().and_then(|v| v.parse::<u64>().ok());
+        let epoch_start_secs = as_u64(&configuration["last_reconfiguration_time"])
+            .context("Invalid last reconfiguration time")?
+            / 1_000_000;
+        let epoch_interval_secs =
+            as_u64(&block["epoch_interval"]).context("Invalid epoch interval")? / 1_000_000;
+        let boundary_secs = epoch_start_secs + epoch_interval_secs;
+        let now_secs = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+
+        let validators = validator_set["active_validators"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|validator| {
+                json!({
+                    "address": validator["addr"],
+                    "voting_power": as_u64(&validator["voting_power"]),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(json!({
+            "epoch": as_u64(&configuration["epoch"]),
+            "epoch_start": format_timestamp(epoch_start_secs),
+            "epoch_interval_secs": epoch_interval_secs,
+            "estimated_boundary": format_timestamp(boundary_secs),
+            "secs_to_boundary": boundary_secs.saturating_sub(now_secs),
+            "seats_offered": chairs.and_then(|chairs| as_u64(&chairs["seats_offered"])),
+            "validators": validators,
+        }))
+    }
+
+    /// The bids of the eligible validators from their auction resources, the unexpired ones by
+    /// descending bid and then the expired ones. The clearing bid is projected with the seats of
+    /// the current epoch and without the other checks of the auction, such as jail and vouches
+    async fn get_pof_bids(&self, account: Option<AccountAddress>) -> Result<Value> {
+        let epoch_info = self.get_epoch_info().await?;
+        let epoch = epoch_info["epoch"]
+            .as_u64()
+            .context("Invalid epoch returned by the node")?;
+        let seats = epoch_info["seats_offered"].as_u64();
+        let mut candidates = self
+            .view_first("0x1::validator_universe::get_eligible_validators", None)
+            .await
+            .context("Failed to get the eligible validators, is this a 0L network?")?
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|address| AccountAddress::from_hex_literal(address.as_str()?).ok())
+            .collect::<Vec<_>>();
+        if let Some(account) = account {
+            if !candidates.contains(&account) {
+                candidates.push(account);
+            }
+        }
+
+        let auctions = join_all_requests(candidates.iter().map(|candidate| {
+            self.client
+                .get_resource_ext(*candidate, "0x1::proof_of_fee::ProofOfFeeAuction")
+        }))
+        .await;
+        let as_u64 = |value: &Value| value.as_str().and_then(|v| v.parse::<u64>().ok());
+        let mut bids = vec![];
+        for (candidate, auction) in candidates.into_iter().zip(auctions) {
+            // A validator which never bid has no auction resource
+            let auction = match auction? {
+                Some(auction) => auction,
+                None => continue,
+            };
+            let bid = as_u64(&auction["bid"]).context("Invalid bid in the auction resource")?;
+            let expiration_epoch = as_u64(&auction["epoch_expiration"])
+                .context("Invalid expiration epoch in the auction resource")?;
+            // As 0x1::proof_of_fee::current_bid, a bid still counts in its expiration epoch and
+            // one expiring at epoch 0 never expires
+            let expired = expiration_epoch != 0 && expiration_epoch < epoch;
+            bids.push((candidate, bid, expiration_epoch, expired));
+        }
+        bids.sort_by_key(|(_, bid, _, expired)| (*expired, std::cmp::Reverse(*bid)));
+
+        let active = bids.iter().filter(|(_, _, _, expired)| !expired).count() as u64;
+        let clearing_bid = seats.and_then(|seats| {
+            let last_seated = active.min(seats).checked_sub(1)?;
+            bids.get(last_seated as usize).map(|(_, bid, _, _)| *bid)
+        });
+        let rows = bids
+            .iter()
+            .enumerate()
+            .map(|(index, (candidate, bid, expiration_epoch, expired))| {
+                let rank = (!expired).then_some(index as u64 + 1);
+                json!({
+                    "account": format_address(candidate),
+                    "bid": bid,
+                    "expiration_epoch": expiration_epoch,
+                    "expired": expired,
+                    "rank": rank,
+                    "seated": seats.map(|seats| rank.map_or(false, |rank| rank <= seats)),
+                })
+            })
+            .collect::<Vec<_>>();
+        let account = account.map(|account| {
+            let address = format_address(&account);
+            rows.iter()
+                .find(|row| row["account"] == json!(address))
+                .cloned()
+                .unwrap_or_else(|| json!({ "account": address, "bid": null, "seated": false }))
+        });
+
+        Ok(json!({
+            "epoch": epoch,
+            "estimated_boundary": epoch_info["estimated_boundary"],
+            "seats_offered": seats,
+            "projected_clearing_bid": clearing_bid,
+            "bids": rows,
+            "account": account,
+        }))
+    }
+
+    /// One row per transaction, ordered by version
+    async fn get_transactions(
+        &self,
+        account: AccountAddress,
+        start_version: u64,
+        limit: u64,
+        received: bool,
+    ) -> Result<Vec<Value>> {
+        let mut transactions = self
+            .client
+            .get_sent_transactions(account, start_version, limit)
+            .await?;
+        if received {
+            transactions.extend(
+                self.client
+                    .get_received_transactions(account, start_version, limit)
+                    .await?,
+            );
+            transactions.sort_by_key(|t| t.version());
+            transactions.dedup_by_key(|t| t.version());
+            transactions.truncate(limit as usize);
+        }
+
+        let mut rows = vec![];
+        for transaction in transactions {
+            // Only user transactions have a sender and a payload, all of them serialize the same way
+            let transaction = serde_json::to_value(&transaction)?;
+            let function = match transaction["payload"]["function"].as_str() {
+                Some(function) => function.to_string(),
+                None => transaction["payload"]["type"]
+                    .as_str()
+                    .or(transaction["type"].as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+            };
+            rows.push(json!({
+                "version": transaction["version"].as_str().and_then(|v| v.parse::<u64>().ok()),
+                "hash": transaction["hash"],
+                "sender": transaction["sender"],
+                "function": function,
+                "success": transaction["success"],
+                "vm_status": transaction["vm_status"],
+                "gas_used": transaction["gas_used"].as_str().and_then(|g| g.parse::<u64>().ok()),
+                "gas_unit_price": transaction["gas_unit_price"].as_str().and_then(|g| g.parse::<u64>().ok()),
+            }));
+        }
+        Ok(rows)
+    }
+}
+
+/// The balance of an account and the part of it which can be transferred, all of it unless the
+/// account is a slow wallet
+#[derive(Debug)]
+pub struct AccountBalance {
+    pub balance: u64,
+    pub unlocked: u64,
+    pub slow_wallet: bool,
+}
+
+/// The balance of the primary store of an account, in base units, then with the decimals of
+/// the asset
+#[derive(Debug, Serialize)]
+pub struct FungibleAssetBalance {
+    pub metadata: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub balance: u64,
+    pub balance_coins: String,
+}
+
+/// The Balances query as a document, from the balances of `get_balances`
+pub fn balances_json(
+    accounts: &[AccountAddress],
+    balances: &[Result<AccountBalance>],
+    version: Option<u64>,
+) -> Value {
+    let rows = accounts.iter().zip(balances).map(|(account, balance)| {
+        let address = format_address(account);
+        match balance {
+            Ok(balance) => json!({
+                "account": address,
+                "balance": balance.balance,
+                "unlocked": balance.unlocked,
+                "slow_wallet": balance.slow_wallet,
+            }),
+            Err(err) => json!({ "account": address, "error": format!("{err:#}") }),
+        }
+    });
+    let (balance, unlocked) = total(balances);
+    json!({
+        "ledger_version": version,
+        "accounts": rows.collect::<Vec<_>>(),
+        "total": { "balance": balance, "unlocked": unlocked },
+    })
+}
+
+/// The sums of the balances and of the unlocked amounts which could be read
+fn total(balances: &[Result<AccountBalance>]) -> (u64, u64) {
+    balances
+        .iter()
+        .flatten()
+        .fold((0, 0), |(balance, unlocked), b| {
+            (balance + b.balance, unlocked + b.unlocked)
+        })
+}
+
+/// The version to resume from when a page of transactions is full
+fn next_start_version(transactions: &[Value], limit: u64) -> Option<u64> {
+    if (transactions.len() as u64) < limit {
+        return None;
+    }
+    transactions.last()?["version"].as_u64().map(|v| v + 1)
+}
+
+/// Which subcommand calls the function: entry, view or neither
+fn function_kind(function: &MoveFunction) -> &'static str {
+    if function.is_entry {
+        "entry"
+    } else if function.is_view {
+        "view"
+    } else {
+        "neither"
+    }
+}
+
+/// The signature followed by the return types, as in `0x1::coin::balance<T0>(address): u64`
+fn format_function(function_id: &str, function: &MoveFunction) -> String {
+    let signature = format_signature(function_id, function);
+    match function.return_.len() {
+        0 => signature,
+        1 => format!("{signature}: {}", function.return_[0]),
+        _ => {
+            let types = function.return_.iter().map(|ty| ty.to_string());
+            format!("{signature}: ({})", types.collect::<Vec<_>>().join(", "))
+        }
+    }
+}
+
+/// The signature of the function as a document. The ABI does not keep the names of the
+/// parameters, they are given by position
+fn describe_function(function_id: &str, function: &MoveFunction) -> Value {
+    let generic_type_params = function
+        .generic_type_params
+        .iter()
+        .enumerate()
+        .map(|(index, param)| {
+            json!({ "name": format!("T{index}"), "constraints": param.constraints })
+        })
+        .collect::<Vec<_>>();
+    let params = function
+        .params
+        .iter()
+        .enumerate()
+        .map(|(position, ty)| {
+            let signer = ty.to_string().trim_start_matches('&') == "signer";
+            json!({ "position": position, "type": ty.to_string(), "signer": signer })
+        })
+        .collect::<Vec<_>>();
+    let return_types = function.return_.iter().map(|ty| ty.to_string());
+    json!({
+        "function": function_id,
+        "kind": function_kind(function),
+        "visibility": function.visibility,
+        "generic_type_params": generic_type_params,
+        "params": params,
+        "return": return_types.collect::<Vec<_>>(),
+        "signature": format_function(function_id, function),
+    })
+}
+
+/// The bid board, a row per bid, then whether the queried account would be seated
+pub fn format_pof_bids(board: &Value) -> String {
+    let count = |value: &Value| match value.as_u64() {
+        Some(count) => count.to_string(),
+        None => "unknown".to_string(),
+    };
+    let mut lines = vec![
+        format!(
+            "Epoch: {}, next boundary (estimated) {}",
+            board["epoch"],
+            board["estimated_boundary"].as_str().unwrap_or_default()
+        ),
+        format!("Seats offered: {}", count(&board["seats_offered"])),
+        format!(
+            "Projected clearing bid: {}",
+            match board["projected_clearing_bid"].as_u64() {
+                Some(bid) => format!("{bid} basis points"),
+                None => "unknown".to_string(),
+            }
+        ),
+        String::new(),
+        format!(
+            "{:<6}{:<68}{:>8}  {}",
+            "RANK", "VALIDATOR", "BID", "EXPIRATION EPOCH"
+        ),
+    ];
+    let bids = board["bids"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for row in bids {
+        let status = if row["expired"] == json!(true) {
+            " expired"
+        } else if row["seated"] == json!(true) {
+            " seated"
+        } else {
+            ""
+        };
+        lines.push(format!(
+            "{:<6}{:<68}{:>8}  {}{status}",
+            row["rank"]
+                .as_u64()
+                .map_or("-".to_string(), |rank| rank.to_string()),
+            row["account"].as_str().unwrap_or_default(),
+            row["bid"],
+            row["expiration_epoch"]
+        ));
+    }
+    if bids.is_empty() {
+        lines.push("No bids".to_string());
+    }
+
+    let account = &board["account"];
+    if let Some(address) = account["account"].as_str() {
+        lines.push(String::new());
+        let outcome = if account["bid"].is_null() {
+            "has no bid, bid with txs pof-bid".to_string()
+        } else if account["expired"] == json!(true) {
+            format!(
+                "has an expired bid of {} basis points, it expired after epoch {}",
+                account["bid"], account["expiration_epoch"]
+            )
+        } else {
+            let seated = match account["seated"].as_bool() {
+                Some(true) => "would be seated",
+                Some(false) => "would not be seated",
+                None => "the number of seats is unknown",
+            };
+            format!(
+                "is ranked {} with {} basis points, {seated}",
+                account["rank"], account["bid"]
+            )
+        };
+        lines.push(format!("{address} {outcome}"));
+    }
+    lines.join("\n")
+}
+
+fn is_same_address(value: &Value, account: AccountAddress) -> bool {
+    value
+        .as_str()
+        .and_then(|v| AccountAddress::from_hex_literal(v).ok())
+        == Some(account)
+}
+
+fn format_timestamp(secs: u64) -> String {
+    match NaiveDateTime::from_timestamp_opt(secs as i64, 0) {
+        Some(time) => time.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        None => secs.to_string(),
+    }
 }
@@ -1,7 +1,27 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use clap::Parser;
 use libra_config::extension::client_ext::ClientExt;
-use query::querier::{Querier, QueryType::*};
+use query::{
+    export::{self, Destination, BALANCE_COLUMNS, EVENT_COLUMNS, TRANSACTION_COLUMNS},
+    extension::{client_ext::ClientExt as _, node_api_ext::NodeApiExt as _},
+    follow::{self, FollowStart},
+    querier::{balances_json, format_pof_bids, EventSource, Querier, QueryType::*},
+    watch::{watch, WatchOptions},
+};
+use serde_json::{json, Value};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use txs::{
+    client::{native_or, parse_coin_type},
+    constant::NATIVE_COIN_DECIMALS,
+    extension::client_ext::{ClientExt as _, LedgerVersionOptions},
+    json_query::{format_selected, select},
+    util::{format_output, parse_address, OutputFormat},
+};
+use url::Url;
 use zapatos_sdk::{rest_client::Client, types::account_address::AccountAddress};
 
 #[derive(Parser)]
@@ -9,43 +29,789 @@ use zapatos_sdk::{rest_client::Client, types::account_address::AccountAddress};
 pub struct QueryCli {
     #[clap(subcommand)]
     subcommand: Option<Subcommand>,
+
+    /// REST endpoint of the fullnode to use for this invocation, overriding the one in the config file
+    #[clap(long, global = true)]
+    url: Option<Url>,
+
+    /// Output format of query results. Json and yaml print a single document with no decoration,
+    /// csv prints the CSV layout of account-balance, transactions and events
+    #[clap(long, global = true, value_enum, default_value_t = Format::Text)]
+    format: Format,
+}
+
+/// The output formats of txs, with csv for the queries which have a CSV layout
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Format {
+    /// Human readable output
+    Text,
+    /// A single JSON document without any decoration
+    Json,
+    /// A single YAML document without any decoration
+    Yaml,
+    /// CSV with a header row, see the columns in the help of --csv
+    Csv,
 }
 
 #[derive(clap::Subcommand)]
 enum Subcommand {
     /// Get account balance
     AccountBalance {
-        /// Address of the onchain account to get balance from
-        #[clap(short, long)]
-        account_address: String,
+        /// Address of the onchain account to get balance from. Repeat it to print a table of the
+        /// balances of several accounts with their sum
+        #[clap(short, long, required_unless_present = "addresses_file")]
+        account_address: Vec<String>,
+
+        /// File of the accounts to get the balance of, an address per line, or - for stdin. Empty
+        /// lines and lines starting with # are skipped
+        #[clap(long, conflicts_with = "watch")]
+        addresses_file: Option<PathBuf>,
+
+        /// Fail when the balance of one of the accounts cannot be read, instead of reporting the
+        /// error in its row
+        #[clap(long)]
+        strict: bool,
+
+        #[clap(flatten)]
+        ledger_version: LedgerVersionOptions,
+
+        /// Write the balances to this CSV file, with the columns address, balance, balance_coins,
+        /// unlocked, unlocked_coins and error. Amounts are in base units, then in coins
+        #[clap(long, conflicts_with = "watch")]
+        csv: Option<PathBuf>,
+
+        /// Overwrite the --csv file when it exists
+        #[clap(long, requires = "csv")]
+        force: bool,
+
+        /// Also get the balance of the primary fungible store of the account, for the fungible
+        /// asset of this metadata object address, or @name from the address book
+        #[clap(long, conflicts_with_all = ["addresses_file", "csv", "watch"])]
+        fa_metadata: Option<String>,
+
+        #[clap(flatten)]
+        watch_options: WatchOptions,
     },
 
-    /// Get all resources of an account
+    /// Get all resources of an account, printing the type of each resource followed by its data
     AccountResources {
         /// Address of the onchain account to get resources from
         #[clap(short, long)]
         account_address: String,
+
+        /// Only print the struct tags of the resources
+        #[clap(long)]
+        types_only: bool,
+
+        /// Only print the value at this path of the resources, keyed by resource type, as in
+        /// ["0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"].coin.value or a JSON pointer.
+        /// Strings and numbers are printed bare in the text format
+        #[clap(long, conflicts_with = "types_only")]
+        query: Option<String>,
+
+        #[clap(flatten)]
+        ledger_version: LedgerVersionOptions,
+
+        #[clap(flatten)]
+        watch_options: WatchOptions,
+    },
+
+    /// Get the events emitted by an account, from an event handle or a creation number
+    Events {
+        /// Address of the onchain account which emitted the events
+        #[clap(short, long)]
+        account_address: String,
+
+        /// Struct tag of the resource holding the event handle, e.g. 0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>
+        #[clap(long, requires = "field_name", conflicts_with = "creation_number")]
+        event_handle: Option<String>,
+
+        /// Name of the event handle field of the resource, e.g. deposit_events
+        #[clap(long, requires = "event_handle")]
+        field_name: Option<String>,
+
+        /// Creation number of the event stream, instead of an event handle
+        #[clap(long)]
+        creation_number: Option<u64>,
+
+        /// Sequence number of the first event, the most recent events are returned when omitted
+        #[clap(long)]
+        start: Option<u64>,
+
+        /// Maximum number of events to return, fetched in as many pages as needed
+        #[clap(long, default_value_t = 25)]
+        limit: u64,
+
+        /// Keep polling for new events and print each one as a JSON line, until interrupted.
+        /// Starts from --start, else from the first event
+        #[clap(long, conflicts_with = "limit")]
+        follow: bool,
+
+        /// Skip the events emitted before following started
+        #[clap(long, requires = "follow", conflicts_with = "start")]
+        from_now: bool,
+
+        /// Save the next sequence number to this file after every event, and resume from it when
+        /// it exists, so that a restarted follower picks up where it stopped
+        #[clap(long, requires = "follow")]
+        cursor_file: Option<PathBuf>,
+
+        /// Seconds between two polls for new events
+        #[clap(long, default_value_t = 5, requires = "follow")]
+        interval_secs: u64,
+
+        /// Write the events to this CSV file, with the columns version, sequence_number, type,
+        /// amount, amount_coins and data. The amount of deposits and withdrawals is in base
+        /// units, then in coins, the data is the JSON of the event
+        #[clap(long, conflicts_with = "follow")]
+        csv: Option<PathBuf>,
+
+        /// Overwrite the --csv file when it exists
+        #[clap(long, requires = "csv")]
+        force: bool,
+    },
+
+    /// Get a summary of an account: balance, sequence number, validator and jail status, vouches, and whether it is a community wallet
+    AccountInfo {
+        /// Address of the onchain account to summarize
+        #[clap(short, long)]
+        account_address: String,
+    },
+
+    /// Get the current epoch, when the next epoch boundary is expected, and the validator set
+    EpochInfo {
+        /// Only print the validators and their voting power
+        #[clap(long)]
+        validators_only: bool,
+    },
+
+    /// Get the committed transactions sent by an account, showing the version, hash, function, gas used and VM status of each
+    Transactions {
+        /// Address of the onchain account to get transactions of
+        #[clap(short, long)]
+        account_address: String,
+
+        /// Also list the transactions which deposited coins into the account
+        #[clap(long)]
+        received: bool,
+
+        /// Only list transactions from this ledger version on. To get the next page, pass the version printed after a full page
+        #[clap(long, default_value_t = 0)]
+        start_version: u64,
+
+        /// Maximum number of transactions to return
+        #[clap(long, default_value_t = 25)]
+        limit: u64,
+
+        /// Also write the transactions to this CSV file, with the columns version, hash, sender,
+        /// function, success, vm_status, gas_used, gas_unit_price, fee and fee_coins. The fee is
+        /// in base units, then in coins
+        #[clap(long)]
+        csv: Option<PathBuf>,
+
+        /// Overwrite the --csv file when it exists
+        #[clap(long, requires = "csv")]
+        force: bool,
+    },
+
+    /// List the modules published by an account, with the signature of each of their functions
+    AccountModules {
+        /// Address of the onchain account which published the modules
+        #[clap(short, long)]
+        account_address: String,
+
+        /// Only print the names of the modules
+        #[clap(long)]
+        names_only: bool,
+    },
+
+    /// Get the name, symbol, decimals and total supply of a coin, in base units and in coins. For the native coin, also the 0L final supply and the lifetime burned and recycled amounts when the framework has them
+    CoinInfo {
+        /// Fully qualified struct tag of the coin, e.g. 0x1::my_coin::MyCoin. Defaults to the native coin
+        #[clap(long)]
+        coin_type: Option<String>,
+    },
+
+    /// List the proof-of-fee bids of the eligible validators by descending bid, with the seats offered and the projected clearing bid. Expired bids are listed last
+    PofBids {
+        /// Also tell whether this validator, or @name from the address book, would be seated at its current bid
+        #[clap(short, long)]
+        account: Option<String>,
+
+        /// Poll the node and print the board again whenever it changes, until interrupted, e.g. to follow the bids near the epoch boundary
+        #[clap(long)]
+        watch: bool,
+
+        /// Seconds between two polls
+        #[clap(long, default_value_t = 30, requires = "watch")]
+        interval_secs: u64,
+    },
+
+    /// Print the signature of a function from its on-chain ABI: its type parameters, argument and return types, and whether it is an entry or a view function
+    ShowFunction {
+        /// Function identifier of the form <ADDRESS>::<MODULE_ID>::<FUNCTION_NAME>, where <ADDRESS> can be @name from the address book, e.g. 0x1::coin::transfer
+        #[clap(short, long)]
+        function_id: String,
+    },
+
+    /// Get the item of a table under a key, decoded by the value type. The handle is found in the resource holding the table
+    TableItem {
+        /// Handle of the table
+        #[clap(long)]
+        handle: String,
+
+        /// Move type of the keys of the table, e.g. address or 0x1::string::String
+        #[clap(long)]
+        key_type: String,
+
+        /// Move type of the values of the table, e.g. u64 or 0x1::coin::Coin<0x1::aptos_coin::AptosCoin>
+        #[clap(long)]
+        value_type: String,
+
+        /// Key of the item, as JSON or bare for strings, addresses and integers, e.g. 0x1 or 42
+        #[clap(long)]
+        key: String,
     },
 }
 
 impl QueryCli {
     pub async fn run(&self) -> Result<()> {
-        let client = Client::default()?;
+        let client = match &self.url {
+            Some(url) => Client::from_url(url).await?,
+            None => Client::default()?,
+        };
         let querier = Querier::new(client);
 
+        let has_csv_layout = matches!(
+            self.subcommand,
+            Some(
+                Subcommand::AccountBalance { .. }
+                    | Subcommand::Transactions { .. }
+                    | Subcommand::Events { .. }
+            )
+        );
+        if self.format == Format::Csv && !has_csv_layout {
+            bail!("--format csv is only supported by account-balance, transactions and events");
+        }
+        // The CSV commands print CSV instead of their text output
+        let csv_stdout = self.format == Format::Csv;
+        let format = match self.format {
+            Format::Text | Format::Csv => OutputFormat::Text,
+            Format::Json => OutputFormat::Json,
+            Format::Yaml => OutputFormat::Yaml,
+        };
+
         match &self.subcommand {
-            Some(Subcommand::AccountBalance { account_address }) => {
-                let account = AccountAddress::from_hex_literal(account_address)?;
-                let balance = querier.query(Balance { account }).await?;
-                println!("Account balance: {balance} coins");
+            Some(Subcommand::AccountBalance {
+                account_address,
+                addresses_file,
+                strict,
+                ledger_version,
+                csv,
+                force,
+                fa_metadata,
+                watch_options,
+            }) => {
+                let fa_metadata = fa_metadata
+                    .as_deref()
+                    .map(|metadata| parse_address(metadata, "--fa-metadata"))
+                    .transpose()?;
+                let mut accounts = account_address
+                    .iter()
+                    .map(|address| parse_address(address, "--account-address"))
+                    .collect::<Result<Vec<_>>>()?;
+                if let Some(path) = addresses_file {
+                    accounts.extend(read_addresses(path)?);
+                }
+                let several = accounts.len() > 1 || addresses_file.is_some();
+                if fa_metadata.is_some() && (several || csv_stdout) {
+                    bail!("--fa-metadata takes a single --account-address, without --format csv");
+                }
+                if watch_options.watch {
+                    if csv_stdout {
+                        bail!("--format csv cannot be used with --watch");
+                    }
+                    if several {
+                        bail!("--watch takes a single --account-address");
+                    }
+                    return watch_balance(&querier, accounts[0], watch_options, format).await;
+                }
+                let version = querier
+                    .client
+                    .resolve_ledger_version(ledger_version)
+                    .await?;
+                if csv.is_some() || csv_stdout {
+                    // The balance of a single account is no row of a table, it fails on error
+                    let balances = querier
+                        .get_balances(&accounts, version, *strict || !several)
+                        .await?;
+                    let rows = accounts
+                        .iter()
+                        .zip(&balances)
+                        .map(|(account, balance)| {
+                            export::balance_row(*account, balance, NATIVE_COIN_DECIMALS)
+                        })
+                        .collect::<Vec<_>>();
+                    let csv = csv.as_deref();
+                    export_csv(
+                        "Balances",
+                        csv,
+                        *force,
+                        csv_stdout,
+                        format,
+                        &BALANCE_COLUMNS,
+                        &rows,
+                    )?;
+                    if format != OutputFormat::Text {
+                        let balances = balances_json(&accounts, &balances, version);
+                        println!("{}", format_output(&balances, format)?);
+                    }
+                    return Ok(());
+                }
+                if several {
+                    let query_type = Balances {
+                        accounts,
+                        version,
+                        strict: *strict,
+                    };
+                    if format == OutputFormat::Text {
+                        println!("{}", querier.query(query_type).await?);
+                    } else {
+                        let balances = querier.query_json(query_type).await?;
+                        println!("{}", format_output(&balances, format)?);
+                    }
+                    return Ok(());
+                }
+                let query_type = Balance {
+                    account: accounts[0],
+                    version,
+                    fa_metadata,
+                };
+                if format == OutputFormat::Text {
+                    println!("{}", querier.query(query_type).await?);
+                } else {
+                    let balance = querier.query_json(query_type).await?;
+                    println!("{}", format_output(&balance, format)?);
+                }
+            }
+            Some(Subcommand::AccountResources {
+                account_address,
+                types_only,
+                query,
+                ledger_version,
+                watch_options,
+            }) => {
+                let account = parse_address(account_address, "--account-address")?;
+                if watch_options.watch && query.is_some() {
+                    bail!("--query cannot be used with --watch");
+                }
+                if watch_options.watch {
+                    return watch_resources(&querier, account, *types_only, watch_options, format)
+                        .await;
+                }
+                let query_type = Resources {
+                    account,
+                    types_only: *types_only,
+                    version: querier
+                        .client
+                        .resolve_ledger_version(ledger_version)
+                        .await?,
+                };
+                if let Some(query) = query {
+                    let resources = querier.query_json(query_type).await?;
+                    let keyed = resources
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .map(|r| {
+                            (
+                                r["type"].as_str().unwrap_or_default().to_string(),
+                                r["data"].to_owned(),
+                            )
+                        })
+                        .collect::<serde_json::Map<_, _>>();
+                    let selected = select(&Value::Object(keyed), query)?;
+                    println!("{}", format_selected(&selected, format)?);
+                } else if format == OutputFormat::Text {
+                    let resources = querier.query(query_type).await?;
+                    println!("{resources}");
+                } else {
+                    let resources = querier.query_json(query_type).await?;
+                    println!("{}", format_output(&resources, format)?);
+                }
+            }
+            Some(Subcommand::Events {
+                account_address,
+                event_handle,
+                field_name,
+                creation_number,
+                start,
+                limit,
+                follow,
+                from_now,
+                cursor_file,
+                interval_secs,
+                csv,
+                force,
+            }) => {
+                let account = parse_address(account_address, "--account-address")?;
+                let source = match (event_handle, field_name, creation_number) {
+                    (Some(struct_tag), Some(field_name), None) => EventSource::Handle {
+                        struct_tag: struct_tag.to_owned(),
+                        field_name: field_name.to_owned(),
+                    },
+                    (None, None, Some(creation_number)) => {
+                        EventSource::CreationNumber(*creation_number)
+                    }
+                    _ => bail!("Either --event-handle and --field-name, or --creation-number must be provided"),
+                };
+                if *follow {
+                    if csv_stdout {
+                        bail!("--format csv cannot be used with --follow");
+                    }
+                    let follow_start = if *from_now {
+                        FollowStart::Now
+                    } else {
+                        FollowStart::SequenceNumber(start.unwrap_or_default())
+                    };
+                    return follow::follow(
+                        &querier.client,
+                        account,
+                        &source,
+                        follow_start,
+                        cursor_file.as_deref(),
+                        Duration::from_secs(*interval_secs),
+                    )
+                    .await;
+                }
+                if csv.is_some() || csv_stdout {
+                    let events = querier
+                        .client
+                        .get_events_ext(account, &source, *start, *limit)
+                        .await?;
+                    let rows = events
+                        .iter()
+                        .map(|event| export::event_row(event, NATIVE_COIN_DECIMALS))
+                        .collect::<Vec<_>>();
+                    let csv = csv.as_deref();
+                    export_csv(
+                        "Events",
+                        csv,
+                        *force,
+                        csv_stdout,
+                        format,
+                        &EVENT_COLUMNS,
+                        &rows,
+                    )?;
+                    if format != OutputFormat::Text {
+                        println!("{}", format_output(&events, format)?);
+                    }
+                    return Ok(());
+                }
+                let query_type = Events {
+                    account,
+                    source,
+                    seq_start: *start,
+                    limit: *limit,
+                };
+                if format == OutputFormat::Text {
+                    println!("{}", querier.query(query_type).await?);
+                } else {
+                    let events = querier.query_json(query_type).await?;
+                    println!("{}", format_output(&events, format)?);
+                }
+            }
+            Some(Subcommand::Transactions {
+                account_address,
+                received,
+                start_version,
+                limit,
+                csv,
+                force,
+            }) => {
+                let account = parse_address(account_address, "--account-address")?;
+                let query_type = Txs {
+                    account,
+                    start_version: *start_version,
+                    limit: *limit,
+                    received: *received,
+                };
+                if format == OutputFormat::Text && csv.is_none() && !csv_stdout {
+                    println!("{}", querier.query(query_type).await?);
+                } else {
+                    let transactions = querier.query_json(query_type).await?;
+                    if csv.is_some() || csv_stdout {
+                        let rows = transactions["transactions"]
+                            .as_array()
+                            .into_iter()
+                            .flatten()
+                            .map(|t| export::transaction_row(t, NATIVE_COIN_DECIMALS))
+                            .collect::<Vec<_>>();
+                        let csv = csv.as_deref();
+                        export_csv(
+                            "Transactions",
+                            csv,
+                            *force,
+                            csv_stdout,
+                            format,
+                            &TRANSACTION_COLUMNS,
+                            &rows,
+                        )?;
+                    }
+                    if format != OutputFormat::Text {
+                        println!("{}", format_output(&transactions, format)?);
+                    }
+                }
+            }
+            Some(Subcommand::AccountInfo { account_address }) => {
+                let account = parse_address(account_address, "--account-address")?;
+                if format == OutputFormat::Text {
+                    println!("{}", querier.query(AccountInfo { account }).await?);
+                } else {
+                    let info = querier.query_json(AccountInfo { account }).await?;
+                    println!("{}", format_output(&info, format)?);
+                }
+            }
+            Some(Subcommand::EpochInfo { validators_only }) => {
+                let query_type = Epoch {
+                    validators_only: *validators_only,
+                };
+                if format == OutputFormat::Text {
+                    println!("{}", querier.query(query_type).await?);
+                } else {
+                    let info = querier.query_json(query_type).await?;
+                    println!("{}", format_output(&info, format)?);
+                }
             }
-            Some(Subcommand::AccountResources { account_address }) => {
-                let account = AccountAddress::from_hex_literal(account_address)?;
-                let resources = querier.query(Resources { account }).await?;
-                println!("{resources}");
+            Some(Subcommand::AccountModules {
+                account_address,
+                names_only,
+            }) => {
+                let query_type = Modules {
+                    account: parse_address(account_address, "--account-address")?,
+                    names_only: *names_only,
+                };
+                if format == OutputFormat::Text {
+                    println!("{}", querier.query(query_type).await?);
+                } else {
+                    let modules = querier.query_json(query_type).await?;
+                    println!("{}", format_output(&modules, format)?);
+                }
+            }
+            Some(Subcommand::CoinInfo { coin_type }) => {
+                let query_type = CoinInfo {
+                    coin_type: native_or(coin_type.as_deref().map(parse_coin_type).transpose()?)?,
+                };
+                if format == OutputFormat::Text {
+                    println!("{}", querier.query(query_type).await?);
+                } else {
+                    let info = querier.query_json(query_type).await?;
+                    println!("{}", format_output(&info, format)?);
+                }
+            }
+            Some(Subcommand::PofBids {
+                account,
+                watch,
+                interval_secs,
+            }) => {
+                let account = account
+                    .as_deref()
+                    .map(|account| parse_address(account, "--account"))
+                    .transpose()?;
+                if *watch {
+                    let options = WatchOptions {
+                        watch: true,
+                        interval_secs: *interval_secs,
+                        until: None,
+                        watch_timeout: None,
+                    };
+                    return watch_pof_bids(&querier, account, &options, format).await;
+                }
+                let query_type = PofBids { account };
+                if format == OutputFormat::Text {
+                    println!("{}", querier.query(query_type).await?);
+                } else {
+                    let board = querier.query_json(query_type).await?;
+                    println!("{}", format_output(&board, format)?);
+                }
+            }
+            Some(Subcommand::ShowFunction { function_id }) => {
+                let query_type = Function {
+                    function_id: function_id.to_owned(),
+                };
+                if format == OutputFormat::Text {
+                    println!("{}", querier.query(query_type).await?);
+                } else {
+                    let function = querier.query_json(query_type).await?;
+                    println!("{}", format_output(&function, format)?);
+                }
+            }
+            Some(Subcommand::TableItem {
+                handle,
+                key_type,
+                value_type,
+                key,
+            }) => {
+                let query_type = TableItem {
+                    handle: parse_address(handle, "--handle")?,
+                    key_type: key_type.to_owned(),
+                    value_type: value_type.to_owned(),
+                    key: key.to_owned(),
+                };
+                if format == OutputFormat::Text {
+                    println!("{}", querier.query(query_type).await?);
+                } else {
+                    let item = querier.query_json(query_type).await?;
+                    println!("{}", format_output(&item, format)?);
+                }
             }
             _ => { /* do nothing */ }
         }
         Ok(())
     }
 }
+
+/// Print the balance with the change since the previous line, as a JSON line per change for the
+/// json and yaml output formats
+async fn watch_balance(
+    querier: &Querier,
+    account: AccountAddress,
+    options: &WatchOptions,
+    format: OutputFormat,
+) -> Result<()> {
+    let until = options
+        .until
+        .map(|condition| move |balance: &u64| condition.is_met(*balance));
+    watch(
+        options,
+        || querier.client.get_account_balance_ext(account, None),
+        |previous, balance| {
+            let delta = previous.map(|previous| *balance as i128 - *previous as i128);
+            if format == OutputFormat::Text {
+                let delta = delta.map(|delta| format!(" ({delta:+})"));
+                println!(
+                    "{} Account balance: {balance} coins{}",
+                    now(),
+                    delta.unwrap_or_default()
+                );
+            } else {
+                let line = json!({ "timestamp": now(), "balance": balance, "delta": delta });
+                println!("{line}");
+            }
+            Ok(())
+        },
+        until.as_ref().map(|until| until as &dyn Fn(&u64) -> bool),
+    )
+    .await
+}
+
+/// Print all the resources again whenever one of them changes
+async fn watch_resources(
+    querier: &Querier,
+    account: AccountAddress,
+    types_only: bool,
+    options: &WatchOptions,
+    format: OutputFormat,
+) -> Result<()> {
+    if options.until.is_some() {
+        bail!("--until only applies to account-balance");
+    }
+    watch(
+        options,
+        || {
+            querier.query_json(Resources {
+                account,
+                types_only,
+                version: None,
+            })
+        },
+        |_, resources| {
+            if format != OutputFormat::Text {
+                let line = json!({ "timestamp": now(), "resources": resources });
+                println!("{line}");
+                return Ok(());
+            }
+            println!("{}", now());
+            for resource in resources.as_array().into_iter().flatten() {
+                match resource.as_str() {
+                    Some(resource_type) => println!("{resource_type}"),
+                    None => {
+                        println!("{}", resource["type"].as_str().unwrap_or_default());
+                        println!("{}", serde_json::to_string_pretty(&resource["data"])?);
+                    }
+                }
+            }
+            Ok(())
+        },
+        None,
+    )
+    .await
+}
+
+/// Print the bid board again whenever a bid, the seats or the epoch change
+async fn watch_pof_bids(
+    querier: &Querier,
+    account: Option<AccountAddress>,
+    options: &WatchOptions,
+    format: OutputFormat,
+) -> Result<()> {
+    watch(
+        options,
+        || querier.query_json(PofBids { account }),
+        |_, board| {
+            if format == OutputFormat::Text {
+                println!("{}\n{}\n", now(), format_pof_bids(board));
+            } else {
+                let line = json!({ "timestamp": now(), "board": board });
+                println!("{line}");
+            }
+            Ok(())
+        },
+        None,
+    )
+    .await
+}
+
+fn now() -> String {
+    chrono::Utc::now()
+        .format("%Y-%m-%d %H:%M:%S UTC")
+        .to_string()
+}
+
+/// The addresses of an --addresses-file, or of stdin for -
+fn read_addresses(path: &Path) -> Result<Vec<AccountAddress>> {
+    let content = if path == Path::new("-") {
+        io::read_to_string(io::stdin()).context("Failed to read the addresses from stdin")?
+    } else {
+        fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?
+    };
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| parse_address(line, "--addresses-file"))
+        .collect()
+}
+
+/// Write the rows to the --csv file, and to stdout for --format csv
+#[allow(clippy::too_many_arguments)]
+fn export_csv(
+    what: &str,
+    path: Option<&Path>,
+    force: bool,
+    to_stdout: bool,
+    format: OutputFormat,
+    columns: &[&str],
+    rows: &[Vec<String>],
+) -> Result<()> {
+    if let Some(path) = path {
+        export::write_csv(Destination::File { path, force }, columns, rows)?;
+        if format == OutputFormat::Text && !to_stdout {
+            println!("{what} written to {}", path.display());
+        }
+    }
+    if to_stdout {
+        export::write_csv(Destination::Stdout, columns, rows)?;
+    }
+    Ok(())
+}
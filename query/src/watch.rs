@@ -0,0 +1,176 @@
+//! Poll a query and print it again whenever its value changes
+
+use anyhow::{bail, Context, Result};
+use std::{
+    fmt,
+    future::Future,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+#[derive(clap::Args)]
+pub struct WatchOptions {
+    /// Poll the node and print a new line whenever the value changes, until interrupted
+    #[clap(long, conflicts_with_all = ["at_version", "at_height"])]
+    pub watch: bool,
+
+    /// Seconds between two polls
+    #[clap(long, default_value_t = 5, requires = "watch")]
+    pub interval_secs: u64,
+
+    /// Stop watching when the balance meets this comparison, e.g. ">= 1000000"
+    #[clap(long, requires = "watch")]
+    pub until: Option<Condition>,
+
+    /// Give up after this many seconds, with an error when --until is not met yet
+    #[clap(long, requires = "watch")]
+    pub watch_timeout: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+impl Comparison {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Self::Equal => "==",
+            Self::NotEqual => "!=",
+            Self::Greater => ">",
+            Self::GreaterOrEqual => ">=",
+            Self::Less => "<",
+            Self::LessOrEqual => "<=",
+        }
+    }
+}
+
+/// A comparison of the watched value with a number, e.g. `>= 1000000`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Condition {
+    comparison: Comparison,
+    value: u64,
+}
+
+impl Condition {
+    pub fn is_met(&self, value: u64) -> bool {
+        match self.comparison {
+            Comparison::Equal => value == self.value,
+            Comparison::NotEqual => value != self.value,
+            Comparison::Greater => value > self.value,
+            Comparison::GreaterOrEqual => value >= self.value,
+            Comparison::Less => value < self.value,
+            Comparison::LessOrEqual => value <= self.value,
+        }
+    }
+}
+
+impl FromStr for Condition {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        // Two character operators first, so that >= is not read as >
+        let (comparison, value) = [
+            Comparison::GreaterOrEqual,
+            Comparison::LessOrEqual,
+            Comparison::Equal,
+            Comparison::NotEqual,
+            Comparison::Greater,
+            Comparison::Less,
+        ]
+        .into_iter()
+        .find_map(|comparison| {
+            s.strip_prefix(comparison.symbol())
+                .map(|value| (comparison, value))
+        })
+        .context(format!(
+            "Invalid condition {s}, expected a comparison such as \">= 1000000\""
+        ))?;
+        let value = value.trim().replace('_', "");
+        let value = value
+            .parse()
+            .context(format!("Invalid condition {s}, {value} is not an integer"))?;
+        Ok(Self { comparison, value })
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.comparison.symbol(), self.value)
+    }
+}
+
+/// Poll until the value meets `until`, or forever when it is not set. `print` gets the previous
+/// value and the new one whenever it changes. A failed poll is reported and polled again, so a
+/// node restarting does not stop the watch
+pub async fn watch<T, F, Fut>(
+    options: &WatchOptions,
+    mut poll: F,
+    mut print: impl FnMut(Option<&T>, &T) -> Result<()>,
+    until: Option<&dyn Fn(&T) -> bool>,
+) -> Result<()>
+where
+    T: PartialEq,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let started = Instant::now();
+    let mut last = None;
+    loop {
+        match poll().await {
+            Ok(value) => {
+                if last.as_ref() != Some(&value) {
+                    print(last.as_ref(), &value)?;
+                    if until.map_or(false, |until| until(&value)) {
+                        return Ok(());
+                    }
+                    last = Some(value);
+                }
+            }
+            Err(err) => eprintln!(
+                "Warning: {err:#}, polling again in {}s",
+                options.interval_secs
+            ),
+        }
+
+        if let Some(timeout) = options.watch_timeout {
+            if started.elapsed() >= Duration::from_secs(timeout) {
+                match &options.until {
+                    Some(condition) => {
+                        bail!("Timed out after {timeout}s, the value is not {condition} yet")
+                    }
+                    None => return Ok(()),
+                }
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(options.interval_secs)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_conditions() {
+        let condition: Condition = ">= 1_000_000".parse().unwrap();
+        assert_eq!(condition.to_string(), ">= 1000000");
+        assert!(condition.is_met(1_000_000));
+        assert!(!condition.is_met(999_999));
+
+        let condition: Condition = "<5".parse().unwrap();
+        assert!(condition.is_met(4));
+        assert!(!condition.is_met(5));
+        assert!("!= 0".parse::<Condition>().unwrap().is_met(1));
+
+        assert!("1000".parse::<Condition>().is_err());
+        assert!("=> 1000".parse::<Condition>().is_err());
+        assert!(">= lots".parse::<Condition>().is_err());
+    }
+}
@@ -0,0 +1,206 @@
+use crate::{args::ListArg, move_value::substitute};
+use anyhow::{bail, Result};
+use zapatos_sdk::{
+    move_types::language_storage::TypeTag,
+    rest_client::aptos_api_types::{MoveFunction, MoveType},
+    types::transaction::TransactionArgument,
+};
+
+/// Make sure an entry function call matches the function's declared signature:
+/// the function is an entry function, and the type arguments and arguments agree in count and kind
+pub fn check_entry_function_call(
+    function: &MoveFunction,
+    function_id: &str,
+    type_args: &[TypeTag],
+    args: &[ListArg],
+) -> Result<()> {
+    let signature = format_signature(function_id, function);
+    let params = entry_function_params(function, function_id, type_args)?;
+    if args.len() != params.len() {
+        bail!(
+            "{function_id} expects {} argument(s), got {}: {signature}",
+            params.len(),
+            args.len()
+        );
+    }
+    for (index, (param, arg)) in params.iter().zip(args).enumerate() {
+        if !list_argument_matches(param, arg) {
+            bail!(
+                "Argument {} of {function_id} must be a {param}, got {arg:?}: {signature}",
+                index + 1
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The parameters of an entry function given as arguments, with the type arguments in place
+/// of the generic type parameters. The signers are left out, they are the senders of the transaction
+pub fn entry_function_params(
+    function: &MoveFunction,
+    function_id: &str,
+    type_args: &[TypeTag],
+) -> Result<Vec<MoveType>> {
+    let signature = format_signature(function_id, function);
+    if !function.is_entry {
+        bail!("{function_id} is not an entry function: {signature}");
+    }
+    function_params(function, function_id, type_args)
+}
+
+/// Same as `entry_function_params`, for any function such as a view function
+pub fn function_params(
+    function: &MoveFunction,
+    function_id: &str,
+    type_args: &[TypeTag],
+) -> Result<Vec<MoveType>> {
+    if type_args.len() != function.generic_type_params.len() {
+        bail!(
+            "{function_id} expects {} type argument(s), got {}: {}",
+            function.generic_type_params.len(),
+            type_args.len(),
+            format_signature(function_id, function)
+        );
+    }
+    let type_args = type_args.iter().map(MoveType::from).collect::<Vec<_>>();
+    Ok(function
+        .params
+        .iter()
+        .filter(|param| !is_signer(param))
+        .map(|param| substitute(param, &type_args))
+        .collect())
+}
+
+/// Number of signers the function takes, the sender and the secondary signers of a multi-agent transaction
+pub fn signer_count(function: &MoveFunction) -> usize {
+    function
+        .params
+        .iter()
+        .filter(|param| is_signer(param))
+        .count()
+}
+
+fn is_signer(param: &MoveType) -> bool {
+    match param {
+        MoveType::Signer => true,
+        MoveType::Reference { to, .. } => matches!(**to, MoveType::Signer),
+        _ => false,
+    }
+}
+
+/// Same as `argument_matches`, with strings and options checked against their declared types
+fn list_argument_matches(param: &MoveType, arg: &ListArg) -> bool {
+    if let MoveType::GenericTypeParam { .. } = param {
+        return true;
+    }
+    match arg {
+        ListArg::Move { arg, .. } => argument_matches(param, arg),
+        ListArg::String(_) => struct_name(param) == Some("0x1::string::String".to_string()),
+        ListArg::None | ListArg::Some(_) => match param {
+            MoveType::Struct(tag)
+                if struct_name(param) == Some("0x1::option::Option".to_string()) =>
+            {
+                match (arg, tag.generic_type_params.first()) {
+                    (ListArg::Some(inner), Some(ty)) => list_argument_matches(ty, inner),
+                    _ => true,
+                }
+            }
+            _ => false,
+        },
+    }
+}
+
+fn struct_name(param: &MoveType) -> Option<String> {
+    match param {
+        MoveType::Struct(tag) => Some(format!("{}::{}::{}", tag.address, tag.module, tag.name)),
+        _ => None,
+    }
+}
+
+/// Whether the argument can be passed for the parameter. Generic and struct parameters
+/// other than strings and objects cannot be checked here, they are left to the VM
+fn argument_matches(param: &MoveType, arg: &TransactionArgument) -> bool {
+    match (param, arg) {
+        (MoveType::Bool, TransactionArgument::Bool(_))
+        | (MoveType::U8, TransactionArgument::U8(_))
+        | (MoveType::U16, TransactionArgument::U16(_))
+        | (MoveType::U32, TransactionArgument::U32(_))
+        | (MoveType::U64, TransactionArgument::U64(_))
+        | (MoveType::U128, TransactionArgument::U128(_))
+        | (MoveType::U256, TransactionArgument::U256(_))
+        | (MoveType::Address, TransactionArgument::Address(_)) => true,
+        (MoveType::Vector { items }, TransactionArgument::U8Vector(_)) => {
+            matches!(**items, MoveType::U8)
+        }
+        (MoveType::Struct(_), arg) => match struct_name(param).unwrap_or_default().as_str() {
+            "0x1::string::String" => matches!(arg, TransactionArgument::U8Vector(_)),
+            "0x1::object::Object" => matches!(arg, TransactionArgument::Address(_)),
+            _ => true,
+        },
+        (MoveType::GenericTypeParam { .. }, _) => true,
+        _ => false,
+    }
+}
+
+/// The signature as in `0x1::coin::transfer<T0>(&signer, address, u64)`
+pub fn format_signature(function_id: &str, function: &MoveFunction) -> String {
+    let type_params = (0..function.generic_type_params.len())
+        .map(|index| format!("T{index}"))
+        .collect::<Vec<_>>();
+    let type_params = if type_params.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", type_params.join(", "))
+    };
+    let params = function
+        .params
+        .iter()
+        .map(|param| param.to_string())
+        .collect::<Vec<_>>();
+    format!("{function_id}{type_params}({})", params.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zapatos_sdk::{move_types::parser::parse_type_tag, types::account_address::AccountAddress};
+
+    #[test]
+    fn match_arguments_with_parameters() {
+        assert!(argument_matches(
+            &MoveType::U64,
+            &TransactionArgument::U64(1)
+        ));
+        assert!(!argument_matches(
+            &MoveType::U8,
+            &TransactionArgument::U64(1)
+        ));
+        assert!(argument_matches(
+            &MoveType::Vector {
+                items: Box::new(MoveType::U8)
+            },
+            &TransactionArgument::U8Vector(vec![1, 2])
+        ));
+        assert!(!argument_matches(
+            &MoveType::Address,
+            &TransactionArgument::Bool(true)
+        ));
+        assert!(argument_matches(
+            &MoveType::GenericTypeParam { index: 0 },
+            &TransactionArgument::Address(AccountAddress::ONE)
+        ));
+    }
+
+    #[test]
+    fn match_strings_and_options_with_parameters() {
+        let string = MoveType::from(&parse_type_tag("0x1::string::String").unwrap());
+        let option = MoveType::from(&parse_type_tag("0x1::option::Option<u64>").unwrap());
+        let args = crate::args::parse_list_args("'text', none, some(1), some(true)").unwrap();
+        assert!(list_argument_matches(&string, &args[0]));
+        assert!(!list_argument_matches(&MoveType::U64, &args[0]));
+        assert!(list_argument_matches(&option, &args[1]));
+        assert!(list_argument_matches(&option, &args[2]));
+        assert!(!list_argument_matches(&option, &args[3]));
+        assert!(!list_argument_matches(&string, &args[1]));
+    }
+}
@@ -0,0 +1,221 @@
+//! Modules fetched from the node, kept on disk under the 0L config folder so that checking a call
+//! against its ABI or explaining an abort does not fetch them again on every run. The entries are
+//! per chain and expire after a day, --refresh-abi fetches them again before that, e.g. after a
+//! module upgrade. A cache which cannot be read or written is skipped, it never fails a command
+
+use crate::{constant::ABI_CACHE_TTL_SECS, node_api::NodeApi, retry::retry_read};
+use anyhow::{Context, Result};
+use libra_config::extension::global_config_ext::global_folder;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::debug;
+use zapatos_sdk::{
+    crypto::HashValue, rest_client::aptos_api_types::MoveModuleBytecode,
+    types::account_address::AccountAddress,
+};
+
+pub const ABI_CACHE_FOLDER: &str = "abi-cache";
+
+static REFRESH: AtomicBool = AtomicBool::new(false);
+static CHAIN_ID: Mutex<Option<u8>> = Mutex::new(None);
+
+/// Set from --refresh-abi: the modules are fetched again and the cache updated
+pub fn set_refresh(refresh: bool) {
+    REFRESH.store(refresh, Ordering::Relaxed);
+}
+
+/// The chain of the node when it is already known, which spares a request for it
+pub fn set_chain_id(chain_id: u8) {
+    *CHAIN_ID
+        .lock()
+        .expect("the chain id lock is never poisoned") = Some(chain_id);
+}
+
+/// The chain of the node, once known
+pub fn known_chain_id() -> Option<u8> {
+    *CHAIN_ID
+        .lock()
+        .expect("the chain id lock is never poisoned")
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    chain_id: u8,
+    module_id: String,
+    /// Ledger version of the node when the module was fetched
+    ledger_version: u64,
+    fetched_at_secs: u64,
+    /// Hash of the bytecode, the entry is corrupt when it does not match
+    bytecode_hash: String,
+    module: MoveModuleBytecode,
+}
+
+/// The module, read from the cache when it has a fresh entry for the chain of the node, else
+/// fetched from the node and cached
+pub async fn get_module(
+    client: &impl NodeApi,
+    address: AccountAddress,
+    module_name: &str,
+) -> Result<MoveModuleBytecode> {
+    let module_id = format!("{}::{module_name}", address.to_hex_literal());
+    let cached = match (chain_id(client).await, global_folder()) {
+        (Some(chain_id), Ok(folder)) => Some((
+            chain_id,
+            entry_path(
+                &folder.join(ABI_CACHE_FOLDER),
+                chain_id,
+                address,
+                module_name,
+            ),
+        )),
+        _ => None,
+    };
+    if let Some((chain_id, path)) = &cached {
+        if !REFRESH.load(Ordering::Relaxed) {
+            if let Some(module) = read_entry(path, *chain_id, &module_id, now_secs()) {
+                debug!("Module {module_id} read from the ABI cache");
+                return Ok(module);
+            }
+        }
+    }
+
+    let response = retry_read(|| client.get_account_module(address, module_name)).await?;
+    let (node_chain_id, ledger_version) = (response.state().chain_id, response.state().version);
+    let module = response.into_inner();
+    if let Some((chain_id, path)) = cached.filter(|(chain_id, _)| *chain_id == node_chain_id) {
+        let entry = Entry {
+            chain_id,
+            module_id,
+            ledger_version,
+            fetched_at_secs: now_secs(),
+            bytecode_hash: bytecode_hash(&module),
+            module: module.clone(),
+        };
+        if let Err(err) = write_entry(&path, &entry) {
+            debug!("Module {} not cached: {err:#}", entry.module_id);
+        }
+    }
+    Ok(module)
+}
+
+/// Remove every cached module, returning the folder of the cache unless it was already empty
+pub fn clear() -> Result<Option<PathBuf>> {
+    let folder = global_folder()?.join(ABI_CACHE_FOLDER);
+    if !folder.exists() {
+        return Ok(None);
+    }
+    fs::remove_dir_all(&folder).context(format!("Failed to remove {}", folder.display()))?;
+    Ok(Some(folder))
+}
+
+async fn chain_id(client: &impl NodeApi) -> Option<u8> {
+    let known = known_chain_id();
+    if known.is_some() {
+        return known;
+    }
+    let chain_id = retry_read(|| client.get_index())
+        .await
+        .ok()?
+        .into_inner()
+        .chain_id;
+    set_chain_id(chain_id);
+    Some(chain_id)
+}
+
+fn entry_path(folder: &Path, chain_id: u8, address: AccountAddress, module_name: &str) -> PathBuf {
+    folder
+        .join(chain_id.to_string())
+        .join(address.to_hex_literal())
+        .join(format!("{module_name}.json"))
+}
+
+/// None when the entry is missing, corrupt, of another chain or module, or stale
+fn read_entry(
+    path: &Path,
+    chain_id: u8,
+    module_id: &str,
+    now_secs: u64,
+) -> Option<MoveModuleBytecode> {
+    let content = fs::read_to_string(path).ok()?;
+    let entry = match serde_json::from_str::<Entry>(&content) {
+        Ok(entry) => entry,
+        Err(err) => {
+            debug!(
+                "Ignoring the corrupt ABI cache entry {}: {err}",
+                path.display()
+            );
+            return None;
+        }
+    };
+    let fresh = now_secs.saturating_sub(entry.fetched_at_secs) < ABI_CACHE_TTL_SECS;
+    (entry.chain_id == chain_id
+        && entry.module_id == module_id
+        && fresh
+        && entry.bytecode_hash == bytecode_hash(&entry.module))
+    .then_some(entry.module)
+}
+
+/// Written to a file of its own first, so that a concurrent command never reads half an entry
+fn write_entry(path: &Path, entry: &Entry) -> Result<()> {
+    if let Some(folder) = path.parent() {
+        fs::create_dir_all(folder).context(format!("Failed to create {}", folder.display()))?;
+    }
+    let partial = path.with_extension(format!("{}.tmp", std::process::id()));
+    fs::write(&partial, serde_json::to_string(entry)?)
+        .context(format!("Failed to write {}", partial.display()))?;
+    fs::rename(&partial, path).context(format!("Failed to write {}", path.display()))
+}
+
+fn bytecode_hash(module: &MoveModuleBytecode) -> String {
+    HashValue::sha3_256_of(module.bytecode.inner()).to_hex_literal()
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skip_stale_foreign_and_corrupt_entries() {
+        let folder = std::env::temp_dir().join("txs-abi-cache-test");
+        let _ = fs::remove_dir_all(&folder);
+        let path = entry_path(&folder, 4, AccountAddress::ONE, "coin");
+        let module = MoveModuleBytecode::new(vec![0xa1, 0x1c, 0xeb, 0x0b]);
+        let entry = Entry {
+            chain_id: 4,
+            module_id: "0x1::coin".to_string(),
+            ledger_version: 10,
+            fetched_at_secs: 1_000,
+            bytecode_hash: bytecode_hash(&module),
+            module,
+        };
+        write_entry(&path, &entry).unwrap();
+
+        assert!(read_entry(&path, 4, "0x1::coin", 1_000).is_some());
+        assert!(read_entry(&path, 1, "0x1::coin", 1_000).is_none());
+        assert!(read_entry(&path, 4, "0x1::account", 1_000).is_none());
+        assert!(read_entry(&path, 4, "0x1::coin", 1_000 + ABI_CACHE_TTL_SECS).is_none());
+
+        let tampered = fs::read_to_string(&path)
+            .unwrap()
+            .replace(&entry.bytecode_hash, &HashValue::zero().to_hex_literal());
+        fs::write(&path, tampered).unwrap();
+        assert!(read_entry(&path, 4, "0x1::coin", 1_000).is_none());
+        fs::write(&path, "{ not json").unwrap();
+        assert!(read_entry(&path, 4, "0x1::coin", 1_000).is_none());
+        fs::remove_dir_all(&folder).unwrap();
+    }
+}
@@ -0,0 +1,154 @@
+use crate::util::format_address;
+use anyhow::{bail, Context, Result};
+use libra_config::extension::global_config_ext::global_folder;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::PathBuf};
+use zapatos_sdk::types::account_address::AccountAddress;
+
+pub const ADDRESS_BOOK_FILE: &str = "addresses.yaml";
+
+/// Prefix of the names of the address book where an address is expected, as in `@alice`
+pub const NAME_PREFIX: char = '@';
+
+/// Names for account addresses, saved in the 0L config folder. It only ever holds
+/// public addresses, never keys
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AddressBook {
+    addresses: BTreeMap<String, String>,
+}
+
+impl AddressBook {
+    pub fn path() -> Result<PathBuf> {
+        Ok(global_folder()?.join(ADDRESS_BOOK_FILE))
+    }
+
+    /// The saved address book, empty when it was never saved
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path).context(format!(
+            "Failed to read the address book {}",
+            path.display()
+        ))?;
+        serde_yaml::from_str(&content).context(format!(
+            "Failed to parse the address book {}",
+            path.display()
+        ))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, serde_yaml::to_string(self)?).context(format!(
+            "Failed to write the address book {}",
+            path.display()
+        ))
+    }
+
+    /// Add or replace a name, returning the address it had before
+    pub fn add(&mut self, name: &str, address: AccountAddress) -> Result<Option<AccountAddress>> {
+        validate_name(name)?;
+        self.addresses
+            .insert(name.to_string(), format_address(&address))
+            .map(|previous| parse_saved_address(name, &previous))
+            .transpose()
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<AccountAddress> {
+        let address = self
+            .addresses
+            .remove(name)
+            .context(format!("No address named {name} in the address book"))?;
+        parse_saved_address(name, &address)
+    }
+
+    pub fn get(&self, name: &str) -> Result<AccountAddress> {
+        let address = self.addresses.get(name).context(format!(
+            "No address named {name} in the address book, add it with `txs address add {name} <ADDRESS>`"
+        ))?;
+        parse_saved_address(name, address)
+    }
+
+    /// The first name of the address by alphabetical order, to print it next to the address
+    pub fn name_of(&self, address: &AccountAddress) -> Option<&str> {
+        self.addresses
+            .iter()
+            .find(|(name, saved)| parse_saved_address(name, saved).ok().as_ref() == Some(address))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// The names and their addresses, sorted by name
+    pub fn entries(&self) -> Result<Vec<(String, AccountAddress)>> {
+        self.addresses
+            .iter()
+            .map(|(name, address)| Ok((name.to_owned(), parse_saved_address(name, address)?)))
+            .collect()
+    }
+}
+
+/// The address of `@name` from the address book, or the input as is when it is not a name
+pub fn resolve_address(address: &str) -> Result<String> {
+    match address.trim().strip_prefix(NAME_PREFIX) {
+        Some(name) => Ok(format_address(&AddressBook::load()?.get(name)?)),
+        None => Ok(address.to_string()),
+    }
+}
+
+/// The function id with a `@name` module address replaced by the address from the address book
+pub fn resolve_function_id(function_id: &str) -> Result<String> {
+    match function_id.split_once("::") {
+        Some((address, rest)) => Ok(format!("{}::{rest}", resolve_address(address)?)),
+        None => Ok(function_id.to_string()),
+    }
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        bail!("Invalid name {name}, use letters, digits, _ and - only");
+    }
+    if name.starts_with("0x") {
+        bail!("Invalid name {name}, it would be taken for an address");
+    }
+    Ok(())
+}
+
+fn parse_saved_address(name: &str, address: &str) -> Result<AccountAddress> {
+    AccountAddress::from_hex_literal(address).context(format!(
+        "Invalid address {address} for {name} in the address book"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_get_and_remove_names() {
+        let two = AccountAddress::from_hex_literal("0x2").unwrap();
+        let mut book = AddressBook::default();
+        assert!(book.add("alice", AccountAddress::ONE).unwrap().is_none());
+        assert_eq!(book.add("alice", two).unwrap(), Some(AccountAddress::ONE));
+        assert_eq!(book.get("alice").unwrap(), two);
+        assert!(book.get("bob").is_err());
+        assert_eq!(book.name_of(&two), Some("alice"));
+        assert_eq!(book.name_of(&AccountAddress::ONE), None);
+        assert!(book.add("0xabc", AccountAddress::ONE).is_err());
+        assert!(book.add("a b", AccountAddress::ONE).is_err());
+
+        let yaml = serde_yaml::to_string(&book).unwrap();
+        let book: AddressBook = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(book.entries().unwrap().len(), 1);
+
+        let mut book = book;
+        assert_eq!(book.remove("alice").unwrap(), two);
+        assert!(book.remove("alice").is_err());
+    }
+}
@@ -0,0 +1,733 @@
+use crate::{address_book::NAME_PREFIX, util::parse_address};
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{json, Value};
+use std::fmt;
+use zapatos_sdk::{
+    move_types::{
+        parser::{parse_transaction_arguments, parse_type_tag},
+        transaction_argument::convert_txn_args,
+        u256::U256,
+    },
+    rest_client::aptos_api_types::{MoveStructTag, MoveType},
+    types::{account_address::AccountAddress, transaction::TransactionArgument},
+};
+
+/// Arguments of a function call as given on the command line
+#[derive(Clone, Debug)]
+pub enum FunctionArgs {
+    /// Separated by commas, as in `0x1, true, 12, 24_u8, x"123456", "text", none, some(42)`
+    List(String),
+    /// A JSON array, each element typed by the function's ABI or given as `{"type": ..., "value": ...}`
+    Json(String),
+}
+
+impl FunctionArgs {
+    /// The arguments from the --args or the --args-json option, at most one of them is set
+    pub fn from_options(args: Option<String>, args_json: Option<String>) -> Option<Self> {
+        match (args, args_json) {
+            (_, Some(args_json)) => Some(Self::Json(args_json)),
+            (Some(args), None) => Some(Self::List(args)),
+            (None, None) => None,
+        }
+    }
+}
+
+/// One argument of an --args list
+#[derive(Clone, PartialEq)]
+pub enum ListArg {
+    /// An argument of the Move syntax, as in `0x1`, `true`, `24_u8` or `x"1234"`
+    Move {
+        text: String,
+        arg: TransactionArgument,
+    },
+    /// A single or double quoted string, for a `0x1::string::String` parameter
+    String(String),
+    /// `none`, for an empty `0x1::option::Option<T>`
+    None,
+    /// `some(<argument>)`, for an `0x1::option::Option<T>` holding a value
+    Some(Box<ListArg>),
+}
+
+impl ListArg {
+    /// BCS encoding of the argument. Strings encode like `vector<u8>` and options like vectors
+    /// of zero or one element, so no type information is needed
+    pub fn to_bcs(&self) -> Result<Vec<u8>> {
+        Ok(match self {
+            Self::Move { arg, .. } => convert_txn_args(&[arg.to_owned()]).remove(0),
+            Self::String(string) => bcs::to_bytes(string)?,
+            Self::None => vec![0],
+            Self::Some(inner) => {
+                let mut bytes = vec![1];
+                bytes.extend(inner.to_bcs()?);
+                bytes
+            }
+        })
+    }
+
+    /// The argument of a script, which only takes primitive values and byte vectors.
+    /// Strings are passed as their UTF-8 bytes
+    pub fn to_script_argument(&self) -> Result<TransactionArgument> {
+        match self {
+            Self::Move { arg, .. } => Ok(arg.to_owned()),
+            Self::String(string) => Ok(TransactionArgument::U8Vector(string.as_bytes().to_vec())),
+            Self::None | Self::Some(_) => bail!("Scripts cannot take option arguments"),
+        }
+    }
+
+    /// JSON encoding of the argument, as expected by the node's view function API
+    pub fn to_view_json(&self) -> Value {
+        match self {
+            Self::Move { arg, .. } => argument_view_json(arg),
+            Self::String(string) => json!(string),
+            Self::None => json!({ "vec": [] }),
+            Self::Some(inner) => json!({ "vec": [inner.to_view_json()] }),
+        }
+    }
+}
+
+/// JSON encoding of a Move argument. 64 bits and larger integers are strings in the API
+pub(crate) fn argument_view_json(arg: &TransactionArgument) -> Value {
+    match arg {
+        TransactionArgument::Bool(b) => json!(b),
+        TransactionArgument::U8(n) => json!(n),
+        TransactionArgument::U16(n) => json!(n),
+        TransactionArgument::U32(n) => json!(n),
+        TransactionArgument::U64(n) => json!(n.to_string()),
+        TransactionArgument::U128(n) => json!(n.to_string()),
+        TransactionArgument::U256(n) => json!(n.to_string()),
+        TransactionArgument::Address(address) => json!(address.to_hex_literal()),
+        TransactionArgument::U8Vector(bytes) => json!(format!("0x{}", hex::encode(bytes))),
+    }
+}
+
+// Printed as the argument of the Move syntax would be
+impl fmt::Debug for ListArg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Move { arg, .. } => write!(f, "{arg:?}"),
+            Self::String(string) => write!(f, "String({string:?})"),
+            Self::None => write!(f, "None"),
+            Self::Some(inner) => write!(f, "Some({inner:?})"),
+        }
+    }
+}
+
+/// Parse an --args list. Commas inside quotes and parentheses do not separate arguments
+pub fn parse_list_args(list: &str) -> Result<Vec<ListArg>> {
+    split_list(list)?
+        .iter()
+        .enumerate()
+        .map(|(index, token)| {
+            parse_list_arg(token).context(format!("Invalid argument at index {index}: {token}"))
+        })
+        .collect()
+}
+
+fn parse_list_arg(token: &str) -> Result<ListArg> {
+    let token = token.trim();
+    if token.starts_with('\'') || token.starts_with('"') {
+        return Ok(ListArg::String(unquote(token)?));
+    }
+    if token == "none" {
+        return Ok(ListArg::None);
+    }
+    if let Some(inner) = token
+        .strip_prefix("some(")
+        .and_then(|inner| inner.strip_suffix(')'))
+    {
+        return Ok(ListArg::Some(Box::new(parse_list_arg(inner)?)));
+    }
+    if token.starts_with(NAME_PREFIX) {
+        return Ok(ListArg::Move {
+            text: token.to_string(),
+            arg: TransactionArgument::Address(parse_address(token, "--args")?),
+        });
+    }
+    if let Some((literal, width)) = split_integer_suffix(token) {
+        return Ok(ListArg::Move {
+            text: token.to_string(),
+            arg: parse_integer_literal(literal, width)?,
+        });
+    }
+    let mut args = parse_transaction_arguments(token)?;
+    if args.len() != 1 {
+        bail!("Expected a single argument");
+    }
+    Ok(ListArg::Move {
+        text: token.to_string(),
+        arg: args.remove(0),
+    })
+}
+
+/// Split the list on the commas outside of quotes and parentheses
+fn split_list(list: &str) -> Result<Vec<String>> {
+    let mut tokens = vec![];
+    let mut token = String::new();
+    let mut quote = None;
+    let mut depth = 0;
+    let mut chars = list.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(_), '\\') => {
+                token.push(c);
+                if let Some(escaped) = chars.next() {
+                    token.push(escaped);
+                }
+                continue;
+            }
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '(' | '[') => depth += 1,
+            (None, ')' | ']') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                tokens.push(token.trim().to_string());
+                token.clear();
+                continue;
+            }
+            _ => {}
+        }
+        token.push(c);
+    }
+    if quote.is_some() {
+        bail!("Unterminated quote in the arguments: {list}");
+    }
+    if !token.trim().is_empty() || !tokens.is_empty() {
+        tokens.push(token.trim().to_string());
+    }
+    Ok(tokens)
+}
+
+/// The content of a quoted string, with `\\`, `\'`, `\"`, `\n` and `\t` escapes
+fn unquote(token: &str) -> Result<String> {
+    let quote = token.chars().next().unwrap_or_default();
+    let content = token[1..]
+        .strip_suffix(quote)
+        .context("Expected a closing quote")?;
+    let mut string = String::new();
+    let mut chars = content.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            string.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => string.push('\n'),
+            Some('t') => string.push('\t'),
+            Some(escaped @ ('\\' | '\'' | '"')) => string.push(escaped),
+            Some(escaped) => bail!("Unknown escape sequence \\{escaped}"),
+            None => bail!("Unterminated escape sequence"),
+        }
+    }
+    Ok(string)
+}
+
+/// Unsigned integer types of Move
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntegerWidth {
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+}
+
+const U256_MAX: &str =
+    "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+
+impl IntegerWidth {
+    const ALL: [Self; 6] = [
+        Self::U8,
+        Self::U16,
+        Self::U32,
+        Self::U64,
+        Self::U128,
+        Self::U256,
+    ];
+
+    fn of(ty: &MoveType) -> Option<Self> {
+        match ty {
+            MoveType::U8 => Some(Self::U8),
+            MoveType::U16 => Some(Self::U16),
+            MoveType::U32 => Some(Self::U32),
+            MoveType::U64 => Some(Self::U64),
+            MoveType::U128 => Some(Self::U128),
+            MoveType::U256 => Some(Self::U256),
+            _ => None,
+        }
+    }
+
+    fn bits(self) -> u32 {
+        match self {
+            Self::U8 => 8,
+            Self::U16 => 16,
+            Self::U32 => 32,
+            Self::U64 => 64,
+            Self::U128 => 128,
+            Self::U256 => 256,
+        }
+    }
+
+    fn max(self) -> String {
+        match self {
+            Self::U256 => U256_MAX.to_string(),
+            width => (u128::MAX >> (128 - width.bits())).to_string(),
+        }
+    }
+}
+
+impl fmt::Display for IntegerWidth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "u{}", self.bits())
+    }
+}
+
+/// Split a literal such as `1_000u64`, `0xff_u8` or `42` into its digits and width.
+/// Decimal literals without a suffix are u64, hex ones are addresses so not integers
+fn split_integer_suffix(token: &str) -> Option<(&str, IntegerWidth)> {
+    let (literal, width) = IntegerWidth::ALL
+        .iter()
+        .find_map(|width| {
+            token
+                .strip_suffix(&width.to_string())
+                .map(|literal| (literal.strip_suffix('_').unwrap_or(literal), *width))
+        })
+        .or_else(|| {
+            token
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == '_')
+                .then_some((token, IntegerWidth::U64))
+        })?;
+    literal
+        .starts_with(|c: char| c.is_ascii_digit())
+        .then_some((literal, width))
+}
+
+/// Parse a decimal or 0x-prefixed hex integer with optional `_` separators,
+/// failing when it does not fit the width
+pub fn parse_integer_literal(literal: &str, width: IntegerWidth) -> Result<TransactionArgument> {
+    let digits = literal.replace('_', "");
+    let (digits, radix) = match digits.strip_prefix("0x") {
+        Some(digits) => (digits, 16),
+        None => (digits.as_str(), 10),
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        bail!("Expected an unsigned integer, got {literal}");
+    }
+    let out_of_range = || {
+        anyhow!(
+            "{literal} is out of range for {width}, which holds 0 to {}",
+            width.max()
+        )
+    };
+    if width == IntegerWidth::U256 {
+        let value = U256::from_str_radix(digits, radix).map_err(|_| out_of_range())?;
+        return Ok(TransactionArgument::U256(value));
+    }
+    let value = u128::from_str_radix(digits, radix).map_err(|_| out_of_range())?;
+    if value >> (width.bits() - 1) >> 1 != 0 {
+        return Err(out_of_range());
+    }
+    Ok(match width {
+        IntegerWidth::U8 => TransactionArgument::U8(value as u8),
+        IntegerWidth::U16 => TransactionArgument::U16(value as u16),
+        IntegerWidth::U32 => TransactionArgument::U32(value as u32),
+        IntegerWidth::U64 => TransactionArgument::U64(value as u64),
+        _ => TransactionArgument::U128(value),
+    })
+}
+
+/// A function argument and the Move type it is encoded as
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypedArg {
+    pub ty: MoveType,
+    pub value: Value,
+}
+
+impl TypedArg {
+    /// BCS encoding of the argument, as expected in an entry function payload
+    pub fn to_bcs(&self) -> Result<Vec<u8>> {
+        let mut bytes = vec![];
+        encode_bcs(&self.ty, &self.value, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// JSON encoding of the argument, as expected by the node's view function API
+    pub fn to_view_json(&self) -> Result<Value> {
+        to_view_json(&self.ty, &self.value)
+    }
+}
+
+/// Parse a JSON array of arguments. Each element is either typed by `params`, the declared
+/// parameters of the function without its signers, or given explicitly as `{"type": ..., "value": ...}`
+pub fn parse_json_args(json: &str, params: Option<&[MoveType]>) -> Result<Vec<TypedArg>> {
+    let values: Vec<Value> = serde_json::from_str(json).context(format!(
+        "Invalid --args-json, expected a JSON array: {json}"
+    ))?;
+    if let Some(params) = params {
+        if values.len() != params.len() {
+            bail!(
+                "Expected {} argument(s), got {} in --args-json",
+                params.len(),
+                values.len()
+            );
+        }
+    }
+
+    let mut args = vec![];
+    for (index, value) in values.into_iter().enumerate() {
+        let arg = match explicit_type(&value) {
+            Some(ty) => {
+                let ty = parse_move_type(ty).context(format!("Argument at index {index}"))?;
+                TypedArg {
+                    ty,
+                    value: value["value"].to_owned(),
+                }
+            }
+            None => match params.and_then(|params| params.get(index)) {
+                Some(ty) => TypedArg {
+                    ty: ty.to_owned(),
+                    value,
+                },
+                None => bail!(
+                    "Argument at index {index} has no type, give it as {{\"type\": ..., \"value\": ...}}"
+                ),
+            },
+        };
+        // Encode once so that an invalid value is reported with its index
+        arg.to_bcs()
+            .context(format!("Invalid argument at index {index}"))?;
+        args.push(arg);
+    }
+    Ok(args)
+}
+
+/// A single value of the Move type `ty`, given as JSON or bare as in `0x1` or `text`. The value is
+/// checked against the type, e.g. for the key of a table item
+pub fn parse_typed_arg(ty: &str, value: &str) -> Result<TypedArg> {
+    let ty = parse_move_type(ty)?;
+    let is_string =
+        matches!(&ty, MoveType::Struct(tag) if struct_name(tag) == "0x1::string::String");
+    let value = match serde_json::from_str::<Value>(value) {
+        Ok(json) if json.is_string() || !is_string => json,
+        _ => Value::String(value.to_string()),
+    };
+    let arg = TypedArg { ty, value };
+    arg.to_view_json()
+        .context(format!("{} is not a valid {}", arg.value, arg.ty))?;
+    Ok(arg)
+}
+
+/// The type of an argument given as `{"type": ..., "value": ...}`
+fn explicit_type(value: &Value) -> Option<&str> {
+    let object = value.as_object()?;
+    if object.len() == 2 && object.contains_key("value") {
+        object.get("type")?.as_str()
+    } else {
+        None
+    }
+}
+
+/// A Move type as written in Move, e.g. `0x1::option::Option<u64>`
+pub fn parse_move_type(ty: &str) -> Result<MoveType> {
+    let type_tag = parse_type_tag(ty).context(format!("Invalid type: {ty}"))?;
+    Ok(MoveType::from(&type_tag))
+}
+
+fn struct_name(tag: &MoveStructTag) -> String {
+    format!("{}::{}::{}", tag.address, tag.module, tag.name)
+}
+
+fn encode_bcs(ty: &MoveType, value: &Value, bytes: &mut Vec<u8>) -> Result<()> {
+    match ty {
+        MoveType::Bool => {
+            let value = value
+                .as_bool()
+                .context(format!("Expected a bool, got {value}"))?;
+            bytes.push(value as u8);
+        }
+        ty if IntegerWidth::of(ty).is_some() => {
+            bytes.extend(convert_txn_args(&[parse_integer(value, ty)?]).remove(0))
+        }
+        MoveType::Address => bytes.extend(bcs::to_bytes(&json_address(value)?)?),
+        MoveType::Vector { items } if matches!(**items, MoveType::U8) && value.is_string() => {
+            bytes.extend(bcs::to_bytes(&parse_hex(value)?)?)
+        }
+        MoveType::Vector { items } => {
+            let values = value
+                .as_array()
+                .context(format!("Expected an array of {items}, got {value}"))?;
+            write_uleb128(values.len(), bytes);
+            for (index, value) in values.iter().enumerate() {
+                encode_bcs(items, value, bytes).context(format!("At vector index {index}"))?;
+            }
+        }
+        MoveType::Struct(tag) => match struct_name(tag).as_str() {
+            "0x1::string::String" => {
+                let value = value
+                    .as_str()
+                    .context(format!("Expected a string, got {value}"))?;
+                bytes.extend(bcs::to_bytes(value)?);
+            }
+            "0x1::option::Option" => {
+                let inner = tag
+                    .generic_type_params
+                    .first()
+                    .context("Option without a type parameter")?;
+                if value.is_null() {
+                    bytes.push(0);
+                } else {
+                    bytes.push(1);
+                    encode_bcs(inner, value, bytes)?;
+                }
+            }
+            "0x1::object::Object" => bytes.extend(bcs::to_bytes(&json_address(value)?)?),
+            _ => bail!("Arguments of type {ty} are not supported"),
+        },
+        _ => bail!("Arguments of type {ty} are not supported"),
+    }
+    Ok(())
+}
+
+/// The node takes 64 bit and larger integers as strings, bytes as hex and options as vectors
+fn to_view_json(ty: &MoveType, value: &Value) -> Result<Value> {
+    Ok(match ty {
+        ty if IntegerWidth::of(ty).is_some() => argument_view_json(&parse_integer(value, ty)?),
+        MoveType::Address => json!(json_address(value)?.to_hex_literal()),
+        MoveType::Vector { items } if matches!(**items, MoveType::U8) && value.is_string() => {
+            json!(format!("0x{}", hex::encode(parse_hex(value)?)))
+        }
+        MoveType::Vector { items } => {
+            let values = value
+                .as_array()
+                .context(format!("Expected an array of {items}, got {value}"))?;
+            Value::Array(
+                values
+                    .iter()
+                    .map(|value| to_view_json(items, value))
+                    .collect::<Result<_>>()?,
+            )
+        }
+        MoveType::Struct(tag) if struct_name(tag) == "0x1::option::Option" => {
+            let inner = tag
+                .generic_type_params
+                .first()
+                .context("Option without a type parameter")?;
+            if value.is_null() {
+                json!({ "vec": [] })
+            } else {
+                json!({ "vec": [to_view_json(inner, value)?] })
+            }
+        }
+        MoveType::Struct(tag) if struct_name(tag) == "0x1::object::Object" => {
+            json!(json_address(value)?.to_hex_literal())
+        }
+        _ => value.to_owned(),
+    })
+}
+
+/// An integer given as a JSON number, or as a string for the values JSON numbers cannot hold
+fn parse_integer(value: &Value, ty: &MoveType) -> Result<TransactionArgument> {
+    let width = IntegerWidth::of(ty).context(format!("{ty} is not an integer type"))?;
+    match value {
+        Value::Number(number) if number.is_u64() => {
+            parse_integer_literal(&number.to_string(), width)
+        }
+        Value::String(literal) => parse_integer_literal(literal, width),
+        _ => bail!("Expected an unsigned integer, got {value}"),
+    }
+}
+
+fn json_address(value: &Value) -> Result<AccountAddress> {
+    let address = value
+        .as_str()
+        .context(format!("Expected an address, got {value}"))?;
+    parse_address(address, "--args-json")
+}
+
+fn parse_hex(value: &Value) -> Result<Vec<u8>> {
+    let hex_string = value.as_str().unwrap_or_default();
+    hex::decode(hex_string.trim_start_matches("0x"))
+        .context(format!("Expected hex encoded bytes, got {value}"))
+}
+
+fn write_uleb128(mut value: usize, bytes: &mut Vec<u8>) {
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn move_type(ty: &str) -> MoveType {
+        parse_move_type(ty).unwrap()
+    }
+
+    fn bcs_of(json: &str, params: &[&str]) -> Vec<Vec<u8>> {
+        let params = params.iter().map(|ty| move_type(ty)).collect::<Vec<_>>();
+        parse_json_args(json, Some(&params))
+            .unwrap()
+            .iter()
+            .map(|arg| arg.to_bcs().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn parse_nested_byte_vectors() {
+        assert_eq!(
+            bcs_of(r#"[["0x0102", "0x"]]"#, &["vector<vector<u8>>"]),
+            vec![bcs::to_bytes(&vec![vec![1u8, 2], vec![]]).unwrap()]
+        );
+        assert_eq!(
+            bcs_of("[[]]", &["vector<vector<u8>>"]),
+            vec![bcs::to_bytes(&Vec::<Vec<u8>>::new()).unwrap()]
+        );
+    }
+
+    #[test]
+    fn parse_address_vectors() {
+        assert_eq!(
+            bcs_of(r#"[["0x1", "0x2"]]"#, &["vector<address>"]),
+            vec![bcs::to_bytes(&vec![
+                AccountAddress::ONE,
+                AccountAddress::from_hex_literal("0x2").unwrap()
+            ])
+            .unwrap()]
+        );
+    }
+
+    #[test]
+    fn parse_strings_with_commas() {
+        assert_eq!(
+            bcs_of(r#"["hello, world"]"#, &["0x1::string::String"]),
+            vec![bcs::to_bytes("hello, world").unwrap()]
+        );
+    }
+
+    #[test]
+    fn parse_booleans_and_integers() {
+        assert_eq!(
+            bcs_of(
+                r#"[true, false, 7, "18446744073709551615"]"#,
+                &["bool", "bool", "u8", "u64"]
+            ),
+            vec![vec![1], vec![0], vec![7], u64::MAX.to_le_bytes().to_vec()]
+        );
+    }
+
+    #[test]
+    fn parse_explicit_types() {
+        let args = parse_json_args(
+            r#"[{"type": "vector<u8>", "value": "0x1234"}, {"type": "u16", "value": 3}]"#,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            args[0].to_bcs().unwrap(),
+            bcs::to_bytes(&vec![0x12u8, 0x34]).unwrap()
+        );
+        assert_eq!(args[1].ty, MoveType::U16);
+        assert_eq!(args[1].to_bcs().unwrap(), vec![3, 0]);
+    }
+
+    #[test]
+    fn point_at_the_invalid_argument() {
+        let params = [MoveType::Bool, MoveType::U8];
+        let err = parse_json_args("[true, 256]", Some(&params)).unwrap_err();
+        assert!(format!("{err:#}").contains("index 1"));
+
+        let err = parse_json_args("[1]", None).unwrap_err();
+        assert!(err.to_string().contains("index 0"));
+
+        assert!(parse_json_args("[true]", Some(&params)).is_err());
+    }
+
+    #[test]
+    fn parse_strings_and_options_in_lists() {
+        let args =
+            parse_list_args(r#"'a, b', "say \"hi\"", none, some(42), some('x'), 0x1"#).unwrap();
+        assert_eq!(args[0], ListArg::String("a, b".to_string()));
+        assert_eq!(args[1], ListArg::String("say \"hi\"".to_string()));
+        assert_eq!(args[2], ListArg::None);
+        assert_eq!(args.len(), 6);
+
+        assert_eq!(args[0].to_bcs().unwrap(), bcs::to_bytes("a, b").unwrap());
+        assert_eq!(
+            args[2].to_bcs().unwrap(),
+            bcs::to_bytes(&None::<u64>).unwrap()
+        );
+        assert_eq!(
+            args[3].to_bcs().unwrap(),
+            bcs::to_bytes(&Some(42u64)).unwrap()
+        );
+        assert_eq!(
+            args[4].to_bcs().unwrap(),
+            bcs::to_bytes(&Some("x".to_string())).unwrap()
+        );
+        assert_eq!(args[3].to_view_json(), json!({ "vec": ["42"] }));
+
+        assert!(parse_list_args("'unterminated").is_err());
+        assert!(parse_list_args("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_integer_literals() {
+        let args = parse_list_args("1_000u64, 0xff_u8, 42, 0x1, 7u16").unwrap();
+        assert_eq!(args[0].to_bcs().unwrap(), 1000u64.to_le_bytes());
+        assert_eq!(args[1].to_bcs().unwrap(), [255]);
+        assert_eq!(args[2].to_bcs().unwrap(), 42u64.to_le_bytes());
+        // Hex without a suffix is an address
+        assert_eq!(args[3].to_bcs().unwrap(), AccountAddress::ONE.to_vec());
+        assert_eq!(args[4].to_view_json(), json!(7));
+
+        let err = parse_list_args("1, 256u8").unwrap_err();
+        assert!(format!("{err:#}").contains("256 is out of range for u8, which holds 0 to 255"));
+        assert!(parse_list_args("18446744073709551616").is_err());
+        assert!(parse_list_args("0xgg_u8").is_err());
+        let err = parse_json_args(r#"[65536]"#, Some(&[MoveType::U16])).unwrap_err();
+        assert!(format!("{err:#}").contains("out of range for u16, which holds 0 to 65535"));
+    }
+
+    #[test]
+    fn parse_table_keys() {
+        let key = parse_typed_arg("address", "0x1").unwrap();
+        assert_eq!(key.to_view_json().unwrap(), json!("0x1"));
+        let key = parse_typed_arg("u64", "42").unwrap();
+        assert_eq!(key.to_view_json().unwrap(), json!("42"));
+        // A string key may be bare, even when it reads as another JSON value
+        let key = parse_typed_arg("0x1::string::String", "true").unwrap();
+        assert_eq!(key.to_view_json().unwrap(), json!("true"));
+
+        let err = parse_typed_arg("u64", "abc").unwrap_err();
+        assert!(format!("{err:#}").contains("is not a valid u64"), "{err:#}");
+    }
+
+    #[test]
+    fn round_trip_u256() {
+        let max = U256_MAX;
+        for literal in ["0", "340282366920938463463374607431768211456", max] {
+            let arg = &parse_list_args(&format!("{literal}u256")).unwrap()[0];
+            let decoded: U256 = bcs::from_bytes(&arg.to_bcs().unwrap()).unwrap();
+            assert_eq!(decoded.to_string(), literal);
+            assert_eq!(arg.to_view_json(), json!(literal));
+        }
+
+        // 2^128 as 32 little endian bytes
+        let mut expected = vec![0; 32];
+        expected[16] = 1;
+        assert_eq!(
+            bcs_of(
+                r#"["0x1_0000_0000_0000_0000_0000_0000_0000_0000"]"#,
+                &["u256"]
+            ),
+            vec![expected]
+        );
+        let err = parse_list_args(&format!("{max}1u256")).unwrap_err();
+        assert!(format!("{err:#}").contains(&format!("which holds 0 to {max}")));
+    }
+}
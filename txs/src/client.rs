@@ -0,0 +1,590 @@
+use crate::{
+    args::FunctionArgs,
+    constant::{DEFAULT_POLL_INTERVAL_MS, DEFAULT_WAIT_TIMEOUT_SECS},
+    error::TxsError,
+    extension::client_ext::{ledger_version_error, ClientExt, TransactionOptions},
+    funds::{gas_payer, has_coin_store, max_fee, Funds},
+    fungible_asset,
+    move_value::decode_view_result,
+    node_api::NodeApi,
+    retry::{retry_read, submit_with_retry},
+    signer::TransactionSigner,
+    util::format_address,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use libra_config::extension::client_ext::new_client;
+use serde_json::Value;
+use std::time::Duration;
+use url::Url;
+use zapatos_sdk::{
+    crypto::{ed25519::Ed25519PrivateKey, HashValue},
+    move_types::{
+        identifier::Identifier,
+        language_storage::{ModuleId, TypeTag},
+        parser::parse_type_tag,
+    },
+    rest_client::{
+        aptos_api_types::{Transaction, ViewRequest},
+        error::RestError,
+        Client,
+    },
+    types::{
+        account_address::AccountAddress,
+        chain_id::ChainId,
+        transaction::{EntryFunction, SignedTransaction, TransactionPayload},
+        LocalAccount,
+    },
+};
+
+pub(crate) const COIN_TYPE: &str = "0x1::aptos_coin::AptosCoin";
+
+/// A committed transaction
+#[derive(Clone, Debug)]
+pub struct TxResult {
+    pub hash: HashValue,
+    pub version: u64,
+    pub gas_used: u64,
+    pub vm_status: String,
+    pub transaction: Transaction,
+}
+
+/// What a transfer moves
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Asset {
+    /// A coin, the native coin when None
+    Coin(Option<TypeTag>),
+    /// A fungible asset, by the address of its metadata object
+    Fungible(AccountAddress),
+}
+
+impl Asset {
+    pub fn payload(
+        &self,
+        to_account: AccountAddress,
+        base_amount: u64,
+    ) -> Result<TransactionPayload> {
+        match self {
+            Self::Coin(coin_type) => transfer_payload(to_account, base_amount, coin_type.clone()),
+            Self::Fungible(metadata) => {
+                fungible_asset::transfer_payload(*metadata, to_account, base_amount)
+            }
+        }
+    }
+}
+
+/// The operations of the CLI as a library: the results are returned instead of printed, and
+/// the failures are `TxsError`s, including a transaction which fails on-chain. Turn off the
+/// progress lines printed while signing with `txs::util::set_progress_output(false)`. The node
+/// is a `Client` but for tests, see `txs::node_api::MockNode`
+#[derive(Clone, Debug)]
+pub struct TxsClient<N = Client> {
+    client: N,
+    chain_id: ChainId,
+    wait_timeout: Duration,
+    poll_interval: Duration,
+}
+
+impl TxsClient {
+    /// A client of the node at `url`, which must be on chain `chain_id`. Nothing is sent yet.
+    /// The connections are shared with the other clients of the process
+    pub fn new(url: Url, chain_id: ChainId) -> Self {
+        // Only fails when the TLS backend cannot be set up, a client of its own may still work
+        let client = new_client(url.clone()).unwrap_or_else(|_| Client::new(url));
+        Self::from_client(client, chain_id)
+    }
+}
+
+impl<N: NodeApi> TxsClient<N> {
+    pub fn from_client(client: N, chain_id: ChainId) -> Self {
+        Self {
+            client,
+            chain_id,
+            wait_timeout: Duration::from_secs(DEFAULT_WAIT_TIMEOUT_SECS),
+            poll_interval: Duration::from_millis(DEFAULT_POLL_INTERVAL_MS),
+        }
+    }
+
+    /// A client on the chain the node reports
+    pub async fn connect(client: N) -> Result<Self, TxsError> {
+        let chain_id = retry_read(|| client.get_index())
+            .await
+            .context("Failed to get the chain id of the node")?
+            .into_inner()
+            .chain_id;
+        Ok(Self::from_client(client, ChainId::new(chain_id)))
+    }
+
+    /// How long `submit` waits for the transaction to be committed, and how often it asks
+    pub fn with_wait(mut self, timeout: Duration, poll_interval: Duration) -> Self {
+        self.wait_timeout = timeout;
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn rest_client(&self) -> &N {
+        &self.client
+    }
+
+    pub fn chain_id(&self) -> ChainId {
+        self.chain_id
+    }
+
+    /// Balance of the native coin, in base units
+    pub async fn balance(&self, address: AccountAddress) -> Result<u64, TxsError> {
+        match retry_read(|| self.client.get_account_balance(address)).await {
+            Ok(response) => Ok(response.into_inner()),
+            Err(RestError::Api(err)) if err.status_code.as_u16() == 404 => {
+                Err(TxsError::AccountNotFound(address))
+            }
+            Err(err) => Err(anyhow!(err)
+                .context(format!("Failed to get the balance of {address}"))
+                .into()),
+        }
+    }
+
+    /// Transfer `amount` base units of the native coin and wait for the transfer to be committed
+    pub async fn transfer(
+        &self,
+        to: AccountAddress,
+        amount: u64,
+        signer: &mut LocalAccount,
+        options: TransactionOptions,
+    ) -> Result<TxResult, TxsError> {
+        let payload = transfer_payload(to, amount, None)?;
+        let signed_trans = self.client.sign_payload(signer, payload, options).await?;
+        self.submit(&signed_trans).await
+    }
+
+    /// Decimals of the coin or of the fungible asset
+    pub async fn decimals(&self, asset: &Asset) -> Result<u8, TxsError> {
+        Ok(match asset {
+            Asset::Coin(coin_type) => {
+                self.client
+                    .get_coin_decimals(&native_or(coin_type.clone())?)
+                    .await?
+            }
+            Asset::Fungible(metadata) => fungible_asset::decimals(&self.client, *metadata).await?,
+        })
+    }
+
+    /// What the account holds of the asset, and of the native coin which pays the gas
+    pub async fn funds(&self, asset: &Asset, account: AccountAddress) -> Result<Funds, TxsError> {
+        Ok(match asset {
+            Asset::Coin(coin_type) => Funds::get(&self.client, account, coin_type.as_ref()).await?,
+            Asset::Fungible(metadata) => {
+                Funds::with_fungible_asset(&self.client, account, *metadata).await?
+            }
+        })
+    }
+
+    /// Sign the transfer of `amount` base units of the asset, or of all the spendable funds of the
+    /// signer when None. Returns the transaction with the amount it transfers. Nothing is
+    /// submitted, see `check_funds` and `submit`
+    pub async fn sign_transfer(
+        &self,
+        asset: &Asset,
+        to: AccountAddress,
+        amount: Option<u64>,
+        signer: &TransactionSigner,
+        options: TransactionOptions,
+    ) -> Result<(SignedTransaction, u64), TxsError> {
+        // The primary store of a fungible asset is created on the first transfer
+        if let Asset::Coin(Some(coin_type)) = asset {
+            check_coin_store(&self.client, to, coin_type).await?;
+        }
+        let (base_amount, options) = match amount {
+            Some(amount) => (amount, options),
+            None => {
+                let (base_amount, options, funds) =
+                    self.sweep_amount(asset, to, signer, options).await?;
+                // The amount is only right for the balance it was computed from
+                let sender = signer.address(&self.client).await?;
+                if self.funds(asset, sender).await? != funds {
+                    return Err(anyhow!("The balance of the sender changed while the amount was computed, nothing was sent. Run the command again").into());
+                }
+                (base_amount, options)
+            }
+        };
+        let payload = asset.payload(to, base_amount)?;
+        let signed_trans = signer.sign_payload(&self.client, payload, options).await?;
+        Ok((signed_trans, base_amount))
+    }
+
+    /// Fail when the sender of the transfer cannot pay its amount and its worst-case fee, as a
+    /// transfer which cannot be paid fails on-chain and still costs gas. The fee is checked
+    /// against the fee payer instead when there is one
+    pub async fn check_funds(
+        &self,
+        asset: &Asset,
+        signed_trans: &SignedTransaction,
+        base_amount: u64,
+    ) -> Result<(), TxsError> {
+        let gas_payer = gas_payer(signed_trans);
+        if gas_payer == signed_trans.sender() {
+            return Ok(self
+                .funds(asset, gas_payer)
+                .await?
+                .check(base_amount, max_fee(signed_trans))?);
+        }
+        self.funds(asset, signed_trans.sender())
+            .await?
+            .check(base_amount, 0)?;
+        Ok(self
+            .funds(&Asset::Coin(None), gas_payer)
+            .await?
+            .check(0, max_fee(signed_trans))
+            .context(format!(
+                "The fee payer {} cannot pay the gas",
+                format_address(&gas_payer)
+            ))?)
+    }
+
+    /// The amount which leaves the sender with nothing spendable, with the gas options the
+    /// transfer must be signed with so that the fee cannot exceed the one deducted. The gas is
+    /// simulated with no buffer, any gas left unused stays on the account
+    async fn sweep_amount(
+        &self,
+        asset: &Asset,
+        to: AccountAddress,
+        signer: &TransactionSigner,
+        options: TransactionOptions,
+    ) -> Result<(u64, TransactionOptions, Funds)> {
+        let sender = signer.address(&self.client).await?;
+        let funds = self.funds(asset, sender).await?;
+        let gas_unit_price = match options.gas_unit_price {
+            Some(gas_unit_price) => gas_unit_price,
+            None => {
+                self.client
+                    .estimate_gas_unit_price(options.gas_priority)
+                    .await
+            }
+        };
+        let max_gas_amount = match (options.max_gas_amount, signer) {
+            (Some(max_gas_amount), _) => max_gas_amount,
+            (None, TransactionSigner::PrivateKey(_)) => {
+                // The gas of a transfer does not depend on its amount. The probe is never submitted
+                let probe = asset.payload(to, 1)?;
+                let probe_options = TransactionOptions {
+                    gas_unit_price: Some(gas_unit_price),
+                    gas_buffer_percent: 0,
+                    ..options.clone()
+                };
+                signer
+                    .sign_payload(&self.client, probe, probe_options)
+                    .await?
+                    .max_gas_amount()
+            }
+            (None, TransactionSigner::Ledger(_)) => {
+                bail!("--all with --ledger needs --max-gas, the gas cannot be simulated without the key")
+            }
+        };
+
+        let base_amount = funds
+            .spendable(max_gas_amount.saturating_mul(gas_unit_price))
+            .context("Nothing was sent")?;
+        let options = TransactionOptions {
+            max_gas_amount: Some(max_gas_amount),
+            gas_unit_price: Some(gas_unit_price),
+            ..options
+        };
+        Ok((base_amount, options, funds))
+    }
+
+    /// Sign a call to an entry function, checked against the ABI of its module
+    pub async fn generate_entry_function(
+        &self,
+        signer: &mut LocalAccount,
+        function_id: &str,
+        type_args: Option<String>,
+        args: Option<FunctionArgs>,
+        options: TransactionOptions,
+    ) -> Result<SignedTransaction, TxsError> {
+        Ok(self
+            .client
+            .generate_transaction(signer, &[], function_id, type_args, args, options, true)
+            .await?)
+    }
+
+    /// Sign a call to an entry function as `signer`, and as the secondary signers of a
+    /// multi-agent transaction when there are any. The arguments are checked against the ABI of
+    /// the module unless `check_abi` is false
+    #[allow(clippy::too_many_arguments)]
+    pub async fn sign_entry_function(
+        &self,
+        signer: &TransactionSigner,
+        secondary_private_keys: &[Ed25519PrivateKey],
+        function_id: &str,
+        type_args: Option<String>,
+        args: Option<FunctionArgs>,
+        options: TransactionOptions,
+        check_abi: bool,
+    ) -> Result<SignedTransaction, TxsError> {
+        let payload = self
+            .client
+            .entry_function_payload_ext(
+                function_id,
+                type_args,
+                args,
+                check_abi,
+                secondary_private_keys.len() + 1,
+            )
+            .await?;
+        Ok(signer
+            .sign_multi_agent_payload(&self.client, secondary_private_keys, payload, options)
+            .await?)
+    }
+
+    /// Submit the transaction and wait for it to be committed
+    pub async fn submit(&self, signed_trans: &SignedTransaction) -> Result<TxResult, TxsError> {
+        let hash = self.submit_no_wait(signed_trans).await?;
+        self.wait(hash, signed_trans.expiration_timestamp_secs())
+            .await
+    }
+
+    /// Submit the transaction without waiting for it, returning its hash
+    pub async fn submit_no_wait(
+        &self,
+        signed_trans: &SignedTransaction,
+    ) -> Result<HashValue, TxsError> {
+        self.check(signed_trans)?;
+        Ok(submit_with_retry(&self.client, signed_trans).await?)
+    }
+
+    /// Wait for a submitted transaction to be committed. Fails with `TxsError::Timeout` when it
+    /// is still pending after the wait timeout
+    pub async fn wait(
+        &self,
+        hash: HashValue,
+        expiration_timestamp_secs: u64,
+    ) -> Result<TxResult, TxsError> {
+        let transaction = self
+            .client
+            .wait_for_transaction_ext(
+                hash,
+                expiration_timestamp_secs,
+                self.wait_timeout,
+                self.poll_interval,
+            )
+            .await?;
+        let info = transaction.transaction_info().map_err(TxsError::Other)?;
+        Ok(TxResult {
+            hash,
+            version: u64::from(info.version),
+            gas_used: u64::from(info.gas_used),
+            vm_status: info.vm_status.to_owned(),
+            transaction,
+        })
+    }
+
+    /// Execute a View function, its results decoded with the ABI of the module. Arguments given
+    /// as JSON are typed with the ABI of the function
+    pub async fn view(
+        &self,
+        function_id: &str,
+        type_args: Option<String>,
+        args: Option<FunctionArgs>,
+        version: Option<u64>,
+    ) -> Result<Value, TxsError> {
+        let request = self.view_request(function_id, type_args, args).await?;
+        Ok(Value::Array(
+            self.execute_view(&request, version, false).await?,
+        ))
+    }
+
+    /// The request `view` sends, with the arguments given as JSON typed with the ABI of the
+    /// function
+    pub async fn view_request(
+        &self,
+        function_id: &str,
+        type_args: Option<String>,
+        args: Option<FunctionArgs>,
+    ) -> Result<ViewRequest, TxsError> {
+        Ok(self
+            .client
+            .view_request(function_id, type_args, args)
+            .await?)
+    }
+
+    /// Execute the View request at the ledger version, the latest one when None. The results
+    /// are decoded with the ABI of the module, unless `raw` as returned by the node
+    pub async fn execute_view(
+        &self,
+        request: &ViewRequest,
+        version: Option<u64>,
+        raw: bool,
+    ) -> Result<Vec<Value>, TxsError> {
+        let result = match version {
+            Some(version) => retry_read(|| self.client.view(request, Some(version)))
+                .await
+                .map_err(|err| {
+                    ledger_version_error(
+                        err,
+                        &format!("Failed to execute View request at ledger version {version}"),
+                    )
+                })?,
+            None => retry_read(|| self.client.view(request, None))
+                .await
+                .context("Failed to execute View request")?,
+        }
+        .into_inner();
+        if raw {
+            return Ok(result);
+        }
+        Ok(decode_view_result(&self.client, request, &result).await?)
+    }
+
+    /// Make sure the node would not reject the transaction outright, its expiration being
+    /// checked by `submit_with_retry`
+    fn check(&self, signed_trans: &SignedTransaction) -> Result<()> {
+        if signed_trans.chain_id() != self.chain_id {
+            bail!(
+                "The transaction was signed for chain id {}, but the node is on chain id {}",
+                signed_trans.chain_id().id(),
+                self.chain_id.id()
+            );
+        }
+        Ok(())
+    }
+}
+
+pub fn parse_coin_type(coin_type: &str) -> Result<TypeTag> {
+    parse_type_tag(coin_type).context(format!("Invalid coin type: {coin_type}"))
+}
+
+/// The given coin type, or the native coin when there is none
+pub fn native_or(coin_type: Option<TypeTag>) -> Result<TypeTag> {
+    match coin_type {
+        Some(coin_type) => Ok(coin_type),
+        None => parse_coin_type(COIN_TYPE),
+    }
+}
+
+/// Unlike the native coin, other coins are not registered automatically when received
+pub async fn check_coin_store(
+    client: &impl NodeApi,
+    account: AccountAddress,
+    coin_type: &TypeTag,
+) -> Result<()> {
+    if !has_coin_store(client, account, coin_type).await? {
+        bail!("The recipient {account} has not registered {coin_type}, it must register it before it can receive any: `txs register-coin --coin-type {coin_type}` signed by the recipient");
+    }
+    Ok(())
+}
+
+/// The native coin uses the same entry function as before, other coins use `0x1::coin::transfer`
+pub fn transfer_payload(
+    to_account: AccountAddress,
+    base_amount: u64,
+    coin_type: Option<TypeTag>,
+) -> Result<TransactionPayload> {
+    let args = vec![bcs::to_bytes(&to_account)?, bcs::to_bytes(&base_amount)?];
+    let entry_function = match coin_type {
+        Some(coin_type) => EntryFunction::new(
+            ModuleId::new(AccountAddress::ONE, Identifier::new("coin")?),
+            Identifier::new("transfer")?,
+            vec![coin_type],
+            args,
+        ),
+        None => EntryFunction::new(
+            ModuleId::new(AccountAddress::ONE, Identifier::new("aptos_account")?),
+            Identifier::new("transfer_coins")?,
+            vec![parse_coin_type(COIN_TYPE)?],
+            args,
+        ),
+    };
+    Ok(TransactionPayload::EntryFunction(entry_function))
+}
+
+/// `0x1::aptos_account::transfer`, which creates the recipient account when it does not exist
+pub fn create_account_payload(
+    account_address: AccountAddress,
+    base_amount: u64,
+) -> Result<TransactionPayload> {
+    Ok(TransactionPayload::EntryFunction(EntryFunction::new(
+        ModuleId::new(AccountAddress::ONE, Identifier::new("aptos_account")?),
+        Identifier::new("transfer")?,
+        vec![],
+        vec![
+            bcs::to_bytes(&account_address)?,
+            bcs::to_bytes(&base_amount)?,
+        ],
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::transaction_hash;
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        str::FromStr,
+        thread,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+    use zapatos_sdk::{
+        crypto::{ed25519::Ed25519PrivateKey, PrivateKey},
+        rest_client::FaucetClient,
+        types::transaction::authenticator::AuthenticationKey,
+    };
+
+    #[tokio::test]
+    async fn account_not_found_on_balance() {
+        // Answers every request with the 404 of a missing account
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let _ = stream.read(&mut [0; 4096]);
+                let body = r#"{"message":"Account not found","error_code":"account_not_found","vm_error_code":null}"#;
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 404 Not Found\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+            }
+        });
+
+        let url = Url::from_str(&format!("http://{address}")).unwrap();
+        let client = TxsClient::new(url, ChainId::test());
+        let err = client.balance(AccountAddress::ONE).await.unwrap_err();
+        assert!(
+            matches!(err, TxsError::AccountNotFound(address) if address == AccountAddress::ONE),
+            "{err:?}"
+        );
+    }
+
+    // Needs a local testnet with its faucet, as in the demo of the README
+    #[tokio::test]
+    #[ignore]
+    async fn local_hash_matches_the_node() {
+        let url = Url::from_str("http://localhost:8080").unwrap();
+        let faucet =
+            FaucetClient::new(Url::from_str("http://localhost:8081").unwrap(), url.clone());
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let seed = HashValue::sha3_256_of(&now.as_nanos().to_le_bytes());
+        let private_key = Ed25519PrivateKey::try_from(seed.as_ref()).unwrap();
+        let address = AuthenticationKey::ed25519(&private_key.public_key()).derived_address();
+        faucet.fund(address, 100_000_000).await.unwrap();
+
+        let client = TxsClient::connect(Client::new(url)).await.unwrap();
+        let mut sender = LocalAccount::new(address, private_key, 0);
+        let payload = transfer_payload(AccountAddress::ONE, 1, None).unwrap();
+        let signed_trans = client
+            .rest_client()
+            .sign_payload(&mut sender, payload, TransactionOptions::default())
+            .await
+            .unwrap();
+        let hash = client.submit_no_wait(&signed_trans).await.unwrap();
+        assert_eq!(hash, transaction_hash(&signed_trans));
+        let result = client
+            .wait(hash, signed_trans.expiration_timestamp_secs())
+            .await
+            .unwrap();
+        let info = result.transaction.transaction_info().unwrap();
+        assert_eq!(
+            info.hash.to_string(),
+            transaction_hash(&signed_trans).to_hex_literal()
+        );
+    }
+}
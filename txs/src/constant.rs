@@ -1,2 +1,68 @@
 pub const DEFAULT_MAX_GAS_AMOUNT: u64 = 5_000;
 pub const DEFAULT_GAS_UNIT_PRICE: u64 = 100;
+pub const DEFAULT_GAS_BUFFER_PERCENT: u64 = 20;
+pub const DEFAULT_WAIT_TIMEOUT_SECS: u64 = 60;
+pub const DEFAULT_POLL_INTERVAL_MS: u64 = 500;
+/// Exit code of the errors which fit none of the other exit codes
+pub const EXIT_CODE_FAILURE: i32 = 1;
+/// Exit code of invalid command line arguments, as set by clap
+pub const EXIT_CODE_INVALID_ARGUMENTS: i32 = 2;
+/// Exit code when the node could not be reached, timed out or failed on its side
+pub const EXIT_CODE_NETWORK: i32 = 3;
+/// Exit code when the account, resource, module or transaction looked up is not on-chain
+pub const EXIT_CODE_NOT_FOUND: i32 = 4;
+/// Exit code when the node did not accept the transaction into its mempool
+pub const EXIT_CODE_REJECTED: i32 = 5;
+/// Exit code when the transaction was committed but aborted on-chain
+pub const EXIT_CODE_ABORTED: i32 = 6;
+/// Exit code when the transaction was submitted but not seen committed before the wait timeout
+pub const EXIT_CODE_WAIT_TIMEOUT: i32 = 7;
+/// Exit code when the transaction expired before it was submitted
+pub const EXIT_CODE_EXPIRED: i32 = 8;
+/// Highest exit code of `txs selftest`, which exits with the number of failed checks
+pub const MAX_FAILED_CHECKS_EXIT_CODE: i32 = 125;
+/// Largest transaction the network accepts, in bytes
+pub const MAX_TRANSACTION_SIZE_BYTES: usize = 64 * 1024;
+/// Highest proof-of-fee bid, in basis points of the validator's reward
+pub const MAX_POF_BID_BPS: u64 = 10_000;
+/// Time for the fee payer to countersign a sponsored transaction before it expires
+pub const DEFAULT_COUNTERSIGN_EXPIRATION_SECS: u64 = 60 * 60;
+/// Time to bring an unsigned transaction to the signing machine and back before it expires
+pub const DEFAULT_UNSIGNED_EXPIRATION_SECS: u64 = 60 * 60;
+/// Time to review and approve a transaction on a Ledger device before it expires
+pub const DEFAULT_LEDGER_EXPIRATION_SECS: u64 = 5 * 60;
+/// Environment variable with the private key to sign with when no key flag is given
+pub const PRIVATE_KEY_ENV: &str = "TXS_PRIVATE_KEY";
+/// Fullnode of `--network mainnet`
+pub const MAINNET_URL: &str = "https://rpc.openlibra.space:8080";
+/// Fullnode of `--network testnet`
+pub const TESTNET_URL: &str = "https://testnet.openlibra.space:8080";
+/// How far the ledger of the node may be behind the clock before doctor reports it as lagging
+pub const DOCTOR_MAX_LAG_SECS: u64 = 60;
+/// Retries of a failed request to the node, see --max-retries
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+/// Most independent requests sent to a node at once by the commands which fan out, e.g. a
+/// balance per account
+pub const MAX_REQUESTS_IN_FLIGHT: usize = 8;
+/// Delay before the first retry, doubled on each of the next ones
+pub const RETRY_BASE_DELAY_MS: u64 = 500;
+/// Delay before the first retry of a rate limited request
+pub const RATE_LIMITED_RETRY_DELAY_MS: u64 = 2_000;
+/// Longest delay between two retries
+pub const RETRY_MAX_DELAY_MS: u64 = 30_000;
+/// How far behind the clock the ledger of the node may be before a warning is printed
+pub const STALENESS_WARNING_SECS: u64 = 30;
+/// How far behind the clock the ledger of the node may be before it is refused, see --max-staleness-secs
+pub const DEFAULT_MAX_STALENESS_SECS: u64 = 5 * 60;
+/// Decimals of the native coin, which pays the gas
+pub const NATIVE_COIN_DECIMALS: u8 = 6;
+/// Amount `txs demo` transfers by default, in base units
+pub const DEMO_TRANSFER_AMOUNT: u64 = 1_000;
+/// Amount the faucet funds the fresh sender of `txs demo` with by default, in base units
+pub const DEMO_FUND_AMOUNT: u64 = 100_000_000;
+/// How long a module stays in the ABI cache before it is fetched again
+pub const ABI_CACHE_TTL_SECS: u64 = 24 * 60 * 60;
+/// How long to wait for another txs process to release a shared file, e.g. a nonce file
+pub const LOCK_TIMEOUT_SECS: u64 = 30;
+/// Epochs after which a vouch no longer counts, as in 0x1::vouch of the 0L framework
+pub const VOUCH_EXPIRATION_EPOCHS: u64 = 45;
@@ -0,0 +1,247 @@
+use crate::{
+    constant::{
+        EXIT_CODE_ABORTED, EXIT_CODE_EXPIRED, EXIT_CODE_FAILURE, EXIT_CODE_INVALID_ARGUMENTS,
+        EXIT_CODE_NETWORK, EXIT_CODE_NOT_FOUND, EXIT_CODE_REJECTED, EXIT_CODE_WAIT_TIMEOUT,
+        MAX_FAILED_CHECKS_EXIT_CODE,
+    },
+    exit_code::ChecksFailedError,
+    extension::client_ext::{TransactionFailedError, WaitTimeoutError},
+};
+use libra_config::extension::client_ext::timeout_error;
+use std::{fmt, time::Duration};
+use zapatos_sdk::{
+    crypto::HashValue, rest_client::error::RestError, types::account_address::AccountAddress,
+};
+
+/// The failures of the library API, to match on instead of error messages. The CLI turns
+/// them into `anyhow::Error`s, and into exit codes with `exit_code`
+#[derive(Debug)]
+pub enum TxsError {
+    InvalidKey(String),
+    InvalidAddress(String),
+    AccountNotFound(AccountAddress),
+    /// Anything else the node has not got, e.g. a resource, a module or a transaction
+    NotFound(String),
+    /// The node answered with an error, `body` is its error message
+    RestError {
+        status: u16,
+        body: String,
+    },
+    /// The node did not take the transaction into its mempool
+    Rejected(String),
+    /// The transaction expired before it was submitted, so it was not
+    Expired {
+        ago_secs: u64,
+    },
+    /// The simulation of the transaction to estimate its gas failed
+    SimulationFailed {
+        vm_status: String,
+    },
+    /// The transaction was committed but a Move module aborted it
+    Aborted {
+        location: String,
+        code: u64,
+        vm_status: String,
+    },
+    /// The transaction was committed but failed otherwise, e.g. out of gas
+    ExecutionFailed {
+        vm_status: String,
+    },
+    /// The transaction was not committed before the wait timeout, it may still be later
+    Timeout {
+        hash: HashValue,
+        timeout: Duration,
+    },
+    /// The node could not be reached or did not answer in time
+    Network(String),
+    Other(anyhow::Error),
+}
+
+impl TxsError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            TxsError::InvalidKey(_) | TxsError::InvalidAddress(_) => EXIT_CODE_INVALID_ARGUMENTS,
+            TxsError::AccountNotFound(_) | TxsError::NotFound(_) => EXIT_CODE_NOT_FOUND,
+            TxsError::Rejected(_) => EXIT_CODE_REJECTED,
+            TxsError::Expired { .. } => EXIT_CODE_EXPIRED,
+            TxsError::Aborted { .. } | TxsError::ExecutionFailed { .. } => EXIT_CODE_ABORTED,
+            TxsError::Timeout { .. } => EXIT_CODE_WAIT_TIMEOUT,
+            TxsError::Network(_) => EXIT_CODE_NETWORK,
+            TxsError::RestError { status, .. } if *status == 429 || *status >= 500 => {
+                EXIT_CODE_NETWORK
+            }
+            TxsError::RestError { .. } | TxsError::SimulationFailed { .. } => EXIT_CODE_FAILURE,
+            TxsError::Other(err) => err
+                .chain()
+                .find_map(|cause| cause.downcast_ref::<ChecksFailedError>())
+                .map_or(EXIT_CODE_FAILURE, |err| {
+                    err.failed.min(MAX_FAILED_CHECKS_EXIT_CODE as usize) as i32
+                }),
+        }
+    }
+}
+
+impl fmt::Display for TxsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TxsError::InvalidKey(message)
+            | TxsError::InvalidAddress(message)
+            | TxsError::NotFound(message)
+            | TxsError::Network(message) => write!(f, "{message}"),
+            TxsError::AccountNotFound(address) => {
+                write!(f, "Account {} not found", address.to_hex_literal())
+            }
+            TxsError::RestError { status, body } => {
+                write!(f, "The node failed with status {status}: {body}")
+            }
+            TxsError::Rejected(message) => {
+                write!(f, "The node rejected the transaction: {message}")
+            }
+            TxsError::Expired { ago_secs } => write!(
+                f,
+                "The transaction expired {ago_secs}s ago and was not submitted, generate it again with a later --expiration-secs or --expire-at"
+            ),
+            TxsError::SimulationFailed { vm_status } => write!(
+                f,
+                "Unable to estimate the max gas, the simulated transaction failed with VM status: {vm_status}"
+            ),
+            TxsError::Aborted { vm_status, .. } | TxsError::ExecutionFailed { vm_status } => {
+                write!(f, "The transaction failed with VM status: {vm_status}")
+            }
+            TxsError::Timeout { hash, timeout } => write!(
+                f,
+                "Transaction {} was not committed after {}s, check its status again later",
+                hash.to_hex_literal(),
+                timeout.as_secs()
+            ),
+            TxsError::Other(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for TxsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TxsError::Other(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Categorize the errors of the anyhow based internals by the first known error of the chain
+impl From<anyhow::Error> for TxsError {
+    fn from(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<TxsError>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+        let category = err.chain().find_map(|cause| {
+            if let Some(timeout) = cause.downcast_ref::<WaitTimeoutError>() {
+                Some(TxsError::Timeout {
+                    hash: timeout.hash,
+                    timeout: timeout.timeout,
+                })
+            } else if let Some(failed) = cause.downcast_ref::<TransactionFailedError>() {
+                Some(match parse_abort(&failed.vm_status) {
+                    Some((location, code)) => TxsError::Aborted {
+                        location,
+                        code,
+                        vm_status: failed.vm_status.to_owned(),
+                    },
+                    None => TxsError::ExecutionFailed {
+                        vm_status: failed.vm_status.to_owned(),
+                    },
+                })
+            } else if let Some(err) = cause.downcast_ref::<RestError>() {
+                from_rest_error(err)
+            } else {
+                cause
+                    .downcast_ref::<reqwest::Error>()
+                    .filter(|err| err.is_connect() || err.is_timeout())
+                    .map(|err| TxsError::Network(err.to_string()))
+            }
+        });
+        match category {
+            // The message of a timed out request names the node and the timeout
+            Some(TxsError::Network(message)) => TxsError::Network(
+                timeout_error(&err).map_or(message, |timeout| timeout.to_string()),
+            ),
+            Some(category) => category,
+            None => TxsError::Other(err),
+        }
+    }
+}
+
+fn from_rest_error(err: &RestError) -> Option<TxsError> {
+    match err {
+        RestError::Api(response) => {
+            let body = response.error.message.to_owned();
+            Some(match response.status_code.as_u16() {
+                404 => TxsError::NotFound(body),
+                status => TxsError::RestError { status, body },
+            })
+        }
+        RestError::Http(status, err) => Some(match status.as_u16() {
+            404 => TxsError::NotFound(err.to_string()),
+            status => TxsError::RestError {
+                status,
+                body: err.to_string(),
+            },
+        }),
+        RestError::Timeout(_) => Some(TxsError::Network(err.to_string())),
+        RestError::Unknown(unknown) => unknown
+            .downcast_ref::<reqwest::Error>()
+            .filter(|err| err.is_connect() || err.is_timeout())
+            .map(|_| TxsError::Network(err.to_string())),
+        _ => None,
+    }
+}
+
+/// The module and the code of a Move abort, named or not, as in
+/// `Move abort in 0x1::coin: EINSUFFICIENT_BALANCE(0x10006): Not enough coins`
+fn parse_abort(vm_status: &str) -> Option<(String, u64)> {
+    let (location, rest) = vm_status.strip_prefix("Move abort in ")?.split_once(": ")?;
+    let code = match rest.split_once('(') {
+        Some((_, named)) => named.split_once(')')?.0,
+        None => rest,
+    };
+    let code = u64::from_str_radix(code.trim().strip_prefix("0x")?, 16).ok()?;
+    Some((location.to_string(), code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Context};
+
+    #[test]
+    fn categorize_errors() {
+        let aborted = TxsError::from(anyhow!(TransactionFailedError {
+            hash: HashValue::zero(),
+            vm_status: "Move abort in 0x1::coin: EINSUFFICIENT_BALANCE(0x10006): Not enough coins"
+                .to_string(),
+        }));
+        assert!(
+            matches!(aborted, TxsError::Aborted { ref location, code: 0x10006, .. } if location == "0x1::coin")
+        );
+        assert_eq!(aborted.exit_code(), EXIT_CODE_ABORTED);
+        assert_eq!(
+            parse_abort("Move abort in 0x1::slow_wallet: 0x30008"),
+            Some(("0x1::slow_wallet".to_string(), 0x30008))
+        );
+
+        let expired = TxsError::from(anyhow!(TxsError::Expired { ago_secs: 5 }));
+        assert_eq!(expired.exit_code(), EXIT_CODE_EXPIRED);
+
+        let invalid = anyhow!(TxsError::InvalidAddress("Invalid address".to_string()))
+            .context("Failed to read the recipients");
+        assert!(matches!(
+            TxsError::from(invalid),
+            TxsError::InvalidAddress(_)
+        ));
+        assert!(matches!(
+            TxsError::from(anyhow!("bad input")),
+            TxsError::Other(_)
+        ));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::error::TxsError;
+use std::fmt;
+
+/// The exit codes listed in `txs --help`
+pub const EXIT_CODES_HELP: &str = "Exit codes:
+  1  any other error
+  2  invalid arguments
+  3  the node could not be reached, timed out or failed on its side
+  4  the account, resource, module or transaction is not on-chain
+  5  the transaction was rejected by the mempool of the node
+  6  the transaction was committed but aborted on-chain
+  7  the transaction was submitted but not committed before the wait timeout
+  8  the transaction expired before it was submitted
+`txs selftest` exits with the number of failed checks instead, up to 125";
+
+/// Some checks of `txs selftest` failed, the exit code is their number
+#[derive(Debug)]
+pub struct ChecksFailedError {
+    pub failed: usize,
+}
+
+impl fmt::Display for ChecksFailedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} check(s) failed", self.failed)
+    }
+}
+
+impl std::error::Error for ChecksFailedError {}
+
+/// The exit code of the category of the error, as `TxsError` sorts it
+pub fn exit_code(err: anyhow::Error) -> i32 {
+    TxsError::from(err).exit_code()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constant::{
+            EXIT_CODE_ABORTED, EXIT_CODE_EXPIRED, EXIT_CODE_FAILURE, EXIT_CODE_NETWORK,
+            EXIT_CODE_NOT_FOUND, EXIT_CODE_REJECTED, EXIT_CODE_WAIT_TIMEOUT,
+            MAX_FAILED_CHECKS_EXIT_CODE,
+        },
+        extension::client_ext::TransactionFailedError,
+    };
+    use anyhow::{anyhow, Context};
+    use reqwest::StatusCode;
+    use zapatos_sdk::{
+        crypto::HashValue,
+        rest_client::{
+            aptos_api_types::{AptosError, AptosErrorCode},
+            error::{AptosErrorResponse, RestError},
+        },
+    };
+
+    fn api_error(status_code: StatusCode, error_code: AptosErrorCode) -> anyhow::Error {
+        anyhow!(RestError::Api(AptosErrorResponse {
+            error: AptosError {
+                message: "error".to_string(),
+                error_code,
+                vm_error_code: None,
+            },
+            state: None,
+            status_code,
+        }))
+    }
+
+    #[test]
+    fn map_errors_to_exit_codes() {
+        let not_found = api_error(StatusCode::NOT_FOUND, AptosErrorCode::AccountNotFound)
+            .context("Failed to get the account");
+        assert_eq!(exit_code(not_found), EXIT_CODE_NOT_FOUND);
+        // Only a submission is rejected, the VM error of a view function is not
+        let view_failed = api_error(StatusCode::BAD_REQUEST, AptosErrorCode::VmError)
+            .context("Failed to call the view function");
+        assert_eq!(exit_code(view_failed), EXIT_CODE_FAILURE);
+        let rejected = anyhow!(TxsError::Rejected("error".to_string()))
+            .context("Failed to submit the transaction");
+        assert_eq!(exit_code(rejected), EXIT_CODE_REJECTED);
+        let aborted = anyhow!(TransactionFailedError {
+            hash: HashValue::zero(),
+            vm_status: "Move abort".to_string(),
+        });
+        assert_eq!(exit_code(aborted), EXIT_CODE_ABORTED);
+        let timeout = anyhow!(RestError::Timeout("request"));
+        assert_eq!(exit_code(timeout), EXIT_CODE_NETWORK);
+        assert_eq!(exit_code(anyhow!("bad input")), EXIT_CODE_FAILURE);
+        assert_eq!(exit_code(anyhow!(ChecksFailedError { failed: 3 })), 3);
+        assert_eq!(
+            exit_code(anyhow!(ChecksFailedError { failed: 300 })),
+            MAX_FAILED_CHECKS_EXIT_CODE
+        );
+    }
+
+    #[test]
+    fn list_the_codes_in_the_help() {
+        for (code, meaning) in [
+            (EXIT_CODE_FAILURE, "any other error"),
+            (EXIT_CODE_NETWORK, "the node could not be reached"),
+            (EXIT_CODE_NOT_FOUND, "is not on-chain"),
+            (EXIT_CODE_REJECTED, "rejected by the mempool"),
+            (EXIT_CODE_ABORTED, "aborted on-chain"),
+            (EXIT_CODE_WAIT_TIMEOUT, "before the wait timeout"),
+            (EXIT_CODE_EXPIRED, "expired before it was submitted"),
+        ] {
+            let line = EXIT_CODES_HELP
+                .lines()
+                .find(|line| line.trim_start().starts_with(&format!("{code} ")))
+                .unwrap();
+            assert!(line.contains(meaning), "{line}");
+        }
+    }
+}
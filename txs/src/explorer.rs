@@ -0,0 +1,84 @@
+//! Links to submitted transactions in a block explorer
+
+use anyhow::{Context, Result};
+use libra_config::extension::global_config_ext::global_folder;
+use std::{collections::BTreeMap, fs, path::PathBuf, sync::Mutex};
+use zapatos_sdk::crypto::HashValue;
+
+/// Explorer URL templates by network name, in the 0L config folder
+pub const EXPLORER_FILE: &str = "explorer.yaml";
+
+static EXPLORER: Mutex<Option<Explorer>> = Mutex::new(None);
+
+/// A URL template such as `https://explorer.example/txn/{hash}?network={chain}`, with the
+/// network it was picked for
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Explorer {
+    pub template: String,
+    /// Name of the network, filled in for `{chain}`
+    pub chain: String,
+}
+
+impl Explorer {
+    pub fn url(&self, hash: &HashValue) -> String {
+        self.template
+            .replace("{hash}", &hash.to_hex_literal())
+            .replace("{chain}", &self.chain)
+    }
+}
+
+/// Set once from the config and --explorer-url, None with --no-explorer-link
+pub fn set_explorer(explorer: Option<Explorer>) {
+    *EXPLORER
+        .lock()
+        .expect("the explorer lock is never poisoned") = explorer;
+}
+
+/// The link to the transaction, None when no explorer is set for the network
+pub fn explorer_url(hash: &HashValue) -> Option<String> {
+    EXPLORER
+        .lock()
+        .expect("the explorer lock is never poisoned")
+        .as_ref()
+        .map(|explorer| explorer.url(hash))
+}
+
+pub fn explorer_file() -> Result<PathBuf> {
+    Ok(global_folder()?.join(EXPLORER_FILE))
+}
+
+/// The template the config sets for the network, e.g. `mainnet: https://...`
+pub fn configured_template(chain: &str) -> Result<Option<String>> {
+    let path = explorer_file()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content =
+        fs::read_to_string(&path).context(format!("Failed to read {}", path.display()))?;
+    let mut templates: BTreeMap<String, String> =
+        serde_yaml::from_str(&content).context(format!(
+            "Failed to parse {}, expected a URL template per network",
+            path.display()
+        ))?;
+    Ok(templates.remove(chain))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_the_template() {
+        let explorer = Explorer {
+            template: "https://explorer.example/txn/{hash}?network={chain}".to_string(),
+            chain: "testnet".to_string(),
+        };
+        assert_eq!(
+            explorer.url(&HashValue::zero()),
+            format!(
+                "https://explorer.example/txn/0x{}?network=testnet",
+                "0".repeat(64)
+            )
+        );
+    }
+}
@@ -1,23 +1,48 @@
-use crate::util::{format_args, format_type_args, parse_function_id};
-use anyhow::{anyhow, Context, Result};
+use crate::{
+    abi::{check_entry_function_call, entry_function_params, function_params, signer_count},
+    abi_cache,
+    address_book::resolve_function_id,
+    args::{parse_json_args, parse_list_args, FunctionArgs, ListArg, TypedArg},
+    constant::{DEFAULT_GAS_BUFFER_PERCENT, DEFAULT_GAS_UNIT_PRICE, DEFAULT_MAX_GAS_AMOUNT},
+    error::TxsError,
+    journal,
+    move_abort::MoveAbort,
+    node_api::NodeApi,
+    retry::retry_read,
+    sponsored_transaction::SponsoredTransaction,
+    util::{format_args, format_type_args, parse_function_id, progress},
+};
+use anyhow::{anyhow, bail, Context, Result};
 use async_trait::async_trait;
-use std::time::SystemTime;
-use std::{str::FromStr, time::UNIX_EPOCH};
+use colored::Colorize;
+use libra_config::extension::client_ext::DEFAULT_TIMEOUT_SECS;
+use std::time::{Duration, Instant, SystemTime};
+use std::{fmt, str::FromStr, time::UNIX_EPOCH};
 use zapatos_sdk::{
+    crypto::{
+        ed25519::{Ed25519PublicKey, Ed25519Signature},
+        HashValue,
+    },
     move_types::{
         language_storage::{ModuleId, TypeTag},
-        parser::{parse_transaction_arguments, parse_type_tags},
-        transaction_argument::convert_txn_args,
+        parser::parse_type_tags,
     },
     rest_client::{
-        aptos_api_types::{EntryFunctionId, MoveType, ViewRequest},
-        Account, Client,
+        aptos_api_types::{
+            AptosErrorCode, EntryFunctionId, MoveFunction, MoveModule, MoveType, Transaction,
+            UserTransaction, ViewRequest,
+        },
+        error::RestError,
+        Account,
     },
     transaction_builder::TransactionBuilder,
     types::{
         account_address::AccountAddress,
         chain_id::ChainId,
-        transaction::{EntryFunction, SignedTransaction, TransactionArgument, TransactionPayload},
+        transaction::{
+            authenticator::{AccountAuthenticator, TransactionAuthenticator},
+            EntryFunction, RawTransaction, SignedTransaction, TransactionPayload,
+        },
         LocalAccount,
     },
 };
@@ -26,25 +51,161 @@ use zapatos_sdk::{
 pub trait ClientExt {
     async fn get_sequence_number(&self, account: AccountAddress) -> Result<u64>;
 
+    /// Sign a call to an entry function. Unless `check_abi` is false, the call is first checked
+    /// against the ABI of the module, so a wrong call fails before paying gas.
+    /// With secondary signers, it is a multi-agent transaction they also sign
+    #[allow(clippy::too_many_arguments)]
     async fn generate_transaction(
         &self,
         from_account: &mut LocalAccount,
+        secondary_signers: &[LocalAccount],
         function_id: &str,
         ty_args: Option<String>,
-        args: Option<String>,
+        args: Option<FunctionArgs>,
+        options: TransactionOptions,
+        check_abi: bool,
+    ) -> Result<SignedTransaction>;
+
+    /// The payload of a call to an entry function, checked against the ABI of the module unless
+    /// `check_abi` is false. `signers` is the number of accounts which sign the transaction
+    async fn entry_function_payload_ext(
+        &self,
+        function_id: &str,
+        ty_args: Option<String>,
+        args: Option<FunctionArgs>,
+        check_abi: bool,
+        signers: usize,
+    ) -> Result<TransactionPayload>;
+
+    /// Build the transaction of a sender without signing it, e.g. to sign it on another machine.
+    /// The max gas cannot be estimated without the key, it is the default when not set
+    async fn build_raw_transaction(
+        &self,
+        sender: AccountAddress,
+        payload: TransactionPayload,
+        options: TransactionOptions,
+    ) -> Result<RawTransaction>;
+
+    async fn sign_payload(
+        &self,
+        from_account: &mut LocalAccount,
+        payload: TransactionPayload,
+        options: TransactionOptions,
+    ) -> Result<SignedTransaction>;
+
+    /// Same as `sign_payload`, with the secondary signers of a multi-agent transaction
+    /// signing it too. They are in the order the function takes them
+    async fn sign_multi_agent_payload(
+        &self,
+        from_account: &mut LocalAccount,
+        secondary_signers: &[LocalAccount],
+        payload: TransactionPayload,
+        options: TransactionOptions,
+    ) -> Result<SignedTransaction>;
+
+    /// Same as `sign_multi_agent_payload`, with `fee_payer` paying the gas instead of the sender.
+    /// It signs the transaction last
+    async fn sign_fee_payer_payload(
+        &self,
+        from_account: &mut LocalAccount,
+        secondary_signers: &[LocalAccount],
+        fee_payer: &LocalAccount,
+        payload: TransactionPayload,
         options: TransactionOptions,
     ) -> Result<SignedTransaction>;
 
+    /// Sign as the sender and the secondary signers of a transaction whose gas the account at
+    /// `fee_payer_address` pays, for it to countersign later. The max gas cannot be estimated
+    /// without the key of the fee payer, it is the default when not set
+    async fn sign_for_fee_payer(
+        &self,
+        from_account: &mut LocalAccount,
+        secondary_signers: &[LocalAccount],
+        fee_payer_address: AccountAddress,
+        payload: TransactionPayload,
+        options: TransactionOptions,
+    ) -> Result<SponsoredTransaction>;
+
+    /// The chain id, expiration and gas of the transaction, estimating what `options` leaves out
+    async fn transaction_builder(
+        &self,
+        from_account: &LocalAccount,
+        secondary_signers: &[LocalAccount],
+        fee_payer: Option<&LocalAccount>,
+        payload: TransactionPayload,
+        options: TransactionOptions,
+    ) -> Result<TransactionBuilder>;
+
+    /// Simulate the transaction and add a safety buffer on top of the gas it used.
+    /// The simulation charges the fee payer when there is one
+    async fn estimate_max_gas_amount(
+        &self,
+        from_account: &LocalAccount,
+        secondary_signers: &[LocalAccount],
+        fee_payer: Option<&LocalAccount>,
+        transaction_builder: TransactionBuilder,
+        gas_buffer_percent: u64,
+    ) -> Result<u64>;
+
+    /// Ask the node for the gas unit price matching the priority, falling back to the default
+    async fn estimate_gas_unit_price(&self, gas_priority: GasPriority) -> u64;
+
     async fn view_ext(
         &self,
         function_id: &str,
         ty_args: Option<String>,
         args: Option<String>,
     ) -> Result<Vec<serde_json::Value>>;
+
+    /// Dry run the transaction on the node, nothing gets committed on-chain
+    async fn simulate_ext(&self, signed_trans: &SignedTransaction) -> Result<UserTransaction>;
+
+    /// Number of decimal places of a coin, from its on-chain CoinInfo
+    async fn get_coin_decimals(&self, coin_type: &TypeTag) -> Result<u8>;
+
+    /// The ABI of an on-chain function, from the ABI of its module
+    async fn get_function_abi(&self, function_id: &str) -> Result<MoveFunction>;
+
+    /// The parameters of the entry function called by the payload, to decode its arguments.
+    /// None for other payloads, or when the ABI cannot be fetched
+    async fn payload_params(&self, payload: &TransactionPayload) -> Option<Vec<MoveType>>;
+
+    /// Build a View request from the arguments given on the command line. Arguments
+    /// given as JSON are typed with the ABI of the function
+    async fn view_request(
+        &self,
+        function_id: &str,
+        ty_args: Option<String>,
+        args: Option<FunctionArgs>,
+    ) -> Result<ViewRequest>;
+
+    /// The ABI of an on-chain module
+    async fn get_module_abi(
+        &self,
+        address: AccountAddress,
+        module_name: &str,
+    ) -> Result<MoveModule>;
+
+    /// Name the abort code of a Move abort using the error map of the aborting module,
+    /// falling back to the VM status as is when it cannot be found
+    async fn explain_vm_status(&self, vm_status: &str) -> String;
+
+    /// Poll the node until the transaction is committed. Fails with a `WaitTimeoutError`
+    /// when it is still unknown or pending after the timeout
+    async fn wait_for_transaction_ext(
+        &self,
+        hash: HashValue,
+        expiration_timestamp_secs: u64,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<Transaction>;
+
+    /// The ledger version to read the state at, None for the latest state
+    async fn resolve_ledger_version(&self, options: &LedgerVersionOptions) -> Result<Option<u64>>;
 }
 
 #[async_trait]
-impl ClientExt for Client {
+impl<N: NodeApi> ClientExt for N {
     async fn get_sequence_number(&self, account: AccountAddress) -> Result<u64> {
         let response = self
             .get_account_resource(account, "0x1::account::Account")
@@ -60,12 +221,34 @@ impl ClientExt for Client {
     async fn generate_transaction(
         &self,
         from_account: &mut LocalAccount,
+        secondary_signers: &[LocalAccount],
         function_id: &str,
         ty_args: Option<String>,
-        args: Option<String>,
+        args: Option<FunctionArgs>,
         options: TransactionOptions,
+        check_abi: bool,
     ) -> Result<SignedTransaction> {
-        let chain_id = self.get_index().await?.inner().chain_id;
+        let payload = self
+            .entry_function_payload_ext(
+                function_id,
+                ty_args,
+                args,
+                check_abi,
+                secondary_signers.len() + 1,
+            )
+            .await?;
+        self.sign_multi_agent_payload(from_account, secondary_signers, payload, options)
+            .await
+    }
+
+    async fn entry_function_payload_ext(
+        &self,
+        function_id: &str,
+        ty_args: Option<String>,
+        args: Option<FunctionArgs>,
+        check_abi: bool,
+        signers: usize,
+    ) -> Result<TransactionPayload> {
         let (module_address, module_name, function_name) = parse_function_id(function_id)?;
         let module = ModuleId::new(module_address, module_name);
         let ty_args: Vec<TypeTag> = if let Some(ty_args) = ty_args {
@@ -74,36 +257,348 @@ impl ClientExt for Client {
         } else {
             vec![]
         };
-        let args: Vec<TransactionArgument> = if let Some(args) = args {
-            parse_transaction_arguments(&args)
-                .context(format!("Unable to parse argument(s): {args}"))?
+        progress(format_type_args(&ty_args));
+
+        let function = if check_abi {
+            let function = self.get_function_abi(function_id).await?;
+            let expected_signers = signer_count(&function);
+            if expected_signers != signers {
+                bail!(
+                    "{function_id} takes {expected_signers} signer(s), got the sender and {} secondary signer(s)",
+                    signers - 1
+                );
+            }
+            Some(function)
         } else {
-            vec![]
+            None
+        };
+        let args = match args {
+            Some(FunctionArgs::Json(json)) => {
+                let params = function
+                    .map(|function| entry_function_params(&function, function_id, &ty_args))
+                    .transpose()?;
+                let args = parse_json_args(&json, params.as_deref())?;
+                progress(format_args(&args));
+                args.iter().map(TypedArg::to_bcs).collect::<Result<_>>()?
+            }
+            args => {
+                let args = match args {
+                    Some(FunctionArgs::List(args)) => parse_list_args(&args)
+                        .context(format!("Unable to parse argument(s): {args}"))?,
+                    _ => vec![],
+                };
+                progress(format_args(&args));
+                if let Some(function) = function {
+                    check_entry_function_call(&function, function_id, &ty_args, &args)?;
+                }
+                args.iter().map(ListArg::to_bcs).collect::<Result<_>>()?
+            }
         };
 
-        println!("{}", format_type_args(&ty_args));
-        println!("{}", format_args(&args));
+        Ok(TransactionPayload::EntryFunction(EntryFunction::new(
+            module,
+            function_name,
+            ty_args,
+            args,
+        )))
+    }
 
-        let expiration_timestamp_secs = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
-            + options.timeout_secs;
+    async fn build_raw_transaction(
+        &self,
+        sender: AccountAddress,
+        payload: TransactionPayload,
+        options: TransactionOptions,
+    ) -> Result<RawTransaction> {
+        let chain_id = ChainId::new(self.get_index().await?.inner().chain_id);
+        let sequence_number = match options.sequence_number {
+            Some(sequence_number) => sequence_number,
+            None => self.get_sequence_number(sender).await?,
+        };
+        let expiration_timestamp_secs = options.expiration_timestamp_secs();
+        let gas_unit_price = match options.gas_unit_price {
+            Some(gas_unit_price) => gas_unit_price,
+            None => self.estimate_gas_unit_price(options.gas_priority).await,
+        };
+        let max_gas_amount = options.max_gas_amount.unwrap_or_else(|| {
+            progress(format!(
+                "Max gas: {DEFAULT_MAX_GAS_AMOUNT}, the default. It cannot be estimated without the key, set --max-gas to change it"
+            ));
+            DEFAULT_MAX_GAS_AMOUNT
+        });
 
-        let transaction_builder = TransactionBuilder::new(
-            TransactionPayload::EntryFunction(EntryFunction::new(
-                module,
-                function_name,
-                ty_args,
-                convert_txn_args(&args),
-            )),
-            expiration_timestamp_secs,
-            ChainId::new(chain_id),
+        Ok(
+            TransactionBuilder::new(payload, expiration_timestamp_secs, chain_id)
+                .sender(sender)
+                .sequence_number(sequence_number)
+                .max_gas_amount(max_gas_amount)
+                .gas_unit_price(gas_unit_price)
+                .build(),
         )
-        .max_gas_amount(options.max_gas_amount)
-        .gas_unit_price(options.gas_unit_price);
+    }
+
+    async fn sign_payload(
+        &self,
+        from_account: &mut LocalAccount,
+        payload: TransactionPayload,
+        options: TransactionOptions,
+    ) -> Result<SignedTransaction> {
+        self.sign_multi_agent_payload(from_account, &[], payload, options)
+            .await
+    }
+
+    async fn sign_multi_agent_payload(
+        &self,
+        from_account: &mut LocalAccount,
+        secondary_signers: &[LocalAccount],
+        payload: TransactionPayload,
+        options: TransactionOptions,
+    ) -> Result<SignedTransaction> {
+        let transaction_builder = self
+            .transaction_builder(from_account, secondary_signers, None, payload, options)
+            .await?;
+        if secondary_signers.is_empty() {
+            return Ok(from_account.sign_with_transaction_builder(transaction_builder));
+        }
+        let raw_trans = transaction_builder
+            .sender(from_account.address())
+            .sequence_number(from_account.increment_sequence_number())
+            .build();
+        Ok(raw_trans
+            .sign_multi_agent(
+                from_account.private_key(),
+                secondary_signers
+                    .iter()
+                    .map(LocalAccount::address)
+                    .collect(),
+                secondary_signers
+                    .iter()
+                    .map(LocalAccount::private_key)
+                    .collect(),
+            )?
+            .into_inner())
+    }
+
+    async fn sign_fee_payer_payload(
+        &self,
+        from_account: &mut LocalAccount,
+        secondary_signers: &[LocalAccount],
+        fee_payer: &LocalAccount,
+        payload: TransactionPayload,
+        options: TransactionOptions,
+    ) -> Result<SignedTransaction> {
+        let raw_trans = self
+            .transaction_builder(
+                from_account,
+                secondary_signers,
+                Some(fee_payer),
+                payload,
+                options,
+            )
+            .await?
+            .sender(from_account.address())
+            .sequence_number(from_account.increment_sequence_number())
+            .build();
+        SponsoredTransaction::sign(
+            raw_trans,
+            from_account,
+            secondary_signers,
+            fee_payer.address(),
+        )?
+        .countersign(fee_payer.private_key())
+    }
+
+    async fn sign_for_fee_payer(
+        &self,
+        from_account: &mut LocalAccount,
+        secondary_signers: &[LocalAccount],
+        fee_payer_address: AccountAddress,
+        payload: TransactionPayload,
+        options: TransactionOptions,
+    ) -> Result<SponsoredTransaction> {
+        let max_gas_amount = options.max_gas_amount.unwrap_or_else(|| {
+            progress(format!(
+                "Max gas: {DEFAULT_MAX_GAS_AMOUNT}, the default. It cannot be estimated without the key of the fee payer, set --max-gas to change it"
+            ));
+            DEFAULT_MAX_GAS_AMOUNT
+        });
+        let options = TransactionOptions {
+            max_gas_amount: Some(max_gas_amount),
+            ..options
+        };
+        let raw_trans = self
+            .transaction_builder(from_account, secondary_signers, None, payload, options)
+            .await?
+            .sender(from_account.address())
+            .sequence_number(from_account.increment_sequence_number())
+            .build();
+        SponsoredTransaction::sign(
+            raw_trans,
+            from_account,
+            secondary_signers,
+            fee_payer_address,
+        )
+    }
+
+    async fn transaction_builder(
+        &self,
+        from_account: &LocalAccount,
+        secondary_signers: &[LocalAccount],
+        fee_payer: Option<&LocalAccount>,
+        payload: TransactionPayload,
+        options: TransactionOptions,
+    ) -> Result<TransactionBuilder> {
+        let chain_id = ChainId::new(self.get_index().await?.inner().chain_id);
+        let expiration_timestamp_secs = options.expiration_timestamp_secs();
+
+        let gas_unit_price = match options.gas_unit_price {
+            Some(gas_unit_price) => gas_unit_price,
+            None => self.estimate_gas_unit_price(options.gas_priority).await,
+        };
+
+        let max_gas_amount = match options.max_gas_amount {
+            Some(max_gas_amount) => max_gas_amount,
+            None => {
+                let transaction_builder =
+                    TransactionBuilder::new(payload.clone(), expiration_timestamp_secs, chain_id)
+                        .gas_unit_price(gas_unit_price);
+                self.estimate_max_gas_amount(
+                    from_account,
+                    secondary_signers,
+                    fee_payer,
+                    transaction_builder,
+                    options.gas_buffer_percent,
+                )
+                .await?
+            }
+        };
+
+        Ok(
+            TransactionBuilder::new(payload, expiration_timestamp_secs, chain_id)
+                .max_gas_amount(max_gas_amount)
+                .gas_unit_price(gas_unit_price),
+        )
+    }
+
+    async fn estimate_max_gas_amount(
+        &self,
+        from_account: &LocalAccount,
+        secondary_signers: &[LocalAccount],
+        fee_payer: Option<&LocalAccount>,
+        transaction_builder: TransactionBuilder,
+        gas_buffer_percent: u64,
+    ) -> Result<u64> {
+        // The node replaces the max gas with the most the sender, or the fee payer, can afford
+        let raw_trans = transaction_builder
+            .sender(from_account.address())
+            .sequence_number(from_account.sequence_number())
+            .max_gas_amount(DEFAULT_MAX_GAS_AMOUNT)
+            .build();
+        let authenticator = if let Some(fee_payer) = fee_payer {
+            TransactionAuthenticator::fee_payer(
+                unsigned_account(from_account.public_key())?,
+                secondary_signers
+                    .iter()
+                    .map(LocalAccount::address)
+                    .collect(),
+                secondary_signers
+                    .iter()
+                    .map(|signer| unsigned_account(signer.public_key()))
+                    .collect::<Result<_>>()?,
+                fee_payer.address(),
+                unsigned_account(fee_payer.public_key())?,
+            )
+        } else if secondary_signers.is_empty() {
+            TransactionAuthenticator::ed25519(
+                from_account.public_key().to_owned(),
+                zero_signature()?,
+            )
+        } else {
+            TransactionAuthenticator::multi_agent(
+                unsigned_account(from_account.public_key())?,
+                secondary_signers
+                    .iter()
+                    .map(LocalAccount::address)
+                    .collect(),
+                secondary_signers
+                    .iter()
+                    .map(|signer| unsigned_account(signer.public_key()))
+                    .collect::<Result<_>>()?,
+            )
+        };
+        let unsigned_trans = SignedTransaction::new_with_authenticator(raw_trans, authenticator);
+
+        let simulated_trans = match self
+            .simulate_with_gas_estimation(&unsigned_trans, true, false)
+            .await
+        {
+            Ok(response) => response.into_inner().into_iter().next(),
+            Err(err) => {
+                eprintln!(
+                    "{}",
+                    format!("Failed to simulate the transaction: {err}").yellow()
+                );
+                None
+            }
+        };
+        let simulated_trans = match simulated_trans {
+            Some(simulated_trans) => simulated_trans,
+            None => {
+                eprintln!(
+                    "{}",
+                    format!(
+                        "Unable to estimate the max gas, falling back to the default of {DEFAULT_MAX_GAS_AMOUNT}"
+                    )
+                    .yellow()
+                );
+                return Ok(DEFAULT_MAX_GAS_AMOUNT);
+            }
+        };
 
-        Ok(from_account.sign_with_transaction_builder(transaction_builder))
+        if !simulated_trans.info.success {
+            return Err(TxsError::SimulationFailed {
+                vm_status: self
+                    .explain_vm_status(&simulated_trans.info.vm_status)
+                    .await,
+            }
+            .into());
+        }
+
+        let gas_used = u64::from(simulated_trans.info.gas_used);
+        let max_gas_amount = (gas_used + gas_used * gas_buffer_percent / 100)
+            .min(u64::from(simulated_trans.request.max_gas_amount));
+        progress(format!(
+            "Estimated max gas: {max_gas_amount} ({gas_used} used in simulation + {gas_buffer_percent}% buffer)"
+        ));
+        Ok(max_gas_amount)
+    }
+
+    async fn estimate_gas_unit_price(&self, gas_priority: GasPriority) -> u64 {
+        match self.estimate_gas_price().await {
+            Ok(response) => {
+                let estimation = response.into_inner();
+                let gas_unit_price = match gas_priority {
+                    GasPriority::Low => estimation.deprioritized_gas_estimate,
+                    GasPriority::Normal => None,
+                    GasPriority::Aggressive => estimation.prioritized_gas_estimate,
+                }
+                .unwrap_or(estimation.gas_estimate);
+                progress(format!(
+                    "Estimated gas unit price: {gas_unit_price} ({} priority)",
+                    format!("{gas_priority:?}").to_lowercase()
+                ));
+                gas_unit_price
+            }
+            Err(err) => {
+                eprintln!(
+                    "{}",
+                    format!(
+                        "Unable to estimate the gas unit price, falling back to the default of {DEFAULT_GAS_UNIT_PRICE}: {err}"
+                    )
+                    .yellow()
+                );
+                DEFAULT_GAS_UNIT_PRICE
+            }
+        }
     }
 
     async fn view_ext(
@@ -112,47 +607,418 @@ impl ClientExt for Client {
         ty_args: Option<String>,
         args: Option<String>,
     ) -> Result<Vec<serde_json::Value>> {
-        let entry_fuction_id = EntryFunctionId::from_str(function_id)
-            .context(format!("Invalid function id: {function_id}"))?;
-        let ty_args: Vec<MoveType> = if let Some(ty_args) = ty_args {
-            parse_type_tags(&ty_args)
-                .context(format!("Unable to parse the type argument(s): {ty_args}"))?
-                .iter()
-                .map(|t| t.into())
-                .collect()
-        } else {
-            vec![]
+        let request = parse_view_request(function_id, ty_args, args)?;
+        self.view(&request, None)
+            .await
+            .context("Failed to execute View request")
+            .map(|res| res.inner().to_owned())
+    }
+
+    async fn simulate_ext(&self, signed_trans: &SignedTransaction) -> Result<UserTransaction> {
+        let unsigned_trans = unsigned_transaction(signed_trans)?;
+
+        self.simulate(&unsigned_trans)
+            .await
+            .context("Failed to simulate the transaction")?
+            .into_inner()
+            .into_iter()
+            .next()
+            .context("The node returned no simulation result")
+    }
+
+    async fn get_function_abi(&self, function_id: &str) -> Result<MoveFunction> {
+        let (module_address, module_name, function_name) = parse_function_id(function_id)?;
+        self.get_module_abi(module_address, module_name.as_str())
+            .await?
+            .exposed_functions
+            .into_iter()
+            .find(|f| f.name.as_str() == function_name.as_str())
+            .context(format!("Function {function_id} does not exist"))
+    }
+
+    async fn payload_params(&self, payload: &TransactionPayload) -> Option<Vec<MoveType>> {
+        let entry_function = match payload {
+            TransactionPayload::EntryFunction(entry_function) => entry_function,
+            _ => return None,
         };
-        let args: Vec<serde_json::Value> = if let Some(args) = args {
-            let mut output = vec![];
-            for arg in args.split(',') {
-                let arg = serde_json::Value::try_from(arg.trim())
-                    .context(format!("Failed to parse argument: {arg}"))?;
-                output.push(arg);
+        let function_id = format!(
+            "{}::{}::{}",
+            entry_function.module().address().to_hex_literal(),
+            entry_function.module().name(),
+            entry_function.function()
+        );
+        let function = self.get_function_abi(&function_id).await.ok()?;
+        entry_function_params(&function, &function_id, entry_function.ty_args()).ok()
+    }
+
+    async fn view_request(
+        &self,
+        function_id: &str,
+        ty_args: Option<String>,
+        args: Option<FunctionArgs>,
+    ) -> Result<ViewRequest> {
+        let json = match args {
+            Some(FunctionArgs::Json(json)) => json,
+            Some(FunctionArgs::List(args)) => {
+                return parse_view_request(function_id, ty_args, Some(args))
             }
-            output
-        } else {
-            vec![]
+            None => return parse_view_request(function_id, ty_args, None),
         };
 
-        println!("{}", format_type_args(&ty_args));
-        println!("{}", format_args(&args));
-
-        let request = ViewRequest {
-            function: entry_fuction_id,
-            type_arguments: ty_args,
-            arguments: args,
+        let type_tags = match &ty_args {
+            Some(ty_args) => parse_type_tags(ty_args)
+                .context(format!("Unable to parse the type argument(s): {ty_args}"))?,
+            None => vec![],
         };
+        let function = self.get_function_abi(function_id).await?;
+        let params = function_params(&function, function_id, &type_tags)?;
+        let mut request = parse_view_request(function_id, ty_args, None)?;
+        request.arguments = parse_json_args(&json, Some(&params))?
+            .iter()
+            .map(TypedArg::to_view_json)
+            .collect::<Result<_>>()?;
+        Ok(request)
+    }
 
-        self.view(&request, None)
+    async fn get_module_abi(
+        &self,
+        address: AccountAddress,
+        module_name: &str,
+    ) -> Result<MoveModule> {
+        let module_id = format!("{}::{module_name}", address.to_hex_literal());
+        abi_cache::get_module(self, address, module_name)
             .await
-            .context("Failed to execute View request")
-            .map(|res| res.inner().to_owned())
+            .context(format!("Failed to get module {module_id}"))?
+            .try_parse_abi()
+            .context(format!("Failed to parse the ABI of module {module_id}"))?
+            .abi
+            .context(format!("Module {module_id} has no ABI"))
+    }
+
+    async fn get_coin_decimals(&self, coin_type: &TypeTag) -> Result<u8> {
+        let coin_address = match coin_type {
+            TypeTag::Struct(struct_tag) => struct_tag.address,
+            _ => bail!("Invalid coin type: {coin_type}"),
+        };
+        let coin_info = self
+            .get_account_resource(coin_address, &format!("0x1::coin::CoinInfo<{coin_type}>"))
+            .await
+            .context(format!("Failed to get the coin info of {coin_type}"))?
+            .into_inner()
+            .context(format!("{coin_type} is not a coin, it has no CoinInfo"))?;
+        let decimals = coin_info.data["decimals"]
+            .as_u64()
+            .context(format!("No decimals in the coin info of {coin_type}"))?;
+        Ok(u8::try_from(decimals)?)
     }
+
+    async fn explain_vm_status(&self, vm_status: &str) -> String {
+        let move_abort = match MoveAbort::parse(vm_status) {
+            Some(move_abort) => move_abort,
+            None => return vm_status.to_string(),
+        };
+        let description =
+            abi_cache::get_module(self, move_abort.module_address, &move_abort.module_name)
+                .await
+                .and_then(|module| move_abort.explain(module.bytecode.inner()));
+
+        match description {
+            Ok(Some(description)) => move_abort.format(&description),
+            _ => vm_status.to_string(),
+        }
+    }
+
+    async fn wait_for_transaction_ext(
+        &self,
+        hash: HashValue,
+        expiration_timestamp_secs: u64,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<Transaction> {
+        let start = Instant::now();
+        loop {
+            match retry_read(|| self.get_transaction_by_hash(hash)).await {
+                Ok(response) => {
+                    let transaction = response.into_inner();
+                    if !matches!(transaction, Transaction::PendingTransaction(_)) {
+                        journal::record_outcome(hash, transaction.success());
+                        if !transaction.success() {
+                            return Err(TransactionFailedError {
+                                hash,
+                                vm_status: self.explain_vm_status(&transaction.vm_status()).await,
+                            }
+                            .into());
+                        }
+                        return Ok(transaction);
+                    }
+                }
+                Err(RestError::Api(err)) if err.status_code.as_u16() == 404 => {
+                    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+                    if now > expiration_timestamp_secs {
+                        bail!(
+                            "Transaction {} expired before being committed",
+                            hash.to_hex_literal()
+                        );
+                    }
+                }
+                Err(err) => {
+                    return Err(err).context(format!(
+                        "Failed to get the transaction {}",
+                        hash.to_hex_literal()
+                    ))
+                }
+            }
+
+            if start.elapsed() >= timeout {
+                return Err(WaitTimeoutError { hash, timeout }.into());
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    async fn resolve_ledger_version(&self, options: &LedgerVersionOptions) -> Result<Option<u64>> {
+        match (options.at_version, options.at_height) {
+            (Some(version), _) => Ok(Some(version)),
+            (None, Some(height)) => {
+                let block = self
+                    .get_block_by_height(height, false)
+                    .await
+                    .map_err(|err| {
+                        ledger_version_error(
+                            err,
+                            &format!("Failed to get the block at height {height}"),
+                        )
+                    })?
+                    .into_inner();
+                Ok(Some(block.last_version.into()))
+            }
+            (None, None) => Ok(None),
+        }
+    }
+}
+
+/// Simulations must carry an invalid signature, so that they can never be replayed on-chain.
+/// The transaction keeps its signers, with their signatures zeroed
+fn unsigned_transaction(signed_trans: &SignedTransaction) -> Result<SignedTransaction> {
+    let authenticator = match signed_trans.authenticator() {
+        TransactionAuthenticator::Ed25519 { public_key, .. } => {
+            TransactionAuthenticator::ed25519(public_key, zero_signature()?)
+        }
+        TransactionAuthenticator::MultiAgent {
+            sender,
+            secondary_signer_addresses,
+            secondary_signers,
+        } => TransactionAuthenticator::multi_agent(
+            unsigned_account(&ed25519_public_key(&sender)?)?,
+            secondary_signer_addresses,
+            secondary_signers
+                .iter()
+                .map(|signer| unsigned_account(&ed25519_public_key(signer)?))
+                .collect::<Result<_>>()?,
+        ),
+        // The fee payer is kept, so the simulation charges it the gas
+        TransactionAuthenticator::FeePayer {
+            sender,
+            secondary_signer_addresses,
+            secondary_signers,
+            fee_payer_address,
+            fee_payer_signer,
+        } => TransactionAuthenticator::fee_payer(
+            unsigned_account(&ed25519_public_key(&sender)?)?,
+            secondary_signer_addresses,
+            secondary_signers
+                .iter()
+                .map(|signer| unsigned_account(&ed25519_public_key(signer)?))
+                .collect::<Result<_>>()?,
+            fee_payer_address,
+            unsigned_account(&ed25519_public_key(&fee_payer_signer)?)?,
+        ),
+        _ => bail!("Only transactions signed with Ed25519 keys can be simulated"),
+    };
+    Ok(SignedTransaction::new_with_authenticator(
+        signed_trans.to_owned().into_raw_transaction(),
+        authenticator,
+    ))
+}
+
+fn unsigned_account(public_key: &Ed25519PublicKey) -> Result<AccountAuthenticator> {
+    Ok(AccountAuthenticator::ed25519(
+        public_key.to_owned(),
+        zero_signature()?,
+    ))
+}
+
+fn ed25519_public_key(authenticator: &AccountAuthenticator) -> Result<Ed25519PublicKey> {
+    match authenticator {
+        AccountAuthenticator::Ed25519 { public_key, .. } => Ok(public_key.to_owned()),
+        _ => bail!("Only transactions signed with Ed25519 keys can be simulated"),
+    }
+}
+
+fn zero_signature() -> Result<Ed25519Signature> {
+    Ok(Ed25519Signature::try_from([0u8; 64].as_ref())?)
 }
 
+/// Turn an error of a read at a past ledger version into one which says when the node
+/// no longer has that state, instead of a generic failure
+pub fn ledger_version_error(err: RestError, context: &str) -> anyhow::Error {
+    match &err {
+        RestError::Api(response)
+            if matches!(
+                response.error.error_code,
+                AptosErrorCode::VersionPruned | AptosErrorCode::BlockPruned
+            ) =>
+        {
+            anyhow!(
+                "{context}: the node has pruned this state, an archive node is needed to read it ({})",
+                response.error.message
+            )
+        }
+        RestError::Api(response) => anyhow!("{context}: {}", response.error.message),
+        _ => anyhow!(err).context(context.to_owned()),
+    }
+}
+
+/// Build a View request from the function id, type arguments and arguments given on the command line
+pub fn parse_view_request(
+    function_id: &str,
+    ty_args: Option<String>,
+    args: Option<String>,
+) -> Result<ViewRequest> {
+    let function_id = &resolve_function_id(function_id)?;
+    let entry_fuction_id = EntryFunctionId::from_str(function_id)
+        .context(format!("Invalid function id: {function_id}"))?;
+    let ty_args: Vec<MoveType> = if let Some(ty_args) = ty_args {
+        parse_type_tags(&ty_args)
+            .context(format!("Unable to parse the type argument(s): {ty_args}"))?
+            .iter()
+            .map(|t| t.into())
+            .collect()
+    } else {
+        vec![]
+    };
+    let args: Vec<serde_json::Value> = if let Some(args) = args {
+        parse_list_args(&args)
+            .context(format!("Unable to parse argument(s): {args}"))?
+            .iter()
+            .map(ListArg::to_view_json)
+            .collect()
+    } else {
+        vec![]
+    };
+
+    Ok(ViewRequest {
+        function: entry_fuction_id,
+        type_arguments: ty_args,
+        arguments: args,
+    })
+}
+
+#[derive(Clone)]
 pub struct TransactionOptions {
-    pub max_gas_amount: u64,
-    pub gas_unit_price: u64,
+    /// Estimated by simulating the transaction when not set
+    pub max_gas_amount: Option<u64>,
+    /// Safety margin added on top of the simulated gas used when estimating the max gas
+    pub gas_buffer_percent: u64,
+    /// Estimated by the node according to the gas priority when not set
+    pub gas_unit_price: Option<u64>,
+    pub gas_priority: GasPriority,
+    /// Seconds the transaction is valid for once built
     pub timeout_secs: u64,
+    /// Unix time the transaction expires at, overriding timeout_secs
+    pub expire_at: Option<u64>,
+    /// Taken from the chain when not set, e.g. from a nonce file to sign offline
+    pub sequence_number: Option<u64>,
+}
+
+impl TransactionOptions {
+    pub fn expiration_timestamp_secs(&self) -> u64 {
+        self.expire_at.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+                + self.timeout_secs
+        })
+    }
+}
+
+/// The defaults of the command line flags
+impl Default for TransactionOptions {
+    fn default() -> Self {
+        Self {
+            max_gas_amount: None,
+            gas_buffer_percent: DEFAULT_GAS_BUFFER_PERCENT,
+            gas_unit_price: None,
+            gas_priority: GasPriority::default(),
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            expire_at: None,
+            sequence_number: None,
+        }
+    }
+}
+
+/// The transaction may still be committed later, so it must not be reported as failed
+#[derive(Debug)]
+pub struct WaitTimeoutError {
+    pub hash: HashValue,
+    pub timeout: Duration,
+}
+
+impl fmt::Display for WaitTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Transaction {} was not committed after {}s, check its status again later",
+            self.hash.to_hex_literal(),
+            self.timeout.as_secs()
+        )
+    }
+}
+
+impl std::error::Error for WaitTimeoutError {}
+
+/// The transaction was committed but aborted on-chain, its gas is spent
+#[derive(Debug)]
+pub struct TransactionFailedError {
+    pub hash: HashValue,
+    /// Explained with the error description of the module when it aborted
+    pub vm_status: String,
+}
+
+impl fmt::Display for TransactionFailedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Transaction {} failed with VM status: {}",
+            self.hash.to_hex_literal(),
+            self.vm_status
+        )
+    }
+}
+
+impl std::error::Error for TransactionFailedError {}
+
+/// Point in time to read on-chain state at, the latest state when neither is set
+#[derive(Clone, Debug, Default, clap::Args)]
+pub struct LedgerVersionOptions {
+    /// Read the state at this ledger version
+    #[clap(long, conflicts_with = "at_height")]
+    pub at_version: Option<u64>,
+
+    /// Read the state at the last ledger version of the block at this height
+    #[clap(long)]
+    pub at_height: Option<u64>,
+}
+
+/// Which of the node's gas unit price estimates to use
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum GasPriority {
+    /// The deprioritized estimate, cheaper but may take longer to be executed
+    Low,
+    /// The market estimate
+    #[default]
+    Normal,
+    /// The prioritized estimate, more expensive but executed sooner
+    Aggressive,
 }
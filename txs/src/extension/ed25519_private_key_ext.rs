@@ -1,27 +1,100 @@
 use super::client_ext::ClientExt;
-use anyhow::Result;
+use crate::{
+    node_api::{Lookup, NodeApi},
+    secret::{parse_private_key, strict_permissions, Secret},
+};
+use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
-use libra_config::extension::client_ext::ClientExt as ConfigClientExt;
+use libra_wallet::{
+    address_scheme::find_account, legacy::get_keys_from_mnem, utils::check_key_file_permissions,
+};
+use std::path::Path;
 use zapatos_sdk::{
     crypto::ed25519::Ed25519PrivateKey,
-    rest_client::Client,
     types::{AccountKey, LocalAccount},
 };
 
+/// Keys under which a private key can be stored in a yaml key file
+const YAML_PRIVATE_KEY_FIELDS: [&str; 2] = ["account_private_key", "private_key"];
+
 #[async_trait]
 pub trait Ed25519PrivateKeyExt {
-    async fn get_account(&self, sequence_number: Option<u64>) -> Result<LocalAccount>;
+    /// Read a private key from a file holding either the raw hex encoded key
+    /// or a yaml document such as the private-keys.yaml generated by libra-wallet. The file must
+    /// not be readable by other users, see `set_strict_permissions`
+    fn from_key_file(path: &Path) -> Result<Ed25519PrivateKey>;
+
+    /// Derive the private key of the nth account of a BIP-39 mnemonic,
+    /// using the same m/44'/637'/<INDEX>'/0'/0' path as Aptos wallets
+    fn from_mnemonic(mnemonic: &str, derivation_index: u32) -> Result<Ed25519PrivateKey>;
+
+    /// Derive the owner key of a 0L v5/v6 mnemonic, with the legacy 0L scheme
+    fn from_legacy_mnemonic(mnemonic: &str) -> Result<Ed25519PrivateKey>;
+
+    /// The account the key signs for. Without a sequence number the account is looked up
+    /// on-chain, so a key also signs for the legacy 0L address or the address which rotated
+    /// to it, see `libra_wallet::address_scheme::find_account`
+    async fn get_account<N: NodeApi>(
+        &self,
+        client: &N,
+        sequence_number: Option<u64>,
+    ) -> Result<LocalAccount>;
 }
 
 #[async_trait]
 impl Ed25519PrivateKeyExt for Ed25519PrivateKey {
-    async fn get_account(&self, sequence_number: Option<u64>) -> Result<LocalAccount> {
+    fn from_key_file(path: &Path) -> Result<Ed25519PrivateKey> {
+        let content = Secret::new(
+            std::fs::read_to_string(path)
+                .context(format!("Failed to read the key file {}", path.display()))?,
+        );
+        check_key_file_permissions(path, strict_permissions())?;
+        let yaml_key = serde_yaml::from_str::<serde_yaml::Value>(content.expose())
+            .ok()
+            .and_then(|yaml| {
+                YAML_PRIVATE_KEY_FIELDS
+                    .iter()
+                    .find_map(|field| yaml.get(*field)?.as_str().map(str::to_owned))
+            })
+            .map(Secret::new);
+        parse_private_key(
+            yaml_key.as_ref().unwrap_or(&content),
+            &path.display().to_string(),
+        )
+    }
+
+    fn from_mnemonic(mnemonic: &str, derivation_index: u32) -> Result<Ed25519PrivateKey> {
+        let derive_path = format!("m/44'/637'/{derivation_index}'/0'/0'");
+        // The error of an invalid mnemonic can quote its words, so it is dropped
+        let account = LocalAccount::from_derive_path(&derive_path, mnemonic.trim(), 0)
+            .map_err(|_| anyhow!("Failed to derive the private key from the mnemonic"))?;
+        Ok(Ed25519PrivateKey::try_from(
+            account.private_key().to_bytes().as_ref(),
+        )?)
+    }
+
+    fn from_legacy_mnemonic(mnemonic: &str) -> Result<Ed25519PrivateKey> {
+        // As above, the error can quote the words of the mnemonic
+        let keys = get_keys_from_mnem(mnemonic.trim().to_owned())
+            .map_err(|_| anyhow!("Failed to derive the private key from the legacy mnemonic"))?;
+        Ok(keys.child_0_owner.pri_key)
+    }
+
+    async fn get_account<N: NodeApi>(
+        &self,
+        client: &N,
+        sequence_number: Option<u64>,
+    ) -> Result<LocalAccount> {
         let account_key = AccountKey::from_private_key(self.to_owned());
-        let account_address = account_key.authentication_key().derived_address();
+        let mut account_address = account_key.authentication_key().derived_address();
         let sequence_number = match sequence_number {
             Some(seq) => seq,
             None => {
-                let client = Client::default()?;
+                if let Some((address, _)) =
+                    find_account(&Lookup(client), &account_key.authentication_key()).await?
+                {
+                    account_address = address;
+                }
                 client.get_sequence_number(account_address).await?
             }
         };
@@ -37,7 +110,12 @@ impl Ed25519PrivateKeyExt for Ed25519PrivateKey {
 #[cfg(test)]
 mod tests {
     use crate::extension::ed25519_private_key_ext::Ed25519PrivateKeyExt;
-    use zapatos_sdk::crypto::{ed25519::Ed25519PrivateKey, ValidCryptoMaterialStringExt};
+    use std::str::FromStr;
+    use url::Url;
+    use zapatos_sdk::{
+        crypto::{ed25519::Ed25519PrivateKey, ValidCryptoMaterialStringExt},
+        rest_client::Client,
+    };
 
     #[tokio::test]
     async fn create_local_account_from_private_key() {
@@ -45,7 +123,9 @@ mod tests {
             "c43f57994644ebda1eabfebf84def73fbd1d3ce442a9d2b2f4cb9f4da7b9908c",
         )
         .unwrap();
-        let account = private_key.get_account(Some(0)).await.unwrap();
+        // The sequence number is given, so the client never reaches out to the node
+        let client = Client::new(Url::from_str("http://localhost:8080").unwrap());
+        let account = private_key.get_account(&client, Some(0)).await.unwrap();
         let expected_public_key =
             "ef00c7b6f6246543445a847a6d136d293c107b05044f7fc105a063c93c50d7a0";
         let expected_auth_key = "fda03992f666875ddf854193fccd3e62ea111d066029490dd37c891ed9c3f880";
@@ -57,4 +137,32 @@ mod tests {
         assert_eq!(expected_auth_key, account.authentication_key().to_string());
         assert_eq!(expected_account_address, account.address().to_hex_literal());
     }
+
+    #[test]
+    fn read_private_key_from_yaml_and_hex_files() {
+        let encoded_key = "0xc43f57994644ebda1eabfebf84def73fbd1d3ce442a9d2b2f4cb9f4da7b9908c";
+        let expected = Ed25519PrivateKey::from_encoded_string(encoded_key).unwrap();
+        let dir = std::env::temp_dir().join("txs-key-file-test");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let yaml_file = dir.join("private-keys.yaml");
+        std::fs::write(
+            &yaml_file,
+            format!("---\naccount_address: fda03992\naccount_private_key: \"{encoded_key}\"\n"),
+        )
+        .unwrap();
+        let hex_file = dir.join("private-key.txt");
+        std::fs::write(&hex_file, format!("  {encoded_key}\n")).unwrap();
+
+        assert_eq!(
+            expected,
+            Ed25519PrivateKey::from_key_file(&yaml_file).unwrap()
+        );
+        assert_eq!(
+            expected,
+            Ed25519PrivateKey::from_key_file(&hex_file).unwrap()
+        );
+
+        std::fs::remove_dir_all(dir).ok();
+    }
 }
@@ -1,2 +1,3 @@
 pub mod client_ext;
 pub mod ed25519_private_key_ext;
+pub mod signed_transaction_ext;
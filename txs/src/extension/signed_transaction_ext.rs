@@ -0,0 +1,87 @@
+use crate::util::{
+    format_output, format_signed_transaction_json, format_signed_transaction_with_params,
+    OutputFormat,
+};
+use anyhow::{bail, Context, Result};
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+use zapatos_sdk::{rest_client::aptos_api_types::MoveType, types::transaction::SignedTransaction};
+
+pub trait SignedTransactionExt {
+    /// Read a BCS encoded transaction written by `save`, or from stdin when the path is `-`
+    fn load(path: &Path) -> Result<SignedTransaction>;
+
+    /// Write the BCS encoded transaction to a file, refusing to overwrite it unless forced
+    fn save(&self, path: &Path, force: bool) -> Result<()>;
+
+    /// Write a human readable summary next to the BCS file, returning the path of the summary.
+    /// The arguments are decoded with `params`, the parameters of the function from its ABI
+    fn save_summary(
+        &self,
+        path: &Path,
+        format: OutputFormat,
+        params: Option<&[MoveType]>,
+        force: bool,
+    ) -> Result<PathBuf>;
+}
+
+impl SignedTransactionExt for SignedTransaction {
+    fn load(path: &Path) -> Result<SignedTransaction> {
+        let bytes = if path == Path::new("-") {
+            let mut bytes = vec![];
+            std::io::stdin()
+                .read_to_end(&mut bytes)
+                .context("Failed to read the signed transaction from stdin")?;
+            bytes
+        } else {
+            std::fs::read(path).context(format!("Failed to read {}", path.display()))?
+        };
+        bcs::from_bytes(&bytes).context("The input is not a BCS encoded signed transaction")
+    }
+
+    fn save(&self, path: &Path, force: bool) -> Result<()> {
+        let bytes = bcs::to_bytes(self).context("Failed to serialize the signed transaction")?;
+        write_file(path, &bytes, force)
+    }
+
+    fn save_summary(
+        &self,
+        path: &Path,
+        format: OutputFormat,
+        params: Option<&[MoveType]>,
+        force: bool,
+    ) -> Result<PathBuf> {
+        let summary = format_signed_transaction_with_params(self, params);
+        let (extension, content) = match format {
+            OutputFormat::Text => ("txt", summary),
+            OutputFormat::Json | OutputFormat::Yaml => {
+                let mut document = format_signed_transaction_json(self, params);
+                document.summary = Some(summary);
+                let extension = if format == OutputFormat::Json {
+                    "json"
+                } else {
+                    "yaml"
+                };
+                (extension, format_output(&document, format)?)
+            }
+        };
+
+        let mut summary_path = path.as_os_str().to_owned();
+        summary_path.push(format!(".{extension}"));
+        let summary_path = PathBuf::from(summary_path);
+        write_file(&summary_path, content.as_bytes(), force)?;
+        Ok(summary_path)
+    }
+}
+
+fn write_file(path: &Path, bytes: &[u8], force: bool) -> Result<()> {
+    if path.exists() && !force {
+        bail!(
+            "{} already exists, use --force to overwrite it",
+            path.display()
+        );
+    }
+    std::fs::write(path, bytes).context(format!("Failed to write {}", path.display()))
+}
@@ -0,0 +1,56 @@
+//! Fan-out of independent requests to a node, with a bound on how many are in flight so that a
+//! long list does not trip the rate limit of the node
+
+use crate::constant::MAX_REQUESTS_IN_FLIGHT;
+use futures::{stream, Future, StreamExt};
+
+/// Await every future, at most `limit` at once, and return their outputs in the same order as
+/// the futures. A limit of 0 is taken as 1
+pub async fn join_all_limited<I, F>(futures: I, limit: usize) -> Vec<F::Output>
+where
+    I: IntoIterator<Item = F>,
+    F: Future,
+{
+    stream::iter(futures).buffered(limit.max(1)).collect().await
+}
+
+/// `join_all_limited` with the default bound, MAX_REQUESTS_IN_FLIGHT
+pub async fn join_all_requests<I, F>(futures: I) -> Vec<F::Output>
+where
+    I: IntoIterator<Item = F>,
+    F: Future,
+{
+    join_all_limited(futures, MAX_REQUESTS_IN_FLIGHT).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    #[tokio::test]
+    async fn keep_the_order_and_the_limit() {
+        let in_flight = AtomicUsize::new(0);
+        let most_in_flight = AtomicUsize::new(0);
+        let outputs = join_all_limited(
+            (0..10u64).map(|i| {
+                let (in_flight, most_in_flight) = (&in_flight, &most_in_flight);
+                async move {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    most_in_flight.fetch_max(now, Ordering::SeqCst);
+                    // The first ones finish last
+                    tokio::time::sleep(Duration::from_millis(10 * (10 - i))).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    i
+                }
+            }),
+            3,
+        )
+        .await;
+        assert_eq!(outputs, (0..10).collect::<Vec<_>>());
+        assert_eq!(most_in_flight.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,270 @@
+//! Check that the sender can pay for a transaction before it is submitted, as a transaction
+//! which runs out of funds still costs gas
+
+use crate::{
+    constant::NATIVE_COIN_DECIMALS,
+    extension::client_ext::ClientExt,
+    fungible_asset,
+    node_api::NodeApi,
+    retry::retry_read,
+    util::{format_address, format_decimal_amount},
+};
+use anyhow::{bail, Context, Result};
+use zapatos_sdk::{
+    move_types::language_storage::TypeTag,
+    types::{
+        account_address::AccountAddress,
+        transaction::{authenticator::TransactionAuthenticator, SignedTransaction},
+    },
+};
+
+const NATIVE_COIN_TYPE: &str = "0x1::aptos_coin::AptosCoin";
+
+/// What the sender holds, in base units
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Funds {
+    /// Balance of the native coin, which pays the gas
+    pub balance: u64,
+    /// The part of the balance a slow wallet can transfer, None when it is not a slow wallet
+    pub unlocked: Option<u64>,
+    /// Balance of the coin transferred when it is not the native coin, or of the fungible asset
+    pub coin_balance: Option<u64>,
+}
+
+impl Funds {
+    /// The funds of the account, with the balance of `coin_type` when it is not the native coin
+    pub async fn get(
+        client: &impl NodeApi,
+        account: AccountAddress,
+        coin_type: Option<&TypeTag>,
+    ) -> Result<Self> {
+        let balance = coin_balance(client, account, NATIVE_COIN_TYPE).await?;
+        let unlocked = unlocked_amount(client, account).await?;
+        let coin_balance = match coin_type {
+            Some(coin_type) => Some(coin_balance(client, account, &coin_type.to_string()).await?),
+            None => None,
+        };
+        Ok(Self {
+            balance,
+            unlocked,
+            coin_balance,
+        })
+    }
+
+    /// The funds of the account, with its balance of the fungible asset of `metadata`
+    pub async fn with_fungible_asset(
+        client: &impl NodeApi,
+        account: AccountAddress,
+        metadata: AccountAddress,
+    ) -> Result<Self> {
+        let mut funds = Self::get(client, account, None).await?;
+        funds.coin_balance = Some(fungible_asset::balance(client, account, metadata, None).await?);
+        Ok(funds)
+    }
+
+    /// Fail with the shortfall when the sender cannot pay `amount` of the coin plus `max_fee`
+    /// in native coins. The gas is paid from the whole balance, only transfers of the native
+    /// coin are limited to the unlocked part of a slow wallet
+    pub fn check(&self, amount: u64, max_fee: u64) -> Result<()> {
+        let describe = |base_units: u64| {
+            format!(
+                "{} coins ({base_units} base units)",
+                format_decimal_amount(base_units, NATIVE_COIN_DECIMALS)
+            )
+        };
+        match self.coin_balance {
+            Some(coin_balance) if amount > coin_balance => bail!(
+                "The sender has {coin_balance} base units of the coin, {} short of the {amount} transferred",
+                amount - coin_balance
+            ),
+            Some(_) if max_fee > self.balance => bail!(
+                "The sender has {}, {} short of the worst-case fee of {}",
+                describe(self.balance),
+                describe(max_fee - self.balance),
+                describe(max_fee)
+            ),
+            Some(_) => Ok(()),
+            None => {
+                if let Some(unlocked) = self.unlocked.filter(|unlocked| amount > *unlocked) {
+                    bail!(
+                        "The sender is a slow wallet with {} unlocked, {} short of the {} transferred",
+                        describe(unlocked),
+                        describe(amount - unlocked),
+                        describe(amount)
+                    );
+                }
+                let needed = amount as u128 + max_fee as u128;
+                if needed > self.balance as u128 {
+                    bail!(
+                        "The sender has {}, {} short of the {} transferred plus the worst-case fee of {}",
+                        describe(self.balance),
+                        describe((needed - self.balance as u128) as u64),
+                        describe(amount),
+                        describe(max_fee)
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// All the sender can transfer once `max_fee` is paid: the balance of the coin, else the
+    /// native balance less the fee, capped by the unlocked part of a slow wallet
+    pub fn spendable(&self, max_fee: u64) -> Result<u64> {
+        if max_fee > self.balance {
+            bail!(
+                "The sender has {} base units, less than the worst-case fee of {max_fee}",
+                self.balance
+            );
+        }
+        let spendable = match self.coin_balance {
+            Some(coin_balance) => coin_balance,
+            None => {
+                let after_fee = self.balance - max_fee;
+                self.unlocked
+                    .map_or(after_fee, |unlocked| unlocked.min(after_fee))
+            }
+        };
+        if spendable == 0 {
+            bail!("The sender has nothing to transfer once the fee is paid");
+        }
+        Ok(spendable)
+    }
+}
+
+/// The account which pays the gas: the fee payer when there is one, else the sender
+pub fn gas_payer(signed_trans: &SignedTransaction) -> AccountAddress {
+    match signed_trans.authenticator() {
+        TransactionAuthenticator::FeePayer {
+            fee_payer_address, ..
+        } => fee_payer_address,
+        _ => signed_trans.sender(),
+    }
+}
+
+/// The fee the gas payer pays at most, if the transaction uses all of its max gas
+pub fn max_fee(signed_trans: &SignedTransaction) -> u64 {
+    signed_trans
+        .max_gas_amount()
+        .saturating_mul(signed_trans.gas_unit_price())
+}
+
+/// Unlike the native coin, other coins must be registered by an account before it receives any
+pub async fn has_coin_store(
+    client: &impl NodeApi,
+    account: AccountAddress,
+    coin_type: &TypeTag,
+) -> Result<bool> {
+    Ok(client
+        .get_account_resource(account, &format!("0x1::coin::CoinStore<{coin_type}>"))
+        .await
+        .context(format!(
+            "Failed to get the coin stores of {}",
+            format_address(&account)
+        ))?
+        .into_inner()
+        .is_some())
+}
+
+/// Zero when the account has no coin store of the coin
+async fn coin_balance(
+    client: &impl NodeApi,
+    account: AccountAddress,
+    coin_type: &str,
+) -> Result<u64> {
+    let resource_type = format!("0x1::coin::CoinStore<{coin_type}>");
+    let coin_store = retry_read(|| client.get_account_resource(account, &resource_type))
+        .await
+        .context(format!(
+            "Failed to get the balance of {}",
+            format_address(&account)
+        ))?
+        .into_inner();
+    match coin_store {
+        Some(coin_store) => coin_store.data["coin"]["value"]
+            .as_str()
+            .and_then(|value| value.parse().ok())
+            .context(format!("Invalid balance in {resource_type}")),
+        None => Ok(0),
+    }
+}
+
+/// The unlocked amount of a slow wallet, None when the account is not one
+async fn unlocked_amount(client: &impl NodeApi, account: AccountAddress) -> Result<Option<u64>> {
+    let slow_wallet =
+        retry_read(|| client.get_account_resource(account, "0x1::slow_wallet::SlowWallet"))
+            .await
+            .context(format!(
+                "Failed to get the slow wallet of {}",
+                format_address(&account)
+            ))?;
+    if slow_wallet.inner().is_none() {
+        return Ok(None);
+    }
+    // The view function accounts for the coins unlocked since the resource was last written
+    let unlocked = client
+        .view_ext(
+            "0x1::slow_wallet::unlocked_amount",
+            None,
+            Some(account.to_hex_literal()),
+        )
+        .await?;
+    unlocked
+        .first()
+        .and_then(|value| value.as_str())
+        .and_then(|value| value.parse().ok())
+        .map(Some)
+        .context("Invalid unlocked amount returned by the node")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_the_native_balance() {
+        let funds = Funds {
+            balance: 1_000,
+            unlocked: None,
+            coin_balance: None,
+        };
+        assert!(funds.check(900, 100).is_ok());
+        let err = funds.check(901, 100).unwrap_err().to_string();
+        assert!(err.contains("0.000001 coins (1 base units) short"), "{err}");
+        assert_eq!(funds.spendable(100).unwrap(), 900);
+        assert!(funds.spendable(1_000).is_err());
+    }
+
+    #[test]
+    fn slow_wallets_only_transfer_the_unlocked_part() {
+        // The total balance covers the transfer and the fee, the unlocked part does not
+        let funds = Funds {
+            balance: 10_000,
+            unlocked: Some(500),
+            coin_balance: None,
+        };
+        let err = funds.check(600, 100).unwrap_err().to_string();
+        assert!(err.starts_with("The sender is a slow wallet"), "{err}");
+        assert!(funds.check(500, 100).is_ok());
+        // The fee is paid from the locked part too
+        assert!(funds.check(500, 9_500).is_ok());
+        assert!(funds.check(500, 9_501).is_err());
+
+        assert_eq!(funds.spendable(100).unwrap(), 500);
+        assert_eq!(funds.spendable(9_800).unwrap(), 200);
+        assert!(funds.spendable(10_001).is_err());
+    }
+
+    #[test]
+    fn other_coins_only_pay_the_fee_in_native_coins() {
+        let funds = Funds {
+            balance: 100,
+            unlocked: Some(0),
+            coin_balance: Some(5_000),
+        };
+        assert!(funds.check(5_000, 100).is_ok());
+        assert!(funds.check(5_001, 100).is_err());
+        assert!(funds.check(5_000, 101).is_err());
+        assert_eq!(funds.spendable(100).unwrap(), 5_000);
+    }
+}
@@ -0,0 +1,162 @@
+//! Fungible assets, the standard 0L and Aptos are moving the coins to. An asset is known by the
+//! address of its metadata object, and an account holds it in its primary fungible store
+
+use crate::{
+    extension::client_ext::parse_view_request, node_api::NodeApi, retry::retry_read,
+    util::format_address,
+};
+use anyhow::{Context, Result};
+use serde_json::Value;
+use zapatos_sdk::{
+    move_types::{identifier::Identifier, language_storage::ModuleId},
+    types::{
+        account_address::AccountAddress,
+        transaction::{EntryFunction, TransactionPayload},
+    },
+};
+
+/// The type of the metadata objects, the type argument of the functions taking one
+pub const METADATA_TYPE: &str = "0x1::fungible_asset::Metadata";
+
+/// `0x1::primary_fungible_store::transfer`, which creates the primary store of the recipient when
+/// it has none
+pub fn transfer_payload(
+    metadata: AccountAddress,
+    to_account: AccountAddress,
+    base_amount: u64,
+) -> Result<TransactionPayload> {
+    Ok(TransactionPayload::EntryFunction(EntryFunction::new(
+        ModuleId::new(
+            AccountAddress::ONE,
+            Identifier::new("primary_fungible_store")?,
+        ),
+        Identifier::new("transfer")?,
+        vec![crate::client::parse_coin_type(METADATA_TYPE)?],
+        vec![
+            bcs::to_bytes(&metadata)?,
+            bcs::to_bytes(&to_account)?,
+            bcs::to_bytes(&base_amount)?,
+        ],
+    )))
+}
+
+/// Balance of the primary store of the account in base units, zero when it has none. At a
+/// ledger version or the latest one
+pub async fn balance(
+    client: &impl NodeApi,
+    account: AccountAddress,
+    metadata: AccountAddress,
+    version: Option<u64>,
+) -> Result<u64> {
+    let value = view(
+        client,
+        "0x1::primary_fungible_store::balance",
+        &format!(
+            "{}, {}",
+            account.to_hex_literal(),
+            metadata.to_hex_literal()
+        ),
+        version,
+    )
+    .await
+    .context(format!(
+        "Failed to get the balance of {} in fungible asset {}",
+        format_address(&account),
+        format_address(&metadata)
+    ))?;
+    value
+        .as_str()
+        .and_then(|balance| balance.parse().ok())
+        .context(format!("Invalid balance returned by the node: {value}"))
+}
+
+/// Number of decimal places of the asset, from its metadata
+pub async fn decimals(client: &impl NodeApi, metadata: AccountAddress) -> Result<u8> {
+    let value = view(
+        client,
+        "0x1::fungible_asset::decimals",
+        &metadata.to_hex_literal(),
+        None,
+    )
+    .await
+    .context(format!(
+        "Failed to get the decimals of fungible asset {}, is it the address of its metadata?",
+        format_address(&metadata)
+    ))?;
+    value
+        .as_u64()
+        .and_then(|decimals| u8::try_from(decimals).ok())
+        .context(format!("Invalid decimals returned by the node: {value}"))
+}
+
+/// The symbol of the asset, from its metadata
+pub async fn symbol(client: &impl NodeApi, metadata: AccountAddress) -> Result<String> {
+    let value = view(
+        client,
+        "0x1::fungible_asset::symbol",
+        &metadata.to_hex_literal(),
+        None,
+    )
+    .await
+    .context(format!(
+        "Failed to get the symbol of fungible asset {}",
+        format_address(&metadata)
+    ))?;
+    value
+        .as_str()
+        .map(str::to_string)
+        .context(format!("Invalid symbol returned by the node: {value}"))
+}
+
+/// The single value returned by a view function of the metadata type
+async fn view(
+    client: &impl NodeApi,
+    function_id: &str,
+    args: &str,
+    version: Option<u64>,
+) -> Result<Value> {
+    let request = parse_view_request(
+        function_id,
+        Some(METADATA_TYPE.to_string()),
+        Some(args.to_string()),
+    )?;
+    retry_read(|| client.view(&request, version))
+        .await?
+        .into_inner()
+        .into_iter()
+        .next()
+        .context(format!("{function_id} returned nothing"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transfer_through_the_primary_store() {
+        let metadata = AccountAddress::from_hex_literal("0xa").unwrap();
+        let to = AccountAddress::from_hex_literal("0xcafe").unwrap();
+        let payload = transfer_payload(metadata, to, 100).unwrap();
+        let entry_function = match payload {
+            TransactionPayload::EntryFunction(entry_function) => entry_function,
+            _ => panic!("not an entry function"),
+        };
+        assert_eq!(
+            entry_function.module(),
+            &ModuleId::new(
+                AccountAddress::ONE,
+                Identifier::new("primary_fungible_store").unwrap()
+            )
+        );
+        assert_eq!(entry_function.function().as_str(), "transfer");
+        assert_eq!(entry_function.ty_args()[0].to_string(), METADATA_TYPE);
+        assert_eq!(
+            entry_function.args(),
+            &[
+                bcs::to_bytes(&metadata).unwrap(),
+                bcs::to_bytes(&to).unwrap(),
+                bcs::to_bytes(&100u64).unwrap()
+            ]
+        );
+    }
+}
@@ -0,0 +1,289 @@
+//! An append-only record of the submitted transactions, so that a job run again does not
+//! submit the same payment twice
+
+use crate::{
+    lock::FileLock,
+    node_api::NodeApi,
+    retry::retry_read,
+    util::{format_signed_transaction_json, transaction_hash},
+};
+use anyhow::{bail, Context, Result};
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use zapatos_sdk::{
+    crypto::HashValue,
+    rest_client::{aptos_api_types::Transaction, error::RestError},
+    types::transaction::SignedTransaction,
+};
+
+static JOURNAL: Mutex<Option<Journal>> = Mutex::new(None);
+
+/// A JSONL file with a line per submission and per outcome
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Journal {
+    pub path: PathBuf,
+    /// Submit the duplicates of recorded transactions anyway, still recording them
+    pub allow_duplicate: bool,
+}
+
+/// Set once from --journal, every submission is then recorded
+pub fn set_journal(journal: Option<Journal>) {
+    *JOURNAL.lock().expect("the journal lock is never poisoned") = journal;
+}
+
+fn journal() -> Option<Journal> {
+    JOURNAL
+        .lock()
+        .expect("the journal lock is never poisoned")
+        .clone()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    Submitted,
+    Committed,
+    /// Rejected by the node, or committed but aborted
+    Failed,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    time: String,
+    status: Status,
+    transaction_hash: String,
+    /// The submitted entries describe the transaction, the outcomes only refer to its hash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sender: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sequence_number: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expiration_timestamp_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    payload: Option<Value>,
+}
+
+/// The hash of the sender and the payload: the function, its type arguments and arguments, and
+/// so the amount. The sequence number is left out, as a job run again signs the same payment with
+/// the next one
+pub fn content_hash(signed_trans: &SignedTransaction) -> Result<HashValue> {
+    Ok(HashValue::sha3_256_of(&bcs::to_bytes(&(
+        signed_trans.sender(),
+        signed_trans.payload(),
+    ))?))
+}
+
+/// Refuse the transaction when the journal has the same one committed or still pending, then
+/// record it as submitted. A transaction with the same sequence number can only replace the
+/// recorded one, so it is no duplicate. Does nothing without --journal
+pub async fn record_submission(
+    client: &impl NodeApi,
+    signed_trans: &SignedTransaction,
+) -> Result<()> {
+    let journal = match journal() {
+        Some(journal) => journal,
+        None => return Ok(()),
+    };
+    // Held until the submission is recorded, so that concurrent jobs cannot both go ahead
+    let _lock = FileLock::acquire(&journal.path)?;
+    let entries = read_entries(&journal.path)?;
+    let content_hash = content_hash(signed_trans)?.to_hex_literal();
+
+    if !journal.allow_duplicate {
+        for (entry, status) in duplicates(&entries, &content_hash, signed_trans.sequence_number()) {
+            let status = match status {
+                Status::Submitted => status_on_chain(client, entry).await?,
+                status => Some(status),
+            };
+            let state = match status {
+                Some(Status::Committed) => "committed",
+                Some(Status::Submitted) => "pending",
+                Some(Status::Failed) | None => continue,
+            };
+            bail!(
+                "{} has the same transaction {state} as {}, with sequence number {}. Pass --allow-duplicate to submit it anyway",
+                journal.path.display(),
+                entry.transaction_hash,
+                entry.sequence_number.unwrap_or_default()
+            );
+        }
+    }
+
+    append(
+        &journal.path,
+        &Entry {
+            time: now(),
+            status: Status::Submitted,
+            transaction_hash: transaction_hash(signed_trans).to_hex_literal(),
+            content_hash: Some(content_hash),
+            sender: Some(signed_trans.sender().to_hex_literal()),
+            sequence_number: Some(signed_trans.sequence_number()),
+            expiration_timestamp_secs: Some(signed_trans.expiration_timestamp_secs()),
+            payload: serde_json::to_value(
+                format_signed_transaction_json(signed_trans, None).payload,
+            )
+            .ok(),
+        },
+    )
+}
+
+/// Record whether a transaction was committed successfully, it failed when it was rejected or
+/// aborted. A failure to write is only a warning, the
+/// outcome is looked up on-chain the next time it matters
+pub fn record_outcome(hash: HashValue, success: bool) {
+    let journal = match journal() {
+        Some(journal) => journal,
+        None => return,
+    };
+    let entry = Entry {
+        time: now(),
+        status: if success {
+            Status::Committed
+        } else {
+            Status::Failed
+        },
+        transaction_hash: hash.to_hex_literal(),
+        content_hash: None,
+        sender: None,
+        sequence_number: None,
+        expiration_timestamp_secs: None,
+        payload: None,
+    };
+    let recorded = FileLock::acquire(&journal.path).and_then(|_lock| append(&journal.path, &entry));
+    if let Err(err) = recorded {
+        eprintln!("Warning: {err:#}");
+    }
+}
+
+/// The submissions of the same content with another sequence number, with their last status
+fn duplicates<'a>(
+    entries: &'a [Entry],
+    content_hash: &str,
+    sequence_number: u64,
+) -> Vec<(&'a Entry, Status)> {
+    let mut statuses = HashMap::new();
+    for entry in entries {
+        statuses.insert(entry.transaction_hash.as_str(), entry.status);
+    }
+    entries
+        .iter()
+        .filter(|entry| {
+            entry.content_hash.as_deref() == Some(content_hash)
+                && entry.sequence_number != Some(sequence_number)
+        })
+        .map(|entry| (entry, statuses[entry.transaction_hash.as_str()]))
+        .collect()
+}
+
+/// None when the node has not got the transaction and it expired, so it never will be committed
+async fn status_on_chain(client: &impl NodeApi, entry: &Entry) -> Result<Option<Status>> {
+    let hash = HashValue::from_hex(entry.transaction_hash.trim_start_matches("0x")).context(
+        format!("Invalid hash {} in the journal", entry.transaction_hash),
+    )?;
+    match retry_read(|| client.get_transaction_by_hash(hash)).await {
+        Ok(response) => Ok(Some(match response.into_inner() {
+            Transaction::PendingTransaction(_) => Status::Submitted,
+            transaction if transaction.success() => Status::Committed,
+            _ => Status::Failed,
+        })),
+        Err(RestError::Api(err)) if err.status_code.as_u16() == 404 => {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+            let expired = entry
+                .expiration_timestamp_secs
+                .map_or(true, |expiration| expiration < now);
+            // Not expired, it may still be on its way to the node
+            Ok((!expired).then_some(Status::Submitted))
+        }
+        Err(err) => Err(err).context(format!(
+            "Failed to check whether the transaction {} of the journal is committed",
+            entry.transaction_hash
+        )),
+    }
+}
+
+fn read_entries(path: &Path) -> Result<Vec<Entry>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    fs::read_to_string(path)
+        .context(format!("Failed to read {}", path.display()))?
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).context(format!(
+                "Invalid entry at line {} of the journal {}",
+                index + 1,
+                path.display()
+            ))
+        })
+        .collect()
+}
+
+/// Only ever appended to, a line per entry
+fn append(path: &Path, entry: &Entry) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .context(format!("Failed to open the journal {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)
+        .context(format!("Failed to write to the journal {}", path.display()))
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(status: Status, hash: &str, content_hash: Option<&str>, sequence: u64) -> Entry {
+        Entry {
+            time: now(),
+            status,
+            transaction_hash: hash.to_string(),
+            content_hash: content_hash.map(str::to_string),
+            sender: None,
+            sequence_number: content_hash.map(|_| sequence),
+            expiration_timestamp_secs: None,
+            payload: None,
+        }
+    }
+
+    #[test]
+    fn find_the_duplicates_with_their_last_status() {
+        let entries = [
+            entry(Status::Submitted, "0xa", Some("0xc"), 1),
+            entry(Status::Committed, "0xa", None, 0),
+            entry(Status::Submitted, "0xb", Some("0xc"), 2),
+            entry(Status::Submitted, "0xd", Some("0xe"), 3),
+        ];
+        let found = duplicates(&entries, "0xc", 5);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].1, Status::Committed);
+        assert_eq!(found[1].1, Status::Submitted);
+        // A replacement with the same sequence number cannot be committed as well
+        assert_eq!(duplicates(&entries, "0xc", 1).len(), 1);
+        assert!(duplicates(&entries, "0xf", 1).is_empty());
+
+        let line = serde_json::to_string(&entries[1]).unwrap();
+        assert!(!line.contains("content_hash"), "{line}");
+        assert_eq!(
+            serde_json::from_str::<Entry>(&line).unwrap().status,
+            Status::Committed
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! A small path language to pick a field out of a JSON document for --query: dot paths as in
+//! `data.coin.value` or `[0].vals[2]`, with `["..."]` for keys holding dots, and JSON pointers
+//! as in `/data/coin/value`
+
+use crate::util::{format_output, OutputFormat};
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// The value at `path`. When a key or an index is missing, the error lists what is available
+/// at that point of the path
+pub fn select(value: &Value, path: &str) -> Result<Value> {
+    let segments = parse_path(path).context(format!("Invalid --query: {path}"))?;
+    let mut current = value;
+    let mut walked = String::new();
+    for segment in segments {
+        let at = if walked.is_empty() {
+            "the top level".to_string()
+        } else {
+            format!("`{walked}`")
+        };
+        current = match (current, &segment) {
+            (Value::Object(object), Segment::Key(key)) => match object.get(key) {
+                Some(value) => value,
+                None => {
+                    let keys = object.keys().map(String::as_str).collect::<Vec<_>>();
+                    bail!(
+                        "No key `{key}` at {at}, the available keys are: {}",
+                        keys.join(", ")
+                    )
+                }
+            },
+            (Value::Array(array), segment) => {
+                // A JSON pointer gives indexes as keys
+                let index = match segment {
+                    Segment::Index(index) => Some(*index),
+                    Segment::Key(key) => key.parse::<usize>().ok(),
+                };
+                match index.and_then(|index| array.get(index)) {
+                    Some(value) => value,
+                    None => bail!(
+                        "No {} at {at}, which is an array of {} element(s), indexed from 0",
+                        describe(segment),
+                        array.len()
+                    ),
+                }
+            }
+            (value, segment) => bail!(
+                "No {} at {at}, which is {}",
+                describe(segment),
+                kind_of(value)
+            ),
+        };
+        walked.push_str(&match &segment {
+            Segment::Key(key) if walked.is_empty() => key.to_owned(),
+            Segment::Key(key) => format!(".{key}"),
+            Segment::Index(index) => format!("[{index}]"),
+        });
+    }
+    Ok(current.to_owned())
+}
+
+/// The selected value as printed by --query. Scalars are bare in the text output format, to be
+/// read into shell variables, the json and yaml formats print a document as without --query
+pub fn format_selected(value: &Value, format: OutputFormat) -> Result<String> {
+    match (format, value) {
+        (OutputFormat::Text, Value::String(string)) => Ok(string.to_owned()),
+        (OutputFormat::Text, Value::Null | Value::Bool(_) | Value::Number(_)) => {
+            Ok(value.to_string())
+        }
+        _ => format_output(value, format),
+    }
+}
+
+fn parse_path(path: &str) -> Result<Vec<Segment>> {
+    let path = path.trim();
+    if let Some(pointer) = path.strip_prefix('/') {
+        return Ok(pointer
+            .split('/')
+            .map(|token| Segment::Key(token.replace("~1", "/").replace("~0", "~")))
+            .collect());
+    }
+
+    let mut segments = vec![];
+    let mut chars = path.chars();
+    let mut key = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            // A leading dot is allowed, as in jq
+            '.' => {
+                if !key.is_empty() {
+                    segments.push(Segment::Key(std::mem::take(&mut key)));
+                }
+            }
+            '[' => {
+                if !key.is_empty() {
+                    segments.push(Segment::Key(std::mem::take(&mut key)));
+                }
+                let mut inside = String::new();
+                loop {
+                    match chars.next() {
+                        Some(']') if !is_open_quote(&inside) => break,
+                        Some(c) => inside.push(c),
+                        None => bail!("Missing ] after [{inside}"),
+                    }
+                }
+                segments.push(if is_quoted(&inside) {
+                    Segment::Key(
+                        serde_json::from_str(&inside)
+                            .context(format!("Invalid quoted key [{inside}]"))?,
+                    )
+                } else {
+                    Segment::Index(
+                        inside
+                            .trim()
+                            .parse()
+                            .context(format!("[{inside}] is neither an index nor a quoted key"))?,
+                    )
+                });
+            }
+            c => key.push(c),
+        }
+    }
+    if !key.is_empty() {
+        segments.push(Segment::Key(key));
+    }
+    Ok(segments)
+}
+
+fn is_quoted(text: &str) -> bool {
+    text.len() >= 2 && text.starts_with('"') && text.ends_with('"') && !text.ends_with("\\\"")
+}
+
+/// Whether a `]` read now would be part of a quoted key
+fn is_open_quote(text: &str) -> bool {
+    text.starts_with('"') && !is_quoted(text)
+}
+
+fn describe(segment: &Segment) -> String {
+    match segment {
+        Segment::Key(key) => format!("key `{key}`"),
+        Segment::Index(index) => format!("index {index}"),
+    }
+}
+
+fn kind_of(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a bool",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn select_by_dot_path_and_pointer() {
+        let value = json!([{ "coin": { "value": "42" }, "vals": [1, 2, 3], "a.b": true }]);
+        assert_eq!(select(&value, "[0].coin.value").unwrap(), json!("42"));
+        assert_eq!(select(&value, "[0].vals[2]").unwrap(), json!(3));
+        assert_eq!(select(&value, r#"[0]["a.b"]"#).unwrap(), json!(true));
+        assert_eq!(select(&value, "/0/coin/value").unwrap(), json!("42"));
+        assert_eq!(
+            select(&value, ".[0].coin").unwrap(),
+            json!({ "value": "42" })
+        );
+        assert_eq!(select(&value, "").unwrap(), value);
+
+        let err = select(&value, "[0].coin.amount").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No key `amount` at `[0].coin`, the available keys are: value"
+        );
+        let err = select(&value, "[0].vals[3]").unwrap_err();
+        assert!(err.to_string().contains("array of 3 element(s)"), "{err}");
+        assert!(select(&value, "[0].vals[x]").is_err());
+
+        assert_eq!(
+            format_selected(&json!("42"), OutputFormat::Text).unwrap(),
+            "42"
+        );
+        assert_eq!(
+            format_selected(&json!("42"), OutputFormat::Json).unwrap(),
+            "\"42\""
+        );
+    }
+}
@@ -0,0 +1,26 @@
+use crate::unsigned_transaction::signing_message;
+use anyhow::Result;
+pub use libra_wallet::ledger::Ledger;
+use zapatos_sdk::types::transaction::{RawTransaction, SignedTransaction};
+
+/// Signing transactions with the key of a Ledger device, the device itself is in libra-wallet
+pub trait LedgerExt {
+    /// Have the device show the transaction and sign it once the user approves it
+    fn sign_transaction(
+        &self,
+        derivation_index: u32,
+        raw_trans: RawTransaction,
+    ) -> Result<SignedTransaction>;
+}
+
+impl LedgerExt for Ledger {
+    fn sign_transaction(
+        &self,
+        derivation_index: u32,
+        raw_trans: RawTransaction,
+    ) -> Result<SignedTransaction> {
+        let public_key = self.public_key(derivation_index)?;
+        let signature = self.sign(derivation_index, &signing_message(&raw_trans)?)?;
+        Ok(SignedTransaction::new(raw_trans, public_key, signature))
+    }
+}
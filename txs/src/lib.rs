@@ -1,5 +1,34 @@
+pub mod abi;
+pub mod abi_cache;
+pub mod address_book;
+pub mod args;
+pub mod client;
 pub mod constant;
+pub mod error;
+pub mod exit_code;
+pub mod explorer;
 pub mod extension;
+pub mod fan_out;
+pub mod funds;
+pub mod fungible_asset;
+pub mod journal;
+pub mod json_query;
+#[cfg(feature = "ledger")]
+pub mod ledger;
+pub mod lock;
+pub mod logging;
+pub mod message;
+pub mod move_abort;
+pub mod move_value;
+pub mod multisig;
+pub mod network;
+pub mod node_api;
+pub mod nonce;
+pub mod retry;
+pub mod secret;
+pub mod signer;
+pub mod sponsored_transaction;
+pub mod unsigned_transaction;
 pub mod util;
 pub mod coin_client {
     pub use zapatos_sdk::coin_client::*;
@@ -7,6 +36,9 @@ pub mod coin_client {
 pub mod crypto {
     pub use zapatos_sdk::crypto::*;
 }
+pub mod move_types {
+    pub use zapatos_sdk::move_types::*;
+}
 pub mod rest_client {
     pub use zapatos_sdk::rest_client::*;
 }
@@ -0,0 +1,72 @@
+//! Exclusive access to a file shared by concurrent txs processes
+
+use crate::constant::LOCK_TIMEOUT_SECS;
+use anyhow::{bail, Context, Result};
+use std::{
+    fs::{self, OpenOptions},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Held while `<path>.lock` exists, the lock file is removed when it is dropped
+#[derive(Debug)]
+pub struct FileLock {
+    path: PathBuf,
+}
+
+impl FileLock {
+    /// Wait for the other processes to release the lock of `path`, up to LOCK_TIMEOUT_SECS
+    pub fn acquire(path: &Path) -> Result<Self> {
+        let mut lock_path = path.as_os_str().to_owned();
+        lock_path.push(".lock");
+        let lock_path = PathBuf::from(lock_path);
+        let started = Instant::now();
+        loop {
+            // Creating the file fails when it exists, whichever process gets there first
+            match OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&lock_path)
+            {
+                Ok(_) => return Ok(Self { path: lock_path }),
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                    if started.elapsed() >= Duration::from_secs(LOCK_TIMEOUT_SECS) {
+                        bail!(
+                            "{} is still locked after {LOCK_TIMEOUT_SECS}s. If no other txs process uses it, delete {}",
+                            path.display(),
+                            lock_path.display()
+                        );
+                    }
+                    thread::sleep(Duration::from_millis(50));
+                }
+                Err(err) => {
+                    return Err(err).context(format!("Failed to create {}", lock_path.display()))
+                }
+            }
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn release_the_lock_when_dropped() {
+        let path = std::env::temp_dir().join("txs-lock-test.json");
+        let lock = FileLock::acquire(&path).unwrap();
+        let lock_path = lock.path.clone();
+        assert!(lock_path.exists());
+        drop(lock);
+        assert!(!lock_path.exists());
+        FileLock::acquire(&path).unwrap();
+    }
+}
@@ -0,0 +1,92 @@
+use crate::util::colors_enabled;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::{fs::File, path::Path, sync::Mutex};
+use tracing::Level;
+use tracing_subscriber::fmt::writer::{BoxMakeWriter, MakeWriterExt};
+
+const REDACTED_FIELDS: [&str; 3] = ["signature", "private_key", "secret"];
+
+/// Log to stderr from -v on: the endpoints used and the retries at info level, every request
+/// with its status and latency at debug level (-vv), and the payloads at trace level (-vvv).
+/// The log file gets the same lines, at info level on its own. Without either nothing is set
+/// up, so the output is unchanged
+pub fn init(verbose: u8, log_file: Option<&Path>) -> Result<()> {
+    let level = match verbose {
+        0 if log_file.is_none() => return Ok(()),
+        0 | 1 => Level::INFO,
+        2 => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+    let file = log_file
+        .map(|path| {
+            File::create(path).context(format!("Failed to create the log file {}", path.display()))
+        })
+        .transpose()?;
+    let writer = match (verbose, file) {
+        (_, None) => BoxMakeWriter::new(std::io::stderr),
+        (0, Some(file)) => BoxMakeWriter::new(Mutex::new(file)),
+        (_, Some(file)) => BoxMakeWriter::new(std::io::stderr.and(Mutex::new(file))),
+    };
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(writer)
+        .with_ansi(log_file.is_none() && colors_enabled())
+        .try_init()
+        .map_err(|err| anyhow::anyhow!(err))
+        .context("Failed to set up logging")
+}
+
+/// Replace the signatures and keys in a payload before it is logged
+pub fn redact(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                if REDACTED_FIELDS
+                    .iter()
+                    .any(|redacted| name.contains(redacted))
+                {
+                    *field = Value::String("<redacted>".to_string());
+                } else {
+                    redact(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+/// A payload as JSON with its signatures and keys redacted, for trace logs
+pub fn redacted_json<T: serde::Serialize>(payload: &T) -> String {
+    match serde_json::to_value(payload) {
+        Ok(mut value) => {
+            redact(&mut value);
+            value.to_string()
+        }
+        Err(err) => format!("<not serializable: {err}>"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn redact_signatures_in_payloads() {
+        let mut payload = json!({
+            "sender": "0x1",
+            "authenticator": {"Ed25519": {"public_key": "0xab", "signature": "0xcd"}},
+            "secondary_signers": [{"private_key": "0xef"}],
+        });
+        redact(&mut payload);
+        assert_eq!(payload["authenticator"]["Ed25519"]["public_key"], "0xab");
+        assert_eq!(
+            payload["authenticator"]["Ed25519"]["signature"],
+            "<redacted>"
+        );
+        assert_eq!(payload["secondary_signers"][0]["private_key"], "<redacted>");
+        assert_eq!(payload["sender"], "0x1");
+    }
+}
@@ -1,10 +1,16 @@
-use anyhow::Result;
 use clap::Parser;
-use txs_cli::TxsCli;
+use libra_wallet::secret::install_panic_hook;
+use std::process;
+use txs::exit_code::exit_code;
+use txs_cli::{print_error, TxsCli};
 
 mod txs_cli;
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    TxsCli::parse().run().await
+async fn main() {
+    install_panic_hook();
+    if let Err(err) = TxsCli::parse().run().await {
+        print_error(&err);
+        process::exit(exit_code(err));
+    }
 }
@@ -0,0 +1,48 @@
+use anyhow::{Context, Result};
+use zapatos_sdk::crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+    Signature, SigningKey,
+};
+
+/// Prefix of every signed message. Transactions are signed behind the hash of their type name,
+/// so the signature of a message can never be replayed as the signature of a transaction
+pub const SIGNED_MESSAGE_PREFIX: &[u8] = b"0L Signed Message:\n";
+
+/// The bytes actually signed for an off-chain message
+pub fn signing_message(message: &[u8]) -> Vec<u8> {
+    [SIGNED_MESSAGE_PREFIX, message].concat()
+}
+
+/// Sign an off-chain message, e.g. to prove control of an address
+pub fn sign_message(private_key: &Ed25519PrivateKey, message: &[u8]) -> Ed25519Signature {
+    private_key.sign_arbitrary_message(&signing_message(message))
+}
+
+pub fn verify_message(
+    public_key: &Ed25519PublicKey,
+    message: &[u8],
+    signature: &Ed25519Signature,
+) -> Result<()> {
+    signature
+        .verify_arbitrary_msg(&signing_message(message), public_key)
+        .context("The signature does not match the message and the public key")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zapatos_sdk::crypto::PrivateKey;
+
+    #[test]
+    fn sign_and_verify_messages() {
+        let private_key = Ed25519PrivateKey::try_from(&[7u8; 32][..]).unwrap();
+        let public_key = private_key.public_key();
+        let signature = sign_message(&private_key, b"airdrop registration");
+        verify_message(&public_key, b"airdrop registration", &signature).unwrap();
+        assert!(verify_message(&public_key, b"another message", &signature).is_err());
+
+        // The prefix is part of what is signed, the bare message does not verify
+        let bare = private_key.sign_arbitrary_message(b"airdrop registration");
+        assert!(verify_message(&public_key, b"airdrop registration", &bare).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use zapatos_move_binary_format::CompiledModule;
+use zapatos_sdk::{move_types::errmap::ErrorDescription, types::account_address::AccountAddress};
+
+const METADATA_V0_KEY: &[u8] = b"aptos::metadata_v0";
+const METADATA_V1_KEY: &[u8] = b"aptos::metadata_v1";
+
+/// Mirrors the metadata the framework compiler attaches to modules, only the error map is used
+#[derive(Deserialize)]
+struct RuntimeModuleMetadataV0 {
+    error_map: BTreeMap<u64, ErrorDescription>,
+}
+
+#[derive(Deserialize)]
+struct RuntimeModuleMetadataV1 {
+    error_map: BTreeMap<u64, ErrorDescription>,
+    #[allow(dead_code)]
+    struct_attributes: BTreeMap<String, Vec<KnownAttribute>>,
+    #[allow(dead_code)]
+    fun_attributes: BTreeMap<String, Vec<KnownAttribute>>,
+}
+
+#[derive(Deserialize)]
+struct KnownAttribute {
+    #[allow(dead_code)]
+    kind: u8,
+    #[allow(dead_code)]
+    args: Vec<String>,
+}
+
+/// A Move abort the node could not name, as in `Move abort in 0x1::slow_wallet: 0x30008`
+#[derive(Debug, PartialEq, Eq)]
+pub struct MoveAbort {
+    pub module_address: AccountAddress,
+    pub module_name: String,
+    pub code: u64,
+}
+
+impl MoveAbort {
+    /// Returns None for any other VM status, including aborts the node already named
+    pub fn parse(vm_status: &str) -> Option<Self> {
+        let (location, code) = vm_status.strip_prefix("Move abort in ")?.split_once(": ")?;
+        let (module_address, module_name) = location.split_once("::")?;
+        Some(Self {
+            module_address: AccountAddress::from_hex_literal(module_address).ok()?,
+            module_name: module_name.to_string(),
+            code: u64::from_str_radix(code.trim().strip_prefix("0x")?, 16).ok()?,
+        })
+    }
+
+    /// Look the abort code up in the error map of the module bytecode
+    pub fn explain(&self, module_bytes: &[u8]) -> Result<Option<ErrorDescription>> {
+        let module = CompiledModule::deserialize(module_bytes).context(format!(
+            "Failed to deserialize the module {}",
+            self.module_name
+        ))?;
+
+        let mut error_map = BTreeMap::new();
+        for metadata in &module.metadata {
+            if metadata.key == METADATA_V1_KEY {
+                error_map = bcs::from_bytes::<RuntimeModuleMetadataV1>(&metadata.value)?.error_map;
+            } else if metadata.key == METADATA_V0_KEY {
+                error_map = bcs::from_bytes::<RuntimeModuleMetadataV0>(&metadata.value)?.error_map;
+            }
+        }
+
+        // Error constants are declared with the reason only, the category sits on top of it
+        Ok(error_map
+            .remove(&(self.code & 0xFFFF))
+            .or_else(|| error_map.remove(&self.code)))
+    }
+
+    pub fn format(&self, description: &ErrorDescription) -> String {
+        format!(
+            "Move abort in {}::{}: {}({:#x}): {}",
+            self.module_address.to_hex_literal(),
+            self.module_name,
+            description.code_name,
+            self.code,
+            description.code_description
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_move_abort_from_vm_status() {
+        assert_eq!(
+            MoveAbort::parse("Move abort in 0x1::slow_wallet: 0x30008"),
+            Some(MoveAbort {
+                module_address: AccountAddress::ONE,
+                module_name: "slow_wallet".to_string(),
+                code: 0x30008,
+            })
+        );
+        assert_eq!(
+            MoveAbort::parse(
+                "Move abort in 0x1::coin: EINSUFFICIENT_BALANCE(0x10006): Not enough coins"
+            ),
+            None
+        );
+        assert_eq!(MoveAbort::parse("Out of gas"), None);
+    }
+}
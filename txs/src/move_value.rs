@@ -0,0 +1,232 @@
+use crate::{extension::client_ext::ClientExt, node_api::NodeApi};
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use zapatos_sdk::{
+    rest_client::aptos_api_types::{
+        MoveModule, MoveStructField, MoveStructTag, MoveType, ViewRequest,
+    },
+    types::account_address::AccountAddress,
+};
+
+/// Structs the node already returns in a readable form, their fields are not needed
+const STRING_TYPE: &str = "0x1::string::String";
+const OPTION_TYPE: &str = "0x1::option::Option";
+
+/// Field layouts of the structs reachable from a set of Move types, by `address::module::name`
+#[derive(Default)]
+pub struct StructLayouts {
+    structs: HashMap<String, Vec<MoveStructField>>,
+}
+
+impl StructLayouts {
+    /// Load the fields of every struct reachable from the types, fetching the ABI of each module once
+    pub async fn load(client: &impl NodeApi, types: &[MoveType]) -> Result<Self> {
+        let mut layouts = Self::default();
+        let mut modules: HashMap<String, MoveModule> = HashMap::new();
+        let mut pending = types.to_vec();
+        while let Some(ty) = pending.pop() {
+            let tag = match ty {
+                MoveType::Vector { items } => {
+                    pending.push(*items);
+                    continue;
+                }
+                MoveType::Struct(tag) => tag,
+                _ => continue,
+            };
+            pending.extend(tag.generic_type_params.iter().cloned());
+
+            let key = struct_key(&tag);
+            if key == STRING_TYPE || key == OPTION_TYPE || layouts.structs.contains_key(&key) {
+                continue;
+            }
+            let module_key = format!("{}::{}", tag.address, tag.module);
+            if !modules.contains_key(&module_key) {
+                let abi = client
+                    .get_module_abi(*tag.address.inner(), tag.module.as_str())
+                    .await?;
+                modules.insert(module_key.clone(), abi);
+            }
+            let fields = modules[&module_key]
+                .structs
+                .iter()
+                .find(|s| s.name == tag.name)
+                .map(|s| s.fields.clone())
+                .context(format!("Struct {key} not found in the ABI of its module"))?;
+            pending.extend(
+                fields
+                    .iter()
+                    .map(|field| substitute(&field.typ, &tag.generic_type_params)),
+            );
+            layouts.structs.insert(key, fields);
+        }
+        Ok(layouts)
+    }
+
+    /// Decode a value as the node returns it for a Move type: integers as numbers,
+    /// addresses in full, `vector<u8>` as hex and structs as objects of decoded fields
+    pub fn decode(&self, ty: &MoveType, value: &Value) -> Value {
+        match ty {
+            // Only integers which fit in a JSON number are converted, larger ones stay strings
+            MoveType::U64 | MoveType::U128 | MoveType::U256 => {
+                match value.as_str().and_then(|v| v.parse::<u64>().ok()) {
+                    Some(number) => json!(number),
+                    None => value.to_owned(),
+                }
+            }
+            MoveType::Address => {
+                match value
+                    .as_str()
+                    .and_then(|v| AccountAddress::from_hex_literal(v).ok())
+                {
+                    Some(address) => json!(format!("0x{}", address.to_hex())),
+                    None => value.to_owned(),
+                }
+            }
+            // The node already encodes vector<u8> as a hex string
+            MoveType::Vector { items } if matches!(**items, MoveType::U8) => value.to_owned(),
+            MoveType::Vector { items } => match value.as_array() {
+                Some(values) => {
+                    Value::Array(values.iter().map(|v| self.decode(items, v)).collect())
+                }
+                None => value.to_owned(),
+            },
+            MoveType::Struct(tag) => self.decode_struct(tag, value),
+            _ => value.to_owned(),
+        }
+    }
+
+    fn decode_struct(&self, tag: &MoveStructTag, value: &Value) -> Value {
+        let key = struct_key(tag);
+        if key == OPTION_TYPE {
+            // An option is a vector of zero or one element
+            return match (tag.generic_type_params.first(), value["vec"].as_array()) {
+                (Some(ty), Some(values)) => match values.first() {
+                    Some(inner) => self.decode(ty, inner),
+                    None => Value::Null,
+                },
+                _ => value.to_owned(),
+            };
+        }
+        match self.structs.get(&key) {
+            Some(fields) => Value::Object(
+                fields
+                    .iter()
+                    .map(|field| {
+                        let ty = substitute(&field.typ, &tag.generic_type_params);
+                        let name = field.name.to_string();
+                        let decoded = self.decode(&ty, &value[name.as_str()]);
+                        (name, decoded)
+                    })
+                    .collect(),
+            ),
+            None => value.to_owned(),
+        }
+    }
+}
+
+/// Decode the values returned by a view function using the return types declared in its ABI
+pub async fn decode_view_result(
+    client: &impl NodeApi,
+    request: &ViewRequest,
+    result: &[Value],
+) -> Result<Vec<Value>> {
+    let module = &request.function.module;
+    let function = client
+        .get_module_abi(*module.address.inner(), module.name.as_str())
+        .await?
+        .exposed_functions
+        .into_iter()
+        .find(|f| f.name == request.function.name)
+        .context(format!(
+            "Function {} not found in the ABI of its module",
+            request.function
+        ))?;
+
+    let return_types = function
+        .return_
+        .iter()
+        .map(|ty| substitute(ty, &request.type_arguments))
+        .collect::<Vec<_>>();
+    let layouts = StructLayouts::load(client, &return_types).await?;
+    Ok(return_types
+        .iter()
+        .zip(result)
+        .map(|(ty, value)| layouts.decode(ty, value))
+        .collect())
+}
+
+fn struct_key(tag: &MoveStructTag) -> String {
+    format!("{}::{}::{}", tag.address, tag.module, tag.name)
+}
+
+/// Replace the generic type parameters of a declared type with the actual type arguments
+pub(crate) fn substitute(ty: &MoveType, type_args: &[MoveType]) -> MoveType {
+    match ty {
+        MoveType::GenericTypeParam { index } => type_args
+            .get(*index as usize)
+            .cloned()
+            .unwrap_or_else(|| ty.to_owned()),
+        MoveType::Vector { items } => MoveType::Vector {
+            items: Box::new(substitute(items, type_args)),
+        },
+        MoveType::Struct(tag) => MoveType::Struct(MoveStructTag {
+            generic_type_params: tag
+                .generic_type_params
+                .iter()
+                .map(|ty| substitute(ty, type_args))
+                .collect(),
+            ..tag.to_owned()
+        }),
+        _ => ty.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zapatos_sdk::move_types::parser::parse_type_tag;
+
+    fn move_type(ty: &str) -> MoveType {
+        MoveType::from(&parse_type_tag(ty).unwrap())
+    }
+
+    #[test]
+    fn decode_values_by_declared_type() {
+        let layouts = StructLayouts::default();
+        assert_eq!(layouts.decode(&MoveType::U64, &json!("42")), json!(42));
+        assert_eq!(
+            layouts.decode(
+                &MoveType::U128,
+                &json!("340282366920938463463374607431768211455")
+            ),
+            json!("340282366920938463463374607431768211455")
+        );
+        assert_eq!(
+            layouts.decode(&MoveType::Address, &json!("0x1")),
+            json!(format!("0x{}", AccountAddress::ONE.to_hex()))
+        );
+        assert_eq!(
+            layouts.decode(&move_type("vector<u8>"), &json!("0x0102")),
+            json!("0x0102")
+        );
+        assert_eq!(
+            layouts.decode(&move_type("vector<u64>"), &json!(["1", "2"])),
+            json!([1, 2])
+        );
+        assert_eq!(
+            layouts.decode(
+                &move_type("0x1::option::Option<u64>"),
+                &json!({ "vec": ["7"] })
+            ),
+            json!(7)
+        );
+        assert_eq!(
+            layouts.decode(
+                &move_type("0x1::option::Option<u64>"),
+                &json!({ "vec": [] })
+            ),
+            Value::Null
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use crate::{unsigned_transaction::UnsignedTransaction, util::format_address};
+use anyhow::{bail, Context, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+use zapatos_sdk::{
+    crypto::{
+        ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+        multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
+        PrivateKey, Signature, ValidCryptoMaterialStringExt,
+    },
+    types::transaction::{
+        authenticator::{AuthenticationKey, TransactionAuthenticator},
+        SignedTransaction,
+    },
+};
+
+/// Version of the proposal file written by this version of txs
+pub const MULTISIG_PROPOSAL_VERSION: u32 = 1;
+
+/// The owners of a k-of-n MultiEd25519 account. The address is derived from the keys and
+/// the threshold, so the same owners in another order are another account
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultisigAccount {
+    pub address: String,
+    /// Number of owners who must approve a transaction
+    pub threshold: u8,
+    /// Ed25519 public keys of the owners, as hex
+    pub public_keys: Vec<String>,
+}
+
+/// A transaction of a multisig account with the approvals of its owners so far. The file
+/// goes from one owner to the next, each adding their signature
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MultisigProposal {
+    pub version: u32,
+    pub account: MultisigAccount,
+    pub transaction: UnsignedTransaction,
+    /// Signatures of the signing message of the transaction, by index of the owner's key
+    pub approvals: BTreeMap<u8, String>,
+}
+
+impl MultisigAccount {
+    pub fn new(public_keys: &[Ed25519PublicKey], threshold: u8) -> Result<Self> {
+        for (index, public_key) in public_keys.iter().enumerate() {
+            if public_keys[..index].contains(public_key) {
+                bail!(
+                    "The public key {} is given more than once",
+                    public_key.to_encoded_string()?
+                );
+            }
+        }
+        let multi_public_key = MultiEd25519PublicKey::new(public_keys.to_vec(), threshold)
+            .context(format!(
+                "Invalid multisig account of {} owner(s) with a threshold of {threshold}",
+                public_keys.len()
+            ))?;
+        Ok(Self {
+            address: format_address(
+                &AuthenticationKey::multi_ed25519(&multi_public_key).derived_address(),
+            ),
+            threshold,
+            public_keys: public_keys
+                .iter()
+                .map(|public_key| Ok(public_key.to_encoded_string()?))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let account: Self = load_json(path, "multisig account")?;
+        account.validate()?;
+        Ok(account)
+    }
+
+    pub fn save(&self, path: &Path, force: bool) -> Result<()> {
+        save_json(self, path, force)
+    }
+
+    pub fn owner_keys(&self) -> Result<Vec<Ed25519PublicKey>> {
+        self.public_keys
+            .iter()
+            .map(|public_key| {
+                Ed25519PublicKey::from_encoded_string(public_key)
+                    .context(format!("Invalid owner public key {public_key}"))
+            })
+            .collect()
+    }
+
+    /// Check that the address is the one of the owners and threshold, so an edited file is caught
+    pub fn validate(&self) -> Result<()> {
+        let derived = Self::new(&self.owner_keys()?, self.threshold)?;
+        if derived.address != self.address {
+            bail!(
+                "The owners and threshold are those of {}, not of the multisig account {}",
+                derived.address,
+                self.address
+            );
+        }
+        Ok(())
+    }
+
+    fn multi_public_key(&self) -> Result<MultiEd25519PublicKey> {
+        Ok(MultiEd25519PublicKey::new(
+            self.owner_keys()?,
+            self.threshold,
+        )?)
+    }
+}
+
+impl MultisigProposal {
+    pub fn new(account: MultisigAccount, transaction: UnsignedTransaction) -> Result<Self> {
+        account.validate()?;
+        if transaction.sender != account.address {
+            bail!(
+                "The transaction is sent by {}, not by the multisig account {}. Build it with --sender {}",
+                transaction.sender,
+                account.address,
+                account.address
+            );
+        }
+        // Fails on an edited transaction
+        transaction.raw_transaction()?;
+        Ok(Self {
+            version: MULTISIG_PROPOSAL_VERSION,
+            account,
+            transaction,
+            approvals: BTreeMap::new(),
+        })
+    }
+
+    /// Read a proposal, checking each of its approvals
+    pub fn load(path: &Path) -> Result<Self> {
+        let proposal: Self = load_json(path, "multisig proposal")?;
+        if proposal.version > MULTISIG_PROPOSAL_VERSION {
+            bail!(
+                "{} is a multisig proposal of version {}, this version of txs reads up to version {MULTISIG_PROPOSAL_VERSION}",
+                path.display(),
+                proposal.version
+            );
+        }
+        proposal.validate()?;
+        Ok(proposal)
+    }
+
+    pub fn save(&self, path: &Path, force: bool) -> Result<()> {
+        save_json(self, path, force)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        self.account.validate()?;
+        if self.transaction.sender != self.account.address {
+            bail!(
+                "The transaction is sent by {}, not by the multisig account {}",
+                self.transaction.sender,
+                self.account.address
+            );
+        }
+        self.transaction.raw_transaction()?;
+        self.signatures()?;
+        Ok(())
+    }
+
+    /// Sign the transaction as one of the owners, returning the index of the owner's key
+    pub fn approve(&mut self, private_key: &Ed25519PrivateKey) -> Result<u8> {
+        let public_key = private_key.public_key();
+        let index = self
+            .account
+            .owner_keys()?
+            .iter()
+            .position(|owner_key| owner_key == &public_key)
+            .context(format!(
+                "The key {} is not an owner of the multisig account {}",
+                public_key.to_encoded_string()?,
+                self.account.address
+            ))?;
+        let signature = match self.transaction.sign(private_key)?.authenticator() {
+            TransactionAuthenticator::Ed25519 { signature, .. } => signature,
+            _ => bail!("Expected an Ed25519 signature"),
+        };
+        let index = u8::try_from(index)?;
+        self.approvals.insert(index, signature.to_encoded_string()?);
+        Ok(index)
+    }
+
+    /// Number of approvals still needed before the transaction can be submitted
+    pub fn missing_approvals(&self) -> usize {
+        usize::from(self.account.threshold).saturating_sub(self.approvals.len())
+    }
+
+    /// The transaction signed by the owners who approved it, once there are enough of them
+    pub fn signed_transaction(&self) -> Result<SignedTransaction> {
+        let missing = self.missing_approvals();
+        if missing > 0 {
+            bail!(
+                "The proposal has {} of the {} approvals needed, {missing} missing",
+                self.approvals.len(),
+                self.account.threshold
+            );
+        }
+        let signature = MultiEd25519Signature::new(self.signatures()?)
+            .context("Failed to assemble the multisig signature")?;
+        Ok(SignedTransaction::new_with_authenticator(
+            self.transaction.raw_transaction()?,
+            TransactionAuthenticator::multi_ed25519(self.account.multi_public_key()?, signature),
+        ))
+    }
+
+    /// The approvals, each checked to be a signature of the transaction by its owner
+    fn signatures(&self) -> Result<Vec<(Ed25519Signature, u8)>> {
+        let owner_keys = self.account.owner_keys()?;
+        let message = hex::decode(&self.transaction.signing_message)?;
+        self.approvals
+            .iter()
+            .map(|(index, signature)| {
+                let owner_key = owner_keys
+                    .get(usize::from(*index))
+                    .context(format!("No owner {index} in the multisig account"))?;
+                let signature = Ed25519Signature::from_encoded_string(signature)
+                    .context(format!("Invalid signature of owner {index}"))?;
+                signature
+                    .verify_arbitrary_msg(&message, owner_key)
+                    .context(format!(
+                        "The approval of owner {index} is not a signature of this transaction by its key"
+                    ))?;
+                Ok((signature, *index))
+            })
+            .collect()
+    }
+}
+
+fn load_json<T: DeserializeOwned>(path: &Path, what: &str) -> Result<T> {
+    let content =
+        std::fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content).context(format!("{} is not a {what} file", path.display()))
+}
+
+fn save_json<T: Serialize>(value: &T, path: &Path, force: bool) -> Result<()> {
+    if path.exists() && !force {
+        bail!(
+            "{} already exists, use --force to overwrite it",
+            path.display()
+        );
+    }
+    std::fs::write(path, serde_json::to_string_pretty(value)?)
+        .context(format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zapatos_sdk::types::{
+        account_address::AccountAddress,
+        chain_id::ChainId,
+        transaction::{RawTransaction, Script, TransactionPayload},
+    };
+
+    fn private_key(seed: u8) -> Ed25519PrivateKey {
+        Ed25519PrivateKey::try_from(&[seed; 32][..]).unwrap()
+    }
+
+    #[test]
+    fn approve_and_assemble_a_2_of_3_proposal() {
+        let public_keys = (1..=3)
+            .map(|seed| private_key(seed).public_key())
+            .collect::<Vec<_>>();
+        let account = MultisigAccount::new(&public_keys, 2).unwrap();
+        assert!(MultisigAccount::new(&public_keys, 4).is_err());
+        assert!(
+            MultisigAccount::new(&[public_keys[0].clone(), public_keys[0].clone()], 1).is_err()
+        );
+
+        let raw_trans = RawTransaction::new(
+            AccountAddress::from_hex_literal(&account.address).unwrap(),
+            0,
+            TransactionPayload::Script(Script::new(vec![], vec![], vec![])),
+            1_000,
+            100,
+            1_700_000_000,
+            ChainId::new(4),
+        );
+        let mut proposal =
+            MultisigProposal::new(account, UnsignedTransaction::new(&raw_trans).unwrap()).unwrap();
+        assert_eq!(proposal.approve(&private_key(1)).unwrap(), 0);
+        assert_eq!(proposal.missing_approvals(), 1);
+        assert!(proposal.signed_transaction().is_err());
+        assert!(proposal.approve(&private_key(4)).is_err());
+
+        // An approval moved to another owner no longer verifies
+        let mut moved = proposal.clone();
+        let signature = moved.approvals.remove(&0).unwrap();
+        moved.approvals.insert(1, signature);
+        assert!(moved.validate().is_err());
+
+        assert_eq!(proposal.approve(&private_key(3)).unwrap(), 2);
+        assert_eq!(proposal.missing_approvals(), 0);
+        proposal
+            .signed_transaction()
+            .unwrap()
+            .check_signature()
+            .unwrap();
+    }
+}
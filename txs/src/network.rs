@@ -0,0 +1,127 @@
+use crate::constant::{MAINNET_URL, STALENESS_WARNING_SECS, TESTNET_URL};
+use anyhow::{bail, Result};
+use std::{
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use url::Url;
+use zapatos_sdk::types::chain_id::NamedChain;
+
+/// Well-known networks, each with the fullnode to use and the chain id it must report
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    /// A local testnet on localhost:8080
+    Local,
+    /// Any other node, given with --url. The chain id is only checked with --chain-id
+    Custom,
+}
+
+impl Network {
+    pub fn url(self) -> Option<Url> {
+        let url = match self {
+            Network::Mainnet => MAINNET_URL,
+            Network::Testnet => TESTNET_URL,
+            Network::Local => "http://localhost:8080",
+            Network::Custom => return None,
+        };
+        Some(Url::from_str(url).expect("the preset urls are valid"))
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Network::Mainnet => "mainnet",
+            Network::Testnet => "testnet",
+            Network::Local => "local",
+            Network::Custom => "custom",
+        }
+    }
+
+    /// The preset on this chain id, custom for any other chain
+    pub fn from_chain_id(chain_id: u8) -> Self {
+        [Network::Mainnet, Network::Testnet, Network::Local]
+            .into_iter()
+            .find(|network| network.chain_id() == Some(chain_id))
+            .unwrap_or(Network::Custom)
+    }
+
+    pub fn chain_id(self) -> Option<u8> {
+        match self {
+            Network::Mainnet => Some(NamedChain::MAINNET.id()),
+            Network::Testnet => Some(NamedChain::TESTNET.id()),
+            Network::Local => Some(NamedChain::TESTING.id()),
+            Network::Custom => None,
+        }
+    }
+}
+
+/// Refuse to go on when the node is not on the expected chain, unless the mismatch is allowed
+pub fn check_chain_id(
+    node_chain_id: u8,
+    expected_chain_id: u8,
+    allow_mismatch: bool,
+) -> Result<()> {
+    if node_chain_id == expected_chain_id {
+        return Ok(());
+    }
+    if allow_mismatch {
+        eprintln!(
+            "Warning: the node is on chain id {node_chain_id}, not the expected {expected_chain_id}"
+        );
+        return Ok(());
+    }
+    bail!(
+        "The node is on chain id {node_chain_id}, but chain id {expected_chain_id} is expected. Check --url and --network, or pass --allow-chain-id-mismatch"
+    )
+}
+
+/// How far the ledger is behind the clock, from the ledger timestamp the node reports
+pub fn ledger_lag_secs(ledger_timestamp_usecs: u64) -> u64 {
+    let now_secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs())
+        .unwrap_or_default();
+    now_secs.saturating_sub(ledger_timestamp_usecs / 1_000_000)
+}
+
+/// Refuse a node whose ledger is more than `max_staleness_secs` behind, as it gives outdated
+/// state and its transactions expire. Warns from `STALENESS_WARNING_SECS` on
+pub fn check_staleness(lag_secs: u64, max_staleness_secs: u64) -> Result<()> {
+    if lag_secs > max_staleness_secs {
+        bail!(
+            "The node is {lag_secs}s behind, more than --max-staleness-secs {max_staleness_secs}. Use another node with --url, or pass --no-staleness-check for a local network"
+        );
+    }
+    if lag_secs > STALENESS_WARNING_SECS {
+        eprintln!("Warning: the node is {lag_secs}s behind, its state may be outdated");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_the_chain_id_of_presets() {
+        assert_eq!(Network::Local.chain_id(), Some(4));
+        assert_eq!(Network::Custom.url(), None);
+        assert_eq!(Network::from_chain_id(4), Network::Local);
+        assert_eq!(Network::from_chain_id(42), Network::Custom);
+
+        let chain_id = Network::Mainnet.chain_id().unwrap();
+        assert!(check_chain_id(chain_id, chain_id, false).is_ok());
+        let err = check_chain_id(2, chain_id, false).unwrap_err();
+        assert!(err.to_string().starts_with("The node is on chain id 2"));
+        assert!(check_chain_id(2, chain_id, true).is_ok());
+    }
+
+    #[test]
+    fn refuse_stale_nodes() {
+        assert!(ledger_lag_secs(u64::MAX) == 0);
+        assert!(check_staleness(STALENESS_WARNING_SECS + 1, 300).is_ok());
+        let err = check_staleness(301, 300).unwrap_err();
+        assert!(err.to_string().starts_with("The node is 301s behind"));
+    }
+}
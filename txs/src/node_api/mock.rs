@@ -0,0 +1,366 @@
+//! A node answering from fixtures, for the tests of the library and of the code built on it
+
+use super::NodeApi;
+use crate::client::COIN_TYPE;
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+use zapatos_sdk::{
+    crypto::HashValue,
+    rest_client::{
+        aptos_api_types::{
+            AptosError, AptosErrorCode, Block, GasEstimation, IndexResponse, MoveModuleBytecode,
+            PendingTransaction, Transaction, TransactionData, UserTransaction, ViewRequest,
+        },
+        error::{AptosErrorResponse, RestError},
+        Account, Resource, Response, State,
+    },
+    types::{account_address::AccountAddress, transaction::SignedTransaction},
+};
+
+/// What the node answers to a route, its body as the node would send it
+#[derive(Clone, Debug, Deserialize)]
+pub struct Fixture {
+    pub status: u16,
+    pub body: Value,
+}
+
+#[derive(Deserialize)]
+struct FixtureFile {
+    chain_id: u8,
+    ledger_version: u64,
+    routes: BTreeMap<String, Fixture>,
+}
+
+/// A node answering from fixtures, for tests. A fixture file holds the chain id and the ledger
+/// version the responses carry, and the fixtures by route, e.g.
+/// `GET /accounts/0x1/resource/0x1::account::Account` with the addresses as hex literals. The
+/// routes are those of the REST API, but the balance which is read from the `CoinStore` of the
+/// native coin, the View requests which all go to `POST /view`, the table items which go to
+/// `POST /tables/<HANDLE>/item/<KEY>`, and the transactions in BCS which are read from
+/// `GET /bcs/transactions/by_hash/<HASH>` as the serde JSON of a `TransactionData`. A `*`
+/// segment matches any segment, and a route without a fixture answers with a 404. The clones
+/// of a node share what it was sent
+#[derive(Clone, Debug)]
+pub struct MockNode {
+    state: State,
+    routes: BTreeMap<String, Fixture>,
+    requests: Arc<Mutex<Vec<String>>>,
+    submitted: Arc<Mutex<Vec<SignedTransaction>>>,
+    view_requests: Arc<Mutex<Vec<ViewRequest>>>,
+}
+
+impl MockNode {
+    pub fn from_json(fixtures: &str) -> Result<Self> {
+        let file = serde_json::from_str::<FixtureFile>(fixtures).context("Invalid fixtures")?;
+        Ok(Self {
+            state: State {
+                chain_id: file.chain_id,
+                epoch: 1,
+                version: file.ledger_version,
+                timestamp_usecs: 0,
+                oldest_ledger_version: 0,
+                oldest_block_height: 0,
+                block_height: 0,
+                cursor: None,
+            },
+            routes: file.routes,
+            requests: Arc::default(),
+            submitted: Arc::default(),
+            view_requests: Arc::default(),
+        })
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let fixtures = std::fs::read_to_string(path)
+            .context(format!("Failed to read the fixtures {}", path.display()))?;
+        Self::from_json(&fixtures).context(format!("Invalid fixtures {}", path.display()))
+    }
+
+    /// Answer the route with this fixture instead, e.g. to turn a success into a failure
+    pub fn with_route(mut self, route: &str, status: u16, body: Value) -> Self {
+        self.routes
+            .insert(route.to_string(), Fixture { status, body });
+        self
+    }
+
+    /// The routes requested so far, in order
+    pub fn requests(&self) -> Vec<String> {
+        self.requests
+            .lock()
+            .expect("the requests lock is never poisoned")
+            .clone()
+    }
+
+    /// The transactions submitted so far, in order, to check what a command signed
+    pub fn submitted(&self) -> Vec<SignedTransaction> {
+        self.submitted
+            .lock()
+            .expect("the submitted lock is never poisoned")
+            .clone()
+    }
+
+    /// The View requests executed so far, in order, with the arguments the node was sent
+    pub fn view_requests(&self) -> Vec<ViewRequest> {
+        self.view_requests
+            .lock()
+            .expect("the view requests lock is never poisoned")
+            .clone()
+    }
+
+    fn fixture(&self, route: &str) -> Option<&Fixture> {
+        self.routes.get(route).or_else(|| {
+            self.routes
+                .iter()
+                .find(|(pattern, _)| route_matches(pattern, route))
+                .map(|(_, fixture)| fixture)
+        })
+    }
+
+    fn answer<T: DeserializeOwned>(&self, route: String) -> Result<Response<T>, RestError> {
+        self.requests
+            .lock()
+            .expect("the requests lock is never poisoned")
+            .push(route.clone());
+        let fixture = match self.fixture(&route) {
+            Some(fixture) => fixture,
+            None => {
+                return Err(self.api_error(
+                    StatusCode::NOT_FOUND,
+                    AptosError {
+                        message: format!("No fixture for {route}"),
+                        error_code: AptosErrorCode::ResourceNotFound,
+                        vm_error_code: None,
+                    },
+                ))
+            }
+        };
+        let status = StatusCode::from_u16(fixture.status)
+            .map_err(|err| RestError::Unknown(anyhow!("Invalid status of {route}: {err}")))?;
+        if !status.is_success() {
+            let error =
+                serde_json::from_value::<AptosError>(fixture.body.clone()).map_err(|err| {
+                    RestError::Unknown(anyhow!("Malformed error response to {route}: {err}"))
+                })?;
+            return Err(self.api_error(status, error));
+        }
+        let inner = serde_json::from_value::<T>(fixture.body.clone())
+            .map_err(|err| RestError::Unknown(anyhow!("Malformed response to {route}: {err}")))?;
+        Ok(Response::new(inner, self.state.clone()))
+    }
+
+    fn api_error(&self, status_code: StatusCode, error: AptosError) -> RestError {
+        RestError::Api(AptosErrorResponse {
+            error,
+            state: Some(self.state.clone()),
+            status_code,
+        })
+    }
+}
+
+#[async_trait]
+impl NodeApi for MockNode {
+    async fn get_index(&self) -> Result<Response<IndexResponse>, RestError> {
+        self.answer("GET /".to_string())
+    }
+
+    async fn get_account(&self, address: AccountAddress) -> Result<Response<Account>, RestError> {
+        self.answer(format!("GET /accounts/{}", address.to_hex_literal()))
+    }
+
+    async fn get_account_balance(
+        &self,
+        address: AccountAddress,
+    ) -> Result<Response<u64>, RestError> {
+        let coin_store = format!("0x1::coin::CoinStore<{COIN_TYPE}>");
+        let response = self.get_account_resource(address, &coin_store).await?;
+        let value = response
+            .inner()
+            .as_ref()
+            .and_then(|resource| resource.data["coin"]["value"].as_str())
+            .and_then(|value| value.parse::<u64>().ok())
+            .ok_or_else(|| RestError::Unknown(anyhow!("No coin value in {coin_store}")))?;
+        Ok(response.map(|_| value))
+    }
+
+    async fn get_account_resource(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+    ) -> Result<Response<Option<Resource>>, RestError> {
+        self.answer(format!(
+            "GET /accounts/{}/resource/{resource_type}",
+            address.to_hex_literal()
+        ))
+    }
+
+    async fn get_account_resource_at_version(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        version: u64,
+    ) -> Result<Response<Option<Resource>>, RestError> {
+        self.answer(format!(
+            "GET /accounts/{}/resource/{resource_type}?ledger_version={version}",
+            address.to_hex_literal()
+        ))
+    }
+
+    async fn get_account_resources(
+        &self,
+        address: AccountAddress,
+    ) -> Result<Response<Vec<Resource>>, RestError> {
+        self.answer(format!(
+            "GET /accounts/{}/resources",
+            address.to_hex_literal()
+        ))
+    }
+
+    async fn get_account_resources_at_version(
+        &self,
+        address: AccountAddress,
+        version: u64,
+    ) -> Result<Response<Vec<Resource>>, RestError> {
+        self.answer(format!(
+            "GET /accounts/{}/resources?ledger_version={version}",
+            address.to_hex_literal()
+        ))
+    }
+
+    async fn get_account_module(
+        &self,
+        address: AccountAddress,
+        module_name: &str,
+    ) -> Result<Response<MoveModuleBytecode>, RestError> {
+        self.answer(format!(
+            "GET /accounts/{}/module/{module_name}",
+            address.to_hex_literal()
+        ))
+    }
+
+    async fn view(
+        &self,
+        request: &ViewRequest,
+        _version: Option<u64>,
+    ) -> Result<Response<Vec<Value>>, RestError> {
+        self.view_requests
+            .lock()
+            .expect("the view requests lock is never poisoned")
+            .push(request.clone());
+        self.answer("POST /view".to_string())
+    }
+
+    async fn estimate_gas_price(&self) -> Result<Response<GasEstimation>, RestError> {
+        self.answer("GET /estimate_gas_price".to_string())
+    }
+
+    async fn simulate(
+        &self,
+        _signed_trans: &SignedTransaction,
+    ) -> Result<Response<Vec<UserTransaction>>, RestError> {
+        self.answer("POST /transactions/simulate".to_string())
+    }
+
+    async fn simulate_with_gas_estimation(
+        &self,
+        _signed_trans: &SignedTransaction,
+        _estimate_max_gas_amount: bool,
+        _estimate_max_gas_unit_price: bool,
+    ) -> Result<Response<Vec<UserTransaction>>, RestError> {
+        self.answer("POST /transactions/simulate".to_string())
+    }
+
+    async fn submit(
+        &self,
+        signed_trans: &SignedTransaction,
+    ) -> Result<Response<PendingTransaction>, RestError> {
+        self.submitted
+            .lock()
+            .expect("the submitted lock is never poisoned")
+            .push(signed_trans.clone());
+        self.answer("POST /transactions".to_string())
+    }
+
+    async fn get_transaction_by_hash(
+        &self,
+        hash: HashValue,
+    ) -> Result<Response<Transaction>, RestError> {
+        self.answer(format!(
+            "GET /transactions/by_hash/{}",
+            hash.to_hex_literal()
+        ))
+    }
+
+    async fn get_transaction_by_hash_bcs(
+        &self,
+        hash: HashValue,
+    ) -> Result<Response<TransactionData>, RestError> {
+        self.answer(format!(
+            "GET /bcs/transactions/by_hash/{}",
+            hash.to_hex_literal()
+        ))
+    }
+
+    async fn get_block_by_height(
+        &self,
+        height: u64,
+        _with_transactions: bool,
+    ) -> Result<Response<Block>, RestError> {
+        self.answer(format!("GET /blocks/by_height/{height}"))
+    }
+
+    async fn get_table_item(
+        &self,
+        table_handle: AccountAddress,
+        _key_type: &str,
+        _value_type: &str,
+        key: Value,
+    ) -> Result<Response<Value>, RestError> {
+        // The key is part of the route, as the tables are read one item at a time
+        let key = key.as_str().map_or(key.to_string(), str::to_owned);
+        self.answer(format!(
+            "POST /tables/{}/item/{key}",
+            table_handle.to_hex_literal()
+        ))
+    }
+}
+
+fn route_matches(pattern: &str, route: &str) -> bool {
+    let (pattern, route) = (
+        pattern.split('/').collect::<Vec<_>>(),
+        route.split('/').collect::<Vec<_>>(),
+    );
+    pattern.len() == route.len()
+        && pattern
+            .iter()
+            .zip(&route)
+            .all(|(pattern, segment)| *pattern == "*" || pattern == segment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_routes_by_segment() {
+        assert!(route_matches(
+            "GET /transactions/by_hash/*",
+            "GET /transactions/by_hash/0xab"
+        ));
+        assert!(!route_matches(
+            "GET /transactions/by_hash/*",
+            "GET /transactions/by_hash"
+        ));
+        assert!(!route_matches(
+            "GET /accounts/*/module/coin",
+            "GET /accounts/0x1/module/account"
+        ));
+    }
+}
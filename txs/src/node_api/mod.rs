@@ -0,0 +1,425 @@
+//! The REST calls txs makes to a node, as a trait so that the library and its tests can run
+//! against something else than a live node. `Client` is the production implementation,
+//! `MockNode` answers from fixtures without any network, with the `mock` feature
+
+use async_trait::async_trait;
+use libra_wallet::address_scheme::AccountLookup;
+use serde_json::Value;
+use zapatos_sdk::{
+    crypto::HashValue,
+    rest_client::{
+        aptos_api_types::{
+            Block, GasEstimation, IndexResponse, MoveModuleBytecode, PendingTransaction,
+            Transaction, TransactionData, UserTransaction, ViewRequest,
+        },
+        error::RestError,
+        Account, Client, Resource, Response,
+    },
+    types::{account_address::AccountAddress, transaction::SignedTransaction},
+};
+
+#[cfg(any(test, feature = "mock"))]
+mod mock;
+#[cfg(any(test, feature = "mock"))]
+pub use mock::{Fixture, MockNode};
+
+/// The calls have the signatures of the `Client` methods of the same name, except for the
+/// balance which is already in base units
+#[async_trait]
+pub trait NodeApi: Send + Sync {
+    async fn get_index(&self) -> Result<Response<IndexResponse>, RestError>;
+
+    async fn get_account(&self, address: AccountAddress) -> Result<Response<Account>, RestError>;
+
+    /// Balance of the native coin, in base units
+    async fn get_account_balance(
+        &self,
+        address: AccountAddress,
+    ) -> Result<Response<u64>, RestError>;
+
+    async fn get_account_resource(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+    ) -> Result<Response<Option<Resource>>, RestError>;
+
+    async fn get_account_resource_at_version(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        version: u64,
+    ) -> Result<Response<Option<Resource>>, RestError>;
+
+    async fn get_account_resources(
+        &self,
+        address: AccountAddress,
+    ) -> Result<Response<Vec<Resource>>, RestError>;
+
+    async fn get_account_resources_at_version(
+        &self,
+        address: AccountAddress,
+        version: u64,
+    ) -> Result<Response<Vec<Resource>>, RestError>;
+
+    async fn get_account_module(
+        &self,
+        address: AccountAddress,
+        module_name: &str,
+    ) -> Result<Response<MoveModuleBytecode>, RestError>;
+
+    async fn view(
+        &self,
+        request: &ViewRequest,
+        version: Option<u64>,
+    ) -> Result<Response<Vec<Value>>, RestError>;
+
+    async fn estimate_gas_price(&self) -> Result<Response<GasEstimation>, RestError>;
+
+    async fn simulate(
+        &self,
+        signed_trans: &SignedTransaction,
+    ) -> Result<Response<Vec<UserTransaction>>, RestError>;
+
+    async fn simulate_with_gas_estimation(
+        &self,
+        signed_trans: &SignedTransaction,
+        estimate_max_gas_amount: bool,
+        estimate_max_gas_unit_price: bool,
+    ) -> Result<Response<Vec<UserTransaction>>, RestError>;
+
+    async fn submit(
+        &self,
+        signed_trans: &SignedTransaction,
+    ) -> Result<Response<PendingTransaction>, RestError>;
+
+    async fn get_transaction_by_hash(
+        &self,
+        hash: HashValue,
+    ) -> Result<Response<Transaction>, RestError>;
+
+    /// The transaction in BCS, with the signed transaction of a pending one
+    async fn get_transaction_by_hash_bcs(
+        &self,
+        hash: HashValue,
+    ) -> Result<Response<TransactionData>, RestError>;
+
+    async fn get_block_by_height(
+        &self,
+        height: u64,
+        with_transactions: bool,
+    ) -> Result<Response<Block>, RestError>;
+
+    async fn get_table_item(
+        &self,
+        table_handle: AccountAddress,
+        key_type: &str,
+        value_type: &str,
+        key: Value,
+    ) -> Result<Response<Value>, RestError>;
+}
+
+#[async_trait]
+impl NodeApi for Client {
+    async fn get_index(&self) -> Result<Response<IndexResponse>, RestError> {
+        Client::get_index(self).await
+    }
+
+    async fn get_account(&self, address: AccountAddress) -> Result<Response<Account>, RestError> {
+        Client::get_account(self, address).await
+    }
+
+    async fn get_account_balance(
+        &self,
+        address: AccountAddress,
+    ) -> Result<Response<u64>, RestError> {
+        Ok(Client::get_account_balance(self, address)
+            .await?
+            .map(|balance| balance.get()))
+    }
+
+    async fn get_account_resource(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+    ) -> Result<Response<Option<Resource>>, RestError> {
+        Client::get_account_resource(self, address, resource_type).await
+    }
+
+    async fn get_account_resource_at_version(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        version: u64,
+    ) -> Result<Response<Option<Resource>>, RestError> {
+        Client::get_account_resource_at_version(self, address, resource_type, version).await
+    }
+
+    async fn get_account_resources(
+        &self,
+        address: AccountAddress,
+    ) -> Result<Response<Vec<Resource>>, RestError> {
+        Client::get_account_resources(self, address).await
+    }
+
+    async fn get_account_resources_at_version(
+        &self,
+        address: AccountAddress,
+        version: u64,
+    ) -> Result<Response<Vec<Resource>>, RestError> {
+        Client::get_account_resources_at_version(self, address, version).await
+    }
+
+    async fn get_account_module(
+        &self,
+        address: AccountAddress,
+        module_name: &str,
+    ) -> Result<Response<MoveModuleBytecode>, RestError> {
+        Client::get_account_module(self, address, module_name).await
+    }
+
+    async fn view(
+        &self,
+        request: &ViewRequest,
+        version: Option<u64>,
+    ) -> Result<Response<Vec<Value>>, RestError> {
+        Client::view(self, request, version).await
+    }
+
+    async fn estimate_gas_price(&self) -> Result<Response<GasEstimation>, RestError> {
+        Client::estimate_gas_price(self).await
+    }
+
+    async fn simulate(
+        &self,
+        signed_trans: &SignedTransaction,
+    ) -> Result<Response<Vec<UserTransaction>>, RestError> {
+        Client::simulate(self, signed_trans).await
+    }
+
+    async fn simulate_with_gas_estimation(
+        &self,
+        signed_trans: &SignedTransaction,
+        estimate_max_gas_amount: bool,
+        estimate_max_gas_unit_price: bool,
+    ) -> Result<Response<Vec<UserTransaction>>, RestError> {
+        Client::simulate_with_gas_estimation(
+            self,
+            signed_trans,
+            estimate_max_gas_amount,
+            estimate_max_gas_unit_price,
+        )
+        .await
+    }
+
+    async fn submit(
+        &self,
+        signed_trans: &SignedTransaction,
+    ) -> Result<Response<PendingTransaction>, RestError> {
+        Client::submit(self, signed_trans).await
+    }
+
+    async fn get_transaction_by_hash(
+        &self,
+        hash: HashValue,
+    ) -> Result<Response<Transaction>, RestError> {
+        Client::get_transaction_by_hash(self, hash).await
+    }
+
+    async fn get_transaction_by_hash_bcs(
+        &self,
+        hash: HashValue,
+    ) -> Result<Response<TransactionData>, RestError> {
+        Client::get_transaction_by_hash_bcs(self, hash).await
+    }
+
+    async fn get_block_by_height(
+        &self,
+        height: u64,
+        with_transactions: bool,
+    ) -> Result<Response<Block>, RestError> {
+        Client::get_block_by_height(self, height, with_transactions).await
+    }
+
+    async fn get_table_item(
+        &self,
+        table_handle: AccountAddress,
+        key_type: &str,
+        value_type: &str,
+        key: Value,
+    ) -> Result<Response<Value>, RestError> {
+        Client::get_table_item(self, table_handle, key_type, value_type, key).await
+    }
+}
+
+/// A borrowed node, e.g. for a `TxsClient` of a node the caller keeps
+#[async_trait]
+impl<N: NodeApi + ?Sized> NodeApi for &N {
+    async fn get_index(&self) -> Result<Response<IndexResponse>, RestError> {
+        (**self).get_index().await
+    }
+
+    async fn get_account(&self, address: AccountAddress) -> Result<Response<Account>, RestError> {
+        (**self).get_account(address).await
+    }
+
+    async fn get_account_balance(
+        &self,
+        address: AccountAddress,
+    ) -> Result<Response<u64>, RestError> {
+        (**self).get_account_balance(address).await
+    }
+
+    async fn get_account_resource(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+    ) -> Result<Response<Option<Resource>>, RestError> {
+        (**self).get_account_resource(address, resource_type).await
+    }
+
+    async fn get_account_resource_at_version(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        version: u64,
+    ) -> Result<Response<Option<Resource>>, RestError> {
+        (**self)
+            .get_account_resource_at_version(address, resource_type, version)
+            .await
+    }
+
+    async fn get_account_resources(
+        &self,
+        address: AccountAddress,
+    ) -> Result<Response<Vec<Resource>>, RestError> {
+        (**self).get_account_resources(address).await
+    }
+
+    async fn get_account_resources_at_version(
+        &self,
+        address: AccountAddress,
+        version: u64,
+    ) -> Result<Response<Vec<Resource>>, RestError> {
+        (**self)
+            .get_account_resources_at_version(address, version)
+            .await
+    }
+
+    async fn get_account_module(
+        &self,
+        address: AccountAddress,
+        module_name: &str,
+    ) -> Result<Response<MoveModuleBytecode>, RestError> {
+        (**self).get_account_module(address, module_name).await
+    }
+
+    async fn view(
+        &self,
+        request: &ViewRequest,
+        version: Option<u64>,
+    ) -> Result<Response<Vec<Value>>, RestError> {
+        (**self).view(request, version).await
+    }
+
+    async fn estimate_gas_price(&self) -> Result<Response<GasEstimation>, RestError> {
+        (**self).estimate_gas_price().await
+    }
+
+    async fn simulate(
+        &self,
+        signed_trans: &SignedTransaction,
+    ) -> Result<Response<Vec<UserTransaction>>, RestError> {
+        (**self).simulate(signed_trans).await
+    }
+
+    async fn simulate_with_gas_estimation(
+        &self,
+        signed_trans: &SignedTransaction,
+        estimate_max_gas_amount: bool,
+        estimate_max_gas_unit_price: bool,
+    ) -> Result<Response<Vec<UserTransaction>>, RestError> {
+        (**self)
+            .simulate_with_gas_estimation(
+                signed_trans,
+                estimate_max_gas_amount,
+                estimate_max_gas_unit_price,
+            )
+            .await
+    }
+
+    async fn submit(
+        &self,
+        signed_trans: &SignedTransaction,
+    ) -> Result<Response<PendingTransaction>, RestError> {
+        (**self).submit(signed_trans).await
+    }
+
+    async fn get_transaction_by_hash(
+        &self,
+        hash: HashValue,
+    ) -> Result<Response<Transaction>, RestError> {
+        (**self).get_transaction_by_hash(hash).await
+    }
+
+    async fn get_transaction_by_hash_bcs(
+        &self,
+        hash: HashValue,
+    ) -> Result<Response<TransactionData>, RestError> {
+        (**self).get_transaction_by_hash_bcs(hash).await
+    }
+
+    async fn get_block_by_height(
+        &self,
+        height: u64,
+        with_transactions: bool,
+    ) -> Result<Response<Block>, RestError> {
+        (**self)
+            .get_block_by_height(height, with_transactions)
+            .await
+    }
+
+    async fn get_table_item(
+        &self,
+        table_handle: AccountAddress,
+        key_type: &str,
+        value_type: &str,
+        key: Value,
+    ) -> Result<Response<Value>, RestError> {
+        (**self)
+            .get_table_item(table_handle, key_type, value_type, key)
+            .await
+    }
+}
+
+/// A node as the `AccountLookup` of `libra_wallet::address_scheme::find_account`
+pub(crate) struct Lookup<'a, N>(pub &'a N);
+
+#[async_trait]
+impl<N: NodeApi> AccountLookup for Lookup<'_, N> {
+    async fn account_resource(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+    ) -> Result<Option<Value>, RestError> {
+        Ok(self
+            .0
+            .get_account_resource(address, resource_type)
+            .await?
+            .into_inner()
+            .map(|resource| resource.data))
+    }
+
+    async fn table_item(
+        &self,
+        handle: AccountAddress,
+        key_type: &str,
+        value_type: &str,
+        key: Value,
+    ) -> Result<Value, RestError> {
+        Ok(self
+            .0
+            .get_table_item(handle, key_type, value_type, key)
+            .await?
+            .into_inner())
+    }
+}
@@ -0,0 +1,131 @@
+//! A local file holding the next sequence number of an account, so that several transactions
+//! can be signed offline without querying the chain for each
+
+use crate::{lock::FileLock, util::format_address};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use zapatos_sdk::types::account_address::AccountAddress;
+
+#[derive(Serialize, Deserialize)]
+struct NonceFile {
+    address: String,
+    next_sequence_number: u64,
+}
+
+/// The next sequence number of a nonce file, which no other process can take until the lease
+/// is committed or dropped
+#[derive(Debug)]
+pub struct NonceLease {
+    path: PathBuf,
+    pub address: AccountAddress,
+    pub sequence_number: u64,
+    _lock: FileLock,
+}
+
+impl NonceLease {
+    pub fn take(path: &Path) -> Result<Self> {
+        let lock = FileLock::acquire(path)?;
+        let (address, sequence_number) = read(path)?.context(format!(
+            "{} not found, create it with `txs nonce sync --nonce-file {}`",
+            path.display(),
+            path.display()
+        ))?;
+        Ok(Self {
+            path: path.to_owned(),
+            address,
+            sequence_number,
+            _lock: lock,
+        })
+    }
+
+    /// Record that the transaction of `sender` was signed with the sequence number, the file
+    /// then holds the next one. Dropping the lease instead leaves the file as it was
+    pub fn commit(self, sender: AccountAddress) -> Result<()> {
+        if sender != self.address {
+            bail!(
+                "{} holds the sequence number of {}, not of the sender {}",
+                self.path.display(),
+                format_address(&self.address),
+                format_address(&sender)
+            );
+        }
+        write(&self.path, self.address, self.sequence_number + 1)
+    }
+}
+
+/// The account and the next sequence number of the file, None when it does not exist
+pub fn read(path: &Path) -> Result<Option<(AccountAddress, u64)>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let nonce: NonceFile = serde_json::from_str(
+        &fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?,
+    )
+    .context(format!("{} is not a nonce file", path.display()))?;
+    let address = AccountAddress::from_hex_literal(&nonce.address).context(format!(
+        "Invalid address {} in {}",
+        nonce.address,
+        path.display()
+    ))?;
+    Ok(Some((address, nonce.next_sequence_number)))
+}
+
+/// Overwrite the file, e.g. with the sequence number of the account on-chain. Returns what the
+/// file held before
+pub fn reset(
+    path: &Path,
+    address: AccountAddress,
+    next_sequence_number: u64,
+) -> Result<Option<(AccountAddress, u64)>> {
+    let _lock = FileLock::acquire(path)?;
+    let previous = read(path)?;
+    write(path, address, next_sequence_number)?;
+    Ok(previous)
+}
+
+/// Written to a temporary file first, so that an interrupted write leaves the previous number
+fn write(path: &Path, address: AccountAddress, next_sequence_number: u64) -> Result<()> {
+    let nonce = NonceFile {
+        address: address.to_hex_literal(),
+        next_sequence_number,
+    };
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, serde_json::to_string_pretty(&nonce)?)
+        .context(format!("Failed to write {}", temporary.display()))?;
+    fs::rename(&temporary, path).context(format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_the_sequence_numbers_in_turn() {
+        let path = std::env::temp_dir().join("txs-nonce-test.json");
+        let _ = fs::remove_file(&path);
+        assert!(NonceLease::take(&path).is_err());
+
+        assert_eq!(None, reset(&path, AccountAddress::ONE, 7).unwrap());
+        let lease = NonceLease::take(&path).unwrap();
+        assert_eq!(lease.sequence_number, 7);
+        lease.commit(AccountAddress::ONE).unwrap();
+
+        // A lease dropped without being committed leaves the number to the next transaction
+        let lease = NonceLease::take(&path).unwrap();
+        assert_eq!(lease.sequence_number, 8);
+        drop(lease);
+        let lease = NonceLease::take(&path).unwrap();
+        assert_eq!(lease.sequence_number, 8);
+        assert!(lease.commit(AccountAddress::ZERO).is_err());
+
+        assert_eq!(
+            Some((AccountAddress::ONE, 8)),
+            reset(&path, AccountAddress::ONE, 3).unwrap()
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,199 @@
+use crate::{
+    constant::{
+        DEFAULT_MAX_RETRIES, RATE_LIMITED_RETRY_DELAY_MS, RETRY_BASE_DELAY_MS, RETRY_MAX_DELAY_MS,
+    },
+    error::TxsError,
+    journal,
+    logging::redacted_json,
+    node_api::NodeApi,
+    util::{progress, transaction_hash},
+};
+use anyhow::{anyhow, bail, Context, Result};
+use std::{
+    collections::hash_map::RandomState,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    sync::atomic::{AtomicU32, Ordering},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tracing::{debug, info, trace};
+use zapatos_sdk::{
+    crypto::HashValue,
+    rest_client::{aptos_api_types::AptosErrorCode, error::RestError},
+    types::transaction::SignedTransaction,
+};
+
+static MAX_RETRIES: AtomicU32 = AtomicU32::new(DEFAULT_MAX_RETRIES);
+
+/// Set from --max-retries, for every request made afterwards
+pub fn set_max_retries(max_retries: u32) {
+    MAX_RETRIES.store(max_retries, Ordering::Relaxed);
+}
+
+/// Run an idempotent read, retrying it with exponential backoff on rate limits, server errors,
+/// timeouts and failed connections
+pub async fn retry_read<T, F, Fut>(request: F) -> Result<T, RestError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, RestError>>,
+{
+    retry(request, is_transient).await
+}
+
+/// Submit a signed transaction, retrying only when the node provably never got it. When the
+/// node says the sequence number is used, the transaction is looked up by hash, as an earlier
+/// attempt may have gone through after all. An expired transaction is never submitted, nor the
+/// duplicate of one in the journal
+pub async fn submit_with_retry(
+    client: &impl NodeApi,
+    signed_trans: &SignedTransaction,
+) -> Result<HashValue> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    if signed_trans.expiration_timestamp_secs() <= now {
+        bail!(TxsError::Expired {
+            ago_secs: now - signed_trans.expiration_timestamp_secs()
+        });
+    }
+    journal::record_submission(client, signed_trans).await?;
+    trace!("Submitting {}", redacted_json(signed_trans));
+    let submitted = match retry(|| client.submit(signed_trans), never_reached_node).await {
+        Ok(response) => Ok(response.into_inner().hash.into()),
+        Err(err) if is_sequence_number_used(&err) => {
+            let hash = transaction_hash(signed_trans);
+            match client.get_transaction_by_hash(hash).await {
+                Ok(_) => {
+                    progress(
+                        "The node already has the transaction, an earlier attempt went through",
+                    );
+                    Ok(hash)
+                }
+                Err(_) => Err(rejected(err)).context("Failed to submit the transaction"),
+            }
+        }
+        Err(err) => Err(rejected(err)).context("Failed to submit the transaction"),
+    };
+    if submitted.is_err() {
+        journal::record_outcome(transaction_hash(signed_trans), false);
+    }
+    submitted
+}
+
+/// The error of a submission, as `TxsError::Rejected` when the node refused the transaction
+/// into its mempool. A VM error elsewhere, e.g. of a view function, is no rejection
+fn rejected(err: RestError) -> anyhow::Error {
+    match &err {
+        RestError::Api(response)
+            if matches!(
+                response.error.error_code,
+                AptosErrorCode::VmError
+                    | AptosErrorCode::SequenceNumberTooOld
+                    | AptosErrorCode::InvalidTransactionUpdate
+                    | AptosErrorCode::MempoolIsFull
+            ) =>
+        {
+            anyhow!(TxsError::Rejected(response.error.message.to_owned()))
+        }
+        _ => anyhow!(err),
+    }
+}
+
+async fn retry<T, F, Fut>(mut request: F, retryable: fn(&RestError) -> bool) -> Result<T, RestError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, RestError>>,
+{
+    let max_retries = MAX_RETRIES.load(Ordering::Relaxed);
+    let mut attempt = 0;
+    loop {
+        let started = Instant::now();
+        let result = request().await;
+        let elapsed_ms = started.elapsed().as_millis();
+        match &result {
+            Ok(_) => debug!("Request succeeded in {elapsed_ms}ms"),
+            Err(err) => debug!("Request failed in {elapsed_ms}ms: {err}"),
+        }
+        match result {
+            Err(err) if attempt < max_retries && retryable(&err) => {
+                let delay = backoff(attempt, is_rate_limited(&err));
+                info!("Retrying in {}ms after: {err}", delay.as_millis());
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Exponential backoff with jitter, between half and all of the delay. The SDK drops the
+/// headers of failed responses, so Retry-After cannot be read: rate limited requests back off
+/// from a longer delay instead
+fn backoff(attempt: u32, rate_limited: bool) -> Duration {
+    let base_ms = if rate_limited {
+        RATE_LIMITED_RETRY_DELAY_MS
+    } else {
+        RETRY_BASE_DELAY_MS
+    };
+    let delay_ms = base_ms
+        .saturating_mul(1 << attempt.min(16))
+        .min(RETRY_MAX_DELAY_MS);
+    let jitter_ms = RandomState::new().build_hasher().finish() % (delay_ms / 2 + 1);
+    Duration::from_millis(delay_ms / 2 + jitter_ms)
+}
+
+fn is_transient(err: &RestError) -> bool {
+    match err {
+        RestError::Http(status, _) => status.as_u16() == 429 || status.is_server_error(),
+        RestError::Api(response) => {
+            response.status_code.as_u16() == 429 || response.status_code.is_server_error()
+        }
+        RestError::Timeout(_) => true,
+        RestError::Unknown(err) => err
+            .downcast_ref::<reqwest::Error>()
+            .map_or(false, |err| err.is_connect() || err.is_timeout()),
+        _ => false,
+    }
+}
+
+/// Rate limited requests are rejected before being looked at, and failed connections never
+/// sent the request. Anything else may have reached the mempool
+fn never_reached_node(err: &RestError) -> bool {
+    match err {
+        RestError::Unknown(err) => err
+            .downcast_ref::<reqwest::Error>()
+            .map_or(false, |err| err.is_connect()),
+        err => is_rate_limited(err),
+    }
+}
+
+fn is_rate_limited(err: &RestError) -> bool {
+    match err {
+        RestError::Http(status, _) => status.as_u16() == 429,
+        RestError::Api(response) => response.status_code.as_u16() == 429,
+        _ => false,
+    }
+}
+
+fn is_sequence_number_used(err: &RestError) -> bool {
+    matches!(err, RestError::Api(response) if response.error.message.contains("SEQUENCE_NUMBER_TOO_OLD"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn back_off_exponentially_with_jitter() {
+        for attempt in 0..4 {
+            let delay = backoff(attempt, false).as_millis() as u64;
+            let full = RETRY_BASE_DELAY_MS << attempt;
+            assert!((full / 2..=full).contains(&delay), "{delay}");
+        }
+        assert!(backoff(30, false).as_millis() as u64 <= RETRY_MAX_DELAY_MS);
+        assert!(backoff(0, true) >= Duration::from_millis(RATE_LIMITED_RETRY_DELAY_MS / 2));
+
+        assert!(is_transient(&RestError::Timeout("request")));
+        assert!(!never_reached_node(&RestError::Timeout("request")));
+        assert!(!is_transient(&RestError::Unknown(anyhow!("bad request"))));
+    }
+}
@@ -0,0 +1,59 @@
+use crate::error::TxsError;
+use anyhow::Result;
+pub use libra_wallet::secret::Secret;
+use std::sync::atomic::{AtomicBool, Ordering};
+use zapatos_sdk::crypto::{ed25519::Ed25519PrivateKey, ValidCryptoMaterialStringExt};
+
+static STRICT_PERMISSIONS: AtomicBool = AtomicBool::new(false);
+
+/// Fail instead of warning when a key file is readable by other users
+pub fn set_strict_permissions(strict: bool) {
+    STRICT_PERMISSIONS.store(strict, Ordering::Relaxed);
+}
+
+pub fn strict_permissions() -> bool {
+    STRICT_PERMISSIONS.load(Ordering::Relaxed)
+}
+
+/// Parse a hex encoded private key. The parse error is dropped, as the hex error quotes the
+/// offending character of the key
+pub fn parse_private_key(private_key: &Secret, source: &str) -> Result<Ed25519PrivateKey> {
+    let encoded = private_key.expose().trim();
+    let hex = encoded.strip_prefix("0x").unwrap_or(encoded);
+    if hex.len() != 64 {
+        return Err(TxsError::InvalidKey(format!(
+            "Failed to parse the private key from {source}: expected 64 hex characters, got {}",
+            hex.len()
+        ))
+        .into());
+    }
+    Ed25519PrivateKey::from_encoded_string(encoded).map_err(|_| {
+        TxsError::InvalidKey(format!(
+            "Failed to parse the private key from {source}: it is not valid hex"
+        ))
+        .into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const KEY: &str = "c43f57994644ebda1eabfebf84def73fbd1d3ce442a9d2b2f4cb9f4da7b9908c";
+
+    #[test]
+    fn redact_keys_in_errors() {
+        let secret = Secret::from_str(KEY).unwrap();
+        assert_eq!(format!("{secret} {secret:?}"), "<redacted> <redacted>");
+        assert!(parse_private_key(&secret, "--private-key").is_ok());
+
+        // A bad hex character, which the hex error would quote, and a key one character short
+        for key in [format!("{}z", &KEY[..63]), KEY[..63].to_string()] {
+            let err = parse_private_key(&Secret::new(key.clone()), "--private-key").unwrap_err();
+            let rendered = format!("{err:?} {err:#}");
+            assert!(!rendered.contains(&key[..8]), "{rendered}");
+            assert!(!rendered.contains("'z'"), "{rendered}");
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{
+    constant::DEFAULT_LEDGER_EXPIRATION_SECS,
+    extension::{
+        client_ext::{ClientExt, TransactionOptions},
+        ed25519_private_key_ext::Ed25519PrivateKeyExt,
+    },
+    node_api::NodeApi,
+    sponsored_transaction::SponsoredTransaction,
+};
+#[cfg(feature = "ledger")]
+use crate::{
+    ledger::{Ledger, LedgerExt},
+    util::progress,
+};
+use anyhow::{bail, Result};
+use zapatos_sdk::{
+    crypto::ed25519::Ed25519PrivateKey,
+    types::{
+        account_address::AccountAddress,
+        transaction::{RawTransaction, SignedTransaction, TransactionPayload},
+        LocalAccount,
+    },
+};
+
+/// Where the signature of a transaction comes from
+pub enum TransactionSigner {
+    PrivateKey(Ed25519PrivateKey),
+    /// The key of a Ledger device at this derivation index
+    Ledger(u32),
+}
+
+impl TransactionSigner {
+    /// The key of the sender, for the transactions a Ledger device does not sign
+    pub fn private_key(&self) -> Result<&Ed25519PrivateKey> {
+        match self {
+            Self::PrivateKey(private_key) => Ok(private_key),
+            Self::Ledger(_) => bail!(
+                "A Ledger device only signs transactions without secondary signers or a fee payer"
+            ),
+        }
+    }
+
+    /// The account the transactions are signed for
+    pub async fn address<N: NodeApi>(&self, client: &N) -> Result<AccountAddress> {
+        match self {
+            Self::PrivateKey(private_key) => {
+                Ok(private_key.get_account(client, None).await?.address())
+            }
+            Self::Ledger(derivation_index) => ledger_address(*derivation_index),
+        }
+    }
+
+    pub async fn sign_payload<N: NodeApi>(
+        &self,
+        client: &N,
+        payload: TransactionPayload,
+        options: TransactionOptions,
+    ) -> Result<SignedTransaction> {
+        match self {
+            Self::PrivateKey(private_key) => {
+                let mut account = private_key
+                    .get_account(client, options.sequence_number)
+                    .await?;
+                client.sign_payload(&mut account, payload, options).await
+            }
+            Self::Ledger(derivation_index) => {
+                let sender = ledger_address(*derivation_index)?;
+                // Time to review the transaction on the device
+                let options = TransactionOptions {
+                    timeout_secs: options.timeout_secs.max(DEFAULT_LEDGER_EXPIRATION_SECS),
+                    ..options
+                };
+                let raw_trans = client
+                    .build_raw_transaction(sender, payload, options)
+                    .await?;
+                ledger_sign(*derivation_index, raw_trans)
+            }
+        }
+    }
+
+    /// Sign as the sender, and as the secondary signers of a multi-agent transaction when there
+    /// are any
+    pub async fn sign_multi_agent_payload<N: NodeApi>(
+        &self,
+        client: &N,
+        secondary_private_keys: &[Ed25519PrivateKey],
+        payload: TransactionPayload,
+        options: TransactionOptions,
+    ) -> Result<SignedTransaction> {
+        if secondary_private_keys.is_empty() {
+            return self.sign_payload(client, payload, options).await;
+        }
+        let mut account = self
+            .private_key()?
+            .get_account(client, options.sequence_number)
+            .await?;
+        let secondary_signers = secondary_accounts(client, secondary_private_keys).await?;
+        client
+            .sign_multi_agent_payload(&mut account, &secondary_signers, payload, options)
+            .await
+    }
+
+    /// Sign as the sender, the secondary signers and the fee payer, which pays the gas instead
+    /// of the sender
+    pub async fn sign_fee_payer_payload<N: NodeApi>(
+        &self,
+        client: &N,
+        secondary_private_keys: &[Ed25519PrivateKey],
+        fee_payer_private_key: &Ed25519PrivateKey,
+        payload: TransactionPayload,
+        options: TransactionOptions,
+    ) -> Result<SignedTransaction> {
+        let mut account = self
+            .private_key()?
+            .get_account(client, options.sequence_number)
+            .await?;
+        let secondary_signers = secondary_accounts(client, secondary_private_keys).await?;
+        let fee_payer = fee_payer_private_key.get_account(client, Some(0)).await?;
+        client
+            .sign_fee_payer_payload(
+                &mut account,
+                &secondary_signers,
+                &fee_payer,
+                payload,
+                options,
+            )
+            .await
+    }
+
+    /// Sign as the sender and the secondary signers, for the account at `fee_payer_address` to
+    /// countersign later
+    pub async fn sign_for_fee_payer<N: NodeApi>(
+        &self,
+        client: &N,
+        secondary_private_keys: &[Ed25519PrivateKey],
+        fee_payer_address: AccountAddress,
+        payload: TransactionPayload,
+        options: TransactionOptions,
+    ) -> Result<SponsoredTransaction> {
+        let mut account = self
+            .private_key()?
+            .get_account(client, options.sequence_number)
+            .await?;
+        let secondary_signers = secondary_accounts(client, secondary_private_keys).await?;
+        client
+            .sign_for_fee_payer(
+                &mut account,
+                &secondary_signers,
+                fee_payer_address,
+                payload,
+                options,
+            )
+            .await
+    }
+}
+
+/// Only the addresses and keys of the secondary signers are used, not their sequence numbers
+async fn secondary_accounts(
+    client: &impl NodeApi,
+    private_keys: &[Ed25519PrivateKey],
+) -> Result<Vec<LocalAccount>> {
+    let mut accounts = vec![];
+    for private_key in private_keys {
+        accounts.push(private_key.get_account(client, Some(0)).await?);
+    }
+    Ok(accounts)
+}
+
+#[cfg(feature = "ledger")]
+pub fn ledger_address(derivation_index: u32) -> Result<AccountAddress> {
+    Ledger::open()?.address(derivation_index)
+}
+
+#[cfg(feature = "ledger")]
+pub fn ledger_sign(derivation_index: u32, raw_trans: RawTransaction) -> Result<SignedTransaction> {
+    progress("Review the transaction on the Ledger device and approve it...");
+    Ledger::open()?.sign_transaction(derivation_index, raw_trans)
+}
+
+#[cfg(not(feature = "ledger"))]
+pub fn ledger_address(_derivation_index: u32) -> Result<AccountAddress> {
+    bail!(LEDGER_UNSUPPORTED)
+}
+
+#[cfg(not(feature = "ledger"))]
+pub fn ledger_sign(
+    _derivation_index: u32,
+    _raw_trans: RawTransaction,
+) -> Result<SignedTransaction> {
+    bail!(LEDGER_UNSUPPORTED)
+}
+
+#[cfg(not(feature = "ledger"))]
+const LEDGER_UNSUPPORTED: &str =
+    "This build of txs has no Ledger support, build it with `cargo build --features ledger`";
@@ -0,0 +1,253 @@
+use crate::util::format_address;
+use anyhow::{bail, Context, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::path::Path;
+use zapatos_sdk::{
+    crypto::{ed25519::Ed25519PrivateKey, PrivateKey, SigningKey},
+    types::{
+        account_address::AccountAddress,
+        transaction::{
+            authenticator::AccountAuthenticator, RawTransaction, RawTransactionWithData,
+            SignedTransaction,
+        },
+        LocalAccount,
+    },
+};
+
+/// Version of the sponsored transaction file written by this version of txs.
+/// Bump it when the format changes, older files keep being read
+pub const SPONSORED_TRANSACTION_VERSION: u32 = 1;
+
+/// A transaction whose gas is paid by a fee payer instead of its sender. The sender and the
+/// secondary signers sign it first, then the fee payer countersigns it, possibly on another
+/// machine. They all sign the same message, which names the fee payer
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SponsoredTransaction {
+    pub version: u32,
+    /// Hex of the BCS encoded raw transaction
+    pub raw_transaction: String,
+    pub secondary_signer_addresses: Vec<String>,
+    pub fee_payer_address: String,
+    /// Hex of the BCS encoded authenticator of the sender
+    pub sender_authenticator: String,
+    /// Hex of the BCS encoded authenticators of the secondary signers, in their order
+    pub secondary_authenticators: Vec<String>,
+    // Copies of fields of the raw transaction, for people reading the file
+    pub sender: String,
+    pub sequence_number: u64,
+    pub chain_id: u8,
+    pub expiration_timestamp_secs: u64,
+}
+
+impl SponsoredTransaction {
+    /// Sign as the sender and the secondary signers, leaving the fee payer to countersign
+    pub fn sign(
+        raw_trans: RawTransaction,
+        sender: &LocalAccount,
+        secondary_signers: &[LocalAccount],
+        fee_payer_address: AccountAddress,
+    ) -> Result<Self> {
+        let secondary_signer_addresses = secondary_signers
+            .iter()
+            .map(LocalAccount::address)
+            .collect::<Vec<_>>();
+        let message = RawTransactionWithData::new_fee_payer(
+            raw_trans.clone(),
+            secondary_signer_addresses.clone(),
+            fee_payer_address,
+        );
+        let authenticator = |account: &LocalAccount| -> Result<String> {
+            let signature = account.private_key().sign(&message)?;
+            let authenticator =
+                AccountAuthenticator::ed25519(account.public_key().to_owned(), signature);
+            Ok(hex::encode(bcs::to_bytes(&authenticator)?))
+        };
+
+        Ok(Self {
+            version: SPONSORED_TRANSACTION_VERSION,
+            raw_transaction: hex::encode(bcs::to_bytes(&raw_trans)?),
+            secondary_signer_addresses: secondary_signer_addresses
+                .iter()
+                .map(format_address)
+                .collect(),
+            fee_payer_address: format_address(&fee_payer_address),
+            sender_authenticator: authenticator(sender)?,
+            secondary_authenticators: secondary_signers
+                .iter()
+                .map(authenticator)
+                .collect::<Result<_>>()?,
+            sender: format_address(&raw_trans.sender()),
+            sequence_number: raw_trans.sequence_number(),
+            chain_id: raw_trans.chain_id().id(),
+            expiration_timestamp_secs: raw_trans.expiration_timestamp_secs(),
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content =
+            std::fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+        let sponsored: Self = serde_json::from_str(&content).context(format!(
+            "{} is not a sponsored transaction file",
+            path.display()
+        ))?;
+        if sponsored.version > SPONSORED_TRANSACTION_VERSION {
+            bail!(
+                "{} is a sponsored transaction file of version {}, this version of txs reads up to version {SPONSORED_TRANSACTION_VERSION}",
+                path.display(),
+                sponsored.version
+            );
+        }
+        Ok(sponsored)
+    }
+
+    /// Write the file as JSON, refusing to overwrite it unless forced
+    pub fn save(&self, path: &Path, force: bool) -> Result<()> {
+        if path.exists() && !force {
+            bail!(
+                "{} already exists, use --force to overwrite it",
+                path.display()
+            );
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .context(format!("Failed to write {}", path.display()))
+    }
+
+    /// Sign as the fee payer, without any network access. The signatures of the other signers
+    /// are checked first, so an edited file is not countersigned
+    pub fn countersign(&self, private_key: &Ed25519PrivateKey) -> Result<SignedTransaction> {
+        let raw_trans: RawTransaction = from_hex_bcs(&self.raw_transaction, "raw transaction")?;
+        let secondary_signer_addresses = self
+            .secondary_signer_addresses
+            .iter()
+            .map(|address| from_hex_address(address))
+            .collect::<Result<Vec<_>>>()?;
+        let fee_payer_address = from_hex_address(&self.fee_payer_address)?;
+        let sender: AccountAuthenticator =
+            from_hex_bcs(&self.sender_authenticator, "sender authenticator")?;
+        let secondary_signers = self
+            .secondary_authenticators
+            .iter()
+            .map(|authenticator| from_hex_bcs(authenticator, "secondary authenticator"))
+            .collect::<Result<Vec<AccountAuthenticator>>>()?;
+        if secondary_signers.len() != secondary_signer_addresses.len() {
+            bail!(
+                "The file has {} secondary signer(s) but {} of their signatures",
+                secondary_signer_addresses.len(),
+                secondary_signers.len()
+            );
+        }
+
+        let message = RawTransactionWithData::new_fee_payer(
+            raw_trans.clone(),
+            secondary_signer_addresses.clone(),
+            fee_payer_address,
+        );
+        sender
+            .verify(&message)
+            .context("The signature of the sender does not match the transaction of the file")?;
+        for authenticator in &secondary_signers {
+            authenticator.verify(&message).context(
+                "The signature of a secondary signer does not match the transaction of the file",
+            )?;
+        }
+        let fee_payer_signer =
+            AccountAuthenticator::ed25519(private_key.public_key(), private_key.sign(&message)?);
+
+        Ok(SignedTransaction::new_fee_payer(
+            raw_trans,
+            sender,
+            secondary_signer_addresses,
+            secondary_signers,
+            fee_payer_address,
+            fee_payer_signer,
+        ))
+    }
+}
+
+fn from_hex_address(address: &str) -> Result<AccountAddress> {
+    AccountAddress::from_hex_literal(address)
+        .context(format!("The file holds an invalid address: {address}"))
+}
+
+fn from_hex_bcs<T: DeserializeOwned>(hex: &str, name: &str) -> Result<T> {
+    let bytes = hex::decode(hex).context(format!("The {name} of the file is not hex"))?;
+    bcs::from_bytes(&bytes).context(format!("The file holds an invalid {name}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zapatos_sdk::types::{
+        chain_id::ChainId,
+        transaction::{authenticator::TransactionAuthenticator, Script, TransactionPayload},
+        AccountKey,
+    };
+
+    fn account(byte: u8) -> LocalAccount {
+        let account_key =
+            AccountKey::from_private_key(Ed25519PrivateKey::try_from(&[byte; 32][..]).unwrap());
+        LocalAccount::new(
+            account_key.authentication_key().derived_address(),
+            account_key,
+            0,
+        )
+    }
+
+    #[test]
+    fn countersign_as_the_fee_payer() {
+        let (sender, secondary_signer, fee_payer) = (account(1), account(2), account(3));
+        let raw_trans = RawTransaction::new(
+            sender.address(),
+            7,
+            TransactionPayload::Script(Script::new(vec![], vec![], vec![])),
+            1_000,
+            100,
+            1_700_000_000,
+            ChainId::new(4),
+        );
+        let sponsored = SponsoredTransaction::sign(
+            raw_trans.clone(),
+            &sender,
+            std::slice::from_ref(&secondary_signer),
+            fee_payer.address(),
+        )
+        .unwrap();
+        let sponsored: SponsoredTransaction =
+            serde_json::from_str(&serde_json::to_string(&sponsored).unwrap()).unwrap();
+
+        let signed_trans = sponsored.countersign(fee_payer.private_key()).unwrap();
+        assert_eq!(signed_trans.sender(), sender.address());
+        match signed_trans.authenticator() {
+            TransactionAuthenticator::FeePayer {
+                secondary_signer_addresses,
+                fee_payer_address,
+                fee_payer_signer,
+                ..
+            } => {
+                assert_eq!(fee_payer_address, fee_payer.address());
+                assert_eq!(secondary_signer_addresses, vec![secondary_signer.address()]);
+                assert_eq!(
+                    fee_payer_signer,
+                    AccountAuthenticator::ed25519(
+                        fee_payer.public_key().to_owned(),
+                        fee_payer
+                            .private_key()
+                            .sign(&RawTransactionWithData::new_fee_payer(
+                                raw_trans,
+                                secondary_signer_addresses,
+                                fee_payer_address,
+                            ))
+                            .unwrap(),
+                    )
+                );
+            }
+            _ => panic!("Expected a fee payer authenticator"),
+        }
+        signed_trans.verify_signature().unwrap();
+
+        // The fee payer is part of what the sender signed, it cannot be swapped
+        let mut edited = sponsored;
+        edited.fee_payer_address = format_address(&sender.address());
+        assert!(edited.countersign(sender.private_key()).is_err());
+    }
+}
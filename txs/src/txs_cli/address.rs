@@ -0,0 +1,72 @@
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+use txs::{
+    address_book::AddressBook,
+    util::{format_address, format_output, parse_address, OutputFormat},
+};
+
+#[derive(clap::Subcommand)]
+pub enum AddressCommand {
+    /// Save an address under a name, replacing the address the name had
+    Add {
+        /// Letters, digits, _ and - only
+        name: String,
+
+        /// The account address, never a private key
+        address: String,
+    },
+
+    /// Print the saved names and their addresses
+    List,
+
+    /// Forget a name
+    Remove { name: String },
+}
+
+pub fn run(command: &AddressCommand, format: OutputFormat) -> Result<()> {
+    let mut book = AddressBook::load()?;
+    match command {
+        AddressCommand::Add { name, address } => {
+            let address = parse_address(address, "<ADDRESS>")?;
+            match book.add(name, address)? {
+                Some(previous) => println!(
+                    "{name} changed from {} to {}",
+                    format_address(&previous),
+                    format_address(&address)
+                ),
+                None => println!("Added {name}: {}", format_address(&address)),
+            }
+            book.save()
+        }
+        AddressCommand::List => {
+            let entries = book.entries()?;
+            if format == OutputFormat::Text {
+                if entries.is_empty() {
+                    println!(
+                        "The address book is empty, add names with `txs address add <NAME> <ADDRESS>`"
+                    );
+                }
+                let width = entries.iter().map(|(name, _)| name.len()).max();
+                for (name, address) in &entries {
+                    println!(
+                        "{name:width$}  {}",
+                        format_address(&address),
+                        width = width.unwrap_or_default()
+                    );
+                }
+            } else {
+                let entries = entries
+                    .into_iter()
+                    .map(|(name, address)| (name, json!(format_address(&address))))
+                    .collect::<Map<String, Value>>();
+                println!("{}", format_output(&entries, format)?);
+            }
+            Ok(())
+        }
+        AddressCommand::Remove { name } => {
+            let address = book.remove(name)?;
+            println!("Removed {name}: {}", format_address(&address));
+            book.save()
+        }
+    }
+}
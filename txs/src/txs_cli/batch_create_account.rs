@@ -0,0 +1,405 @@
+use super::{create_account, prompt, WaitOptions};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+    time::Duration,
+};
+use txs::{
+    client::create_account_payload,
+    crypto::HashValue,
+    extension::client_ext::{ClientExt, TransactionOptions},
+    node_api::NodeApi,
+    rest_client::FaucetClient,
+    retry::submit_with_retry,
+    types::{account_address::AccountAddress, LocalAccount},
+    util::parse_address,
+};
+
+/// Where the coins of the new accounts come from
+pub enum Funding<'a> {
+    Faucet(&'a FaucetClient),
+    /// Transfers from this account
+    Funder(LocalAccount),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    Created,
+    /// The account existed before, nothing was sent
+    Skipped,
+    Failed,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct RowResult {
+    row: usize,
+    address: String,
+    coins: u64,
+    status: Status,
+    hash: Option<String>,
+    error: Option<String>,
+}
+
+struct NewAccount {
+    row: usize,
+    address: AccountAddress,
+    coins: u64,
+}
+
+impl NewAccount {
+    fn result(&self, status: Status, hash: Option<HashValue>, error: Option<String>) -> RowResult {
+        RowResult {
+            row: self.row,
+            address: self.address.to_hex_literal(),
+            coins: self.coins,
+            status,
+            hash: hash.map(|hash| hash.to_hex_literal()),
+            error,
+        }
+    }
+}
+
+/// Create the account of every row, one after the other. Rows which failed are reported and
+/// the others still created, a rerun only retries them
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    client: &impl NodeApi,
+    batch_file: &Path,
+    results_file: Option<&Path>,
+    default_coins: Option<u64>,
+    mut funding: Funding<'_>,
+    options: TransactionOptions,
+    wait_options: &WaitOptions,
+    yes: bool,
+) -> Result<()> {
+    if wait_options.no_wait {
+        bail!("--no-wait cannot be used with --batch-file, the accounts are checked once created");
+    }
+    let accounts = read_accounts(batch_file, default_coins)?;
+    let results_file = results_file
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| batch_file.with_extension("results.json"));
+    let previous_results = read_previous_results(&results_file)?;
+
+    let mut results = BTreeMap::new();
+    let mut pending = vec![];
+    for account in &accounts {
+        match previous_results.get(&account.address.to_hex_literal()) {
+            Some(previous_result) => {
+                let mut previous_result = previous_result.to_owned();
+                previous_result.row = account.row;
+                results.insert(account.row, previous_result);
+            }
+            None => pending.push(account),
+        }
+    }
+
+    let total: u128 = pending.iter().map(|account| account.coins as u128).sum();
+    prompt::confirm(
+        &[
+            (
+                "Funded by",
+                match &funding {
+                    Funding::Faucet(_) => "the faucet".to_string(),
+                    Funding::Funder(funder) => funder.address().to_hex_literal(),
+                },
+            ),
+            ("Rows", accounts.len().to_string()),
+            ("Already done", results.len().to_string()),
+            ("Total", format!("{total} base units")),
+        ],
+        yes,
+    )?;
+
+    for account in pending {
+        let result = match create_account::balance(client, account.address).await {
+            Ok(Some(_)) => account.result(Status::Skipped, None, None),
+            Ok(None) => {
+                let created = match &mut funding {
+                    Funding::Faucet(faucet_client) => create_with_faucet(faucet_client, account)
+                        .await
+                        .map(|_| None),
+                    Funding::Funder(funder) => {
+                        fund(client, funder, account, options.clone(), wait_options)
+                            .await
+                            .map(Some)
+                    }
+                };
+                match created {
+                    Ok(hash) => account.result(Status::Created, hash, None),
+                    Err(err) => {
+                        if let Funding::Funder(funder) = &funding {
+                            // Its sequence number may not have been used, ask the node
+                            funder.set_sequence_number(
+                                client.get_sequence_number(funder.address()).await?,
+                            );
+                        }
+                        account.result(Status::Failed, None, Some(format!("{err:#}")))
+                    }
+                }
+            }
+            Err(err) => account.result(Status::Failed, None, Some(format!("{err:#}"))),
+        };
+        results.insert(account.row, result);
+        write_results(&results_file, results.values())?;
+    }
+    write_results(&results_file, results.values())?;
+
+    println!("{}", format_results(results.values()));
+    println!("Results saved to {}", results_file.display());
+
+    let failed = results
+        .values()
+        .filter(|result| result.status == Status::Failed)
+        .count();
+    if failed > 0 {
+        bail!("{failed} account(s) failed, rerun the same command to retry them");
+    }
+    Ok(())
+}
+
+/// Validate every row before any account is created, reporting all the invalid rows at once
+fn read_accounts(batch_file: &Path, default_coins: Option<u64>) -> Result<Vec<NewAccount>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .comment(Some(b'#'))
+        .from_path(batch_file)
+        .context(format!("Failed to open {}", batch_file.display()))?;
+
+    let mut accounts = vec![];
+    let mut errors = vec![];
+    let mut rows = HashMap::new();
+    for (index, record) in reader.records().enumerate() {
+        let row = index + 1;
+        let record = record.context(format!("Failed to read row {row}"))?;
+        if row == 1 && record.get(0) == Some("address") {
+            continue;
+        }
+
+        match parse_row(row, &record, default_coins) {
+            Ok(account) => match rows.insert(account.address, row) {
+                Some(duplicate_row) => {
+                    errors.push(format!("row {row}: duplicate of row {duplicate_row}"))
+                }
+                None => accounts.push(account),
+            },
+            Err(err) => errors.push(format!("row {row}: {err:#}")),
+        }
+    }
+
+    if !errors.is_empty() {
+        bail!(
+            "Invalid rows in {}, no account was created:\n{}",
+            batch_file.display(),
+            errors.join("\n")
+        );
+    }
+    if accounts.is_empty() {
+        bail!("No accounts in {}", batch_file.display());
+    }
+    Ok(accounts)
+}
+
+fn parse_row(
+    row: usize,
+    record: &csv::StringRecord,
+    default_coins: Option<u64>,
+) -> Result<NewAccount> {
+    if record.is_empty() || record.len() > 2 {
+        bail!("expected address[,coins]");
+    }
+    let address = parse_address(&record[0], &format!("row {row}"))?;
+    let coins = match record.get(1) {
+        Some(coins) if !coins.is_empty() => coins.parse::<u64>().context(format!(
+            "invalid coins {coins}, expected an integer in base units"
+        ))?,
+        _ => default_coins.unwrap_or_default(),
+    };
+    Ok(NewAccount {
+        row,
+        address,
+        coins,
+    })
+}
+
+async fn create_with_faucet(faucet_client: &FaucetClient, account: &NewAccount) -> Result<()> {
+    if account.coins == 0 {
+        faucet_client.create_account(account.address).await?;
+    } else {
+        faucet_client.fund(account.address, account.coins).await?;
+    }
+    Ok(())
+}
+
+async fn fund(
+    client: &impl NodeApi,
+    funder: &mut LocalAccount,
+    account: &NewAccount,
+    options: TransactionOptions,
+    wait_options: &WaitOptions,
+) -> Result<HashValue> {
+    let payload = create_account_payload(account.address, account.coins)?;
+    let signed_trans = client.sign_payload(funder, payload, options).await?;
+    let hash = submit_with_retry(client, &signed_trans).await?;
+    client
+        .wait_for_transaction_ext(
+            hash,
+            signed_trans.expiration_timestamp_secs(),
+            Duration::from_secs(wait_options.wait_timeout_secs),
+            Duration::from_millis(wait_options.poll_interval_ms),
+        )
+        .await?;
+    Ok(hash)
+}
+
+/// Rows which a previous run of the same file created or skipped, failed rows are retried
+fn read_previous_results(results_file: &Path) -> Result<HashMap<String, RowResult>> {
+    if !results_file.exists() {
+        return Ok(HashMap::new());
+    }
+    let results: Vec<RowResult> = serde_json::from_str(
+        &fs::read_to_string(results_file)
+            .context(format!("Failed to read {}", results_file.display()))?,
+    )
+    .context(format!(
+        "{} is not a batch account creation results file",
+        results_file.display()
+    ))?;
+
+    Ok(results
+        .into_iter()
+        .filter(|result| matches!(result.status, Status::Created | Status::Skipped))
+        .map(|result| (result.address.to_owned(), result))
+        .collect())
+}
+
+fn write_results<'a>(
+    results_file: &Path,
+    results: impl Iterator<Item = &'a RowResult>,
+) -> Result<()> {
+    let results = results.collect::<Vec<_>>();
+    fs::write(results_file, serde_json::to_string_pretty(&results)?)
+        .context(format!("Failed to write {}", results_file.display()))
+}
+
+fn format_results<'a>(results: impl Iterator<Item = &'a RowResult>) -> String {
+    let mut lines = vec![format!(
+        "{:<5} {:<8} {:<66} {:<20} HASH / ERROR",
+        "ROW", "STATUS", "ADDRESS", "COINS"
+    )];
+    for result in results {
+        let status = serde_json::to_value(result.status)
+            .ok()
+            .and_then(|status| status.as_str().map(str::to_string))
+            .unwrap_or_default();
+        lines.push(format!(
+            "{:<5} {:<8} {:<66} {:<20} {}",
+            result.row,
+            status,
+            result.address,
+            result.coins,
+            result
+                .error
+                .as_deref()
+                .or(result.hash.as_deref())
+                .unwrap_or_default()
+        ));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        address, arg, entry_function, mock_node, options, private_key, sender, wait_options,
+    };
+    use std::path::PathBuf;
+    use txs::node_api::MockNode;
+
+    /// A batch file of its own, as the results of a file are kept for its next run
+    fn write_batch(name: &str, rows: &str) -> (PathBuf, PathBuf) {
+        let batch_file = std::env::temp_dir().join(format!(
+            "txs-batch-create-test-{name}-{}.csv",
+            std::process::id()
+        ));
+        fs::write(&batch_file, rows).unwrap();
+        let results_file = batch_file.with_extension("results.json");
+        fs::remove_file(&results_file).ok();
+        (batch_file, results_file)
+    }
+
+    async fn create(node: &MockNode, batch_file: &Path, results_file: &Path) -> Result<()> {
+        let funder = LocalAccount::new(sender(), private_key(), 0);
+        run(
+            node,
+            batch_file,
+            Some(results_file),
+            Some(10),
+            Funding::Funder(funder),
+            options(),
+            &wait_options(),
+            true,
+        )
+        .await
+    }
+
+    fn statuses(results_file: &Path) -> Vec<Status> {
+        let results = fs::read_to_string(results_file).unwrap();
+        serde_json::from_str::<Vec<RowResult>>(&results)
+            .unwrap()
+            .iter()
+            .map(|result| result.status)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn fund_the_missing_accounts_in_turn() {
+        // 0x2 exists in the fixtures, the others take the default of 10 base units
+        let (batch_file, results_file) =
+            write_batch("created", "address,coins\n0x2\n0x5,20\n0x6\n");
+        let node = mock_node();
+        create(&node, &batch_file, &results_file).await.unwrap();
+        assert_eq!(
+            statuses(&results_file),
+            [Status::Skipped, Status::Created, Status::Created]
+        );
+        let submitted = node.submitted();
+        let funded = submitted
+            .iter()
+            .map(|signed_trans| {
+                let call = entry_function(signed_trans);
+                (
+                    signed_trans.sequence_number(),
+                    arg::<AccountAddress>(&call, 0),
+                    arg::<u64>(&call, 1),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(funded, [(0, address("0x5"), 20), (1, address("0x6"), 10)]);
+
+        // A rerun of the same file only retries what failed
+        let node = mock_node();
+        create(&node, &batch_file, &results_file).await.unwrap();
+        assert!(node.submitted().is_empty());
+    }
+
+    #[tokio::test]
+    async fn report_every_invalid_row() {
+        let (batch_file, results_file) =
+            write_batch("invalid", "0x5\nnot an address\n0x6,many\n0x5\n");
+        let node = mock_node();
+        let err = create(&node, &batch_file, &results_file).await.unwrap_err();
+        let err = format!("{err:#}");
+        assert!(err.contains("row 2"), "{err}");
+        assert!(err.contains("row 3: invalid coins many"), "{err}");
+        assert!(err.contains("row 4: duplicate of row 1"), "{err}");
+        assert!(node.requests().is_empty());
+    }
+}
@@ -0,0 +1,605 @@
+use super::{prompt, transfer_coin, BatchOptions, WaitOptions};
+use anyhow::{bail, Context, Result};
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+    time::Duration,
+};
+use tokio::time::Instant;
+use txs::{
+    crypto::{ed25519::Ed25519PrivateKey, HashValue},
+    extension::{
+        client_ext::{ClientExt, TransactionOptions, WaitTimeoutError},
+        ed25519_private_key_ext::Ed25519PrivateKeyExt,
+    },
+    move_types::language_storage::TypeTag,
+    node_api::NodeApi,
+    retry::submit_with_retry,
+    types::{account_address::AccountAddress, LocalAccount},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    /// Committed on-chain
+    Success,
+    /// Accepted by the node but not seen committed, either with --no-wait or after a timeout
+    Submitted,
+    Failed,
+    /// Not attempted because an earlier row stopped the batch
+    NotSent,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct RowResult {
+    row: usize,
+    address: String,
+    amount: String,
+    base_amount: u64,
+    coin_type: String,
+    status: Status,
+    hash: Option<String>,
+    error: Option<String>,
+}
+
+struct Transfer {
+    row: usize,
+    to_account: AccountAddress,
+    amount: String,
+    base_amount: u64,
+    coin_type: Option<TypeTag>,
+    coin_type_tag: TypeTag,
+}
+
+impl Transfer {
+    /// Rows are unique, so this identifies a transfer across runs of the same file
+    fn key(&self) -> (String, u64, String) {
+        (
+            self.to_account.to_hex_literal(),
+            self.base_amount,
+            self.coin_type_tag.to_string(),
+        )
+    }
+
+    fn result(&self, status: Status, hash: Option<HashValue>, error: Option<String>) -> RowResult {
+        RowResult {
+            row: self.row,
+            address: self.to_account.to_hex_literal(),
+            amount: self.amount.to_owned(),
+            base_amount: self.base_amount,
+            coin_type: self.coin_type_tag.to_string(),
+            status,
+            hash: hash.map(|hash| hash.to_hex_literal()),
+            error,
+        }
+    }
+}
+
+pub async fn run(
+    client: &impl NodeApi,
+    csv_file: &Path,
+    batch_options: &BatchOptions,
+    private_key: &Ed25519PrivateKey,
+    options: TransactionOptions,
+    wait_options: &WaitOptions,
+    yes: bool,
+) -> Result<()> {
+    let concurrency = batch_options.concurrency as usize;
+    if concurrency > 1 && wait_options.no_wait {
+        bail!("--no-wait cannot be used with --concurrency, a rejected transaction would leave the following ones stuck unnoticed");
+    }
+    if matches!(batch_options.tps_limit, Some(tps_limit) if tps_limit <= 0.0) {
+        bail!("--tps-limit must be greater than zero");
+    }
+
+    let transfers = read_transfers(client, csv_file, batch_options.raw).await?;
+    let results_file = batch_options
+        .results_file
+        .to_owned()
+        .unwrap_or_else(|| csv_file.with_extension("results.json"));
+    let previous_results = read_previous_results(&results_file)?;
+
+    let mut results = BTreeMap::new();
+    let mut pending = vec![];
+    let mut totals = BTreeMap::<String, u128>::new();
+    for transfer in &transfers {
+        match previous_results.get(&transfer.key()) {
+            Some(previous_result) => {
+                let mut previous_result = previous_result.to_owned();
+                previous_result.row = transfer.row;
+                results.insert(transfer.row, previous_result);
+            }
+            None => {
+                *totals
+                    .entry(transfer.coin_type_tag.to_string())
+                    .or_default() += transfer.base_amount as u128;
+                pending.push(transfer);
+            }
+        }
+    }
+
+    let mut from_account = private_key.get_account(client, None).await?;
+    let mut details = vec![
+        ("Sender", from_account.address().to_hex_literal()),
+        ("Rows", transfers.len().to_string()),
+        ("Already sent", results.len().to_string()),
+    ];
+    for (coin_type, total) in &totals {
+        details.push(("Total", format!("{total} base units of {coin_type}")));
+    }
+    prompt::confirm(&details, yes)?;
+
+    let mut options = options;
+    let mut throttle = Throttle::new(batch_options.tps_limit);
+    let mut stopped = false;
+    for chunk in pending.chunks(concurrency) {
+        if stopped {
+            for transfer in chunk {
+                results.insert(transfer.row, transfer.result(Status::NotSent, None, None));
+            }
+            continue;
+        }
+
+        let outcomes = send_chunk(
+            client,
+            &mut from_account,
+            chunk,
+            &mut options,
+            wait_options,
+            &mut throttle,
+        )
+        .await;
+        let mut failed = false;
+        for (transfer, outcome) in chunk.iter().zip(outcomes) {
+            let result = match outcome {
+                Ok(hash) if wait_options.no_wait => {
+                    transfer.result(Status::Submitted, Some(hash), None)
+                }
+                Ok(hash) => transfer.result(Status::Success, Some(hash), None),
+                Err((hash, err)) if err.is::<WaitTimeoutError>() => {
+                    // It may still be committed, sending it again could pay the recipient twice
+                    stopped = true;
+                    transfer.result(Status::Submitted, hash, Some(format!("{err:#}")))
+                }
+                Err((hash, err)) => {
+                    failed = true;
+                    transfer.result(Status::Failed, hash, Some(format!("{err:#}")))
+                }
+            };
+            results.insert(transfer.row, result);
+        }
+
+        if failed {
+            stopped |= !batch_options.continue_on_error;
+            // Some sequence numbers may not have been used, ask the node
+            from_account = private_key.get_account(client, None).await?;
+        }
+        write_results(&results_file, results.values())?;
+    }
+    write_results(&results_file, results.values())?;
+
+    println!("{}", format_results(results.values()));
+    println!("Results saved to {}", results_file.display());
+
+    let failed = results
+        .values()
+        .filter(|result| result.status == Status::Failed)
+        .count();
+    if failed > 0 {
+        bail!("{failed} transfer(s) failed, rerun the same command to retry the rows which were not sent");
+    }
+    if stopped {
+        bail!("A transaction was not seen committed in time, check it with tx-status before rerunning the same command");
+    }
+    Ok(())
+}
+
+/// Validate every row before anything is sent, reporting all the invalid rows at once
+async fn read_transfers(
+    client: &impl NodeApi,
+    csv_file: &Path,
+    raw: bool,
+) -> Result<Vec<Transfer>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .comment(Some(b'#'))
+        .from_path(csv_file)
+        .context(format!("Failed to open {}", csv_file.display()))?;
+
+    let mut transfers = vec![];
+    let mut errors = vec![];
+    let mut decimals = HashMap::<String, u8>::new();
+    let mut rows = HashMap::new();
+    for (index, record) in reader.records().enumerate() {
+        let row = index + 1;
+        let record = record.context(format!("Failed to read row {row}"))?;
+        if row == 1 && record.get(0) == Some("address") {
+            continue;
+        }
+
+        let transfer = parse_row(client, row, &record, raw, &mut decimals).await;
+        match transfer {
+            Ok(transfer) => match rows.insert(transfer.key(), row) {
+                Some(duplicate_row) => {
+                    errors.push(format!("row {row}: duplicate of row {duplicate_row}"))
+                }
+                None => transfers.push(transfer),
+            },
+            Err(err) => errors.push(format!("row {row}: {err:#}")),
+        }
+    }
+
+    if !errors.is_empty() {
+        bail!(
+            "Invalid rows in {}, nothing was sent:\n{}",
+            csv_file.display(),
+            errors.join("\n")
+        );
+    }
+    if transfers.is_empty() {
+        bail!("No transfers in {}", csv_file.display());
+    }
+    Ok(transfers)
+}
+
+async fn parse_row(
+    client: &impl NodeApi,
+    row: usize,
+    record: &csv::StringRecord,
+    raw: bool,
+    decimals: &mut HashMap<String, u8>,
+) -> Result<Transfer> {
+    if record.len() < 2 || record.len() > 3 {
+        bail!("expected address,amount[,coin_type]");
+    }
+    let to_account = AccountAddress::from_hex_literal(&record[0])
+        .context(format!("invalid address {}", &record[0]))?;
+    let coin_type = match record.get(2) {
+        Some(coin_type) if !coin_type.is_empty() => {
+            Some(transfer_coin::parse_coin_type(coin_type)?)
+        }
+        _ => None,
+    };
+    let coin_type_tag = transfer_coin::native_or(coin_type.clone())?;
+
+    let coin_decimals = if raw {
+        None
+    } else {
+        let key = coin_type_tag.to_string();
+        if !decimals.contains_key(&key) {
+            decimals.insert(key.clone(), client.get_coin_decimals(&coin_type_tag).await?);
+        }
+        decimals.get(&key).copied()
+    };
+    let base_amount = transfer_coin::parse_amount(&record[1], coin_decimals)?;
+    if base_amount == 0 {
+        bail!("the amount must be greater than zero");
+    }
+    if let Some(coin_type) = &coin_type {
+        transfer_coin::check_coin_store(client, to_account, coin_type).await?;
+    }
+
+    Ok(Transfer {
+        row,
+        to_account,
+        amount: record[1].to_string(),
+        base_amount,
+        coin_type,
+        coin_type_tag,
+    })
+}
+
+/// Rows which were sent by a previous run of the same file, failed rows are retried
+fn read_previous_results(results_file: &Path) -> Result<HashMap<(String, u64, String), RowResult>> {
+    if !results_file.exists() {
+        return Ok(HashMap::new());
+    }
+    let results: Vec<RowResult> = serde_json::from_str(
+        &fs::read_to_string(results_file)
+            .context(format!("Failed to read {}", results_file.display()))?,
+    )
+    .context(format!(
+        "{} is not a batch transfer results file",
+        results_file.display()
+    ))?;
+
+    Ok(results
+        .into_iter()
+        .filter(|result| matches!(result.status, Status::Success | Status::Submitted))
+        .map(|result| {
+            (
+                (
+                    result.address.to_owned(),
+                    result.base_amount,
+                    result.coin_type.to_owned(),
+                ),
+                result,
+            )
+        })
+        .collect())
+}
+
+type Outcome = std::result::Result<HashValue, (Option<HashValue>, anyhow::Error)>;
+
+/// Sign the transfers with consecutive sequence numbers, submit them concurrently and wait for all of them
+async fn send_chunk(
+    client: &impl NodeApi,
+    from_account: &mut LocalAccount,
+    chunk: &[&Transfer],
+    options: &mut TransactionOptions,
+    wait_options: &WaitOptions,
+    throttle: &mut Throttle,
+) -> Vec<Outcome> {
+    let mut outcomes: Vec<Option<Outcome>> = Vec::with_capacity(chunk.len());
+    let mut signed_transactions = vec![];
+    for transfer in chunk {
+        let signed_trans = match transfer_coin::transfer_payload(
+            transfer.to_account,
+            transfer.base_amount,
+            transfer.coin_type.clone(),
+        ) {
+            Ok(payload) => {
+                client
+                    .sign_payload(from_account, payload, options.clone())
+                    .await
+            }
+            Err(err) => Err(err),
+        };
+
+        match signed_trans {
+            Ok(signed_trans) => {
+                if chunk.len() > 1 {
+                    // Simulating a transaction ahead of the on-chain sequence number fails,
+                    // so the gas estimated for the first one is reused for the others
+                    options.max_gas_amount = options
+                        .max_gas_amount
+                        .or(Some(signed_trans.max_gas_amount()));
+                    options.gas_unit_price = options
+                        .gas_unit_price
+                        .or(Some(signed_trans.gas_unit_price()));
+                }
+                signed_transactions.push((outcomes.len(), signed_trans));
+                outcomes.push(None);
+            }
+            Err(err) => outcomes.push(Some(Err((None, err)))),
+        }
+    }
+
+    let submissions = join_all(signed_transactions.iter().map(|(_, signed_trans)| {
+        let slot = throttle.next_slot();
+        async move {
+            tokio::time::sleep_until(slot).await;
+            // Retried, as a gap in the sequence numbers leaves all the following transactions stuck
+            submit_with_retry(client, signed_trans).await
+        }
+    }))
+    .await;
+
+    // Transactions after a rejected one cannot be committed until they expire
+    let stuck_behind = signed_transactions
+        .iter()
+        .zip(&submissions)
+        .find(|(_, submission)| submission.is_err())
+        .map(|((_, signed_trans), _)| signed_trans.sequence_number());
+
+    let waits = join_all(signed_transactions.iter().zip(submissions).map(
+        |((_, signed_trans), submission)| async move {
+            let hash = submission.map_err(|err| (None, err))?;
+            if !wait_options.no_wait {
+                client
+                    .wait_for_transaction_ext(
+                        hash,
+                        signed_trans.expiration_timestamp_secs(),
+                        Duration::from_secs(wait_options.wait_timeout_secs),
+                        Duration::from_millis(wait_options.poll_interval_ms),
+                    )
+                    .await
+                    .map_err(|err| (Some(hash), err))?;
+            }
+            Ok(hash)
+        },
+    ))
+    .await;
+
+    for ((index, signed_trans), outcome) in signed_transactions.iter().zip(waits) {
+        let outcome = match (outcome, stuck_behind) {
+            (Err((hash, err)), Some(sequence_number))
+                if signed_trans.sequence_number() > sequence_number =>
+            {
+                Err((
+                    hash,
+                    err.context(format!(
+                        "Stuck behind sequence number {sequence_number}, which the node rejected"
+                    )),
+                ))
+            }
+            (outcome, _) => outcome,
+        };
+        outcomes[*index] = Some(outcome);
+    }
+    outcomes.into_iter().flatten().collect()
+}
+
+/// Spreads the submissions out to stay under --tps-limit
+struct Throttle {
+    interval: Option<Duration>,
+    next_slot: Instant,
+}
+
+impl Throttle {
+    fn new(tps_limit: Option<f64>) -> Self {
+        Self {
+            interval: tps_limit.map(|tps_limit| Duration::from_secs_f64(1.0 / tps_limit)),
+            next_slot: Instant::now(),
+        }
+    }
+
+    fn next_slot(&mut self) -> Instant {
+        let slot = self.next_slot.max(Instant::now());
+        if let Some(interval) = self.interval {
+            self.next_slot = slot + interval;
+        }
+        slot
+    }
+}
+
+fn write_results<'a>(
+    results_file: &Path,
+    results: impl Iterator<Item = &'a RowResult>,
+) -> Result<()> {
+    let results = results.collect::<Vec<_>>();
+    fs::write(results_file, serde_json::to_string_pretty(&results)?)
+        .context(format!("Failed to write {}", results_file.display()))
+}
+
+fn format_results<'a>(results: impl Iterator<Item = &'a RowResult>) -> String {
+    let mut lines = vec![format!(
+        "{:<5} {:<10} {:<66} {:<20} HASH / ERROR",
+        "ROW", "STATUS", "ADDRESS", "AMOUNT"
+    )];
+    for result in results {
+        let status = serde_json::to_value(result.status)
+            .ok()
+            .and_then(|status| status.as_str().map(str::to_string))
+            .unwrap_or_default();
+        lines.push(format!(
+            "{:<5} {:<10} {:<66} {:<20} {}",
+            result.row,
+            status,
+            result.address,
+            result.amount,
+            result
+                .error
+                .as_deref()
+                .or(result.hash.as_deref())
+                .unwrap_or_default()
+        ));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        aborted_transaction, address, arg, entry_function, mock_node, options, private_key,
+        wait_options, TRANSACTION,
+    };
+    use txs::node_api::MockNode;
+
+    /// A batch file of its own, as the results of a file are kept for its next run
+    fn batch_file(name: &str, rows: &str) -> (std::path::PathBuf, BatchOptions) {
+        let csv_file = std::env::temp_dir().join(format!(
+            "txs-batch-transfer-test-{name}-{}.csv",
+            std::process::id()
+        ));
+        fs::write(&csv_file, rows).unwrap();
+        let results_file = csv_file.with_extension("results.json");
+        fs::remove_file(&results_file).ok();
+        let batch_options = BatchOptions {
+            results_file: Some(results_file),
+            raw: false,
+            continue_on_error: false,
+            concurrency: 1,
+            tps_limit: None,
+        };
+        (csv_file, batch_options)
+    }
+
+    async fn send(node: &MockNode, csv_file: &Path, batch_options: &BatchOptions) -> Result<()> {
+        run(
+            node,
+            csv_file,
+            batch_options,
+            &private_key(),
+            options(),
+            &wait_options(),
+            true,
+        )
+        .await
+    }
+
+    fn statuses(batch_options: &BatchOptions) -> Vec<Status> {
+        let results = fs::read_to_string(batch_options.results_file.as_ref().unwrap()).unwrap();
+        serde_json::from_str::<Vec<RowResult>>(&results)
+            .unwrap()
+            .iter()
+            .map(|result| result.status)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn send_with_consecutive_sequence_numbers() {
+        let (csv_file, mut batch_options) = batch_file("sent", "address,amount\n0x3,1\n0x4,2.5\n");
+        batch_options.concurrency = 2;
+        let node = mock_node();
+        send(&node, &csv_file, &batch_options).await.unwrap();
+        assert_eq!(statuses(&batch_options), [Status::Success, Status::Success]);
+        let mut sent = node
+            .submitted()
+            .iter()
+            .map(|signed_trans| {
+                let call = entry_function(signed_trans);
+                (
+                    signed_trans.sequence_number(),
+                    arg::<AccountAddress>(&call, 0),
+                    arg::<u64>(&call, 1),
+                )
+            })
+            .collect::<Vec<_>>();
+        sent.sort();
+        assert_eq!(
+            sent,
+            [
+                (0, address("0x3"), 1_000_000),
+                (1, address("0x4"), 2_500_000)
+            ]
+        );
+
+        // The rows sent are skipped on the next run
+        let node = mock_node();
+        send(&node, &csv_file, &batch_options).await.unwrap();
+        assert!(node.submitted().is_empty());
+    }
+
+    #[tokio::test]
+    async fn stop_at_the_first_failure() {
+        let (csv_file, mut batch_options) = batch_file("aborted", "0x3,1\n0x4,2\n");
+        let node = mock_node().with_route(TRANSACTION, 200, aborted_transaction());
+        let err = send(&node, &csv_file, &batch_options).await.unwrap_err();
+        assert!(
+            format!("{err:#}").contains("1 transfer(s) failed"),
+            "{err:#}"
+        );
+        assert_eq!(statuses(&batch_options), [Status::Failed, Status::NotSent]);
+        assert_eq!(node.submitted().len(), 1);
+
+        batch_options.continue_on_error = true;
+        let node = mock_node().with_route(TRANSACTION, 200, aborted_transaction());
+        send(&node, &csv_file, &batch_options).await.unwrap_err();
+        assert_eq!(statuses(&batch_options), [Status::Failed, Status::Failed]);
+        assert_eq!(node.submitted().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn check_every_row_before_sending() {
+        let (csv_file, batch_options) = batch_file("invalid", "0x3,0\n0x4,x\n0x5,1\n");
+        let node = mock_node();
+        let err = format!(
+            "{:#}",
+            send(&node, &csv_file, &batch_options).await.unwrap_err()
+        );
+        assert!(
+            err.contains("row 1: the amount must be greater than zero"),
+            "{err}"
+        );
+        assert!(err.contains("row 2"), "{err}");
+        assert!(!err.contains("row 3"), "{err}");
+        assert!(node.submitted().is_empty());
+    }
+}
@@ -0,0 +1,228 @@
+use super::{prompt, submit_transaction, WaitOptions};
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+use txs::{
+    crypto::HashValue,
+    extension::{
+        client_ext::{ClientExt, TransactionOptions},
+        signed_transaction_ext::SignedTransactionExt,
+    },
+    node_api::NodeApi,
+    rest_client::{
+        aptos_api_types::{Transaction, TransactionData},
+        error::RestError,
+    },
+    retry::retry_read,
+    signer::TransactionSigner,
+    types::transaction::{authenticator::TransactionAuthenticator, SignedTransaction},
+    util::{format_address, transaction_hash},
+};
+
+/// The stuck transaction, looked up in the mempool or read from its signed file
+pub enum Original<'a> {
+    Hash(&'a str),
+    File(&'a Path),
+}
+
+/// Replace the original with the same payload and sequence number at a higher gas unit price,
+/// which the mempool takes in its place as long as the original is not committed
+pub async fn run(
+    client: &impl NodeApi,
+    original: Original<'_>,
+    gas_unit_price: u64,
+    signer: &TransactionSigner,
+    options: TransactionOptions,
+    wait_options: &WaitOptions,
+    yes: bool,
+) -> Result<()> {
+    let original = match original {
+        Original::Hash(hash) => pending_transaction(client, hash).await?,
+        Original::File(path) => SignedTransaction::load(path)?,
+    };
+    let original_hash = transaction_hash(&original);
+    if gas_unit_price <= original.gas_unit_price() {
+        bail!(
+            "The replacement must pay more than the {} per gas unit of the original, --gas-unit-price is {gas_unit_price}",
+            original.gas_unit_price()
+        );
+    }
+    if !matches!(
+        original.authenticator(),
+        TransactionAuthenticator::Ed25519 { .. }
+    ) {
+        bail!("Only transactions signed by the sender alone can be bumped");
+    }
+    check_not_committed(client, &original, original_hash).await?;
+
+    let options = TransactionOptions {
+        max_gas_amount: Some(original.max_gas_amount()),
+        gas_unit_price: Some(gas_unit_price),
+        sequence_number: Some(original.sequence_number()),
+        ..options
+    };
+    let replacement = signer
+        .sign_payload(client, original.payload().clone(), options)
+        .await?;
+    if replacement.sender() != original.sender() {
+        bail!(
+            "The key signs for {}, the original was sent by {}",
+            format_address(&replacement.sender()),
+            format_address(&original.sender())
+        );
+    }
+
+    println!("Original transaction: {}", original_hash.to_hex_literal());
+    println!(
+        "Replacement transaction: {}",
+        transaction_hash(&replacement).to_hex_literal()
+    );
+    prompt::confirm_transaction(
+        &replacement,
+        &[
+            ("Replaces", original_hash.to_hex_literal()),
+            (
+                "Gas unit price",
+                format!("{gas_unit_price}, up from {}", original.gas_unit_price()),
+            ),
+        ],
+        yes,
+    )?;
+    submit_transaction::run(client, &replacement, wait_options).await
+}
+
+async fn pending_transaction(client: &impl NodeApi, hash: &str) -> Result<SignedTransaction> {
+    let hash = HashValue::from_hex(hash.trim().trim_start_matches("0x"))
+        .context(format!("Invalid transaction hash: {hash}"))?;
+    match retry_read(|| client.get_transaction_by_hash_bcs(hash)).await {
+        Ok(response) => match response.into_inner() {
+            TransactionData::Pending(signed_trans) => Ok(*signed_trans),
+            TransactionData::OnChain(_) => bail!(
+                "Transaction {} is already committed, there is nothing to bump",
+                hash.to_hex_literal()
+            ),
+        },
+        Err(RestError::Api(err)) if err.status_code.as_u16() == 404 => bail!(
+            "Transaction {} is not in the mempool of the node, it may have expired. Pass its signed file with --file instead",
+            hash.to_hex_literal()
+        ),
+        Err(err) => Err(err).context(format!(
+            "Failed to get the transaction {}",
+            hash.to_hex_literal()
+        )),
+    }
+}
+
+/// A replacement only makes sense while the sequence number of the original is unused
+async fn check_not_committed(
+    client: &impl NodeApi,
+    original: &SignedTransaction,
+    original_hash: HashValue,
+) -> Result<()> {
+    match client.get_transaction_by_hash(original_hash).await {
+        Ok(response) if !matches!(response.inner(), Transaction::PendingTransaction(_)) => bail!(
+            "Transaction {} is already committed, there is nothing to bump",
+            original_hash.to_hex_literal()
+        ),
+        Ok(_) => {}
+        // No longer in the mempool, the replacement can still use its sequence number
+        Err(RestError::Api(err)) if err.status_code.as_u16() == 404 => {}
+        Err(err) => {
+            return Err(err).context(format!(
+                "Failed to get the transaction {}",
+                original_hash.to_hex_literal()
+            ))
+        }
+    }
+    let sequence_number = client.get_sequence_number(original.sender()).await?;
+    if sequence_number > original.sequence_number() {
+        bail!(
+            "Sequence number {} of {} is already used on-chain, by the original or by another transaction",
+            original.sequence_number(),
+            format_address(&original.sender())
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        at_sequence_number, fixture_body, mock_node, options, private_key, signed_transfer,
+        submitted, wait_options, SUBMIT, TRANSACTION,
+    };
+    use serde_json::json;
+    use txs::node_api::MockNode;
+
+    const BCS_TRANSACTION: &str = "GET /bcs/transactions/by_hash/*";
+
+    /// The transfer of the fixtures, waiting in the mempool
+    async fn stuck_node() -> MockNode {
+        let node = mock_node();
+        let pending = TransactionData::Pending(Box::new(signed_transfer(&node).await));
+        let mut pending_transaction = fixture_body(SUBMIT);
+        pending_transaction["type"] = json!("pending_transaction");
+        node.with_route(BCS_TRANSACTION, 200, serde_json::to_value(pending).unwrap())
+            .with_route(TRANSACTION, 200, pending_transaction)
+    }
+
+    async fn bump(node: &MockNode, gas_unit_price: u64) -> Result<()> {
+        // The replacement is stuck too, it is not waited for
+        let wait_options = WaitOptions {
+            no_wait: true,
+            ..wait_options()
+        };
+        run(
+            node,
+            Original::Hash("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+            gas_unit_price,
+            &TransactionSigner::PrivateKey(private_key()),
+            options(),
+            &wait_options,
+            true,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn replace_at_a_higher_price() {
+        let node = stuck_node().await;
+        let original = signed_transfer(&node).await;
+        bump(&node, 150).await.unwrap();
+        let replacement = submitted(&node);
+        assert_eq!(replacement.payload(), original.payload());
+        assert_eq!(replacement.sequence_number(), original.sequence_number());
+        assert_eq!(replacement.max_gas_amount(), original.max_gas_amount());
+        assert_eq!(replacement.gas_unit_price(), 150);
+    }
+
+    #[tokio::test]
+    async fn only_pay_more() {
+        let node = stuck_node().await;
+        let err = bump(&node, 100).await.unwrap_err();
+        assert!(format!("{err:#}").contains("must pay more"), "{err:#}");
+        assert!(node.submitted().is_empty());
+    }
+
+    #[tokio::test]
+    async fn leave_a_committed_original() {
+        let err = bump(&mock_node(), 150).await.unwrap_err();
+        assert!(format!("{err:#}").contains("not in the mempool"), "{err:#}");
+
+        // The transaction of the fixtures is committed
+        let node = stuck_node()
+            .await
+            .with_route(TRANSACTION, 200, fixture_body(TRANSACTION));
+        let err = bump(&node, 150).await.unwrap_err();
+        assert!(format!("{err:#}").contains("already committed"), "{err:#}");
+
+        // Another transaction used the sequence number
+        let node = at_sequence_number(stuck_node().await, 1);
+        let err = bump(&node, 150).await.unwrap_err();
+        assert!(
+            format!("{err:#}").contains("already used on-chain"),
+            "{err:#}"
+        );
+        assert!(node.submitted().is_empty());
+    }
+}
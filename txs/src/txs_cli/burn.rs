@@ -0,0 +1,228 @@
+use super::{submit_transaction, WaitOptions};
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use txs::{
+    constant::NATIVE_COIN_DECIMALS,
+    crypto::ed25519::Ed25519PrivateKey,
+    extension::{client_ext::TransactionOptions, ed25519_private_key_ext::Ed25519PrivateKeyExt},
+    node_api::NodeApi,
+    retry::retry_read,
+    types::account_address::AccountAddress,
+    util::{
+        entry_function_payload, format_address, format_decimal_amount, format_output,
+        parse_address, OutputFormat,
+    },
+};
+
+const PREFERENCE_TYPE: &str = "0x1::burn::UserBurnPreference";
+const TRACKER_TYPE: &str = "0x1::ol_account::BurnTracker";
+const SET_PREFERENCE_FUNCTION: &str = "0x1::burn::set_send_community";
+
+/// Whether the burns of the account are recycled to the match index of the community wallets or
+/// burned, with its lifetime burn figures from its burn tracker
+pub async fn preference(
+    client: &impl NodeApi,
+    account: &str,
+    format: OutputFormat,
+) -> Result<String> {
+    let account = parse_address(account, "--account")?;
+    let preference = get_preference(client, account).await?;
+    if format != OutputFormat::Text {
+        return format_output(&preference, format);
+    }
+
+    let amount = |key: &str| match preference[key].as_u64() {
+        Some(amount) => format!(
+            "{} coins ({amount} base units)",
+            format_decimal_amount(amount, NATIVE_COIN_DECIMALS)
+        ),
+        None => "unknown".to_string(),
+    };
+    Ok([
+        format!("Account: {}", format_address(&account)),
+        format!("Burn preference: {}", describe(&preference["recycle"])),
+        format!("Lifetime burned: {}", amount("lifetime_burned")),
+        format!(
+            "Burned at the last calculation: {}",
+            amount("burn_at_last_calc")
+        ),
+        format!(
+            "Balance at the last calculation: {}",
+            amount("prev_balance")
+        ),
+    ]
+    .join("\n"))
+}
+
+/// Set whether the burns of the signer are recycled to the match index or burned, with
+/// 0x1::burn::set_send_community
+pub async fn set_preference(
+    client: &impl NodeApi,
+    recycle: bool,
+    private_key: &Ed25519PrivateKey,
+    options: TransactionOptions,
+    wait_options: &WaitOptions,
+    yes: bool,
+) -> Result<()> {
+    let mut from_account = private_key.get_account(client, None).await?;
+    // The current one is only shown in the confirmation, an unreadable one does not stop the change
+    let current = get_preference(client, from_account.address())
+        .await
+        .map_or(Value::Null, |preference| preference["recycle"].to_owned());
+
+    let payload = entry_function_payload(
+        SET_PREFERENCE_FUNCTION,
+        vec![],
+        vec![bcs::to_bytes(&recycle)?],
+    )?;
+    let details = [
+        ("Function", SET_PREFERENCE_FUNCTION.to_string()),
+        ("Current preference", describe(&current)),
+        ("New preference", describe(&json!(recycle))),
+    ];
+    submit_transaction::sign_and_submit(
+        client,
+        &mut from_account,
+        payload,
+        options,
+        &details,
+        wait_options,
+        yes,
+    )
+    .await
+}
+
+/// The preference of the account, false when it never set one since the framework then burns,
+/// and the figures of its burn tracker in base units. None where the account has no tracker
+async fn get_preference(client: &impl NodeApi, account: AccountAddress) -> Result<Value> {
+    let resource = |resource_type: &'static str| async move {
+        retry_read(|| client.get_account_resource(account, resource_type))
+            .await
+            .context(format!(
+                "Failed to get resource {resource_type} of {}",
+                format_address(&account)
+            ))
+            .map(|response| response.into_inner().map(|resource| resource.data))
+    };
+    let recycle = resource(PREFERENCE_TYPE)
+        .await?
+        .map_or(false, |preference| {
+            preference["send_community"] == json!(true)
+        });
+    let tracker = resource(TRACKER_TYPE).await?.unwrap_or_default();
+    let figure = |field: &str| {
+        tracker[field]
+            .as_str()
+            .and_then(|figure| figure.parse::<u64>().ok())
+    };
+    Ok(json!({
+        "account": format_address(&account),
+        "recycle": recycle,
+        "lifetime_burned": figure("cumu_burn"),
+        "burn_at_last_calc": figure("burn_at_last_calc"),
+        "prev_balance": figure("prev_balance"),
+    }))
+}
+
+fn describe(recycle: &Value) -> String {
+    match recycle.as_bool() {
+        Some(true) => "recycle, to the match index of the community wallets".to_string(),
+        Some(false) => "burn".to_string(),
+        None => "unknown".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        arg, function_id, mock_node, options, private_key, submitted_call, wait_options,
+    };
+    use txs::node_api::MockNode;
+
+    const PREFERENCE: &str = "GET /accounts/*/resource/0x1::burn::UserBurnPreference";
+    const TRACKER: &str = "GET /accounts/*/resource/0x1::ol_account::BurnTracker";
+
+    fn burning_node() -> MockNode {
+        mock_node()
+            .with_route(
+                PREFERENCE,
+                200,
+                json!({ "type": PREFERENCE_TYPE, "data": { "send_community": true } }),
+            )
+            .with_route(
+                TRACKER,
+                200,
+                json!({
+                    "type": TRACKER_TYPE,
+                    "data": {
+                        "cumu_burn": "2000000",
+                        "burn_at_last_calc": "500000",
+                        "prev_balance": "1000000",
+                    },
+                }),
+            )
+    }
+
+    #[tokio::test]
+    async fn show_the_preference_and_the_tracker() {
+        let output = preference(&burning_node(), "0x2", OutputFormat::Text)
+            .await
+            .unwrap();
+        assert!(output.contains("Burn preference: recycle"), "{output}");
+        assert!(
+            output.contains("Lifetime burned: 2 coins (2000000 base units)"),
+            "{output}"
+        );
+        assert!(
+            output.contains("Burned at the last calculation: 0.5 coins"),
+            "{output}"
+        );
+    }
+
+    #[tokio::test]
+    async fn burn_when_no_preference_was_set() {
+        let node = mock_node()
+            .with_route(PREFERENCE, 200, Value::Null)
+            .with_route(TRACKER, 200, Value::Null);
+        let output = preference(&node, "0x2", OutputFormat::Json).await.unwrap();
+        let output = serde_json::from_str::<Value>(&output).unwrap();
+        assert_eq!(output["recycle"], json!(false));
+        assert_eq!(output["lifetime_burned"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn set_the_preference() {
+        let node = burning_node();
+        set_preference(
+            &node,
+            false,
+            &private_key(),
+            options(),
+            &wait_options(),
+            true,
+        )
+        .await
+        .unwrap();
+        let call = submitted_call(&node);
+        assert_eq!(function_id(&call), SET_PREFERENCE_FUNCTION);
+        assert!(!arg::<bool>(&call, 0));
+    }
+
+    #[tokio::test]
+    async fn set_the_preference_without_reading_it() {
+        // The current preference is only shown, the change goes ahead when it cannot be read
+        let node = mock_node();
+        set_preference(
+            &node,
+            true,
+            &private_key(),
+            options(),
+            &wait_options(),
+            true,
+        )
+        .await
+        .unwrap();
+        assert!(arg::<bool>(&submitted_call(&node), 0));
+    }
+}
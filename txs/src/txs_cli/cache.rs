@@ -0,0 +1,20 @@
+use anyhow::Result;
+use txs::abi_cache;
+
+#[derive(clap::Subcommand)]
+pub enum CacheCommand {
+    /// Remove every cached module, they are fetched from the node again when needed
+    Clear,
+}
+
+pub fn run(command: &CacheCommand) -> Result<()> {
+    match command {
+        CacheCommand::Clear => {
+            match abi_cache::clear()? {
+                Some(folder) => println!("Removed the ABI cache {}", folder.display()),
+                None => println!("The ABI cache is already empty"),
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use super::{prompt, submit_transaction, transfer_coin, WaitOptions};
+use anyhow::{bail, Context, Result};
+use txs::{
+    crypto::ed25519::Ed25519PrivateKey,
+    extension::{
+        client_ext::{ClientExt, TransactionOptions},
+        ed25519_private_key_ext::Ed25519PrivateKeyExt,
+    },
+    node_api::NodeApi,
+    rest_client::aptos_api_types::Transaction,
+    types::account_address::AccountAddress,
+    util::{entry_function_payload, parse_address},
+};
+
+/// Propose a payment from a community wallet, as one of its authorities
+#[allow(clippy::too_many_arguments)]
+pub async fn propose_transfer(
+    client: &impl NodeApi,
+    wallet: &str,
+    recipient: &str,
+    amount: &str,
+    description: &str,
+    private_key: &Ed25519PrivateKey,
+    options: TransactionOptions,
+    wait_options: &WaitOptions,
+    yes: bool,
+) -> Result<()> {
+    let mut from_account = private_key.get_account(client, None).await?;
+    let wallet = parse_address(wallet, "--wallet")?;
+    let recipient = parse_address(recipient, "--recipient")?;
+
+    let authorities = get_authorities(client, wallet).await?;
+    if !authorities.contains(&from_account.address()) {
+        bail!(
+            "{} is not an authority of the community wallet {}",
+            from_account.address().to_hex_literal(),
+            wallet.to_hex_literal()
+        );
+    }
+
+    let decimals = client
+        .get_coin_decimals(&transfer_coin::native_or(None)?)
+        .await?;
+    let base_amount = transfer_coin::parse_amount(amount, Some(decimals))?;
+    let function_id = "0x1::donor_voice_txs::propose_payment_tx";
+    let payload = entry_function_payload(
+        function_id,
+        vec![],
+        vec![
+            bcs::to_bytes(&wallet)?,
+            bcs::to_bytes(&recipient)?,
+            bcs::to_bytes(&base_amount)?,
+            bcs::to_bytes(description.as_bytes())?,
+        ],
+    )?;
+
+    let signed_trans = client
+        .sign_payload(&mut from_account, payload, options)
+        .await?;
+    prompt::confirm_transaction(
+        &signed_trans,
+        &[
+            ("Function", function_id.to_string()),
+            ("Community wallet", wallet.to_hex_literal()),
+            ("Recipient", recipient.to_hex_literal()),
+            (
+                "Amount",
+                transfer_coin::describe_amount(base_amount, Some(decimals)),
+            ),
+            ("Description", description.to_string()),
+        ],
+        yes,
+    )?;
+    if let Some(transaction) =
+        submit_transaction::submit(client, &signed_trans, wait_options).await?
+    {
+        match proposal_id(&transaction) {
+            Some(id) => println!("Proposal id: {id}"),
+            None => println!("The proposal id was not found in the events of the transaction"),
+        }
+    }
+    Ok(())
+}
+
+/// Veto a proposed payment from a community wallet, as one of its donors
+pub async fn veto(
+    client: &impl NodeApi,
+    wallet: &str,
+    proposal_id: u64,
+    private_key: &Ed25519PrivateKey,
+    options: TransactionOptions,
+    wait_options: &WaitOptions,
+    yes: bool,
+) -> Result<()> {
+    let mut from_account = private_key.get_account(client, None).await?;
+    let wallet = parse_address(wallet, "--wallet")?;
+    let function_id = "0x1::donor_voice_txs::propose_veto_tx";
+    let payload = entry_function_payload(
+        function_id,
+        vec![],
+        vec![bcs::to_bytes(&wallet)?, bcs::to_bytes(&proposal_id)?],
+    )?;
+    submit_transaction::sign_and_submit(
+        client,
+        &mut from_account,
+        payload,
+        options,
+        &[
+            ("Function", function_id.to_string()),
+            ("Community wallet", wallet.to_hex_literal()),
+            ("Proposal id", proposal_id.to_string()),
+        ],
+        wait_options,
+        yes,
+    )
+    .await
+}
+
+async fn get_authorities(
+    client: &impl NodeApi,
+    wallet: AccountAddress,
+) -> Result<Vec<AccountAddress>> {
+    let result = client
+        .view_ext(
+            "0x1::multi_action::get_authorities",
+            None,
+            Some(wallet.to_hex_literal()),
+        )
+        .await
+        .context(format!(
+            "Failed to get the authorities of {}, is it a community wallet?",
+            wallet.to_hex_literal()
+        ))?;
+    result
+        .first()
+        .and_then(|v| v.as_array())
+        .context("Invalid authorities returned by the node")?
+        .iter()
+        .map(|v| {
+            AccountAddress::from_hex_literal(v.as_str().unwrap_or_default())
+                .context("Invalid authority address returned by the node")
+        })
+        .collect()
+}
+
+/// The id of the proposal, from the event the multi action module emits for it
+fn proposal_id(transaction: &Transaction) -> Option<u64> {
+    let events = match transaction {
+        Transaction::UserTransaction(transaction) => &transaction.events,
+        _ => return None,
+    };
+    events
+        .iter()
+        .filter(|event| {
+            let event_type = event.typ.to_string();
+            event_type.contains("multi_action") || event_type.contains("donor_voice")
+        })
+        .find_map(|event| {
+            let id = &event.data["proposal_id"];
+            let id = if id.is_null() { &event.data["id"] } else { id };
+            id.as_str().and_then(|id| id.parse().ok())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        address, arg, fixture_body, function_id, mock_node, options, private_key, sender,
+        submitted_call, wait_options, TRANSACTION, VIEW,
+    };
+    use serde_json::{json, Value};
+    use txs::node_api::MockNode;
+
+    /// A community wallet the account of `private_key` is an authority of
+    fn wallet_node() -> MockNode {
+        mock_node().with_route(VIEW, 200, json!([[sender().to_hex_literal()]]))
+    }
+
+    async fn propose(node: &MockNode) -> Result<()> {
+        propose_transfer(
+            node,
+            "0x4",
+            "0x3",
+            "1.5",
+            "A grant",
+            &private_key(),
+            options(),
+            &wait_options(),
+            true,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn propose_a_payment() {
+        let node = wallet_node();
+        propose(&node).await.unwrap();
+        let request = &node.view_requests()[0];
+        assert_eq!(
+            request.function.to_string(),
+            "0x1::multi_action::get_authorities"
+        );
+        assert_eq!(request.arguments, [json!("0x4")]);
+
+        let call = submitted_call(&node);
+        assert_eq!(
+            function_id(&call),
+            "0x1::donor_voice_txs::propose_payment_tx"
+        );
+        assert_eq!(arg::<AccountAddress>(&call, 0), address("0x4"));
+        assert_eq!(arg::<AccountAddress>(&call, 1), address("0x3"));
+        assert_eq!(arg::<u64>(&call, 2), 1_500_000);
+        assert_eq!(arg::<Vec<u8>>(&call, 3), b"A grant");
+    }
+
+    #[tokio::test]
+    async fn only_authorities_propose() {
+        let node = mock_node().with_route(VIEW, 200, json!([["0x5"]]));
+        let err = propose(&node).await.unwrap_err();
+        assert!(
+            format!("{err:#}").contains("is not an authority"),
+            "{err:#}"
+        );
+        assert!(node.submitted().is_empty());
+    }
+
+    #[tokio::test]
+    async fn veto_a_payment() {
+        let node = mock_node();
+        veto(
+            &node,
+            "0x4",
+            3,
+            &private_key(),
+            options(),
+            &wait_options(),
+            true,
+        )
+        .await
+        .unwrap();
+        let call = submitted_call(&node);
+        assert_eq!(function_id(&call), "0x1::donor_voice_txs::propose_veto_tx");
+        assert_eq!(arg::<AccountAddress>(&call, 0), address("0x4"));
+        assert_eq!(arg::<u64>(&call, 1), 3);
+    }
+
+    #[test]
+    fn find_the_proposal_id_in_the_events() {
+        let event = |event_type: &str, data: Value| {
+            json!({
+                "guid": { "creation_number": "0", "account_address": "0x4" },
+                "sequence_number": "0",
+                "type": event_type,
+                "data": data,
+            })
+        };
+        let mut transaction = fixture_body(TRANSACTION);
+        transaction["events"] = json!([
+            event("0x1::coin::DepositEvent", json!({ "amount": "7" })),
+            event(
+                "0x1::multi_action::ProposalCreated",
+                json!({ "proposal_id": "12" })
+            ),
+        ]);
+        let transaction = serde_json::from_value::<Transaction>(transaction).unwrap();
+        assert_eq!(proposal_id(&transaction), Some(12));
+
+        let transaction = serde_json::from_value::<Transaction>(fixture_body(TRANSACTION)).unwrap();
+        assert_eq!(proposal_id(&transaction), None);
+    }
+}
@@ -0,0 +1,96 @@
+use super::TxsCli;
+use anyhow::Result;
+use clap::CommandFactory;
+use clap_complete::{generate, Shell};
+use libra_config::extension::cli_config_ext::load_profiles;
+use txs::address_book::{AddressBook, NAME_PREFIX};
+
+/// Values only known at runtime, listed for the completion scripts by `txs __complete`
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum CompletionValues {
+    Profiles,
+    Aliases,
+}
+
+/// Completes --profile with the profiles of the config, and words starting with @ with the
+/// names of the address book, before falling back to the completion generated by clap
+const BASH_DYNAMIC: &str = r#"
+_txs_dynamic() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    local prev="${COMP_WORDS[COMP_CWORD-1]}"
+    if [[ "$prev" == "--profile" ]]; then
+        COMPREPLY=($(compgen -W "$(txs __complete profiles 2>/dev/null)" -- "$cur"))
+    elif [[ "$cur" == @* ]]; then
+        COMPREPLY=($(compgen -W "$(txs __complete aliases 2>/dev/null)" -- "$cur"))
+    else
+        _txs "$@"
+    fi
+}
+complete -F _txs_dynamic -o bashdefault -o default txs
+"#;
+
+const ZSH_DYNAMIC: &str = r#"
+_txs_dynamic() {
+    if [[ "${words[CURRENT-1]}" == "--profile" ]]; then
+        compadd -- ${(f)"$(txs __complete profiles 2>/dev/null)"}
+    elif [[ "${words[CURRENT]}" == @* ]]; then
+        compadd -- ${(f)"$(txs __complete aliases 2>/dev/null)"}
+    else
+        _txs "$@"
+    fi
+}
+"#;
+
+const FISH_DYNAMIC: &str = r#"
+complete -c txs -l profile -x -a "(txs __complete profiles 2>/dev/null)"
+complete -c txs -n "string match -q -- '@*' (commandline -ct)" -x -a "(txs __complete aliases 2>/dev/null)"
+"#;
+
+/// Print the completion script of the shell. Bash, zsh and fish also complete profile names
+/// and address book names at runtime, powershell only completes subcommands and flags
+pub fn run(shell: Shell) -> Result<()> {
+    let mut command = TxsCli::command();
+    let mut script = vec![];
+    generate(shell, &mut command, "txs", &mut script);
+    let script = String::from_utf8(script)?;
+    let script = match shell {
+        Shell::Bash => script + BASH_DYNAMIC,
+        // The generated script calls `_txs` on load, which has to go through the dynamic
+        // completion defined before it
+        Shell::Zsh => script
+            .replacen('\n', &format!("\n{ZSH_DYNAMIC}"), 1)
+            .replace("    _txs \"$@\"\nelse", "    _txs_dynamic \"$@\"\nelse")
+            .replace("compdef _txs txs", "compdef _txs_dynamic txs"),
+        Shell::Fish => script + FISH_DYNAMIC,
+        _ => script,
+    };
+    print!("{script}");
+    Ok(())
+}
+
+/// Print the completion candidates one per line. Errors print nothing, so a broken config
+/// never garbles the completion
+pub fn complete(values: CompletionValues) {
+    for candidate in candidates(values) {
+        println!("{candidate}");
+    }
+}
+
+/// The profile names, or the address book names with their @ prefix. Empty when they cannot be
+/// read
+pub fn candidates(values: CompletionValues) -> Vec<String> {
+    match values {
+        CompletionValues::Profiles => load_profiles()
+            .map(|profiles| profiles.into_keys().collect())
+            .unwrap_or_default(),
+        CompletionValues::Aliases => AddressBook::load()
+            .and_then(|book| book.entries())
+            .map(|entries| {
+                entries
+                    .into_iter()
+                    .map(|(name, _)| format!("{NAME_PREFIX}{name}"))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
@@ -1,10 +1,21 @@
-use anyhow::{Context, Result};
-use libra_config::extension::faucet_client_ext::FaucetClientExt;
-use txs::{rest_client::FaucetClient, types::account_address::AccountAddress};
+use super::{prompt, submit_transaction, WaitOptions};
+use anyhow::{bail, Context, Result};
+use txs::{
+    client::create_account_payload,
+    crypto::ed25519::Ed25519PrivateKey,
+    extension::{
+        client_ext::{ClientExt, TransactionOptions},
+        ed25519_private_key_ext::Ed25519PrivateKeyExt,
+    },
+    node_api::NodeApi,
+    rest_client::{error::RestError, FaucetClient},
+    retry::retry_read,
+    types::account_address::AccountAddress,
+    util::{format_address, parse_address},
+};
 
-pub async fn run(account_address: &str, coins: u64) -> Result<()> {
-    let faucet_client = FaucetClient::default()?;
-    let account_address = AccountAddress::from_hex_literal(account_address)?;
+pub async fn run(faucet_client: &FaucetClient, account_address: &str, coins: u64) -> Result<()> {
+    let account_address = parse_address(account_address, "--account-address")?;
 
     if coins == 0 {
         faucet_client
@@ -12,15 +23,132 @@ pub async fn run(account_address: &str, coins: u64) -> Result<()> {
             .await
             .context(format!(
                 "Failed to create account {}",
-                account_address.to_hex_literal()
+                format_address(&account_address)
             ))?;
     } else {
         faucet_client
             .fund(account_address, coins)
             .await
-            .context(format!("Failed to create account {}", account_address))?;
+            .context(format!(
+                "Failed to create account {}",
+                format_address(&account_address)
+            ))?;
     }
 
     println!("Success!");
     Ok(())
 }
+
+/// Create the account by transferring `coins` base units from the funder, for networks without
+/// a faucet. Once committed, the account must exist with at least the coins transferred
+pub async fn run_with_funder(
+    client: &impl NodeApi,
+    funder_private_key: &Ed25519PrivateKey,
+    account_address: &str,
+    coins: u64,
+    options: TransactionOptions,
+    wait_options: &WaitOptions,
+    yes: bool,
+) -> Result<()> {
+    let account_address = parse_address(account_address, "--account-address")?;
+    let balance_before = balance(client, account_address).await?;
+
+    let mut funder = funder_private_key.get_account(client, None).await?;
+    let payload = create_account_payload(account_address, coins)?;
+    let signed_trans = client.sign_payload(&mut funder, payload, options).await?;
+    prompt::confirm_transaction(
+        &signed_trans,
+        &[
+            ("New account", format_address(&account_address)),
+            ("Funding", format!("{coins} base units")),
+        ],
+        yes,
+    )?;
+    if submit_transaction::submit(client, &signed_trans, wait_options)
+        .await?
+        .is_none()
+    {
+        return Ok(());
+    }
+
+    let balance_after = balance(client, account_address).await?.context(format!(
+        "The transaction is committed but the account {} does not exist",
+        format_address(&account_address)
+    ))?;
+    let expected = balance_before.unwrap_or_default().saturating_add(coins);
+    if balance_after < expected {
+        bail!(
+            "The account {} has a balance of {balance_after} base units, expected at least {expected}",
+            format_address(&account_address)
+        );
+    }
+    println!(
+        "Account {} exists with a balance of {balance_after} base units",
+        format_address(&account_address)
+    );
+    Ok(())
+}
+
+/// The balance of the native coin, None when the account does not exist
+pub async fn balance(
+    client: &impl NodeApi,
+    account_address: AccountAddress,
+) -> Result<Option<u64>> {
+    match retry_read(|| client.get_account_balance(account_address)).await {
+        Ok(response) => Ok(Some(response.into_inner())),
+        Err(RestError::Api(err)) if err.status_code.as_u16() == 404 => Ok(None),
+        Err(err) => Err(err).context(format!(
+            "Failed to get the balance of {}",
+            format_address(&account_address)
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        address, arg, function_id, mock_node, options, private_key, submitted_call, wait_options,
+    };
+    use txs::node_api::MockNode;
+
+    async fn create(node: &MockNode, account_address: &str, coins: u64) -> Result<()> {
+        run_with_funder(
+            node,
+            &private_key(),
+            account_address,
+            coins,
+            options(),
+            &wait_options(),
+            true,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn fund_the_new_account() {
+        // 0x2 holds 1000 base units in the fixtures
+        let node = mock_node();
+        create(&node, "0x2", 0).await.unwrap();
+        let call = submitted_call(&node);
+        assert_eq!(function_id(&call), "0x1::aptos_account::transfer");
+        assert_eq!(arg::<AccountAddress>(&call, 0), address("0x2"));
+        assert_eq!(arg::<u64>(&call, 1), 0);
+    }
+
+    #[tokio::test]
+    async fn check_the_balance_once_committed() {
+        // The balances of the fixtures do not change once the transaction is committed
+        let err = create(&mock_node(), "0x2", 10).await.unwrap_err();
+        assert!(
+            format!("{err:#}").contains("a balance of 1000 base units, expected at least 1010"),
+            "{err:#}"
+        );
+
+        // A missing account is not an error, until it is still missing after the commit
+        let node = mock_node();
+        let err = create(&node, "0x5", 10).await.unwrap_err();
+        assert_eq!(arg::<u64>(&submitted_call(&node), 1), 10);
+        assert!(format!("{err:#}").contains("does not exist"), "{err:#}");
+    }
+}
@@ -1,123 +1,238 @@
-use anyhow::{Context, Result};
-use libra_config::extension::{client_ext::ClientExt, faucet_client_ext::FaucetClientExt};
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use std::future::Future;
 use txs::{
-    coin_client::CoinClient,
-    crypto::ed25519::Ed25519PrivateKey,
-    rest_client::{Client, FaucetClient},
-    types::{AccountKey, LocalAccount},
+    client::TxsClient,
+    crypto::{ed25519::Ed25519PrivateKey, HashValue},
+    extension::{client_ext::TransactionOptions, ed25519_private_key_ext::Ed25519PrivateKeyExt},
+    node_api::NodeApi,
+    rest_client::{aptos_api_types::Transaction, FaucetClient},
+    types::{account_address::AccountAddress, AccountKey, LocalAccount},
+    util::format_address,
 };
 
-pub async fn run() -> Result<()> {
-    let client = Client::default()?;
-    let coin_client = CoinClient::new(&client);
-    let faucet_client = FaucetClient::default()?;
-
-    // let mut alice = LocalAccount::generate(&mut rand::rngs::OsRng); // Aptos Alice
-    // 0L Alice - from pri key
-    let arr: [u8; 32] = [
-        196, 63, 87, 153, 70, 68, 235, 218, 30, 171, 254, 191, 132, 222, 247, 63, 189, 29, 60, 228,
-        66, 169, 210, 178, 244, 203, 159, 77, 167, 185, 144, 140,
-    ];
-    let pri = Ed25519PrivateKey::try_from(arr.as_ref()).unwrap();
-    println!("--- tc::main: pri: {:x?}", pri.to_bytes());
-    println!("--- tc::main: pri: {:?}", pri.to_bytes());
-    let acckey = AccountKey::from_private_key(pri);
-    println!("--- tc::main: pub: {:x?}", acckey.public_key());
-    println!("--- tc::main: aut: {:x?}", acckey.authentication_key());
-    println!(
-        "--- tc::main: add: {:x?}",
-        acckey.authentication_key().derived_address()
-    );
-    let mut alice = LocalAccount::new(acckey.authentication_key().derived_address(), acckey, 0);
-    // ol alice addr 0xfda03992f666875ddf854193fccd3e62ea111d066029490dd37c891ed9c3f880
-
-    // let bob = LocalAccount::generate(&mut rand::rngs::OsRng);
-    // 0L: Using bob with fix address instead of random
-    let derive_path = "m/44'/637'/0'/0'/0'";
-    let mnemonic_phrase =
-        "circle ship inner pact earn inflict valve retire mechanic talk mouse outer display snack dose ahead orient tooth shrimp achieve pink slam kingdom rifle";
-    let bob = LocalAccount::from_derive_path(derive_path, mnemonic_phrase, 0).unwrap();
-
-    // Print account addresses.
-    println!("\n=== Addresses ===");
-    println!("Alice: {}", alice.address().to_hex_literal());
-    println!("Bob: {}", bob.address().to_hex_literal());
-
-    // Create the accounts on chain, but only fund Alice.
-    faucet_client
-        .fund(alice.address(), 100_000_000)
-        .await
-        .context("Failed to fund Alice's account")?;
-    faucet_client
-        .create_account(bob.address())
-        .await
-        .context("Failed to fund Bob's account")?;
-
-    // Print initial balances.
-    println!("\n=== Initial Balances ===");
-    println!(
-        "Alice: {:?}",
-        coin_client
-            .get_account_balance(&alice.address())
-            .await
-            .context("Failed to get Alice's account balance")?
-    );
-    println!(
-        "Bob: {:?}",
-        coin_client
-            .get_account_balance(&bob.address())
-            .await
-            .context("Failed to get Bob's account balance")?
-    );
-
-    // Have Alice send Bob some coins.
-    let txn_hash = coin_client
-        .transfer(&mut alice, bob.address(), 1_000, None)
-        .await
-        .context("Failed to submit transaction to transfer coins")?;
-    client.wait_for_transaction(&txn_hash).await?;
-
-    // Print intermediate balances.
-    println!("\n=== Intermediate Balances ===");
-    println!(
-        "Alice: {:?}",
-        coin_client
-            .get_account_balance(&alice.address())
-            .await
-            .context("Failed to get Alice's account balance the second time")?
-    );
-    println!(
-        "Bob: {:?}",
-        coin_client
-            .get_account_balance(&bob.address())
-            .await
-            .context("Failed to get Bob's account balance the second time")?
-    );
-
-    // Have Alice send Bob some more coins.
-    let txn_hash = coin_client
-        .transfer(&mut alice, bob.address(), 1_000, None)
-        .await
-        .context("Failed to submit transaction to transfer coins")?;
-
-    client.wait_for_transaction(&txn_hash).await?;
-
-    // Print final balances.
-    println!("\n=== Final Balances ===");
-    println!(
-        "Alice: {:?}",
-        coin_client
-            .get_account_balance(&alice.address())
-            .await
-            .context("Failed to get Alice's account balance the second time")?
-    );
-    println!(
-        "Bob: {:?}",
-        coin_client
-            .get_account_balance(&bob.address())
-            .await
-            .context("Failed to get Bob's account balance the second time")?
-    );
+const STEPS: usize = 5;
 
+/// Where the sender of the demo comes from
+pub enum Sender {
+    /// A throwaway account, created and funded by the faucet with this amount
+    Fresh(u64),
+    /// The account of the key, which must hold the amount and the fee
+    Key(Ed25519PrivateKey),
+}
+
+/// Create or take the sender and the recipient, transfer `amount` between them and check the
+/// balances after it. Each step prints whether it passed, the first one to fail fails the demo.
+/// The faucet is only needed for the fresh accounts
+pub async fn run(
+    client: &impl NodeApi,
+    faucet_client: Option<&FaucetClient>,
+    sender: Sender,
+    recipient: Option<AccountAddress>,
+    amount: u64,
+) -> Result<()> {
+    let txs_client = TxsClient::connect(client).await?;
+    let mut steps = Steps::default();
+
+    let mut sender = steps
+        .run("Create the sender", async {
+            match sender {
+                Sender::Fresh(fund_amount) => {
+                    let account = fresh_account()?;
+                    faucet(faucet_client)?
+                        .fund(account.address(), fund_amount)
+                        .await
+                        .context("The faucet failed to fund the sender")?;
+                    let detail = format!(
+                        "{} funded with {fund_amount}",
+                        format_address(&account.address())
+                    );
+                    Ok((account, detail))
+                }
+                Sender::Key(private_key) => {
+                    let account = private_key.get_account(client, None).await?;
+                    let detail = format!(
+                        "{} at sequence number {}",
+                        format_address(&account.address()),
+                        account.sequence_number()
+                    );
+                    Ok((account, detail))
+                }
+            }
+        })
+        .await?;
+
+    let recipient = steps
+        .run("Create the recipient", async {
+            match recipient {
+                Some(address) => {
+                    txs_client.balance(address).await?;
+                    Ok((address, format!("{} exists", format_address(&address))))
+                }
+                None => {
+                    let address = fresh_account()?.address();
+                    faucet(faucet_client)?
+                        .create_account(address)
+                        .await
+                        .context("The faucet failed to create the recipient")?;
+                    Ok((address, format_address(&address)))
+                }
+            }
+        })
+        .await?;
+
+    let (sender_before, recipient_before) = steps
+        .run("Read the balances", async {
+            let balances = (
+                txs_client.balance(sender.address()).await?,
+                txs_client.balance(recipient).await?,
+            );
+            let detail = format!("sender {}, recipient {}", balances.0, balances.1);
+            Ok((balances, detail))
+        })
+        .await?;
+
+    let fee = steps
+        .run(&format!("Transfer {amount} to the recipient"), async {
+            let result = txs_client
+                .transfer(
+                    recipient,
+                    amount,
+                    &mut sender,
+                    TransactionOptions::default(),
+                )
+                .await?;
+            let gas_unit_price = match &result.transaction {
+                Transaction::UserTransaction(user) => u64::from(user.request.gas_unit_price),
+                _ => bail!("Transaction {} is no user transaction", result.hash),
+            };
+            let fee = result.gas_used * gas_unit_price;
+            let detail = format!(
+                "{} committed at version {}, fee {fee}",
+                result.hash.to_hex_literal(),
+                result.version
+            );
+            Ok((fee, detail))
+        })
+        .await?;
+
+    steps
+        .run("Verify the balances", async {
+            let sender_after = txs_client.balance(sender.address()).await?;
+            let recipient_after = txs_client.balance(recipient).await?;
+            if recipient_after != recipient_before + amount {
+                bail!(
+                    "The recipient holds {recipient_after}, {} was expected",
+                    recipient_before + amount
+                );
+            }
+            let expected = sender_before.saturating_sub(amount + fee);
+            if sender_after != expected {
+                bail!("The sender holds {sender_after}, {expected} was expected");
+            }
+            let detail = format!("sender {sender_after}, recipient {recipient_after}");
+            Ok(((), detail))
+        })
+        .await?;
+
+    println!("All {STEPS} steps passed");
     Ok(())
 }
+
+/// Numbers the steps and prints their outcome
+#[derive(Default)]
+struct Steps {
+    done: usize,
+}
+
+impl Steps {
+    /// The step yields its value and a line describing what it did
+    async fn run<T>(
+        &mut self,
+        name: &str,
+        step: impl Future<Output = Result<(T, String)>>,
+    ) -> Result<T> {
+        self.done += 1;
+        let number = format!("{}/{STEPS}", self.done);
+        match step.await {
+            Ok((value, detail)) => {
+                println!("{} {number} {name}: {detail}", "OK:".green().bold());
+                Ok(value)
+            }
+            Err(err) => {
+                println!("{} {number} {name}: {err:#}", "Failed:".red().bold());
+                Err(err.context(format!("Step {number} failed: {name}")))
+            }
+        }
+    }
+}
+
+/// A throwaway account with a new random key
+pub fn fresh_account() -> Result<LocalAccount> {
+    let private_key = Ed25519PrivateKey::try_from(HashValue::random().to_vec().as_slice())?;
+    let account_key = AccountKey::from_private_key(private_key);
+    let address = account_key.authentication_key().derived_address();
+    Ok(LocalAccount::new(address, account_key, 0))
+}
+
+fn faucet(faucet_client: Option<&FaucetClient>) -> Result<&FaucetClient> {
+    faucet_client.context("A faucet is needed to create the fresh accounts")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        address, arg, error_body, fixture_body, function_id, funded_node, private_key,
+        submitted_call, COIN_STORE, SIMULATE, TRANSACTION,
+    };
+    use serde_json::json;
+    use txs::node_api::MockNode;
+
+    /// The demo pays the gas the node estimates
+    fn demo_node() -> MockNode {
+        funded_node(10_000_000).with_route(SIMULATE, 200, json!([fixture_body(TRANSACTION)]))
+    }
+
+    async fn demo(node: &MockNode, sender: Sender) -> Result<()> {
+        run(node, None, sender, Some(address("0x2")), 10).await
+    }
+
+    #[tokio::test]
+    async fn transfer_then_verify_the_balances() {
+        let node = demo_node();
+        // The balances of the fixtures do not change once committed, only the last step fails
+        let err = demo(&node, Sender::Key(private_key())).await.unwrap_err();
+        let message = format!("{err:#}");
+        assert!(message.contains("Step 5/5 failed"), "{message}");
+        assert!(
+            message.contains("The recipient holds 10000000, 10000010 was expected"),
+            "{message}"
+        );
+
+        let call = submitted_call(&node);
+        assert_eq!(function_id(&call), "0x1::aptos_account::transfer_coins");
+        assert_eq!(arg::<AccountAddress>(&call, 0), address("0x2"));
+        assert_eq!(arg::<u64>(&call, 1), 10);
+    }
+
+    #[tokio::test]
+    async fn stop_at_the_failed_step() {
+        // The recipient does not exist, nothing is sent
+        let node = demo_node().with_route(
+            COIN_STORE,
+            404,
+            error_body("Resource not found", "resource_not_found"),
+        );
+        let err = demo(&node, Sender::Key(private_key())).await.unwrap_err();
+        assert!(format!("{err:#}").contains("Step 2/5 failed"), "{err:#}");
+        assert!(node.submitted().is_empty());
+
+        let node = demo_node();
+        let err = demo(&node, Sender::Fresh(1_000)).await.unwrap_err();
+        let message = format!("{err:#}");
+        assert!(message.contains("Step 1/5 failed"), "{message}");
+        assert!(message.contains("A faucet is needed"), "{message}");
+        assert!(node.submitted().is_empty());
+    }
+}
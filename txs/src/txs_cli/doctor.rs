@@ -0,0 +1,346 @@
+use anyhow::{bail, Result};
+use colored::Colorize;
+use libra_config::extension::{cli_config_ext::load_profiles, client_ext::ClientExt};
+use std::str::FromStr;
+use txs::{
+    constant::DOCTOR_MAX_LAG_SECS,
+    crypto::PrivateKey,
+    network::ledger_lag_secs,
+    node_api::NodeApi,
+    rest_client::Client,
+    types::{account_address::AccountAddress, transaction::authenticator::AuthenticationKey},
+    util::format_address,
+};
+use url::Url;
+
+/// The outcome of one check. The remediation hint is only printed on failure
+struct Check {
+    name: &'static str,
+    passed: bool,
+    /// A failed critical check makes the command fail, others only warn
+    critical: bool,
+    detail: String,
+    hint: &'static str,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: String) -> Self {
+        Self {
+            name,
+            passed: true,
+            critical: false,
+            detail,
+            hint: "",
+        }
+    }
+
+    fn fail(name: &'static str, critical: bool, detail: String, hint: &'static str) -> Self {
+        Self {
+            name,
+            passed: false,
+            critical,
+            detail,
+            hint,
+        }
+    }
+
+    fn print(&self) {
+        let status = match (self.passed, self.critical) {
+            (true, _) => "PASS".green().bold(),
+            (false, true) => "FAIL".red().bold(),
+            (false, false) => "WARN".yellow().bold(),
+        };
+        println!("[{status}] {}: {}", self.name, self.detail);
+        if !self.passed {
+            println!("       {}", self.hint);
+        }
+    }
+}
+
+/// Check the config, the key, the node and the account in turn, stopping at the first check
+/// the next ones depend on. Fails when a critical check failed
+pub async fn run(
+    rest_url: Option<Url>,
+    profile: Option<&str>,
+    expected_chain_id: Option<u8>,
+) -> Result<()> {
+    let checks = checks(rest_url, profile, expected_chain_id).await;
+    for check in &checks {
+        check.print();
+    }
+    let failed = checks
+        .iter()
+        .filter(|check| !check.passed && check.critical)
+        .count();
+    if failed > 0 {
+        bail!("{failed} critical check(s) failed");
+    }
+    println!("{}", "All critical checks passed".green().bold());
+    Ok(())
+}
+
+async fn checks(
+    rest_url: Option<Url>,
+    profile: Option<&str>,
+    expected_chain_id: Option<u8>,
+) -> Vec<Check> {
+    let mut checks = vec![];
+    let profile_name = profile.unwrap_or("default");
+
+    let profile = match load_profiles() {
+        Ok(mut profiles) => profiles.remove(profile_name),
+        Err(err) => {
+            checks.push(Check::fail(
+                "Config",
+                true,
+                format!("{err:#}"),
+                "Fix the config.yaml of the .0L folder, or create it again with `libra-config init`",
+            ));
+            return checks;
+        }
+    };
+    match &profile {
+        Some(_) => checks.push(Check::pass(
+            "Config",
+            format!("profile {profile_name} found"),
+        )),
+        None => checks.push(Check::fail(
+            "Config",
+            rest_url.is_none(),
+            format!("no profile {profile_name} in the config"),
+            "Create it with `libra-config init`, or see the profiles with `txs profile list`",
+        )),
+    }
+
+    let mut address = profile.as_ref().and_then(|profile| profile.account);
+    if let Some(private_key) = profile
+        .as_ref()
+        .and_then(|profile| profile.private_key.as_ref())
+    {
+        let public_key = private_key.public_key();
+        let derived_address = AuthenticationKey::ed25519(&public_key).derived_address();
+        let public_key_matches = profile
+            .as_ref()
+            .and_then(|profile| profile.public_key.as_ref())
+            .map_or(true, |configured| *configured == public_key);
+        if !public_key_matches {
+            checks.push(Check::fail(
+                "Key",
+                true,
+                "the private key does not match the public key of the profile".to_string(),
+                "Set the key pair of the profile again with `libra-config init`",
+            ));
+        } else if address.map_or(false, |address| address != derived_address) {
+            checks.push(Check::fail(
+                "Key",
+                false,
+                format!(
+                    "the key derives {}, not the account of the profile",
+                    format_address(&derived_address)
+                ),
+                "Expected only if the account rotated its key, otherwise fix the account of the profile",
+            ));
+        } else {
+            checks.push(Check::pass(
+                "Key",
+                format!("derives {}", format_address(&derived_address)),
+            ));
+        }
+        address = address.or(Some(derived_address));
+    } else {
+        checks.push(Check::fail(
+            "Key",
+            false,
+            "no private key in the profile".to_string(),
+            "Pass a key flag to the commands that sign, e.g. --keystore or --private-key-file",
+        ));
+    }
+
+    let url = rest_url.or_else(|| {
+        profile
+            .as_ref()
+            .and_then(|profile| profile.rest_url.as_deref())
+            .and_then(|url| Url::from_str(url).ok())
+    });
+    let Some(url) = url else {
+        checks.push(Check::fail(
+            "Node",
+            true,
+            "no REST endpoint configured".to_string(),
+            "Pass --url or --network, or set the rest_url of the profile with `libra-config init`",
+        ));
+        return checks;
+    };
+    let client = match Client::from_url(&url).await {
+        Ok(client) => client,
+        Err(err) => {
+            checks.push(Check::fail(
+                "Node",
+                true,
+                format!("{err:#}"),
+                "Check the url and the network, or use another fullnode with --url",
+            ));
+            return checks;
+        }
+    };
+    match node_checks(&client, &url, expected_chain_id).await {
+        Ok(node_checks) => checks.extend(node_checks),
+        Err(check) => {
+            checks.push(check);
+            return checks;
+        }
+    }
+
+    match address {
+        Some(address) => checks.push(account_check(&client, address).await),
+        None => checks.push(Check::fail(
+            "Account",
+            false,
+            "no account to look up".to_string(),
+            "Set the account of the profile with `libra-config init`",
+        )),
+    }
+    checks
+}
+
+/// The chain id and the sync of the node. Fails with the check of an unreachable node, which the
+/// next checks depend on
+async fn node_checks(
+    client: &impl NodeApi,
+    url: &Url,
+    expected_chain_id: Option<u8>,
+) -> Result<Vec<Check>, Check> {
+    let mut checks = vec![];
+    let index = client
+        .get_index()
+        .await
+        .map_err(|err| {
+            Check::fail(
+                "Node",
+                true,
+                format!("{url} failed: {err}"),
+                "Check the url and the network, or use another fullnode with --url",
+            )
+        })?
+        .into_inner();
+    let node = format!(
+        "{url} on chain id {}, ledger version {}",
+        index.chain_id,
+        u64::from(index.ledger_version)
+    );
+    match expected_chain_id {
+        Some(expected) if expected != index.chain_id => checks.push(Check::fail(
+            "Node",
+            true,
+            format!("{node}, chain id {expected} expected"),
+            "The node is on another network, check --url and --network",
+        )),
+        _ => checks.push(Check::pass("Node", node)),
+    }
+
+    let lag_secs = ledger_lag_secs(u64::from(index.ledger_timestamp));
+    if lag_secs > DOCTOR_MAX_LAG_SECS {
+        checks.push(Check::fail(
+            "Sync",
+            true,
+            format!("the ledger is {lag_secs}s behind the clock"),
+            "The node is syncing or stuck, use another fullnode with --url, or check the clock of this machine",
+        ));
+    } else {
+        checks.push(Check::pass("Sync", format!("{lag_secs}s behind the clock")));
+    }
+    Ok(checks)
+}
+
+async fn account_check(client: &impl NodeApi, address: AccountAddress) -> Check {
+    match client.get_account(address).await {
+        Ok(account) => Check::pass(
+            "Account",
+            format!(
+                "{} exists, sequence number {}",
+                format_address(&address),
+                account.inner().sequence_number
+            ),
+        ),
+        Err(err) => Check::fail(
+            "Account",
+            true,
+            format!("{} not found on-chain: {err}", format_address(&address)),
+            "Create it by sending it coins with `txs transfer-coins` from a funded account",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{error_body, fixture_body, mock_node, sender};
+    use serde_json::json;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    const ACCOUNT_INFO: &str = "GET /accounts/*";
+
+    fn url() -> Url {
+        Url::parse("http://localhost:8080").unwrap()
+    }
+
+    #[tokio::test]
+    async fn check_the_chain_id_and_the_sync() {
+        // The ledger of the fixtures stopped in 2023
+        let checks = node_checks(&mock_node(), &url(), Some(4)).await.unwrap();
+        assert!(checks[0].passed, "{}", checks[0].detail);
+        assert_eq!(checks[1].name, "Sync");
+        assert!(!checks[1].passed && checks[1].critical);
+
+        let checks = node_checks(&mock_node(), &url(), Some(1)).await.unwrap();
+        assert!(!checks[0].passed, "{}", checks[0].detail);
+        assert!(
+            checks[0].detail.contains("chain id 1 expected"),
+            "{}",
+            checks[0].detail
+        );
+
+        let mut index = fixture_body("GET /");
+        let now_usecs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_micros();
+        index["ledger_timestamp"] = json!(now_usecs.to_string());
+        let node = mock_node().with_route("GET /", 200, index);
+        let checks = node_checks(&node, &url(), None).await.unwrap();
+        assert!(checks.iter().all(|check| check.passed));
+
+        // The account is not looked up on an unreachable node
+        let node =
+            mock_node().with_route("GET /", 503, error_body("Unavailable", "internal_error"));
+        let Err(check) = node_checks(&node, &url(), None).await else {
+            panic!("the node answered");
+        };
+        assert_eq!(check.name, "Node");
+        assert!(check.critical);
+    }
+
+    #[tokio::test]
+    async fn check_the_account() {
+        let check = account_check(&mock_node(), sender()).await;
+        assert!(check.passed, "{}", check.detail);
+        assert!(
+            check.detail.contains("sequence number 0"),
+            "{}",
+            check.detail
+        );
+
+        let node = mock_node().with_route(
+            ACCOUNT_INFO,
+            404,
+            error_body("Account not found", "account_not_found"),
+        );
+        let check = account_check(&node, sender()).await;
+        assert!(!check.passed && check.critical);
+        assert!(
+            check.detail.contains("not found on-chain"),
+            "{}",
+            check.detail
+        );
+    }
+}
@@ -1,34 +1,375 @@
-use anyhow::Result;
-use libra_config::extension::client_ext::{ClientExt as ConfigClientExt, DEFAULT_TIMEOUT_SECS};
+use super::txs_client;
+use anyhow::{bail, Context, Result};
+use std::{fs, path::Path};
 use txs::{
-    constant::{DEFAULT_GAS_UNIT_PRICE, DEFAULT_MAX_GAS_AMOUNT},
-    crypto::{ed25519::Ed25519PrivateKey, ValidCryptoMaterialStringExt},
-    extension::{
-        client_ext::{ClientExt, TransactionOptions},
-        ed25519_private_key_ext::Ed25519PrivateKeyExt,
+    args::{parse_list_args, FunctionArgs, ListArg},
+    crypto::ed25519::Ed25519PrivateKey,
+    extension::client_ext::{ClientExt, TransactionOptions},
+    move_types::parser::parse_type_tags,
+    node_api::NodeApi,
+    signer::TransactionSigner,
+    sponsored_transaction::SponsoredTransaction,
+    types::{
+        account_address::AccountAddress,
+        transaction::{RawTransaction, SignedTransaction, TransactionPayload},
     },
-    rest_client::Client,
-    types::transaction::SignedTransaction,
+    util::{format_args, format_type_args, script_payload},
 };
 
+#[allow(clippy::too_many_arguments)]
 pub async fn run(
+    client: &impl NodeApi,
     function_id: &str,
-    private_key: &str,
+    signer: &TransactionSigner,
+    secondary_private_keys: &[Ed25519PrivateKey],
+    fee_payer_private_key: Option<&Ed25519PrivateKey>,
     type_args: Option<String>,
-    args: Option<String>,
-    max_gas: Option<u64>,
-    gas_unit_price: Option<u64>,
+    args: Option<FunctionArgs>,
+    options: TransactionOptions,
+    check_abi: bool,
 ) -> Result<SignedTransaction> {
-    let client = Client::default()?;
-    let private_key = Ed25519PrivateKey::from_encoded_string(private_key)?;
-    let mut account = private_key.get_account(None).await?;
-    let options = TransactionOptions {
-        max_gas_amount: max_gas.unwrap_or(DEFAULT_MAX_GAS_AMOUNT),
-        gas_unit_price: gas_unit_price.unwrap_or(DEFAULT_GAS_UNIT_PRICE),
-        timeout_secs: DEFAULT_TIMEOUT_SECS,
+    if let Some(fee_payer_private_key) = fee_payer_private_key {
+        let payload = client
+            .entry_function_payload_ext(
+                function_id,
+                type_args,
+                args,
+                check_abi,
+                secondary_private_keys.len() + 1,
+            )
+            .await?;
+        return signer
+            .sign_fee_payer_payload(
+                client,
+                secondary_private_keys,
+                fee_payer_private_key,
+                payload,
+                options,
+            )
+            .await;
+    }
+    Ok(txs_client(client)
+        .await?
+        .sign_entry_function(
+            signer,
+            secondary_private_keys,
+            function_id,
+            type_args,
+            args,
+            options,
+            check_abi,
+        )
+        .await?)
+}
+
+/// Sign a compiled Move script with its type arguments and arguments
+#[allow(clippy::too_many_arguments)]
+pub async fn run_script(
+    client: &impl NodeApi,
+    script_file: &Path,
+    signer: &TransactionSigner,
+    secondary_private_keys: &[Ed25519PrivateKey],
+    fee_payer_private_key: Option<&Ed25519PrivateKey>,
+    type_args: Option<String>,
+    args: Option<FunctionArgs>,
+    options: TransactionOptions,
+) -> Result<SignedTransaction> {
+    let payload = script_file_payload(script_file, type_args, args)?;
+    match fee_payer_private_key {
+        Some(fee_payer_private_key) => {
+            signer
+                .sign_fee_payer_payload(
+                    client,
+                    secondary_private_keys,
+                    fee_payer_private_key,
+                    payload,
+                    options,
+                )
+                .await
+        }
+        None => {
+            signer
+                .sign_multi_agent_payload(client, secondary_private_keys, payload, options)
+                .await
+        }
+    }
+}
+
+/// Sign as the sender and the secondary signers of a transaction whose gas the account at
+/// `fee_payer_address` pays, for an entry function or a script. It countersigns it later
+#[allow(clippy::too_many_arguments)]
+pub async fn run_for_fee_payer(
+    client: &impl NodeApi,
+    function_id: Option<&str>,
+    script_file: Option<&Path>,
+    signer: &TransactionSigner,
+    secondary_private_keys: &[Ed25519PrivateKey],
+    fee_payer_address: AccountAddress,
+    type_args: Option<String>,
+    args: Option<FunctionArgs>,
+    options: TransactionOptions,
+    check_abi: bool,
+) -> Result<SponsoredTransaction> {
+    let payload = match (function_id, script_file) {
+        (_, Some(script_file)) => script_file_payload(script_file, type_args, args)?,
+        (Some(function_id), None) => {
+            client
+                .entry_function_payload_ext(
+                    function_id,
+                    type_args,
+                    args,
+                    check_abi,
+                    secondary_private_keys.len() + 1,
+                )
+                .await?
+        }
+        (None, None) => bail!("One of --function-id or --script-file is required"),
     };
+    signer
+        .sign_for_fee_payer(
+            client,
+            secondary_private_keys,
+            fee_payer_address,
+            payload,
+            options,
+        )
+        .await
+}
+
+/// Build the transaction of `sender` without signing it, for an entry function or a script
+#[allow(clippy::too_many_arguments)]
+pub async fn run_unsigned(
+    client: &impl NodeApi,
+    sender: AccountAddress,
+    function_id: Option<&str>,
+    script_file: Option<&Path>,
+    type_args: Option<String>,
+    args: Option<FunctionArgs>,
+    options: TransactionOptions,
+    check_abi: bool,
+) -> Result<RawTransaction> {
+    let payload = match (function_id, script_file) {
+        (_, Some(script_file)) => script_file_payload(script_file, type_args, args)?,
+        (Some(function_id), None) => {
+            client
+                .entry_function_payload_ext(function_id, type_args, args, check_abi, 1)
+                .await?
+        }
+        (None, None) => bail!("One of --function-id or --script-file is required"),
+    };
+    client.build_raw_transaction(sender, payload, options).await
+}
+
+fn script_file_payload(
+    script_file: &Path,
+    type_args: Option<String>,
+    args: Option<FunctionArgs>,
+) -> Result<TransactionPayload> {
+    let code = fs::read(script_file).context(format!(
+        "Failed to read the script {}",
+        script_file.display()
+    ))?;
+    let type_args = match type_args {
+        Some(type_args) => parse_type_tags(&type_args)
+            .context(format!("Unable to parse the type argument(s): {type_args}"))?,
+        None => vec![],
+    };
+    println!("{}", format_type_args(&type_args));
+    let args = match args {
+        Some(FunctionArgs::List(args)) => {
+            parse_list_args(&args).context(format!("Unable to parse argument(s): {args}"))?
+        }
+        Some(FunctionArgs::Json(_)) => {
+            bail!("Scripts have no ABI to type --args-json with, use --args instead")
+        }
+        None => vec![],
+    };
+    println!("{}", format_args(&args));
+    let args = args
+        .iter()
+        .map(ListArg::to_script_argument)
+        .collect::<Result<_>>()?;
+
+    script_payload(code, type_args, args)
+        .context(format!("Invalid script {}", script_file.display()))
+}
 
-    client
-        .generate_transaction(&mut account, function_id, type_args, args, options)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        address, arg, entry_function, function_id, mock_node, module, options, private_key, sender,
+    };
+    use serde_json::json;
+    use txs::{
+        crypto::PrivateKey,
+        node_api::MockNode,
+        types::transaction::authenticator::{AuthenticationKey, TransactionAuthenticator},
+    };
+
+    /// A module of its own, as the modules are cached by name for the whole test run
+    const MODULE: &str = "GET /accounts/0x1/module/gift";
+
+    fn gift_node() -> MockNode {
+        mock_node().with_route(
+            MODULE,
+            200,
+            module(
+                "gift",
+                json!([{
+                    "name": "give",
+                    "visibility": "public",
+                    "is_entry": true,
+                    "is_view": false,
+                    "generic_type_params": [],
+                    "params": ["&signer", "address", "u64"],
+                    "return": [],
+                }]),
+            ),
+        )
+    }
+
+    async fn generate(node: &MockNode, args: &str) -> Result<SignedTransaction> {
+        run(
+            node,
+            "0x1::gift::give",
+            &TransactionSigner::PrivateKey(private_key()),
+            &[],
+            None,
+            None,
+            Some(FunctionArgs::List(args.to_string())),
+            options(),
+            true,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn sign_the_call_of_the_function() {
+        let signed_trans = generate(&gift_node(), "0x3, 10").await.unwrap();
+        signed_trans.clone().check_signature().unwrap();
+        assert_eq!(signed_trans.sender(), sender());
+        assert_eq!(signed_trans.gas_unit_price(), 100);
+        let call = entry_function(&signed_trans);
+        assert_eq!(function_id(&call), "0x1::gift::give");
+        assert_eq!(arg::<AccountAddress>(&call, 0), address("0x3"));
+        assert_eq!(arg::<u64>(&call, 1), 10);
+    }
+
+    #[tokio::test]
+    async fn check_the_arguments_against_the_abi() {
+        let err = generate(&gift_node(), "0x3").await.unwrap_err();
+        assert!(
+            format!("{err:#}").contains("expects 2 argument(s), got 1"),
+            "{err:#}"
+        );
+
+        let err = generate(&gift_node(), "10, 0x3").await.unwrap_err();
+        assert!(
+            format!("{err:#}").contains("Argument 1 of 0x1::gift::give must be a address"),
+            "{err:#}"
+        );
+
+        // The gift takes no secondary signer
+        let err = run(
+            &gift_node(),
+            "0x1::gift::give",
+            &TransactionSigner::PrivateKey(private_key()),
+            &[private_key()],
+            None,
+            None,
+            Some(FunctionArgs::List("0x3, 10".to_string())),
+            options(),
+            true,
+        )
+        .await
+        .unwrap_err();
+        assert!(format!("{err:#}").contains("takes 1 signer(s)"), "{err:#}");
+    }
+
+    #[tokio::test]
+    async fn sign_as_the_fee_payer_too() {
+        let fee_payer_private_key = Ed25519PrivateKey::try_from([2; 32].as_slice()).unwrap();
+        let signed_trans = run(
+            &gift_node(),
+            "0x1::gift::give",
+            &TransactionSigner::PrivateKey(private_key()),
+            &[],
+            Some(&fee_payer_private_key),
+            None,
+            Some(FunctionArgs::List("0x3, 10".to_string())),
+            options(),
+            true,
+        )
+        .await
+        .unwrap();
+        signed_trans.clone().check_signature().unwrap();
+        assert_eq!(signed_trans.sender(), sender());
+        match signed_trans.authenticator() {
+            TransactionAuthenticator::FeePayer {
+                fee_payer_address, ..
+            } => assert_eq!(
+                fee_payer_address,
+                AuthenticationKey::ed25519(&fee_payer_private_key.public_key()).derived_address()
+            ),
+            _ => panic!("Expected a fee payer authenticator"),
+        }
+    }
+
+    #[tokio::test]
+    async fn build_the_transaction_of_another_sender() {
+        let raw_trans = run_unsigned(
+            &gift_node(),
+            address("0x4"),
+            Some("0x1::gift::give"),
+            None,
+            None,
+            Some(FunctionArgs::List("0x3, 10".to_string())),
+            options(),
+            true,
+        )
         .await
+        .unwrap();
+        assert_eq!(raw_trans.sender(), address("0x4"));
+        assert_eq!(raw_trans.sequence_number(), 0);
+        assert_eq!(raw_trans.max_gas_amount(), 2_000);
+
+        let err = run_unsigned(
+            &gift_node(),
+            address("0x4"),
+            None,
+            None,
+            None,
+            None,
+            options(),
+            true,
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("One of --function-id or --script-file"),
+            "{err:#}"
+        );
+    }
+
+    #[test]
+    fn type_the_script_arguments_with_a_list() {
+        let script_file =
+            std::env::temp_dir().join(format!("txs-script-test-{}.mv", std::process::id()));
+        fs::write(&script_file, [0xa1, 0x1c, 0xeb, 0x0b]).unwrap();
+        let err = script_file_payload(
+            &script_file,
+            None,
+            Some(FunctionArgs::Json(json!([1]).to_string())),
+        )
+        .unwrap_err();
+        fs::remove_file(&script_file).unwrap();
+        assert!(format!("{err:#}").contains("use --args instead"), "{err:#}");
+
+        let err = script_file_payload(&script_file, None, None).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("Failed to read the script"),
+            "{err:#}"
+        );
+    }
 }
@@ -0,0 +1,260 @@
+use super::{submit_transaction, vouch, WaitOptions};
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use txs::{
+    crypto::ed25519::Ed25519PrivateKey,
+    extension::{client_ext::TransactionOptions, ed25519_private_key_ext::Ed25519PrivateKeyExt},
+    node_api::NodeApi,
+    retry::retry_read,
+    types::account_address::AccountAddress,
+    util::{entry_function_payload, format_address, format_output, parse_address, OutputFormat},
+};
+
+const JAIL_TYPE: &str = "0x1::jail::Jail";
+const UNJAIL_FUNCTION: &str = "0x1::jail::unjail_by_voucher";
+
+/// Whether the validator is jailed, with its failures to rejoin the set and the lifetime counts
+/// of its jail resource
+pub async fn status(client: &impl NodeApi, account: &str, format: OutputFormat) -> Result<String> {
+    let account = parse_address(account, "--account")?;
+    let status = get_status(client, account).await?;
+    if format == OutputFormat::Text {
+        Ok(format_status(&status))
+    } else {
+        format_output(&status, format)
+    }
+}
+
+/// Unjail a validator the signer vouches for. The call aborts unless the validator is jailed
+/// and the signer is one of its vouchers, which is checked before signing
+pub async fn unjail(
+    client: &impl NodeApi,
+    for_account: &str,
+    private_key: &Ed25519PrivateKey,
+    options: TransactionOptions,
+    wait_options: &WaitOptions,
+    yes: bool,
+) -> Result<()> {
+    let mut from_account = private_key.get_account(client, None).await?;
+    let for_account = parse_address(for_account, "--for-account")?;
+    let status = get_status(client, for_account).await?;
+    if status["is_jailed"] == json!(false) {
+        bail!(
+            "{} is not jailed, there is nothing to unjail",
+            format_address(&for_account)
+        );
+    }
+
+    match vouch::received_vouches(client, for_account).await {
+        Some(vouchers) => {
+            let vouches = vouchers
+                .iter()
+                .any(|(voucher, _)| *voucher == from_account.address());
+            if !vouches {
+                bail!(
+                    "{} does not vouch for {}, {UNJAIL_FUNCTION} would abort: only the vouchers of a jailed validator can unjail it. Its vouchers are: {}",
+                    format_address(&from_account.address()),
+                    format_address(&for_account),
+                    vouchers
+                        .iter()
+                        .map(|(voucher, _)| format_address(voucher))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+        }
+        None => eprintln!(
+            "Warning: The vouchers of {} could not be read, the call aborts unless the signer vouches for it",
+            format_address(&for_account)
+        ),
+    }
+
+    let payload =
+        entry_function_payload(UNJAIL_FUNCTION, vec![], vec![bcs::to_bytes(&for_account)?])?;
+    let details = [
+        ("Function", UNJAIL_FUNCTION.to_string()),
+        ("Validator", format_address(&for_account)),
+        (
+            "Consecutive failures to rejoin",
+            unknown(&status["consecutive_failure_to_rejoin"]),
+        ),
+    ];
+    submit_transaction::sign_and_submit(
+        client,
+        &mut from_account,
+        payload,
+        options,
+        &details,
+        wait_options,
+        yes,
+    )
+    .await
+}
+
+/// The jail resource of the account, its counts as numbers. None where the node leaves a field out
+async fn get_status(client: &impl NodeApi, account: AccountAddress) -> Result<Value> {
+    let jail = retry_read(|| client.get_account_resource(account, JAIL_TYPE))
+        .await
+        .context(format!(
+            "Failed to get the jail resource of {}",
+            format_address(&account)
+        ))?
+        .into_inner()
+        .context(format!(
+            "{} has no jail resource, it has never been a validator",
+            format_address(&account)
+        ))?
+        .data;
+    let count = |field: &str| {
+        jail[field]
+            .as_str()
+            .and_then(|count| count.parse::<u64>().ok())
+    };
+    Ok(json!({
+        "account": format_address(&account),
+        "is_jailed": jail["is_jailed"].as_bool(),
+        "consecutive_failure_to_rejoin": count("consecutive_failure_to_rejoin"),
+        "lifetime_jailed": count("lifetime_jailed"),
+        "lifetime_vouchees_jailed": count("lifetime_vouchees_jailed"),
+    }))
+}
+
+fn format_status(status: &Value) -> String {
+    let jailed = match status["is_jailed"].as_bool() {
+        Some(true) => "yes, one of its vouchers can unjail it with txs unjail",
+        Some(false) => "no",
+        None => "unknown",
+    };
+    [
+        format!("Account: {}", unknown(&status["account"])),
+        format!("Jailed: {jailed}"),
+        format!(
+            "Consecutive failures to rejoin: {}",
+            unknown(&status["consecutive_failure_to_rejoin"])
+        ),
+        format!("Lifetime jailed: {}", unknown(&status["lifetime_jailed"])),
+        format!(
+            "Lifetime vouchees jailed: {}",
+            unknown(&status["lifetime_vouchees_jailed"])
+        ),
+    ]
+    .join("\n")
+}
+
+fn unknown(value: &Value) -> String {
+    match value {
+        Value::Null => "unknown".to_string(),
+        Value::String(s) => s.to_owned(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        address, arg, error_body, function_id, mock_node, options, private_key, sender,
+        submitted_call, wait_options, VIEW,
+    };
+    use txs::node_api::MockNode;
+
+    const JAIL: &str = "GET /accounts/*/resource/0x1::jail::Jail";
+
+    fn jail(data: Value) -> Value {
+        json!({ "type": JAIL_TYPE, "data": data })
+    }
+
+    /// A jailed validator, vouched for by the account of `private_key`
+    fn jail_node() -> MockNode {
+        mock_node()
+            .with_route(
+                JAIL,
+                200,
+                jail(json!({
+                    "is_jailed": true,
+                    "consecutive_failure_to_rejoin": "2",
+                    "lifetime_jailed": "3",
+                    "lifetime_vouchees_jailed": "0",
+                })),
+            )
+            .with_route(VIEW, 200, json!([[sender().to_hex_literal()], ["5"]]))
+    }
+
+    async fn unjail_0x4(node: &MockNode) -> Result<()> {
+        unjail(
+            node,
+            "0x4",
+            &private_key(),
+            options(),
+            &wait_options(),
+            true,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn show_the_counts_of_the_jail() {
+        let output = status(&jail_node(), "0x4", OutputFormat::Text)
+            .await
+            .unwrap();
+        assert!(output.contains("Jailed: yes"), "{output}");
+        assert!(
+            output.contains("Consecutive failures to rejoin: 2"),
+            "{output}"
+        );
+        assert!(output.contains("Lifetime jailed: 3"), "{output}");
+
+        let output = status(&jail_node(), "0x4", OutputFormat::Json)
+            .await
+            .unwrap();
+        let status_json = serde_json::from_str::<Value>(&output).unwrap();
+        assert_eq!(status_json["is_jailed"], json!(true));
+        assert_eq!(status_json["lifetime_vouchees_jailed"], json!(0));
+
+        // The counts the node leaves out are unknown, not zero
+        let node = mock_node().with_route(JAIL, 200, jail(json!({ "is_jailed": false })));
+        let output = status(&node, "0x4", OutputFormat::Text).await.unwrap();
+        assert!(output.contains("Jailed: no"), "{output}");
+        assert!(output.contains("Lifetime jailed: unknown"), "{output}");
+
+        let node = mock_node().with_route(JAIL, 200, Value::Null);
+        let err = status(&node, "0x4", OutputFormat::Text).await.unwrap_err();
+        assert!(
+            format!("{err:#}").contains("it has never been a validator"),
+            "{err:#}"
+        );
+    }
+
+    #[tokio::test]
+    async fn unjail_as_a_voucher() {
+        let node = jail_node();
+        unjail_0x4(&node).await.unwrap();
+        let call = submitted_call(&node);
+        assert_eq!(function_id(&call), UNJAIL_FUNCTION);
+        assert_eq!(arg::<AccountAddress>(&call, 0), address("0x4"));
+
+        // The call would abort, it is not sent
+        let node = jail_node().with_route(VIEW, 200, json!([["0x5"], ["5"]]));
+        let err = unjail_0x4(&node).await.unwrap_err();
+        assert!(format!("{err:#}").contains("does not vouch for"), "{err:#}");
+        assert!(node.submitted().is_empty());
+
+        let node = jail_node().with_route(
+            JAIL,
+            200,
+            jail(json!({ "is_jailed": false, "lifetime_jailed": "1" })),
+        );
+        let err = unjail_0x4(&node).await.unwrap_err();
+        assert!(format!("{err:#}").contains("is not jailed"), "{err:#}");
+        assert!(node.submitted().is_empty());
+
+        // Unreadable vouchers are only a warning, the chain has the last word
+        let node = jail_node().with_route(
+            VIEW,
+            404,
+            error_body("Function not found", "function_not_found"),
+        );
+        unjail_0x4(&node).await.unwrap();
+        assert_eq!(node.submitted().len(), 1);
+    }
+}
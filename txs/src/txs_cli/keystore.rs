@@ -0,0 +1,98 @@
+use anyhow::{bail, Context, Result};
+use libra_wallet::keystore::{load_private_key, read_password, Keystore};
+use std::{fs, path::PathBuf};
+use txs::{
+    crypto::{ed25519::Ed25519PrivateKey, ValidCryptoMaterialStringExt},
+    extension::ed25519_private_key_ext::Ed25519PrivateKeyExt,
+};
+
+#[derive(clap::Subcommand)]
+pub enum KeystoreCommand {
+    /// Encrypt a plaintext private key file with a password
+    Import {
+        /// The plaintext private key file, as taken by --private-key-file
+        #[clap(long)]
+        private_key_file: PathBuf,
+
+        /// Where to save the keystore
+        #[clap(short, long)]
+        output_file: PathBuf,
+
+        /// File holding the new password. Without it the password is read from the LIBRA_KEYSTORE_PASSWORD environment variable or asked for
+        #[clap(long)]
+        password_file: Option<PathBuf>,
+
+        /// Overwrite the output file if it already exists
+        #[clap(long)]
+        force: bool,
+    },
+
+    /// Decrypt a keystore back to a plaintext private key file
+    Export {
+        /// The keystore to decrypt
+        #[clap(long)]
+        keystore: PathBuf,
+
+        /// File holding the password of the keystore
+        #[clap(long)]
+        password_file: Option<PathBuf>,
+
+        /// Where to save the plaintext private key
+        #[clap(short, long)]
+        output_file: PathBuf,
+
+        /// Overwrite the output file if it already exists
+        #[clap(long)]
+        force: bool,
+    },
+}
+
+pub fn run(command: &KeystoreCommand) -> Result<()> {
+    match command {
+        KeystoreCommand::Import {
+            private_key_file,
+            output_file,
+            password_file,
+            force,
+        } => {
+            let private_key = Ed25519PrivateKey::from_key_file(private_key_file)?;
+            let password = read_password(password_file.as_deref(), true)?;
+            let keystore = Keystore::encrypt(&private_key, &password)?;
+            keystore.save(output_file, *force)?;
+            println!(
+                "Keystore of {} saved to {}",
+                keystore.account_address,
+                output_file.display()
+            );
+            println!(
+                "Use it with --keystore {}, then delete the plaintext key {}",
+                output_file.display(),
+                private_key_file.display()
+            );
+            Ok(())
+        }
+        KeystoreCommand::Export {
+            keystore,
+            password_file,
+            output_file,
+            force,
+        } => {
+            if output_file.exists() && !force {
+                bail!(
+                    "{} already exists, use --force to overwrite it",
+                    output_file.display()
+                );
+            }
+            let private_key = load_private_key(keystore, password_file.as_deref())?;
+            fs::write(output_file, private_key.to_encoded_string()?).context(format!(
+                "Failed to write the private key to {}",
+                output_file.display()
+            ))?;
+            println!(
+                "Private key saved in plaintext to {}, keep it safe",
+                output_file.display()
+            );
+            Ok(())
+        }
+    }
+}
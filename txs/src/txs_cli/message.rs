@@ -0,0 +1,184 @@
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use std::path::PathBuf;
+use txs::{
+    crypto::{
+        ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+        PrivateKey, ValidCryptoMaterialStringExt,
+    },
+    message::{sign_message, verify_message},
+    node_api::NodeApi,
+    retry::retry_read,
+    types::{account_address::AccountAddress, transaction::authenticator::AuthenticationKey},
+    util::format_address,
+};
+
+/// The message to sign or verify
+#[derive(clap::Args)]
+pub struct MessageOptions {
+    /// The message, as text
+    #[clap(required_unless_present = "message_file")]
+    message: Option<String>,
+
+    /// File holding the message, signed byte for byte
+    #[clap(long, conflicts_with = "message")]
+    message_file: Option<PathBuf>,
+}
+
+impl MessageOptions {
+    fn bytes(&self) -> Result<Vec<u8>> {
+        match (&self.message, &self.message_file) {
+            (_, Some(path)) => {
+                std::fs::read(path).context(format!("Failed to read {}", path.display()))
+            }
+            (Some(message), None) => Ok(message.as_bytes().to_vec()),
+            (None, None) => bail!("Pass the message or --message-file"),
+        }
+    }
+}
+
+pub fn sign(message_options: &MessageOptions, private_key: &Ed25519PrivateKey) -> Result<()> {
+    let message = message_options.bytes()?;
+    let public_key = private_key.public_key();
+    let signature = sign_message(private_key, &message);
+    println!("Public key: {public_key}");
+    println!(
+        "Address: {} (derived from the key, an account which rotated its key keeps its own address)",
+        format_address(&AuthenticationKey::ed25519(&public_key).derived_address())
+    );
+    println!("Signature: 0x{}", hex::encode(signature.to_bytes()));
+    Ok(())
+}
+
+/// Check the signature of the message, then that the key is the key of the account on-chain
+/// when an address is given
+pub async fn verify(
+    message_options: &MessageOptions,
+    signature: &str,
+    public_key: &str,
+    on_chain: Option<(AccountAddress, &dyn NodeApi)>,
+) -> Result<()> {
+    let message = message_options.bytes()?;
+    let public_key = Ed25519PublicKey::from_encoded_string(public_key.trim())
+        .context(format!("Invalid public key {public_key}"))?;
+    let signature = Ed25519Signature::from_encoded_string(signature.trim())
+        .context(format!("Invalid signature {signature}"))?;
+    verify_message(&public_key, &message, &signature)?;
+    println!(
+        "{} the message is signed by public key {public_key}",
+        "Verified:".green().bold()
+    );
+
+    if let Some((address, client)) = on_chain {
+        let auth_key = retry_read(|| client.get_account(address))
+            .await
+            .context(format!(
+                "Failed to get the account {}",
+                format_address(&address)
+            ))?
+            .into_inner()
+            .authentication_key;
+        if auth_key != AuthenticationKey::ed25519(&public_key) {
+            bail!(
+                "The public key is not the key of {}, its authentication key is {auth_key}",
+                format_address(&address)
+            );
+        }
+        println!(
+            "{} the public key is the key of {}",
+            "Verified:".green().bold(),
+            format_address(&address)
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{mock_node, private_key, sender};
+    use serde_json::json;
+    use txs::node_api::MockNode;
+
+    fn hello() -> MessageOptions {
+        MessageOptions {
+            message: Some("hello".to_string()),
+            message_file: None,
+        }
+    }
+
+    /// Verify the signature of `hello` by the key, against the account of `private_key` when a
+    /// node is given
+    async fn verify_hello(
+        signature: &[u8],
+        signing_key: &Ed25519PrivateKey,
+        node: Option<&MockNode>,
+    ) -> Result<()> {
+        verify(
+            &hello(),
+            &format!("0x{}", hex::encode(signature)),
+            &signing_key.public_key().to_encoded_string().unwrap(),
+            node.map(|node| (sender(), node as &dyn NodeApi)),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn verify_the_signature_of_the_message() {
+        let signature = sign_message(&private_key(), b"hello").to_bytes();
+        verify_hello(&signature, &private_key(), None)
+            .await
+            .unwrap();
+
+        let other_signature = sign_message(&private_key(), b"hello!").to_bytes();
+        let err = verify_hello(&other_signature, &private_key(), None)
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("does not match the message"),
+            "{err:#}"
+        );
+
+        let err = verify_hello(&signature[1..], &private_key(), None)
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("Invalid signature"), "{err:#}");
+    }
+
+    #[tokio::test]
+    async fn verify_the_key_of_the_account() {
+        let node = mock_node();
+        let signature = sign_message(&private_key(), b"hello").to_bytes();
+        verify_hello(&signature, &private_key(), Some(&node))
+            .await
+            .unwrap();
+        assert_eq!(
+            node.requests(),
+            [format!("GET /accounts/{}", sender().to_hex_literal())]
+        );
+
+        // A valid signature of another key, e.g. after the account rotated its key
+        let other_key = Ed25519PrivateKey::try_from([2; 32].as_slice()).unwrap();
+        let signature = sign_message(&other_key, b"hello").to_bytes();
+        verify_hello(&signature, &other_key, None).await.unwrap();
+        let err = verify_hello(&signature, &other_key, Some(&node))
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("is not the key of"), "{err:#}");
+
+        let rotated = mock_node().with_route(
+            "GET /accounts/*",
+            200,
+            json!({
+                "authentication_key": format!(
+                    "0x{}",
+                    hex::encode(AuthenticationKey::ed25519(&other_key.public_key()).to_vec())
+                ),
+                "sequence_number": "3",
+            }),
+        );
+        verify_hello(&signature, &other_key, Some(&rotated))
+            .await
+            .unwrap();
+    }
+}
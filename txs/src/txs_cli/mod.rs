@@ -1,75 +1,388 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use batch_create_account::Funding;
+use chrono::{DateTime, Utc};
 use clap::Parser;
 use colored::Colorize;
 use indoc::indoc;
-use txs::util::format_signed_transaction;
+use libra_config::extension::{
+    cli_config_ext::{config_file_path, profile_private_key},
+    client_ext::{
+        profile_rest_urls, set_timeouts, timeout_error, ClientExt, DEFAULT_CONNECT_TIMEOUT_SECS,
+        DEFAULT_TIMEOUT_SECS,
+    },
+    faucet_client_ext::FaucetClientExt,
+};
+use libra_wallet::{keystore::load_private_key, utils::check_key_file_permissions};
+use std::{io::Read, path::PathBuf};
+use tracing::info;
+use txs::{
+    abi_cache,
+    args::FunctionArgs,
+    client::TxsClient,
+    constant::{
+        DEFAULT_COUNTERSIGN_EXPIRATION_SECS, DEFAULT_GAS_BUFFER_PERCENT, DEFAULT_MAX_RETRIES,
+        DEFAULT_MAX_STALENESS_SECS, DEFAULT_POLL_INTERVAL_MS, DEFAULT_UNSIGNED_EXPIRATION_SECS,
+        DEFAULT_WAIT_TIMEOUT_SECS, DEMO_FUND_AMOUNT, DEMO_TRANSFER_AMOUNT, PRIVATE_KEY_ENV,
+    },
+    crypto::{
+        ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+        PrivateKey, ValidCryptoMaterialStringExt,
+    },
+    exit_code::EXIT_CODES_HELP,
+    explorer::{configured_template, set_explorer, Explorer},
+    extension::{
+        client_ext::{ClientExt as _, GasPriority, LedgerVersionOptions, TransactionOptions},
+        ed25519_private_key_ext::Ed25519PrivateKeyExt,
+        signed_transaction_ext::SignedTransactionExt,
+    },
+    funds::{gas_payer, max_fee, Funds},
+    journal::{set_journal, Journal},
+    logging,
+    network::{check_chain_id, check_staleness, ledger_lag_secs, Network},
+    node_api::NodeApi,
+    nonce::NonceLease,
+    rest_client::{aptos_api_types::MoveType, Client, FaucetClient},
+    retry::{self, retry_read},
+    secret::{parse_private_key, set_strict_permissions, strict_permissions, Secret},
+    signer::{self, TransactionSigner},
+    sponsored_transaction::SponsoredTransaction,
+    types::{
+        chain_id::ChainId,
+        transaction::{authenticator::AuthenticationKey, SignedTransaction},
+    },
+    unsigned_transaction::UnsignedTransaction,
+    util::{
+        format_address, format_output, format_signed_transaction_json,
+        format_signed_transaction_with_params, parse_address, set_color, transaction_hash,
+        ColorChoice, OutputFormat,
+    },
+};
+use url::Url;
 
+mod address;
+mod batch_create_account;
+mod batch_transfer;
+mod bump;
+mod burn;
+mod cache;
+mod community;
+mod completions;
 mod create_account;
 mod demo;
+mod doctor;
 mod generate_transaction;
+mod jail;
+mod keystore;
+mod message;
+mod multisig;
+mod nonce;
+mod pof_bid;
+mod profile;
+mod prompt;
+mod publish;
+mod receipt;
+mod register_coin;
+mod repl;
+mod rotate_key;
+mod selftest;
+mod simulate_transaction;
 mod submit_transaction;
+#[cfg(test)]
+mod test_node;
 mod transfer_coin;
+mod tx_status;
+mod validator;
+mod verify_transaction;
 mod view;
+mod vote;
+mod vouch;
 
 #[derive(Parser)]
-#[clap(name = env!("CARGO_PKG_NAME"), author, version, about, long_about = None, arg_required_else_help = true)]
+#[clap(name = env!("CARGO_PKG_NAME"), author, version, about, long_about = None, arg_required_else_help = true, after_help = EXIT_CODES_HELP)]
 pub struct TxsCli {
     #[clap(subcommand)]
     subcommand: Option<Subcommand>,
+
+    /// REST endpoint of the fullnode to use for this invocation, overriding the one of --network and of the config file. Repeat it to fail over to the next endpoints
+    #[clap(long, global = true)]
+    url: Vec<Url>,
+
+    /// Well-known network to connect to. The node must report the chain id of the network
+    #[clap(long, global = true, value_enum)]
+    network: Option<Network>,
+
+    /// Chain id the node must report, overriding the one of --network
+    #[clap(long, global = true)]
+    chain_id: Option<u8>,
+
+    /// Go on with a warning when the node reports another chain id than expected
+    #[clap(long, global = true)]
+    allow_chain_id_mismatch: bool,
+
+    /// Refuse the node when its ledger is behind the clock by more than this. A warning is printed from 30s on
+    #[clap(long, global = true, default_value_t = DEFAULT_MAX_STALENESS_SECS)]
+    max_staleness_secs: u64,
+
+    /// Do not check how far behind the node is, e.g. for local networks with a fake clock
+    #[clap(long, global = true, conflicts_with = "max_staleness_secs")]
+    no_staleness_check: bool,
+
+    /// Longest wait to connect to the node. Proxies are taken from the HTTPS_PROXY, HTTP_PROXY and NO_PROXY environment variables
+    #[clap(long, global = true, default_value_t = DEFAULT_CONNECT_TIMEOUT_SECS)]
+    connect_timeout_secs: u64,
+
+    /// Longest wait for the node to answer a request
+    #[clap(long, global = true, default_value_t = DEFAULT_TIMEOUT_SECS)]
+    request_timeout_secs: u64,
+
+    /// Retries of a request which failed with a rate limit, a server error or a lost connection. Submissions are only retried when the node provably did not get them
+    #[clap(long, global = true, default_value_t = DEFAULT_MAX_RETRIES)]
+    max_retries: u32,
+
+    /// Profile of the config to take the REST endpoint and the signing key from, instead of the default profile. Key flags and --url override it
+    #[clap(long, global = true)]
+    profile: Option<String>,
+
+    /// Log the endpoints used and the retries to stderr. Repeat it to log every request with its latency (-vv) and the payloads with their signatures redacted (-vvv)
+    #[clap(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Write the log to this file too, e.g. to attach it to a bug report
+    #[clap(long, global = true)]
+    log_file: Option<PathBuf>,
+
+    /// Color the output always, never, or only on a terminal without NO_COLOR set
+    #[clap(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    /// Same as --color never
+    #[clap(long, global = true, conflicts_with = "color")]
+    no_color: bool,
+
+    /// Output format of query results. Json and yaml print a single document with no decoration
+    #[clap(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// Refuse the key files and the config which other users can read, instead of warning
+    #[clap(long, global = true)]
+    strict_permissions: bool,
+
+    /// Faucet to create and fund accounts with, instead of the one of the APTOS_FAUCET_URL environment variable or http://0.0.0.0:8081
+    #[clap(long, global = true)]
+    faucet_url: Option<Url>,
+
+    /// Block explorer link printed after every submission, with {hash} and {chain} filled in, e.g. https://explorer.example/txn/{hash}?network={chain}. Overrides the template of the network in ~/.0L/explorer.yaml
+    #[clap(long, global = true)]
+    explorer_url: Option<String>,
+
+    /// Do not print the block explorer link of submitted transactions
+    #[clap(long, global = true, conflicts_with = "explorer_url")]
+    no_explorer_link: bool,
+
+    /// Append-only JSONL file recording every submitted transaction. The same payload from the same sender is refused while the journal has it committed or pending, e.g. when a job is run again
+    #[clap(long, global = true, env = "TXS_JOURNAL")]
+    journal: Option<PathBuf>,
+
+    /// Submit even when the journal has the same transaction committed or pending
+    #[clap(long, global = true, requires = "journal")]
+    allow_duplicate: bool,
+
+    /// Fetch the modules from the node instead of reading them from the ABI cache, e.g. after a module upgrade, and cache them again
+    #[clap(long, global = true)]
+    refresh_abi: bool,
 }
 
 #[derive(clap::Subcommand)]
 enum Subcommand {
-    /// Demo transfer coin example for local testnet
-    Demo,
+    /// Smoke test of a network: create or take two accounts, transfer between them and check the balances. Prints whether each step passed and fails on the first failed step, e.g. as a health check after a deployment
+    Demo {
+        /// Amount to transfer, in base units of the native coin
+        #[clap(long, default_value_t = DEMO_TRANSFER_AMOUNT)]
+        amount: u64,
 
-    /// Create onchain account by using Aptos faucet
-    CreateAccount {
-        /// Create onchain account with the given address
+        /// Amount the faucet funds a fresh sender with, in base units
+        #[clap(long, default_value_t = DEMO_FUND_AMOUNT, conflicts_with = "use_key")]
+        fund_amount: u64,
+
+        /// Send from the account of the key flags, else of the profile, instead of a fresh account funded by the faucet
+        #[clap(long)]
+        use_key: bool,
+
+        /// Send to this existing account, or @name from the address book, instead of a fresh account created by the faucet
+        #[clap(long)]
+        recipient: Option<String>,
+
+        #[clap(flatten)]
+        key_options: PrivateKeyOptions,
+    },
+
+    /// Run named checks of the read path and, with throwaway accounts funded by the faucet, of the write path, e.g. in CI. Prints PASS, FAIL or SKIP for each check and exits with the number of failed checks
+    Selftest {
+        /// Only run the checks which read from the node, no faucet is needed
+        #[clap(long)]
+        skip_writes: bool,
+
+        /// Also check the balance of this account, or @name from the address book
         #[clap(short, long)]
-        account_address: String,
+        account_address: Option<String>,
+
+        /// Amount the faucet funds the throwaway sender with, in base units
+        #[clap(long, default_value_t = DEMO_FUND_AMOUNT)]
+        fund_amount: u64,
+    },
+
+    /// Create onchain account by using Aptos faucet, or by a transfer from a funder key on networks without a faucet
+    CreateAccount {
+        /// Create onchain account with the given address, or @name from the address book
+        #[clap(short, long, required_unless_present = "batch_file")]
+        account_address: Option<String>,
 
-        /// The amount of coins to fund the new account
+        /// The amount of coins to fund the new account, in base units of the native coin. Required with a funder key, except with --batch-file where it is the amount of the rows without one
         #[clap(short, long)]
         coins: Option<u64>,
+
+        /// Create the accounts of a CSV file of `address[,coins]` lines instead. Accounts which already exist are skipped
+        #[clap(long, conflicts_with = "account_address")]
+        batch_file: Option<PathBuf>,
+
+        /// Where to save the result of each row of --batch-file, defaults to <BATCH_FILE>.results.json. Rows it records as created or skipped are not tried again
+        #[clap(long, requires = "batch_file")]
+        results_file: Option<PathBuf>,
+
+        /// Private key of the account which funds the new account with `0x1::aptos_account::transfer`, instead of the faucet
+        #[clap(long, conflicts_with = "funder_private_key_file")]
+        funder_private_key: Option<Secret>,
+
+        /// Path of a file holding the private key of the funder, raw hex or a libra-wallet yaml file
+        #[clap(long)]
+        funder_private_key_file: Option<PathBuf>,
+
+        #[clap(flatten)]
+        gas_options: GasOptions,
+
+        #[clap(flatten)]
+        wait_options: WaitOptions,
+
+        /// Send the transfer of the funder without asking for confirmation, required when stdin is not a terminal
+        #[clap(short, long)]
+        yes: bool,
+    },
+
+    /// Register a coin on the account of the key, which cannot receive the coin before
+    RegisterCoin {
+        /// Fully qualified struct tag of the coin, e.g. 0x1::my_coin::MyCoin
+        #[clap(long)]
+        coin_type: String,
+
+        #[clap(flatten)]
+        private_key_options: PrivateKeyOptions,
+
+        #[clap(flatten)]
+        gas_options: GasOptions,
+
+        #[clap(flatten)]
+        wait_options: WaitOptions,
+
+        /// Send the transaction without asking for confirmation, required when stdin is not a terminal
+        #[clap(short, long)]
+        yes: bool,
     },
 
     /// Transfer coins between accounts
     TransferCoins {
-        /// Address of the recipient
+        /// Address of the recipient, or @name from the address book
         #[clap(short, long)]
         to_account: String,
 
-        /// The amount of coins to transfer
-        #[clap(short, long)]
-        amount: u64,
+        /// The amount of coins to transfer, e.g. 1.5 or 12_000.25. It is converted to base units using the decimals of the coin
+        #[clap(short, long, required_unless_present = "all")]
+        amount: Option<String>,
+
+        /// Transfer the whole spendable balance, the unlocked coins of a slow wallet, less the fee learnt by simulating the transfer
+        #[clap(long, conflicts_with_all = ["amount", "raw", "force", "fee_payer_private_key", "fee_payer_private_key_file"])]
+        all: bool,
+
+        /// Take --amount as an integer number of base units, as is
+        #[clap(long)]
+        raw: bool,
+
+        /// Fully qualified struct tag of the coin to transfer, e.g. 0x1::my_coin::MyCoin. Defaults to the native coin
+        #[clap(long)]
+        coin_type: Option<String>,
+
+        /// Transfer a fungible asset instead of a coin, with 0x1::primary_fungible_store::transfer. The address of the metadata object of the asset, or @name from the address book
+        #[clap(long, conflicts_with = "coin_type")]
+        fa_metadata: Option<String>,
+
+        #[clap(flatten)]
+        private_key_options: PrivateKeyOptions,
+
+        #[clap(flatten)]
+        ledger_options: LedgerOptions,
+
+        #[clap(flatten)]
+        fee_payer_options: FeePayerOptions,
 
-        /// Private key of the account to withdraw money from
+        #[clap(flatten)]
+        gas_options: GasOptions,
+
+        #[clap(flatten)]
+        sequence_options: SequenceOptions,
+
+        #[clap(flatten)]
+        wait_options: WaitOptions,
+
+        /// Submit the transfer even when the balance of the sender cannot pay for the amount and the worst-case fee
+        #[clap(long)]
+        force: bool,
+
+        /// Send the transaction without asking for confirmation, required when stdin is not a terminal
         #[clap(short, long)]
-        private_key: String,
+        yes: bool,
+    },
 
-        /// Maximum number of gas units to be used to send this transaction
+    /// Transfer coins to many recipients, from a CSV file of address,amount[,coin_type] rows
+    BatchTransfer {
+        /// Path of the CSV file. A header row starting with `address` and lines starting with # are ignored
         #[clap(short, long)]
-        max_gas: Option<u64>,
+        csv: PathBuf,
+
+        #[clap(flatten)]
+        batch_options: BatchOptions,
+
+        #[clap(flatten)]
+        private_key_options: PrivateKeyOptions,
+
+        #[clap(flatten)]
+        gas_options: GasOptions,
 
-        /// The amount of coins to pay for 1 gas unit. The higher the price is, the higher priority your transaction will be executed with
+        #[clap(flatten)]
+        wait_options: WaitOptions,
+
+        /// Send the transfers without asking for confirmation, required when stdin is not a terminal
         #[clap(short, long)]
-        gas_unit_price: Option<u64>,
+        yes: bool,
     },
 
-    /// Generate a transaction that executes an Entry function on-chain
+    /// Generate a transaction that executes an Entry function or a compiled script on-chain
     GenerateTransaction {
         #[clap(
             short,
             long,
             help = indoc!{r#"
-                Function identifier has the form <ADDRESS>::<MODULE_ID>::<FUNCTION_NAME>
+                Function identifier has the form <ADDRESS>::<MODULE_ID>::<FUNCTION_NAME>,
+                where <ADDRESS> can be @name from the address book
 
                 Example:
                 0x1::coin::transfer
-            "#}
+            "#},
+            required_unless_present = "script_file"
         )]
-        function_id: String,
+        function_id: Option<String>,
+
+        /// Path of a compiled Move script to run instead of an entry function
+        #[clap(long, conflicts_with_all = ["function_id", "skip_abi_check"])]
+        script_file: Option<PathBuf>,
 
         #[clap(
             short,
@@ -88,138 +401,2053 @@ enum Subcommand {
             short,
             long,
             help = indoc!{ r#"
-                Function arguments separated by commas
+                Function arguments separated by commas. Strings are single or double quoted,
+                with backslash escapes, options are none or some(<argument>) and @name is an
+                address from the address book
 
                 Example:
-                '0x1, true, 12, 24_u8, x"123456"'
+                '0x1, true, 12, 24_u8, x"123456", "hello, world", none, some(42)'
             "#}
         )]
         args: Option<String>,
 
-        /// Maximum amount of gas units to be used to send this transaction
-        #[clap(short, long)]
-        max_gas: Option<u64>,
+        #[clap(
+            long,
+            conflicts_with = "args",
+            help = indoc!{ r#"
+                Function arguments as a JSON array, for strings with commas and nested or empty vectors.
+                Each element is typed by the function's ABI, or explicitly as {"type": ..., "value": ...}
 
-        /// The amount of coins to pay for 1 gas unit. The higher the price is, the higher priority your transaction will be executed with
-        #[clap(short, long)]
-        gas_unit_price: Option<u64>,
+                Example:
+                '["0x1", "hello, world", [["0x12"], []], {"type": "u8", "value": 24}]'
+            "#}
+        )]
+        args_json: Option<String>,
 
-        /// Private key to sign the transaction
-        #[clap(short, long)]
-        private_key: String,
+        #[clap(flatten)]
+        gas_options: GasOptions,
+
+        #[clap(flatten)]
+        sequence_options: SequenceOptions,
+
+        #[clap(flatten)]
+        private_key_options: PrivateKeyOptions,
+
+        #[clap(flatten)]
+        ledger_options: LedgerOptions,
+
+        #[clap(flatten)]
+        secondary_signer_options: SecondarySignerOptions,
+
+        #[clap(flatten)]
+        fee_payer_options: FeePayerOptions,
 
         /// Submit the generated transaction to the blockchain
         #[clap(short, long)]
         submit: bool,
-    },
 
-    /// Execute a View function on-chain
-    View {
-        #[clap(
-            short,
-            long,
-            help = indoc!{r#"
-                Function identifier has the form <ADDRESS>::<MODULE_ID>::<FUNCTION_NAME>
+        #[clap(flatten)]
+        wait_options: WaitOptions,
 
-                Example:
-                0x1::coin::balance
-            "#}
-        )]
-        function_id: String,
+        /// Submit the transaction without asking for confirmation, required with --submit when stdin is not a terminal
+        #[clap(short, long)]
+        yes: bool,
+
+        /// Dry run the transaction on the node and print the gas used, VM status and state changes.
+        /// Combined with --submit, the transaction is only submitted if the simulation succeeds
+        #[clap(long)]
+        simulate: bool,
 
+        /// Save the BCS encoded signed transaction to this file, e.g. to submit it from another machine
+        #[clap(long)]
+        output_file: Option<PathBuf>,
+
+        /// Also write a human readable summary next to the output file, as <OUTPUT_FILE>.<txt|json|yaml>
+        #[clap(long, value_enum, requires = "output_file")]
+        output_format: Option<OutputFormat>,
+
+        /// Sign as the sender only, the gas being paid by this account, or @name from the address book.
+        /// The transaction is saved to --output-file for the fee payer to countersign with
+        /// countersign-transaction. It expires an hour after it is signed by default
         #[clap(
-            short,
             long,
-            help = indoc!{ r#"
-                Type arguments separated by commas
-
-                Example: 
-                'u8, u16, u32, u64, u128, u256, bool, address, vector<u8>, signer'
-                '0x1::aptos_coin::AptosCoin'
-            "#}
+            requires = "output_file",
+            conflicts_with_all = [
+                "submit",
+                "simulate",
+                "output_format",
+                "fee_payer_private_key",
+                "fee_payer_private_key_file",
+            ]
         )]
-        type_args: Option<String>,
+        fee_payer_address: Option<String>,
 
+        /// Build the transaction without signing it and save it to this file, to sign it with
+        /// sign-transaction, e.g. on an air-gapped machine. It expires an hour after it is built by default
         #[clap(
-            short,
             long,
-            help = indoc!{ r#"
-                Function arguments separated by commas
-
-                Example:
-                '0x1, true, 12, 24_u8, x"123456"'
-            "#}
+            requires = "sender",
+            conflicts_with_all = [
+                "submit",
+                "simulate",
+                "output_file",
+                "private_key",
+                "private_key_file",
+                "mnemonic",
+                "keystore",
+                "private_key_stdin",
+                "ledger",
+                "secondary_private_key",
+                "secondary_private_key_file",
+                "fee_payer_private_key",
+                "fee_payer_private_key_file",
+                "fee_payer_address",
+            ]
         )]
-        args: Option<String>,
+        unsigned_output: Option<PathBuf>,
+
+        /// Address of the sender of the transaction built with --unsigned-output, or @name from the address book
+        #[clap(long, requires = "unsigned_output")]
+        sender: Option<String>,
+
+        /// Overwrite the output files if they already exist, and submit even when the balance of the sender cannot pay the worst-case fee
+        #[clap(long)]
+        force: bool,
+
+        /// Do not check the function id, type arguments and arguments against the module ABI before signing
+        #[clap(long)]
+        skip_abi_check: bool,
     },
-}
 
-impl TxsCli {
-    pub async fn run(&self) -> Result<()> {
-        match &self.subcommand {
-            Some(Subcommand::Demo) => demo::run().await,
-            Some(Subcommand::CreateAccount {
-                account_address,
-                coins,
-            }) => create_account::run(account_address, coins.unwrap_or_default()).await,
-            Some(Subcommand::TransferCoins {
-                to_account,
-                amount,
-                private_key,
-                max_gas,
-                gas_unit_price,
-            }) => {
-                transfer_coin::run(
-                    to_account,
-                    amount.to_owned(),
-                    private_key,
-                    max_gas.to_owned(),
-                    gas_unit_price.to_owned(),
-                )
-                .await
-            }
-            Some(Subcommand::GenerateTransaction {
-                function_id,
-                type_args,
-                args,
-                max_gas,
-                gas_unit_price,
-                private_key,
-                submit,
-            }) => {
-                println!("====================");
-                let signed_trans = generate_transaction::run(
-                    function_id,
-                    private_key,
-                    type_args.to_owned(),
-                    args.to_owned(),
-                    max_gas.to_owned(),
-                    gas_unit_price.to_owned(),
-                )
-                .await?;
+    /// Countersign as the fee payer a transaction saved with generate-transaction --fee-payer-address,
+    /// without any network access
+    CountersignTransaction {
+        /// Path of the sponsored transaction file
+        #[clap(short, long)]
+        file: PathBuf,
 
-                println!("{}", format_signed_transaction(&signed_trans));
+        /// Key of the fee payer
+        #[clap(flatten)]
+        private_key_options: PrivateKeyOptions,
 
-                if *submit {
-                    println!("{}", "Submitting transaction...".green().bold());
-                    submit_transaction::run(&signed_trans).await?;
-                    println!("Success!");
-                }
-                Ok(())
-            }
-            Some(Subcommand::View {
-                function_id,
-                type_args,
-                args,
-            }) => {
-                println!("====================");
-                println!(
-                    "{}",
-                    view::run(function_id, type_args.to_owned(), args.to_owned()).await?
-                );
-                Ok(())
-            }
-            _ => Ok(()),
+        /// Where to save the BCS encoded signed transaction, to submit it with submit-transaction
+        #[clap(short, long)]
+        output_file: PathBuf,
+
+        /// Overwrite the output file if it already exists
+        #[clap(long)]
+        force: bool,
+    },
+
+    /// Sign a transaction saved with generate-transaction --unsigned-output, without any network access
+    SignTransaction {
+        /// Path of the unsigned transaction file
+        #[clap(short, long)]
+        file: PathBuf,
+
+        #[clap(flatten)]
+        private_key_options: PrivateKeyOptions,
+
+        /// Where to save the BCS encoded signed transaction, to submit it with submit-transaction
+        #[clap(short, long)]
+        output_file: PathBuf,
+
+        /// Overwrite the output file if it already exists
+        #[clap(long)]
+        force: bool,
+    },
+
+    /// Submit a signed transaction saved with generate-transaction --output-file
+    SubmitTransaction {
+        /// Path of the BCS encoded signed transaction, or - to read it from stdin
+        #[clap(short, long)]
+        file: PathBuf,
+
+        #[clap(flatten)]
+        wait_options: WaitOptions,
+    },
+
+    /// Replace a transaction stuck in the mempool with the same one at a higher gas unit price, unless it was committed meanwhile
+    Bump {
+        /// Hash of the pending transaction, looked up in the mempool of the node
+        #[clap(long, required_unless_present = "file", conflicts_with = "file")]
+        hash: Option<String>,
+
+        /// Path of the BCS encoded signed transaction, e.g. when the node no longer has it
+        #[clap(long)]
+        file: Option<PathBuf>,
+
+        /// The gas unit price of the replacement, strictly higher than the one of the original
+        #[clap(short, long)]
+        gas_unit_price: u64,
+
+        #[clap(flatten)]
+        expiration_options: ExpirationOptions,
+
+        #[clap(flatten)]
+        private_key_options: PrivateKeyOptions,
+
+        #[clap(flatten)]
+        ledger_options: LedgerOptions,
+
+        #[clap(flatten)]
+        wait_options: WaitOptions,
+
+        /// Submit the replacement without asking for confirmation, required when stdin is not a terminal
+        #[clap(short, long)]
+        yes: bool,
+    },
+
+    /// Print the hash of a signed transaction, the same the node will give it, without any
+    /// network access. Announce it before handing the file over to be submitted
+    HashTransaction {
+        /// Path of the BCS encoded signed transaction, or - to read it from stdin
+        #[clap(short, long)]
+        file: PathBuf,
+    },
+
+    /// Check a signed transaction before submitting it, e.g. one signed by someone else: its
+    /// signatures, the keys of its signers, its expiration and its chain id
+    VerifyTransaction {
+        /// Path of the BCS encoded signed transaction, or - to read it from stdin
+        #[clap(short, long)]
+        file: PathBuf,
+
+        /// Check the key of the sender against this Ed25519 public key instead of the
+        /// authentication key on-chain, without network access
+        #[clap(long)]
+        expected_public_key: Option<String>,
+    },
+
+    /// Sign an off-chain message, e.g. to prove control of an address. The message is signed
+    /// behind a prefix, so the signature can never be used for a transaction
+    SignMessage {
+        #[clap(flatten)]
+        message_options: message::MessageOptions,
+
+        #[clap(flatten)]
+        private_key_options: PrivateKeyOptions,
+    },
+
+    /// Check the signature of a message made with sign-message
+    VerifyMessage {
+        #[clap(flatten)]
+        message_options: message::MessageOptions,
+
+        /// The signature printed by sign-message, as hex
+        #[clap(long)]
+        signature: String,
+
+        /// Ed25519 public key of the signer, printed by sign-message. The signature cannot be checked without it
+        #[clap(long)]
+        public_key: String,
+
+        /// Also check on-chain that the public key is the key of this account, as an address or @name
+        #[clap(long)]
+        address: Option<String>,
+    },
+
+    /// Look up a transaction by hash, e.g. one submitted with --no-wait
+    TxStatus {
+        /// Hash of the transaction, with or without the 0x prefix
+        hash: String,
+
+        /// Poll the node until the transaction is no longer pending
+        #[clap(long)]
+        wait: bool,
+    },
+
+    /// Vouch for another account, or revoke a vouch given before
+    Vouch {
+        /// Address of the account to vouch for, or @name from the address book
+        #[clap(short, long)]
+        for_account: String,
+
+        /// Revoke the vouch for the account instead
+        #[clap(long)]
+        revoke: bool,
+
+        #[clap(flatten)]
+        private_key_options: PrivateKeyOptions,
+
+        #[clap(flatten)]
+        gas_options: GasOptions,
+
+        #[clap(flatten)]
+        wait_options: WaitOptions,
+
+        /// Send the transaction without asking for confirmation, required when stdin is not a terminal
+        #[clap(short, long)]
+        yes: bool,
+    },
+
+    /// The vouches an account received and gave, with the epoch of each and whether it expired. Both unless --given or --received
+    VouchList {
+        /// Address of the account, or @name from the address book
+        #[clap(short, long)]
+        account: String,
+
+        /// Only the accounts the account vouches for
+        #[clap(long, conflicts_with = "received")]
+        given: bool,
+
+        /// Only the accounts vouching for the account
+        #[clap(long)]
+        received: bool,
+    },
+
+    /// Whether a validator is jailed, with its consecutive failures to rejoin the validator set
+    JailStatus {
+        /// Address of the validator, or @name from the address book
+        #[clap(short, long)]
+        account: String,
+    },
+
+    /// Unjail a validator with 0x1::jail::unjail_by_voucher, as one of its vouchers. Checks it is jailed and the signer vouches for it before signing
+    Unjail {
+        /// Address of the jailed validator, or @name from the address book
+        #[clap(short, long)]
+        for_account: String,
+
+        #[clap(flatten)]
+        private_key_options: PrivateKeyOptions,
+
+        #[clap(flatten)]
+        gas_options: GasOptions,
+
+        #[clap(flatten)]
+        wait_options: WaitOptions,
+
+        /// Send the transaction without asking for confirmation, required when stdin is not a terminal
+        #[clap(short, long)]
+        yes: bool,
+    },
+
+    /// Whether the burns of an account are recycled to the match index or burned, with its lifetime burns from its burn tracker
+    BurnPreference {
+        /// Address of the account, or @name from the address book
+        #[clap(short, long)]
+        account: String,
+    },
+
+    /// Choose whether the burns of the signer are recycled to the match index of the community wallets or burned, with 0x1::burn::set_send_community
+    SetBurnPreference {
+        /// Recycle the burns to the match index
+        #[clap(long, required_unless_present = "burn", conflicts_with = "burn")]
+        recycle: bool,
+
+        /// Burn them, which is what the framework does until a preference is set
+        #[clap(long)]
+        burn: bool,
+
+        #[clap(flatten)]
+        private_key_options: PrivateKeyOptions,
+
+        #[clap(flatten)]
+        gas_options: GasOptions,
+
+        #[clap(flatten)]
+        wait_options: WaitOptions,
+
+        /// Send the transaction without asking for confirmation, required when stdin is not a terminal
+        #[clap(short, long)]
+        yes: bool,
+    },
+
+    /// Update the proof-of-fee bid of a validator for the coming epochs, or retract it
+    PofBid {
+        /// The bid, in basis points of the validator's reward
+        #[clap(short, long, required_unless_present = "retract")]
+        bid: Option<u64>,
+
+        /// The last epoch the bid is valid for, it must be after the current epoch
+        #[clap(short, long, required_unless_present = "retract")]
+        expiry_epoch: Option<u64>,
+
+        /// Retract the current bid instead
+        #[clap(long, conflicts_with_all = ["bid", "expiry_epoch"])]
+        retract: bool,
+
+        #[clap(flatten)]
+        private_key_options: PrivateKeyOptions,
+
+        #[clap(flatten)]
+        gas_options: GasOptions,
+
+        #[clap(flatten)]
+        wait_options: WaitOptions,
+
+        /// Send the transaction without asking for confirmation, required when stdin is not a terminal
+        #[clap(short, long)]
+        yes: bool,
+    },
+
+    /// Enter the validator set, after checking the validator has vouches, a proof-of-fee bid and is not jailed
+    ValidatorJoin {
+        #[clap(flatten)]
+        private_key_options: PrivateKeyOptions,
+
+        #[clap(flatten)]
+        gas_options: GasOptions,
+
+        #[clap(flatten)]
+        wait_options: WaitOptions,
+
+        /// Send the transaction without asking for confirmation, required when stdin is not a terminal
+        #[clap(short, long)]
+        yes: bool,
+    },
+
+    /// Exit the validator set, the validator stops earning rewards
+    ValidatorLeave {
+        #[clap(flatten)]
+        private_key_options: PrivateKeyOptions,
+
+        #[clap(flatten)]
+        gas_options: GasOptions,
+
+        #[clap(flatten)]
+        wait_options: WaitOptions,
+
+        /// Send the transaction without asking for confirmation, required when stdin is not a terminal
+        #[clap(short, long)]
+        yes: bool,
+    },
+
+    /// Propose a payment from a community wallet, as one of its authorities
+    CommunityTransfer {
+        /// Address of the community wallet, or @name from the address book
+        #[clap(short, long)]
+        wallet: String,
+
+        /// Address of the recipient of the payment, or @name from the address book
+        #[clap(short, long)]
+        recipient: String,
+
+        /// The amount of coins to pay, e.g. 1.5 or 12_000.25
+        #[clap(short, long)]
+        amount: String,
+
+        /// What the payment is for
+        #[clap(short, long, default_value = "")]
+        description: String,
+
+        #[clap(flatten)]
+        private_key_options: PrivateKeyOptions,
+
+        #[clap(flatten)]
+        gas_options: GasOptions,
+
+        #[clap(flatten)]
+        wait_options: WaitOptions,
+
+        /// Send the transaction without asking for confirmation, required when stdin is not a terminal
+        #[clap(short, long)]
+        yes: bool,
+    },
+
+    /// Veto a payment proposed from a community wallet, as one of its donors
+    CommunityVeto {
+        /// Address of the community wallet, or @name from the address book
+        #[clap(short, long)]
+        wallet: String,
+
+        /// Id of the proposed payment
+        #[clap(long)]
+        proposal_id: u64,
+
+        #[clap(flatten)]
+        private_key_options: PrivateKeyOptions,
+
+        #[clap(flatten)]
+        gas_options: GasOptions,
+
+        #[clap(flatten)]
+        wait_options: WaitOptions,
+
+        /// Send the transaction without asking for confirmation, required when stdin is not a terminal
+        #[clap(short, long)]
+        yes: bool,
+    },
+
+    /// Vote on an on-chain governance proposal, after showing its metadata and current tally
+    Vote {
+        /// Id of the governance proposal
+        #[clap(long)]
+        proposal_id: u64,
+
+        /// Vote for the proposal
+        #[clap(long, required_unless_present = "against")]
+        should_pass: bool,
+
+        /// Vote against the proposal
+        #[clap(long, conflicts_with = "should_pass")]
+        against: bool,
+
+        #[clap(flatten)]
+        private_key_options: PrivateKeyOptions,
+
+        #[clap(flatten)]
+        gas_options: GasOptions,
+
+        #[clap(flatten)]
+        wait_options: WaitOptions,
+
+        /// Send the transaction without asking for confirmation, required when stdin is not a terminal
+        #[clap(short, long)]
+        yes: bool,
+    },
+
+    /// Rotate the authentication key of the account to a new key, then check on-chain that it changed.
+    /// Both keys sign the rotation, so the new private key is needed, its public key alone is not enough
+    RotateKey {
+        /// The new private key
+        #[clap(long, required_unless_present = "new_private_key_file")]
+        new_private_key: Option<Secret>,
+
+        /// Path of a file holding the new private key, either raw hex or the private-keys.yaml generated by libra-wallet
+        #[clap(long, conflicts_with = "new_private_key")]
+        new_private_key_file: Option<PathBuf>,
+
+        /// Do not offer to replace the old key with the new one in the profiles of the config
+        #[clap(long)]
+        no_update_profile: bool,
+
+        #[clap(flatten)]
+        private_key_options: PrivateKeyOptions,
+
+        #[clap(flatten)]
+        gas_options: GasOptions,
+
+        #[clap(flatten)]
+        wait_options: WaitOptions,
+
+        /// Send the transaction and update the profiles without asking for confirmation, required when stdin is not a terminal
+        #[clap(short, long)]
+        yes: bool,
+    },
+
+    /// Publish a Move package, built from its sources unless --skip-build is set
+    Publish {
+        /// Directory of the package, holding its Move.toml
+        #[clap(long, default_value = ".")]
+        package_dir: PathBuf,
+
+        /// Addresses for the named addresses of the package, e.g. my_addr=0x123,other=@alice
+        #[clap(long, conflicts_with = "skip_build")]
+        named_addresses: Option<String>,
+
+        /// Publish the bytecode and metadata built before in <PACKAGE_DIR>/build, e.g. by the Move CLI
+        #[clap(long)]
+        skip_build: bool,
+
+        #[clap(flatten)]
+        private_key_options: PrivateKeyOptions,
+
+        #[clap(flatten)]
+        gas_options: GasOptions,
+
+        /// Dry run the transaction on the node first, the package is only published if the simulation succeeds
+        #[clap(long)]
+        simulate: bool,
+
+        #[clap(flatten)]
+        wait_options: WaitOptions,
+
+        /// Send the transaction without asking for confirmation, required when stdin is not a terminal
+        #[clap(short, long)]
+        yes: bool,
+    },
+
+    /// Encrypt private keys with a password, usable with --keystore
+    Keystore {
+        #[clap(subcommand)]
+        command: keystore::KeystoreCommand,
+    },
+
+    /// Send transactions from a k-of-n MultiEd25519 account, its owners approving a proposal in turn
+    Multisig {
+        #[clap(subcommand)]
+        command: multisig::MultisigCommand,
+    },
+
+    /// Check the setup end to end: the config, the key, the node and the account. Fails when a critical check fails
+    Doctor,
+
+    /// Keep the next sequence number of an account in a local file, usable with --nonce-file
+    Nonce {
+        #[clap(subcommand)]
+        command: nonce::NonceCommand,
+    },
+
+    /// Manage the ABI cache, the modules of the node kept under ~/.0L to check calls and explain aborts
+    Cache {
+        #[clap(subcommand)]
+        command: cache::CacheCommand,
+    },
+
+    /// Show the profiles of the config, usable with --profile
+    Profile {
+        #[clap(subcommand)]
+        command: profile::ProfileCommand,
+    },
+
+    /// Manage the address book, names usable as @name wherever an address is expected
+    Address {
+        #[clap(subcommand)]
+        command: address::AddressCommand,
+    },
+
+    /// Run subcommands one after another at a prompt, keeping the node, the profile and the unlocked keystores between them, with history and tab completion
+    Repl,
+
+    /// Print the completion script of the shell, e.g. `txs completions zsh > ~/.zfunc/_txs`
+    Completions {
+        #[clap(value_enum)]
+        shell: clap_complete::Shell,
+    },
+
+    /// Print the profile names or the address book names, for the completion scripts
+    #[clap(name = "__complete", hide = true)]
+    Complete {
+        #[clap(value_enum)]
+        values: completions::CompletionValues,
+    },
+
+    /// Execute a View function on-chain
+    View {
+        #[clap(
+            short,
+            long,
+            help = indoc!{r#"
+                Function identifier has the form <ADDRESS>::<MODULE_ID>::<FUNCTION_NAME>,
+                where <ADDRESS> can be @name from the address book
+
+                Example:
+                0x1::coin::balance
+            "#}
+        )]
+        function_id: String,
+
+        #[clap(
+            short,
+            long,
+            help = indoc!{ r#"
+                Type arguments separated by commas
+
+                Example: 
+                'u8, u16, u32, u64, u128, u256, bool, address, vector<u8>, signer'
+                '0x1::aptos_coin::AptosCoin'
+            "#}
+        )]
+        type_args: Option<String>,
+
+        #[clap(
+            short,
+            long,
+            help = indoc!{ r#"
+                Function arguments separated by commas. Strings are single or double quoted,
+                with backslash escapes, options are none or some(<argument>) and @name is an
+                address from the address book
+
+                Example:
+                '0x1, true, 12, 24_u8, x"123456", "hello, world", none, some(42)'
+            "#}
+        )]
+        args: Option<String>,
+
+        #[clap(
+            long,
+            conflicts_with = "args",
+            help = indoc!{ r#"
+                Function arguments as a JSON array, for strings with commas and nested or empty vectors.
+                Each element is typed by the function's ABI, or explicitly as {"type": ..., "value": ...}
+
+                Example:
+                '["0x1", "hello, world", [["0x12"], []], {"type": "u8", "value": 24}]'
+            "#}
+        )]
+        args_json: Option<String>,
+
+        #[clap(flatten)]
+        ledger_version: LedgerVersionOptions,
+
+        /// Print the values as the node returns them, instead of decoding them with the function's declared return types
+        #[clap(long)]
+        raw: bool,
+
+        /// Only print the value at this path of the returned values, as in [0].vals[2] or the JSON pointer /0/vals/2. Strings and numbers are printed bare in the text format
+        #[clap(long)]
+        query: Option<String>,
+    },
+}
+
+#[derive(clap::Args)]
+#[clap(group(
+    clap::ArgGroup::new("private_key_source")
+        .args(["private_key", "private_key_file", "mnemonic", "legacy_mnemonic", "keystore"])
+))]
+struct PrivateKeyOptions {
+    /// Private key to sign the transaction. Without any key flag the key is read from stdin with --private-key-stdin, else from the TXS_PRIVATE_KEY environment variable, else from the --profile profile of the config
+    #[clap(short, long)]
+    private_key: Option<Secret>,
+
+    /// Path of a file holding the private key, either raw hex or the private-keys.yaml generated by libra-wallet
+    #[clap(long)]
+    private_key_file: Option<PathBuf>,
+
+    /// BIP-39 mnemonic to derive the signing key from, using the Aptos path m/44'/637'/<DERIVATION_INDEX>'/0'/0'
+    #[clap(long)]
+    mnemonic: Option<Secret>,
+
+    /// Mnemonic of a 0L v5/v6 account, derived with the legacy 0L scheme. The transaction is sent from the account found on-chain for the key, e.g. its legacy address
+    #[clap(long)]
+    legacy_mnemonic: Option<Secret>,
+
+    /// Index of the account to derive from --mnemonic, or of the key of the Ledger device with --ledger
+    #[clap(long)]
+    derivation_index: Option<u32>,
+
+    /// Path of a keystore holding the private key encrypted with a password, e.g. written by `libra-wallet keygen --encrypt`
+    #[clap(long)]
+    keystore: Option<PathBuf>,
+
+    /// File holding the password of --keystore. Without it the password is read from the LIBRA_KEYSTORE_PASSWORD environment variable or asked for
+    #[clap(long, requires = "keystore")]
+    password_file: Option<PathBuf>,
+
+    /// Read the private key from stdin, e.g. piped from a secret manager. Ignored when a key flag is given. Needs --yes, stdin is then not a terminal
+    #[clap(long)]
+    private_key_stdin: bool,
+}
+
+#[derive(clap::Args)]
+struct LedgerOptions {
+    /// Sign with the Aptos app of a Ledger device, with the key at --derivation-index. Only in builds with the ledger feature
+    #[clap(long, conflicts_with_all = ["private_key", "private_key_file", "mnemonic", "legacy_mnemonic", "keystore", "private_key_stdin"])]
+    ledger: bool,
+}
+
+/// Keys of the secondary signers of a multi-agent transaction, in the order the function takes them.
+/// Keys given with --secondary-private-key come before the ones read from files
+#[derive(clap::Args)]
+struct SecondarySignerOptions {
+    /// Private key of a secondary signer, repeat it for each signer
+    #[clap(long)]
+    secondary_private_key: Vec<Secret>,
+
+    /// Path of a file holding the private key of a secondary signer, repeat it for each signer
+    #[clap(long)]
+    secondary_private_key_file: Vec<PathBuf>,
+}
+
+impl SecondarySignerOptions {
+    fn private_keys(&self) -> Result<Vec<Ed25519PrivateKey>> {
+        let mut private_keys = self
+            .secondary_private_key
+            .iter()
+            .map(|private_key| parse_private_key(private_key, "--secondary-private-key"))
+            .collect::<Result<Vec<_>>>()?;
+        for path in &self.secondary_private_key_file {
+            private_keys.push(Ed25519PrivateKey::from_key_file(path)?);
+        }
+        Ok(private_keys)
+    }
+}
+
+/// Key of the account paying the gas of the transaction instead of the sender
+#[derive(clap::Args)]
+struct FeePayerOptions {
+    /// Private key of the fee payer, which pays the gas of the transaction instead of the sender
+    #[clap(long, conflicts_with = "fee_payer_private_key_file")]
+    fee_payer_private_key: Option<Secret>,
+
+    /// Path of a file holding the private key of the fee payer
+    #[clap(long)]
+    fee_payer_private_key_file: Option<PathBuf>,
+}
+
+impl FeePayerOptions {
+    fn private_key(&self) -> Result<Option<Ed25519PrivateKey>> {
+        match (
+            &self.fee_payer_private_key,
+            &self.fee_payer_private_key_file,
+        ) {
+            (Some(private_key), _) => Ok(Some(parse_private_key(
+                private_key,
+                "--fee-payer-private-key",
+            )?)),
+            (None, Some(path)) => Ok(Some(Ed25519PrivateKey::from_key_file(path)?)),
+            (None, None) => Ok(None),
+        }
+    }
+}
+
+#[derive(clap::Args)]
+struct GasOptions {
+    /// Maximum amount of gas units to be used to send this transaction. Estimated by simulating the transaction when omitted
+    #[clap(short, long)]
+    max_gas: Option<u64>,
+
+    /// The amount of coins to pay for 1 gas unit. The higher the price is, the higher priority your transaction will be executed with
+    #[clap(short, long)]
+    gas_unit_price: Option<u64>,
+
+    /// Which of the node's estimates to use when --gas-unit-price is omitted
+    #[clap(long, value_enum, default_value_t = GasPriority::Normal, conflicts_with = "gas_unit_price")]
+    gas_priority: GasPriority,
+
+    /// Safety margin added on top of the simulated gas used when estimating the max gas
+    #[clap(long, default_value_t = DEFAULT_GAS_BUFFER_PERCENT, conflicts_with = "max_gas")]
+    gas_buffer_percent: u64,
+
+    #[clap(flatten)]
+    expiration_options: ExpirationOptions,
+}
+
+#[derive(clap::Args)]
+struct ExpirationOptions {
+    /// Seconds the transaction is valid for once built, e.g. to collect the signatures of several parties.
+    /// Defaults to 10s, to an hour with --unsigned-output or --fee-payer-address, and to at least 5 minutes with --ledger
+    #[clap(long, conflicts_with = "expire_at")]
+    expiration_secs: Option<u64>,
+
+    /// Time the transaction expires at, in RFC 3339 such as 2024-05-01T12:00:00Z
+    #[clap(long, value_parser = parse_expire_at)]
+    expire_at: Option<u64>,
+}
+
+impl ExpirationOptions {
+    /// The defaults with the expiration set
+    fn transaction_options(&self) -> TransactionOptions {
+        TransactionOptions {
+            timeout_secs: self.expiration_secs.unwrap_or(DEFAULT_TIMEOUT_SECS),
+            expire_at: self.expire_at,
+            ..TransactionOptions::default()
+        }
+    }
+}
+
+/// The Unix time of an RFC 3339 time in the future
+fn parse_expire_at(value: &str) -> Result<u64> {
+    let expire_at = DateTime::parse_from_rfc3339(value).context(format!(
+        "Invalid time {value}, expected RFC 3339 such as 2024-05-01T12:00:00Z"
+    ))?;
+    if expire_at <= Utc::now() {
+        bail!("{value} is already past");
+    }
+    Ok(expire_at.timestamp() as u64)
+}
+
+impl GasOptions {
+    fn transaction_options(&self) -> TransactionOptions {
+        TransactionOptions {
+            max_gas_amount: self.max_gas,
+            gas_buffer_percent: self.gas_buffer_percent,
+            gas_unit_price: self.gas_unit_price,
+            gas_priority: self.gas_priority,
+            ..self.expiration_options.transaction_options()
+        }
+    }
+}
+
+#[derive(clap::Args)]
+struct SequenceOptions {
+    /// Sign with this sequence number instead of the one of the account on-chain, e.g. to sign ahead of pending transactions
+    #[clap(long, conflicts_with = "nonce_file")]
+    sequence_number: Option<u64>,
+
+    /// File holding the next sequence number of the sender, taken and incremented for each transaction saved or submitted.
+    /// Create it with `txs nonce sync`
+    #[clap(long)]
+    nonce_file: Option<PathBuf>,
+}
+
+impl SequenceOptions {
+    /// The options with the sequence number to sign with. When it comes from --nonce-file, the
+    /// lease is to be committed once the transaction is saved or submitted
+    fn apply(
+        &self,
+        options: TransactionOptions,
+    ) -> Result<(TransactionOptions, Option<NonceLease>)> {
+        let lease = self
+            .nonce_file
+            .as_deref()
+            .map(NonceLease::take)
+            .transpose()?;
+        let sequence_number = self
+            .sequence_number
+            .or(lease.as_ref().map(|lease| lease.sequence_number));
+        Ok((
+            TransactionOptions {
+                sequence_number,
+                ..options
+            },
+            lease,
+        ))
+    }
+}
+
+#[derive(clap::Args)]
+struct BatchOptions {
+    /// Where to save the result of each row, defaults to <CSV>.results.json. Rows it records as sent are skipped, so a failed batch can be rerun
+    #[clap(long)]
+    results_file: Option<PathBuf>,
+
+    /// Take the amounts as integer numbers of base units, as is
+    #[clap(long)]
+    raw: bool,
+
+    /// Keep sending the remaining rows after a transfer fails, instead of stopping
+    #[clap(long)]
+    continue_on_error: bool,
+
+    /// Number of transactions signed ahead with consecutive sequence numbers and submitted at once.
+    /// Capped by the number of transactions the mempool accepts per account
+    #[clap(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..=100))]
+    concurrency: u64,
+
+    /// Maximum number of transactions submitted per second
+    #[clap(long)]
+    tps_limit: Option<f64>,
+}
+
+#[derive(clap::Args)]
+struct WaitOptions {
+    /// How long to wait for the submitted transaction to be committed. On timeout the command exits with code 7
+    #[clap(long, default_value_t = DEFAULT_WAIT_TIMEOUT_SECS)]
+    wait_timeout_secs: u64,
+
+    /// How often to poll the node while waiting for the transaction to be committed
+    #[clap(long, default_value_t = DEFAULT_POLL_INTERVAL_MS)]
+    poll_interval_ms: u64,
+
+    /// Only print the transaction hash once the node accepted it, without waiting for it to be committed
+    #[clap(long, conflicts_with_all = ["wait_timeout_secs", "poll_interval_ms", "receipt_format"])]
+    no_wait: bool,
+
+    /// Format of the receipt printed once the transaction is committed: its gas, fee, events and state changes
+    #[clap(long, value_enum, default_value_t = OutputFormat::Text)]
+    receipt_format: OutputFormat,
+}
+
+impl PrivateKeyOptions {
+    /// The key to sign with, falling back to the key of the profile
+    fn private_key(&self, profile: Option<&str>) -> Result<Ed25519PrivateKey> {
+        if self.derivation_index.is_some() && self.mnemonic.is_none() {
+            bail!("--derivation-index is only used with --mnemonic or --ledger");
+        }
+        if let Some(mnemonic) = &self.legacy_mnemonic {
+            if self.private_key.is_some()
+                || self.private_key_file.is_some()
+                || self.mnemonic.is_some()
+                || self.keystore.is_some()
+            {
+                bail!("--legacy-mnemonic cannot be used with another key flag");
+            }
+            return Ed25519PrivateKey::from_legacy_mnemonic(mnemonic.expose());
+        }
+        match (
+            &self.private_key,
+            &self.private_key_file,
+            &self.mnemonic,
+            &self.keystore,
+        ) {
+            (Some(private_key), None, None, None) => parse_private_key(private_key, "--private-key"),
+            (None, Some(path), None, None) => Ed25519PrivateKey::from_key_file(path),
+            (None, None, Some(mnemonic), None) => Ed25519PrivateKey::from_mnemonic(
+                mnemonic.expose(),
+                self.derivation_index.unwrap_or_default(),
+            ),
+            (None, None, None, Some(path)) => match repl::unlocked_keystore(path) {
+                Some(private_key) => Ok(private_key),
+                None => {
+                    let private_key = load_private_key(path, self.password_file.as_deref())?;
+                    repl::keep_unlocked_keystore(path, &private_key);
+                    Ok(private_key)
+                }
+            },
+            (None, None, None, None) => self.fallback_private_key(profile),
+            _ => bail!(
+                "Exactly one of --private-key, --private-key-file, --mnemonic or --keystore must be provided"
+            ),
+        }
+    }
+
+    /// The key when no key flag is given: from stdin with --private-key-stdin, else from the
+    /// environment, else from the profile of the config
+    fn fallback_private_key(&self, profile: Option<&str>) -> Result<Ed25519PrivateKey> {
+        if self.private_key_stdin {
+            let mut private_key = String::new();
+            std::io::stdin()
+                .read_to_string(&mut private_key)
+                .context("Failed to read the private key from stdin")?;
+            return parse_private_key(&Secret::new(private_key), "stdin");
+        }
+        if let Ok(private_key) = std::env::var(PRIVATE_KEY_ENV) {
+            return parse_private_key(&Secret::new(private_key), PRIVATE_KEY_ENV);
+        }
+        let private_key = profile_private_key(profile)?;
+        if let (Some(_), Some(config_file)) = (&private_key, config_file_path()?) {
+            check_key_file_permissions(&config_file, strict_permissions())?;
+        }
+        private_key.context(format!(
+            "No private key given, use --private-key, --private-key-file, --mnemonic, --keystore or --private-key-stdin, set {PRIVATE_KEY_ENV} or add a key to the {} profile of the config",
+            profile.unwrap_or("default")
+        ))
+    }
+
+    /// The key to sign with, or the Ledger device when --ledger is set
+    fn signer(
+        &self,
+        ledger_options: &LedgerOptions,
+        profile: Option<&str>,
+    ) -> Result<TransactionSigner> {
+        if ledger_options.ledger {
+            return Ok(TransactionSigner::Ledger(
+                self.derivation_index.unwrap_or_default(),
+            ));
+        }
+        Ok(TransactionSigner::PrivateKey(self.private_key(profile)?))
+    }
+}
+
+/// The library client of the node, on the chain the node reported when it was picked, which
+/// spares asking for it again
+pub async fn txs_client<N: NodeApi>(client: &N) -> Result<TxsClient<&N>> {
+    Ok(match abi_cache::known_chain_id() {
+        Some(chain_id) => TxsClient::from_client(client, ChainId::new(chain_id)),
+        None => TxsClient::connect(client).await?,
+    })
+}
+
+/// Print the error of a command as the binary does, a timeout as a single line
+pub fn print_error(err: &anyhow::Error) {
+    match timeout_error(err) {
+        Some(timeout) => eprintln!("Error: {timeout}"),
+        None => eprintln!("Error: {err:?}"),
+    }
+}
+
+impl TxsCli {
+    pub async fn run(&self) -> Result<()> {
+        set_color(if self.no_color {
+            ColorChoice::Never
+        } else {
+            self.color
+        });
+        // Set up once for a REPL, by the flags it was started with
+        if !repl::in_session() {
+            logging::init(self.verbose, self.log_file.as_deref())?;
+        }
+        retry::set_max_retries(self.max_retries);
+        set_strict_permissions(self.strict_permissions);
+        set_explorer(self.explorer());
+        set_journal(self.journal.clone().map(|path| Journal {
+            path,
+            allow_duplicate: self.allow_duplicate,
+        }));
+        set_timeouts(self.connect_timeout_secs, self.request_timeout_secs);
+        abi_cache::set_refresh(self.refresh_abi);
+        match &self.subcommand {
+            Some(Subcommand::Demo {
+                amount,
+                fund_amount,
+                use_key,
+                recipient,
+                key_options,
+            }) => {
+                let sender = if *use_key {
+                    demo::Sender::Key(key_options.private_key(self.profile.as_deref())?)
+                } else {
+                    demo::Sender::Fresh(*fund_amount)
+                };
+                let recipient = recipient
+                    .as_deref()
+                    .map(|recipient| parse_address(recipient, "--recipient"))
+                    .transpose()?;
+                // Only the fresh accounts need a faucet
+                let faucet_client = match (&sender, recipient) {
+                    (demo::Sender::Key(_), Some(_)) => None,
+                    _ => Some(self.faucet_client()?),
+                };
+                demo::run(
+                    &self.client().await?,
+                    faucet_client.as_ref(),
+                    sender,
+                    recipient,
+                    *amount,
+                )
+                .await
+            }
+            Some(Subcommand::Selftest {
+                skip_writes,
+                account_address,
+                fund_amount,
+            }) => {
+                let account_address = account_address
+                    .as_deref()
+                    .map(|address| parse_address(address, "--account-address"))
+                    .transpose()?;
+                let faucet_client = if *skip_writes {
+                    None
+                } else {
+                    Some(self.faucet_client()?)
+                };
+                selftest::run(
+                    &self.client().await?,
+                    faucet_client.as_ref(),
+                    account_address,
+                    *fund_amount,
+                )
+                .await
+            }
+            Some(Subcommand::CreateAccount {
+                account_address,
+                coins,
+                batch_file,
+                results_file,
+                funder_private_key,
+                funder_private_key_file,
+                gas_options,
+                wait_options,
+                yes,
+            }) => {
+                let funder_private_key = match (funder_private_key, funder_private_key_file) {
+                    (Some(private_key), _) => {
+                        Some(parse_private_key(private_key, "--funder-private-key")?)
+                    }
+                    (None, Some(path)) => Some(Ed25519PrivateKey::from_key_file(path)?),
+                    (None, None) => None,
+                };
+                if let Some(batch_file) = batch_file {
+                    let client = self.client().await?;
+                    let faucet_client;
+                    let funding = match funder_private_key {
+                        Some(funder_private_key) => {
+                            Funding::Funder(funder_private_key.get_account(&client, None).await?)
+                        }
+                        None => {
+                            faucet_client = self.faucet_client()?;
+                            Funding::Faucet(&faucet_client)
+                        }
+                    };
+                    return batch_create_account::run(
+                        &client,
+                        batch_file,
+                        results_file.as_deref(),
+                        *coins,
+                        funding,
+                        gas_options.transaction_options(),
+                        wait_options,
+                        *yes,
+                    )
+                    .await;
+                }
+                // Without --batch-file clap requires it
+                let account_address = account_address.as_deref().unwrap_or_default();
+                match funder_private_key {
+                    Some(funder_private_key) => {
+                        create_account::run_with_funder(
+                            &self.client().await?,
+                            &funder_private_key,
+                            account_address,
+                            coins.context("--coins is required with a funder key")?,
+                            gas_options.transaction_options(),
+                            wait_options,
+                            *yes,
+                        )
+                        .await
+                    }
+                    None => {
+                        create_account::run(
+                            &self.faucet_client()?,
+                            account_address,
+                            coins.unwrap_or_default(),
+                        )
+                        .await
+                    }
+                }
+            }
+            Some(Subcommand::RegisterCoin {
+                coin_type,
+                private_key_options,
+                gas_options,
+                wait_options,
+                yes,
+            }) => {
+                register_coin::run(
+                    &self.client().await?,
+                    coin_type,
+                    &private_key_options.private_key(self.profile.as_deref())?,
+                    gas_options.transaction_options(),
+                    wait_options,
+                    *yes,
+                )
+                .await
+            }
+            Some(Subcommand::TransferCoins {
+                to_account,
+                amount,
+                all,
+                raw,
+                coin_type,
+                fa_metadata,
+                private_key_options,
+                ledger_options,
+                fee_payer_options,
+                gas_options,
+                sequence_options,
+                wait_options,
+                force,
+                yes,
+            }) => {
+                let (options, nonce_lease) =
+                    sequence_options.apply(gas_options.transaction_options())?;
+                transfer_coin::run(
+                    &self.client().await?,
+                    to_account,
+                    amount.as_deref(),
+                    *raw,
+                    coin_type.as_deref(),
+                    fa_metadata.as_deref(),
+                    &private_key_options.signer(ledger_options, self.profile.as_deref())?,
+                    fee_payer_options.private_key()?.as_ref(),
+                    options,
+                    nonce_lease,
+                    wait_options,
+                    *force,
+                    *yes,
+                )
+                .await
+            }
+            Some(Subcommand::BatchTransfer {
+                csv,
+                batch_options,
+                private_key_options,
+                gas_options,
+                wait_options,
+                yes,
+            }) => {
+                batch_transfer::run(
+                    &self.client().await?,
+                    csv,
+                    batch_options,
+                    &private_key_options.private_key(self.profile.as_deref())?,
+                    gas_options.transaction_options(),
+                    wait_options,
+                    *yes,
+                )
+                .await
+            }
+            Some(Subcommand::GenerateTransaction {
+                function_id,
+                script_file,
+                type_args,
+                args,
+                args_json,
+                gas_options,
+                sequence_options,
+                private_key_options,
+                ledger_options,
+                secondary_signer_options,
+                fee_payer_options,
+                submit,
+                wait_options,
+                yes,
+                simulate,
+                output_file,
+                output_format,
+                fee_payer_address,
+                unsigned_output,
+                sender,
+                force,
+                skip_abi_check,
+            }) => {
+                let args = FunctionArgs::from_options(args.to_owned(), args_json.to_owned());
+                let (options, mut nonce_lease) =
+                    sequence_options.apply(gas_options.transaction_options())?;
+                if let (Some(unsigned_output), Some(sender)) = (unsigned_output, sender) {
+                    let client = self.client().await?;
+                    println!("====================");
+                    let raw_trans = generate_transaction::run_unsigned(
+                        &client,
+                        parse_address(sender, "--sender")?,
+                        function_id.as_deref(),
+                        script_file.as_deref(),
+                        type_args.to_owned(),
+                        args,
+                        TransactionOptions {
+                            timeout_secs: gas_options
+                                .expiration_options
+                                .expiration_secs
+                                .unwrap_or(DEFAULT_UNSIGNED_EXPIRATION_SECS),
+                            ..options
+                        },
+                        !skip_abi_check,
+                    )
+                    .await?;
+                    let unsigned_trans = UnsignedTransaction::new(&raw_trans)?;
+                    unsigned_trans.save(unsigned_output, *force)?;
+                    if let Some(nonce_lease) = nonce_lease {
+                        nonce_lease.commit(raw_trans.sender())?;
+                    }
+                    println!(
+                        "Unsigned transaction saved to {}\nSigning message hash: {}\nSign it with `txs sign-transaction --file {}`",
+                        unsigned_output.display(),
+                        unsigned_trans.signing_message_hash,
+                        unsigned_output.display()
+                    );
+                    return Ok(());
+                }
+
+                let signer = private_key_options.signer(ledger_options, self.profile.as_deref())?;
+                let secondary_private_keys = secondary_signer_options.private_keys()?;
+                let fee_payer_private_key = fee_payer_options.private_key()?;
+                let client = self.client().await?;
+                println!("====================");
+                if let (Some(fee_payer_address), Some(output_file)) =
+                    (fee_payer_address, output_file)
+                {
+                    let sponsored_trans = generate_transaction::run_for_fee_payer(
+                        &client,
+                        function_id.as_deref(),
+                        script_file.as_deref(),
+                        &signer,
+                        &secondary_private_keys,
+                        parse_address(fee_payer_address, "--fee-payer-address")?,
+                        type_args.to_owned(),
+                        args,
+                        TransactionOptions {
+                            timeout_secs: gas_options
+                                .expiration_options
+                                .expiration_secs
+                                .unwrap_or(DEFAULT_COUNTERSIGN_EXPIRATION_SECS),
+                            ..options
+                        },
+                        !skip_abi_check,
+                    )
+                    .await?;
+                    sponsored_trans.save(output_file, *force)?;
+                    if let Some(nonce_lease) = nonce_lease {
+                        nonce_lease.commit(signer.address(&client).await?)?;
+                    }
+                    println!(
+                        "Sender: {}\nGas payer: {} (fee payer)\nTransaction saved to {}, the fee payer countersigns it with `txs countersign-transaction --file {}`",
+                        sponsored_trans.sender,
+                        sponsored_trans.fee_payer_address,
+                        output_file.display(),
+                        output_file.display()
+                    );
+                    return Ok(());
+                }
+
+                let (signed_trans, detail) = match (function_id, script_file) {
+                    (_, Some(script_file)) => (
+                        generate_transaction::run_script(
+                            &client,
+                            script_file,
+                            &signer,
+                            &secondary_private_keys,
+                            fee_payer_private_key.as_ref(),
+                            type_args.to_owned(),
+                            args,
+                            options,
+                        )
+                        .await?,
+                        ("Script", script_file.display().to_string()),
+                    ),
+                    (Some(function_id), None) => (
+                        generate_transaction::run(
+                            &client,
+                            function_id,
+                            &signer,
+                            &secondary_private_keys,
+                            fee_payer_private_key.as_ref(),
+                            type_args.to_owned(),
+                            args,
+                            options,
+                            !skip_abi_check,
+                        )
+                        .await?,
+                        ("Function", function_id.to_owned()),
+                    ),
+                    (None, None) => bail!("One of --function-id or --script-file is required"),
+                };
+
+                let params = client.payload_params(signed_trans.payload()).await;
+                self.print_signed_transaction(&signed_trans, params.as_deref())?;
+
+                // The sequence number is only used up once the transaction leaves this process
+                if let Some(output_file) = output_file {
+                    signed_trans.save(output_file, *force)?;
+                    if let Some(nonce_lease) = nonce_lease.take() {
+                        nonce_lease.commit(signed_trans.sender())?;
+                    }
+                    println!(
+                        "Signed transaction {} saved to {}",
+                        transaction_hash(&signed_trans).to_hex_literal(),
+                        output_file.display()
+                    );
+                    if let Some(output_format) = output_format {
+                        let summary_file = signed_trans.save_summary(
+                            output_file,
+                            *output_format,
+                            params.as_deref(),
+                            *force,
+                        )?;
+                        println!("Summary saved to {}", summary_file.display());
+                    }
+                }
+
+                if *simulate {
+                    let simulation = simulate_transaction::run(&client, &signed_trans).await;
+                    if *submit {
+                        simulation.context("Aborted, the transaction was not submitted")?;
+                    } else {
+                        simulation?;
+                    }
+                }
+
+                if *submit {
+                    if !force {
+                        Funds::get(&client, gas_payer(&signed_trans), None)
+                            .await?
+                            .check(0, max_fee(&signed_trans))
+                            .context("The transaction was not submitted, pass --force to submit it anyway")?;
+                    }
+                    prompt::confirm_transaction(&signed_trans, &[detail], *yes)?;
+                    if let Some(nonce_lease) = nonce_lease.take() {
+                        nonce_lease.commit(signed_trans.sender())?;
+                    }
+                    println!("{}", "Submitting transaction...".green().bold());
+                    submit_transaction::run(&client, &signed_trans, wait_options).await?;
+                }
+                Ok(())
+            }
+            Some(Subcommand::CountersignTransaction {
+                file,
+                private_key_options,
+                output_file,
+                force,
+            }) => {
+                let sponsored_trans = SponsoredTransaction::load(file)?;
+                let private_key = private_key_options.private_key(self.profile.as_deref())?;
+                let key_address =
+                    AuthenticationKey::ed25519(&private_key.public_key()).derived_address();
+                if format_address(&key_address) != sponsored_trans.fee_payer_address {
+                    println!(
+                        "{} the key is not the original key of the fee payer {}, the signature is only valid if the account rotated to it",
+                        "Warning:".yellow().bold(),
+                        sponsored_trans.fee_payer_address
+                    );
+                }
+                let signed_trans = sponsored_trans.countersign(&private_key)?;
+                println!("{}", format_signed_transaction(&signed_trans));
+                signed_trans.save(output_file, *force)?;
+                println!(
+                    "Signed transaction saved to {}, submit it with `txs submit-transaction --file {}`",
+                    output_file.display(),
+                    output_file.display()
+                );
+                Ok(())
+            }
+            Some(Subcommand::SignTransaction {
+                file,
+                private_key_options,
+                output_file,
+                force,
+            }) => {
+                let unsigned_trans = UnsignedTransaction::load(file)?;
+                if let Some(expected_chain_id) = self.expected_chain_id() {
+                    check_chain_id(
+                        unsigned_trans.chain_id,
+                        expected_chain_id,
+                        self.allow_chain_id_mismatch,
+                    )
+                    .context("The transaction was built against another chain")?;
+                }
+                let private_key = private_key_options.private_key(self.profile.as_deref())?;
+                let key_address =
+                    AuthenticationKey::ed25519(&private_key.public_key()).derived_address();
+                if format_address(&key_address) != unsigned_trans.sender {
+                    println!(
+                        "{} the key is not the original key of the sender {}, the signature is only valid if the account rotated to it",
+                        "Warning:".yellow().bold(),
+                        unsigned_trans.sender
+                    );
+                }
+                println!(
+                    "Signing message hash: {}",
+                    unsigned_trans.signing_message_hash
+                );
+                let signed_trans = unsigned_trans.sign(&private_key)?;
+                self.print_signed_transaction(&signed_trans, None)?;
+                signed_trans.save(output_file, *force)?;
+                println!(
+                    "Signed transaction saved to {}, submit it with `txs submit-transaction --file {}`",
+                    output_file.display(),
+                    output_file.display()
+                );
+                Ok(())
+            }
+            Some(Subcommand::SubmitTransaction { file, wait_options }) => {
+                let signed_trans = SignedTransaction::load(file)?;
+                let client = self.client().await?;
+                let params = client.payload_params(signed_trans.payload()).await;
+                self.print_signed_transaction(&signed_trans, params.as_deref())?;
+                println!("{}", "Submitting transaction...".green().bold());
+                submit_transaction::run(&client, &signed_trans, wait_options).await
+            }
+            Some(Subcommand::Bump {
+                hash,
+                file,
+                gas_unit_price,
+                expiration_options,
+                private_key_options,
+                ledger_options,
+                wait_options,
+                yes,
+            }) => {
+                let original = match (hash, file) {
+                    (_, Some(file)) => bump::Original::File(file),
+                    (Some(hash), None) => bump::Original::Hash(hash),
+                    (None, None) => bail!("One of --hash or --file is required"),
+                };
+                bump::run(
+                    &self.client().await?,
+                    original,
+                    *gas_unit_price,
+                    &private_key_options.signer(ledger_options, self.profile.as_deref())?,
+                    expiration_options.transaction_options(),
+                    wait_options,
+                    *yes,
+                )
+                .await
+            }
+            Some(Subcommand::HashTransaction { file }) => {
+                let signed_trans = SignedTransaction::load(file)?;
+                println!("{}", transaction_hash(&signed_trans).to_hex_literal());
+                Ok(())
+            }
+            Some(Subcommand::VerifyTransaction {
+                file,
+                expected_public_key,
+            }) => {
+                let signed_trans = SignedTransaction::load(file)?;
+                match expected_public_key {
+                    Some(public_key) => {
+                        let public_key =
+                            Ed25519PublicKey::from_encoded_string(public_key.trim())
+                                .context(format!("Invalid --expected-public-key {public_key}"))?;
+                        self.print_signed_transaction(&signed_trans, None)?;
+                        verify_transaction::run(
+                            &signed_trans,
+                            verify_transaction::Expected::Offline {
+                                public_key,
+                                chain_id: self.expected_chain_id(),
+                            },
+                        )
+                        .await
+                    }
+                    None => {
+                        let client = self.client().await?;
+                        let params = client.payload_params(signed_trans.payload()).await;
+                        self.print_signed_transaction(&signed_trans, params.as_deref())?;
+                        verify_transaction::run(
+                            &signed_trans,
+                            verify_transaction::Expected::OnChain(&client),
+                        )
+                        .await
+                    }
+                }
+            }
+            Some(Subcommand::SignMessage {
+                message_options,
+                private_key_options,
+            }) => {
+                let private_key = private_key_options.private_key(self.profile.as_deref())?;
+                message::sign(message_options, &private_key)
+            }
+            Some(Subcommand::VerifyMessage {
+                message_options,
+                signature,
+                public_key,
+                address,
+            }) => match address {
+                Some(address) => {
+                    let address = parse_address(address, "--address")?;
+                    let client = self.client().await?;
+                    message::verify(
+                        message_options,
+                        signature,
+                        public_key,
+                        Some((address, &client)),
+                    )
+                    .await
+                }
+                None => message::verify(message_options, signature, public_key, None).await,
+            },
+            Some(Subcommand::TxStatus { hash, wait }) => {
+                let client = self.client().await?;
+                println!(
+                    "{}",
+                    tx_status::run(&client, hash, *wait, self.format).await?
+                );
+                Ok(())
+            }
+            Some(Subcommand::Vouch {
+                for_account,
+                revoke,
+                private_key_options,
+                gas_options,
+                wait_options,
+                yes,
+            }) => {
+                vouch::run(
+                    &self.client().await?,
+                    for_account,
+                    *revoke,
+                    &private_key_options.private_key(self.profile.as_deref())?,
+                    gas_options.transaction_options(),
+                    wait_options,
+                    *yes,
+                )
+                .await
+            }
+            Some(Subcommand::VouchList {
+                account,
+                given,
+                received,
+            }) => {
+                let direction = match (given, received) {
+                    (true, _) => vouch::Direction::Given,
+                    (_, true) => vouch::Direction::Received,
+                    _ => vouch::Direction::Both,
+                };
+                let client = self.client().await?;
+                println!(
+                    "{}",
+                    vouch::list(&client, account, direction, self.format).await?
+                );
+                Ok(())
+            }
+            Some(Subcommand::JailStatus { account }) => {
+                let client = self.client().await?;
+                println!("{}", jail::status(&client, account, self.format).await?);
+                Ok(())
+            }
+            Some(Subcommand::Unjail {
+                for_account,
+                private_key_options,
+                gas_options,
+                wait_options,
+                yes,
+            }) => {
+                jail::unjail(
+                    &self.client().await?,
+                    for_account,
+                    &private_key_options.private_key(self.profile.as_deref())?,
+                    gas_options.transaction_options(),
+                    wait_options,
+                    *yes,
+                )
+                .await
+            }
+            Some(Subcommand::BurnPreference { account }) => {
+                let client = self.client().await?;
+                println!("{}", burn::preference(&client, account, self.format).await?);
+                Ok(())
+            }
+            Some(Subcommand::SetBurnPreference {
+                recycle,
+                burn: _,
+                private_key_options,
+                gas_options,
+                wait_options,
+                yes,
+            }) => {
+                burn::set_preference(
+                    &self.client().await?,
+                    *recycle,
+                    &private_key_options.private_key(self.profile.as_deref())?,
+                    gas_options.transaction_options(),
+                    wait_options,
+                    *yes,
+                )
+                .await
+            }
+            Some(Subcommand::PofBid {
+                bid,
+                expiry_epoch,
+                retract,
+                private_key_options,
+                gas_options,
+                wait_options,
+                yes,
+            }) => {
+                let action = match (bid, expiry_epoch) {
+                    (Some(bid), Some(expiry_epoch)) if !retract => pof_bid::BidAction::Update {
+                        bid: *bid,
+                        expiry_epoch: *expiry_epoch,
+                    },
+                    _ => pof_bid::BidAction::Retract,
+                };
+                pof_bid::run(
+                    &self.client().await?,
+                    action,
+                    &private_key_options.private_key(self.profile.as_deref())?,
+                    gas_options.transaction_options(),
+                    wait_options,
+                    *yes,
+                )
+                .await
+            }
+            Some(Subcommand::ValidatorJoin {
+                private_key_options,
+                gas_options,
+                wait_options,
+                yes,
+            }) => {
+                validator::run(
+                    &self.client().await?,
+                    validator::Membership::Join,
+                    &private_key_options.private_key(self.profile.as_deref())?,
+                    gas_options.transaction_options(),
+                    wait_options,
+                    *yes,
+                )
+                .await
+            }
+            Some(Subcommand::ValidatorLeave {
+                private_key_options,
+                gas_options,
+                wait_options,
+                yes,
+            }) => {
+                validator::run(
+                    &self.client().await?,
+                    validator::Membership::Leave,
+                    &private_key_options.private_key(self.profile.as_deref())?,
+                    gas_options.transaction_options(),
+                    wait_options,
+                    *yes,
+                )
+                .await
+            }
+            Some(Subcommand::CommunityTransfer {
+                wallet,
+                recipient,
+                amount,
+                description,
+                private_key_options,
+                gas_options,
+                wait_options,
+                yes,
+            }) => {
+                community::propose_transfer(
+                    &self.client().await?,
+                    wallet,
+                    recipient,
+                    amount,
+                    description,
+                    &private_key_options.private_key(self.profile.as_deref())?,
+                    gas_options.transaction_options(),
+                    wait_options,
+                    *yes,
+                )
+                .await
+            }
+            Some(Subcommand::CommunityVeto {
+                wallet,
+                proposal_id,
+                private_key_options,
+                gas_options,
+                wait_options,
+                yes,
+            }) => {
+                community::veto(
+                    &self.client().await?,
+                    wallet,
+                    *proposal_id,
+                    &private_key_options.private_key(self.profile.as_deref())?,
+                    gas_options.transaction_options(),
+                    wait_options,
+                    *yes,
+                )
+                .await
+            }
+            Some(Subcommand::Vote {
+                proposal_id,
+                should_pass,
+                against: _,
+                private_key_options,
+                gas_options,
+                wait_options,
+                yes,
+            }) => {
+                vote::run(
+                    &self.client().await?,
+                    *proposal_id,
+                    *should_pass,
+                    &private_key_options.private_key(self.profile.as_deref())?,
+                    gas_options.transaction_options(),
+                    wait_options,
+                    *yes,
+                    self.format,
+                )
+                .await
+            }
+            Some(Subcommand::RotateKey {
+                new_private_key,
+                new_private_key_file,
+                no_update_profile,
+                private_key_options,
+                gas_options,
+                wait_options,
+                yes,
+            }) => {
+                let new_private_key = match (new_private_key, new_private_key_file) {
+                    (Some(new_private_key), _) => {
+                        parse_private_key(new_private_key, "--new-private-key")?
+                    }
+                    (None, Some(path)) => Ed25519PrivateKey::from_key_file(path)?,
+                    (None, None) => {
+                        bail!("One of --new-private-key or --new-private-key-file is required")
+                    }
+                };
+                rotate_key::run(
+                    &self.client().await?,
+                    &new_private_key,
+                    !no_update_profile,
+                    &private_key_options.private_key(self.profile.as_deref())?,
+                    gas_options.transaction_options(),
+                    wait_options,
+                    *yes,
+                )
+                .await
+            }
+            Some(Subcommand::Publish {
+                package_dir,
+                named_addresses,
+                skip_build,
+                private_key_options,
+                gas_options,
+                simulate,
+                wait_options,
+                yes,
+            }) => {
+                publish::run(
+                    &self.client().await?,
+                    package_dir,
+                    named_addresses.as_deref(),
+                    *skip_build,
+                    &private_key_options.private_key(self.profile.as_deref())?,
+                    gas_options.transaction_options(),
+                    *simulate,
+                    wait_options,
+                    *yes,
+                )
+                .await
+            }
+            Some(Subcommand::Multisig {
+                command:
+                    multisig::MultisigCommand::Submit {
+                        file,
+                        wait_options,
+                        yes,
+                    },
+            }) => multisig::submit(&self.client().await?, file, wait_options, *yes).await,
+            Some(Subcommand::Keystore { command }) => keystore::run(command),
+            Some(Subcommand::Multisig { command }) => {
+                multisig::run(command, self.profile.as_deref())
+            }
+            Some(Subcommand::Doctor) => {
+                doctor::run(
+                    self.flag_rest_urls()?.into_iter().next(),
+                    self.profile.as_deref(),
+                    self.expected_chain_id(),
+                )
+                .await
+            }
+            Some(Subcommand::Nonce { command }) => nonce::run(&self.client().await?, command).await,
+            Some(Subcommand::Cache { command }) => cache::run(command),
+            Some(Subcommand::Repl) => repl::run().await,
+            Some(Subcommand::Profile { command }) => profile::run(command, self.format),
+            Some(Subcommand::Address { command }) => address::run(command, self.format),
+            Some(Subcommand::Completions { shell }) => completions::run(*shell),
+            Some(Subcommand::Complete { values }) => {
+                completions::complete(*values);
+                Ok(())
+            }
+            Some(Subcommand::View {
+                function_id,
+                type_args,
+                args,
+                args_json,
+                ledger_version,
+                raw,
+                query,
+            }) => {
+                let client = self.client().await?;
+                let version = client.resolve_ledger_version(ledger_version).await?;
+                println!(
+                    "{}",
+                    view::run(
+                        &client,
+                        function_id,
+                        type_args.to_owned(),
+                        FunctionArgs::from_options(args.to_owned(), args_json.to_owned()),
+                        version,
+                        *raw,
+                        query.as_deref(),
+                        self.format,
+                    )
+                    .await?
+                );
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The client of the first healthy node, trying the endpoints in turn. The node is picked
+    /// before anything is sent, so a transaction is only ever submitted to one node
+    async fn client(&self) -> Result<Client> {
+        let all_urls = self.rest_urls()?;
+        if let Some(client) = repl::session_client(&all_urls, self.expected_chain_id()) {
+            return Ok(client);
+        }
+        let mut urls = all_urls.iter().peekable();
+        while let Some(url) = urls.next() {
+            match self.healthy_client(url).await {
+                Ok(client) => {
+                    info!("Using the node at {url}");
+                    repl::keep_client(&all_urls, self.expected_chain_id(), &client);
+                    return Ok(client);
+                }
+                Err(err) if urls.peek().is_some() => {
+                    eprintln!("Warning: {url} failed: {err:#}. Trying the next node")
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        bail!("Rest url is not set, pass --url or --network, or set it with `libra-config init`")
+    }
+
+    /// The client of the node, checked to be on the expected chain when one is known, and to be
+    /// in sync unless --no-staleness-check is set
+    async fn healthy_client(&self, url: &Url) -> Result<Client> {
+        let client = Client::from_url(url).await?;
+        let expected_chain_id = self.expected_chain_id();
+        if expected_chain_id.is_none() && self.no_staleness_check {
+            return Ok(client);
+        }
+        let index = retry_read(|| client.get_index())
+            .await
+            .context("Failed to get the state of the node")?
+            .into_inner();
+        abi_cache::set_chain_id(index.chain_id);
+        if let Some(expected_chain_id) = expected_chain_id {
+            check_chain_id(
+                index.chain_id,
+                expected_chain_id,
+                self.allow_chain_id_mismatch,
+            )?;
+        }
+        if !self.no_staleness_check {
+            check_staleness(
+                ledger_lag_secs(u64::from(index.ledger_timestamp)),
+                self.max_staleness_secs,
+            )?;
+        }
+        Ok(client)
+    }
+
+    fn faucet_client(&self) -> Result<FaucetClient> {
+        if let Some(faucet_url) = &self.faucet_url {
+            let rest_url = self
+                .rest_urls()?
+                .into_iter()
+                .next()
+                .context("No REST endpoint for the faucet, pass --url or --network")?;
+            return Ok(FaucetClient::new(faucet_url.to_owned(), rest_url));
+        }
+        match (self.flag_rest_urls()?.first(), &self.profile) {
+            (Some(url), _) => Ok(FaucetClient::from_rest_url(url)),
+            (None, Some(profile)) => FaucetClient::from_profile(profile),
+            (None, None) => FaucetClient::default(),
+        }
+    }
+
+    /// The endpoints to try in order: those of --url, else the fullnode of --network, else those
+    /// of the profile
+    fn rest_urls(&self) -> Result<Vec<Url>> {
+        let urls = self.flag_rest_urls()?;
+        if !urls.is_empty() {
+            return Ok(urls);
+        }
+        profile_rest_urls(self.profile.as_deref())
+    }
+
+    /// --url, else the fullnode of --network. Empty to use those of the config
+    fn flag_rest_urls(&self) -> Result<Vec<Url>> {
+        match (self.url.is_empty(), self.network) {
+            (false, _) => Ok(self.url.to_owned()),
+            (true, Some(Network::Custom)) => bail!("--network custom needs --url"),
+            (true, network) => Ok(network.and_then(Network::url).into_iter().collect()),
+        }
+    }
+
+    /// The explorer of --explorer-url, else the one the config sets for the network. The network
+    /// is the one of --network, else the preset of --chain-id
+    fn explorer(&self) -> Option<Explorer> {
+        if self.no_explorer_link {
+            return None;
+        }
+        let chain = self
+            .network
+            .or_else(|| self.expected_chain_id().map(Network::from_chain_id))
+            .unwrap_or(Network::Custom)
+            .name();
+        let template = match &self.explorer_url {
+            Some(template) => Some(template.to_owned()),
+            None => configured_template(chain).unwrap_or_else(|err| {
+                eprintln!("Warning: {err:#}, no explorer link is printed");
+                None
+            }),
+        };
+        template.map(|template| Explorer {
+            template,
+            chain: chain.to_string(),
+        })
+    }
+
+    fn expected_chain_id(&self) -> Option<u8> {
+        self.chain_id
+            .or_else(|| self.network.and_then(Network::chain_id))
+    }
+
+    /// Print the transaction to review, as a document with --format json or yaml. Its arguments
+    /// are decoded with `params`, the parameters of the function from its ABI
+    fn print_signed_transaction(
+        &self,
+        signed_trans: &SignedTransaction,
+        params: Option<&[MoveType]>,
+    ) -> Result<()> {
+        match self.format {
+            OutputFormat::Text => println!(
+                "{}",
+                format_signed_transaction_with_params(signed_trans, params)
+            ),
+            format => println!(
+                "{}",
+                format_output(
+                    &format_signed_transaction_json(signed_trans, params),
+                    format
+                )?
+            ),
         }
+        Ok(())
     }
 }
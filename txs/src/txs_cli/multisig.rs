@@ -0,0 +1,274 @@
+use super::{prompt, submit_transaction, PrivateKeyOptions, WaitOptions};
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+use txs::{
+    crypto::{ed25519::Ed25519PublicKey, ValidCryptoMaterialStringExt},
+    extension::client_ext::ClientExt,
+    multisig::{MultisigAccount, MultisigProposal},
+    node_api::NodeApi,
+    unsigned_transaction::UnsignedTransaction,
+    util::format_signed_transaction_with_params,
+};
+
+#[derive(clap::Subcommand)]
+pub enum MultisigCommand {
+    /// Derive the address of a k-of-n MultiEd25519 account and save its owners to a file to share with them
+    Create {
+        /// Ed25519 public key of an owner, repeat it for each owner. The order is part of the account
+        #[clap(long, required = true)]
+        public_key: Vec<String>,
+
+        /// Number of owners who must approve a transaction
+        #[clap(long)]
+        threshold: u8,
+
+        /// Where to save the owners of the account
+        #[clap(short, long)]
+        output_file: PathBuf,
+
+        /// Overwrite the output file if it already exists
+        #[clap(long)]
+        force: bool,
+    },
+
+    /// Start a proposal from a transaction built with generate-transaction --unsigned-output --sender <MULTISIG_ADDRESS>
+    Propose {
+        /// The file written by multisig create
+        #[clap(long)]
+        account_file: PathBuf,
+
+        /// The unsigned transaction file
+        #[clap(long)]
+        unsigned_file: PathBuf,
+
+        /// Where to save the proposal, which the owners approve in turn
+        #[clap(short, long)]
+        output_file: PathBuf,
+
+        /// Overwrite the output file if it already exists
+        #[clap(long)]
+        force: bool,
+    },
+
+    /// Approve a proposal as one of the owners, adding the signature to the proposal file. No network access is needed
+    Approve {
+        /// The proposal file, updated in place
+        #[clap(short, long)]
+        file: PathBuf,
+
+        #[clap(flatten)]
+        private_key_options: PrivateKeyOptions,
+    },
+
+    /// Submit a proposal once enough owners approved it
+    Submit {
+        /// The proposal file
+        #[clap(short, long)]
+        file: PathBuf,
+
+        #[clap(flatten)]
+        wait_options: WaitOptions,
+
+        /// Send the transaction without asking for confirmation, required when stdin is not a terminal
+        #[clap(short, long)]
+        yes: bool,
+    },
+}
+
+/// The commands which run without a node, all but submit
+pub fn run(command: &MultisigCommand, profile: Option<&str>) -> Result<()> {
+    match command {
+        MultisigCommand::Create {
+            public_key,
+            threshold,
+            output_file,
+            force,
+        } => {
+            let public_keys = public_key
+                .iter()
+                .map(|public_key| {
+                    Ed25519PublicKey::from_encoded_string(public_key.trim())
+                        .context(format!("Invalid public key {public_key}"))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let account = MultisigAccount::new(&public_keys, *threshold)?;
+            account.save(output_file, *force)?;
+            println!(
+                "Multisig account: {}, {threshold} of {} owners\nSaved to {}",
+                account.address,
+                public_keys.len(),
+                output_file.display()
+            );
+            println!(
+                "The account is created on-chain when it first receives coins, e.g. with `txs transfer-coins --to-account {}`",
+                account.address
+            );
+            Ok(())
+        }
+        MultisigCommand::Propose {
+            account_file,
+            unsigned_file,
+            output_file,
+            force,
+        } => {
+            let proposal = MultisigProposal::new(
+                MultisigAccount::load(account_file)?,
+                UnsignedTransaction::load(unsigned_file)?,
+            )?;
+            proposal.save(output_file, *force)?;
+            println!("Proposal saved to {}", output_file.display());
+            print_approvals(&proposal, output_file);
+            Ok(())
+        }
+        MultisigCommand::Approve {
+            file,
+            private_key_options,
+        } => {
+            let mut proposal = MultisigProposal::load(file)?;
+            println!(
+                "Signing message hash: {}",
+                proposal.transaction.signing_message_hash
+            );
+            let index = proposal.approve(&private_key_options.private_key(profile)?)?;
+            proposal.save(file, true)?;
+            println!(
+                "Approved as owner {index}, {}",
+                proposal.account.public_keys[usize::from(index)]
+            );
+            print_approvals(&proposal, file);
+            Ok(())
+        }
+        MultisigCommand::Submit { .. } => unreachable!("submit needs a node"),
+    }
+}
+
+pub async fn submit(
+    client: &impl NodeApi,
+    file: &Path,
+    wait_options: &WaitOptions,
+    yes: bool,
+) -> Result<()> {
+    let proposal = MultisigProposal::load(file)?;
+    let signed_trans = proposal.signed_transaction()?;
+    let params = client.payload_params(signed_trans.payload()).await;
+    println!(
+        "{}",
+        format_signed_transaction_with_params(&signed_trans, params.as_deref())
+    );
+    prompt::confirm_transaction(
+        &signed_trans,
+        &[(
+            "Approvals",
+            format!(
+                "{} of {} owners",
+                proposal.approvals.len(),
+                proposal.account.public_keys.len()
+            ),
+        )],
+        yes,
+    )?;
+    println!("{}", "Submitting transaction...".green().bold());
+    submit_transaction::run(client, &signed_trans, wait_options).await
+}
+
+fn print_approvals(proposal: &MultisigProposal, file: &Path) {
+    let missing = proposal.missing_approvals();
+    println!(
+        "{} of the {} approvals needed",
+        proposal.approvals.len(),
+        proposal.account.threshold
+    );
+    if missing == 0 {
+        println!(
+            "Ready, submit it with `txs multisig submit --file {}`",
+            file.display()
+        );
+    } else {
+        println!(
+            "{missing} approval(s) missing, approve it with `txs multisig approve --file {}`",
+            file.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{mock_node, private_key, submitted, wait_options};
+    use txs::{
+        crypto::{ed25519::Ed25519PrivateKey, PrivateKey},
+        node_api::MockNode,
+        types::{
+            account_address::AccountAddress,
+            chain_id::ChainId,
+            transaction::{
+                authenticator::TransactionAuthenticator, RawTransaction, Script, TransactionPayload,
+            },
+        },
+    };
+
+    /// A proposal of a `threshold` of 2 account, approved by the owner of `private_key` alone and
+    /// saved to a file of its own
+    fn proposal_file(name: &str, threshold: u8) -> PathBuf {
+        let other_key = Ed25519PrivateKey::try_from([2; 32].as_slice()).unwrap();
+        let account = MultisigAccount::new(
+            &[private_key().public_key(), other_key.public_key()],
+            threshold,
+        )
+        .unwrap();
+        let raw_trans = RawTransaction::new(
+            AccountAddress::from_hex_literal(&account.address).unwrap(),
+            0,
+            TransactionPayload::Script(Script::new(vec![], vec![], vec![])),
+            2_000,
+            100,
+            4_102_444_800,
+            ChainId::new(4),
+        );
+        let mut proposal =
+            MultisigProposal::new(account, UnsignedTransaction::new(&raw_trans).unwrap()).unwrap();
+        proposal.approve(&private_key()).unwrap();
+        let file = std::env::temp_dir().join(format!(
+            "txs-multisig-test-{name}-{}.json",
+            std::process::id()
+        ));
+        proposal.save(&file, true).unwrap();
+        file
+    }
+
+    async fn submit_file(node: &MockNode, file: &Path) -> Result<()> {
+        submit(node, file, &wait_options(), true).await
+    }
+
+    #[tokio::test]
+    async fn submit_the_approvals_of_the_owners() {
+        let approved = proposal_file("approved", 1);
+        let node = mock_node();
+        submit_file(&node, &approved).await.unwrap();
+        let signed_trans = submitted(&node);
+        let proposal = MultisigProposal::load(&approved).unwrap();
+        assert_eq!(
+            signed_trans.sender(),
+            AccountAddress::from_hex_literal(&proposal.account.address).unwrap()
+        );
+        match signed_trans.authenticator() {
+            TransactionAuthenticator::MultiEd25519 { signature, .. } => {
+                assert_eq!(signature.signatures().len(), 1)
+            }
+            authenticator => panic!("Not a multisig authenticator: {authenticator:?}"),
+        }
+        signed_trans.check_signature().unwrap();
+        std::fs::remove_file(approved).unwrap();
+    }
+
+    #[tokio::test]
+    async fn wait_for_the_threshold() {
+        let missing = proposal_file("missing", 2);
+        let node = mock_node();
+        let err = submit_file(&node, &missing).await.unwrap_err();
+        assert!(format!("{err:#}").contains("1 missing"), "{err:#}");
+        assert!(node.submitted().is_empty());
+        std::fs::remove_file(missing).unwrap();
+    }
+}
@@ -0,0 +1,109 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use txs::{
+    extension::client_ext::ClientExt,
+    node_api::NodeApi,
+    nonce,
+    util::{format_address, parse_address},
+};
+
+#[derive(clap::Subcommand)]
+pub enum NonceCommand {
+    /// Set the nonce file to the sequence number of the account on-chain, e.g. after signed transactions were never submitted
+    Sync {
+        /// Path of the nonce file, created when it does not exist
+        #[clap(long)]
+        nonce_file: PathBuf,
+
+        /// Address of the account, or @name from the address book. Defaults to the account of the nonce file, required to create it
+        #[clap(long)]
+        account_address: Option<String>,
+    },
+}
+
+pub async fn run(client: &impl NodeApi, command: &NonceCommand) -> Result<()> {
+    match command {
+        NonceCommand::Sync {
+            nonce_file,
+            account_address,
+        } => {
+            let address = match account_address {
+                Some(account_address) => parse_address(account_address, "--account-address")?,
+                None => {
+                    nonce::read(nonce_file)?
+                        .context(format!(
+                            "{} does not exist, pass --account-address to create it",
+                            nonce_file.display()
+                        ))?
+                        .0
+                }
+            };
+            let sequence_number = client.get_sequence_number(address).await?;
+            match nonce::reset(nonce_file, address, sequence_number)? {
+                Some((previous_address, previous)) if previous_address == address => println!(
+                    "Next sequence number of {}: {sequence_number}, the nonce file held {previous}",
+                    format_address(&address)
+                ),
+                Some((previous_address, _)) => println!(
+                    "Next sequence number of {}: {sequence_number}, the nonce file was of {} before",
+                    format_address(&address),
+                    format_address(&previous_address)
+                ),
+                None => println!(
+                    "Next sequence number of {}: {sequence_number}, saved to {}",
+                    format_address(&address),
+                    nonce_file.display()
+                ),
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{address, at_sequence_number, mock_node};
+
+    fn sync(nonce_file: &std::path::Path, account_address: Option<&str>) -> NonceCommand {
+        NonceCommand::Sync {
+            nonce_file: nonce_file.to_path_buf(),
+            account_address: account_address.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn sync_the_nonce_file_with_the_chain() {
+        let nonce_file =
+            std::env::temp_dir().join(format!("txs-nonce-test-{}", std::process::id()));
+        let _ = std::fs::remove_file(&nonce_file);
+        let err = run(&mock_node(), &sync(&nonce_file, None))
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("pass --account-address to create it"),
+            "{err:#}"
+        );
+
+        run(&mock_node(), &sync(&nonce_file, Some("0x2")))
+            .await
+            .unwrap();
+        assert_eq!(nonce::read(&nonce_file).unwrap(), Some((address("0x2"), 0)));
+
+        // Transactions were signed and never submitted, the file is behind the chain
+        let node = at_sequence_number(mock_node(), 7);
+        run(&node, &sync(&nonce_file, None)).await.unwrap();
+        assert_eq!(nonce::read(&nonce_file).unwrap(), Some((address("0x2"), 7)));
+        assert_eq!(
+            node.requests(),
+            ["GET /accounts/0x2/resource/0x1::account::Account"]
+        );
+
+        // Another account takes the file over
+        run(&mock_node(), &sync(&nonce_file, Some("0x3")))
+            .await
+            .unwrap();
+        assert_eq!(nonce::read(&nonce_file).unwrap(), Some((address("0x3"), 0)));
+        let _ = std::fs::remove_file(&nonce_file);
+    }
+}
@@ -0,0 +1,171 @@
+use super::{submit_transaction, WaitOptions};
+use anyhow::{bail, Context, Result};
+use txs::{
+    constant::MAX_POF_BID_BPS,
+    crypto::ed25519::Ed25519PrivateKey,
+    extension::{
+        client_ext::{ClientExt, TransactionOptions},
+        ed25519_private_key_ext::Ed25519PrivateKeyExt,
+    },
+    node_api::NodeApi,
+    types::account_address::AccountAddress,
+    util::entry_function_payload,
+};
+
+/// What to do with the proof-of-fee bid of the validator
+pub enum BidAction {
+    Update { bid: u64, expiry_epoch: u64 },
+    Retract,
+}
+
+pub async fn run(
+    client: &impl NodeApi,
+    action: BidAction,
+    private_key: &Ed25519PrivateKey,
+    options: TransactionOptions,
+    wait_options: &WaitOptions,
+    yes: bool,
+) -> Result<()> {
+    let mut from_account = private_key.get_account(client, None).await?;
+    let current_epoch = u64::from(client.get_index().await?.into_inner().epoch);
+
+    let (payload, mut details) = match action {
+        BidAction::Update { bid, expiry_epoch } => {
+            if bid > MAX_POF_BID_BPS {
+                bail!(
+                    "Invalid bid: {bid}, it must be between 0 and {MAX_POF_BID_BPS} basis points"
+                );
+            }
+            if expiry_epoch <= current_epoch {
+                bail!("Invalid expiry epoch: {expiry_epoch}, the current epoch is already {current_epoch}");
+            }
+            let function_id = "0x1::proof_of_fee::pof_update_bid";
+            let payload = entry_function_payload(
+                function_id,
+                vec![],
+                vec![bcs::to_bytes(&bid)?, bcs::to_bytes(&expiry_epoch)?],
+            )?;
+            let details = vec![
+                ("Function", function_id.to_string()),
+                ("New bid", format!("{bid} basis points")),
+                ("Expiry epoch", expiry_epoch.to_string()),
+            ];
+            (payload, details)
+        }
+        BidAction::Retract => {
+            let function_id = "0x1::proof_of_fee::pof_retract_bid";
+            let payload = entry_function_payload(function_id, vec![], vec![])?;
+            (payload, vec![("Function", function_id.to_string())])
+        }
+    };
+
+    details.push(("Current epoch", current_epoch.to_string()));
+    let (previous_bid, previous_expiry_epoch) = current_bid(client, from_account.address()).await?;
+    details.push((
+        "Previous bid",
+        format!("{previous_bid} basis points, expiring at epoch {previous_expiry_epoch}"),
+    ));
+    submit_transaction::sign_and_submit(
+        client,
+        &mut from_account,
+        payload,
+        options,
+        &details,
+        wait_options,
+        yes,
+    )
+    .await
+}
+
+/// The bid of the validator in basis points and the epoch it expires at
+pub async fn current_bid(client: &impl NodeApi, validator: AccountAddress) -> Result<(u64, u64)> {
+    let result = client
+        .view_ext(
+            "0x1::proof_of_fee::current_bid",
+            None,
+            Some(validator.to_hex_literal()),
+        )
+        .await
+        .context("Failed to get the current bid of the validator")?;
+    let value_of = |index: usize| {
+        result
+            .get(index)
+            .and_then(|v| v.as_str())
+            .and_then(|v| v.parse::<u64>().ok())
+            .context("Invalid bid returned by the node")
+    };
+    Ok((value_of(0)?, value_of(1)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        arg, function_id, mock_node, options, private_key, submitted_call, wait_options, VIEW,
+    };
+    use serde_json::json;
+    use txs::node_api::MockNode;
+
+    fn bidding_node() -> MockNode {
+        mock_node().with_route(VIEW, 200, json!(["50", "3"]))
+    }
+
+    async fn bid(node: &MockNode, action: BidAction) -> Result<()> {
+        run(
+            node,
+            action,
+            &private_key(),
+            options(),
+            &wait_options(),
+            true,
+        )
+        .await
+    }
+
+    fn update(bid: u64, expiry_epoch: u64) -> BidAction {
+        BidAction::Update { bid, expiry_epoch }
+    }
+
+    #[tokio::test]
+    async fn update_or_retract_the_bid() {
+        let node = bidding_node();
+        bid(&node, update(100, 10)).await.unwrap();
+        let call = submitted_call(&node);
+        assert_eq!(function_id(&call), "0x1::proof_of_fee::pof_update_bid");
+        assert_eq!(arg::<u64>(&call, 0), 100);
+        assert_eq!(arg::<u64>(&call, 1), 10);
+
+        let node = bidding_node();
+        bid(&node, BidAction::Retract).await.unwrap();
+        let call = submitted_call(&node);
+        assert_eq!(function_id(&call), "0x1::proof_of_fee::pof_retract_bid");
+        assert!(call.args().is_empty());
+    }
+
+    #[tokio::test]
+    async fn check_the_bid_before_signing() {
+        let node = bidding_node();
+        // The bid of the whole reward is valid, a basis point more is not
+        bid(&node, update(MAX_POF_BID_BPS, 10)).await.unwrap();
+        let err = bid(&node, update(MAX_POF_BID_BPS + 1, 10))
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("Invalid bid"), "{err:#}");
+
+        // The ledger of the fixtures is at epoch 1
+        let err = bid(&node, update(100, 1)).await.unwrap_err();
+        assert!(
+            format!("{err:#}").contains("the current epoch is already 1"),
+            "{err:#}"
+        );
+        assert_eq!(node.submitted().len(), 1);
+
+        let node = mock_node().with_route(VIEW, 200, json!(["fifty", "3"]));
+        let err = bid(&node, BidAction::Retract).await.unwrap_err();
+        assert!(
+            format!("{err:#}").contains("Invalid bid returned by the node"),
+            "{err:#}"
+        );
+        assert!(node.submitted().is_empty());
+    }
+}
@@ -0,0 +1,77 @@
+use anyhow::{Context, Result};
+use libra_config::extension::cli_config_ext::load_profiles;
+use serde_json::{json, Map, Value};
+use txs::util::{format_address, format_output, OutputFormat};
+
+#[derive(clap::Subcommand)]
+pub enum ProfileCommand {
+    /// Print the profiles of the config with their account and REST endpoint
+    List,
+
+    /// Print the settings of a profile. The private key itself is never printed
+    Show { name: String },
+}
+
+pub fn run(command: &ProfileCommand, format: OutputFormat) -> Result<()> {
+    let profiles = load_profiles()?
+        .into_iter()
+        .map(|(name, profile)| {
+            let settings = json!({
+                "network": profile.network.map(|network| network.to_string()),
+                "account": profile.account.map(|account| format_address(&account)),
+                "public_key": profile.public_key.map(|public_key| public_key.to_string()),
+                "private_key": profile.private_key.is_some(),
+                "rest_url": profile.rest_url,
+                "faucet_url": profile.faucet_url,
+            });
+            (name, settings)
+        })
+        .collect::<Map<String, Value>>();
+    match command {
+        ProfileCommand::List => {
+            if format != OutputFormat::Text {
+                println!("{}", format_output(&profiles, format)?);
+                return Ok(());
+            }
+            if profiles.is_empty() {
+                println!("No profile found, create one with `libra-config init`");
+            }
+            let width = profiles.keys().map(String::len).max();
+            for (name, settings) in &profiles {
+                println!(
+                    "{name:width$}  {}  {}",
+                    settings["account"].as_str().unwrap_or("no account"),
+                    settings["rest_url"].as_str().unwrap_or("no REST endpoint"),
+                    width = width.unwrap_or_default()
+                );
+            }
+            Ok(())
+        }
+        ProfileCommand::Show { name } => {
+            let settings = profiles
+                .get(name)
+                .context(format!("Profile {name} not found, see `txs profile list`"))?;
+            if format != OutputFormat::Text {
+                println!("{}", format_output(settings, format)?);
+                return Ok(());
+            }
+            println!("Profile: {name}");
+            for (label, field) in [
+                ("Network", "network"),
+                ("Account", "account"),
+                ("Public key", "public_key"),
+                ("REST endpoint", "rest_url"),
+                ("Faucet", "faucet_url"),
+            ] {
+                println!("{label}: {}", settings[field].as_str().unwrap_or("-"));
+            }
+            let private_key = if settings["private_key"] == true {
+                "stored in the config"
+            } else {
+                "-"
+            };
+            println!("Private key: {private_key}");
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use anyhow::{bail, Result};
+use dialoguer::Confirm;
+use txs::types::transaction::{authenticator::TransactionAuthenticator, SignedTransaction};
+
+/// Show what the transaction is about to do and ask for an explicit confirmation, unless `yes` is set.
+/// The transaction is only signed in memory at this point, declining discards it
+pub fn confirm_transaction(
+    signed_trans: &SignedTransaction,
+    details: &[(&str, String)],
+    yes: bool,
+) -> Result<()> {
+    let mut all_details = vec![("Sender", signed_trans.sender().to_hex_literal())];
+    if let TransactionAuthenticator::FeePayer {
+        fee_payer_address, ..
+    } = signed_trans.authenticator()
+    {
+        all_details.push(("Gas payer", fee_payer_address.to_hex_literal()));
+    }
+    all_details.extend(details.iter().cloned());
+    all_details.extend([
+        ("Max gas", signed_trans.max_gas_amount().to_string()),
+        ("Gas unit price", signed_trans.gas_unit_price().to_string()),
+        (
+            "Max fee",
+            format!(
+                "{} base units",
+                signed_trans.max_gas_amount() * signed_trans.gas_unit_price()
+            ),
+        ),
+    ]);
+    confirm(&all_details, yes)
+}
+
+/// Print the details and ask for an explicit confirmation, unless `yes` is set
+pub fn confirm(details: &[(&str, String)], yes: bool) -> Result<()> {
+    for (name, value) in details {
+        println!("{name}: {value}");
+    }
+
+    if yes {
+        return Ok(());
+    }
+    if !atty::is(atty::Stream::Stdin) {
+        bail!("Stdin is not a terminal, pass --yes to proceed without confirmation");
+    }
+    if !Confirm::new()
+        .with_prompt("Proceed?")
+        .default(false)
+        .interact()?
+    {
+        bail!("Aborted, nothing was sent");
+    }
+    Ok(())
+}
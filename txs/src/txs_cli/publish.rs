@@ -0,0 +1,338 @@
+use super::{prompt, simulate_transaction, submit_transaction, WaitOptions};
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use txs::{
+    constant::MAX_TRANSACTION_SIZE_BYTES,
+    crypto::ed25519::Ed25519PrivateKey,
+    extension::{
+        client_ext::{ClientExt, TransactionOptions},
+        ed25519_private_key_ext::Ed25519PrivateKeyExt,
+    },
+    move_types::language_storage::ModuleId,
+    node_api::NodeApi,
+    types::account_address::AccountAddress,
+    util::{entry_function_payload, format_address, parse_address},
+};
+use zapatos_framework::{BuildOptions, BuiltPackage};
+use zapatos_move_binary_format::{access::ModuleAccess, CompiledModule};
+
+/// Warn about the size of the transaction past this share of the limit
+const SIZE_WARNING_PERCENT: usize = 90;
+
+/// Compiled modules of a package and its metadata, as `0x1::code::publish_package_txn` takes them
+struct Package {
+    metadata: Vec<u8>,
+    modules: Vec<(ModuleId, Vec<u8>)>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    client: &impl NodeApi,
+    package_dir: &Path,
+    named_addresses: Option<&str>,
+    skip_build: bool,
+    private_key: &Ed25519PrivateKey,
+    options: TransactionOptions,
+    simulate: bool,
+    wait_options: &WaitOptions,
+    yes: bool,
+) -> Result<()> {
+    let mut from_account = private_key.get_account(client, None).await?;
+    let package = if skip_build {
+        load_built_package(package_dir)?
+    } else {
+        build_package(package_dir, parse_named_addresses(named_addresses)?)?
+    };
+
+    println!("Modules to publish:");
+    for (id, code) in &package.modules {
+        println!(
+            "  {}::{} ({} bytes)",
+            format_address(id.address()),
+            id.name(),
+            code.len()
+        );
+    }
+
+    let code = package
+        .modules
+        .iter()
+        .map(|(_, code)| code.to_owned())
+        .collect::<Vec<_>>();
+    let function_id = "0x1::code::publish_package_txn";
+    let payload = entry_function_payload(
+        function_id,
+        vec![],
+        vec![bcs::to_bytes(&package.metadata)?, bcs::to_bytes(&code)?],
+    )?;
+
+    let size = bcs::to_bytes(&payload)?.len();
+    if size > MAX_TRANSACTION_SIZE_BYTES {
+        bail!(
+            "The package is {size} bytes, over the {MAX_TRANSACTION_SIZE_BYTES} bytes a transaction can hold. Split it into smaller packages"
+        );
+    }
+    if size * 100 > MAX_TRANSACTION_SIZE_BYTES * SIZE_WARNING_PERCENT {
+        println!(
+            "{} the package is {size} bytes, close to the {MAX_TRANSACTION_SIZE_BYTES} bytes a transaction can hold",
+            "Warning:".yellow().bold()
+        );
+    }
+
+    let signed_trans = client
+        .sign_payload(&mut from_account, payload, options)
+        .await?;
+    if simulate {
+        simulate_transaction::run(client, &signed_trans)
+            .await
+            .context("Aborted, the package was not published")?;
+    }
+    prompt::confirm_transaction(
+        &signed_trans,
+        &[
+            ("Function", function_id.to_string()),
+            ("Modules", package.modules.len().to_string()),
+            ("Size", format!("{size} bytes")),
+        ],
+        yes,
+    )?;
+    submit_transaction::run(client, &signed_trans, wait_options).await
+}
+
+/// Named addresses given as `name=address,other=address`
+fn parse_named_addresses(
+    named_addresses: Option<&str>,
+) -> Result<BTreeMap<String, AccountAddress>> {
+    let named_addresses = match named_addresses {
+        Some(named_addresses) => named_addresses,
+        None => return Ok(BTreeMap::new()),
+    };
+    named_addresses
+        .split(',')
+        .map(|pair| {
+            let (name, address) = pair.split_once('=').context(format!(
+                "Invalid named address {pair}, expected <NAME>=<ADDRESS>"
+            ))?;
+            Ok((
+                name.trim().to_string(),
+                parse_address(address, "--named-addresses")?,
+            ))
+        })
+        .collect()
+}
+
+fn build_package(
+    package_dir: &Path,
+    named_addresses: BTreeMap<String, AccountAddress>,
+) -> Result<Package> {
+    println!("{}", "Building package...".green().bold());
+    let built = BuiltPackage::build(
+        package_dir.to_path_buf(),
+        BuildOptions {
+            named_addresses,
+            ..BuildOptions::default()
+        },
+    )
+    .context(format!(
+        "Failed to build the Move package in {}",
+        package_dir.display()
+    ))?;
+    let metadata = bcs::to_bytes(&built.extract_metadata()?)?;
+    Package::new(metadata, built.extract_code())
+}
+
+/// The bytecode and metadata of a package built before, from `build/<PACKAGE>/` in the package directory
+fn load_built_package(package_dir: &Path) -> Result<Package> {
+    let build_dir = package_dir.join("build");
+    let package_dirs = fs::read_dir(&build_dir)
+        .context(format!(
+            "No build folder in {}, build the package first or leave out --skip-build",
+            package_dir.display()
+        ))?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<PathBuf>>>()?
+        .into_iter()
+        .filter(|path| path.join("package-metadata.bcs").exists())
+        .collect::<Vec<_>>();
+    let built_dir = match package_dirs.as_slice() {
+        [built_dir] => built_dir,
+        [] => bail!("No built package in {}", build_dir.display()),
+        _ => bail!(
+            "More than one built package in {}, remove the stale ones",
+            build_dir.display()
+        ),
+    };
+
+    let metadata = fs::read(built_dir.join("package-metadata.bcs"))?;
+    let modules_dir = built_dir.join("bytecode_modules");
+    let mut code = vec![];
+    // Dependencies are in a subfolder, they are not part of the package
+    for entry in
+        fs::read_dir(&modules_dir).context(format!("Failed to read {}", modules_dir.display()))?
+    {
+        let path = entry?.path();
+        if path
+            .extension()
+            .map_or(false, |extension| extension == "mv")
+        {
+            code.push(fs::read(&path).context(format!("Failed to read {}", path.display()))?);
+        }
+    }
+    if code.is_empty() {
+        bail!("No compiled module in {}", modules_dir.display());
+    }
+    Package::new(metadata, code)
+}
+
+impl Package {
+    /// The modules are ordered so that each comes after the modules of the package it uses
+    fn new(metadata: Vec<u8>, code: Vec<Vec<u8>>) -> Result<Self> {
+        let mut pending = code
+            .into_iter()
+            .map(|code| {
+                let module =
+                    CompiledModule::deserialize(&code).context("Invalid compiled module")?;
+                Ok((module.self_id(), module.immediate_dependencies(), code))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut modules: Vec<(ModuleId, Vec<u8>)> = vec![];
+        while !pending.is_empty() {
+            let ids = pending
+                .iter()
+                .map(|(id, ..)| id.clone())
+                .collect::<Vec<_>>();
+            let ready = pending
+                .iter()
+                .position(|(_, dependencies, _)| {
+                    dependencies
+                        .iter()
+                        .all(|dependency| !ids.contains(dependency))
+                })
+                .context("The modules of the package depend on each other in a cycle")?;
+            let (id, _, code) = pending.remove(ready);
+            modules.push((id, code));
+        }
+        Ok(Self { metadata, modules })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        address, arg, function_id, mock_node, options, private_key, submitted_call, wait_options,
+        SIMULATE,
+    };
+    use txs::node_api::MockNode;
+    use zapatos_move_binary_format::file_format::empty_module;
+
+    fn package_dir(name: &str) -> PathBuf {
+        let package_dir =
+            std::env::temp_dir().join(format!("txs-publish-test-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&package_dir);
+        package_dir
+    }
+
+    fn empty_module_code() -> Vec<u8> {
+        let mut code = vec![];
+        empty_module().serialize(&mut code).unwrap();
+        code
+    }
+
+    /// A package built before as `build/<NAME>`, of one empty module
+    fn build(package_dir: &Path, name: &str) {
+        let modules_dir = package_dir
+            .join("build")
+            .join(name)
+            .join("bytecode_modules");
+        fs::create_dir_all(&modules_dir).unwrap();
+        fs::write(
+            package_dir
+                .join("build")
+                .join(name)
+                .join("package-metadata.bcs"),
+            [0],
+        )
+        .unwrap();
+        fs::write(modules_dir.join("empty.mv"), empty_module_code()).unwrap();
+    }
+
+    async fn publish(node: &MockNode, package_dir: &Path, simulate: bool) -> Result<()> {
+        run(
+            node,
+            package_dir,
+            None,
+            true,
+            &private_key(),
+            options(),
+            simulate,
+            &wait_options(),
+            true,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn publish_the_built_modules() {
+        let package_dir = package_dir("built");
+        build(&package_dir, "empty");
+        let node = mock_node();
+        publish(&node, &package_dir, false).await.unwrap();
+        let call = submitted_call(&node);
+        assert_eq!(function_id(&call), "0x1::code::publish_package_txn");
+        assert_eq!(arg::<Vec<u8>>(&call, 0), [0]);
+        assert_eq!(arg::<Vec<Vec<u8>>>(&call, 1), [empty_module_code()]);
+
+        // Nothing is submitted when the simulation fails
+        let node = mock_node();
+        let err = publish(&node, &package_dir, true).await.unwrap_err();
+        assert!(
+            format!("{err:#}").contains("the package was not published"),
+            "{err:#}"
+        );
+        assert!(node.requests().iter().any(|route| route == SIMULATE));
+        assert!(node.submitted().is_empty());
+        fs::remove_dir_all(package_dir).unwrap();
+    }
+
+    #[test]
+    fn find_the_one_built_package() {
+        let package_dir = package_dir("stale");
+        let err = load_built_package(&package_dir).err().unwrap();
+        assert!(format!("{err:#}").contains("No build folder"), "{err:#}");
+
+        build(&package_dir, "empty");
+        // The folders without metadata are not packages
+        fs::create_dir_all(package_dir.join("build/locks")).unwrap();
+        let package = load_built_package(&package_dir).unwrap();
+        assert_eq!(package.modules.len(), 1);
+
+        build(&package_dir, "renamed");
+        let err = load_built_package(&package_dir).err().unwrap();
+        assert!(
+            format!("{err:#}").contains("More than one built package"),
+            "{err:#}"
+        );
+        fs::remove_dir_all(package_dir).unwrap();
+    }
+
+    #[test]
+    fn parse_the_named_addresses() {
+        let named_addresses = parse_named_addresses(Some("gift=0x3, other=0x4")).unwrap();
+        assert_eq!(named_addresses["gift"], address("0x3"));
+        assert_eq!(named_addresses["other"], address("0x4"));
+        assert!(parse_named_addresses(None).unwrap().is_empty());
+
+        let err = parse_named_addresses(Some("gift")).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("expected <NAME>=<ADDRESS>"),
+            "{err:#}"
+        );
+    }
+}
@@ -0,0 +1,306 @@
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use txs::{
+    constant::NATIVE_COIN_DECIMALS,
+    crypto::HashValue,
+    explorer::explorer_url,
+    node_api::NodeApi,
+    rest_client::{aptos_api_types::Transaction, error::RestError},
+    types::account_address::AccountAddress,
+    util::{format_decimal_amount, format_output, OutputFormat},
+};
+
+/// Print what the committed transaction did, `vm_status` being the VM status with the abort
+/// decoded when it failed
+pub async fn print(
+    client: &impl NodeApi,
+    transaction: &Transaction,
+    vm_status: &str,
+    format: OutputFormat,
+) -> Result<()> {
+    let receipt = receipt(client, transaction, vm_status).await?;
+    if format == OutputFormat::Text {
+        println!("{}", format_receipt(&receipt));
+    } else {
+        println!("{}", format_output(&receipt, format)?);
+    }
+    Ok(())
+}
+
+async fn receipt(
+    client: &impl NodeApi,
+    transaction: &Transaction,
+    vm_status: &str,
+) -> Result<Value> {
+    // Every kind of committed transaction serializes its info the same way
+    let transaction = serde_json::to_value(transaction)?;
+    let as_u64 = |value: &Value| value.as_str().and_then(|v| v.parse::<u64>().ok());
+    let version = as_u64(&transaction["version"]);
+    let gas_used = as_u64(&transaction["gas_used"]);
+    let fee = gas_used
+        .zip(as_u64(&transaction["gas_unit_price"]))
+        .map(|(gas_used, gas_unit_price)| gas_used * gas_unit_price);
+
+    let events = transaction["events"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|event| json!({ "type": event["type"], "data": event["data"] }))
+        .collect::<Vec<_>>();
+
+    let mut changes = vec![];
+    for change in transaction["changes"].as_array().into_iter().flatten() {
+        let (address, kind, name) = match change["type"].as_str().unwrap_or_default() {
+            "write_resource" => {
+                let resource_type = change["data"]["type"].as_str().unwrap_or_default();
+                let kind = match (version, change["address"].as_str()) {
+                    (Some(version), Some(address)) => {
+                        resource_change(client, address, resource_type, version).await
+                    }
+                    _ => "written",
+                };
+                (&change["address"], kind, resource_type.to_string())
+            }
+            "delete_resource" => (
+                &change["address"],
+                "deleted",
+                change["resource"].as_str().unwrap_or_default().to_string(),
+            ),
+            "write_module" => (
+                &change["address"],
+                "module published",
+                change["data"]["abi"]["name"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            ),
+            "delete_module" => (
+                &change["address"],
+                "module deleted",
+                change["module"].as_str().unwrap_or_default().to_string(),
+            ),
+            "write_table_item" => (
+                &change["handle"],
+                "table item written",
+                change["key"].as_str().unwrap_or_default().to_string(),
+            ),
+            "delete_table_item" => (
+                &change["handle"],
+                "table item deleted",
+                change["key"].as_str().unwrap_or_default().to_string(),
+            ),
+            other => (&change["address"], other, String::new()),
+        };
+        changes.push(json!({
+            "address": address,
+            "change": kind,
+            "name": name,
+        }));
+    }
+
+    let explorer_url = transaction["hash"]
+        .as_str()
+        .and_then(|hash| HashValue::from_hex(hash.trim_start_matches("0x")).ok())
+        .and_then(|hash| explorer_url(&hash));
+
+    Ok(json!({
+        "hash": transaction["hash"],
+        "explorer_url": explorer_url,
+        "version": version,
+        "success": transaction["success"],
+        "vm_status": vm_status,
+        "gas_used": gas_used,
+        "fee": fee,
+        "fee_coins": fee.map(|fee| format_decimal_amount(fee, NATIVE_COIN_DECIMALS)),
+        "events": events,
+        "changes": changes,
+    }))
+}
+
+/// Whether a written resource was created or modified, from its state at the previous version
+async fn resource_change(
+    client: &impl NodeApi,
+    address: &str,
+    resource_type: &str,
+    version: u64,
+) -> &'static str {
+    let (address, previous_version) = match (
+        AccountAddress::from_hex_literal(address),
+        version.checked_sub(1),
+    ) {
+        (Ok(address), Some(previous_version)) => (address, previous_version),
+        _ => return "written",
+    };
+    match client
+        .get_account_resource_at_version(address, resource_type, previous_version)
+        .await
+    {
+        Ok(response) if response.inner().is_some() => "modified",
+        Ok(_) => "created",
+        Err(RestError::Api(err)) if err.status_code.as_u16() == 404 => "created",
+        // The node may have pruned the previous version
+        Err(_) => "written",
+    }
+}
+
+fn format_receipt(receipt: &Value) -> String {
+    let mut lines = vec![
+        format!(
+            "Transaction: {}",
+            receipt["hash"].as_str().unwrap_or_default()
+        ),
+        format!("Version: {}", receipt["version"]),
+        format!("Success: {}", receipt["success"]),
+        format!(
+            "VM status: {}",
+            receipt["vm_status"].as_str().unwrap_or_default()
+        ),
+        format!("Gas used: {}", receipt["gas_used"]),
+        format!(
+            "Fee: {} coins ({} base units)",
+            receipt["fee_coins"].as_str().unwrap_or("unknown"),
+            receipt["fee"]
+        ),
+    ];
+
+    let events = receipt["events"].as_array().cloned().unwrap_or_default();
+    lines.push(format!("Events: {}", events.len()));
+    for event in events {
+        lines.push(format!(
+            "    {} {}",
+            event["type"].as_str().unwrap_or_default(),
+            event["data"]
+        ));
+    }
+
+    let mut changes_by_address: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for change in receipt["changes"].as_array().into_iter().flatten() {
+        changes_by_address
+            .entry(change["address"].as_str().unwrap_or_default())
+            .or_default()
+            .push(format!(
+                "{} {}",
+                change["change"].as_str().unwrap_or_default(),
+                change["name"].as_str().unwrap_or_default()
+            ));
+    }
+    lines.push(format!(
+        "State changes: {}",
+        receipt["changes"].as_array().map_or(0, Vec::len)
+    ));
+    for (address, changes) in changes_by_address {
+        lines.push(format!("    {address}"));
+        for change in changes {
+            lines.push(format!("        {change}"));
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        aborted_transaction, coin_store, error_body, fixture_body, mock_node, TRANSACTION,
+    };
+
+    const PREVIOUS_STORE: &str = "GET /accounts/0x2/resource/0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>?ledger_version=100";
+
+    /// The transaction of the fixtures, writing the coin store of 0x2
+    fn transfer() -> Transaction {
+        let mut transaction = fixture_body(TRANSACTION);
+        transaction["changes"] = json!([{
+            "type": "write_resource",
+            "address": "0x2",
+            "state_key_hash": "0x0",
+            "data": coin_store(990),
+        }]);
+        serde_json::from_value(transaction).unwrap()
+    }
+
+    #[tokio::test]
+    async fn tell_created_from_modified_resources() {
+        let node = mock_node().with_route(PREVIOUS_STORE, 200, coin_store(1000));
+        let described = receipt(&node, &transfer(), "Executed successfully")
+            .await
+            .unwrap();
+        assert_eq!(described["fee"], json!(600));
+        assert_eq!(described["changes"][0]["change"], json!("modified"));
+        assert_eq!(node.requests(), [PREVIOUS_STORE]);
+
+        // Not there at the previous version
+        let described = receipt(&mock_node(), &transfer(), "Executed successfully")
+            .await
+            .unwrap();
+        assert_eq!(described["changes"][0]["change"], json!("created"));
+
+        // Pruned, or answered with something else
+        let node = mock_node().with_route(
+            PREVIOUS_STORE,
+            500,
+            error_body("Internal error", "internal_error"),
+        );
+        let described = receipt(&node, &transfer(), "Executed successfully")
+            .await
+            .unwrap();
+        assert_eq!(described["changes"][0]["change"], json!("written"));
+
+        let node = mock_node().with_route(PREVIOUS_STORE, 200, json!({ "not": "a resource" }));
+        let described = receipt(&node, &transfer(), "Executed successfully")
+            .await
+            .unwrap();
+        assert_eq!(described["changes"][0]["change"], json!("written"));
+    }
+
+    #[tokio::test]
+    async fn list_the_events_and_the_changes_by_address() {
+        let mut transaction = serde_json::to_value(transfer()).unwrap();
+        transaction["changes"] = json!([
+            {
+                "type": "delete_resource",
+                "address": "0x2",
+                "state_key_hash": "0x0",
+                "resource": "0x1::gift::Gift",
+            },
+            {
+                "type": "write_table_item",
+                "handle": "0x7",
+                "state_key_hash": "0x0",
+                "key": "0x01",
+                "value": "0x02",
+                "data": null,
+            },
+        ]);
+        transaction["events"] = json!([{
+            "guid": { "creation_number": "0", "account_address": "0x2" },
+            "sequence_number": "0",
+            "type": "0x1::coin::DepositEvent",
+            "data": { "amount": "10" },
+        }]);
+        let transaction = serde_json::from_value(transaction).unwrap();
+        let node = mock_node();
+        let described = receipt(&node, &transaction, "Executed successfully")
+            .await
+            .unwrap();
+        // Only the written resources are looked up
+        assert!(node.requests().is_empty(), "{:?}", node.requests());
+
+        let text = format_receipt(&described);
+        assert!(
+            text.contains("Events: 1\n    0x1::coin::DepositEvent {\"amount\":\"10\"}"),
+            "{text}"
+        );
+        assert!(
+            text.contains("State changes: 2\n    0x2\n        deleted 0x1::gift::Gift\n    0x7\n        table item written 0x01"),
+            "{text}"
+        );
+
+        let aborted = serde_json::from_value(aborted_transaction()).unwrap();
+        let described = receipt(&mock_node(), &aborted, "EINSUFFICIENT_BALANCE")
+            .await
+            .unwrap();
+        assert_eq!(described["success"], json!(false));
+        assert!(format_receipt(&described).contains("VM status: EINSUFFICIENT_BALANCE"));
+    }
+}
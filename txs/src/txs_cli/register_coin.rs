@@ -0,0 +1,112 @@
+use super::{prompt, submit_transaction, transfer_coin, WaitOptions};
+use anyhow::{bail, Result};
+use txs::{
+    crypto::ed25519::Ed25519PrivateKey,
+    extension::{
+        client_ext::{ClientExt, TransactionOptions},
+        ed25519_private_key_ext::Ed25519PrivateKeyExt,
+    },
+    funds::has_coin_store,
+    node_api::NodeApi,
+    util::{entry_function_payload, format_address},
+};
+
+/// Publish the CoinStore of the coin on the account of the key, so it can receive the coin
+pub async fn run(
+    client: &impl NodeApi,
+    coin_type: &str,
+    private_key: &Ed25519PrivateKey,
+    options: TransactionOptions,
+    wait_options: &WaitOptions,
+    yes: bool,
+) -> Result<()> {
+    let coin_type = transfer_coin::parse_coin_type(coin_type)?;
+    let mut account = private_key.get_account(client, None).await?;
+    if has_coin_store(client, account.address(), &coin_type).await? {
+        println!(
+            "{} already registered {coin_type}, nothing to do",
+            format_address(&account.address())
+        );
+        return Ok(());
+    }
+
+    let payload = entry_function_payload(
+        "0x1::managed_coin::register",
+        vec![coin_type.clone()],
+        vec![],
+    )?;
+    let signed_trans = client.sign_payload(&mut account, payload, options).await?;
+    prompt::confirm_transaction(
+        &signed_trans,
+        &[
+            ("Account", format_address(&account.address())),
+            ("Coin type", coin_type.to_string()),
+        ],
+        yes,
+    )?;
+    if submit_transaction::submit(client, &signed_trans, wait_options)
+        .await?
+        .is_none()
+    {
+        return Ok(());
+    }
+
+    if !has_coin_store(client, account.address(), &coin_type).await? {
+        bail!(
+            "The transaction is committed but {} has no CoinStore of {coin_type}",
+            format_address(&account.address())
+        );
+    }
+    println!(
+        "{} can now receive {coin_type}",
+        format_address(&account.address())
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        coin_store, function_id, mock_node, options, private_key, submitted_call, wait_options,
+        COIN_STORE,
+    };
+    use serde_json::Value;
+    use txs::node_api::MockNode;
+
+    const COIN_TYPE: &str = "0x1::aptos_coin::AptosCoin";
+
+    async fn register(node: &MockNode) -> Result<()> {
+        run(
+            node,
+            COIN_TYPE,
+            &private_key(),
+            options(),
+            &wait_options(),
+            true,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn register_the_coin_store() {
+        // The node of the fixtures never creates the store, so the check after the commit fails
+        let node = mock_node().with_route(COIN_STORE, 200, Value::Null);
+        let err = register(&node).await.unwrap_err();
+        assert!(format!("{err:#}").contains("has no CoinStore"), "{err:#}");
+        let call = submitted_call(&node);
+        assert_eq!(function_id(&call), "0x1::managed_coin::register");
+        assert_eq!(
+            call.ty_args(),
+            [transfer_coin::parse_coin_type(COIN_TYPE).unwrap()]
+        );
+        assert!(call.args().is_empty());
+    }
+
+    #[tokio::test]
+    async fn skip_a_registered_coin() {
+        let node = mock_node().with_route(COIN_STORE, 200, coin_store(0));
+        register(&node).await.unwrap();
+        assert!(node.submitted().is_empty());
+    }
+}
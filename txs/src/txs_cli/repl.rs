@@ -0,0 +1,313 @@
+use super::{
+    completions::{candidates, CompletionValues},
+    print_error, TxsCli,
+};
+use anyhow::{bail, Context as _, Result};
+use clap::{CommandFactory, FromArgMatches};
+use libra_config::extension::global_config_ext::global_folder;
+use rustyline::{
+    completion::{Completer, Pair},
+    error::ReadlineError,
+    highlight::Highlighter,
+    hint::Hinter,
+    history::DefaultHistory,
+    validate::Validator,
+    Context, Editor, Helper,
+};
+use std::{
+    collections::HashMap,
+    iter,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+use txs::{address_book::NAME_PREFIX, crypto::ed25519::Ed25519PrivateKey, rest_client::Client};
+use url::Url;
+use zeroize::Zeroizing;
+
+pub const HISTORY_FILE: &str = "repl_history";
+const PROMPT: &str = "txs> ";
+
+/// The lines of the REPL itself, the other ones are txs subcommands
+const BUILTINS: [&str; 4] = ["set", "use", "exit", "quit"];
+
+/// Flags whose value is a secret, the lines holding one are kept out of the history
+const SECRET_FLAGS: [&str; 5] = [
+    "-p",
+    "--private-key",
+    "--mnemonic",
+    "--legacy-mnemonic",
+    "--secondary-private-key",
+];
+
+/// Kept from one line of the REPL to the next, set while a REPL runs
+#[derive(Default)]
+struct Session {
+    /// The node picked for the endpoints and the expected chain id, checked once for the session
+    client: Option<(Vec<Url>, Option<u8>, Client)>,
+    /// Decrypted keys by keystore path, so the password is only asked for once
+    keystores: HashMap<PathBuf, Zeroizing<[u8; 32]>>,
+}
+
+static SESSION: Mutex<Option<Session>> = Mutex::new(None);
+
+fn with_session<T>(f: impl FnOnce(&mut Session) -> T) -> Option<T> {
+    SESSION
+        .lock()
+        .expect("the session lock is never poisoned")
+        .as_mut()
+        .map(f)
+}
+
+pub fn in_session() -> bool {
+    with_session(|_| ()).is_some()
+}
+
+/// The client of the session when it was picked for the same endpoints and chain id
+pub fn session_client(urls: &[Url], chain_id: Option<u8>) -> Option<Client> {
+    with_session(|session| match &session.client {
+        Some((session_urls, session_chain_id, client))
+            if session_urls == urls && *session_chain_id == chain_id =>
+        {
+            Some(client.clone())
+        }
+        _ => None,
+    })
+    .flatten()
+}
+
+/// Keep the client for the next lines, does nothing outside of the REPL
+pub fn keep_client(urls: &[Url], chain_id: Option<u8>, client: &Client) {
+    with_session(|session| session.client = Some((urls.to_vec(), chain_id, client.clone())));
+}
+
+pub fn unlocked_keystore(path: &Path) -> Option<Ed25519PrivateKey> {
+    with_session(|session| {
+        session
+            .keystores
+            .get(path)
+            .and_then(|bytes| Ed25519PrivateKey::try_from(bytes.as_slice()).ok())
+    })
+    .flatten()
+}
+
+/// Keep the decrypted key of the keystore for the next lines, does nothing outside of the REPL
+pub fn keep_unlocked_keystore(path: &Path, private_key: &Ed25519PrivateKey) {
+    with_session(|session| {
+        session
+            .keystores
+            .insert(path.to_owned(), Zeroizing::new(private_key.to_bytes()));
+    });
+}
+
+/// Read subcommands at a prompt and run each one as `txs` would, with the global flags txs
+/// was started with. `set <FLAGS>` adds global flags for the next lines and `use <PROFILE>`
+/// selects a profile. Logging keeps the flags of the start
+pub async fn run() -> Result<()> {
+    if in_session() {
+        bail!("Already in the REPL");
+    }
+    *SESSION.lock().expect("the session lock is never poisoned") = Some(Session::default());
+    let result = read_lines().await;
+    *SESSION.lock().expect("the session lock is never poisoned") = None;
+    result
+}
+
+async fn read_lines() -> Result<()> {
+    let mut session_args = startup_args();
+    let mut editor =
+        Editor::<ReplHelper, DefaultHistory>::new().context("Failed to set up the prompt")?;
+    editor.set_helper(Some(ReplHelper::new()));
+    let history = global_folder().ok().map(|folder| folder.join(HISTORY_FILE));
+    if let Some(path) = &history {
+        // There is no history the first time
+        let _ = editor.load_history(path);
+    }
+    println!("Run txs subcommands, e.g. `view -f 0x1::coin::balance ...`. `exit` or Ctrl-D quits");
+
+    loop {
+        let line = match editor.readline(PROMPT) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(err).context("Failed to read the line"),
+        };
+        let words = match shell_words::split(&line) {
+            Ok(words) => words,
+            Err(err) => {
+                eprintln!("Error: {err}");
+                continue;
+            }
+        };
+        if words.is_empty() {
+            continue;
+        }
+        if !words.iter().any(|word| is_secret_flag(word)) {
+            let _ = editor.add_history_entry(line.as_str());
+        }
+
+        let (command, rest) = (words[0].as_str(), &words[1..]);
+        match command {
+            "exit" | "quit" => break,
+            "set" | "use" => {
+                let flags = match (command, rest) {
+                    ("use", [profile]) => vec!["--profile".to_string(), profile.to_owned()],
+                    ("use", _) => {
+                        eprintln!("Error: use takes the name of a profile");
+                        continue;
+                    }
+                    _ => rest.to_vec(),
+                };
+                let args = [session_args.clone(), flags].concat();
+                // Checked now rather than failing every next line
+                match parse_line(&args, &[]) {
+                    Ok(_) => session_args = args,
+                    Err(err) => {
+                        let _ = err.print();
+                    }
+                }
+            }
+            _ => match parse_line(&session_args, &words) {
+                Ok(cli) => {
+                    if let Err(err) = Box::pin(cli.run()).await {
+                        print_error(&err);
+                    }
+                }
+                Err(err) => {
+                    let _ = err.print();
+                }
+            },
+        }
+    }
+
+    if let Some(path) = &history {
+        if let Err(err) = editor.save_history(path) {
+            eprintln!(
+                "Warning: Failed to save the history to {}: {err}",
+                path.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The arguments txs was started with, but the repl subcommand
+fn startup_args() -> Vec<String> {
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    if let Some(index) = args.iter().position(|arg| arg == "repl") {
+        args.remove(index);
+    }
+    args
+}
+
+/// Parsed exactly as the command line, the flags of the line coming after the ones of the
+/// session and overriding them
+fn parse_line(session_args: &[String], words: &[String]) -> Result<TxsCli, clap::Error> {
+    let matches = TxsCli::command()
+        .args_override_self(true)
+        .arg_required_else_help(false)
+        .try_get_matches_from(
+            iter::once("txs")
+                .chain(session_args.iter().map(String::as_str))
+                .chain(words.iter().map(String::as_str)),
+        )?;
+    TxsCli::from_arg_matches(&matches)
+}
+
+fn is_secret_flag(word: &str) -> bool {
+    let flag = word.split('=').next().unwrap_or(word);
+    SECRET_FLAGS.contains(&flag)
+}
+
+/// Completes the subcommands with their aliases, their flags, the profiles after --profile and
+/// the address book names of the words starting with @
+struct ReplHelper {
+    command: clap::Command,
+}
+
+impl ReplHelper {
+    fn new() -> Self {
+        let mut command = TxsCli::command();
+        // Copies the global flags to every subcommand
+        command.build();
+        Self { command }
+    }
+
+    fn candidates(&self, previous: &[&str], word: &str) -> Vec<String> {
+        if word.starts_with(NAME_PREFIX) {
+            return candidates(CompletionValues::Aliases);
+        }
+        if previous.last() == Some(&"--profile") {
+            return candidates(CompletionValues::Profiles);
+        }
+        let mut command = &self.command;
+        for previous in previous {
+            if let Some(subcommand) = command.find_subcommand(previous) {
+                command = subcommand;
+            }
+        }
+        if word.starts_with('-') {
+            return command
+                .get_arguments()
+                .filter(|arg| !arg.is_hide_set())
+                .filter_map(|arg| arg.get_long())
+                .map(|long| format!("--{long}"))
+                .collect();
+        }
+        let mut names = command
+            .get_subcommands()
+            .filter(|subcommand| !subcommand.is_hide_set())
+            .flat_map(|subcommand| {
+                iter::once(subcommand.get_name()).chain(subcommand.get_all_aliases())
+            })
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        if previous.is_empty() {
+            names.extend(BUILTINS.iter().map(|builtin| builtin.to_string()));
+        }
+        names
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let start = line[..pos]
+            .rfind(char::is_whitespace)
+            .map_or(0, |index| index + 1);
+        let word = &line[start..pos];
+        let previous = line[..start].split_whitespace().collect::<Vec<_>>();
+        let mut candidates = self
+            .candidates(&previous, word)
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(word))
+            .collect::<Vec<_>>();
+        candidates.sort();
+        candidates.dedup();
+        Ok((
+            start,
+            candidates
+                .into_iter()
+                .map(|candidate| Pair {
+                    display: candidate.clone(),
+                    replacement: candidate,
+                })
+                .collect(),
+        ))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
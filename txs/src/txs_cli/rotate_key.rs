@@ -0,0 +1,287 @@
+use super::{prompt, submit_transaction, WaitOptions};
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use dialoguer::Confirm;
+use libra_config::extension::cli_config_ext::update_profile_keys;
+use serde::Serialize;
+use txs::{
+    crypto::{
+        ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+        PrivateKey, SigningKey,
+    },
+    extension::{
+        client_ext::{ClientExt, TransactionOptions},
+        ed25519_private_key_ext::Ed25519PrivateKeyExt,
+    },
+    node_api::NodeApi,
+    types::{account_address::AccountAddress, transaction::authenticator::AuthenticationKey},
+    util::{entry_function_payload, format_address},
+};
+
+/// Scheme id of Ed25519 keys in `0x1::account`
+const ED25519_SCHEME: u8 = 0;
+
+/// What both the current and the new key sign, to prove they are held by the account owner.
+/// It is `0x1::account::RotationProofChallenge` behind its type info
+#[derive(Serialize)]
+struct RotationProofChallenge {
+    account_address: AccountAddress,
+    module_name: String,
+    struct_name: String,
+    sequence_number: u64,
+    originator: AccountAddress,
+    current_auth_key: AccountAddress,
+    new_public_key: Vec<u8>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    client: &impl NodeApi,
+    new_private_key: &Ed25519PrivateKey,
+    update_profile: bool,
+    private_key: &Ed25519PrivateKey,
+    options: TransactionOptions,
+    wait_options: &WaitOptions,
+    yes: bool,
+) -> Result<()> {
+    let mut account = private_key.get_account(client, None).await?;
+    let address = account.address();
+    let current_public_key = account.public_key().to_owned();
+    let new_public_key = new_private_key.public_key();
+    if new_public_key == current_public_key {
+        bail!(
+            "The new key is the current key of {}",
+            format_address(&address)
+        );
+    }
+
+    let current_auth_key = client
+        .get_account(address)
+        .await
+        .context(format!(
+            "Failed to get the account {}",
+            format_address(&address)
+        ))?
+        .into_inner()
+        .authentication_key;
+    let challenge = bcs::to_bytes(&RotationProofChallenge {
+        account_address: AccountAddress::ONE,
+        module_name: "account".to_string(),
+        struct_name: "RotationProofChallenge".to_string(),
+        sequence_number: account.sequence_number(),
+        originator: address,
+        current_auth_key: current_auth_key.derived_address(),
+        new_public_key: new_public_key.to_bytes().to_vec(),
+    })?;
+    let cap_rotate_key = private_key.sign_arbitrary_message(&challenge).to_bytes();
+    let cap_update_table = new_private_key
+        .sign_arbitrary_message(&challenge)
+        .to_bytes();
+
+    let function_id = "0x1::account::rotate_authentication_key";
+    let payload = entry_function_payload(
+        function_id,
+        vec![],
+        vec![
+            bcs::to_bytes(&ED25519_SCHEME)?,
+            bcs::to_bytes(&current_public_key.to_bytes().to_vec())?,
+            bcs::to_bytes(&ED25519_SCHEME)?,
+            bcs::to_bytes(&new_public_key.to_bytes().to_vec())?,
+            bcs::to_bytes(&cap_rotate_key.to_vec())?,
+            bcs::to_bytes(&cap_update_table.to_vec())?,
+        ],
+    )?;
+    let signed_trans = client.sign_payload(&mut account, payload, options).await?;
+    prompt::confirm_transaction(
+        &signed_trans,
+        &[
+            ("Function", function_id.to_string()),
+            ("New public key", new_public_key.to_string()),
+        ],
+        yes,
+    )?;
+    submit_transaction::run(client, &signed_trans, wait_options).await?;
+    if wait_options.no_wait {
+        println!("Not waiting for the rotation, check the authentication key of the account later");
+        return Ok(());
+    }
+
+    let auth_key = client
+        .get_account(address)
+        .await
+        .context(format!(
+            "Failed to get the account {}",
+            format_address(&address)
+        ))?
+        .into_inner()
+        .authentication_key;
+    if auth_key != AuthenticationKey::ed25519(&new_public_key) {
+        bail!(
+            "The authentication key of {} is still {auth_key}, the rotation did not take effect",
+            format_address(&address)
+        );
+    }
+    println!(
+        "{} the authentication key of {} is now {auth_key}",
+        "Rotated:".green().bold(),
+        format_address(&address)
+    );
+    println!(
+        "The account keeps its address {}, which is no longer derived from its key",
+        format_address(&address)
+    );
+
+    if update_profile {
+        update_profiles(&current_public_key, new_private_key, yes)?;
+    }
+    Ok(())
+}
+
+/// Replace the old key in the profiles of the config, after asking. The rotation is done by
+/// then, so failing to update the config is only a warning
+fn update_profiles(
+    old_public_key: &Ed25519PublicKey,
+    new_private_key: &Ed25519PrivateKey,
+    yes: bool,
+) -> Result<()> {
+    if !yes {
+        if !atty::is(atty::Stream::Stdin) {
+            println!("Stdin is not a terminal, the profiles of the config were not updated");
+            return Ok(());
+        }
+        if !Confirm::new()
+            .with_prompt("Replace the old key with the new one in the profiles of the config?")
+            .default(true)
+            .interact()?
+        {
+            return Ok(());
+        }
+    }
+    match update_profile_keys(old_public_key, new_private_key) {
+        Ok(profiles) if profiles.is_empty() => {
+            println!("No profile of the config holds the old key")
+        }
+        Ok(profiles) => println!("Updated the profile(s) {}", profiles.join(", ")),
+        Err(err) => eprintln!(
+            "{}",
+            format!("Failed to update the config, replace the key of its profiles by hand: {err}")
+                .yellow()
+        ),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        arg, function_id, mock_node, options, private_key, sender, submitted_call, wait_options,
+    };
+    use serde_json::json;
+    use txs::{
+        crypto::{ed25519::Ed25519Signature, Signature},
+        node_api::MockNode,
+    };
+
+    const ACCOUNT_INFO: &str = "GET /accounts/*";
+
+    fn new_private_key() -> Ed25519PrivateKey {
+        Ed25519PrivateKey::try_from([2; 32].as_slice()).unwrap()
+    }
+
+    /// The node answers with the key of after the rotation from the start, so that the check
+    /// after the rotation passes
+    fn rotated_node() -> MockNode {
+        let new_auth_key = AuthenticationKey::ed25519(&new_private_key().public_key());
+        mock_node().with_route(
+            ACCOUNT_INFO,
+            200,
+            json!({
+                "authentication_key": format!("0x{}", hex::encode(new_auth_key.to_vec())),
+                "sequence_number": "0",
+            }),
+        )
+    }
+
+    async fn rotate(
+        node: &MockNode,
+        new_private_key: &Ed25519PrivateKey,
+        wait_options: &WaitOptions,
+    ) -> Result<()> {
+        run(
+            node,
+            new_private_key,
+            false,
+            &private_key(),
+            options(),
+            wait_options,
+            true,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn prove_both_keys() {
+        let node = mock_node();
+        let no_wait = WaitOptions {
+            no_wait: true,
+            ..wait_options()
+        };
+        rotate(&node, &new_private_key(), &no_wait).await.unwrap();
+        let call = submitted_call(&node);
+        assert_eq!(
+            function_id(&call),
+            "0x1::account::rotate_authentication_key"
+        );
+        let current_public_key = private_key().public_key();
+        let new_public_key = new_private_key().public_key();
+        assert_eq!(arg::<u8>(&call, 0), ED25519_SCHEME);
+        assert_eq!(arg::<Vec<u8>>(&call, 1), current_public_key.to_bytes());
+        assert_eq!(arg::<u8>(&call, 2), ED25519_SCHEME);
+        assert_eq!(arg::<Vec<u8>>(&call, 3), new_public_key.to_bytes());
+
+        // Both keys sign the challenge of the current authentication key
+        let challenge = bcs::to_bytes(&RotationProofChallenge {
+            account_address: AccountAddress::ONE,
+            module_name: "account".to_string(),
+            struct_name: "RotationProofChallenge".to_string(),
+            sequence_number: 0,
+            originator: sender(),
+            current_auth_key: sender(),
+            new_public_key: new_public_key.to_bytes().to_vec(),
+        })
+        .unwrap();
+        for (index, public_key) in [(4, &current_public_key), (5, &new_public_key)] {
+            let signature =
+                Ed25519Signature::try_from(arg::<Vec<u8>>(&call, index).as_slice()).unwrap();
+            signature
+                .verify_arbitrary_msg(&challenge, public_key)
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn check_the_key_after_the_rotation() {
+        rotate(&rotated_node(), &new_private_key(), &wait_options())
+            .await
+            .unwrap();
+
+        // The key of the fixtures stays the same
+        let node = mock_node();
+        let err = rotate(&node, &new_private_key(), &wait_options())
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("did not take effect"),
+            "{err:#}"
+        );
+        assert_eq!(node.submitted().len(), 1);
+
+        let node = mock_node();
+        let err = rotate(&node, &private_key(), &wait_options())
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("is the current key"), "{err:#}");
+        assert!(node.submitted().is_empty());
+    }
+}
@@ -0,0 +1,409 @@
+use super::demo::fresh_account;
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use serde_json::json;
+use std::{fmt::Display, future::Future};
+use txs::{
+    args::FunctionArgs,
+    client::TxsClient,
+    constant::DEMO_TRANSFER_AMOUNT,
+    exit_code::ChecksFailedError,
+    extension::{
+        client_ext::{ClientExt, TransactionOptions},
+        signed_transaction_ext::SignedTransactionExt,
+    },
+    node_api::NodeApi,
+    rest_client::FaucetClient,
+    secret::{parse_private_key, Secret},
+    types::{account_address::AccountAddress, transaction::SignedTransaction, AccountKey},
+    util::{format_address, transaction_hash},
+};
+
+/// The entry function the write checks call, as generate-transaction would
+const TRANSFER_FUNCTION: &str = "0x1::aptos_account::transfer";
+
+/// The write checks after generate-local-account, in their order
+const WRITE_CHECKS: [&str; 4] = [
+    "create-account",
+    "get-account-balance of the sender",
+    "transfer",
+    "generate-transaction",
+];
+
+/// Tallies the checks and prints a named line for each
+#[derive(Default)]
+struct Checks {
+    passed: usize,
+    failed: usize,
+    skipped: usize,
+}
+
+impl Checks {
+    /// The check yields its value and a line describing what it saw, None when it failed
+    async fn run<T>(
+        &mut self,
+        name: &str,
+        check: impl Future<Output = Result<(T, String)>>,
+    ) -> Option<T> {
+        match check.await {
+            Ok((value, detail)) => {
+                self.passed += 1;
+                println!("{} {name}: {detail}", "PASS".green().bold());
+                Some(value)
+            }
+            Err(err) => {
+                self.failed += 1;
+                println!("{} {name}: {err:#}", "FAIL".red().bold());
+                None
+            }
+        }
+    }
+
+    fn skip(&mut self, name: &str, reason: impl Display) {
+        self.skipped += 1;
+        println!("{} {name}: {reason}", "SKIP".yellow().bold());
+    }
+}
+
+/// Go through the read path, then unless `faucet_client` is None the write path with throwaway
+/// accounts: create them with the faucet, read a balance, transfer, and sign a transaction to a
+/// file and submit it from there. The checks after a failed one which need its result are
+/// skipped. Fails with the number of failed checks
+pub async fn run(
+    client: &impl NodeApi,
+    faucet_client: Option<&FaucetClient>,
+    account_address: Option<AccountAddress>,
+    fund_amount: u64,
+) -> Result<()> {
+    let mut checks = Checks::default();
+    let txs_client = checks
+        .run("node", async {
+            let txs_client = TxsClient::connect(client).await?;
+            let index = client.get_index().await?.into_inner();
+            let detail = format!(
+                "chain id {}, ledger version {}",
+                index.chain_id,
+                u64::from(index.ledger_version)
+            );
+            Ok((txs_client, detail))
+        })
+        .await;
+    let txs_client = match txs_client {
+        Some(txs_client) => txs_client,
+        None => return summary(&checks),
+    };
+
+    read_checks(&mut checks, client, &txs_client, account_address).await;
+    match faucet_client {
+        Some(faucet_client) => {
+            write_checks(&mut checks, client, &txs_client, faucet_client, fund_amount).await
+        }
+        None => println!("Write checks skipped with --skip-writes"),
+    }
+    summary(&checks)
+}
+
+async fn read_checks(
+    checks: &mut Checks,
+    client: &impl NodeApi,
+    txs_client: &TxsClient<impl NodeApi>,
+    account_address: Option<AccountAddress>,
+) {
+    checks
+        .run("view", async {
+            let result = txs_client
+                .view(
+                    "0x1::account::exists_at",
+                    None,
+                    Some(FunctionArgs::List("0x1".to_string())),
+                    None,
+                )
+                .await?;
+            if result != json!([true]) {
+                bail!("0x1::account::exists_at(0x1) returned {result}, [true] was expected");
+            }
+            Ok(((), "0x1::account::exists_at(0x1) is true".to_string()))
+        })
+        .await;
+
+    checks
+        .run("abi", async {
+            let function = client.get_function_abi(TRANSFER_FUNCTION).await?;
+            if !function.is_entry {
+                bail!("{TRANSFER_FUNCTION} is not an entry function");
+            }
+            Ok(((), format!("{TRANSFER_FUNCTION} is an entry function")))
+        })
+        .await;
+
+    match account_address {
+        Some(address) => {
+            checks
+                .run("get-account-balance", async {
+                    let balance = txs_client.balance(address).await?;
+                    Ok(((), format!("{} holds {balance}", format_address(&address))))
+                })
+                .await;
+        }
+        None => checks.skip("get-account-balance", "pass --account-address to check one"),
+    }
+}
+
+async fn write_checks(
+    checks: &mut Checks,
+    client: &impl NodeApi,
+    txs_client: &TxsClient<impl NodeApi>,
+    faucet_client: &FaucetClient,
+    fund_amount: u64,
+) {
+    let accounts = checks
+        .run("generate-local-account", async {
+            let sender = fresh_account()?;
+            let recipient = fresh_account()?;
+            // The key read back as from a key file must sign for the same address
+            let encoded = Secret::new(hex::encode(sender.private_key().to_bytes()));
+            let read_back = parse_private_key(&encoded, "the generated key")?;
+            let address = AccountKey::from_private_key(read_back)
+                .authentication_key()
+                .derived_address();
+            if address != sender.address() {
+                bail!("The key read back derives another address {address}");
+            }
+            let detail = format!(
+                "sender {}, recipient {}",
+                format_address(&sender.address()),
+                format_address(&recipient.address())
+            );
+            Ok(((sender, recipient), detail))
+        })
+        .await;
+    let (mut sender, recipient) = match accounts {
+        Some(accounts) => accounts,
+        None => return skip_writes(checks, "generate-local-account", &WRITE_CHECKS),
+    };
+    let recipient = recipient.address();
+
+    let created = checks
+        .run("create-account", async {
+            faucet_client
+                .fund(sender.address(), fund_amount)
+                .await
+                .context("The faucet failed to fund the sender")?;
+            faucet_client
+                .create_account(recipient)
+                .await
+                .context("The faucet failed to create the recipient")?;
+            Ok(((), format!("sender funded with {fund_amount}")))
+        })
+        .await;
+    if created.is_none() {
+        return skip_writes(checks, "create-account", &WRITE_CHECKS[1..]);
+    }
+
+    checks
+        .run("get-account-balance of the sender", async {
+            let balance = txs_client.balance(sender.address()).await?;
+            if balance != fund_amount {
+                bail!("The sender holds {balance}, {fund_amount} was expected");
+            }
+            Ok(((), format!("{balance}")))
+        })
+        .await;
+
+    let transferred = checks
+        .run("transfer", async {
+            let result = txs_client
+                .transfer(
+                    recipient,
+                    DEMO_TRANSFER_AMOUNT,
+                    &mut sender,
+                    TransactionOptions::default(),
+                )
+                .await?;
+            let balance = txs_client.balance(recipient).await?;
+            if balance != DEMO_TRANSFER_AMOUNT {
+                bail!("The recipient holds {balance}, {DEMO_TRANSFER_AMOUNT} was expected");
+            }
+            let detail = format!(
+                "{} committed at version {}",
+                result.hash.to_hex_literal(),
+                result.version
+            );
+            Ok(((), detail))
+        })
+        .await;
+    if transferred.is_none() {
+        // The sequence number of the sender is unknown after a failed transfer
+        return skip_writes(checks, "transfer", &WRITE_CHECKS[3..]);
+    }
+
+    checks
+        .run("generate-transaction", async {
+            let signed_trans = client
+                .generate_transaction(
+                    &mut sender,
+                    &[],
+                    TRANSFER_FUNCTION,
+                    None,
+                    Some(FunctionArgs::List(format!(
+                        "{}, {DEMO_TRANSFER_AMOUNT}",
+                        recipient.to_hex_literal()
+                    ))),
+                    TransactionOptions::default(),
+                    true,
+                )
+                .await?;
+            let read_back = round_trip(&signed_trans)?;
+            let result = txs_client.submit(&read_back).await?;
+            let balance = txs_client.balance(recipient).await?;
+            if balance != 2 * DEMO_TRANSFER_AMOUNT {
+                bail!(
+                    "The recipient holds {balance}, {} was expected",
+                    2 * DEMO_TRANSFER_AMOUNT
+                );
+            }
+            Ok((
+                (),
+                format!(
+                    "{} signed to a file and submitted",
+                    result.hash.to_hex_literal()
+                ),
+            ))
+        })
+        .await;
+}
+
+/// Save the transaction to a temporary file and load it again, checking it is unchanged. The
+/// file is removed either way
+fn round_trip(signed_trans: &SignedTransaction) -> Result<SignedTransaction> {
+    let path = std::env::temp_dir().join(format!("txs-selftest-{}.bcs", std::process::id()));
+    let read_back = signed_trans
+        .save(&path, true)
+        .and_then(|_| SignedTransaction::load(&path));
+    let _ = std::fs::remove_file(&path);
+    let read_back = read_back?;
+    if transaction_hash(&read_back) != transaction_hash(signed_trans) {
+        bail!("The transaction read back from its file has another hash");
+    }
+    Ok(read_back)
+}
+
+fn skip_writes(checks: &mut Checks, failed: &str, skipped: &[&str]) {
+    for name in skipped {
+        checks.skip(name, format!("needs {failed}"));
+    }
+}
+
+fn summary(checks: &Checks) -> Result<()> {
+    println!(
+        "{} passed, {} failed, {} skipped",
+        checks.passed, checks.failed, checks.skipped
+    );
+    if checks.failed > 0 {
+        return Err(ChecksFailedError {
+            failed: checks.failed,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        address, error_body, mock_node, module, signed_transfer, VIEW,
+    };
+    use txs::node_api::MockNode;
+
+    /// The node of the fixtures with the modules the read checks call
+    fn read_node() -> MockNode {
+        mock_node()
+            .with_route(
+                "GET /accounts/0x1/module/account",
+                200,
+                module(
+                    "account",
+                    json!([{
+                        "name": "exists_at",
+                        "visibility": "public",
+                        "is_entry": false,
+                        "is_view": true,
+                        "generic_type_params": [],
+                        "params": ["address"],
+                        "return": ["bool"],
+                    }]),
+                ),
+            )
+            .with_route(
+                "GET /accounts/0x1/module/aptos_account",
+                200,
+                module(
+                    "aptos_account",
+                    json!([{
+                        "name": "transfer",
+                        "visibility": "public",
+                        "is_entry": true,
+                        "is_view": false,
+                        "generic_type_params": [],
+                        "params": ["&signer", "address", "u64"],
+                        "return": [],
+                    }]),
+                ),
+            )
+            .with_route(VIEW, 200, json!([true]))
+    }
+
+    async fn read(node: &MockNode, account_address: Option<AccountAddress>) -> Checks {
+        let mut checks = Checks::default();
+        let txs_client = TxsClient::connect(node).await.unwrap();
+        read_checks(&mut checks, node, &txs_client, account_address).await;
+        checks
+    }
+
+    #[tokio::test]
+    async fn run_the_read_checks() {
+        let node = read_node();
+        let checks = read(&node, Some(address("0x2"))).await;
+        assert_eq!((checks.passed, checks.failed, checks.skipped), (3, 0, 0));
+        let request = &node.view_requests()[0];
+        assert_eq!(request.function.to_string(), "0x1::account::exists_at");
+        assert_eq!(request.arguments, [json!("0x1")]);
+        assert!(node.requests().contains(
+            &"GET /accounts/0x2/resource/0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"
+                .to_string()
+        ));
+
+        // Each check fails or is skipped on its own
+        let node = read_node().with_route(VIEW, 200, json!([false]));
+        let checks = read(&node, Some(address("0x2"))).await;
+        assert_eq!((checks.passed, checks.failed, checks.skipped), (2, 1, 0));
+
+        let checks = read(&read_node(), None).await;
+        assert_eq!((checks.passed, checks.failed, checks.skipped), (2, 0, 1));
+
+        let node = read_node().with_route(
+            "GET /accounts/0x2/resource/0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+            404,
+            error_body("Account not found", "account_not_found"),
+        );
+        let checks = read(&node, Some(address("0x2"))).await;
+        assert_eq!((checks.passed, checks.failed, checks.skipped), (2, 1, 0));
+    }
+
+    #[tokio::test]
+    async fn stop_without_a_node() {
+        let node =
+            read_node().with_route("GET /", 503, error_body("Unavailable", "internal_error"));
+        let err = run(&node, None, Some(address("0x2")), 0).await.unwrap_err();
+        assert_eq!(err.downcast_ref::<ChecksFailedError>().unwrap().failed, 1);
+        assert!(node.view_requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn read_back_the_signed_file() {
+        let signed_trans = signed_transfer(&mock_node()).await;
+        let read_back = round_trip(&signed_trans).unwrap();
+        assert_eq!(read_back, signed_trans);
+    }
+}
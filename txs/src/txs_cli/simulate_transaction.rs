@@ -0,0 +1,56 @@
+use anyhow::{bail, Result};
+use colored::Colorize;
+use txs::{
+    extension::client_ext::ClientExt, node_api::NodeApi, types::transaction::SignedTransaction,
+    util::format_simulated_transaction,
+};
+
+/// Simulate the transaction and print the outcome. Fails if the transaction would abort,
+/// so it can be used as a pre-flight check before submitting
+pub async fn run(client: &impl NodeApi, signed_trans: &SignedTransaction) -> Result<()> {
+    println!("{}", "Simulating transaction...".green().bold());
+    let simulated_trans = client.simulate_ext(signed_trans).await?;
+    println!("{}", format_simulated_transaction(&simulated_trans));
+
+    if !simulated_trans.info.success {
+        bail!(
+            "Simulation failed with VM status: {}",
+            client
+                .explain_vm_status(&simulated_trans.info.vm_status)
+                .await
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        aborted_transaction, fixture_body, mock_node, signed_transfer, SIMULATE, TRANSACTION,
+    };
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn simulate_without_submitting() {
+        let node = mock_node().with_route(SIMULATE, 200, json!([fixture_body(TRANSACTION)]));
+        let signed_trans = signed_transfer(&node).await;
+        run(&node, &signed_trans).await.unwrap();
+        assert!(node.requests().iter().any(|route| route == SIMULATE));
+        assert!(node.submitted().is_empty());
+    }
+
+    #[tokio::test]
+    async fn fail_with_the_decoded_abort() {
+        let node = mock_node().with_route(SIMULATE, 200, json!([aborted_transaction()]));
+        let signed_trans = signed_transfer(&node).await;
+        let err = run(&node, &signed_trans).await.unwrap_err();
+        let message = format!("{err:#}");
+        assert!(
+            message.contains("Simulation failed with VM status"),
+            "{message}"
+        );
+        assert!(message.contains("EINSUFFICIENT_BALANCE"), "{message}");
+        assert!(node.submitted().is_empty());
+    }
+}
@@ -1,10 +1,224 @@
+use super::{prompt, receipt, txs_client, WaitOptions};
 use anyhow::Result;
-use libra_config::extension::client_ext::ClientExt;
-use txs::{rest_client::Client, types::transaction::SignedTransaction};
+use std::time::Duration;
+use txs::{
+    error::TxsError,
+    explorer::explorer_url,
+    extension::client_ext::{ClientExt, TransactionOptions},
+    node_api::NodeApi,
+    rest_client::aptos_api_types::Transaction,
+    types::{
+        transaction::{SignedTransaction, TransactionPayload},
+        LocalAccount,
+    },
+};
 
-pub async fn run(signed_trans: &SignedTransaction) -> Result<()> {
-    let client = Client::default()?;
-    let pending_trans = client.submit(signed_trans).await?.into_inner();
-    client.wait_for_transaction(&pending_trans).await?;
+pub async fn run(
+    client: &impl NodeApi,
+    signed_trans: &SignedTransaction,
+    wait_options: &WaitOptions,
+) -> Result<()> {
+    submit(client, signed_trans, wait_options).await?;
     Ok(())
 }
+
+/// Submit the transaction and wait for it, unless --no-wait is set, then print its receipt.
+/// Returns the committed transaction, None when it was not waited for
+pub async fn submit(
+    client: &impl NodeApi,
+    signed_trans: &SignedTransaction,
+    wait_options: &WaitOptions,
+) -> Result<Option<Transaction>> {
+    let txs_client = txs_client(client).await?.with_wait(
+        Duration::from_secs(wait_options.wait_timeout_secs),
+        Duration::from_millis(wait_options.poll_interval_ms),
+    );
+    let hash = match txs_client.submit_no_wait(signed_trans).await {
+        Ok(hash) => hash,
+        Err(err) => return Err(explain_sequence_number(client, signed_trans, err.into()).await),
+    };
+    let explorer_url = explorer_url(&hash);
+    if wait_options.no_wait {
+        println!("{}", hash.to_hex_literal());
+        // Only the hash goes to stdout, for scripts
+        if let Some(explorer_url) = explorer_url {
+            eprintln!("Explorer: {explorer_url}");
+        }
+        return Ok(None);
+    }
+
+    println!("Transaction hash: {}", hash.to_hex_literal());
+    if let Some(explorer_url) = explorer_url {
+        println!("Explorer: {explorer_url}");
+    }
+    let result = match txs_client
+        .wait(hash, signed_trans.expiration_timestamp_secs())
+        .await
+    {
+        Ok(result) => result,
+        Err(err) => {
+            // A failed transaction is committed too, and paid for
+            if let TxsError::Aborted { vm_status, .. } | TxsError::ExecutionFailed { vm_status } =
+                &err
+            {
+                if let Ok(transaction) = client.get_transaction_by_hash(hash).await {
+                    receipt::print(
+                        client,
+                        &transaction.into_inner(),
+                        vm_status,
+                        wait_options.receipt_format,
+                    )
+                    .await?;
+                }
+            }
+            return Err(err.into());
+        }
+    };
+    receipt::print(
+        client,
+        &result.transaction,
+        &result.vm_status,
+        wait_options.receipt_format,
+    )
+    .await?;
+    Ok(Some(result.transaction))
+}
+
+/// Add how the sequence number of the transaction differs from the one of the account on-chain
+/// when the node refused it, e.g. as it was signed from a nonce file out of sync with the chain
+async fn explain_sequence_number(
+    client: &impl NodeApi,
+    signed_trans: &SignedTransaction,
+    err: anyhow::Error,
+) -> anyhow::Error {
+    let message = format!("{err:#}");
+    let mismatch = if message.contains("SEQUENCE_NUMBER_TOO_OLD") {
+        "which the account already used"
+    } else if message.contains("SEQUENCE_NUMBER_TOO_NEW") {
+        "ahead of the account, a transaction before it was never submitted"
+    } else {
+        return err;
+    };
+    let on_chain = match client.get_sequence_number(signed_trans.sender()).await {
+        Ok(on_chain) => on_chain,
+        Err(_) => return err,
+    };
+    err.context(format!(
+        "The transaction has sequence number {}, {mismatch}: the next one on-chain is {on_chain}. \
+        If it was signed with --nonce-file, set the file to the chain with `txs nonce sync`",
+        signed_trans.sequence_number()
+    ))
+}
+
+/// Sign the payload, show it with the details for confirmation, then submit it
+pub async fn sign_and_submit(
+    client: &impl NodeApi,
+    from_account: &mut LocalAccount,
+    payload: TransactionPayload,
+    options: TransactionOptions,
+    details: &[(&str, String)],
+    wait_options: &WaitOptions,
+    yes: bool,
+) -> Result<()> {
+    let signed_trans = client.sign_payload(from_account, payload, options).await?;
+    prompt::confirm_transaction(&signed_trans, details, yes)?;
+    run(client, &signed_trans, wait_options).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        aborted_transaction, at_sequence_number, category, error_body, mock_node, signed_transfer,
+        submitted, wait_options, SUBMIT, TRANSACTION,
+    };
+    use txs::node_api::MockNode;
+
+    fn waited_for(node: &MockNode) -> bool {
+        node.requests()
+            .iter()
+            .any(|route| route.starts_with("GET /transactions/by_hash/"))
+    }
+
+    #[tokio::test]
+    async fn submit_then_wait_for_the_commit() {
+        let node = mock_node();
+        let signed_trans = signed_transfer(&node).await;
+        let transaction = submit(&node, &signed_trans, &wait_options()).await.unwrap();
+        assert!(transaction.unwrap().success());
+        assert_eq!(submitted(&node), signed_trans);
+        assert!(waited_for(&node));
+
+        let node = mock_node();
+        let no_wait = WaitOptions {
+            no_wait: true,
+            ..wait_options()
+        };
+        assert!(submit(&node, &signed_trans, &no_wait)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(submitted(&node), signed_trans);
+        assert!(!waited_for(&node));
+
+        // The aborted transaction is committed, its receipt is printed before the error
+        let node = mock_node().with_route(TRANSACTION, 200, aborted_transaction());
+        let err = category(run(&node, &signed_trans, &wait_options()).await);
+        assert!(
+            matches!(err, TxsError::Aborted { code: 0x10006, .. }),
+            "{err:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn explain_a_sequence_number_out_of_sync() {
+        let signed_trans = signed_transfer(&mock_node()).await;
+        let refused = |code: &str| {
+            at_sequence_number(mock_node(), 7)
+                .with_route(
+                    SUBMIT,
+                    400,
+                    error_body(
+                        &format!("Invalid transaction: Type: Validation Code: {code}"),
+                        "vm_error",
+                    ),
+                )
+                // Not an earlier attempt which went through
+                .with_route(
+                    TRANSACTION,
+                    404,
+                    error_body("Transaction not found", "transaction_not_found"),
+                )
+        };
+
+        let node = refused("SEQUENCE_NUMBER_TOO_OLD");
+        let err = run(&node, &signed_trans, &wait_options())
+            .await
+            .unwrap_err();
+        let message = format!("{err:#}");
+        assert!(
+            message.contains(
+                "sequence number 0, which the account already used: the next one on-chain is 7"
+            ),
+            "{message}"
+        );
+        assert!(message.contains("txs nonce sync"), "{message}");
+
+        let node = refused("SEQUENCE_NUMBER_TOO_NEW");
+        let err = run(&node, &signed_trans, &wait_options())
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("a transaction before it was never submitted"),
+            "{err:#}"
+        );
+        assert!(matches!(TxsError::from(err), TxsError::Rejected(_)));
+
+        // Other rejections are left as they are
+        let node = refused("INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE");
+        let err = run(&node, &signed_trans, &wait_options())
+            .await
+            .unwrap_err();
+        assert!(!format!("{err:#}").contains("on-chain is"), "{err:#}");
+    }
+}
@@ -0,0 +1,210 @@
+//! The node the tests of the subcommands run against, answering from the fixtures of the
+//! library tests without any network
+
+use super::WaitOptions;
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::sync::Once;
+use txs::{
+    client::transfer_payload,
+    crypto::{ed25519::Ed25519PrivateKey, PrivateKey},
+    error::TxsError,
+    extension::client_ext::{ClientExt, TransactionOptions},
+    node_api::MockNode,
+    types::{
+        account_address::AccountAddress,
+        transaction::{
+            authenticator::AuthenticationKey, EntryFunction, SignedTransaction, TransactionPayload,
+        },
+        LocalAccount,
+    },
+    util::{set_progress_output, OutputFormat},
+};
+
+const FIXTURES: &str = include_str!("../../tests/fixtures/node/transfer.json");
+pub const TRANSACTION: &str = "GET /transactions/by_hash/*";
+pub const SUBMIT: &str = "POST /transactions";
+pub const VIEW: &str = "POST /view";
+pub const SIMULATE: &str = "POST /transactions/simulate";
+pub const ACCOUNT: &str = "GET /accounts/*/resource/0x1::account::Account";
+pub const COIN_STORE: &str =
+    "GET /accounts/*/resource/0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>";
+
+static SETUP: Once = Once::new();
+
+/// The node of the fixtures, on which every account exists at sequence number 0 with the key
+/// of `private_key`. The modules fetched for the ABI checks are cached in a home folder of the
+/// test run, so that the cache of the user is neither read nor written
+pub fn mock_node() -> MockNode {
+    SETUP.call_once(|| {
+        let home = std::env::temp_dir().join(format!("txs-cli-test-{}", std::process::id()));
+        std::fs::create_dir_all(&home).unwrap();
+        std::env::set_var("HOME", home);
+        set_progress_output(false);
+    });
+    let node = MockNode::from_json(FIXTURES).unwrap().with_route(
+        "GET /accounts/0x1/resource/0x1::account::OriginatingAddress",
+        200,
+        json!({ "type": "0x1::account::OriginatingAddress", "data": {} }),
+    );
+    at_sequence_number(node, 0)
+}
+
+/// The node, on which every account is at the sequence number with the key of `private_key`
+pub fn at_sequence_number(node: MockNode, sequence_number: u64) -> MockNode {
+    let account = json!({
+        "authentication_key": format!("0x{}", hex::encode(auth_key().to_vec())),
+        "sequence_number": sequence_number.to_string(),
+    });
+    node.with_route(
+        ACCOUNT,
+        200,
+        json!({ "type": "0x1::account::Account", "data": account }),
+    )
+    .with_route("GET /accounts/*", 200, account)
+}
+
+/// The node of the fixtures, on which every account holds `value` base units of the native coin
+/// and is not a slow wallet
+pub fn funded_node(value: u64) -> MockNode {
+    mock_node()
+        .with_route(COIN_STORE, 200, coin_store(value))
+        .with_route(
+            "GET /accounts/*/resource/0x1::slow_wallet::SlowWallet",
+            200,
+            Value::Null,
+        )
+}
+
+pub fn error_body(message: &str, error_code: &str) -> Value {
+    json!({ "message": message, "error_code": error_code, "vm_error_code": null })
+}
+
+/// The body of a route in the fixtures, to answer with a variant of it
+pub fn fixture_body(route: &str) -> Value {
+    serde_json::from_str::<Value>(FIXTURES).unwrap()["routes"][route]["body"].clone()
+}
+
+/// A `CoinStore` of the native coin holding `value` base units
+pub fn coin_store(value: u64) -> Value {
+    json!({
+        "type": "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+        "data": { "coin": { "value": value.to_string() }, "frozen": false },
+    })
+}
+
+/// A module of 0x1 without bytecode, exposing the functions of its ABI
+pub fn module(name: &str, exposed_functions: Value) -> Value {
+    json!({
+        "bytecode": "0x",
+        "abi": {
+            "address": "0x1",
+            "name": name,
+            "friends": [],
+            "exposed_functions": exposed_functions,
+            "structs": [],
+        },
+    })
+}
+
+/// The committed transaction of the fixtures, aborted by 0x1::coin
+pub fn aborted_transaction() -> Value {
+    let mut aborted = fixture_body(TRANSACTION);
+    aborted["success"] = json!(false);
+    aborted["vm_status"] =
+        json!("Move abort in 0x1::coin: EINSUFFICIENT_BALANCE(0x10006): Not enough coins");
+    aborted
+}
+
+/// The answer of the node to a View function which aborted
+pub fn view_abort() -> Value {
+    error_body("Move abort in 0x1::coin: 0x60005", "vm_error")
+}
+
+pub fn private_key() -> Ed25519PrivateKey {
+    Ed25519PrivateKey::try_from([1; 32].as_slice()).unwrap()
+}
+
+pub fn auth_key() -> AuthenticationKey {
+    AuthenticationKey::ed25519(&private_key().public_key())
+}
+
+/// The account of `private_key`
+pub fn sender() -> AccountAddress {
+    auth_key().derived_address()
+}
+
+pub fn address(hex: &str) -> AccountAddress {
+    AccountAddress::from_hex_literal(hex).unwrap()
+}
+
+/// The gas is set, so that nothing is simulated
+pub fn options() -> TransactionOptions {
+    TransactionOptions {
+        max_gas_amount: Some(2_000),
+        gas_unit_price: Some(100),
+        ..TransactionOptions::default()
+    }
+}
+
+pub fn wait_options() -> WaitOptions {
+    WaitOptions {
+        wait_timeout_secs: 1,
+        poll_interval_ms: 10,
+        no_wait: false,
+        receipt_format: OutputFormat::Text,
+    }
+}
+
+/// A transfer of 10 coins to 0x3, signed by `private_key`
+pub async fn signed_transfer(node: &MockNode) -> SignedTransaction {
+    let mut sender = LocalAccount::new(sender(), private_key(), 0);
+    let payload = transfer_payload(address("0x3"), 10, None).unwrap();
+    node.sign_payload(&mut sender, payload, options())
+        .await
+        .unwrap()
+}
+
+/// The library category of the error of a command
+pub fn category<T>(result: Result<T>) -> TxsError {
+    match result {
+        Ok(_) => panic!("the command did not fail"),
+        Err(err) => TxsError::from(err),
+    }
+}
+
+/// The only transaction the node was sent
+pub fn submitted(node: &MockNode) -> SignedTransaction {
+    let mut submitted = node.submitted();
+    assert_eq!(submitted.len(), 1, "{submitted:?}");
+    submitted.remove(0)
+}
+
+/// The entry function called by the only transaction the node was sent
+pub fn submitted_call(node: &MockNode) -> EntryFunction {
+    entry_function(&submitted(node))
+}
+
+/// The entry function the transaction calls
+pub fn entry_function(signed_trans: &SignedTransaction) -> EntryFunction {
+    match signed_trans.payload() {
+        TransactionPayload::EntryFunction(entry_function) => entry_function.clone(),
+        payload => panic!("Not an entry function: {payload:?}"),
+    }
+}
+
+/// The function of the call, e.g. `0x1::coin::transfer`
+pub fn function_id(call: &EntryFunction) -> String {
+    format!(
+        "{}::{}::{}",
+        call.module().address().to_hex_literal(),
+        call.module().name(),
+        call.function()
+    )
+}
+
+/// The argument of the call at `index`, decoded from BCS
+pub fn arg<T: DeserializeOwned>(call: &EntryFunction, index: usize) -> T {
+    bcs::from_bytes(&call.args()[index]).unwrap()
+}
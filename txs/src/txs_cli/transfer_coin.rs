@@ -1,48 +1,176 @@
-use anyhow::{Context, Result};
-use libra_config::extension::client_ext::{ClientExt, DEFAULT_TIMEOUT_SECS};
+use super::{prompt, submit_transaction, txs_client, WaitOptions};
+use anyhow::{bail, Context, Result};
+pub use txs::client::{check_coin_store, native_or, parse_coin_type, transfer_payload};
 use txs::{
-    coin_client::{CoinClient, TransferOptions},
-    constant::{DEFAULT_GAS_UNIT_PRICE, DEFAULT_MAX_GAS_AMOUNT},
-    crypto::{ed25519::Ed25519PrivateKey, ValidCryptoMaterialStringExt},
-    extension::ed25519_private_key_ext::Ed25519PrivateKeyExt,
-    rest_client::Client,
-    types::account_address::AccountAddress,
+    client::Asset,
+    crypto::ed25519::Ed25519PrivateKey,
+    extension::client_ext::TransactionOptions,
+    node_api::NodeApi,
+    nonce::NonceLease,
+    signer::TransactionSigner,
+    util::{format_address, format_decimal_amount, parse_address, parse_decimal_amount},
 };
 
+#[allow(clippy::too_many_arguments)]
 pub async fn run(
+    client: &impl NodeApi,
     to_account: &str,
-    amount: u64,
-    private_key: &str,
-    max_gas: Option<u64>,
-    gas_unit_price: Option<u64>,
+    amount: Option<&str>,
+    raw: bool,
+    coin_type: Option<&str>,
+    fa_metadata: Option<&str>,
+    signer: &TransactionSigner,
+    fee_payer_private_key: Option<&Ed25519PrivateKey>,
+    options: TransactionOptions,
+    nonce_lease: Option<NonceLease>,
+    wait_options: &WaitOptions,
+    force: bool,
+    yes: bool,
 ) -> Result<()> {
-    let client = Client::default()?;
-    let coint_client = CoinClient::new(&client);
-    let private_key = Ed25519PrivateKey::from_encoded_string(private_key)?;
-    let mut from_account = private_key.get_account(None).await?;
-    let to_account = AccountAddress::from_hex_literal(to_account).context(format!(
-        "Failed to parse the recipient address {to_account}"
-    ))?;
-    let transfer_options = TransferOptions {
-        max_gas_amount: max_gas.unwrap_or(DEFAULT_MAX_GAS_AMOUNT),
-        gas_unit_price: gas_unit_price.unwrap_or(DEFAULT_GAS_UNIT_PRICE),
-        timeout_secs: DEFAULT_TIMEOUT_SECS,
-        coin_type: "0x1::aptos_coin::AptosCoin",
+    let to_account = parse_address(to_account, "--to-account")?;
+    let asset = match fa_metadata {
+        Some(metadata) => Asset::Fungible(parse_address(metadata, "--fa-metadata")?),
+        None => Asset::Coin(coin_type.map(parse_coin_type).transpose()?),
     };
+    let txs_client = txs_client(client).await?;
 
-    client
-        .wait_for_transaction(
-            &coint_client
-                .transfer(
-                    &mut from_account,
-                    to_account,
-                    amount,
-                    Some(transfer_options),
-                )
-                .await?,
+    let decimals = if raw {
+        None
+    } else {
+        Some(txs_client.decimals(&asset).await?)
+    };
+    let amount = amount
+        .map(|amount| parse_amount(amount, decimals))
+        .transpose()?;
+    let (signed_trans, base_amount) = match (fee_payer_private_key, amount) {
+        (Some(fee_payer_private_key), Some(base_amount)) => {
+            if let Asset::Coin(Some(coin_type)) = &asset {
+                check_coin_store(client, to_account, coin_type).await?;
+            }
+            let payload = asset.payload(to_account, base_amount)?;
+            let signed_trans = signer
+                .sign_fee_payer_payload(client, &[], fee_payer_private_key, payload, options)
+                .await?;
+            (signed_trans, base_amount)
+        }
+        (Some(_), None) => bail!("--all cannot be used with a fee payer"),
+        (None, amount) => {
+            txs_client
+                .sign_transfer(&asset, to_account, amount, signer, options)
+                .await?
+        }
+    };
+    if !force && amount.is_some() {
+        // It only ever leaves this machine once submitted
+        txs_client
+            .check_funds(&asset, &signed_trans, base_amount)
+            .await
+            .context("The transfer was not submitted, pass --force to submit it anyway")?;
+    }
+    prompt::confirm_transaction(
+        &signed_trans,
+        &[
+            ("Recipient", format_address(&to_account)),
+            (
+                "Amount",
+                match amount {
+                    Some(_) => describe_amount(base_amount, decimals),
+                    None => format!(
+                        "{}, all the spendable balance",
+                        describe_amount(base_amount, decimals)
+                    ),
+                },
+            ),
+            match &asset {
+                Asset::Coin(coin_type) => ("Coin type", native_or(coin_type.clone())?.to_string()),
+                Asset::Fungible(metadata) => ("Fungible asset", format_address(metadata)),
+            },
+        ],
+        yes,
+    )?;
+    if let Some(nonce_lease) = nonce_lease {
+        nonce_lease.commit(signed_trans.sender())?;
+    }
+    submit_transaction::run(client, &signed_trans, wait_options).await
+}
+
+/// Decimal amounts are converted with the decimals of the coin, raw amounts are taken as base units
+pub fn parse_amount(amount: &str, decimals: Option<u8>) -> Result<u64> {
+    match decimals {
+        Some(decimals) => parse_decimal_amount(amount, decimals),
+        None => amount.parse::<u64>().context(format!(
+            "Invalid amount: {amount}, expected an integer in base units"
+        )),
+    }
+}
+
+pub fn describe_amount(base_amount: u64, decimals: Option<u8>) -> String {
+    match decimals {
+        Some(decimals) => format!(
+            "{} ({base_amount} base units)",
+            format_decimal_amount(base_amount, decimals)
+        ),
+        None => format!("{base_amount} base units"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        address, arg, function_id, funded_node, options, private_key, submitted_call, wait_options,
+    };
+    use txs::{node_api::MockNode, types::account_address::AccountAddress};
+
+    async fn transfer(node: &MockNode, amount: Option<&str>, raw: bool) -> Result<()> {
+        run(
+            node,
+            "0x3",
+            amount,
+            raw,
+            None,
+            None,
+            &TransactionSigner::PrivateKey(private_key()),
+            None,
+            options(),
+            None,
+            &wait_options(),
+            false,
+            true,
         )
-        .await?;
+        .await
+    }
+
+    #[tokio::test]
+    async fn transfer_in_coins_or_base_units() {
+        let node = funded_node(10_000_000);
+        transfer(&node, Some("1.5"), false).await.unwrap();
+        let call = submitted_call(&node);
+        assert_eq!(function_id(&call), "0x1::aptos_account::transfer_coins");
+        assert_eq!(arg::<AccountAddress>(&call, 0), address("0x3"));
+        assert_eq!(arg::<u64>(&call, 1), 1_500_000);
+
+        let node = funded_node(10_000_000);
+        transfer(&node, Some("15"), true).await.unwrap();
+        assert_eq!(arg::<u64>(&submitted_call(&node), 1), 15);
+    }
+
+    #[tokio::test]
+    async fn check_the_funds_first() {
+        // 1.5 coins and the fee of 2000 gas at 100 are more than the balance
+        let node = funded_node(1_600_000);
+        let err = transfer(&node, Some("1.5"), false).await.unwrap_err();
+        assert!(format!("{err:#}").contains("pass --force"), "{err:#}");
+        assert!(node.submitted().is_empty());
+    }
 
-    println!("Success!");
-    Ok(())
+    #[tokio::test]
+    async fn sweep_all_but_the_fee() {
+        let node = funded_node(10_000_000);
+        transfer(&node, None, false).await.unwrap();
+        assert_eq!(
+            arg::<u64>(&submitted_call(&node), 1),
+            10_000_000 - 2_000 * 100
+        );
+    }
 }
@@ -0,0 +1,171 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use txs::{
+    constant::{DEFAULT_POLL_INTERVAL_MS, DEFAULT_WAIT_TIMEOUT_SECS},
+    crypto::HashValue,
+    explorer::explorer_url,
+    extension::client_ext::WaitTimeoutError,
+    node_api::NodeApi,
+    rest_client::{aptos_api_types::Transaction, error::RestError},
+    util::{format_output, OutputFormat},
+};
+
+pub async fn run(
+    client: &impl NodeApi,
+    hash: &str,
+    wait: bool,
+    format: OutputFormat,
+) -> Result<String> {
+    let hash = HashValue::from_hex(hash.trim().trim_start_matches("0x"))
+        .context(format!("Invalid transaction hash: {hash}"))?;
+
+    let timeout = Duration::from_secs(DEFAULT_WAIT_TIMEOUT_SECS);
+    let start = Instant::now();
+    let mut status = loop {
+        let status = get_status(client, hash).await?;
+        if !wait || status["status"] != "pending" {
+            break status;
+        }
+        if start.elapsed() >= timeout {
+            return Err(WaitTimeoutError { hash, timeout }.into());
+        }
+        tokio::time::sleep(Duration::from_millis(DEFAULT_POLL_INTERVAL_MS)).await;
+    };
+    if let Some(explorer_url) = explorer_url(&hash) {
+        status["explorer_url"] = json!(explorer_url);
+    }
+
+    if format == OutputFormat::Text {
+        Ok(format_status(&status))
+    } else {
+        format_output(&status, format)
+    }
+}
+
+async fn get_status(client: &impl NodeApi, hash: HashValue) -> Result<Value> {
+    let transaction = match client.get_transaction_by_hash(hash).await {
+        Ok(response) => response.into_inner(),
+        Err(RestError::Api(err)) if err.status_code.as_u16() == 404 => {
+            return Ok(json!({ "hash": hash.to_hex_literal(), "status": "not_found" }));
+        }
+        Err(err) => {
+            return Err(err).context(format!(
+                "Failed to get the transaction {}",
+                hash.to_hex_literal()
+            ))
+        }
+    };
+
+    if matches!(transaction, Transaction::PendingTransaction(_)) {
+        return Ok(json!({ "hash": hash.to_hex_literal(), "status": "pending" }));
+    }
+
+    let info = transaction.transaction_info()?;
+    // Only some kinds of transactions emit events, they all serialize them the same way
+    let events = serde_json::to_value(&transaction)?["events"].take();
+    Ok(json!({
+        "hash": hash.to_hex_literal(),
+        "status": "committed",
+        "success": info.success,
+        "vm_status": info.vm_status,
+        "gas_used": u64::from(info.gas_used),
+        "version": u64::from(info.version),
+        "events": if events.is_null() { json!([]) } else { events },
+    }))
+}
+
+fn format_status(status: &Value) -> String {
+    let mut lines = vec![
+        format!(
+            "Transaction: {}",
+            status["hash"].as_str().unwrap_or_default()
+        ),
+        format!(
+            "Status: {}",
+            status["status"]
+                .as_str()
+                .unwrap_or_default()
+                .replace('_', " ")
+        ),
+    ];
+    if let Some(explorer_url) = status["explorer_url"].as_str() {
+        lines.push(format!("Explorer: {explorer_url}"));
+    }
+    if status["status"] == "committed" {
+        lines.push(format!("Success: {}", status["success"]));
+        lines.push(format!(
+            "VM status: {}",
+            status["vm_status"].as_str().unwrap_or_default()
+        ));
+        lines.push(format!("Gas used: {}", status["gas_used"]));
+        lines.push(format!("Version: {}", status["version"]));
+
+        let events = status["events"].as_array().cloned().unwrap_or_default();
+        lines.push(format!("Events: {}", events.len()));
+        for event in events {
+            lines.push(format!(
+                "    {} {}",
+                event["type"].as_str().unwrap_or_default(),
+                event["data"]
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        aborted_transaction, error_body, fixture_body, mock_node, SUBMIT, TRANSACTION,
+    };
+
+    const HASH: &str = "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+
+    async fn status_json(node: &impl NodeApi, wait: bool) -> Value {
+        let output = run(node, HASH, wait, OutputFormat::Json).await.unwrap();
+        serde_json::from_str(&output).unwrap()
+    }
+
+    #[tokio::test]
+    async fn show_the_outcome_of_a_committed_transaction() {
+        let status = status_json(&mock_node(), false).await;
+        assert_eq!(status["status"], json!("committed"));
+        assert_eq!(status["success"], json!(true));
+        assert_eq!(status["gas_used"], json!(6));
+        assert_eq!(status["version"], json!(101));
+        assert_eq!(status["events"], json!([]));
+
+        let node = mock_node().with_route(TRANSACTION, 200, aborted_transaction());
+        let output = run(&node, HASH, false, OutputFormat::Text).await.unwrap();
+        assert!(output.contains("Success: false"), "{output}");
+        assert!(output.contains("EINSUFFICIENT_BALANCE"), "{output}");
+    }
+
+    #[tokio::test]
+    async fn tell_pending_and_unknown_transactions() {
+        let mut pending = fixture_body(SUBMIT);
+        pending["type"] = json!("pending_transaction");
+        let node = mock_node().with_route(TRANSACTION, 200, pending);
+        assert_eq!(status_json(&node, false).await["status"], json!("pending"));
+        assert_eq!(node.requests().len(), 1);
+
+        // An unknown hash is a status, not a failure
+        let node = mock_node().with_route(
+            TRANSACTION,
+            404,
+            error_body("Transaction not found", "transaction_not_found"),
+        );
+        let output = run(&node, HASH, true, OutputFormat::Text).await.unwrap();
+        assert!(output.contains("Status: not found"), "{output}");
+
+        let err = run(&node, "0xaa", false, OutputFormat::Text)
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("Invalid transaction hash: 0xaa"),
+            "{err:#}"
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use super::{pof_bid, submit_transaction, vouch, WaitOptions};
+use anyhow::{bail, Result};
+use colored::Colorize;
+use txs::{
+    crypto::ed25519::Ed25519PrivateKey,
+    extension::{
+        client_ext::{ClientExt, TransactionOptions},
+        ed25519_private_key_ext::Ed25519PrivateKeyExt,
+    },
+    node_api::NodeApi,
+    types::account_address::AccountAddress,
+    util::entry_function_payload,
+};
+
+/// Whether the validator enters or exits the validator set
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Membership {
+    Join,
+    Leave,
+}
+
+pub async fn run(
+    client: &impl NodeApi,
+    membership: Membership,
+    private_key: &Ed25519PrivateKey,
+    options: TransactionOptions,
+    wait_options: &WaitOptions,
+    yes: bool,
+) -> Result<()> {
+    let mut from_account = private_key.get_account(client, None).await?;
+    let validator = from_account.address();
+
+    let (function_id, mut details) = match membership {
+        Membership::Join => {
+            check_join_preconditions(client, validator).await?;
+            ("0x1::stake::join_validator_set", vec![])
+        }
+        Membership::Leave => (
+            "0x1::stake::leave_validator_set",
+            vec![(
+                "Warning",
+                "the validator stops earning rewards once it leaves the set".to_string(),
+            )],
+        ),
+    };
+    details.insert(0, ("Function", function_id.to_string()));
+    details.insert(1, ("Validator", validator.to_hex_literal()));
+
+    let payload = entry_function_payload(function_id, vec![], vec![bcs::to_bytes(&validator)?])?;
+    submit_transaction::sign_and_submit(
+        client,
+        &mut from_account,
+        payload,
+        options,
+        &details,
+        wait_options,
+        yes,
+    )
+    .await
+}
+
+/// Print whether the validator has vouches, a current proof-of-fee bid and is not jailed,
+/// failing with the unmet ones rather than letting the transaction abort
+async fn check_join_preconditions(client: &impl NodeApi, validator: AccountAddress) -> Result<()> {
+    let current_epoch = u64::from(client.get_index().await?.into_inner().epoch);
+    let vouches = vouch::received_vouches(client, validator).await;
+    let bid = pof_bid::current_bid(client, validator).await.ok();
+    let jailed = client
+        .view_ext(
+            "0x1::jail::is_jailed",
+            None,
+            Some(validator.to_hex_literal()),
+        )
+        .await
+        .ok()
+        .and_then(|result| result.first().and_then(|v| v.as_bool()));
+
+    // None when the node could not tell
+    let checks = [
+        ("Has vouches", vouches.map(|vouches| !vouches.is_empty())),
+        (
+            "Has a proof-of-fee bid for the current epoch",
+            bid.map(|(bid, expiry_epoch)| bid > 0 && expiry_epoch >= current_epoch),
+        ),
+        ("Is not jailed", jailed.map(|jailed| !jailed)),
+    ];
+
+    let mut failed = vec![];
+    for (check, passed) in checks {
+        match passed {
+            Some(true) => println!("{} {check}", "ok".green()),
+            Some(false) => {
+                println!("{} {check}", "failed".red());
+                failed.push(check);
+            }
+            None => println!("{} {check}: could not be checked", "unknown".yellow()),
+        }
+    }
+    if !failed.is_empty() {
+        bail!(
+            "The validator cannot join the validator set, failed checks: {}",
+            failed.join(", ")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        arg, function_id, mock_node, options, private_key, sender, submitted_call, wait_options,
+        VIEW,
+    };
+    use serde_json::json;
+    use txs::node_api::MockNode;
+
+    async fn change(node: &MockNode, membership: Membership) -> Result<()> {
+        run(
+            node,
+            membership,
+            &private_key(),
+            options(),
+            &wait_options(),
+            true,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn join_or_leave_the_set() {
+        let node = mock_node();
+        change(&node, Membership::Leave).await.unwrap();
+        let call = submitted_call(&node);
+        assert_eq!(function_id(&call), "0x1::stake::leave_validator_set");
+        assert_eq!(arg::<AccountAddress>(&call, 0), sender());
+        // Leaving checks nothing
+        assert!(node.view_requests().is_empty());
+
+        // The views of the fixtures answer none of the checks, which do not stop the join
+        let node = mock_node();
+        change(&node, Membership::Join).await.unwrap();
+        assert_eq!(
+            function_id(&submitted_call(&node)),
+            "0x1::stake::join_validator_set"
+        );
+    }
+
+    #[tokio::test]
+    async fn check_the_join_preconditions() {
+        let node = mock_node().with_route(VIEW, 200, json!([true]));
+        let err = change(&node, Membership::Join).await.unwrap_err();
+        assert!(
+            format!("{err:#}").contains("failed checks: Is not jailed"),
+            "{err:#}"
+        );
+        assert!(node.submitted().is_empty());
+
+        // No vouchers at all
+        let node = mock_node().with_route(VIEW, 200, json!([[], []]));
+        let err = change(&node, Membership::Join).await.unwrap_err();
+        assert!(
+            format!("{err:#}").contains("failed checks: Has vouches"),
+            "{err:#}"
+        );
+        assert!(node.submitted().is_empty());
+        let functions = node
+            .view_requests()
+            .iter()
+            .map(|request| request.function.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            functions,
+            [
+                "0x1::vouch::get_received_vouches",
+                "0x1::proof_of_fee::current_bid",
+                "0x1::jail::is_jailed",
+            ]
+        );
+    }
+}
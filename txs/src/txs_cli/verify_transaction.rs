@@ -0,0 +1,240 @@
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use std::time::{SystemTime, UNIX_EPOCH};
+use txs::{
+    crypto::ed25519::Ed25519PublicKey,
+    node_api::NodeApi,
+    retry::retry_read,
+    types::{
+        account_address::AccountAddress,
+        transaction::{
+            authenticator::{AccountAuthenticator, AuthenticationKey, TransactionAuthenticator},
+            SignedTransaction,
+        },
+    },
+    util::format_address,
+};
+
+/// What the signers and the chain of the transaction are checked against
+pub enum Expected<'a> {
+    /// The authentication keys of the signers and the chain id of the node
+    OnChain(&'a dyn NodeApi),
+    /// The key the sender is expected to sign with and the chain id of the flags, when known
+    Offline {
+        public_key: Ed25519PublicKey,
+        chain_id: Option<u8>,
+    },
+}
+
+/// Check the signatures, the keys of the signers, the expiration and the chain id, printing
+/// each outcome. Fails when any of them does not pass
+pub async fn run(signed_trans: &SignedTransaction, expected: Expected<'_>) -> Result<()> {
+    let mut failures = 0;
+    let mut check = |passed: bool, message: String| {
+        if passed {
+            println!("{} {message}", "OK:".green().bold());
+        } else {
+            println!("{} {message}", "Failed:".red().bold());
+            failures += 1;
+        }
+    };
+
+    match signed_trans.verify_signature() {
+        Ok(()) => check(true, "the signatures are valid".to_string()),
+        Err(err) => check(false, format!("the signatures do not verify: {err}")),
+    }
+
+    for (address, authenticator) in signers(signed_trans) {
+        let auth_key = authenticator.authentication_key();
+        let expected_auth_key = match &expected {
+            Expected::OnChain(client) => {
+                retry_read(|| client.get_account(address))
+                    .await
+                    .context(format!(
+                        "Failed to get the account {}",
+                        format_address(&address)
+                    ))?
+                    .into_inner()
+                    .authentication_key
+            }
+            Expected::Offline { public_key, .. } if address == signed_trans.sender() => {
+                AuthenticationKey::ed25519(public_key)
+            }
+            Expected::Offline { .. } => {
+                println!(
+                    "{} the key of {} is not checked offline",
+                    "Warning:".yellow().bold(),
+                    format_address(&address)
+                );
+                continue;
+            }
+        };
+        check(
+            auth_key == expected_auth_key,
+            format!(
+                "{} signs with authentication key {auth_key}, expected {expected_auth_key}",
+                format_address(&address)
+            ),
+        );
+    }
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let expiration = signed_trans.expiration_timestamp_secs();
+    if expiration > now {
+        check(true, format!("expires in {}s", expiration - now));
+    } else {
+        check(false, format!("expired {}s ago", now - expiration));
+    }
+
+    let chain_id = match &expected {
+        Expected::OnChain(client) => Some(
+            retry_read(|| client.get_index())
+                .await
+                .context("Failed to get the chain id of the node")?
+                .into_inner()
+                .chain_id,
+        ),
+        Expected::Offline { chain_id, .. } => *chain_id,
+    };
+    let signed_chain_id = signed_trans.chain_id().id();
+    match chain_id {
+        Some(chain_id) => check(
+            signed_chain_id == chain_id,
+            format!("signed for chain id {signed_chain_id}, expected {chain_id}"),
+        ),
+        None => println!(
+            "{} the chain id {signed_chain_id} is not checked, pass --chain-id or --network",
+            "Warning:".yellow().bold()
+        ),
+    }
+
+    if failures > 0 {
+        bail!("The transaction failed {failures} check(s), do not submit it");
+    }
+    Ok(())
+}
+
+/// The sender and the secondary signers, with their authenticators
+fn signers(signed_trans: &SignedTransaction) -> Vec<(AccountAddress, AccountAuthenticator)> {
+    let authenticator = signed_trans.authenticator();
+    let mut signers = vec![(signed_trans.sender(), authenticator.sender())];
+    if let TransactionAuthenticator::MultiAgent {
+        secondary_signer_addresses,
+        secondary_signers,
+        ..
+    } = authenticator
+    {
+        signers.extend(
+            secondary_signer_addresses
+                .into_iter()
+                .zip(secondary_signers),
+        );
+    }
+    signers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{mock_node, private_key, sender, signed_transfer};
+    use serde_json::json;
+    use txs::{
+        crypto::{ed25519::Ed25519PrivateKey, PrivateKey},
+        types::{
+            chain_id::ChainId,
+            transaction::{RawTransaction, Script, TransactionPayload},
+        },
+    };
+
+    fn other_key() -> Ed25519PrivateKey {
+        Ed25519PrivateKey::try_from([2; 32].as_slice()).unwrap()
+    }
+
+    fn failed_checks(err: anyhow::Error) -> String {
+        let message = format!("{err:#}");
+        assert!(message.contains("do not submit it"), "{message}");
+        message
+    }
+
+    #[tokio::test]
+    async fn check_the_key_of_the_sender_on_chain() {
+        let node = mock_node();
+        let signed_trans = signed_transfer(&node).await;
+        run(&signed_trans, Expected::OnChain(&node)).await.unwrap();
+        assert!(node
+            .requests()
+            .contains(&format!("GET /accounts/{}", sender().to_hex_literal())));
+
+        // The account rotated its key
+        let other_auth_key = AuthenticationKey::ed25519(&other_key().public_key());
+        let node = mock_node().with_route(
+            "GET /accounts/*",
+            200,
+            json!({
+                "authentication_key": format!("0x{}", hex::encode(other_auth_key.to_vec())),
+                "sequence_number": "0",
+            }),
+        );
+        let err = run(&signed_trans, Expected::OnChain(&node))
+            .await
+            .unwrap_err();
+        assert!(failed_checks(err).contains("failed 1 check(s)"));
+    }
+
+    #[tokio::test]
+    async fn check_the_key_and_the_chain_offline() {
+        let signed_trans = signed_transfer(&mock_node()).await;
+        let offline = |public_key, chain_id| Expected::Offline {
+            public_key,
+            chain_id,
+        };
+        run(&signed_trans, offline(private_key().public_key(), Some(4)))
+            .await
+            .unwrap();
+        // The chain id is only warned about when unknown
+        run(&signed_trans, offline(private_key().public_key(), None))
+            .await
+            .unwrap();
+
+        let err = run(&signed_trans, offline(private_key().public_key(), Some(1)))
+            .await
+            .unwrap_err();
+        assert!(failed_checks(err).contains("failed 1 check(s)"));
+        let err = run(&signed_trans, offline(other_key().public_key(), Some(1)))
+            .await
+            .unwrap_err();
+        assert!(failed_checks(err).contains("failed 2 check(s)"));
+    }
+
+    #[tokio::test]
+    async fn check_the_signature_and_the_expiration() {
+        let signed_trans = signed_transfer(&mock_node()).await;
+        let expected = || Expected::Offline {
+            public_key: private_key().public_key(),
+            chain_id: Some(4),
+        };
+
+        // Expired in 1970
+        let expired = RawTransaction::new(
+            sender(),
+            0,
+            TransactionPayload::Script(Script::new(vec![], vec![], vec![])),
+            2_000,
+            100,
+            1,
+            ChainId::new(4),
+        )
+        .sign(&private_key(), private_key().public_key())
+        .unwrap()
+        .into_inner();
+        let err = run(&expired, expected()).await.unwrap_err();
+        assert!(failed_checks(err).contains("failed 1 check(s)"));
+        // The signature of another transaction by the same key
+        let forged = SignedTransaction::new_with_authenticator(
+            signed_trans.clone().into_raw_transaction(),
+            expired.authenticator(),
+        );
+        let err = run(&forged, expected()).await.unwrap_err();
+        assert!(failed_checks(err).contains("failed 1 check(s)"));
+    }
+}
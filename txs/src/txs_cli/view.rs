@@ -1,19 +1,128 @@
+use super::txs_client;
 use anyhow::Result;
-use libra_config::extension::client_ext::ClientExt;
-use txs::{extension::client_ext::ClientExt as ConfigClientExt, rest_client::Client};
+use serde_json::{json, Value};
+use tracing::trace;
+use txs::{
+    args::FunctionArgs,
+    json_query::{format_selected, select},
+    logging::redacted_json,
+    node_api::NodeApi,
+    util::{format_args, format_output, format_type_args, OutputFormat},
+};
 
+#[allow(clippy::too_many_arguments)]
 pub async fn run(
+    client: &impl NodeApi,
     function_id: &str,
     type_args: Option<String>,
-    args: Option<String>,
+    args: Option<FunctionArgs>,
+    version: Option<u64>,
+    raw: bool,
+    query: Option<&str>,
+    format: OutputFormat,
 ) -> Result<String> {
-    let client = Client::default()?;
-    let result = client
-        .view_ext(function_id, type_args, args)
-        .await?
-        .iter()
-        .map(|v| v.to_string())
-        .collect::<Vec<_>>();
-    println!("\n=======OUTPUT=======");
-    Ok(format!("[{}]", result.join(", ")))
+    let txs_client = txs_client(client).await?;
+    let request = txs_client
+        .view_request(function_id, type_args, args)
+        .await?;
+    trace!("View request {}", redacted_json(&request));
+    // The selected value is printed alone, to be read by scripts
+    let decorate = format == OutputFormat::Text && query.is_none();
+    if decorate {
+        println!("====================");
+        println!("{}", format_type_args(&request.type_arguments));
+        println!("{}", format_args(&request.arguments));
+    }
+
+    let result = txs_client.execute_view(&request, version, raw).await?;
+    trace!("View response {}", redacted_json(&result));
+
+    if let Some(query) = query {
+        return format_selected(&select(&Value::Array(result), query)?, format);
+    }
+    if format == OutputFormat::Text {
+        println!("\n=======OUTPUT=======");
+        if let Some(version) = version {
+            println!("At ledger version {version}");
+        }
+        if raw {
+            let result = result.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+            Ok(format!("[{}]", result.join(", ")))
+        } else {
+            // Nested structs read better as indented fields than as a single JSON line
+            format_output(&result, OutputFormat::Yaml)
+        }
+    } else {
+        format_output(
+            &json!({
+                "function": request.function,
+                "type_arguments": request.type_arguments,
+                "arguments": request.arguments,
+                "ledger_version": version,
+                "result": result,
+            }),
+            format,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{category, mock_node, view_abort, VIEW};
+    use txs::{error::TxsError, node_api::MockNode};
+
+    async fn balance(node: &MockNode, query: Option<&str>, format: OutputFormat) -> Result<String> {
+        run(
+            node,
+            "0x1::coin::balance",
+            Some("0x1::aptos_coin::AptosCoin".to_string()),
+            Some(FunctionArgs::List("0x2".to_string())),
+            None,
+            false,
+            query,
+            format,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn send_the_typed_request() {
+        let node = mock_node();
+        let output = balance(&node, None, OutputFormat::Json).await.unwrap();
+        let request = &node.view_requests()[0];
+        assert_eq!(request.function.to_string(), "0x1::coin::balance");
+        assert_eq!(
+            request.type_arguments[0].to_string(),
+            "0x1::aptos_coin::AptosCoin"
+        );
+        assert_eq!(request.arguments, [json!("0x2")]);
+
+        // The u64 the node sends as a string is read as the number the ABI returns
+        let output = serde_json::from_str::<Value>(&output).unwrap();
+        assert_eq!(output["function"], json!("0x1::coin::balance"));
+        assert_eq!(output["ledger_version"], Value::Null);
+        assert_eq!(output["result"], json!([1000]));
+    }
+
+    #[tokio::test]
+    async fn select_the_value_alone() {
+        let output = balance(&mock_node(), Some("[0]"), OutputFormat::Text)
+            .await
+            .unwrap();
+        assert_eq!(output, "1000");
+
+        let err = balance(&mock_node(), Some("[1]"), OutputFormat::Text)
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("[1]"), "{err:#}");
+
+        // The abort of the function is the error of the node
+        let node = mock_node().with_route(VIEW, 400, view_abort());
+        let err = category(balance(&node, None, OutputFormat::Json).await);
+        assert!(
+            matches!(err, TxsError::RestError { status: 400, .. }),
+            "{err:?}"
+        );
+    }
 }
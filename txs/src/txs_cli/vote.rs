@@ -0,0 +1,226 @@
+use super::{prompt, submit_transaction, WaitOptions};
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+use txs::{
+    crypto::ed25519::Ed25519PrivateKey,
+    extension::{
+        client_ext::{ClientExt, TransactionOptions},
+        ed25519_private_key_ext::Ed25519PrivateKeyExt,
+    },
+    node_api::NodeApi,
+    util::{entry_function_payload, format_output, OutputFormat},
+};
+
+/// Type argument of the voting module views for governance proposals
+const PROPOSAL_TYPE: &str = "0x1::governance_proposal::GovernanceProposal";
+
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    client: &impl NodeApi,
+    proposal_id: u64,
+    should_pass: bool,
+    private_key: &Ed25519PrivateKey,
+    options: TransactionOptions,
+    wait_options: &WaitOptions,
+    yes: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    let mut from_account = private_key.get_account(client, None).await?;
+
+    if proposal_view(client, "is_resolved", proposal_id)
+        .await?
+        .as_bool()
+        == Some(true)
+    {
+        bail!("Proposal {proposal_id} is already resolved");
+    }
+    let expiration_secs =
+        parse_u64(&proposal_view(client, "get_proposal_expiration_secs", proposal_id).await?)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    if expiration_secs <= now {
+        bail!(
+            "Voting on proposal {proposal_id} ended {}s ago",
+            now - expiration_secs
+        );
+    }
+
+    let metadata = proposal_metadata(client, proposal_id).await?;
+    let (yes_votes, no_votes) = tally(client, proposal_id).await?;
+
+    let function_id = "0x1::aptos_governance::ol_vote";
+    let payload = entry_function_payload(
+        function_id,
+        vec![],
+        vec![bcs::to_bytes(&proposal_id)?, bcs::to_bytes(&should_pass)?],
+    )?;
+    let signed_trans = client
+        .sign_payload(&mut from_account, payload, options)
+        .await?;
+    let mut details = vec![
+        ("Function", function_id.to_string()),
+        ("Proposal", proposal_id.to_string()),
+    ];
+    details.extend(
+        metadata
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.to_owned())),
+    );
+    details.extend([
+        ("Votes for", yes_votes.to_string()),
+        ("Votes against", no_votes.to_string()),
+        (
+            "Vote",
+            if should_pass { "for" } else { "against" }.to_string(),
+        ),
+    ]);
+    prompt::confirm_transaction(&signed_trans, &details, yes)?;
+
+    let transaction = match submit_transaction::submit(client, &signed_trans, wait_options).await? {
+        Some(transaction) => transaction,
+        None => return Ok(()),
+    };
+    let (yes_votes, no_votes) = tally(client, proposal_id).await?;
+    if format == OutputFormat::Text {
+        println!("Votes for: {yes_votes}, votes against: {no_votes}");
+    } else {
+        // Votes are u128, too large for a JSON number
+        let output = json!({
+            "hash": serde_json::to_value(&transaction)?["hash"],
+            "proposal_id": proposal_id,
+            "should_pass": should_pass,
+            "yes_votes": yes_votes.to_string(),
+            "no_votes": no_votes.to_string(),
+        });
+        println!("{}", format_output(&output, format)?);
+    }
+    Ok(())
+}
+
+/// Call a view function of the voting module on a governance proposal
+async fn proposal_view(client: &impl NodeApi, function: &str, proposal_id: u64) -> Result<Value> {
+    client
+        .view_ext(
+            &format!("0x1::voting::{function}"),
+            Some(PROPOSAL_TYPE.to_string()),
+            Some(format!("0x1, {proposal_id}")),
+        )
+        .await
+        .context(format!("Failed to get proposal {proposal_id}"))?
+        .into_iter()
+        .next()
+        .context("The node returned no value")
+}
+
+/// Votes for and against the proposal
+async fn tally(client: &impl NodeApi, proposal_id: u64) -> Result<(u128, u128)> {
+    let result = client
+        .view_ext(
+            "0x1::voting::get_votes",
+            Some(PROPOSAL_TYPE.to_string()),
+            Some(format!("0x1, {proposal_id}")),
+        )
+        .await
+        .context(format!("Failed to get the votes on proposal {proposal_id}"))?;
+    let votes = |index: usize| {
+        result
+            .get(index)
+            .and_then(|v| v.as_str())
+            .and_then(|v| v.parse::<u128>().ok())
+            .context("Invalid votes returned by the node")
+    };
+    Ok((votes(0)?, votes(1)?))
+}
+
+/// The metadata entries of the proposal, such as its title or metadata location
+async fn proposal_metadata(
+    client: &impl NodeApi,
+    proposal_id: u64,
+) -> Result<Vec<(String, String)>> {
+    let metadata = proposal_view(client, "get_proposal_metadata", proposal_id).await?;
+    let entries = metadata["data"].as_array().cloned().unwrap_or_default();
+    Ok(entries
+        .iter()
+        .map(|entry| {
+            let key = entry["key"].as_str().unwrap_or_default().to_string();
+            // Values are bytes, usually UTF-8 text
+            let value = entry["value"].as_str().unwrap_or_default();
+            let value = hex::decode(value.trim_start_matches("0x"))
+                .ok()
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+                .unwrap_or_else(|| value.to_string());
+            (key, value)
+        })
+        .collect())
+}
+
+fn parse_u64(value: &Value) -> Result<u64> {
+    value
+        .as_str()
+        .and_then(|v| v.parse().ok())
+        .context(format!("Invalid number returned by the node: {value}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        arg, function_id, mock_node, options, private_key, submitted_call, wait_options, VIEW,
+    };
+    use txs::node_api::MockNode;
+
+    /// The node answers every view the same, a time in 2100 reads as an open proposal, its
+    /// expiration and its votes
+    fn open_proposal() -> MockNode {
+        mock_node().with_route(VIEW, 200, json!(["4102444800", "4102444800"]))
+    }
+
+    async fn vote(node: &MockNode, should_pass: bool) -> Result<()> {
+        run(
+            node,
+            7,
+            should_pass,
+            &private_key(),
+            options(),
+            &wait_options(),
+            true,
+            OutputFormat::Json,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn vote_for_or_against() {
+        for should_pass in [true, false] {
+            let node = open_proposal();
+            vote(&node, should_pass).await.unwrap();
+            let call = submitted_call(&node);
+            assert_eq!(function_id(&call), "0x1::aptos_governance::ol_vote");
+            assert_eq!(arg::<u64>(&call, 0), 7);
+            assert_eq!(arg::<bool>(&call, 1), should_pass);
+        }
+
+        let node = open_proposal();
+        vote(&node, true).await.unwrap();
+        let request = &node.view_requests()[0];
+        assert_eq!(request.function.to_string(), "0x1::voting::is_resolved");
+        assert_eq!(request.type_arguments[0].to_string(), PROPOSAL_TYPE);
+    }
+
+    #[tokio::test]
+    async fn only_vote_on_open_proposals() {
+        let node = mock_node().with_route(VIEW, 200, json!([true]));
+        let err = vote(&node, true).await.unwrap_err();
+        assert!(format!("{err:#}").contains("already resolved"), "{err:#}");
+        assert!(node.submitted().is_empty());
+
+        // Expired in 1970
+        let node = mock_node().with_route(VIEW, 200, json!(["1", "1"]));
+        let err = vote(&node, true).await.unwrap_err();
+        assert!(
+            format!("{err:#}").contains("Voting on proposal 7 ended"),
+            "{err:#}"
+        );
+        assert!(node.submitted().is_empty());
+    }
+}
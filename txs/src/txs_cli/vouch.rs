@@ -0,0 +1,302 @@
+use super::{submit_transaction, WaitOptions};
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use txs::{
+    address_book::{AddressBook, NAME_PREFIX},
+    constant::VOUCH_EXPIRATION_EPOCHS,
+    crypto::ed25519::Ed25519PrivateKey,
+    extension::{
+        client_ext::{ClientExt, TransactionOptions},
+        ed25519_private_key_ext::Ed25519PrivateKeyExt,
+    },
+    node_api::NodeApi,
+    types::account_address::AccountAddress,
+    util::{entry_function_payload, format_address, format_output, parse_address, OutputFormat},
+};
+
+pub async fn run(
+    client: &impl NodeApi,
+    for_account: &str,
+    revoke: bool,
+    private_key: &Ed25519PrivateKey,
+    options: TransactionOptions,
+    wait_options: &WaitOptions,
+    yes: bool,
+) -> Result<()> {
+    let mut from_account = private_key.get_account(client, None).await?;
+    let for_account = parse_address(for_account, "--for-account")?;
+    if for_account == from_account.address() {
+        bail!("An account cannot vouch for itself");
+    }
+
+    let function_id = if revoke {
+        "0x1::vouch::revoke"
+    } else {
+        "0x1::vouch::vouch_for"
+    };
+    let payload = entry_function_payload(function_id, vec![], vec![bcs::to_bytes(&for_account)?])?;
+
+    let mut details = vec![
+        ("Function", function_id.to_string()),
+        ("Account", format_address(&for_account)),
+    ];
+    if let Some(received) = received_vouches(client, for_account).await {
+        let vouches = received
+            .iter()
+            .map(|(voucher, epoch)| format!("{} (epoch {epoch})", format_address(voucher)))
+            .collect::<Vec<_>>();
+        details.push((
+            "Vouches received",
+            format!("{}: {}", vouches.len(), vouches.join(", ")),
+        ));
+    }
+    submit_transaction::sign_and_submit(
+        client,
+        &mut from_account,
+        payload,
+        options,
+        &details,
+        wait_options,
+        yes,
+    )
+    .await
+}
+
+/// Which vouches of the account are listed
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Received,
+    Given,
+    Both,
+}
+
+/// The vouches the account received and gave, the epoch each was given in and whether it has
+/// expired, with the names of the address book
+pub async fn list(
+    client: &impl NodeApi,
+    account: &str,
+    direction: Direction,
+    format: OutputFormat,
+) -> Result<String> {
+    let account = parse_address(account, "--account")?;
+    let current_epoch = u64::from(client.get_index().await?.into_inner().epoch);
+    // The names only make the output easier to read, a broken address book does not stop it
+    let address_book = AddressBook::load().unwrap_or_default();
+    let describe = |vouches: Vec<(AccountAddress, u64)>| {
+        vouches
+            .into_iter()
+            .map(|(address, epoch)| {
+                json!({
+                    "account": format_address(&address),
+                    "name": address_book.name_of(&address),
+                    "epoch": epoch,
+                    "expired": epoch.saturating_add(VOUCH_EXPIRATION_EPOCHS) <= current_epoch,
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+    let received = match direction {
+        Direction::Given => None,
+        _ => Some(describe(
+            get_vouches(client, "0x1::vouch::get_received_vouches", account).await?,
+        )),
+    };
+    let given = match direction {
+        Direction::Received => None,
+        _ => Some(describe(
+            get_vouches(client, "0x1::vouch::get_given_vouches", account).await?,
+        )),
+    };
+    let vouches = json!({
+        "account": format_address(&account),
+        "name": address_book.name_of(&account),
+        "current_epoch": current_epoch,
+        "expiration_epochs": VOUCH_EXPIRATION_EPOCHS,
+        "received": received,
+        "given": given,
+    });
+    if format != OutputFormat::Text {
+        return format_output(&vouches, format);
+    }
+
+    let named = |entry: &Value| match entry["name"].as_str() {
+        Some(name) => format!(
+            "{} ({NAME_PREFIX}{name})",
+            entry["account"].as_str().unwrap_or_default()
+        ),
+        None => entry["account"].as_str().unwrap_or_default().to_string(),
+    };
+    let mut lines = vec![
+        format!("Account: {}", named(&vouches)),
+        format!(
+            "Current epoch: {current_epoch}, vouches expire after {VOUCH_EXPIRATION_EPOCHS} epochs"
+        ),
+    ];
+    for (title, key) in [("Vouches received", "received"), ("Vouches given", "given")] {
+        let entries = match vouches[key].as_array() {
+            Some(entries) => entries,
+            None => continue,
+        };
+        let active = entries
+            .iter()
+            .filter(|entry| entry["expired"] == json!(false))
+            .count();
+        lines.push(format!("{title}: {} ({active} active)", entries.len()));
+        for entry in entries {
+            let expired = if entry["expired"] == json!(true) {
+                ", expired"
+            } else {
+                ""
+            };
+            lines.push(format!(
+                "    {} epoch {}{expired}",
+                named(entry),
+                entry["epoch"]
+            ));
+        }
+    }
+    Ok(lines.join("\n"))
+}
+
+/// The vouchers of the account and the epoch each vouch was given in,
+/// None when the node cannot tell, e.g. the account was never vouched for
+pub async fn received_vouches(
+    client: &impl NodeApi,
+    account: AccountAddress,
+) -> Option<Vec<(AccountAddress, u64)>> {
+    get_vouches(client, "0x1::vouch::get_received_vouches", account)
+        .await
+        .ok()
+}
+
+/// The accounts of a vouch view function, which returns them with the epochs of the vouches
+async fn get_vouches(
+    client: &impl NodeApi,
+    function_id: &str,
+    account: AccountAddress,
+) -> Result<Vec<(AccountAddress, u64)>> {
+    let result = client
+        .view_ext(function_id, None, Some(account.to_hex_literal()))
+        .await
+        .context(format!(
+            "Failed to get the vouches of {}",
+            format_address(&account)
+        ))?;
+    let accounts = result.first().and_then(Value::as_array);
+    let epochs = result.get(1).and_then(Value::as_array);
+    let (accounts, epochs) = accounts
+        .zip(epochs)
+        .context(format!("Invalid vouches returned by {function_id}"))?;
+    accounts
+        .iter()
+        .zip(epochs)
+        .map(|(address, epoch)| {
+            let address = address
+                .as_str()
+                .and_then(|address| AccountAddress::from_hex_literal(address).ok());
+            let epoch = epoch.as_str().and_then(|epoch| epoch.parse::<u64>().ok());
+            address
+                .zip(epoch)
+                .context(format!("Invalid vouch returned by {function_id}"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::txs_cli::test_node::{
+        address, arg, fixture_body, function_id, mock_node, options, private_key, sender,
+        submitted_call, wait_options, VIEW,
+    };
+    use txs::node_api::MockNode;
+
+    fn vouch_node() -> MockNode {
+        mock_node().with_route(VIEW, 200, json!([["0x5"], ["1"]]))
+    }
+
+    async fn vouch_for(node: &MockNode, for_account: &str, revoke: bool) -> Result<()> {
+        run(
+            node,
+            for_account,
+            revoke,
+            &private_key(),
+            options(),
+            &wait_options(),
+            true,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn vouch_for_an_account_or_revoke() {
+        let node = vouch_node();
+        vouch_for(&node, "0x4", false).await.unwrap();
+        let call = submitted_call(&node);
+        assert_eq!(function_id(&call), "0x1::vouch::vouch_for");
+        assert_eq!(arg::<AccountAddress>(&call, 0), address("0x4"));
+
+        // The vouches received are only shown, the revocation goes ahead without them
+        let node = mock_node();
+        vouch_for(&node, "0x4", true).await.unwrap();
+        assert_eq!(function_id(&submitted_call(&node)), "0x1::vouch::revoke");
+
+        let node = vouch_node();
+        let err = vouch_for(&node, &sender().to_hex_literal(), false)
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("cannot vouch for itself"),
+            "{err:#}"
+        );
+        assert!(node.submitted().is_empty());
+    }
+
+    #[tokio::test]
+    async fn list_the_vouches_with_their_expiration() {
+        let node = vouch_node();
+        let output = list(&node, "0x4", Direction::Received, OutputFormat::Json)
+            .await
+            .unwrap();
+        let vouches = serde_json::from_str::<Value>(&output).unwrap();
+        assert_eq!(
+            vouches["received"][0]["account"],
+            json!(format_address(&address("0x5")))
+        );
+        assert_eq!(vouches["received"][0]["expired"], json!(false));
+        assert_eq!(vouches["given"], Value::Null);
+        let requests = node.view_requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].function.to_string(),
+            "0x1::vouch::get_received_vouches"
+        );
+        assert_eq!(
+            requests[0].arguments,
+            [json!(address("0x4").to_hex_literal())]
+        );
+
+        // The vouch of epoch 1 expires at the end of the expiration
+        let mut index = fixture_body("GET /");
+        index["epoch"] = json!((1 + VOUCH_EXPIRATION_EPOCHS).to_string());
+        let node = vouch_node().with_route("GET /", 200, index);
+        let output = list(&node, "0x4", Direction::Both, OutputFormat::Text)
+            .await
+            .unwrap();
+        assert!(
+            output.contains("Vouches received: 1 (0 active)"),
+            "{output}"
+        );
+        assert!(output.contains("Vouches given: 1 (0 active)"), "{output}");
+        assert!(output.contains("epoch 1, expired"), "{output}");
+
+        let node = mock_node().with_route(VIEW, 200, json!([["0x5"], ["one"]]));
+        let err = list(&node, "0x4", Direction::Given, OutputFormat::Text)
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("Invalid vouch returned by 0x1::vouch::get_given_vouches"),
+            "{err:#}"
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use crate::util::format_address;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use zapatos_sdk::{
+    crypto::{
+        ed25519::Ed25519PrivateKey,
+        hash::{CryptoHash, CryptoHasher},
+        HashValue, PrivateKey,
+    },
+    types::transaction::{RawTransaction, SignedTransaction},
+};
+
+/// Version of the unsigned transaction file written by this version of txs.
+/// Bump it when the format changes, older files keep being read
+pub const UNSIGNED_TRANSACTION_VERSION: u32 = 1;
+
+/// A transaction built on an online machine, to be signed where the key is, e.g. an air-gapped
+/// machine. The chain id, sequence number, gas and expiration are fixed when it is built
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnsignedTransaction {
+    pub version: u32,
+    /// Hex of the BCS encoded raw transaction
+    pub raw_transaction: String,
+    /// Hex of the bytes an Ed25519 key signs, the raw transaction behind its domain separator
+    pub signing_message: String,
+    /// SHA3-256 of the signing message, to compare with what a signing device shows
+    pub signing_message_hash: String,
+    // Copies of fields of the raw transaction, for people reading the file
+    pub sender: String,
+    pub sequence_number: u64,
+    pub chain_id: u8,
+    pub expiration_timestamp_secs: u64,
+}
+
+impl UnsignedTransaction {
+    pub fn new(raw_trans: &RawTransaction) -> Result<Self> {
+        let message = signing_message(raw_trans)?;
+        Ok(Self {
+            version: UNSIGNED_TRANSACTION_VERSION,
+            raw_transaction: hex::encode(bcs::to_bytes(raw_trans)?),
+            signing_message: hex::encode(&message),
+            signing_message_hash: HashValue::sha3_256_of(&message).to_hex_literal(),
+            sender: format_address(&raw_trans.sender()),
+            sequence_number: raw_trans.sequence_number(),
+            chain_id: raw_trans.chain_id().id(),
+            expiration_timestamp_secs: raw_trans.expiration_timestamp_secs(),
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content =
+            std::fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+        let unsigned: Self = serde_json::from_str(&content).context(format!(
+            "{} is not an unsigned transaction file",
+            path.display()
+        ))?;
+        if unsigned.version > UNSIGNED_TRANSACTION_VERSION {
+            bail!(
+                "{} is an unsigned transaction file of version {}, this version of txs reads up to version {UNSIGNED_TRANSACTION_VERSION}",
+                path.display(),
+                unsigned.version
+            );
+        }
+        Ok(unsigned)
+    }
+
+    /// Write the file as JSON, refusing to overwrite it unless forced
+    pub fn save(&self, path: &Path, force: bool) -> Result<()> {
+        if path.exists() && !force {
+            bail!(
+                "{} already exists, use --force to overwrite it",
+                path.display()
+            );
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .context(format!("Failed to write {}", path.display()))
+    }
+
+    /// The raw transaction, checked against the signing message so an edited file is not signed
+    pub fn raw_transaction(&self) -> Result<RawTransaction> {
+        let bytes = hex::decode(&self.raw_transaction)
+            .context("The raw transaction of the file is not hex")?;
+        let raw_trans: RawTransaction =
+            bcs::from_bytes(&bytes).context("The file holds an invalid raw transaction")?;
+        let message = signing_message(&raw_trans)?;
+        if hex::encode(&message) != self.signing_message
+            || HashValue::sha3_256_of(&message).to_hex_literal() != self.signing_message_hash
+        {
+            bail!("The signing message of the file does not match its raw transaction");
+        }
+        Ok(raw_trans)
+    }
+
+    /// Sign the transaction, without any network access
+    pub fn sign(&self, private_key: &Ed25519PrivateKey) -> Result<SignedTransaction> {
+        Ok(self
+            .raw_transaction()?
+            .sign(private_key, private_key.public_key())?
+            .into_inner())
+    }
+}
+
+/// What is signed for a raw transaction: the domain separator of its type followed by its BCS bytes
+pub fn signing_message(raw_trans: &RawTransaction) -> Result<Vec<u8>> {
+    let mut message = <RawTransaction as CryptoHash>::Hasher::seed().to_vec();
+    message.extend(bcs::to_bytes(raw_trans)?);
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zapatos_sdk::{
+        crypto::Signature,
+        types::{
+            account_address::AccountAddress,
+            chain_id::ChainId,
+            transaction::{authenticator::TransactionAuthenticator, Script, TransactionPayload},
+        },
+    };
+
+    #[test]
+    fn sign_a_saved_transaction() {
+        let raw_trans = RawTransaction::new(
+            AccountAddress::ONE,
+            7,
+            TransactionPayload::Script(Script::new(vec![], vec![], vec![])),
+            1_000,
+            100,
+            1_700_000_000,
+            ChainId::new(4),
+        );
+        let unsigned = UnsignedTransaction::new(&raw_trans).unwrap();
+        let unsigned: UnsignedTransaction =
+            serde_json::from_str(&serde_json::to_string(&unsigned).unwrap()).unwrap();
+        assert_eq!(unsigned.raw_transaction().unwrap(), raw_trans);
+        assert_eq!(unsigned.chain_id, 4);
+
+        let private_key = Ed25519PrivateKey::try_from(&[7u8; 32][..]).unwrap();
+        let signed_trans = unsigned.sign(&private_key).unwrap();
+        assert_eq!(signed_trans.expiration_timestamp_secs(), 1_700_000_000);
+        // The signature is over the signing message of the file
+        match signed_trans.authenticator() {
+            TransactionAuthenticator::Ed25519 {
+                public_key,
+                signature,
+            } => signature
+                .verify_arbitrary_msg(
+                    &hex::decode(&unsigned.signing_message).unwrap(),
+                    &public_key,
+                )
+                .unwrap(),
+            _ => panic!("Expected an Ed25519 authenticator"),
+        }
+
+        let mut edited = unsigned;
+        edited.raw_transaction = hex::encode(
+            bcs::to_bytes(&RawTransaction::new(
+                AccountAddress::ONE,
+                8,
+                TransactionPayload::Script(Script::new(vec![], vec![], vec![])),
+                1_000,
+                100,
+                1_700_000_000,
+                ChainId::new(4),
+            ))
+            .unwrap(),
+        );
+        assert!(edited.raw_transaction().is_err());
+    }
+}
@@ -1,25 +1,610 @@
+use crate::{
+    address_book::{resolve_address, resolve_function_id},
+    args::argument_view_json,
+    constant::NATIVE_COIN_DECIMALS,
+    error::TxsError,
+};
 use anyhow::{bail, Context, Result};
-use std::fmt::{Debug, Display};
+use chrono::{SecondsFormat, TimeZone, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{
+    fmt::{Debug, Display},
+    sync::atomic::{AtomicBool, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use zapatos_move_binary_format::CompiledScript;
 use zapatos_sdk::{
-    move_types::identifier::Identifier,
-    types::{account_address::AccountAddress, transaction::SignedTransaction},
+    crypto::HashValue,
+    move_types::{
+        identifier::Identifier,
+        language_storage::{ModuleId, TypeTag},
+    },
+    rest_client::aptos_api_types::{MoveType, UserTransaction},
+    types::{
+        account_address::AccountAddress,
+        transaction::{
+            authenticator::{AccountAuthenticator, TransactionAuthenticator},
+            EntryFunction, Script, SignedTransaction, TransactionArgument, TransactionPayload,
+        },
+    },
 };
 
+/// How a command prints its result to stdout
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable output
+    #[default]
+    Text,
+    /// A single JSON document without any decoration
+    Json,
+    /// A single YAML document without any decoration
+    Yaml,
+}
+
+static PROGRESS_OUTPUT: AtomicBool = AtomicBool::new(true);
+
+/// Turn off the lines printed while building and submitting transactions, e.g. when the library
+/// is embedded in a service
+pub fn set_progress_output(enabled: bool) {
+    PROGRESS_OUTPUT.store(enabled, Ordering::Relaxed);
+}
+
+/// Print a line about the progress of a transaction, unless turned off
+pub fn progress(line: impl Display) {
+    if PROGRESS_OUTPUT.load(Ordering::Relaxed) {
+        println!("{line}");
+    }
+}
+
+/// When the output is colored
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorChoice {
+    /// When stdout is a terminal and NO_COLOR is not set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Turn the colors of every `colored` string printed afterwards on or off
+pub fn set_color(choice: ColorChoice) {
+    let enabled = should_color(
+        choice,
+        std::env::var_os("NO_COLOR").map_or(false, |value| !value.is_empty()),
+        atty::is(atty::Stream::Stdout),
+    );
+    colored::control::set_override(enabled);
+}
+
+/// Whether the output is colored, as set by `set_color`
+pub fn colors_enabled() -> bool {
+    colored::control::SHOULD_COLORIZE.should_colorize()
+}
+
+fn should_color(choice: ColorChoice, no_color: bool, is_terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => !no_color && is_terminal,
+    }
+}
+
+/// Serialize a command result for the machine readable output formats.
+/// Text output is specific to each command, so it falls back to pretty printed JSON.
+pub fn format_output<T: Serialize>(value: &T, format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Yaml => Ok(serde_yaml::to_string(value)?),
+        OutputFormat::Text | OutputFormat::Json => Ok(serde_json::to_string_pretty(value)?),
+    }
+}
+
+/// The hash the node gives the transaction, known before it is submitted
+pub fn transaction_hash(signed_trans: &SignedTransaction) -> HashValue {
+    signed_trans.clone().committed_hash()
+}
+
 pub fn format_signed_transaction(signed_trans: &SignedTransaction) -> String {
-    let mut raw_trans = signed_trans
-        .to_owned()
-        .into_raw_transaction()
-        .format_for_client(|_| String::new())
-        .replace('\t', "    ");
+    format_signed_transaction_with_params(signed_trans, None)
+}
+
+/// What the transaction does and may cost, to review before submitting it. The arguments of an
+/// entry function are decoded with `params`, its parameters from the ABI, or shown as BCS.
+/// A transaction always gives the same lines but for the time left until its expiration
+pub fn format_signed_transaction_with_params(
+    signed_trans: &SignedTransaction,
+    params: Option<&[MoveType]>,
+) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    format_signed_transaction_at(signed_trans, params, now)
+}
+
+fn format_signed_transaction_at(
+    signed_trans: &SignedTransaction,
+    params: Option<&[MoveType]>,
+    now: u64,
+) -> String {
+    let max_fee = max_fee(signed_trans);
+    let mut lines = vec![
+        format!("Hash: {}", transaction_hash(signed_trans).to_hex_literal()),
+        format!("Sender: {}", format_address(&signed_trans.sender())),
+        format!("Sequence number: {}", signed_trans.sequence_number()),
+        format!("Chain id: {}", signed_trans.chain_id().id()),
+        format!(
+            "Expiration: {}",
+            format_expiration(signed_trans.expiration_timestamp_secs(), now)
+        ),
+        format!(
+            "Gas unit price: {}, max gas: {}",
+            signed_trans.gas_unit_price(),
+            signed_trans.max_gas_amount()
+        ),
+        format!(
+            "Max fee: {} coins ({max_fee} base units)",
+            format_decimal_amount(max_fee, NATIVE_COIN_DECIMALS)
+        ),
+    ];
+
+    if let TransactionAuthenticator::MultiAgent {
+        secondary_signer_addresses,
+        ..
+    } = signed_trans.authenticator()
+    {
+        let secondary_signers = secondary_signer_addresses
+            .iter()
+            .map(format_address)
+            .collect::<Vec<_>>();
+        lines.push(format!(
+            "Signers: {} (sender), {} (secondary)",
+            format_address(&signed_trans.sender()),
+            secondary_signers.join(", ")
+        ));
+    }
+    if let TransactionAuthenticator::FeePayer {
+        secondary_signer_addresses,
+        fee_payer_address,
+        ..
+    } = signed_trans.authenticator()
+    {
+        let mut signers = vec![format!(
+            "{} (sender)",
+            format_address(&signed_trans.sender())
+        )];
+        signers.extend(
+            secondary_signer_addresses
+                .iter()
+                .map(|address| format!("{} (secondary)", format_address(address))),
+        );
+        lines.push(format!("Signers: {}", signers.join(", ")));
+        lines.push(format!(
+            "Gas payer: {} (fee payer), not the sender",
+            format_address(&fee_payer_address)
+        ));
+    }
+
+    let format_arguments = |lines: &mut Vec<String>, arguments: &[ArgumentSummary]| {
+        for (index, argument) in arguments.iter().enumerate() {
+            lines.push(match argument {
+                ArgumentSummary::Decoded { type_, value } => format!(
+                    "    Argument {index} ({type_}): {}",
+                    value
+                        .as_str()
+                        .map_or_else(|| value.to_string(), str::to_string)
+                ),
+                ArgumentSummary::Bcs { bcs } => format!("    Argument {index}: {bcs} (BCS)"),
+            });
+        }
+    };
+    match payload_summary(signed_trans, params) {
+        PayloadSummary::Script {
+            hash,
+            type_arguments,
+            arguments,
+        } => {
+            lines.push(format!("Payload: script, hash {hash}"));
+            lines.push(format!("    {}", format_type_args(&type_arguments)));
+            format_arguments(&mut lines, &arguments);
+        }
+        PayloadSummary::EntryFunction {
+            function,
+            type_arguments,
+            arguments,
+        } => {
+            lines.push(format!("Payload: entry function {function}"));
+            lines.push(format!("    {}", format_type_args(&type_arguments)));
+            format_arguments(&mut lines, &arguments);
+        }
+        PayloadSummary::Other => {
+            lines.push("Payload: neither a script nor an entry function".to_string())
+        }
+    }
+
+    match signed_trans.authenticator() {
+        TransactionAuthenticator::Ed25519 {
+            public_key,
+            signature,
+        } => {
+            lines.push("Authenticator: ed25519".to_string());
+            lines.extend(format_key_and_signature(
+                public_key.to_bytes(),
+                signature.to_bytes(),
+                "    ",
+            ));
+        }
+        TransactionAuthenticator::MultiEd25519 {
+            public_key,
+            signature,
+        } => {
+            lines.push("Authenticator: multi_ed25519".to_string());
+            lines.extend(format_key_and_signature(
+                public_key.to_bytes(),
+                signature.to_bytes(),
+                "    ",
+            ));
+        }
+        TransactionAuthenticator::MultiAgent {
+            sender,
+            secondary_signer_addresses,
+            secondary_signers,
+        } => {
+            lines.push("Authenticator: multi_agent".to_string());
+            lines.extend(format_account_authenticator("Sender", sender));
+            for (address, signer) in secondary_signer_addresses.iter().zip(secondary_signers) {
+                lines.extend(format_account_authenticator(
+                    &format!("Secondary signer {}", format_address(address)),
+                    signer,
+                ));
+            }
+        }
+        TransactionAuthenticator::FeePayer {
+            sender,
+            secondary_signer_addresses,
+            secondary_signers,
+            fee_payer_address,
+            fee_payer_signer,
+        } => {
+            lines.push("Authenticator: fee_payer".to_string());
+            lines.extend(format_account_authenticator("Sender", sender));
+            for (address, signer) in secondary_signer_addresses.iter().zip(secondary_signers) {
+                lines.extend(format_account_authenticator(
+                    &format!("Secondary signer {}", format_address(address)),
+                    signer,
+                ));
+            }
+            lines.extend(format_account_authenticator(
+                &format!("Fee payer {}", format_address(fee_payer_address)),
+                fee_payer_signer,
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+/// A signer of a multi-agent or fee payer transaction, under the lines of the authenticator
+fn format_account_authenticator(signer: &str, authenticator: &AccountAuthenticator) -> Vec<String> {
+    let (scheme, mut lines) = match authenticator {
+        AccountAuthenticator::Ed25519 {
+            public_key,
+            signature,
+        } => (
+            "ed25519",
+            format_key_and_signature(public_key.to_bytes(), signature.to_bytes(), "        "),
+        ),
+        AccountAuthenticator::MultiEd25519 {
+            public_key,
+            signature,
+        } => (
+            "multi_ed25519",
+            format_key_and_signature(public_key.to_bytes(), signature.to_bytes(), "        "),
+        ),
+    };
+    lines.insert(0, format!("    {signer}: {scheme}"));
+    lines
+}
+
+fn format_key_and_signature(
+    public_key: impl AsRef<[u8]>,
+    signature: impl AsRef<[u8]>,
+    indent: &str,
+) -> Vec<String> {
+    vec![
+        format!("{indent}Public key: 0x{}", hex::encode(public_key)),
+        format!("{indent}Signature: 0x{}", hex::encode(signature)),
+    ]
+}
+
+/// The summary of a signed transaction for `--format json`, serialized with its fields in this
+/// order whatever the features of serde_json
+#[derive(Debug, Serialize)]
+pub struct TransactionSummary {
+    pub hash: String,
+    pub sender: String,
+    pub sequence_number: u64,
+    pub chain_id: u8,
+    pub expiration_timestamp_secs: u64,
+    /// In UTC, None when out of the range of dates
+    pub expiration: Option<String>,
+    pub max_gas_amount: u64,
+    pub gas_unit_price: u64,
+    pub max_fee: u64,
+    pub max_fee_coins: String,
+    pub payload: PayloadSummary,
+    pub secondary_signers: Vec<String>,
+    /// The account which pays the gas instead of the sender, if any
+    pub fee_payer: Option<String>,
+    /// The text summary, only set in the summary files written next to a signed transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PayloadSummary {
+    Script {
+        /// The SHA3-256 hash of the bytecode
+        hash: String,
+        type_arguments: Vec<String>,
+        arguments: Vec<ArgumentSummary>,
+    },
+    EntryFunction {
+        function: String,
+        type_arguments: Vec<String>,
+        arguments: Vec<ArgumentSummary>,
+    },
+    Other,
+}
+
+/// An argument decoded as the node shows it in JSON, integers above u32 as strings, or its BCS
+/// bytes when its parameter is unknown
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum ArgumentSummary {
+    Decoded {
+        #[serde(rename = "type")]
+        type_: String,
+        value: Value,
+    },
+    Bcs {
+        bcs: String,
+    },
+}
+
+/// The same summary as `format_signed_transaction_with_params` for `--format json`. The
+/// expiration is given in UTC, so a transaction always gives the same document
+pub fn format_signed_transaction_json(
+    signed_trans: &SignedTransaction,
+    params: Option<&[MoveType]>,
+) -> TransactionSummary {
+    let max_fee = max_fee(signed_trans);
+    let secondary_signers = match signed_trans.authenticator() {
+        TransactionAuthenticator::MultiAgent {
+            secondary_signer_addresses,
+            ..
+        }
+        | TransactionAuthenticator::FeePayer {
+            secondary_signer_addresses,
+            ..
+        } => secondary_signer_addresses
+            .iter()
+            .map(AccountAddress::to_hex_literal)
+            .collect(),
+        _ => vec![],
+    };
+    let fee_payer = match signed_trans.authenticator() {
+        TransactionAuthenticator::FeePayer {
+            fee_payer_address, ..
+        } => Some(fee_payer_address.to_hex_literal()),
+        _ => None,
+    };
+    TransactionSummary {
+        hash: transaction_hash(signed_trans).to_hex_literal(),
+        sender: signed_trans.sender().to_hex_literal(),
+        sequence_number: signed_trans.sequence_number(),
+        chain_id: signed_trans.chain_id().id(),
+        expiration_timestamp_secs: signed_trans.expiration_timestamp_secs(),
+        expiration: format_utc(signed_trans.expiration_timestamp_secs()),
+        max_gas_amount: signed_trans.max_gas_amount(),
+        gas_unit_price: signed_trans.gas_unit_price(),
+        max_fee,
+        max_fee_coins: format_decimal_amount(max_fee, NATIVE_COIN_DECIMALS),
+        payload: payload_summary(signed_trans, params),
+        secondary_signers,
+        fee_payer,
+        summary: None,
+    }
+}
+
+fn payload_summary(
+    signed_trans: &SignedTransaction,
+    params: Option<&[MoveType]>,
+) -> PayloadSummary {
+    match signed_trans.payload() {
+        TransactionPayload::Script(script) => PayloadSummary::Script {
+            hash: HashValue::sha3_256_of(script.code()).to_hex_literal(),
+            type_arguments: script.ty_args().iter().map(ToString::to_string).collect(),
+            arguments: script
+                .args()
+                .iter()
+                .map(|arg| ArgumentSummary::Decoded {
+                    type_: script_argument_type(arg).to_string(),
+                    value: argument_view_json(arg),
+                })
+                .collect(),
+        },
+        TransactionPayload::EntryFunction(entry_function) => PayloadSummary::EntryFunction {
+            function: entry_function_name(entry_function),
+            type_arguments: entry_function
+                .ty_args()
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            arguments: entry_function
+                .args()
+                .iter()
+                .enumerate()
+                .map(|(index, arg)| {
+                    match decode_argument(arg, params.and_then(|params| params.get(index))) {
+                        Some((param, value)) => ArgumentSummary::Decoded {
+                            type_: param.to_string(),
+                            value,
+                        },
+                        None => ArgumentSummary::Bcs {
+                            bcs: format!("0x{}", hex::encode(arg)),
+                        },
+                    }
+                })
+                .collect(),
+        },
+        _ => PayloadSummary::Other,
+    }
+}
+
+/// The type of a script argument, its value is as the view functions take it
+fn script_argument_type(arg: &TransactionArgument) -> &'static str {
+    match arg {
+        TransactionArgument::Bool(_) => "bool",
+        TransactionArgument::U8(_) => "u8",
+        TransactionArgument::U16(_) => "u16",
+        TransactionArgument::U32(_) => "u32",
+        TransactionArgument::U64(_) => "u64",
+        TransactionArgument::U128(_) => "u128",
+        TransactionArgument::U256(_) => "u256",
+        TransactionArgument::Address(_) => "address",
+        TransactionArgument::U8Vector(_) => "vector<u8>",
+    }
+}
+
+/// The most the sender, or the fee payer, pays for the transaction, in base units of the native coin
+fn max_fee(signed_trans: &SignedTransaction) -> u64 {
+    signed_trans
+        .max_gas_amount()
+        .saturating_mul(signed_trans.gas_unit_price())
+}
+
+fn entry_function_name(entry_function: &EntryFunction) -> String {
+    format!(
+        "{}::{}::{}",
+        entry_function.module().address().to_hex_literal(),
+        entry_function.module().name(),
+        entry_function.function()
+    )
+}
+
+/// A Unix time in UTC, None when out of the range of dates
+fn format_utc(secs: u64) -> Option<String> {
+    i64::try_from(secs)
+        .ok()
+        .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+        .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+/// The expiration in UTC, and how long until then
+fn format_expiration(expiration_timestamp_secs: u64, now: u64) -> String {
+    let time = format_utc(expiration_timestamp_secs)
+        .unwrap_or_else(|| format!("{expiration_timestamp_secs} (Unix time)"));
+    if expiration_timestamp_secs > now {
+        format!(
+            "{time}, expires in {}",
+            format_duration(expiration_timestamp_secs - now)
+        )
+    } else {
+        format!(
+            "{time}, expired {} ago",
+            format_duration(now - expiration_timestamp_secs)
+        )
+    }
+}
 
-    if let Some(index) = raw_trans.rfind('\n') {
-        raw_trans.replace_range(index.., "\n}");
+/// Seconds under two minutes, whole minutes from then on
+fn format_duration(secs: u64) -> String {
+    if secs < 120 {
+        format!("{secs}s")
+    } else {
+        format!("{} minutes", secs / 60)
     }
+}
+
+/// An argument with its parameter, when it has one with a readable form
+fn decode_argument<'a>(bytes: &[u8], param: Option<&'a MoveType>) -> Option<(&'a MoveType, Value)> {
+    let param = param?;
+    Some((param, decode_bcs_argument(bytes, param)?))
+}
+
+/// An argument decoded from BCS as the node shows it in JSON. Integers above u32 and addresses
+/// are strings
+fn decode_bcs_argument(bytes: &[u8], param: &MoveType) -> Option<Value> {
+    let value = match param {
+        MoveType::Bool => json!(bcs::from_bytes::<bool>(bytes).ok()?),
+        MoveType::U8 => json!(bcs::from_bytes::<u8>(bytes).ok()?),
+        MoveType::U16 => json!(bcs::from_bytes::<u16>(bytes).ok()?),
+        MoveType::U32 => json!(bcs::from_bytes::<u32>(bytes).ok()?),
+        MoveType::U64 => json!(bcs::from_bytes::<u64>(bytes).ok()?.to_string()),
+        MoveType::U128 => json!(bcs::from_bytes::<u128>(bytes).ok()?.to_string()),
+        MoveType::Address => json!(bcs::from_bytes::<AccountAddress>(bytes)
+            .ok()?
+            .to_hex_literal()),
+        MoveType::Vector { items } => match items.as_ref() {
+            MoveType::U8 => json!(format!(
+                "0x{}",
+                hex::encode(bcs::from_bytes::<Vec<u8>>(bytes).ok()?)
+            )),
+            MoveType::U64 => json!(bcs::from_bytes::<Vec<u64>>(bytes)
+                .ok()?
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()),
+            MoveType::Address => json!(bcs::from_bytes::<Vec<AccountAddress>>(bytes)
+                .ok()?
+                .iter()
+                .map(AccountAddress::to_hex_literal)
+                .collect::<Vec<_>>()),
+            _ => return None,
+        },
+        MoveType::Struct(tag) => {
+            match format!("{}::{}::{}", tag.address, tag.module, tag.name).as_str() {
+                "0x1::string::String" => json!(bcs::from_bytes::<String>(bytes).ok()?),
+                "0x1::object::Object" => json!(bcs::from_bytes::<AccountAddress>(bytes)
+                    .ok()?
+                    .to_hex_literal()),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    Some(value)
+}
 
-    let authenticator =
-        format!("{:#?}", signed_trans.authenticator()).replace("Ed25519 {", "Authenticator {");
+/// Describe the outcome of a simulated transaction, making it clear nothing was committed
+pub fn format_simulated_transaction(simulated_trans: &UserTransaction) -> String {
+    let info = &simulated_trans.info;
+    let mut lines = vec![
+        "=======SIMULATION (not executed on-chain)=======".to_string(),
+        format!("Success: {}", info.success),
+        format!("VM status: {}", info.vm_status),
+        format!("Gas used: {}", u64::from(info.gas_used)),
+        format!("Events: {}", simulated_trans.events.len()),
+    ];
+    for event in &simulated_trans.events {
+        lines.push(format!("    {} {}", event.typ, event.data));
+    }
 
-    format!("{raw_trans}\n{authenticator}")
+    lines.push(format!("State changes: {}", info.changes.len()));
+    for change in &info.changes {
+        // Every kind of change serializes with its kind in `type`,
+        // resource changes also carry the address and the resource type
+        let change = serde_json::to_value(change).unwrap_or_default();
+        let mut line = format!("    {}", change["type"].as_str().unwrap_or("unknown"));
+        if let Some(address) = change["address"].as_str() {
+            line.push_str(&format!(" {address}"));
+        }
+        if let Some(resource_type) = change["data"]["type"].as_str() {
+            line.push_str(&format!(" {resource_type}"));
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
 }
 
 pub fn format_type_args<T: Display>(type_args: &[T]) -> String {
@@ -37,7 +622,36 @@ pub fn format_args<T: Debug>(args: &[T]) -> String {
     format!("Arguments: {args:#?}")
 }
 
+/// Parse an account address given with the flag, with or without the 0x prefix and leading zeros,
+/// or as @name from the address book
+pub fn parse_address(input: &str, flag: &str) -> Result<AccountAddress> {
+    let address = resolve_address(input).context(format!("Invalid address for {flag}"))?;
+    let address = address.trim();
+    let digits = address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+        .unwrap_or(address);
+    if digits.is_empty()
+        || digits.len() > AccountAddress::LENGTH * 2
+        || !digits.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return Err(TxsError::InvalidAddress(format!(
+            "Invalid address for {flag}: {input}, expected 1 to {} hex digits with an optional 0x prefix",
+            AccountAddress::LENGTH * 2
+        ))
+        .into());
+    }
+    AccountAddress::from_hex(format!("{digits:0>64}"))
+        .context(format!("Invalid address for {flag}: {input}"))
+}
+
+/// The canonical form of an address, 0x and all 64 hex digits
+pub fn format_address(address: &AccountAddress) -> String {
+    format!("0x{}", address.to_hex())
+}
+
 pub fn parse_function_id(function_id: &str) -> Result<(AccountAddress, Identifier, Identifier)> {
+    let function_id = resolve_function_id(function_id)?;
     let id_parts = function_id
         .split("::")
         .map(|i| i.trim())
@@ -53,3 +667,339 @@ pub fn parse_function_id(function_id: &str) -> Result<(AccountAddress, Identifie
         .context(format!("Failed to parse function name: {}", id_parts[2]))?;
     Ok((module_address, module_name, function_name))
 }
+
+/// Payload running a compiled Move script, checked to be a script rather than a module
+pub fn script_payload(
+    code: Vec<u8>,
+    type_args: Vec<TypeTag>,
+    args: Vec<TransactionArgument>,
+) -> Result<TransactionPayload> {
+    CompiledScript::deserialize(&code).context("The file is not a compiled Move script")?;
+    Ok(TransactionPayload::Script(Script::new(
+        code, type_args, args,
+    )))
+}
+
+/// Payload of a call to an entry function, with BCS encoded arguments
+pub fn entry_function_payload(
+    function_id: &str,
+    type_args: Vec<TypeTag>,
+    args: Vec<Vec<u8>>,
+) -> Result<TransactionPayload> {
+    let (module_address, module_name, function_name) = parse_function_id(function_id)?;
+    Ok(TransactionPayload::EntryFunction(EntryFunction::new(
+        ModuleId::new(module_address, module_name),
+        function_name,
+        type_args,
+        args,
+    )))
+}
+
+/// Convert a human readable amount such as `12_000.25` to base units of a coin with `decimals` decimals
+pub fn parse_decimal_amount(amount: &str, decimals: u8) -> Result<u64> {
+    let digits = amount.trim().replace('_', "");
+    let (whole, fraction) = digits.split_once('.').unwrap_or((&digits, ""));
+    let is_number = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_number(whole) || !is_number(fraction) {
+        bail!("Invalid amount: {amount}");
+    }
+    if fraction.len() > decimals as usize {
+        bail!("Invalid amount: {amount}, the coin only supports {decimals} decimal places");
+    }
+
+    format!("{whole}{fraction:0<width$}", width = decimals as usize)
+        .parse::<u64>()
+        .context(format!("Invalid amount: {amount} is too large"))
+}
+
+/// Convert base units of a coin with `decimals` decimals to a human readable amount
+pub fn format_decimal_amount(amount: u64, decimals: u8) -> String {
+    let amount = format!("{amount:0>width$}", width = decimals as usize + 1);
+    let (whole, fraction) = amount.split_at(amount.len() - decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{whole}.{fraction}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_only_terminals_by_default() {
+        assert!(should_color(ColorChoice::Auto, false, true));
+        assert!(!should_color(ColorChoice::Auto, false, false));
+        assert!(!should_color(ColorChoice::Auto, true, true));
+        assert!(should_color(ColorChoice::Always, true, false));
+        assert!(!should_color(ColorChoice::Never, false, true));
+    }
+
+    #[test]
+    fn convert_decimal_amounts() {
+        assert_eq!(parse_decimal_amount("1.5", 6).unwrap(), 1_500_000);
+        assert_eq!(
+            parse_decimal_amount("12_000.25", 6).unwrap(),
+            12_000_250_000
+        );
+        assert_eq!(parse_decimal_amount(".000001", 6).unwrap(), 1);
+        assert_eq!(parse_decimal_amount("7", 0).unwrap(), 7);
+        assert!(parse_decimal_amount("0.0000001", 6).is_err());
+        assert!(parse_decimal_amount("1.2.3", 6).is_err());
+        assert!(parse_decimal_amount("-1", 6).is_err());
+        assert!(parse_decimal_amount(".", 6).is_err());
+
+        assert_eq!(format_decimal_amount(1_500_000, 6), "1.5");
+        assert_eq!(format_decimal_amount(1, 6), "0.000001");
+        assert_eq!(format_decimal_amount(12_000_000_000, 6), "12000");
+        assert_eq!(format_decimal_amount(7, 0), "7");
+    }
+
+    #[test]
+    fn parse_addresses_in_any_form() {
+        let one = format!("0x{}1", "0".repeat(63));
+        for input in ["0x1", "1", "0X01", &one, &one[2..]] {
+            let address = parse_address(input, "--to-account").unwrap();
+            assert_eq!(address, AccountAddress::ONE);
+            assert_eq!(format_address(&address), one);
+        }
+        for input in ["", "0x", "0xzz", &format!("{one}0")] {
+            let err = parse_address(input, "--to-account").unwrap_err();
+            assert!(err
+                .to_string()
+                .starts_with("Invalid address for --to-account"));
+        }
+    }
+
+    #[test]
+    fn sign_a_script() {
+        use zapatos_sdk::{
+            crypto::{ed25519::Ed25519PrivateKey, PrivateKey},
+            transaction_builder::TransactionBuilder,
+            types::{
+                chain_id::ChainId, transaction::authenticator::AuthenticationKey, LocalAccount,
+            },
+        };
+
+        let code = include_bytes!("../tests/fixtures/noop_script.mv").to_vec();
+        let payload = script_payload(code.clone(), vec![], vec![]).unwrap();
+        assert!(script_payload(vec![1, 2, 3], vec![], vec![]).is_err());
+
+        let private_key = Ed25519PrivateKey::try_from(&[7u8; 32][..]).unwrap();
+        let address = AuthenticationKey::ed25519(&private_key.public_key()).derived_address();
+        let mut account = LocalAccount::new(address, private_key, 0);
+        let signed_trans = account.sign_with_transaction_builder(
+            TransactionBuilder::new(payload, u64::MAX, ChainId::test())
+                .max_gas_amount(1_000)
+                .gas_unit_price(100),
+        );
+        signed_trans.verify_signature().unwrap();
+        let formatted = format_signed_transaction(&signed_trans);
+        assert!(formatted.contains(&format!(
+            "Payload: script, hash {}",
+            HashValue::sha3_256_of(&code).to_hex_literal()
+        )));
+        assert!(formatted.contains("Chain id: 4\n"));
+    }
+
+    /// A transaction of 0x1 with fixed fields, its signature does not matter here
+    fn fixture_transaction(payload: TransactionPayload) -> SignedTransaction {
+        use zapatos_sdk::{
+            crypto::{ed25519::Ed25519PrivateKey, PrivateKey},
+            transaction_builder::TransactionBuilder,
+            types::chain_id::ChainId,
+        };
+
+        let private_key = Ed25519PrivateKey::try_from(&[7u8; 32][..]).unwrap();
+        TransactionBuilder::new(payload, 2_000_000_000, ChainId::test())
+            .sender(AccountAddress::ONE)
+            .sequence_number(7)
+            .max_gas_amount(1_000)
+            .gas_unit_price(100)
+            .build()
+            .sign(&private_key, private_key.public_key())
+            .unwrap()
+            .into_inner()
+    }
+
+    /// The summary without its hash, which is checked on its own as it depends on the signature
+    fn summary_without_hash(
+        signed_trans: &SignedTransaction,
+        params: Option<&[MoveType]>,
+    ) -> Value {
+        let mut summary =
+            serde_json::to_value(format_signed_transaction_json(signed_trans, params)).unwrap();
+        let hash = summary.as_object_mut().unwrap().remove("hash");
+        assert_eq!(
+            hash,
+            Some(json!(transaction_hash(signed_trans).to_hex_literal()))
+        );
+        summary
+    }
+
+    fn golden(fixture: &str) -> Value {
+        serde_json::from_str(fixture).unwrap()
+    }
+
+    #[test]
+    fn summarize_fixture_transactions() {
+        let coin_type = crate::client::parse_coin_type("0x1::aptos_coin::AptosCoin").unwrap();
+        let to = AccountAddress::from_hex_literal("0xcafe").unwrap();
+        let transfer =
+            fixture_transaction(crate::client::transfer_payload(to, 100, Some(coin_type)).unwrap());
+        let params = [MoveType::Address, MoveType::U64];
+        assert_eq!(
+            summary_without_hash(&transfer, Some(&params)),
+            golden(include_str!("../tests/fixtures/transfer_summary.json"))
+        );
+        assert_eq!(
+            summary_without_hash(&transfer, None),
+            golden(include_str!("../tests/fixtures/transfer_summary_bcs.json"))
+        );
+        let code = include_bytes!("../tests/fixtures/noop_script.mv").to_vec();
+        let script = fixture_transaction(script_payload(code, vec![], vec![]).unwrap());
+        assert_eq!(
+            summary_without_hash(&script, None),
+            golden(include_str!("../tests/fixtures/script_summary.json"))
+        );
+
+        let formatted = format_signed_transaction_with_params(&transfer, Some(&params));
+        for line in [
+            format!("Sender: {}\n", format_address(&AccountAddress::ONE)),
+            "Sequence number: 7\n".to_string(),
+            "Gas unit price: 100, max gas: 1000\n".to_string(),
+            "Max fee: 0.1 coins (100000 base units)\n".to_string(),
+            "Payload: entry function 0x1::coin::transfer\n".to_string(),
+            "    Type Arguments: [0x1::aptos_coin::AptosCoin]\n".to_string(),
+            "    Argument 0 (address): 0xcafe\n".to_string(),
+            "    Argument 1 (u64): 100\n".to_string(),
+        ] {
+            assert!(formatted.contains(&line), "{line} not in {formatted}");
+        }
+        let formatted = format_signed_transaction(&transfer);
+        assert!(formatted.contains("    Argument 1: 0x6400000000000000 (BCS)\n"));
+
+        assert!(format_expiration(2_000_000_000, 1_999_999_941).ends_with(", expires in 59s"));
+        assert!(format_expiration(100, 105).ends_with(", expired 5s ago"));
+        assert!(
+            format_expiration(2_000_003_600, 2_000_000_000).ends_with(", expires in 60 minutes")
+        );
+    }
+
+    /// The folder of the signed transactions whose summaries must not change
+    const SIGNED_FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/signed");
+
+    /// The signed transaction of a fixture, from the key of [7; 32] with fixed fields, and the
+    /// parameters of its entry function
+    fn signed_fixture(name: &str) -> (SignedTransaction, Option<Vec<MoveType>>) {
+        use zapatos_sdk::{
+            crypto::{ed25519::Ed25519PrivateKey, PrivateKey},
+            transaction_builder::TransactionBuilder,
+            types::{chain_id::ChainId, transaction::authenticator::AuthenticationKey},
+        };
+
+        let cafe = AccountAddress::from_hex_literal("0xcafe").unwrap();
+        let (payload, params) = match name {
+            "transfer" => (
+                crate::client::transfer_payload(cafe, 100, None).unwrap(),
+                Some(vec![MoveType::Address, MoveType::U64]),
+            ),
+            "entry_function" => {
+                let args = vec![
+                    bcs::to_bytes(&cafe).unwrap(),
+                    bcs::to_bytes(&vec![0xab_u8; 32]).unwrap(),
+                    bcs::to_bytes(b"https://example.org/proposal.json".as_slice()).unwrap(),
+                    bcs::to_bytes(&vec![0x11_u8; 32]).unwrap(),
+                    bcs::to_bytes(&false).unwrap(),
+                ];
+                let bytes = MoveType::Vector {
+                    items: Box::new(MoveType::U8),
+                };
+                (
+                    entry_function_payload(
+                        "0x1::aptos_governance::create_proposal_v2",
+                        vec![],
+                        args,
+                    )
+                    .unwrap(),
+                    Some(vec![
+                        MoveType::Address,
+                        bytes.clone(),
+                        bytes.clone(),
+                        bytes,
+                        MoveType::Bool,
+                    ]),
+                )
+            }
+            "script" => {
+                let code = include_bytes!("../tests/fixtures/noop_script.mv").to_vec();
+                let args = vec![
+                    TransactionArgument::U64(5),
+                    TransactionArgument::Address(cafe),
+                    TransactionArgument::Bool(true),
+                    TransactionArgument::U8Vector(b"0l".to_vec()),
+                ];
+                (script_payload(code, vec![], args).unwrap(), None)
+            }
+            _ => unreachable!("no fixture {name}"),
+        };
+
+        let private_key = Ed25519PrivateKey::try_from(&[7u8; 32][..]).unwrap();
+        let sender = AuthenticationKey::ed25519(&private_key.public_key()).derived_address();
+        let signed_trans = TransactionBuilder::new(payload, 2_000_000_000, ChainId::test())
+            .sender(sender)
+            .sequence_number(7)
+            .max_gas_amount(1_000)
+            .gas_unit_price(100)
+            .build()
+            .sign(&private_key, private_key.public_key())
+            .unwrap()
+            .into_inner();
+        (signed_trans, params)
+    }
+
+    /// The summaries of the transactions in tests/fixtures/signed must be the committed ones to
+    /// the byte. Run with UPDATE_GOLDENS=1 to write them again after a deliberate change
+    #[test]
+    fn golden_signed_transactions() {
+        let update = std::env::var_os("UPDATE_GOLDENS").is_some();
+        for name in ["transfer", "entry_function", "script"] {
+            let (built, params) = signed_fixture(name);
+            let path = std::path::Path::new(SIGNED_FIXTURES).join(name);
+            let bytes = bcs::to_bytes(&built).unwrap();
+            if update {
+                std::fs::write(path.with_extension("bcs"), &bytes).unwrap();
+            }
+            assert_eq!(
+                std::fs::read(path.with_extension("bcs")).unwrap(),
+                bytes,
+                "{name}.bcs is not the transaction signed in the test"
+            );
+
+            let signed_trans = bcs::from_bytes::<SignedTransaction>(&bytes).unwrap();
+            signed_trans.verify_signature().unwrap();
+            let now = signed_trans.expiration_timestamp_secs() - 3_600;
+            let text = format_signed_transaction_at(&signed_trans, params.as_deref(), now);
+            let json = serde_json::to_string_pretty(&format_signed_transaction_json(
+                &signed_trans,
+                params.as_deref(),
+            ))
+            .unwrap();
+            for (extension, rendered) in [("txt", text), ("json", json)] {
+                let golden_path = path.with_extension(extension);
+                let rendered = format!("{rendered}\n");
+                if update {
+                    std::fs::write(&golden_path, &rendered).unwrap();
+                }
+                assert_eq!(
+                    rendered,
+                    std::fs::read_to_string(&golden_path).unwrap(),
+                    "The summary drifted from {name}.{extension}, rerun with UPDATE_GOLDENS=1 if \
+                     the change is deliberate"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use std::{net::TcpListener, process::Command};
+use txs::constant::{EXIT_CODE_INVALID_ARGUMENTS, EXIT_CODE_NETWORK};
+
+fn txs(args: &[&str]) -> Option<i32> {
+    Command::new(env!("CARGO_BIN_EXE_txs"))
+        .args(args)
+        .output()
+        .expect("txs runs")
+        .status
+        .code()
+}
+
+#[test]
+fn exit_with_invalid_arguments() {
+    assert_eq!(
+        txs(&["transfer-coins", "--no-such-flag"]),
+        Some(EXIT_CODE_INVALID_ARGUMENTS)
+    );
+}
+
+#[test]
+fn exit_with_unreachable_node() {
+    // A port nothing listens on once the listener is dropped
+    let address = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let url = format!("http://{address}");
+    assert_eq!(
+        txs(&[
+            "--url",
+            &url,
+            "--max-retries",
+            "0",
+            "view",
+            "--function-id",
+            "0x1::coin::balance",
+        ]),
+        Some(EXIT_CODE_NETWORK)
+    );
+}
@@ -0,0 +1,225 @@
+//! The library operations against a node answering from fixtures, without any network
+
+use serde_json::{json, Value};
+use std::{sync::Once, time::Duration};
+use txs::{
+    args::FunctionArgs,
+    client::{parse_coin_type, transfer_payload, TxResult, TxsClient},
+    crypto::ed25519::Ed25519PrivateKey,
+    error::TxsError,
+    extension::client_ext::{ClientExt, TransactionOptions},
+    fungible_asset,
+    node_api::MockNode,
+    types::{account_address::AccountAddress, LocalAccount},
+    util::set_progress_output,
+};
+
+const FIXTURES: &str = include_str!("fixtures/node/transfer.json");
+const COIN_STORE: &str =
+    "GET /accounts/0x2/resource/0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>";
+const TRANSACTION: &str = "GET /transactions/by_hash/*";
+
+static SETUP: Once = Once::new();
+
+/// The modules fetched for the ABI checks are cached in a home folder of the test run, so that
+/// the cache of the user is neither read nor written
+fn mock_node() -> MockNode {
+    SETUP.call_once(|| {
+        let home = std::env::temp_dir().join(format!("txs-node-api-test-{}", std::process::id()));
+        std::fs::create_dir_all(&home).unwrap();
+        std::env::set_var("HOME", home);
+        set_progress_output(false);
+    });
+    MockNode::from_json(FIXTURES).unwrap()
+}
+
+async fn connect(node: MockNode) -> TxsClient<MockNode> {
+    TxsClient::connect(node)
+        .await
+        .unwrap()
+        .with_wait(Duration::from_millis(50), Duration::from_millis(10))
+}
+
+fn error_body(message: &str, error_code: &str) -> Value {
+    json!({ "message": message, "error_code": error_code, "vm_error_code": null })
+}
+
+/// The body of a route in the fixtures, to answer with a variant of it
+fn fixture_body(route: &str) -> Value {
+    serde_json::from_str::<Value>(FIXTURES).unwrap()["routes"][route]["body"].clone()
+}
+
+fn address(hex: &str) -> AccountAddress {
+    AccountAddress::from_hex_literal(hex).unwrap()
+}
+
+async fn transfer(client: &TxsClient<MockNode>) -> Result<TxResult, TxsError> {
+    let private_key = Ed25519PrivateKey::try_from([1; 32].as_slice()).unwrap();
+    let mut sender = LocalAccount::new(address("0x2"), private_key, 0);
+    let options = TransactionOptions {
+        max_gas_amount: Some(2_000),
+        gas_unit_price: Some(100),
+        ..TransactionOptions::default()
+    };
+    client
+        .transfer(address("0x3"), 10, &mut sender, options)
+        .await
+}
+
+#[tokio::test]
+async fn balance() {
+    let client = connect(mock_node()).await;
+    assert_eq!(client.chain_id().id(), 4);
+    assert_eq!(client.balance(address("0x2")).await.unwrap(), 1_000);
+
+    let node = mock_node().with_route(
+        COIN_STORE,
+        404,
+        error_body("Account not found", "account_not_found"),
+    );
+    let err = connect(node)
+        .await
+        .balance(address("0x2"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, TxsError::AccountNotFound(_)), "{err:?}");
+
+    let node = mock_node().with_route(
+        COIN_STORE,
+        200,
+        json!({ "type": "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>", "data": {} }),
+    );
+    let err = connect(node)
+        .await
+        .balance(address("0x2"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, TxsError::Other(_)), "{err:?}");
+}
+
+#[tokio::test]
+async fn account_resource() {
+    let node = mock_node();
+    let coin_type = parse_coin_type("0x1::aptos_coin::AptosCoin").unwrap();
+    assert_eq!(node.get_coin_decimals(&coin_type).await.unwrap(), 6);
+
+    let other_coin = parse_coin_type("0x1::other::Coin").unwrap();
+    let err = node.get_coin_decimals(&other_coin).await.unwrap_err();
+    assert!(
+        matches!(TxsError::from(err), TxsError::NotFound(_)),
+        "a coin without fixture is not on-chain"
+    );
+}
+
+#[tokio::test]
+async fn view() {
+    let client = connect(mock_node()).await;
+    let result = client
+        .view(
+            "0x1::coin::balance",
+            Some("0x1::aptos_coin::AptosCoin".to_string()),
+            Some(FunctionArgs::List("0x2".to_string())),
+            None,
+        )
+        .await
+        .unwrap();
+    // Decoded with the ABI of the module, the node returns the u64 as a string
+    assert_eq!(result, json!([1000]));
+
+    let err = client
+        .view("0x1::missing::balance", None, None, None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, TxsError::NotFound(_)), "{err:?}");
+
+    let node = mock_node().with_route("POST /view", 200, json!({ "not": "a list" }));
+    let err = connect(node)
+        .await
+        .view("0x1::coin::balance", None, None, None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, TxsError::Other(_)), "{err:?}");
+
+    // An abort of the view function is a failed request, not a rejected transaction
+    let node = mock_node().with_route(
+        "POST /view",
+        400,
+        error_body("Move abort in 0x1::coin: 0x60005", "vm_error"),
+    );
+    let err = connect(node)
+        .await
+        .view("0x1::coin::balance", None, None, None)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, TxsError::RestError { status: 400, .. }),
+        "{err:?}"
+    );
+}
+
+#[tokio::test]
+async fn fungible_asset_balance() {
+    let node = mock_node().with_route("POST /view", 200, json!(["2500"]));
+    let balance = fungible_asset::balance(&node, address("0x2"), address("0xa"), None)
+        .await
+        .unwrap();
+    assert_eq!(balance, 2_500);
+
+    let node = mock_node().with_route("POST /view", 200, json!([6]));
+    assert_eq!(
+        fungible_asset::decimals(&node, address("0xa"))
+            .await
+            .unwrap(),
+        6
+    );
+}
+
+#[tokio::test]
+async fn committed_transfer() {
+    let node = mock_node();
+    let result = transfer(&connect(node.clone()).await).await.unwrap();
+    assert_eq!((result.version, result.gas_used), (101, 6));
+    let submitted = node.submitted();
+    assert_eq!(submitted.len(), 1);
+    assert_eq!(
+        submitted[0].payload(),
+        &transfer_payload(address("0x3"), 10, None).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn aborted_transfer() {
+    let mut aborted = fixture_body(TRANSACTION);
+    aborted["success"] = json!(false);
+    aborted["vm_status"] =
+        json!("Move abort in 0x1::coin: EINSUFFICIENT_BALANCE(0x10006): Not enough coins");
+    let node = mock_node().with_route(TRANSACTION, 200, aborted);
+    let err = transfer(&connect(node).await).await.unwrap_err();
+    assert!(
+        matches!(err, TxsError::Aborted { ref location, code: 0x10006, .. } if location == "0x1::coin"),
+        "{err:?}"
+    );
+}
+
+#[tokio::test]
+async fn rejected_transfer() {
+    let node = mock_node().with_route(
+        "POST /transactions",
+        400,
+        error_body(
+            "Invalid transaction: Type: Validation Code: INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE",
+            "vm_error",
+        ),
+    );
+    let err = transfer(&connect(node).await).await.unwrap_err();
+    assert!(matches!(err, TxsError::Rejected(_)), "{err:?}");
+}
+
+#[tokio::test]
+async fn pending_transfer() {
+    let mut pending = fixture_body("POST /transactions");
+    pending["type"] = json!("pending_transaction");
+    let node = mock_node().with_route(TRANSACTION, 200, pending);
+    let err = transfer(&connect(node).await).await.unwrap_err();
+    assert!(matches!(err, TxsError::Timeout { .. }), "{err:?}");
+}